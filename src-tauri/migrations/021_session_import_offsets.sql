@@ -0,0 +1,16 @@
+-- Migration 021: Per-file byte offsets for incremental session import
+-- Large JSONL session files (Claude Code, Codex) only grow at the end. Tracking how
+-- far each file has been consumed lets re-imports parse just the appended lines.
+
+CREATE TABLE IF NOT EXISTS session_import_offsets (
+  repo_id INTEGER NOT NULL,
+  source_path TEXT NOT NULL,
+  session_id TEXT NOT NULL,
+  byte_offset INTEGER NOT NULL DEFAULT 0,
+  updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  PRIMARY KEY (repo_id, source_path),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_import_offsets_session
+  ON session_import_offsets(repo_id, session_id);
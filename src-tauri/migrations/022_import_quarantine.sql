@@ -0,0 +1,16 @@
+-- Migration 022: Quarantine for session files flagged with security warnings
+-- Manual imports refuse files whose parse raised Security-severity warnings. Flagged
+-- files are held here (with redacted excerpts) until the user confirms or discards them.
+
+CREATE TABLE IF NOT EXISTS import_quarantine (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  repo_id INTEGER NOT NULL,
+  file_path TEXT NOT NULL,
+  tool TEXT,
+  warnings TEXT NOT NULL,   -- JSON array of ParseWarning
+  excerpts TEXT NOT NULL,   -- JSON array of redacted flagged lines
+  file_sha256 TEXT,         -- content fingerprint at quarantine time
+  quarantined_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  UNIQUE (repo_id, file_path),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
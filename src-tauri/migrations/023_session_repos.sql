@@ -0,0 +1,21 @@
+-- Migration 023: Global session storage with per-repo membership
+-- sessions.id is derived from the tool's own session id, so a session that touched
+-- two repos has one id. The trace is stored once (owned by the repo that imported it
+-- first, sessions.repo_id); session_repos lists every repo the session belongs to and
+-- each repo keeps its own session_links row.
+
+CREATE TABLE IF NOT EXISTS session_repos (
+  session_id TEXT NOT NULL,
+  repo_id INTEGER NOT NULL,
+  source_path TEXT,
+  added_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  PRIMARY KEY (session_id, repo_id),
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE,
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_repos_repo ON session_repos(repo_id);
+
+-- Existing sessions belong to the repo that imported them.
+INSERT OR IGNORE INTO session_repos (session_id, repo_id, source_path, added_at)
+SELECT id, repo_id, source_path, imported_at FROM sessions;
@@ -0,0 +1,16 @@
+-- Migration 024: Journal of planned import batches
+-- Batch imports and backfills record their candidate files here before starting and
+-- delete each row once the file is handled. Rows left behind after a crash are the
+-- files still pending; they are resumed on the next startup.
+
+CREATE TABLE IF NOT EXISTS import_journal (
+  batch_id TEXT NOT NULL,
+  repo_id INTEGER NOT NULL,
+  kind TEXT NOT NULL CHECK(kind IN ('batch', 'backfill')),
+  file_path TEXT NOT NULL,
+  planned_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  PRIMARY KEY (batch_id, file_path),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_import_journal_repo ON import_journal(repo_id);
@@ -0,0 +1,13 @@
+-- Migration 025: Change-detection watermarks for Cursor's composer database
+-- Records the (updatedAt, rowid) of the last composer chat imported per repo, so
+-- the next import only reads chats created or edited since then.
+
+CREATE TABLE IF NOT EXISTS cursor_composer_watermarks (
+  repo_id INTEGER NOT NULL,
+  db_path TEXT NOT NULL,
+  updated_at_ms INTEGER NOT NULL DEFAULT 0,
+  last_rowid INTEGER NOT NULL DEFAULT 0,
+  checked_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  PRIMARY KEY (repo_id, db_path),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
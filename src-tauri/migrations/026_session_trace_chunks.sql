@@ -0,0 +1,11 @@
+-- Migration 026: Overflow storage for very large session traces
+-- sessions.raw_json keeps the first chunk of messages; the rest of a large trace is
+-- stored here in order, one JSON array of messages per row.
+
+CREATE TABLE IF NOT EXISTS session_trace_chunks (
+  session_id TEXT NOT NULL,
+  chunk_index INTEGER NOT NULL,
+  messages_json TEXT NOT NULL,
+  PRIMARY KEY (session_id, chunk_index),
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
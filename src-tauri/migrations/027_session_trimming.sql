@@ -0,0 +1,6 @@
+-- Migration 027: Record message trimming on imported sessions
+-- Sessions longer than the configured message limit are trimmed on import;
+-- trim_strategy is NULL for sessions stored in full.
+
+ALTER TABLE sessions ADD COLUMN trim_strategy TEXT;
+ALTER TABLE sessions ADD COLUMN trimmed_message_count INTEGER NOT NULL DEFAULT 0;
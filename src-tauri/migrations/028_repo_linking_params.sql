@@ -0,0 +1,12 @@
+-- Migration 028: Per-repo session linking weights and threshold
+-- Repos without a row use the defaults in linking.rs (0.6 / 0.4 / 0.7).
+
+CREATE TABLE IF NOT EXISTS repo_linking_params (
+    repo_id INTEGER PRIMARY KEY,
+    temporal_weight REAL NOT NULL,
+    file_overlap_weight REAL NOT NULL,
+    confidence_threshold REAL NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,29 @@
+-- Migration 029: Allow a session to link to several commits
+-- A long session often produces more than one commit. session_links was
+-- UNIQUE(repo_id, session_id); SQLite cannot drop a table constraint, so the
+-- table is rebuilt with UNIQUE(repo_id, session_id, commit_sha). Each row keeps
+-- its own confidence; the highest-confidence row is a session's primary link.
+
+CREATE TABLE session_links_multi (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  repo_id INTEGER NOT NULL,
+  session_id TEXT NOT NULL,
+  commit_sha TEXT NOT NULL,
+  confidence REAL NOT NULL,
+  auto_linked BOOLEAN NOT NULL DEFAULT 1,
+  created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  needs_review INTEGER NOT NULL DEFAULT 0,
+  UNIQUE(repo_id, session_id, commit_sha),
+  FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+INSERT INTO session_links_multi (id, repo_id, session_id, commit_sha, confidence, auto_linked, created_at, needs_review)
+SELECT id, repo_id, session_id, commit_sha, confidence, auto_linked, created_at, needs_review
+FROM session_links;
+
+DROP TABLE session_links;
+ALTER TABLE session_links_multi RENAME TO session_links;
+
+CREATE INDEX IF NOT EXISTS idx_session_links_repo_commit ON session_links(repo_id, commit_sha);
+CREATE INDEX IF NOT EXISTS idx_session_links_repo_id ON session_links(repo_id);
+CREATE INDEX IF NOT EXISTS idx_session_links_repo_session ON session_links(repo_id, session_id, confidence DESC);
@@ -0,0 +1,4 @@
+-- Migration 030: Weight for commit-message similarity in session linking
+-- Existing repos keep their current scores (weight 0 = signal off).
+
+ALTER TABLE repo_linking_params ADD COLUMN message_weight REAL NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Migration 031: Branch checked out while a session ran
+-- Recorded from tool metadata (Claude Code `gitBranch`, Codex `session_meta.git`)
+-- so linking can prefer commits on that branch.
+
+ALTER TABLE sessions ADD COLUMN git_branch TEXT;
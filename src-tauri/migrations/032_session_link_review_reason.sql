@@ -0,0 +1,5 @@
+-- Migration 032: Why a session link needs review
+-- 'close_match' (another commit scored about as high) or 'author_mismatch'
+-- (the commit was authored by someone other than the user). NULL otherwise.
+
+ALTER TABLE session_links ADD COLUMN review_reason TEXT;
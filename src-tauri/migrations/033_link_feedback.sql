@@ -0,0 +1,27 @@
+-- Migration 033: Learn linking params from user corrections
+-- Auto links keep their component scores so a later confirm (manual link to
+-- the same commit) or rejection (unlink) can be recorded as a labelled sample.
+-- repo_linking_params.learned_from counts the samples the stored params were
+-- fitted on; 0 means they were set by hand, which pauses learning.
+
+ALTER TABLE session_links ADD COLUMN temporal_score REAL;
+ALTER TABLE session_links ADD COLUMN file_score REAL;
+ALTER TABLE session_links ADD COLUMN message_score REAL;
+
+ALTER TABLE repo_linking_params ADD COLUMN learned_from INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS link_feedback (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_id INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    commit_sha TEXT NOT NULL,
+    temporal_score REAL NOT NULL,
+    file_score REAL NOT NULL,
+    message_score REAL NOT NULL DEFAULT 0,
+    accepted INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_link_feedback_repo ON link_feedback(repo_id);
@@ -0,0 +1,5 @@
+-- Migration 034: Fingerprints of lines written by a session's edit tool calls
+-- JSON array of SHA-256[:16] hashes (edit contents themselves are not stored).
+-- Compared with commit diffs when linking; NULL when no edits were recorded.
+
+ALTER TABLE sessions ADD COLUMN edit_fingerprints TEXT;
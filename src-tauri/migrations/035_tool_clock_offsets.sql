@@ -0,0 +1,13 @@
+-- Migration 035: Per-tool clock offsets for linking
+-- Some tools log local time without an offset, so their session times are off
+-- by the user's UTC offset. offset_min is added to a session's time before it
+-- is compared with commit times. 'manual' rows are set by the user and are
+-- never replaced by detection; 'detected' rows come from confirmed links.
+
+CREATE TABLE IF NOT EXISTS tool_clock_offsets (
+    tool TEXT PRIMARY KEY,
+    offset_min INTEGER NOT NULL,
+    source TEXT NOT NULL CHECK(source IN ('manual', 'detected')),
+    samples INTEGER NOT NULL DEFAULT 0,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+);
@@ -0,0 +1,11 @@
+-- Migration 036: Monorepo sub-path scopes for linking
+-- Each row is a repo-relative directory (e.g. packages/api). A session whose
+-- files all fall under configured scopes is only scored against commits that
+-- touch those scopes, so unrelated commits elsewhere in the repo are skipped.
+
+CREATE TABLE IF NOT EXISTS repo_link_scopes (
+    repo_id INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    PRIMARY KEY (repo_id, path),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,6 @@
+-- Migration 037: Keep the confidence of corrected links
+-- The calibration report buckets confirmed and rejected links by the
+-- confidence they were suggested with. Older rows have no confidence; the
+-- report recomputes it from their component scores with the current params.
+
+ALTER TABLE link_feedback ADD COLUMN confidence REAL;
@@ -0,0 +1,31 @@
+-- Migration 038: Link sessions to pull requests
+-- Pull requests are registered by the frontend (from the GitHub connector).
+-- A session belongs to a PR when it is linked to one of the PR's commits
+-- (head not reachable from base) or ran on the PR's head branch.
+
+CREATE TABLE IF NOT EXISTS pull_requests (
+    repo_id INTEGER NOT NULL,
+    number INTEGER NOT NULL,
+    title TEXT NOT NULL,
+    head_branch TEXT NOT NULL,
+    base_branch TEXT NOT NULL,
+    head_sha TEXT,
+    url TEXT,
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    PRIMARY KEY (repo_id, number),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS session_pr_links (
+    repo_id INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    pr_number INTEGER NOT NULL,
+    -- 'commit': linked to a PR commit; 'branch': ran on the head branch only
+    match_kind TEXT NOT NULL CHECK(match_kind IN ('commit', 'branch')),
+    commit_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    PRIMARY KEY (repo_id, session_id, pr_number),
+    FOREIGN KEY (repo_id, pr_number) REFERENCES pull_requests(repo_id, number) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_session_pr_links_pr ON session_pr_links(repo_id, pr_number);
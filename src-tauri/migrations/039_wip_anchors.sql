@@ -0,0 +1,25 @@
+-- Migration 039: Anchor sessions to uncommitted changes
+-- A session that ends before its work is committed has nothing to link to.
+-- It is anchored to a snapshot of the dirty working tree instead: the files
+-- it left changed and their content hashes. The post-commit hook upgrades the
+-- anchor to real session links as those files are committed.
+
+CREATE TABLE IF NOT EXISTS wip_anchors (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_id INTEGER NOT NULL,
+    session_id TEXT NOT NULL,
+    -- git blob hash of the snapshot manifest (sorted path/blob pairs)
+    snapshot_hash TEXT NOT NULL,
+    -- HEAD when the snapshot was taken
+    base_sha TEXT,
+    -- JSON array of {path, blob, committedSha}
+    files TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    -- set once every anchored file has been committed
+    resolved_at TEXT,
+    resolved_commit_sha TEXT,
+    UNIQUE(repo_id, session_id, snapshot_hash),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_wip_anchors_open ON wip_anchors(repo_id, resolved_at);
@@ -0,0 +1,17 @@
+-- Migration 040: Record commits rewritten by rebase/amend
+-- The post-rewrite hook reports old -> new commit pairs. Recording them lets
+-- line attributions (and session links) follow a commit to its rewritten
+-- copy even when conflict resolution changed its patch-id.
+
+CREATE TABLE IF NOT EXISTS commit_rewrites (
+    repo_id INTEGER NOT NULL,
+    old_sha TEXT NOT NULL,
+    new_sha TEXT NOT NULL,
+    -- git command that rewrote the commit ('rebase', 'amend')
+    command TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (repo_id, new_sha, old_sha),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_commit_rewrites_old ON commit_rewrites(repo_id, old_sha);
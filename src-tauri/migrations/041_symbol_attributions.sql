@@ -0,0 +1,31 @@
+-- Migration 041: Symbol-level attribution
+-- Attributed line ranges rolled up to the functions/classes that contain
+-- them, so a symbol can be reported as e.g. "80% AI-written".
+
+CREATE TABLE IF NOT EXISTS symbol_attributions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_id INTEGER NOT NULL,
+    commit_sha TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    -- Qualified name, e.g. 'Parser::parse' or 'Widget.render'
+    symbol_name TEXT NOT NULL,
+    -- Normalized kind: 'function', 'method', 'class', 'struct', 'enum', 'trait', 'impl', 'interface', 'module'
+    symbol_kind TEXT NOT NULL,
+    start_line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL,
+    total_lines INTEGER NOT NULL DEFAULT 0,
+    human_lines INTEGER NOT NULL DEFAULT 0,
+    ai_agent_lines INTEGER NOT NULL DEFAULT 0,
+    ai_assist_lines INTEGER NOT NULL DEFAULT 0,
+    collaborative_lines INTEGER NOT NULL DEFAULT 0,
+    ai_percentage REAL NOT NULL DEFAULT 0,
+    primary_tool TEXT,
+    computed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_symbol_attributions_file
+    ON symbol_attributions(repo_id, commit_sha, file_path);
+CREATE INDEX IF NOT EXISTS idx_symbol_attributions_name
+    ON symbol_attributions(repo_id, symbol_name);
@@ -0,0 +1,10 @@
+-- Migration 042: Attribution exclusion globs
+-- Files matching a repo's globs (lockfiles, snapshots, codegen output) get no
+-- line attributions and are left out of contribution stats.
+
+CREATE TABLE IF NOT EXISTS attribution_exclusions (
+    repo_id INTEGER NOT NULL,
+    pattern TEXT NOT NULL,
+    PRIMARY KEY (repo_id, pattern),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
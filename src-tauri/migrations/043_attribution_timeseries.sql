@@ -0,0 +1,38 @@
+-- Migration 043: Cached attribution time series
+-- Weekly/monthly rollups of commit_contribution_stats by the commits' author
+-- dates. Periods are recomputed only when stats computed after the stored
+-- watermark fall inside them.
+
+CREATE TABLE IF NOT EXISTS attribution_timeseries (
+    repo_id INTEGER NOT NULL,
+    -- 'week' (periods start on Monday) or 'month'
+    granularity TEXT NOT NULL CHECK(granularity IN ('week', 'month')),
+    period_start TEXT NOT NULL,
+    commits INTEGER NOT NULL DEFAULT 0,
+    total_lines INTEGER NOT NULL DEFAULT 0,
+    human_lines INTEGER NOT NULL DEFAULT 0,
+    ai_agent_lines INTEGER NOT NULL DEFAULT 0,
+    ai_assist_lines INTEGER NOT NULL DEFAULT 0,
+    collaborative_lines INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (repo_id, granularity, period_start),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS attribution_timeseries_tools (
+    repo_id INTEGER NOT NULL,
+    granularity TEXT NOT NULL,
+    period_start TEXT NOT NULL,
+    tool TEXT NOT NULL,
+    line_count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (repo_id, granularity, period_start, tool),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+-- Latest commit_contribution_stats.computed_at folded into each series
+CREATE TABLE IF NOT EXISTS attribution_timeseries_state (
+    repo_id INTEGER NOT NULL,
+    granularity TEXT NOT NULL,
+    watermark TEXT NOT NULL,
+    PRIMARY KEY (repo_id, granularity),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,17 @@
+-- Migration 044: Cached per-directory attribution rollups
+-- One row per commit range and directory depth. The fingerprint records the
+-- state of the repo's line attributions the rollup was computed from, so a
+-- stale rollup is recomputed on the next request.
+
+CREATE TABLE IF NOT EXISTS attribution_directory_rollups (
+    repo_id INTEGER NOT NULL,
+    -- '<from_sha>..<to_sha>' (from is empty for ranges starting at the root)
+    range_key TEXT NOT NULL,
+    depth INTEGER NOT NULL,
+    fingerprint TEXT NOT NULL,
+    -- Serialized DirectoryRollup
+    payload TEXT NOT NULL,
+    computed_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (repo_id, range_key, depth),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
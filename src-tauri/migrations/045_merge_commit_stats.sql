@@ -0,0 +1,7 @@
+-- Migration 045: Mark merge commits in contribution stats
+-- Merge commits are attributed only for lines changed against every parent
+-- (conflict resolutions and edits made while merging); everything else was
+-- attributed on the merged commits already.
+
+ALTER TABLE commit_contribution_stats
+    ADD COLUMN is_merge INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,27 @@
+-- Migration 046: AI tab-completion acceptances
+-- Accepted completions (Copilot/Cursor tab accepts) reported over OTLP or
+-- imported from editor logs. They are captured against the working tree and
+-- matched to committed lines when the commit is attributed, becoming
+-- `ai_tab` line attributions.
+
+CREATE TABLE IF NOT EXISTS completion_acceptances (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_id INTEGER NOT NULL,
+    file_path TEXT NOT NULL,
+    -- Working-tree position at acceptance (a hint; matching is by content)
+    start_line INTEGER,
+    end_line INTEGER,
+    accepted_text TEXT NOT NULL,
+    tool TEXT NOT NULL,
+    model TEXT,
+    -- UTC, 'YYYY-MM-DDTHH:MM:SSZ'
+    accepted_at TEXT NOT NULL,
+    -- Commit named by the event, or the commit the acceptance was matched to
+    commit_sha TEXT,
+    matched_at TEXT,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_completion_acceptances_pending
+    ON completion_acceptances(repo_id, file_path, matched_at);
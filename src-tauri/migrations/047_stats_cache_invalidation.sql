@@ -0,0 +1,59 @@
+-- Migration 047: Invalidate cached contribution stats automatically
+-- Stats are derived from a commit's line attributions (or, without them, its
+-- linked sessions). Whenever either changes, the commit's cached stats are
+-- dropped and recomputed on next read. Rebuilding session_links or
+-- line_attributions drops these triggers; recreate them afterwards.
+
+CREATE TRIGGER IF NOT EXISTS line_attributions_stats_ai
+AFTER INSERT ON line_attributions BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id = new.repo_id AND commit_sha = new.commit_sha;
+  DELETE FROM commit_tool_stats
+  WHERE repo_id = new.repo_id AND commit_sha = new.commit_sha;
+END;
+
+CREATE TRIGGER IF NOT EXISTS line_attributions_stats_ad
+AFTER DELETE ON line_attributions BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id = old.repo_id AND commit_sha = old.commit_sha;
+  DELETE FROM commit_tool_stats
+  WHERE repo_id = old.repo_id AND commit_sha = old.commit_sha;
+END;
+
+CREATE TRIGGER IF NOT EXISTS line_attributions_stats_au
+AFTER UPDATE ON line_attributions BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id)
+    AND commit_sha IN (old.commit_sha, new.commit_sha);
+  DELETE FROM commit_tool_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id)
+    AND commit_sha IN (old.commit_sha, new.commit_sha);
+END;
+
+CREATE TRIGGER IF NOT EXISTS session_links_stats_ai
+AFTER INSERT ON session_links BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id = new.repo_id AND commit_sha = new.commit_sha;
+  DELETE FROM commit_tool_stats
+  WHERE repo_id = new.repo_id AND commit_sha = new.commit_sha;
+END;
+
+CREATE TRIGGER IF NOT EXISTS session_links_stats_ad
+AFTER DELETE ON session_links BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id = old.repo_id AND commit_sha = old.commit_sha;
+  DELETE FROM commit_tool_stats
+  WHERE repo_id = old.repo_id AND commit_sha = old.commit_sha;
+END;
+
+-- Review flags and scores do not affect stats; only the link target and
+-- the ranking that picks the primary session do
+CREATE TRIGGER IF NOT EXISTS session_links_stats_au
+AFTER UPDATE OF session_id, commit_sha, confidence ON session_links BEGIN
+  DELETE FROM commit_contribution_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id)
+    AND commit_sha IN (old.commit_sha, new.commit_sha);
+  DELETE FROM commit_tool_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id)
+    AND commit_sha IN (old.commit_sha, new.commit_sha);
+END;
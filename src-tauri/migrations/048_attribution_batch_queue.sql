@@ -0,0 +1,12 @@
+-- Migration 048: Queue for background attribution batches
+-- `start_attribution_batch` records the commits it will attribute here and
+-- deletes each row once the commit's stats are cached. Rows left behind by a
+-- cancelled or interrupted batch are picked up when the batch is restarted.
+
+CREATE TABLE IF NOT EXISTS attribution_batch_queue (
+    repo_id INTEGER NOT NULL,
+    commit_sha TEXT NOT NULL,
+    queued_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    PRIMARY KEY (repo_id, commit_sha),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Migration 049: Where each line attribution came from, and how sure it is
+-- source: 'note' (imported from an attribution note), 'tool_diff' (measured
+-- against a session's recorded edits), 'completion' (matched tab-completion
+-- acceptance), or 'heuristic' (inferred from session/file overlap).
+-- confidence: 0.0-1.0; ranges below 0.7 are shown as uncertain.
+
+ALTER TABLE line_attributions ADD COLUMN source TEXT NOT NULL DEFAULT 'heuristic';
+ALTER TABLE line_attributions ADD COLUMN confidence REAL NOT NULL DEFAULT 0.5;
+
+-- Existing ai_tab rows were all written from matched completions
+UPDATE line_attributions
+SET source = 'completion', confidence = 0.85
+WHERE author_type = 'ai_tab';
@@ -0,0 +1,11 @@
+-- Migration 050: Per-repo attribution policy rules
+-- `check_attribution_policy` (and `narrative-cli policy check`) evaluates a
+-- repo's rules against a commit range so pre-push hooks and CI can gate on
+-- AI attribution. `rules` holds the serialized `PolicyRule` list.
+
+CREATE TABLE IF NOT EXISTS attribution_policies (
+    repo_id INTEGER PRIMARY KEY,
+    rules TEXT NOT NULL DEFAULT '[]',
+    updated_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
@@ -0,0 +1,11 @@
+-- Migration 051: Per-repo language overrides for attribution breakdowns
+-- `pattern` is a lowercase file extension without the dot (`mdx`) or a
+-- file name (`justfile`); it maps to the language the file is counted under.
+
+CREATE TABLE IF NOT EXISTS attribution_language_overrides (
+    repo_id INTEGER NOT NULL,
+    pattern TEXT NOT NULL,
+    language TEXT NOT NULL,
+    PRIMARY KEY (repo_id, pattern),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
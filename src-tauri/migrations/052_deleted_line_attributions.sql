@@ -0,0 +1,88 @@
+-- Migration 052: Attribution of deleted lines
+-- Contribution stats count only the lines a commit adds. These tables record
+-- the lines it removes: who wrote them (blamed to the commit that last
+-- changed them, with that commit's line attribution) and which session, if
+-- any, removed them. Line numbers refer to the first parent's file.
+
+CREATE TABLE IF NOT EXISTS deleted_line_attributions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_id INTEGER NOT NULL,
+    commit_sha TEXT NOT NULL,
+    file_path TEXT NOT NULL,
+    start_line INTEGER NOT NULL,
+    end_line INTEGER NOT NULL,
+    -- Commit that wrote the removed lines
+    origin_commit_sha TEXT NOT NULL,
+    author_type TEXT NOT NULL CHECK(author_type IN ('human', 'ai_agent', 'ai_tab', 'mixed')),
+    session_id TEXT,
+    tool TEXT,
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_deleted_line_attributions_commit
+    ON deleted_line_attributions(repo_id, commit_sha);
+CREATE INDEX IF NOT EXISTS idx_deleted_line_attributions_origin
+    ON deleted_line_attributions(repo_id, origin_commit_sha);
+
+-- One row per commit once its deletions are computed (zeros included)
+CREATE TABLE IF NOT EXISTS commit_deletion_stats (
+    repo_id INTEGER NOT NULL,
+    commit_sha TEXT NOT NULL,
+    deleted_lines INTEGER NOT NULL DEFAULT 0,
+    deleted_ai_lines INTEGER NOT NULL DEFAULT 0,
+    deleted_human_lines INTEGER NOT NULL DEFAULT 0,
+    -- Highest-confidence session linked to the removing commit
+    removed_by_session_id TEXT,
+    computed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    PRIMARY KEY (repo_id, commit_sha),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+-- Recompute when the removed lines' origin attributions change
+CREATE TRIGGER IF NOT EXISTS line_attributions_deletions_ai
+AFTER INSERT ON line_attributions BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id = new.repo_id AND commit_sha IN (
+    SELECT commit_sha FROM deleted_line_attributions
+    WHERE repo_id = new.repo_id AND origin_commit_sha = new.commit_sha
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS line_attributions_deletions_ad
+AFTER DELETE ON line_attributions BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id = old.repo_id AND commit_sha IN (
+    SELECT commit_sha FROM deleted_line_attributions
+    WHERE repo_id = old.repo_id AND origin_commit_sha = old.commit_sha
+  );
+END;
+
+CREATE TRIGGER IF NOT EXISTS line_attributions_deletions_au
+AFTER UPDATE ON line_attributions BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id) AND commit_sha IN (
+    SELECT commit_sha FROM deleted_line_attributions
+    WHERE repo_id IN (old.repo_id, new.repo_id)
+      AND origin_commit_sha IN (old.commit_sha, new.commit_sha)
+  );
+END;
+
+-- ...and when the removing commit's session links change
+CREATE TRIGGER IF NOT EXISTS session_links_deletions_ai
+AFTER INSERT ON session_links BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id = new.repo_id AND commit_sha = new.commit_sha;
+END;
+
+CREATE TRIGGER IF NOT EXISTS session_links_deletions_ad
+AFTER DELETE ON session_links BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id = old.repo_id AND commit_sha = old.commit_sha;
+END;
+
+CREATE TRIGGER IF NOT EXISTS session_links_deletions_au
+AFTER UPDATE OF session_id, commit_sha, confidence ON session_links BEGIN
+  DELETE FROM commit_deletion_stats
+  WHERE repo_id IN (old.repo_id, new.repo_id)
+    AND commit_sha IN (old.commit_sha, new.commit_sha);
+END;
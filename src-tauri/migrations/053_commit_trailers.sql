@@ -0,0 +1,5 @@
+-- Migration 053: Opt-in commit message trailers
+-- When enabled, the prepare-commit-msg hook appends `Narrative-Session` and
+-- `Assisted-by` trailers for the sessions anchored to staged changes.
+
+ALTER TABLE attribution_prefs ADD COLUMN commit_trailers INTEGER NOT NULL DEFAULT 0;
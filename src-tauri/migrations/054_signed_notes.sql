@@ -0,0 +1,12 @@
+-- Migration 054: Signed Narrative notes
+-- Notes can carry a GPG or SSH signature (like signed tags). `sign_notes`
+-- signs exported notes with the repo's git signing key;
+-- `require_signed_notes` skips importing notes whose signature is missing or
+-- not from a trusted key. Imports record what they found.
+
+ALTER TABLE attribution_prefs ADD COLUMN sign_notes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attribution_prefs ADD COLUMN require_signed_notes INTEGER NOT NULL DEFAULT 0;
+
+-- 'unsigned', 'valid', 'invalid' or 'unverified'
+ALTER TABLE story_anchor_note_meta ADD COLUMN signature_status TEXT;
+ALTER TABLE story_anchor_note_meta ADD COLUMN signer TEXT;
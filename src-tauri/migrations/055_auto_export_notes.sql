@@ -0,0 +1,5 @@
+-- Migration 055: Export sessions notes when auto-import links a session
+-- With `auto_export_notes` on, a session link created or updated by
+-- auto-import exports the linked commits' sessions notes in the background.
+
+ALTER TABLE attribution_prefs ADD COLUMN auto_export_notes INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,21 @@
+-- Migration 056: Cached Story Anchor note presence
+-- Which Narrative notes a commit has, as of a given set of notes ref tips.
+-- Rows for older tips are dropped when a new tip is cached.
+
+CREATE TABLE IF NOT EXISTS story_anchor_status_cache (
+    repo_id INTEGER NOT NULL,
+    commit_sha TEXT NOT NULL,
+    -- Tips of the attribution/sessions/lineage notes refs
+    notes_tip TEXT NOT NULL,
+    -- Ref holding the commit's attribution note (NULL: none)
+    attribution_ref TEXT,
+    has_sessions_note INTEGER NOT NULL DEFAULT 0 CHECK(has_sessions_note IN (0, 1)),
+    has_lineage_note INTEGER NOT NULL DEFAULT 0 CHECK(has_lineage_note IN (0, 1)),
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (repo_id, commit_sha, notes_tip),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_story_anchor_status_cache_tip
+  ON story_anchor_status_cache(repo_id, notes_tip);
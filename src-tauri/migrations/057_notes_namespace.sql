@@ -0,0 +1,7 @@
+-- Migration 057: Per-repo notes ref namespace
+-- NULL keeps the default `refs/notes/narrative`; otherwise the namespace the
+-- repo's sessions/attribution/lineage notes refs live under, e.g.
+-- `refs/notes/acme/narrative`. Changed through `set_notes_namespace`, which
+-- moves existing notes to the new refs.
+
+ALTER TABLE attribution_prefs ADD COLUMN notes_namespace TEXT;
@@ -0,0 +1,25 @@
+-- Migration 058: Release anchors
+-- Sessions and attribution aggregated over a release (a tag and the commits
+-- since the previous tag), one row per tag. The full summary is kept as JSON;
+-- the columns cover listing and sorting.
+
+CREATE TABLE IF NOT EXISTS release_anchors (
+    repo_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    -- Commit the tag points at
+    commit_sha TEXT NOT NULL,
+    -- Exclusive start of the range (NULL: from the root commit)
+    from_ref TEXT,
+    commit_count INTEGER NOT NULL DEFAULT 0,
+    session_count INTEGER NOT NULL DEFAULT 0,
+    total_lines INTEGER NOT NULL DEFAULT 0,
+    ai_percentage REAL NOT NULL DEFAULT 0,
+    -- Ref and hash of the release note (NULL: no note written)
+    note_ref TEXT,
+    note_hash TEXT,
+    summary_json TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (repo_id, tag),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
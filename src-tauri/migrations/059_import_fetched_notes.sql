@@ -0,0 +1,17 @@
+-- Migration 059: Import notes that arrive with a fetch
+-- `notes_import_tips` records each notes ref's tip as of its last import, so
+-- only notes added or changed since then are imported. With
+-- `import_notes_after_fetch` on, a notes fetch or a `git pull` (post-merge
+-- hook) imports them automatically.
+
+ALTER TABLE attribution_prefs ADD COLUMN import_notes_after_fetch INTEGER NOT NULL DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS notes_import_tips (
+    repo_id INTEGER NOT NULL,
+    note_ref TEXT NOT NULL,
+    tip TEXT NOT NULL,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY (repo_id, note_ref),
+    FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
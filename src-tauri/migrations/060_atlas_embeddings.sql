@@ -0,0 +1,19 @@
+-- Migration 060: Atlas chunk embeddings (optional semantic search)
+-- One vector per chunk per embedding model, keyed by the content-addressed
+-- chunk_uid so re-projecting a session keeps the vectors of unchanged chunks.
+-- `vector` holds `dims` little-endian f32 values, L2-normalized.
+
+CREATE TABLE IF NOT EXISTS atlas_chunk_embeddings (
+  chunk_uid TEXT NOT NULL,
+  repo_id INTEGER NOT NULL,
+  model TEXT NOT NULL,
+  dims INTEGER NOT NULL,
+  vector BLOB NOT NULL,
+  created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+  PRIMARY KEY (chunk_uid, model),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_atlas_chunk_embeddings_repo_model
+  ON atlas_chunk_embeddings(repo_id, model);
@@ -0,0 +1,20 @@
+-- Migration 061: Atlas background indexing queue
+-- Sessions waiting to be projected into atlas_chunks. A failed projection
+-- stays queued with its error and is retried once `next_attempt_at` passes,
+-- backing off exponentially with `attempts`.
+
+CREATE TABLE IF NOT EXISTS atlas_index_queue (
+  repo_id INTEGER NOT NULL,
+  session_id TEXT NOT NULL,
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  last_error TEXT,
+  enqueued_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+
+  PRIMARY KEY (repo_id, session_id),
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE,
+  FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_atlas_index_queue_due
+  ON atlas_index_queue(next_attempt_at);
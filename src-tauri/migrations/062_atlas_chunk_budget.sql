@@ -0,0 +1,5 @@
+-- Migration 062: Optional per-repo Atlas chunk budget
+-- When set, `atlas_compact` evicts the oldest chunks (by session import time)
+-- until the repo holds at most this many.
+
+ALTER TABLE atlas_index_state ADD COLUMN chunk_budget INTEGER;
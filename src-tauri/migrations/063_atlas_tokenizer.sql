@@ -0,0 +1,15 @@
+-- Migration 063: Atlas FTS tokenizer settings
+-- Single row describing how atlas_chunks_fts was created. Changing it
+-- recreates the FTS table with the new `tokenize` spec and rebuilds it from
+-- atlas_chunks.
+
+CREATE TABLE IF NOT EXISTS atlas_fts_settings (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  tokenizer TEXT NOT NULL DEFAULT 'unicode61',
+  porter INTEGER NOT NULL DEFAULT 0,
+  remove_diacritics INTEGER NOT NULL DEFAULT 1,
+  token_chars TEXT NOT NULL DEFAULT '',
+  updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT OR IGNORE INTO atlas_fts_settings (id) VALUES (1);
@@ -0,0 +1,12 @@
+-- Migration 064: Per-repo Atlas chunking settings
+-- Repos without a row use the defaults (4000-char chunks, no overlap, every
+-- role). Changing a repo's row re-derives its chunks.
+
+CREATE TABLE IF NOT EXISTS atlas_chunking_settings (
+  repo_id INTEGER PRIMARY KEY,
+  max_chars INTEGER NOT NULL,
+  overlap_chars INTEGER NOT NULL DEFAULT 0,
+  skip_roles TEXT NOT NULL DEFAULT '',
+  updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  FOREIGN KEY (repo_id) REFERENCES repos(id) ON DELETE CASCADE
+);
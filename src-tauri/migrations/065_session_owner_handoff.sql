@@ -0,0 +1,20 @@
+-- Migration 065: Keep shared sessions when their owning repo is deleted
+-- Deleting a repo cascades to the sessions it owns (sessions.repo_id). A session
+-- that other repos share through session_repos is handed to the member that
+-- added it first instead, so only sessions with no other member are deleted.
+
+CREATE TRIGGER IF NOT EXISTS repos_session_owner_handoff
+BEFORE DELETE ON repos BEGIN
+  UPDATE sessions
+  SET repo_id = (
+    SELECT sr.repo_id FROM session_repos sr
+    WHERE sr.session_id = sessions.id AND sr.repo_id != old.id
+    ORDER BY sr.added_at, sr.repo_id
+    LIMIT 1
+  )
+  WHERE repo_id = old.id
+    AND EXISTS (
+      SELECT 1 FROM session_repos sr
+      WHERE sr.session_id = sessions.id AND sr.repo_id != old.id
+    );
+END;
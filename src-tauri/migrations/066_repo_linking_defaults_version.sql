@@ -0,0 +1,11 @@
+-- Migration 066: Versioned session linking defaults per repo
+-- Repos without a repo_linking_params row are scored with the defaults of their
+-- linking_defaults_version (see LinkingParams::for_defaults_version):
+--   1: 0.6 temporal / 0.4 file overlap / 0.0 message, threshold 0.7
+--   2: 0.6 temporal / 0.25 file overlap / 0.15 message, threshold 0.7
+-- Repos added before this migration stay on version 1 so their links keep the
+-- scores they had; repos added from now on start at version 2.
+
+ALTER TABLE repos ADD COLUMN linking_defaults_version INTEGER NOT NULL DEFAULT 2;
+
+UPDATE repos SET linking_defaults_version = 1;
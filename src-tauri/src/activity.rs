@@ -3,10 +3,14 @@
 //! Exposes minimal, UI-friendly aggregates so the frontend can stay simple.
 
 use crate::DbState;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use sqlx::Row;
 use tauri::State;
 
+/// Default and maximum page size for `query_import_history`
+const HISTORY_PAGE_SIZE: i64 = 50;
+const HISTORY_MAX_PAGE_SIZE: i64 = 200;
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ActivityEvent {
@@ -43,6 +47,8 @@ fn tool_label(tool: &str) -> String {
         "cursor" => "Cursor".to_string(),
         "codex" => "Codex".to_string(),
         "codex_otlp" => "Codex".to_string(),
+        "opencode" => "opencode".to_string(),
+        "chatgpt" => "ChatGPT".to_string(),
         other => {
             let mut c = other.replace(['_', '-'], " ");
             if let Some(r) = c.get_mut(0..1) {
@@ -194,30 +200,38 @@ pub async fn get_ingest_activity(
 
         if action == "auto_import" {
             if let Some(sid) = session_id.as_deref() {
-                if let Ok(link_row) = sqlx::query(
+                let links = sqlx::query(
                     r#"
                     SELECT commit_sha, confidence, needs_review
                     FROM session_links
                     WHERE repo_id = ? AND session_id = ?
-                    LIMIT 1
+                    ORDER BY confidence DESC, id
                     "#,
                 )
                 .bind(repo_id)
                 .bind(sid)
-                .fetch_one(&*db.0)
+                .fetch_all(&*db.0)
                 .await
-                {
+                .unwrap_or_default();
+
+                // The highest-confidence link is the session's primary link
+                if let Some(link_row) = links.first() {
                     let commit_sha: String = link_row.get("commit_sha");
                     let confidence: f64 = link_row.get("confidence");
                     let nr: i64 = link_row.try_get("needs_review").unwrap_or(0);
                     needs_review = Some(nr != 0);
-                    commit_shas = Some(vec![commit_sha.clone()]);
+                    commit_shas = Some(links.iter().map(|l| l.get("commit_sha")).collect());
 
                     if status == "imported" {
+                        let more = match links.len() - 1 {
+                            0 => String::new(),
+                            n => format!(" +{n} more"),
+                        };
                         message = format!(
-                            "Imported {} session → linked to {} ({}){}",
+                            "Imported {} session → linked to {}{} ({}){}",
                             tool_label(&source_tool),
                             short_sha(&commit_sha),
+                            more,
                             confidence_label(confidence),
                             if nr != 0 { " · Needs review" } else { "" }
                         );
@@ -313,6 +327,189 @@ pub async fn get_ingest_activity(
     Ok(out)
 }
 
+/// Filters for the import history screen; all are optional and combined with AND.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHistoryFilter {
+    /// `imported` | `partial` | `skipped` | `failed`
+    pub status: Option<String>,
+    pub tool: Option<String>,
+    /// Inclusive lower bound (ISO-8601)
+    pub from_iso: Option<String>,
+    /// Exclusive upper bound (ISO-8601)
+    pub to_iso: Option<String>,
+    /// Substring of the source file path
+    pub path_contains: Option<String>,
+}
+
+/// One row from either `ingest_audit_log` (auto-ingest) or `session_import_log`
+/// (manual imports).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHistoryEntry {
+    /// `audit` or `import`, the log the row came from
+    pub log: String,
+    pub id: i64,
+    pub created_at_iso: String,
+    pub tool: Option<String>,
+    pub path: Option<String>,
+    pub session_id: Option<String>,
+    pub action: String,
+    /// Normalized across both logs (`success` is reported as `imported`)
+    pub status: String,
+    pub redaction_count: Option<i64>,
+    pub warnings: Vec<String>,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHistoryPage {
+    pub entries: Vec<ImportHistoryEntry>,
+    /// Pass back as `cursor` to fetch the next (older) page
+    pub next_cursor: Option<String>,
+}
+
+/// Keyset position of the last row on a page: `<datetime>|<log>|<id>`
+fn encode_history_cursor(entry: &ImportHistoryEntry) -> String {
+    format!("{}|{}|{}", entry.created_at_iso, entry.log, entry.id)
+}
+
+fn decode_history_cursor(cursor: &str) -> Result<(String, String, i64), String> {
+    let mut parts = cursor.rsplitn(3, '|');
+    let id = parts.next().and_then(|id| id.parse::<i64>().ok());
+    let log = parts.next();
+    let at = parts.next();
+    match (at, log, id) {
+        (Some(at), Some(log), Some(id)) => Ok((at.to_string(), log.to_string(), id)),
+        _ => Err(format!("Invalid import history cursor: {cursor}")),
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Import history across both import logs, newest first, with cursor pagination.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn query_import_history(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    filter: Option<ImportHistoryFilter>,
+    cursor: Option<String>,
+    limit: Option<i64>,
+) -> Result<ImportHistoryPage, String> {
+    query_import_history_inner(
+        &db.0,
+        repo_id,
+        &filter.unwrap_or_default(),
+        cursor.as_deref(),
+        limit,
+    )
+    .await
+}
+
+async fn query_import_history_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    filter: &ImportHistoryFilter,
+    cursor: Option<&str>,
+    limit: Option<i64>,
+) -> Result<ImportHistoryPage, String> {
+    let limit = limit
+        .unwrap_or(HISTORY_PAGE_SIZE)
+        .clamp(1, HISTORY_MAX_PAGE_SIZE);
+    let cursor = cursor.map(decode_history_cursor).transpose()?;
+    let (cursor_at, cursor_log, cursor_id) = match cursor {
+        Some((at, log, id)) => (Some(at), Some(log), Some(id)),
+        None => (None, None, None),
+    };
+
+    // Both logs store CURRENT_TIMESTAMP-style text; datetime() normalizes them
+    // (and ISO-8601 filter bounds) so they sort and compare together.
+    let rows = sqlx::query(
+        r#"
+        WITH history AS (
+          SELECT 'audit' AS log, a.id, a.source_tool AS tool, a.source_path AS path,
+                 a.session_id, a.action, a.status, a.redaction_count,
+                 NULL AS warnings, a.error_message, datetime(a.created_at) AS at
+          FROM ingest_audit_log a
+          WHERE a.repo_id = ?1
+          UNION ALL
+          SELECT 'import' AS log, l.id, s.tool, l.file_path AS path,
+                 l.session_id, 'import' AS action,
+                 CASE l.status WHEN 'success' THEN 'imported' ELSE l.status END AS status,
+                 s.redaction_count, l.warnings, l.error_message, datetime(l.imported_at) AS at
+          FROM session_import_log l
+          LEFT JOIN sessions s ON s.id = l.session_id
+          WHERE l.repo_id = ?1
+        )
+        SELECT log, id, tool, path, session_id, action, status, redaction_count,
+               warnings, error_message, at
+        FROM history
+        WHERE (?2 IS NULL OR status = ?2)
+          AND (?3 IS NULL OR tool = ?3)
+          AND (?4 IS NULL OR at >= datetime(?4))
+          AND (?5 IS NULL OR at < datetime(?5))
+          AND (?6 IS NULL OR path LIKE '%' || ?6 || '%' ESCAPE '\')
+          AND (?7 IS NULL OR (at, log, id) < (?7, ?8, ?9))
+        ORDER BY at DESC, log DESC, id DESC
+        LIMIT ?10
+        "#,
+    )
+    .bind(repo_id)
+    .bind(filter.status.as_deref())
+    .bind(filter.tool.as_deref())
+    .bind(filter.from_iso.as_deref())
+    .bind(filter.to_iso.as_deref())
+    .bind(filter.path_contains.as_deref().map(escape_like))
+    .bind(cursor_at)
+    .bind(cursor_log)
+    .bind(cursor_id)
+    // One extra row tells us whether another page exists.
+    .bind(limit + 1)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut entries: Vec<ImportHistoryEntry> = rows
+        .into_iter()
+        .map(|row| {
+            let warnings: Option<String> = row.try_get("warnings").ok().flatten();
+            ImportHistoryEntry {
+                log: row.get("log"),
+                id: row.get("id"),
+                created_at_iso: row.try_get("at").unwrap_or_default(),
+                tool: row.try_get("tool").ok(),
+                path: row.try_get("path").ok(),
+                session_id: row.try_get("session_id").ok(),
+                action: row.get("action"),
+                status: row.get("status"),
+                redaction_count: row.try_get("redaction_count").ok(),
+                warnings: warnings
+                    .and_then(|raw| serde_json::from_str(&raw).ok())
+                    .unwrap_or_default(),
+                error_message: row.try_get("error_message").ok(),
+            }
+        })
+        .collect();
+
+    let next_cursor = if entries.len() as i64 > limit {
+        entries.truncate(limit as usize);
+        entries.last().map(encode_history_cursor)
+    } else {
+        None
+    };
+
+    Ok(ImportHistoryPage {
+        entries,
+        next_cursor,
+    })
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn get_commit_capture_bundle(
     db: State<'_, DbState>,
@@ -420,3 +617,75 @@ pub async fn get_commit_capture_bundle(
         tools_used_top: tools_used.into_iter().take(5).collect(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_history_merges_logs_and_paginates() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            sqlx::query(
+                r#"
+                INSERT INTO ingest_audit_log (repo_id, source_tool, source_path, action, status, created_at)
+                VALUES
+                  (1, 'claude_code', '/home/a/.claude/one.jsonl', 'auto_import', 'imported', '2026-01-01 10:00:00'),
+                  (1, 'codex', '/home/a/.codex/two.jsonl', 'auto_import', 'failed', '2026-01-02 10:00:00'),
+                  (1, 'claude_code', '/home/a/.claude/three.jsonl', 'auto_import', 'skipped', '2026-01-03 10:00:00')
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .expect("audit rows");
+            sqlx::query(
+                r#"
+                INSERT INTO session_import_log (repo_id, file_path, status, warnings, imported_at)
+                VALUES (1, '/home/a/.claude/four.jsonl', 'success', '["Unknown entry type"]', '2026-01-04 10:00:00')
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .expect("import row");
+
+            let filter = ImportHistoryFilter::default();
+            let first = query_import_history_inner(&pool, 1, &filter, None, Some(3))
+                .await
+                .expect("first page");
+            assert_eq!(first.entries.len(), 3);
+            assert_eq!(first.entries[0].log, "import");
+            assert_eq!(first.entries[0].status, "imported");
+            assert_eq!(first.entries[0].warnings, vec!["Unknown entry type".to_string()]);
+
+            let cursor = first.next_cursor.expect("more pages");
+            let second = query_import_history_inner(&pool, 1, &filter, Some(&cursor), Some(3))
+                .await
+                .expect("second page");
+            assert_eq!(second.entries.len(), 1);
+            assert_eq!(second.entries[0].path.as_deref(), Some("/home/a/.claude/one.jsonl"));
+            assert!(second.next_cursor.is_none());
+
+            let claude_imports = ImportHistoryFilter {
+                status: Some("imported".to_string()),
+                path_contains: Some(".claude".to_string()),
+                from_iso: Some("2026-01-02T00:00:00Z".to_string()),
+                ..ImportHistoryFilter::default()
+            };
+            let filtered = query_import_history_inner(&pool, 1, &claude_imports, None, None)
+                .await
+                .expect("filtered");
+            assert_eq!(filtered.entries.len(), 1);
+            assert_eq!(filtered.entries[0].path.as_deref(), Some("/home/a/.claude/four.jsonl"));
+        });
+    }
+}
@@ -84,9 +84,14 @@ async fn agent_list_sessions_internal(
           sl.auto_linked AS auto_linked
         FROM sessions s
         LEFT JOIN session_links sl
-          ON sl.repo_id = s.repo_id
-         AND sl.session_id = s.id
-        WHERE s.repo_id = $1
+          ON sl.id = (
+            SELECT x.id FROM session_links x
+            WHERE x.repo_id = $1 AND x.session_id = s.id
+            ORDER BY x.confidence DESC, x.id
+            LIMIT 1
+          )
+        WHERE (s.repo_id = $1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = $1))
           AND ($2 IS NULL OR s.tool = $2)
         ORDER BY s.imported_at DESC
         LIMIT $3
@@ -143,9 +148,14 @@ async fn agent_get_session_internal(
           sl.auto_linked AS auto_linked
         FROM sessions s
         LEFT JOIN session_links sl
-          ON sl.repo_id = s.repo_id
-         AND sl.session_id = s.id
-        WHERE s.repo_id = $1
+          ON sl.id = (
+            SELECT x.id FROM session_links x
+            WHERE x.repo_id = $1 AND x.session_id = s.id
+            ORDER BY x.confidence DESC, x.id
+            LIMIT 1
+          )
+        WHERE (s.repo_id = $1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = $1))
           AND s.id = $2
         LIMIT 1
         "#,
@@ -195,13 +205,21 @@ async fn agent_link_session_to_commit_internal(
 
     validate_commit_sha(commit_sha)?;
 
-    let session_exists: bool =
-        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM sessions WHERE repo_id = $1 AND id = $2)")
-            .bind(repo_id)
-            .bind(session_id)
-            .fetch_one(db)
-            .await
-            .map_err(|e| format!("Database error validating session: {e}"))?;
+    let session_exists: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS(
+          SELECT 1 FROM sessions s
+          WHERE s.id = $2
+            AND (s.repo_id = $1
+              OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = $1))
+        )
+        "#,
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| format!("Database error validating session: {e}"))?;
 
     if !session_exists {
         return Err(format!(
@@ -225,9 +243,8 @@ async fn agent_link_session_to_commit_internal(
         r#"
         INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked, needs_review)
         VALUES ($1, $2, $3, $4, 0, 0)
-        ON CONFLICT(repo_id, session_id)
+        ON CONFLICT(repo_id, session_id, commit_sha)
         DO UPDATE SET
-          commit_sha = excluded.commit_sha,
           confidence = excluded.confidence,
           auto_linked = 0,
           needs_review = 0
@@ -347,6 +364,20 @@ mod tests {
         .await
         .expect("sessions table");
 
+        pool.execute(
+            r#"
+            CREATE TABLE session_repos (
+                session_id TEXT NOT NULL,
+                repo_id INTEGER NOT NULL,
+                source_path TEXT,
+                added_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+                PRIMARY KEY (session_id, repo_id)
+            );
+            "#,
+        )
+        .await
+        .expect("session_repos table");
+
         pool.execute(
             r#"
             CREATE TABLE commits (
@@ -370,7 +401,7 @@ mod tests {
                 auto_linked BOOLEAN NOT NULL DEFAULT 1,
                 needs_review BOOLEAN NOT NULL DEFAULT 0,
                 created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
-                UNIQUE(repo_id, session_id)
+                UNIQUE(repo_id, session_id, commit_sha)
             );
             "#,
         )
@@ -0,0 +1,182 @@
+//! Per-repo Atlas chunking settings
+//!
+//! Sessions are split into chunks of at most `max_chars`. With an overlap,
+//! each chunk repeats the trailing messages of the previous one (up to
+//! `overlap_chars`), so a match spanning a chunk boundary still lands in one
+//! chunk. `skip_roles` leaves messages of those roles (e.g. `thinking`) out of
+//! the index. Chunks record the derived version of the settings they were
+//! built with; changing the settings re-derives the repo's chunks.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::chunking::CHUNK_TEXT_MAX_CHARS;
+use super::types::ATLAS_DERIVED_VERSION;
+
+/// Smallest `max_chars` accepted
+pub const CHUNK_MIN_CHARS: usize = 500;
+
+/// Roles a chunk can contain, as they appear in `role_mask`
+pub const CHUNK_ROLES: [&str; 5] = ["user", "assistant", "thinking", "plan", "tool_call"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasChunkingOptions {
+    /// Longest chunk, and longest message within one (`CHUNK_MIN_CHARS` up to
+    /// `CHUNK_TEXT_MAX_CHARS`)
+    pub max_chars: usize,
+    /// Trailing text repeated at the start of the next chunk, in whole
+    /// messages; less than half of `max_chars`
+    pub overlap_chars: usize,
+    /// Roles left out of the index
+    pub skip_roles: Vec<String>,
+}
+
+impl Default for AtlasChunkingOptions {
+    fn default() -> Self {
+        Self {
+            max_chars: CHUNK_TEXT_MAX_CHARS,
+            overlap_chars: 0,
+            skip_roles: Vec::new(),
+        }
+    }
+}
+
+impl AtlasChunkingOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(CHUNK_MIN_CHARS..=CHUNK_TEXT_MAX_CHARS).contains(&self.max_chars) {
+            return Err(format!(
+                "maxChars must be between {CHUNK_MIN_CHARS} and {CHUNK_TEXT_MAX_CHARS}"
+            ));
+        }
+        if self.overlap_chars * 2 >= self.max_chars {
+            return Err("overlapChars must be less than half of maxChars".to_string());
+        }
+        if let Some(role) = self
+            .skip_roles
+            .iter()
+            .find(|role| !CHUNK_ROLES.contains(&role.as_str()))
+        {
+            return Err(format!(
+                "Unknown role {role:?}; expected one of {}",
+                CHUNK_ROLES.join(", ")
+            ));
+        }
+        if CHUNK_ROLES
+            .iter()
+            .all(|role| self.skip_roles.iter().any(|skip| skip == role))
+        {
+            return Err("skipRoles can't include every role".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn skips(&self, role: &str) -> bool {
+        self.skip_roles.iter().any(|skip| skip == role)
+    }
+
+    /// `ATLAS_DERIVED_VERSION`, qualified by any non-default settings
+    pub fn derived_version(&self) -> String {
+        let mut version = ATLAS_DERIVED_VERSION.to_string();
+        if self.max_chars != CHUNK_TEXT_MAX_CHARS {
+            version.push_str(&format!("+max{}", self.max_chars));
+        }
+        if self.overlap_chars > 0 {
+            version.push_str(&format!("+overlap{}", self.overlap_chars));
+        }
+        if !self.skip_roles.is_empty() {
+            version.push_str(&format!("+skip:{}", self.skip_roles.join(".")));
+        }
+        version
+    }
+
+    /// Sorted and deduplicated, so equal settings compare equal
+    fn normalized(mut self) -> Self {
+        self.skip_roles.sort();
+        self.skip_roles.dedup();
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasChunkingState {
+    pub repo_id: i64,
+    pub options: AtlasChunkingOptions,
+    pub derived_version: String,
+}
+
+pub async fn fetch_chunking_options(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<AtlasChunkingOptions, String> {
+    let row: Option<(i64, i64, String)> = sqlx::query_as(
+        r#"
+        SELECT max_chars, overlap_chars, skip_roles
+        FROM atlas_chunking_settings
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(row
+        .map(
+            |(max_chars, overlap_chars, skip_roles)| AtlasChunkingOptions {
+                max_chars: max_chars.clamp(CHUNK_MIN_CHARS as i64, CHUNK_TEXT_MAX_CHARS as i64)
+                    as usize,
+                overlap_chars: overlap_chars.max(0) as usize,
+                skip_roles: skip_roles
+                    .split(',')
+                    .filter(|role| !role.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            },
+        )
+        .unwrap_or_default())
+}
+
+/// Store the repo's settings. Returns whether they changed (callers
+/// re-derive the repo's chunks when they did).
+pub async fn store_chunking_options(
+    db: &SqlitePool,
+    repo_id: i64,
+    options: AtlasChunkingOptions,
+) -> Result<(AtlasChunkingOptions, bool), String> {
+    options.validate()?;
+    let options = options.normalized();
+    if fetch_chunking_options(db, repo_id).await? == options {
+        return Ok((options, false));
+    }
+
+    if options == AtlasChunkingOptions::default() {
+        sqlx::query("DELETE FROM atlas_chunking_settings WHERE repo_id = ?")
+            .bind(repo_id)
+            .execute(db)
+            .await
+            .map_err(|e| e.to_string())?;
+        return Ok((options, true));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO atlas_chunking_settings (repo_id, max_chars, overlap_chars, skip_roles, updated_at)
+        VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT(repo_id) DO UPDATE SET
+          max_chars = excluded.max_chars,
+          overlap_chars = excluded.overlap_chars,
+          skip_roles = excluded.skip_roles,
+          updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(repo_id)
+    .bind(options.max_chars as i64)
+    .bind(options.overlap_chars as i64)
+    .bind(options.skip_roles.join(","))
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok((options, true))
+}
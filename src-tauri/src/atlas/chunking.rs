@@ -2,7 +2,7 @@ use crate::import::parser::TraceMessage;
 use sha2::{Digest, Sha256};
 use std::collections::BTreeSet;
 
-use super::types::ATLAS_DERIVED_VERSION;
+use super::chunk_settings::AtlasChunkingOptions;
 
 pub const CHUNK_TEXT_MAX_CHARS: usize = 4_000;
 pub const MAX_CHUNKS_PER_SESSION: usize = 200;
@@ -23,7 +23,13 @@ pub struct DeriveSummary {
     pub truncated: bool,
 }
 
-pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage]) -> DeriveSummary {
+pub fn derive_chunks(
+    repo_id: i64,
+    session_id: &str,
+    messages: &[TraceMessage],
+    options: &AtlasChunkingOptions,
+) -> DeriveSummary {
+    let derived_version = options.derived_version();
     let mut out: Vec<DerivedChunk> = Vec::new();
     let mut truncated = false;
 
@@ -33,12 +39,15 @@ pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage])
     for (idx, msg) in messages.iter().enumerate() {
         let idx = idx as i64;
         let (role, text) = message_to_index_text(msg);
+        if options.skips(role) {
+            continue;
+        }
         let text = normalize_text(&text);
         if text.is_empty() {
             continue;
         }
 
-        let text = truncate_chars(&text, CHUNK_TEXT_MAX_CHARS);
+        let text = truncate_chars(&text, options.max_chars);
 
         // Cost: include a separator if we already have content.
         let additional = if current.is_empty() {
@@ -47,7 +56,7 @@ pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage])
             2 + text.len()
         };
 
-        if !current.is_empty() && current_len + additional > CHUNK_TEXT_MAX_CHARS {
+        if !current.is_empty() && current_len + additional > options.max_chars {
             if out.len() >= MAX_CHUNKS_PER_SESSION {
                 truncated = true;
                 break;
@@ -55,14 +64,23 @@ pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage])
             out.push(finalize_chunk(
                 repo_id,
                 session_id,
+                &derived_version,
                 out.len() as i64,
                 &current,
             ));
-            current.clear();
-            current_len = 0;
+            current = overlap_tail(current, options.overlap_chars);
+            current_len = joined_len(&current);
+            if current_len + 2 + text.len() > options.max_chars {
+                current.clear();
+                current_len = 0;
+            }
         }
 
-        current_len += additional;
+        current_len += if current.is_empty() {
+            text.len()
+        } else {
+            2 + text.len()
+        };
         current.push((idx, text, role));
     }
 
@@ -70,6 +88,7 @@ pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage])
         out.push(finalize_chunk(
             repo_id,
             session_id,
+            &derived_version,
             out.len() as i64,
             &current,
         ));
@@ -83,6 +102,35 @@ pub fn derive_chunks(repo_id: i64, session_id: &str, messages: &[TraceMessage])
     }
 }
 
+/// The trailing messages of a finished chunk that fit in `overlap_chars`,
+/// always leaving at least one behind so chunks keep advancing
+fn overlap_tail(
+    mut items: Vec<(i64, String, &'static str)>,
+    overlap_chars: usize,
+) -> Vec<(i64, String, &'static str)> {
+    let mut keep = 0;
+    let mut len = 0;
+    for (_, text, _) in items.iter().rev().take(items.len().saturating_sub(1)) {
+        let added = if keep == 0 {
+            text.len()
+        } else {
+            2 + text.len()
+        };
+        if len + added > overlap_chars {
+            break;
+        }
+        len += added;
+        keep += 1;
+    }
+    items.drain(..items.len() - keep);
+    items
+}
+
+fn joined_len(items: &[(i64, String, &'static str)]) -> usize {
+    let text: usize = items.iter().map(|(_, text, _)| text.len()).sum();
+    text + 2 * items.len().saturating_sub(1)
+}
+
 fn message_to_index_text(msg: &TraceMessage) -> (&'static str, String) {
     match msg {
         TraceMessage::User { text, .. } => ("user", format!("[USER]\\n{text}")),
@@ -109,6 +157,7 @@ fn message_to_index_text(msg: &TraceMessage) -> (&'static str, String) {
 fn finalize_chunk(
     repo_id: i64,
     session_id: &str,
+    derived_version: &str,
     chunk_index: i64,
     items: &[(i64, String, &'static str)],
 ) -> DerivedChunk {
@@ -135,6 +184,7 @@ fn finalize_chunk(
     let chunk_uid = derive_chunk_uid(
         repo_id,
         session_id,
+        derived_version,
         chunk_index,
         start_message_index,
         end_message_index,
@@ -151,9 +201,10 @@ fn finalize_chunk(
     }
 }
 
-fn derive_chunk_uid(
+pub(crate) fn derive_chunk_uid(
     repo_id: i64,
     session_id: &str,
+    derived_version: &str,
     chunk_index: i64,
     start_message_index: i64,
     end_message_index: i64,
@@ -161,7 +212,7 @@ fn derive_chunk_uid(
 ) -> String {
     let text_hash = sha256_hex(text.as_bytes());
     let canonical = format!(
-        "atl|{ATLAS_DERIVED_VERSION}|repo:{repo_id}|session:{session_id}|chunk:{chunk_index}|msgs:{start_message_index}-{end_message_index}|text:{text_hash}"
+        "atl|{derived_version}|repo:{repo_id}|session:{session_id}|chunk:{chunk_index}|msgs:{start_message_index}-{end_message_index}|text:{text_hash}"
     );
     let full = sha256_hex(canonical.as_bytes());
     let short = &full[..24.min(full.len())];
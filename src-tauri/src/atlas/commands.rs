@@ -1,12 +1,25 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
-use tauri::State;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{Manager, State};
 
+use crate::import::commands::session_in_repo_sql;
 use crate::DbState;
 
+use super::chunk_settings;
 use super::chunking::CHUNK_TEXT_MAX_CHARS;
+use super::compaction;
+use super::embeddings;
+use super::index_queue;
+use super::portable;
 use super::projection;
-use super::types::{AtlasBudgets, AtlasEnvelope, AtlasErrorCode, AtlasMeta, ATLAS_DERIVED_VERSION};
+use super::ranking::{AtlasRankingOptions, Ranker};
+use super::tokenizer;
+use super::types::{
+    AtlasBudgets, AtlasEnvelope, AtlasErrorCode, AtlasMeta, AtlasSearchFilters,
+    ATLAS_DERIVED_VERSION,
+};
 
 const QUERY_MAX_CHARS: usize = 256;
 const QUERY_MAX_TERMS: usize = 8;
@@ -15,6 +28,17 @@ const SNIPPET_MAX_CHARS: usize = 240;
 const GET_SESSION_MAX_CHUNKS: i64 = 25;
 const SESSION_ID_MAX_CHARS: usize = 128;
 const RESPONSE_MAX_CHARS: usize = 60_000;
+const FILTER_MAX_CHARS: usize = 128;
+/// Match markers `snippet()` wraps around matched terms; stripped into
+/// `AtlasSearchResult::highlights`
+const HIGHLIGHT_OPEN: char = '\u{2}';
+const HIGHLIGHT_CLOSE: char = '\u{3}';
+/// Candidates taken from each of the lexical and semantic lists per result
+const HYBRID_CANDIDATE_FACTOR: i64 = 4;
+/// FTS candidates reranked per lexical result
+const RERANK_CANDIDATE_FACTOR: i64 = 4;
+/// Buckets returned per search facet
+const FACET_MAX_BUCKETS: usize = 20;
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -22,21 +46,29 @@ pub struct AtlasCapabilitiesResponse {
     pub derived_version: String,
     pub fts5_enabled: bool,
     pub fts_table_ready: bool,
+    /// Hybrid search and chunk embedding are available (built with the
+    /// `atlas-embeddings` feature and a model is installed)
+    pub embeddings_available: bool,
     pub budgets: AtlasBudgets,
 }
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn atlas_capabilities(
+    app: tauri::AppHandle,
     db: State<'_, DbState>,
 ) -> Result<AtlasEnvelope<AtlasCapabilitiesResponse>, String> {
     let pool = &*db.0;
     let fts5_enabled = detect_fts5(pool).await;
     let fts_table_ready = detect_fts_table(pool).await;
+    let embeddings_available = embedding_model_dir(&app)
+        .map(|dir| embeddings::embeddings_available(&dir))
+        .unwrap_or(false);
 
     Ok(AtlasEnvelope::ok(AtlasCapabilitiesResponse {
         derived_version: ATLAS_DERIVED_VERSION.to_string(),
         fts5_enabled,
         fts_table_ready,
+        embeddings_available,
         budgets: AtlasBudgets {
             query_max_chars: QUERY_MAX_CHARS as u32,
             query_max_terms: QUERY_MAX_TERMS as u32,
@@ -59,6 +91,7 @@ pub struct AtlasIndexState {
     pub last_error: Option<String>,
     pub sessions_indexed: i64,
     pub chunks_indexed: i64,
+    pub chunk_budget: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -94,6 +127,7 @@ pub async fn atlas_introspect(
             last_error: None,
             sessions_indexed: 0,
             chunks_indexed: 0,
+            chunk_budget: None,
         });
 
     let chunks_in_table: i64 = sqlx::query_scalar(
@@ -127,12 +161,28 @@ pub async fn atlas_introspect(
     }))
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AtlasSearchMode {
+    /// BM25 over the FTS index; `score` is BM25 times the ranking factor
+    /// (lower is better)
+    #[default]
+    Lexical,
+    /// BM25 blended with embedding similarity; `score` is 0..1 (higher is
+    /// better)
+    Hybrid,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AtlasSearchRequest {
     pub repo_id: i64,
     pub query: String,
     pub limit: Option<i64>,
+    pub mode: Option<AtlasSearchMode>,
+    pub filters: Option<AtlasSearchFilters>,
+    /// Recency and role reranking (on by default)
+    pub ranking: Option<AtlasRankingOptions>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -143,24 +193,452 @@ pub struct AtlasSearchResult {
     pub chunk_index: i64,
     pub score: f64,
     pub snippet: String,
+    /// Matched terms in `snippet`, in order
+    pub highlights: Vec<AtlasHighlight>,
     pub session_imported_at: Option<String>,
     pub session_tool: Option<String>,
     pub session_model: Option<String>,
 }
 
+/// A matched range of a snippet, as UTF-16 code unit offsets (JS string
+/// indices); `end` is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasHighlight {
+    pub start: u32,
+    pub end: u32,
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AtlasSearchResponse {
+    pub mode: AtlasSearchMode,
     pub results: Vec<AtlasSearchResult>,
+    pub facets: AtlasSearchFacets,
+}
+
+/// Hit counts over all keyword matches, for drill-down filters. In hybrid
+/// mode, chunks found only by embedding similarity aren't counted.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasSearchFacets {
+    pub tools: Vec<AtlasFacetBucket>,
+    /// `YYYY-MM` of the session's import
+    pub months: Vec<AtlasFacetBucket>,
+    pub sessions: Vec<AtlasFacetBucket>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasFacetBucket {
+    pub value: String,
+    /// Matching chunks
+    pub count: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct SearchRow {
+    chunk_uid: String,
+    session_id: String,
+    chunk_index: i64,
+    score: f64,
+    snippet: String,
+    role_mask: String,
+    session_imported_at: Option<String>,
+    session_tool: Option<String>,
+    session_model: Option<String>,
+}
+
+impl SearchRow {
+    fn into_result(self) -> AtlasSearchResult {
+        let (snippet, highlights) = strip_highlights(&self.snippet, SNIPPET_MAX_CHARS);
+        AtlasSearchResult {
+            chunk_uid: self.chunk_uid,
+            session_id: self.session_id,
+            chunk_index: self.chunk_index,
+            score: self.score,
+            snippet,
+            highlights,
+            session_imported_at: self.session_imported_at,
+            session_tool: self.session_tool,
+            session_model: self.session_model,
+        }
+    }
+}
+
+/// How keyword matches are found
+enum KeywordSearch {
+    /// An FTS5 MATCH expression
+    Fts(String),
+    /// Case-insensitive substring matching over `atlas_chunks`, for SQLite
+    /// builds without FTS5
+    Substring(Vec<QueryClause>),
+}
+
+/// A validated search request
+struct SearchPlan<'a> {
+    repo_id: i64,
+    query: &'a str,
+    keywords: KeywordSearch,
+    filters: AtlasSearchFilters,
+    ranker: Option<Ranker>,
+    limit: i64,
+}
+
+/// Scale each row's score by its recency and role factor and re-sort
+fn rerank(rows: &mut [SearchRow], ranker: &Ranker, lower_is_better: bool) {
+    for row in rows.iter_mut() {
+        row.score *= ranker.factor(row.session_imported_at.as_deref(), &row.role_mask);
+    }
+    if lower_is_better {
+        rows.sort_by(|a, b| a.score.total_cmp(&b.score));
+    } else {
+        rows.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+}
+
+async fn lexical_search(
+    pool: &SqlitePool,
+    repo_id: i64,
+    match_query: &str,
+    filters: &AtlasSearchFilters,
+    limit: i64,
+) -> Result<Vec<SearchRow>, sqlx::Error> {
+    let (filter_sql, filter_binds) = filters.sql_conditions();
+    let sql = format!(
+        r#"
+        SELECT
+          c.chunk_uid AS chunk_uid,
+          c.session_id AS session_id,
+          c.chunk_index AS chunk_index,
+          bm25(atlas_chunks_fts) AS score,
+          snippet(atlas_chunks_fts, 0, char(2), char(3), '…', 8) AS snippet,
+          c.role_mask AS role_mask,
+          c.session_imported_at AS session_imported_at,
+          s.tool AS session_tool,
+          s.model AS session_model
+        FROM atlas_chunks_fts
+        JOIN atlas_chunks c ON c.id = atlas_chunks_fts.rowid
+        LEFT JOIN sessions s ON s.id = c.session_id
+        WHERE c.repo_id = ? AND atlas_chunks_fts MATCH ?{filter_sql}
+        ORDER BY score ASC, c.session_imported_at DESC, c.chunk_uid ASC
+        LIMIT ?
+        "#
+    );
+    let mut query = sqlx::query_as::<_, SearchRow>(&sql)
+        .bind(repo_id)
+        .bind(match_query);
+    for value in filter_binds {
+        query = query.bind(value);
+    }
+    query.bind(limit).fetch_all(pool).await
+}
+
+/// Substring fallback for `lexical_search`. `score` is the negated number of
+/// occurrences of the query's terms, so lower is better as with BM25.
+/// Matching folds ASCII case only (SQLite's `lower()`), and terms match
+/// anywhere in a word rather than as prefixes.
+async fn substring_search(
+    pool: &SqlitePool,
+    repo_id: i64,
+    clauses: &[QueryClause],
+    filters: &AtlasSearchFilters,
+    limit: i64,
+) -> Result<Vec<SearchRow>, sqlx::Error> {
+    let positive: Vec<String> = clauses
+        .iter()
+        .filter(|clause| !clause.negated)
+        .flat_map(|clause| clause.any_of.iter().map(QueryItem::needle))
+        .collect();
+    let occurrences = vec![
+        "(LENGTH(lower(c.text)) - LENGTH(REPLACE(lower(c.text), ?, ''))) / LENGTH(?)";
+        positive.len()
+    ]
+    .join(" + ");
+
+    let (match_sql, match_binds) = substring_conditions(clauses);
+    let (filter_sql, filter_binds) = filters.sql_conditions();
+    let sql = format!(
+        r#"
+        SELECT
+          c.chunk_uid AS chunk_uid,
+          c.session_id AS session_id,
+          c.chunk_index AS chunk_index,
+          -1.0 * ({occurrences}) AS score,
+          c.text AS snippet,
+          c.role_mask AS role_mask,
+          c.session_imported_at AS session_imported_at,
+          s.tool AS session_tool,
+          s.model AS session_model
+        FROM atlas_chunks c
+        LEFT JOIN sessions s ON s.id = c.session_id
+        WHERE c.repo_id = ?{match_sql}{filter_sql}
+        ORDER BY score ASC, c.session_imported_at DESC, c.chunk_uid ASC
+        LIMIT ?
+        "#
+    );
+    let mut query = sqlx::query_as::<_, SearchRow>(&sql);
+    for needle in &positive {
+        query = query.bind(needle.clone()).bind(needle.clone());
+    }
+    query = query.bind(repo_id);
+    for value in match_binds.into_iter().chain(filter_binds) {
+        query = query.bind(value);
+    }
+    let mut rows = query.bind(limit).fetch_all(pool).await?;
+    for row in &mut rows {
+        row.snippet = substring_snippet(&row.snippet, &positive);
+    }
+    Ok(rows)
+}
+
+/// `WHERE` conditions over `atlas_chunks c` matching `clauses`
+fn substring_conditions(clauses: &[QueryClause]) -> (String, Vec<String>) {
+    let mut sql = String::new();
+    let mut binds = Vec::new();
+    for clause in clauses {
+        let any_of = vec!["instr(lower(c.text), ?) > 0"; clause.any_of.len()].join(" OR ");
+        let negation = if clause.negated { "NOT " } else { "" };
+        sql.push_str(&format!(" AND {negation}({any_of})"));
+        binds.extend(clause.any_of.iter().map(QueryItem::needle));
+    }
+    (sql, binds)
+}
+
+/// Keyword candidates for a plan, by FTS or substring matching
+async fn keyword_search(
+    pool: &SqlitePool,
+    plan: &SearchPlan<'_>,
+    limit: i64,
+) -> Result<Vec<SearchRow>, sqlx::Error> {
+    match &plan.keywords {
+        KeywordSearch::Fts(match_query) => {
+            lexical_search(pool, plan.repo_id, match_query, &plan.filters, limit).await
+        }
+        KeywordSearch::Substring(clauses) => {
+            substring_search(pool, plan.repo_id, clauses, &plan.filters, limit).await
+        }
+    }
+}
+
+/// Keyword matches, reranked when the plan has a ranker
+async fn lexical_results(
+    pool: &SqlitePool,
+    plan: &SearchPlan<'_>,
+) -> Result<Vec<AtlasSearchResult>, sqlx::Error> {
+    let candidates = if plan.ranker.is_some() {
+        plan.limit * RERANK_CANDIDATE_FACTOR
+    } else {
+        plan.limit
+    };
+    let mut rows = keyword_search(pool, plan, candidates).await?;
+    if let Some(ranker) = &plan.ranker {
+        rerank(&mut rows, ranker, true);
+    }
+    rows.truncate(plan.limit as usize);
+    Ok(rows.into_iter().map(SearchRow::into_result).collect())
+}
+
+/// Hits per tool, month and session over every keyword match of a plan (not
+/// just the returned page), largest first and at most `FACET_MAX_BUCKETS`
+/// each; months are newest first
+async fn facet_counts(
+    pool: &SqlitePool,
+    plan: &SearchPlan<'_>,
+) -> Result<AtlasSearchFacets, sqlx::Error> {
+    let (from_sql, match_sql, match_binds) = match &plan.keywords {
+        KeywordSearch::Fts(match_query) => (
+            "atlas_chunks_fts JOIN atlas_chunks c ON c.id = atlas_chunks_fts.rowid",
+            " AND atlas_chunks_fts MATCH ?".to_string(),
+            vec![match_query.clone()],
+        ),
+        KeywordSearch::Substring(clauses) => {
+            let (sql, binds) = substring_conditions(clauses);
+            ("atlas_chunks c", sql, binds)
+        }
+    };
+    let (filter_sql, filter_binds) = plan.filters.sql_conditions();
+    let sql = format!(
+        r#"
+        SELECT
+          s.tool AS tool,
+          substr(c.session_imported_at, 1, 7) AS month,
+          c.session_id AS session_id,
+          COUNT(*) AS hits
+        FROM {from_sql}
+        LEFT JOIN sessions s ON s.id = c.session_id
+        WHERE c.repo_id = ?{match_sql}{filter_sql}
+        GROUP BY s.tool, month, c.session_id
+        "#
+    );
+    let mut query =
+        sqlx::query_as::<_, (Option<String>, Option<String>, String, i64)>(&sql).bind(plan.repo_id);
+    for value in match_binds.into_iter().chain(filter_binds) {
+        query = query.bind(value);
+    }
+
+    let mut tools: HashMap<String, i64> = HashMap::new();
+    let mut months: HashMap<String, i64> = HashMap::new();
+    let mut sessions: HashMap<String, i64> = HashMap::new();
+    for (tool, month, session_id, hits) in query.fetch_all(pool).await? {
+        if let Some(tool) = tool {
+            *tools.entry(tool).or_default() += hits;
+        }
+        if let Some(month) = month {
+            *months.entry(month).or_default() += hits;
+        }
+        *sessions.entry(session_id).or_default() += hits;
+    }
+
+    let by_count = |counts: HashMap<String, i64>| {
+        let mut buckets: Vec<AtlasFacetBucket> = counts
+            .into_iter()
+            .map(|(value, count)| AtlasFacetBucket { value, count })
+            .collect();
+        buckets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        buckets.truncate(FACET_MAX_BUCKETS);
+        buckets
+    };
+    let mut months = by_count(months);
+    months.sort_by(|a, b| b.value.cmp(&a.value));
+
+    Ok(AtlasSearchFacets {
+        tools: by_count(tools),
+        months,
+        sessions: by_count(sessions),
+    })
+}
+
+/// Rows for chunks by uid; the snippet is the start of the chunk text
+async fn rows_for_chunks(
+    pool: &SqlitePool,
+    repo_id: i64,
+    chunk_uids: &[String],
+) -> Result<Vec<SearchRow>, sqlx::Error> {
+    if chunk_uids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; chunk_uids.len()].join(", ");
+    let sql = format!(
+        r#"
+        SELECT
+          c.chunk_uid AS chunk_uid,
+          c.session_id AS session_id,
+          c.chunk_index AS chunk_index,
+          0.0 AS score,
+          c.text AS snippet,
+          c.role_mask AS role_mask,
+          c.session_imported_at AS session_imported_at,
+          s.tool AS session_tool,
+          s.model AS session_model
+        FROM atlas_chunks c
+        LEFT JOIN sessions s ON s.id = c.session_id
+        WHERE c.repo_id = ? AND c.chunk_uid IN ({placeholders})
+        "#
+    );
+    let mut query = sqlx::query_as::<_, SearchRow>(&sql).bind(repo_id);
+    for uid in chunk_uids {
+        query = query.bind(uid);
+    }
+    query.fetch_all(pool).await
+}
+
+/// Lexical and semantic candidates blended into the top `limit` results
+async fn hybrid_search(
+    app_data_dir: Option<&Path>,
+    pool: &SqlitePool,
+    plan: &SearchPlan<'_>,
+) -> Result<Vec<AtlasSearchResult>, AtlasEnvelope<AtlasSearchResponse>> {
+    let unavailable =
+        |message: String| AtlasEnvelope::err(AtlasErrorCode::EmbeddingsNotAvailable, message);
+    let internal = |message: String| AtlasEnvelope::err(AtlasErrorCode::Internal, message);
+
+    let embedder = app_data_dir
+        .ok_or_else(|| "App data directory unknown".to_string())
+        .and_then(|dir| embeddings::load_embedder(&embeddings::model_dir(dir)))
+        .map_err(unavailable)?;
+    let candidates = plan.limit * HYBRID_CANDIDATE_FACTOR;
+    let lexical = keyword_search(pool, plan, candidates)
+        .await
+        .map_err(|err| internal(format!("Search failed: {err}")))?;
+    let query_vector = embeddings::embed_texts(embedder.clone(), vec![plan.query.to_string()])
+        .await
+        .map_err(internal)?
+        .pop()
+        .unwrap_or_default();
+    let semantic = embeddings::semantic_matches(
+        pool,
+        plan.repo_id,
+        &embedder.model_id,
+        &query_vector,
+        &plan.filters,
+        candidates as usize,
+    )
+    .await
+    .map_err(internal)?;
+
+    let lexical_scores: Vec<(String, f64)> = lexical
+        .iter()
+        .map(|row| (row.chunk_uid.clone(), row.score))
+        .collect();
+    let ranked = embeddings::blend_scores(
+        &lexical_scores,
+        &semantic,
+        embeddings::HYBRID_LEXICAL_WEIGHT,
+    );
+
+    // Lexical hits keep their FTS snippet; the rest are looked up
+    let mut rows: HashMap<String, SearchRow> = lexical
+        .into_iter()
+        .map(|row| (row.chunk_uid.clone(), row))
+        .collect();
+    let missing: Vec<String> = ranked
+        .iter()
+        .filter(|(uid, _)| !rows.contains_key(uid))
+        .map(|(uid, _)| uid.clone())
+        .collect();
+    for row in rows_for_chunks(pool, plan.repo_id, &missing)
+        .await
+        .map_err(|err| internal(format!("Search failed: {err}")))?
+    {
+        rows.insert(row.chunk_uid.clone(), row);
+    }
+
+    let mut ranked_rows: Vec<SearchRow> = ranked
+        .into_iter()
+        .filter_map(|(uid, score)| {
+            let mut row = rows.remove(&uid)?;
+            row.score = score;
+            Some(row)
+        })
+        .collect();
+    if let Some(ranker) = &plan.ranker {
+        rerank(&mut ranked_rows, ranker, false);
+    }
+    ranked_rows.truncate(plan.limit as usize);
+    Ok(ranked_rows
+        .into_iter()
+        .map(SearchRow::into_result)
+        .collect())
 }
 
 #[tauri::command(rename_all = "camelCase")]
 pub async fn atlas_search(
+    app: tauri::AppHandle,
     db: State<'_, DbState>,
     request: AtlasSearchRequest,
 ) -> Result<AtlasEnvelope<AtlasSearchResponse>, String> {
-    let pool = &*db.0;
+    let app_data_dir = app.path().app_data_dir().ok();
+    search(&db.0, app_data_dir.as_deref(), request).await
+}
 
+/// `atlas_search` without the app: embedding models for hybrid mode are
+/// looked up under `app_data_dir` (`None`: hybrid mode is unavailable)
+pub async fn search(
+    pool: &SqlitePool,
+    app_data_dir: Option<&Path>,
+    request: AtlasSearchRequest,
+) -> Result<AtlasEnvelope<AtlasSearchResponse>, String> {
     if !repo_exists(pool, request.repo_id).await {
         return Ok(AtlasEnvelope::err(
             AtlasErrorCode::RepoNotFound,
@@ -187,60 +665,54 @@ pub async fn atlas_search(
         Some(v) => v,
     };
 
-    let fts_table_ready = detect_fts_table(pool).await;
-    if !fts_table_ready {
-        return Ok(AtlasEnvelope::err(
-            AtlasErrorCode::FtsNotAvailable,
-            "FTS index not available in this database build",
-        ));
-    }
-
-    let match_query = match build_match_query(&request.query) {
+    let clauses = match parse_query(&request.query) {
         Ok(v) => v,
         Err(code) => {
             return Ok(AtlasEnvelope::err(code, "Invalid query"));
         }
     };
 
-    #[derive(sqlx::FromRow)]
-    struct SearchRow {
-        chunk_uid: String,
-        session_id: String,
-        chunk_index: i64,
-        score: f64,
-        snippet: String,
-        session_imported_at: Option<String>,
-        session_tool: Option<String>,
-        session_model: Option<String>,
+    let filters = request.filters.unwrap_or_default();
+    if filters.max_value_chars() > FILTER_MAX_CHARS {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::InvalidQuery,
+            format!("Filter value too long (max {FILTER_MAX_CHARS} chars)"),
+        ));
     }
 
-    let rows = sqlx::query_as::<_, SearchRow>(
-        r#"
-        SELECT
-          c.chunk_uid AS chunk_uid,
-          c.session_id AS session_id,
-          c.chunk_index AS chunk_index,
-          bm25(atlas_chunks_fts) AS score,
-          snippet(atlas_chunks_fts, 0, '', '', '…', 8) AS snippet,
-          c.session_imported_at AS session_imported_at,
-          s.tool AS session_tool,
-          s.model AS session_model
-        FROM atlas_chunks_fts
-        JOIN atlas_chunks c ON c.id = atlas_chunks_fts.rowid
-        LEFT JOIN sessions s ON s.repo_id = c.repo_id AND s.id = c.session_id
-        WHERE c.repo_id = ? AND atlas_chunks_fts MATCH ?
-        ORDER BY score ASC, c.session_imported_at DESC, c.chunk_uid ASC
-        LIMIT ?
-        "#,
-    )
-    .bind(request.repo_id)
-    .bind(&match_query)
-    .bind(limit)
-    .fetch_all(pool)
-    .await;
+    // SQLite builds without FTS5 fall back to substring matching
+    let keywords = if detect_fts_table(pool).await {
+        KeywordSearch::Fts(build_match_query(&clauses))
+    } else {
+        KeywordSearch::Substring(clauses)
+    };
 
-    let rows = match rows {
-        Ok(v) => v,
+    let mode = request.mode.unwrap_or_default();
+    let plan = SearchPlan {
+        repo_id: request.repo_id,
+        query: &request.query,
+        keywords,
+        filters,
+        ranker: Ranker::new(&request.ranking.unwrap_or_default(), chrono::Utc::now()),
+        limit,
+    };
+    let mut results: Vec<AtlasSearchResult> = match mode {
+        AtlasSearchMode::Lexical => match lexical_results(pool, &plan).await {
+            Ok(results) => results,
+            Err(err) => {
+                return Ok(AtlasEnvelope::err(
+                    AtlasErrorCode::Internal,
+                    format!("Search failed: {err}"),
+                ));
+            }
+        },
+        AtlasSearchMode::Hybrid => match hybrid_search(app_data_dir, pool, &plan).await {
+            Ok(results) => results,
+            Err(envelope) => return Ok(envelope),
+        },
+    };
+    let facets = match facet_counts(pool, &plan).await {
+        Ok(facets) => facets,
         Err(err) => {
             return Ok(AtlasEnvelope::err(
                 AtlasErrorCode::Internal,
@@ -249,36 +721,30 @@ pub async fn atlas_search(
         }
     };
 
-    let mut results: Vec<AtlasSearchResult> = rows
-        .into_iter()
-        .map(|row| AtlasSearchResult {
-            chunk_uid: row.chunk_uid,
-            session_id: row.session_id,
-            chunk_index: row.chunk_index,
-            score: row.score,
-            snippet: truncate_chars(&row.snippet, SNIPPET_MAX_CHARS),
-            session_imported_at: row.session_imported_at,
-            session_tool: row.session_tool,
-            session_model: row.session_model,
-        })
-        .collect();
-
     // Enforce deterministic response max-chars by truncating from the end (stable ordering).
     let mut truncated = false;
-    while estimate_search_response_chars(&results) > RESPONSE_MAX_CHARS && !results.is_empty() {
+    let facet_chars = estimate_facet_chars(&facets);
+    while estimate_search_response_chars(&results) + facet_chars > RESPONSE_MAX_CHARS
+        && !results.is_empty()
+    {
         results.pop();
         truncated = true;
     }
 
+    let response = AtlasSearchResponse {
+        mode,
+        results,
+        facets,
+    };
     if truncated {
         Ok(AtlasEnvelope::ok_with_meta(
-            AtlasSearchResponse { results },
+            response,
             AtlasMeta {
                 truncated: Some(true),
             },
         ))
     } else {
-        Ok(AtlasEnvelope::ok(AtlasSearchResponse { results }))
+        Ok(AtlasEnvelope::ok(response))
     }
 }
 
@@ -351,14 +817,15 @@ pub async fn atlas_get_session(
         Some(v) => v,
     };
 
-    let row = sqlx::query(
+    let row = sqlx::query(&format!(
         r#"
-        SELECT id, tool, model, imported_at, duration_min, message_count, purged_at
-        FROM sessions
-        WHERE repo_id = ? AND id = ?
+        SELECT s.id, s.tool, s.model, s.imported_at, s.duration_min, s.message_count, s.purged_at
+        FROM sessions s
+        WHERE s.id = ?2 AND {}
         LIMIT 1
         "#,
-    )
+        session_in_repo_sql("s", "?1")
+    ))
     .bind(request.repo_id)
     .bind(&request.session_id)
     .fetch_optional(pool)
@@ -481,15 +948,16 @@ pub async fn atlas_doctor_report(
 
     let fts_table_ready = detect_fts_table(pool).await;
 
-    let indexable_sessions: i64 = sqlx::query_scalar(
+    let indexable_sessions: i64 = sqlx::query_scalar(&format!(
         r#"
         SELECT COUNT(*)
-        FROM sessions
-        WHERE repo_id = ?
-          AND purged_at IS NULL
-          AND raw_json != '{"messages":[]}'
+        FROM sessions s
+        WHERE {}
+          AND s.purged_at IS NULL
+          AND s.raw_json != '{{"messages":[]}}'
         "#,
-    )
+        session_in_repo_sql("s", "?1")
+    ))
     .bind(repo_id)
     .fetch_one(pool)
     .await
@@ -521,6 +989,14 @@ pub async fn atlas_doctor_report(
 
     let missing_sessions = (indexable_sessions - sessions_with_chunks).max(0);
     let state = fetch_index_state(pool, repo_id).await;
+    let derived_version = chunk_settings::fetch_chunking_options(pool, repo_id)
+        .await
+        .unwrap_or_default()
+        .derived_version();
+    // Chunks derived by an older version or with other chunking settings
+    let outdated = state
+        .as_ref()
+        .is_some_and(|s| s.chunks_indexed > 0 && s.derived_version != derived_version);
 
     let (last_rebuild_at, last_updated_at, last_error) = match &state {
         Some(s) => (
@@ -533,7 +1009,7 @@ pub async fn atlas_doctor_report(
 
     let status = if !fts_table_ready {
         "missing_fts".to_string()
-    } else if missing_sessions > 0 {
+    } else if missing_sessions > 0 || outdated {
         "stale".to_string()
     } else {
         "ok".to_string()
@@ -541,7 +1017,7 @@ pub async fn atlas_doctor_report(
 
     Ok(AtlasEnvelope::ok(AtlasDoctorReport {
         repo_id,
-        derived_version: ATLAS_DERIVED_VERSION.to_string(),
+        derived_version,
         fts_table_ready,
         indexable_sessions,
         sessions_with_chunks,
@@ -585,39 +1061,48 @@ pub async fn atlas_doctor_rebuild_derived(
         ));
     }
 
-    let deleted_chunks = match projection::delete_chunks_for_repo(pool, request.repo_id).await {
+    Ok(rebuild_derived(pool, request.repo_id).await)
+}
+
+/// Delete the repo's derived chunks and re-derive them from stored traces
+async fn rebuild_derived(
+    pool: &SqlitePool,
+    repo_id: i64,
+) -> AtlasEnvelope<AtlasDoctorRebuildSummary> {
+    let deleted_chunks = match projection::delete_chunks_for_repo(pool, repo_id).await {
         Ok(v) => v,
         Err(err) => {
-            projection::mark_index_error(pool, request.repo_id, &err).await;
-            return Ok(AtlasEnvelope::err(
+            projection::mark_index_error(pool, repo_id, &err).await;
+            return AtlasEnvelope::err(
                 AtlasErrorCode::Internal,
                 format!("Failed to clear derived chunks: {err}"),
-            ));
+            );
         }
     };
 
-    let sessions = sqlx::query(
+    let sessions = sqlx::query(&format!(
         r#"
-        SELECT id, raw_json
-        FROM sessions
-        WHERE repo_id = ?
-          AND purged_at IS NULL
-          AND raw_json != '{"messages":[]}'
-        ORDER BY imported_at ASC, id ASC
+        SELECT s.id, s.raw_json
+        FROM sessions s
+        WHERE {}
+          AND s.purged_at IS NULL
+          AND s.raw_json != '{{"messages":[]}}'
+        ORDER BY s.imported_at ASC, s.id ASC
         "#,
-    )
-    .bind(request.repo_id)
+        session_in_repo_sql("s", "?1")
+    ))
+    .bind(repo_id)
     .fetch_all(pool)
     .await;
 
     let sessions = match sessions {
         Ok(v) => v,
         Err(err) => {
-            projection::mark_index_error(pool, request.repo_id, &err.to_string()).await;
-            return Ok(AtlasEnvelope::err(
+            projection::mark_index_error(pool, repo_id, &err.to_string()).await;
+            return AtlasEnvelope::err(
                 AtlasErrorCode::Internal,
                 format!("Failed to enumerate sessions: {err}"),
-            ));
+            );
         }
     };
 
@@ -629,10 +1114,16 @@ pub async fn atlas_doctor_rebuild_derived(
         let session_id: String = row.get("id");
         let raw_json: String = row.get("raw_json");
         sessions_processed += 1;
+        let raw_json =
+            match crate::import::trace_chunks::load_trace_json(pool, &session_id, raw_json).await {
+                Ok(json) => json,
+                Err(err) => {
+                    projection::mark_index_error(pool, repo_id, &err.to_string()).await;
+                    continue;
+                }
+            };
 
-        match projection::upsert_chunks_for_session(pool, request.repo_id, &session_id, &raw_json)
-            .await
-        {
+        match projection::upsert_chunks_for_session(pool, repo_id, &session_id, &raw_json).await {
             Ok(sum) => {
                 chunks_written += sum.chunks_written;
                 if sum.truncated {
@@ -641,7 +1132,7 @@ pub async fn atlas_doctor_rebuild_derived(
             }
             Err(err) => {
                 // Best-effort: keep going, but record the latest failure.
-                projection::mark_index_error(pool, request.repo_id, &err).await;
+                projection::mark_index_error(pool, repo_id, &err).await;
             }
         }
     }
@@ -659,65 +1150,375 @@ pub async fn atlas_doctor_rebuild_derived(
         fts_rebuilt = rebuilt.is_ok();
     }
 
-    let _ = projection::refresh_index_state_counts(pool, request.repo_id, Some("rebuild")).await;
+    let _ = projection::refresh_index_state_counts(pool, repo_id, Some("rebuild")).await;
 
-    Ok(AtlasEnvelope::ok(AtlasDoctorRebuildSummary {
-        repo_id: request.repo_id,
+    AtlasEnvelope::ok(AtlasDoctorRebuildSummary {
+        repo_id: repo_id,
         sessions_processed,
         chunks_written,
         truncated_sessions,
         deleted_chunks,
         fts_rebuilt,
-    }))
+    })
 }
 
-// ------------------------- helpers -------------------------
-
-async fn repo_exists(db: &SqlitePool, repo_id: i64) -> bool {
-    let exists: Option<i64> = sqlx::query_scalar(
-        r#"
-        SELECT 1
-        FROM repos
-        WHERE id = ?
-        LIMIT 1
-        "#,
-    )
-    .bind(repo_id)
-    .fetch_optional(db)
-    .await
-    .ok()
-    .flatten();
-    exists.is_some()
-}
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_embed_chunks(
+    app: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    limit: Option<i64>,
+) -> Result<AtlasEnvelope<embeddings::AtlasEmbedSummary>, String> {
+    let pool = &*db.0;
 
-async fn detect_fts5(db: &SqlitePool) -> bool {
-    let used: Option<i64> = sqlx::query_scalar("SELECT sqlite_compileoption_used('ENABLE_FTS5')")
-        .fetch_optional(db)
-        .await
-        .ok()
-        .flatten();
+    let limit = limit.unwrap_or(embeddings::EMBED_DEFAULT_LIMIT);
+    if limit <= 0 || limit > embeddings::EMBED_LIMIT_MAX {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::BudgetLimitTooHigh,
+            format!("limit must be 1..={}", embeddings::EMBED_LIMIT_MAX),
+        ));
+    }
 
-    // Some builds may not expose sqlite_compileoption_used; treat errors as unknown.
-    used.unwrap_or(0) != 0
-}
+    if !repo_exists(pool, repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
 
-async fn detect_fts_table(db: &SqlitePool) -> bool {
-    // 1) Presence in sqlite_master
-    let exists: Option<i64> = sqlx::query_scalar(
-        r#"
-        SELECT 1
-        FROM sqlite_master
-        WHERE type = 'table' AND name = 'atlas_chunks_fts'
-        LIMIT 1
-        "#,
-    )
-    .fetch_optional(db)
-    .await
-    .ok()
-    .flatten();
+    let embedder = match embedding_model_dir(&app).and_then(|dir| embeddings::load_embedder(&dir)) {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(AtlasEnvelope::err(
+                AtlasErrorCode::EmbeddingsNotAvailable,
+                err,
+            ));
+        }
+    };
 
-    if exists.is_none() {
-        return false;
+    match embeddings::embed_pending_chunks(pool, repo_id, embedder, limit).await {
+        Ok(summary) => Ok(AtlasEnvelope::ok(summary)),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Embedding failed: {err}"),
+        )),
+    }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_index_pending(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    limit: Option<i64>,
+) -> Result<AtlasEnvelope<index_queue::AtlasIndexPending>, String> {
+    let pool = &*db.0;
+
+    let limit = match limit {
+        None => 20,
+        Some(v) if v <= 0 => 20,
+        Some(v) if v > LIMIT_MAX => {
+            return Ok(AtlasEnvelope::err(
+                AtlasErrorCode::BudgetLimitTooHigh,
+                format!("Limit too high (max {LIMIT_MAX})"),
+            ));
+        }
+        Some(v) => v,
+    };
+
+    if !repo_exists(pool, repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    match index_queue::pending_for_repo(pool, repo_id, limit).await {
+        Ok(pending) => Ok(AtlasEnvelope::ok(pending)),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Queue query failed: {err}"),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasCompactRequest {
+    pub repo_id: i64,
+    /// New chunk budget to store before compacting; 0 removes the budget
+    pub chunk_budget: Option<i64>,
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_compact(
+    db: State<'_, DbState>,
+    request: AtlasCompactRequest,
+) -> Result<AtlasEnvelope<compaction::AtlasCompactSummary>, String> {
+    let pool = &*db.0;
+
+    if !repo_exists(pool, request.repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    if let Some(budget) = request.chunk_budget {
+        if budget < 0 {
+            return Ok(AtlasEnvelope::err(
+                AtlasErrorCode::InvalidQuery,
+                "chunkBudget must be 0 (no budget) or positive",
+            ));
+        }
+        let budget = (budget > 0).then_some(budget);
+        if let Err(err) = compaction::set_chunk_budget(pool, request.repo_id, budget).await {
+            return Ok(AtlasEnvelope::err(
+                AtlasErrorCode::Internal,
+                format!("Failed to store chunk budget: {err}"),
+            ));
+        }
+    }
+
+    match compaction::compact_repo(pool, request.repo_id).await {
+        Ok(summary) => Ok(AtlasEnvelope::ok(summary)),
+        Err(err) => {
+            projection::mark_index_error(pool, request.repo_id, &err).await;
+            Ok(AtlasEnvelope::err(
+                AtlasErrorCode::Internal,
+                format!("Compaction failed: {err}"),
+            ))
+        }
+    }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_get_tokenizer(
+    db: State<'_, DbState>,
+) -> Result<AtlasEnvelope<tokenizer::AtlasTokenizerOptions>, String> {
+    match tokenizer::fetch_tokenizer_options(&db.0).await {
+        Ok(options) => Ok(AtlasEnvelope::ok(options)),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Failed to read tokenizer settings: {err}"),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasSetTokenizerRequest {
+    pub options: tokenizer::AtlasTokenizerOptions,
+    /// Rebuild even if the options are unchanged
+    pub force: Option<bool>,
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_set_tokenizer(
+    db: State<'_, DbState>,
+    request: AtlasSetTokenizerRequest,
+) -> Result<AtlasEnvelope<tokenizer::AtlasTokenizerSummary>, String> {
+    let pool = &*db.0;
+
+    if let Err(err) = request.options.tokenize_spec() {
+        return Ok(AtlasEnvelope::err(AtlasErrorCode::InvalidQuery, err));
+    }
+
+    match tokenizer::apply_tokenizer_options(pool, request.options, request.force.unwrap_or(false))
+        .await
+    {
+        Ok(summary) => Ok(AtlasEnvelope::ok(summary)),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Failed to apply tokenizer settings: {err}"),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasExportRequest {
+    pub repo_id: i64,
+    pub path: String,
+    /// Include index state (rebuild times, chunk budget)
+    pub include_state: Option<bool>,
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_export(
+    db: State<'_, DbState>,
+    request: AtlasExportRequest,
+) -> Result<AtlasEnvelope<portable::AtlasExportSummary>, String> {
+    let pool = &*db.0;
+
+    if !repo_exists(pool, request.repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    match portable::export_atlas(
+        pool,
+        request.repo_id,
+        Path::new(&request.path),
+        request.include_state.unwrap_or(false),
+    )
+    .await
+    {
+        Ok(summary) => Ok(AtlasEnvelope::ok(summary)),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Export failed: {err}"),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasImportRequest {
+    pub repo_id: i64,
+    pub path: String,
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_import(
+    db: State<'_, DbState>,
+    request: AtlasImportRequest,
+) -> Result<AtlasEnvelope<portable::AtlasImportSummary>, String> {
+    let pool = &*db.0;
+
+    if !repo_exists(pool, request.repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    let file = match portable::read_export(Path::new(&request.path)) {
+        Ok(file) => file,
+        Err(err) => return Ok(AtlasEnvelope::err(AtlasErrorCode::InvalidExportFile, err)),
+    };
+
+    match portable::import_atlas(pool, request.repo_id, file).await {
+        Ok(summary) => Ok(AtlasEnvelope::ok(summary)),
+        Err(err) => {
+            projection::mark_index_error(pool, request.repo_id, &err).await;
+            Ok(AtlasEnvelope::err(
+                AtlasErrorCode::Internal,
+                format!("Import failed: {err}"),
+            ))
+        }
+    }
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_reindex_session(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    session_id: String,
+) -> Result<AtlasEnvelope<index_queue::AtlasReindexSummary>, String> {
+    let pool = &*db.0;
+
+    if session_id.chars().count() > SESSION_ID_MAX_CHARS {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::BudgetSessionIdTooLong,
+            format!("sessionId too long (max {SESSION_ID_MAX_CHARS} chars)"),
+        ));
+    }
+
+    if !repo_exists(pool, repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    let in_repo: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM session_repos WHERE repo_id = ?1 AND session_id = ?2)
+            OR EXISTS (SELECT 1 FROM sessions WHERE repo_id = ?1 AND id = ?2)
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&session_id)
+    .fetch_one(pool)
+    .await
+    .unwrap_or(false);
+    if !in_repo {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::SessionNotFound,
+            "Session not found",
+        ));
+    }
+
+    match index_queue::reindex_session(pool, repo_id, &session_id).await {
+        Ok(Some(summary)) => Ok(AtlasEnvelope::ok(summary)),
+        Ok(None) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::SessionNotFound,
+            "Session has no stored trace to re-index (purged or imported from an export)",
+        )),
+        Err(err) => {
+            projection::mark_index_error(pool, repo_id, &err).await;
+            Ok(AtlasEnvelope::err(
+                AtlasErrorCode::Internal,
+                format!("Re-index failed: {err}"),
+            ))
+        }
+    }
+}
+
+// ------------------------- helpers -------------------------
+
+fn embedding_model_dir(app: &tauri::AppHandle) -> Result<std::path::PathBuf, String> {
+    let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(embeddings::model_dir(&app_data_dir))
+}
+
+async fn repo_exists(db: &SqlitePool, repo_id: i64) -> bool {
+    let exists: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT 1
+        FROM repos
+        WHERE id = ?
+        LIMIT 1
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten();
+    exists.is_some()
+}
+
+async fn detect_fts5(db: &SqlitePool) -> bool {
+    let used: Option<i64> = sqlx::query_scalar("SELECT sqlite_compileoption_used('ENABLE_FTS5')")
+        .fetch_optional(db)
+        .await
+        .ok()
+        .flatten();
+
+    // Some builds may not expose sqlite_compileoption_used; treat errors as unknown.
+    used.unwrap_or(0) != 0
+}
+
+async fn detect_fts_table(db: &SqlitePool) -> bool {
+    // 1) Presence in sqlite_master
+    let exists: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT 1
+        FROM sqlite_master
+        WHERE type = 'table' AND name = 'atlas_chunks_fts'
+        LIMIT 1
+        "#,
+    )
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten();
+
+    if exists.is_none() {
+        return false;
     }
 
     // 2) Simple query succeeds
@@ -727,48 +1528,170 @@ async fn detect_fts_table(db: &SqlitePool) -> bool {
         .is_ok()
 }
 
-fn build_match_query(raw: &str) -> Result<String, AtlasErrorCode> {
+/// One searchable unit of a query: a prefix term or an exact phrase
+enum QueryItem {
+    Term(String),
+    Phrase(Vec<String>),
+}
+
+impl QueryItem {
+    /// Quoted, so FTS operators and punctuation in the input stay literal
+    fn render(&self) -> String {
+        match self {
+            QueryItem::Term(term) => format!("\"{term}\"*"),
+            QueryItem::Phrase(words) => format!("\"{}\"", words.join(" ")),
+        }
+    }
+
+    /// The text a substring search looks for
+    fn needle(&self) -> String {
+        match self {
+            QueryItem::Term(term) => term.clone(),
+            QueryItem::Phrase(words) => words.join(" "),
+        }
+    }
+}
+
+/// Items joined by `OR`; negated clauses are excluded from the results
+struct QueryClause {
+    negated: bool,
+    any_of: Vec<QueryItem>,
+}
+
+impl QueryClause {
+    fn render(&self) -> String {
+        let items: Vec<String> = self.any_of.iter().map(QueryItem::render).collect();
+        if items.len() == 1 {
+            items.into_iter().next().unwrap_or_default()
+        } else {
+            format!("({})", items.join(" OR "))
+        }
+    }
+}
+
+/// Parse a user query into clauses.
+///
+/// Words are prefix terms and `"quoted text"` is an exact phrase; all must
+/// match unless joined by `OR`. `NOT word` or `-word` (also before a phrase)
+/// excludes matches. Operators are only recognized in upper case; everything
+/// else is reduced to a safe subset of characters.
+fn parse_query(raw: &str) -> Result<Vec<QueryClause>, AtlasErrorCode> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Err(AtlasErrorCode::InvalidQuery);
     }
 
-    let mut terms: Vec<String> = Vec::new();
-    for part in trimmed.split_whitespace() {
-        let normalized = normalize_term(part);
-        if normalized.is_empty() {
+    let mut clauses: Vec<QueryClause> = Vec::new();
+    let mut term_count = 0usize;
+    let mut pending_or = false;
+    let mut pending_not = false;
+    let mut chars = trimmed.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
             continue;
         }
-        terms.push(normalized);
-        if terms.len() > QUERY_MAX_TERMS {
+
+        let mut negated = false;
+        if c == '-' {
+            chars.next();
+            negated = true;
+        }
+
+        let item = if chars.peek() == Some(&'"') {
+            chars.next();
+            let mut phrase = String::new();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                phrase.push(c);
+            }
+            let words: Vec<String> = phrase
+                .split_whitespace()
+                .map(normalize_term)
+                .filter(|w| !w.is_empty())
+                .collect();
+            term_count += words.len();
+            (!words.is_empty()).then_some(QueryItem::Phrase(words))
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            if !negated {
+                match word.as_str() {
+                    "OR" => {
+                        pending_or = !clauses.is_empty();
+                        continue;
+                    }
+                    "NOT" => {
+                        pending_not = true;
+                        continue;
+                    }
+                    "AND" => continue,
+                    _ => {}
+                }
+            }
+            let term = normalize_term(&word);
+            term_count += usize::from(!term.is_empty());
+            (!term.is_empty()).then_some(QueryItem::Term(term))
+        };
+
+        if term_count > QUERY_MAX_TERMS {
             return Err(AtlasErrorCode::BudgetTooManyTerms);
         }
+        let Some(item) = item else {
+            continue;
+        };
+
+        let negated = negated || pending_not;
+        match clauses.last_mut() {
+            Some(last) if pending_or && last.negated == negated => last.any_of.push(item),
+            _ => clauses.push(QueryClause {
+                negated,
+                any_of: vec![item],
+            }),
+        }
+        pending_or = false;
+        pending_not = false;
     }
 
-    if terms.is_empty() {
+    if clauses.iter().all(|clause| clause.negated) {
         return Err(AtlasErrorCode::InvalidQuery);
     }
+    Ok(clauses)
+}
 
-    // Use prefix queries for search-as-you-type.
-    let mut out = String::new();
-    for (i, term) in terms.iter().enumerate() {
-        if i > 0 {
-            out.push_str(" AND ");
-        }
-        out.push_str(term);
-        out.push('*');
-    }
+/// Translate parsed clauses into an FTS5 MATCH expression
+fn build_match_query(clauses: &[QueryClause]) -> String {
+    let positive: Vec<String> = clauses
+        .iter()
+        .filter(|clause| !clause.negated)
+        .map(QueryClause::render)
+        .collect();
 
-    Ok(out)
+    let mut out = positive.join(" AND ");
+    for clause in clauses.iter().filter(|clause| clause.negated) {
+        out.push_str(" NOT ");
+        out.push_str(&clause.render());
+    }
+    out
 }
 
 fn normalize_term(input: &str) -> String {
-    // Guard against FTS query injection: allow a small safe subset.
-    // Note: unicode61 tokenizer will still segment; this is just query syntax hygiene.
+    // Guard against FTS query injection: allow a small safe subset (terms are
+    // quoted too). Letters of any script are kept for non-English transcripts;
+    // the tokenizer still segments.
     let mut out = String::new();
     for c in input.chars() {
-        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
-            out.push(c.to_ascii_lowercase());
+        if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {
+            out.extend(c.to_lowercase());
         }
     }
     out
@@ -781,12 +1704,117 @@ fn truncate_chars(input: &str, max_chars: usize) -> String {
     input.chars().take(max_chars).collect()
 }
 
+/// Remove the highlight markers from a `snippet()`, truncating it to
+/// `max_chars` and returning the ranges they enclosed
+fn strip_highlights(marked: &str, max_chars: usize) -> (String, Vec<AtlasHighlight>) {
+    let mut text = String::new();
+    let mut highlights = Vec::new();
+    let mut chars = 0usize;
+    let mut offset = 0u32;
+    let mut open: Option<u32> = None;
+    for c in marked.chars() {
+        match c {
+            HIGHLIGHT_OPEN => open = Some(offset),
+            HIGHLIGHT_CLOSE => {
+                if let Some(start) = open.take().filter(|start| *start < offset) {
+                    highlights.push(AtlasHighlight { start, end: offset });
+                }
+            }
+            _ => {
+                if chars == max_chars {
+                    break;
+                }
+                text.push(c);
+                chars += 1;
+                offset += c.len_utf16() as u32;
+            }
+        }
+    }
+    // A match cut off by truncation ends where the snippet does
+    if let Some(start) = open.filter(|start| *start < offset) {
+        highlights.push(AtlasHighlight { start, end: offset });
+    }
+    (text, highlights)
+}
+
+/// Byte range of the first occurrence of `needle` (lower case) in `text` at
+/// or after byte `from`, ignoring case
+fn find_ignore_case(text: &str, from: usize, needle: &str) -> Option<(usize, usize)> {
+    'start: for (offset, _) in text[from..].char_indices() {
+        let start = from + offset;
+        let mut wanted = needle.chars();
+        let mut pending = wanted.next();
+        for (i, c) in text[start..].char_indices() {
+            for lower in c.to_lowercase() {
+                match pending {
+                    Some(w) if w == lower => pending = wanted.next(),
+                    _ => continue 'start,
+                }
+            }
+            if pending.is_none() {
+                return Some((start, start + i + c.len_utf8()));
+            }
+        }
+        // The text ran out mid-match; later starts can't match either
+        return None;
+    }
+    None
+}
+
+/// A snippet of `text` around the first match of `needles`, with every
+/// match wrapped in highlight markers like `snippet()` output
+fn substring_snippet(text: &str, needles: &[String]) -> String {
+    let next_match = |from: usize| {
+        needles
+            .iter()
+            .filter_map(|needle| find_ignore_case(text, from, needle))
+            .min_by_key(|(start, end)| (*start, std::cmp::Reverse(*end)))
+    };
+
+    // Start a third of a snippet before the first match, at a word boundary
+    let first = next_match(0).map(|(start, _)| start).unwrap_or(0);
+    let lead: Vec<usize> = text[..first]
+        .char_indices()
+        .rev()
+        .take(SNIPPET_MAX_CHARS / 3)
+        .map(|(i, _)| i)
+        .collect();
+    let mut begin = lead.last().copied().unwrap_or(first);
+    if begin > 0 {
+        begin = text[begin..first]
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace())
+            .map(|(i, c)| begin + i + c.len_utf8())
+            .unwrap_or(begin);
+    }
+
+    let mut out = String::new();
+    if begin > 0 {
+        out.push('…');
+    }
+    // strip_highlights truncates; stop marking well past the snippet's end
+    let mut pos = begin;
+    while pos - begin < SNIPPET_MAX_CHARS * 4 {
+        let Some((start, end)) = next_match(pos) else {
+            break;
+        };
+        out.push_str(&text[pos..start]);
+        out.push(HIGHLIGHT_OPEN);
+        out.push_str(&text[start..end]);
+        out.push(HIGHLIGHT_CLOSE);
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
 fn estimate_search_response_chars(results: &[AtlasSearchResult]) -> usize {
     let mut total = 0usize;
     for r in results {
         total += r.chunk_uid.len();
         total += r.session_id.len();
         total += r.snippet.len();
+        total += r.highlights.len() * 24;
         total += r.session_imported_at.as_ref().map(|s| s.len()).unwrap_or(0);
         total += r.session_tool.as_ref().map(|s| s.len()).unwrap_or(0);
         total += r.session_model.as_ref().map(|s| s.len()).unwrap_or(0);
@@ -795,6 +1823,14 @@ fn estimate_search_response_chars(results: &[AtlasSearchResult]) -> usize {
     total
 }
 
+fn estimate_facet_chars(facets: &AtlasSearchFacets) -> usize {
+    [&facets.tools, &facets.months, &facets.sessions]
+        .into_iter()
+        .flatten()
+        .map(|bucket| bucket.value.len() + 32)
+        .sum()
+}
+
 fn estimate_get_session_response_chars(
     session: &AtlasSessionMeta,
     chunks: &[AtlasSessionChunk],
@@ -820,7 +1856,7 @@ async fn fetch_index_state(db: &SqlitePool, repo_id: i64) -> Option<AtlasIndexSt
     let row = sqlx::query(
         r#"
         SELECT repo_id, derived_version, last_rebuild_at, last_updated_at, last_error,
-               sessions_indexed, chunks_indexed
+               sessions_indexed, chunks_indexed, chunk_budget
         FROM atlas_index_state
         WHERE repo_id = ?
         LIMIT 1
@@ -840,5 +1876,379 @@ async fn fetch_index_state(db: &SqlitePool, repo_id: i64) -> Option<AtlasIndexSt
         last_error: row.get("last_error"),
         sessions_indexed: row.get("sessions_indexed"),
         chunks_indexed: row.get("chunks_indexed"),
+        chunk_budget: row.try_get("chunk_budget").ok().flatten(),
     })
 }
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_get_chunking(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<AtlasEnvelope<chunk_settings::AtlasChunkingState>, String> {
+    let pool = &*db.0;
+
+    if !repo_exists(pool, repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    match chunk_settings::fetch_chunking_options(pool, repo_id).await {
+        Ok(options) => Ok(AtlasEnvelope::ok(chunk_settings::AtlasChunkingState {
+            repo_id,
+            derived_version: options.derived_version(),
+            options,
+        })),
+        Err(err) => Ok(AtlasEnvelope::err(
+            AtlasErrorCode::Internal,
+            format!("Failed to read chunking settings: {err}"),
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasSetChunkingRequest {
+    pub repo_id: i64,
+    pub options: chunk_settings::AtlasChunkingOptions,
+    /// Rebuild even if the options are unchanged
+    pub force: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasSetChunkingResponse {
+    pub state: chunk_settings::AtlasChunkingState,
+    /// Absent when the options were unchanged and nothing was rebuilt
+    pub rebuild: Option<AtlasDoctorRebuildSummary>,
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn atlas_set_chunking(
+    db: State<'_, DbState>,
+    request: AtlasSetChunkingRequest,
+) -> Result<AtlasEnvelope<AtlasSetChunkingResponse>, String> {
+    let pool = &*db.0;
+
+    if !repo_exists(pool, request.repo_id).await {
+        return Ok(AtlasEnvelope::err(
+            AtlasErrorCode::RepoNotFound,
+            "Unknown repoId",
+        ));
+    }
+
+    if let Err(err) = request.options.validate() {
+        return Ok(AtlasEnvelope::err(AtlasErrorCode::InvalidQuery, err));
+    }
+
+    let (options, changed) = match chunk_settings::store_chunking_options(
+        pool,
+        request.repo_id,
+        request.options,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(err) => {
+            return Ok(AtlasEnvelope::err(
+                AtlasErrorCode::Internal,
+                format!("Failed to save chunking settings: {err}"),
+            ));
+        }
+    };
+    let state = chunk_settings::AtlasChunkingState {
+        repo_id: request.repo_id,
+        derived_version: options.derived_version(),
+        options,
+    };
+
+    if !changed && !request.force.unwrap_or(false) {
+        return Ok(AtlasEnvelope::ok(AtlasSetChunkingResponse {
+            state,
+            rebuild: None,
+        }));
+    }
+
+    let rebuild = rebuild_derived(pool, request.repo_id).await;
+    match (rebuild.value, rebuild.error) {
+        (Some(summary), _) => Ok(AtlasEnvelope::ok(AtlasSetChunkingResponse {
+            state,
+            rebuild: Some(summary),
+        })),
+        (None, error) => {
+            let (code, message) = error
+                .map(|e| (e.code, e.message))
+                .unwrap_or((AtlasErrorCode::Internal, "Rebuild failed".to_string()));
+            Ok(AtlasEnvelope::err(code, message))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facets_count_shared_sessions_under_their_tool() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo'), (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repos");
+            // Owned by repo 1, shared with repo 2.
+            sqlx::query(
+                "INSERT INTO sessions (id, repo_id, tool, raw_json) VALUES ('shared', 1, 'codex', '{}')",
+            )
+            .execute(&pool)
+            .await
+            .expect("insert session");
+            sqlx::query("INSERT INTO session_repos (session_id, repo_id) VALUES ('shared', 2)")
+                .execute(&pool)
+                .await
+                .expect("insert membership");
+            sqlx::query(
+                r#"
+                INSERT INTO atlas_chunks (
+                  chunk_uid, repo_id, session_id, chunk_index, start_message_index,
+                  end_message_index, role_mask, text, session_imported_at
+                )
+                VALUES ('shared:0', 2, 'shared', 0, 0, 1, 'user', 'rename the parser', '2026-03-04T00:00:00Z')
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .expect("insert chunk");
+
+            let clauses = || parse_query("parser").expect("query");
+            for keywords in [
+                KeywordSearch::Fts(build_match_query(&clauses())),
+                KeywordSearch::Substring(clauses()),
+            ] {
+                let plan = SearchPlan {
+                    repo_id: 2,
+                    query: "parser",
+                    keywords,
+                    filters: AtlasSearchFilters::default(),
+                    ranker: None,
+                    limit: 10,
+                };
+                let facets = facet_counts(&pool, &plan).await.expect("facets");
+                let buckets = |buckets: &[AtlasFacetBucket]| {
+                    buckets
+                        .iter()
+                        .map(|b| (b.value.clone(), b.count))
+                        .collect::<Vec<_>>()
+                };
+                assert_eq!(buckets(&facets.tools), vec![("codex".to_string(), 1)]);
+                assert_eq!(buckets(&facets.months), vec![("2026-03".to_string(), 1)]);
+                assert_eq!(buckets(&facets.sessions), vec![("shared".to_string(), 1)]);
+            }
+        });
+    }
+
+    fn match_query(raw: &str) -> String {
+        build_match_query(&parse_query(raw).expect("query"))
+    }
+
+    #[test]
+    fn words_are_prefix_terms_that_must_all_match() {
+        assert_eq!(match_query("rename parser"), r#""rename"* AND "parser"*"#);
+        // Operators are only recognized in upper case
+        assert_eq!(
+            match_query("parser or lexer"),
+            r#""parser"* AND "or"* AND "lexer"*"#
+        );
+    }
+
+    #[test]
+    fn or_groups_adjacent_terms() {
+        assert_eq!(match_query("parser OR lexer"), r#"("parser"* OR "lexer"*)"#);
+        assert_eq!(
+            match_query("rename parser OR lexer AND tests"),
+            r#""rename"* AND ("parser"* OR "lexer"*) AND "tests"*"#
+        );
+        // A leading OR has nothing to join
+        assert_eq!(match_query("OR parser"), r#""parser"*"#);
+    }
+
+    #[test]
+    fn not_and_minus_exclude_terms_and_phrases() {
+        assert_eq!(match_query("parser NOT lexer"), r#""parser"* NOT "lexer"*"#);
+        assert_eq!(match_query("parser -lexer"), r#""parser"* NOT "lexer"*"#);
+        assert_eq!(
+            match_query(r#"parser -"Token Stream" NOT tests"#),
+            r#""parser"* NOT "token stream" NOT "tests"*"#
+        );
+        // Hyphens inside a word are part of it
+        assert_eq!(match_query("tree-sitter"), r#""tree-sitter"*"#);
+    }
+
+    #[test]
+    fn quoted_text_is_an_exact_phrase() {
+        assert_eq!(
+            match_query(r#"rename "Split  the Parser""#),
+            r#""rename"* AND "split the parser""#
+        );
+        // An unterminated quote runs to the end of the query
+        assert_eq!(
+            match_query(r#"parser "token stream"#),
+            r#""parser"* AND "token stream""#
+        );
+    }
+
+    #[test]
+    fn queries_without_a_positive_term_are_rejected() {
+        for raw in [
+            "",
+            "   ",
+            "-parser",
+            "NOT parser",
+            "-parser NOT lexer",
+            "OR AND",
+            "*()",
+        ] {
+            assert!(
+                matches!(parse_query(raw), Err(AtlasErrorCode::InvalidQuery)),
+                "{raw:?}"
+            );
+        }
+        assert!(matches!(
+            parse_query("a b c d e f g h i"),
+            Err(AtlasErrorCode::BudgetTooManyTerms)
+        ));
+    }
+
+    #[test]
+    fn fts_syntax_is_stripped_from_terms() {
+        assert_eq!(
+            match_query(r#"parser* ^lexer col:name (grammar) +tokens NEAR/2"#),
+            r#""parser"* AND "lexer"* AND "colname"* AND "grammar"* AND "tokens"* AND "near2"*"#
+        );
+        // Quotes and operators can't break out of a phrase
+        assert_eq!(match_query(r#"lexer") OR (1"#), r#""lexer"* AND "or 1""#);
+        // Letters of any script survive, lower-cased
+        assert_eq!(
+            match_query("Überprüfung 構文"),
+            r#""überprüfung"* AND "構文"*"#
+        );
+    }
+
+    fn highlight(start: u32, end: u32) -> AtlasHighlight {
+        AtlasHighlight { start, end }
+    }
+
+    #[test]
+    fn highlight_offsets_count_utf16_code_units() {
+        let (text, highlights) = strip_highlights("h\u{2}éllo\u{3} wörld", SNIPPET_MAX_CHARS);
+        assert_eq!(text, "héllo wörld");
+        assert_eq!(highlights, vec![highlight(1, 5)]);
+
+        // Astral-plane characters are surrogate pairs in JavaScript strings
+        let (text, highlights) =
+            strip_highlights("🦀 \u{2}crab\u{3} 🦀\u{2}𝒳\u{3}", SNIPPET_MAX_CHARS);
+        assert_eq!(text, "🦀 crab 🦀𝒳");
+        assert_eq!(highlights, vec![highlight(3, 7), highlight(10, 12)]);
+
+        // Empty ranges are dropped
+        let (_, highlights) = strip_highlights("a\u{2}\u{3}b", SNIPPET_MAX_CHARS);
+        assert!(highlights.is_empty());
+    }
+
+    #[test]
+    fn truncation_counts_characters_and_closes_open_highlights() {
+        let (text, highlights) = strip_highlights("🦀\u{2}🦀🦀\u{3}", 2);
+        assert_eq!(text, "🦀🦀");
+        assert_eq!(highlights, vec![highlight(2, 4)]);
+    }
+
+    #[test]
+    fn find_ignore_case_returns_byte_ranges_of_the_original_text() {
+        assert_eq!(
+            find_ignore_case("Parser parser", 1, "parser"),
+            Some((7, 13))
+        );
+        assert_eq!(find_ignore_case("Größe", 0, "größe"), Some((0, 7)));
+        // The Kelvin sign (3 bytes) lowercases to an ASCII k
+        assert_eq!(
+            find_ignore_case("20 \u{212A}elvin", 0, "kelvin"),
+            Some((3, 11))
+        );
+        // İ (2 bytes) lowercases to i plus a combining dot (3 bytes)
+        assert_eq!(
+            find_ignore_case("İstanbul", 0, "i\u{307}stanbul"),
+            Some((0, 9))
+        );
+        assert_eq!(find_ignore_case("İstanbul", 0, "istanbul"), None);
+        assert_eq!(find_ignore_case("pars", 0, "parser"), None);
+    }
+
+    #[test]
+    fn substring_snippet_marks_matches_for_strip_highlights() {
+        let needles = ["pars".to_string(), "parser".to_string()];
+        let marked = substring_snippet("Ünïcode 🦀 Parser and PARSER", &needles);
+        assert_eq!(marked, "Ünïcode 🦀 \u{2}Parser\u{3} and \u{2}PARSER\u{3}");
+        let (text, highlights) = strip_highlights(&marked, SNIPPET_MAX_CHARS);
+        assert_eq!(text, "Ünïcode 🦀 Parser and PARSER");
+        assert_eq!(highlights, vec![highlight(11, 17), highlight(22, 28)]);
+
+        // Long lead-ins start at a word boundary before the match
+        let text = format!("{}needle", "wörd ".repeat(40));
+        let marked = substring_snippet(&text, &["needle".to_string()]);
+        assert_eq!(marked, format!("…{}\u{2}needle\u{3}", "wörd ".repeat(15)));
+    }
+
+    fn search_row(uid: &str, score: f64, role_mask: &str, imported_at: Option<&str>) -> SearchRow {
+        SearchRow {
+            chunk_uid: uid.to_string(),
+            session_id: "s1".to_string(),
+            chunk_index: 0,
+            score,
+            snippet: String::new(),
+            role_mask: role_mask.to_string(),
+            session_imported_at: imported_at.map(str::to_string),
+            session_tool: None,
+            session_model: None,
+        }
+    }
+
+    #[test]
+    fn rerank_boosts_rows_in_the_direction_of_their_scores() {
+        let now = "2026-03-31T00:00:00Z";
+        let ranker = Ranker::new(&AtlasRankingOptions::default(), now.parse().unwrap()).unwrap();
+        let order = |rows: &[SearchRow]| {
+            rows.iter()
+                .map(|row| row.chunk_uid.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        // BM25: more negative is more relevant, so a boost must push the
+        // score further below zero
+        let mut rows = [
+            search_row("old", -1.0, "user", None),
+            search_row("fresh_plan", -0.9, "plan", Some(now)),
+            search_row("tool_output", -0.8, "tool_call", None),
+        ];
+        rerank(&mut rows, &ranker, true);
+        assert_eq!(order(&rows), "fresh_plan,old,tool_output");
+        assert!(rows[0].score < -1.7);
+
+        // Blended scores: higher is more relevant
+        let mut rows = [
+            search_row("old", 1.0, "user", None),
+            search_row("fresh_plan", 0.9, "plan", Some(now)),
+            search_row("tool_output", 0.8, "tool_call", None),
+        ];
+        rerank(&mut rows, &ranker, false);
+        assert_eq!(order(&rows), "fresh_plan,old,tool_output");
+        assert!(rows[0].score > 1.7);
+    }
+}
@@ -0,0 +1,180 @@
+//! Atlas index size management
+//!
+//! `compact_repo` drops chunks of purged sessions, enforces the repo's
+//! optional chunk budget by evicting the chunks of the oldest imported
+//! sessions first, drops embeddings left without a chunk and merges the FTS
+//! index segments. Evicted sessions come back on re-import or a rebuild.
+
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use super::projection;
+use super::types::ATLAS_DERIVED_VERSION;
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasIndexSize {
+    pub sessions: i64,
+    pub chunks: i64,
+    /// Chunk text, before FTS overhead
+    pub text_bytes: i64,
+    pub embedding_bytes: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasCompactSummary {
+    pub repo_id: i64,
+    pub chunk_budget: Option<i64>,
+    pub before: AtlasIndexSize,
+    pub after: AtlasIndexSize,
+    /// Chunks of sessions purged since they were indexed
+    pub purged_chunks: u64,
+    /// Chunks evicted to fit the chunk budget
+    pub evicted_chunks: u64,
+    pub pruned_embeddings: u64,
+    pub fts_optimized: bool,
+}
+
+pub async fn index_size(db: &SqlitePool, repo_id: i64) -> Result<AtlasIndexSize, String> {
+    let row = sqlx::query(
+        r#"
+        SELECT
+          COUNT(DISTINCT session_id) AS sessions,
+          COUNT(*) AS chunks,
+          COALESCE(SUM(LENGTH(CAST(text AS BLOB))), 0) AS text_bytes,
+          (SELECT COALESCE(SUM(LENGTH(vector)), 0)
+             FROM atlas_chunk_embeddings WHERE repo_id = ?) AS embedding_bytes
+        FROM atlas_chunks
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(AtlasIndexSize {
+        sessions: row.try_get("sessions").unwrap_or(0),
+        chunks: row.try_get("chunks").unwrap_or(0),
+        text_bytes: row.try_get("text_bytes").unwrap_or(0),
+        embedding_bytes: row.try_get("embedding_bytes").unwrap_or(0),
+    })
+}
+
+pub async fn fetch_chunk_budget(db: &SqlitePool, repo_id: i64) -> Result<Option<i64>, String> {
+    let budget: Option<Option<i64>> =
+        sqlx::query_scalar("SELECT chunk_budget FROM atlas_index_state WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| e.to_string())?;
+    Ok(budget.flatten())
+}
+
+/// Set (`Some`) or clear (`None`) the repo's chunk budget
+pub async fn set_chunk_budget(
+    db: &SqlitePool,
+    repo_id: i64,
+    budget: Option<i64>,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO atlas_index_state (repo_id, derived_version, chunk_budget, updated_at)
+        VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT(repo_id) DO UPDATE SET
+          chunk_budget = excluded.chunk_budget,
+          updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(repo_id)
+    .bind(ATLAS_DERIVED_VERSION)
+    .bind(budget)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub async fn compact_repo(db: &SqlitePool, repo_id: i64) -> Result<AtlasCompactSummary, String> {
+    let before = index_size(db, repo_id).await?;
+    let chunk_budget = fetch_chunk_budget(db, repo_id).await?;
+
+    let purged_chunks = sqlx::query(
+        r#"
+        DELETE FROM atlas_chunks
+        WHERE repo_id = ?
+          AND session_id IN (SELECT id FROM sessions WHERE purged_at IS NOT NULL)
+        "#,
+    )
+    .bind(repo_id)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?
+    .rows_affected();
+
+    let mut evicted_chunks = 0;
+    if let Some(budget) = chunk_budget {
+        let chunks: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM atlas_chunks WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_one(db)
+            .await
+            .map_err(|e| e.to_string())?;
+        let excess = chunks - budget;
+        if excess > 0 {
+            evicted_chunks = sqlx::query(
+                r#"
+                DELETE FROM atlas_chunks
+                WHERE id IN (
+                  SELECT id FROM atlas_chunks
+                  WHERE repo_id = ?
+                  ORDER BY session_imported_at ASC, session_id ASC, chunk_index ASC
+                  LIMIT ?
+                )
+                "#,
+            )
+            .bind(repo_id)
+            .bind(excess)
+            .execute(db)
+            .await
+            .map_err(|e| e.to_string())?
+            .rows_affected();
+        }
+    }
+
+    let pruned_embeddings = sqlx::query(
+        r#"
+        DELETE FROM atlas_chunk_embeddings
+        WHERE repo_id = ?
+          AND chunk_uid NOT IN (SELECT chunk_uid FROM atlas_chunks WHERE repo_id = ?)
+        "#,
+    )
+    .bind(repo_id)
+    .bind(repo_id)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?
+    .rows_affected();
+
+    // Merges every FTS segment into one (the index is shared by all repos)
+    let fts_optimized =
+        sqlx::query("INSERT INTO atlas_chunks_fts(atlas_chunks_fts) VALUES('optimize')")
+            .execute(db)
+            .await
+            .is_ok();
+
+    let _ = projection::refresh_index_state_counts(db, repo_id, None).await;
+    let after = index_size(db, repo_id).await?;
+
+    Ok(AtlasCompactSummary {
+        repo_id,
+        chunk_budget,
+        before,
+        after,
+        purged_chunks,
+        evicted_chunks,
+        pruned_embeddings,
+        fts_optimized,
+    })
+}
@@ -0,0 +1,471 @@
+//! Optional semantic search over atlas chunks.
+//!
+//! Built with the `atlas-embeddings` feature, a local ONNX sentence-embedding
+//! model (`model.onnx` + `tokenizer.json`, e.g. all-MiniLM-L6-v2) placed in
+//! the app data dir under `models/atlas-embedding/` embeds chunks into
+//! `atlas_chunk_embeddings`. Hybrid search blends each candidate's BM25 score
+//! with its cosine similarity to the query, so paraphrased questions still
+//! find chunks that share no keywords with them.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use super::types::AtlasSearchFilters;
+
+/// Model directory, relative to the app data dir
+pub const EMBEDDING_MODEL_DIR: &str = "models/atlas-embedding";
+/// Chunks embedded per call when no limit is given
+pub const EMBED_DEFAULT_LIMIT: i64 = 500;
+pub const EMBED_LIMIT_MAX: i64 = 5_000;
+/// Weight of the normalized BM25 score in hybrid results; the rest is cosine
+pub const HYBRID_LEXICAL_WEIGHT: f64 = 0.5;
+
+#[cfg(feature = "atlas-embeddings")]
+const EMBED_MAX_TOKENS: usize = 256;
+#[cfg(feature = "atlas-embeddings")]
+const EMBED_BATCH_SIZE: usize = 16;
+/// Most recently embedded chunks compared against a query
+const SEMANTIC_MAX_CANDIDATES: i64 = 20_000;
+
+pub fn model_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(EMBEDDING_MODEL_DIR)
+}
+
+/// True if this build can embed and the model files are in place
+pub fn embeddings_available(dir: &Path) -> bool {
+    cfg!(feature = "atlas-embeddings")
+        && dir.join("model.onnx").is_file()
+        && dir.join("tokenizer.json").is_file()
+}
+
+pub struct AtlasEmbedder {
+    /// Identifies the model in `atlas_chunk_embeddings` (hash of model.onnx)
+    pub model_id: String,
+    #[cfg(feature = "atlas-embeddings")]
+    session: ort::session::Session,
+    #[cfg(feature = "atlas-embeddings")]
+    tokenizer: tokenizers::Tokenizer,
+    #[cfg(feature = "atlas-embeddings")]
+    needs_token_type_ids: bool,
+}
+
+impl AtlasEmbedder {
+    #[cfg(feature = "atlas-embeddings")]
+    fn load(dir: &Path) -> Result<Self, String> {
+        use sha2::{Digest, Sha256};
+
+        let model_path = dir.join("model.onnx");
+        let model_bytes = std::fs::read(&model_path)
+            .map_err(|e| format!("Cannot read {}: {}", model_path.display(), e))?;
+        let model_hash = format!("{:x}", Sha256::digest(&model_bytes));
+        let session = ort::session::Session::builder()
+            .and_then(|builder| builder.commit_from_memory(&model_bytes))
+            .map_err(|e| format!("Cannot load embedding model: {}", e))?;
+        let needs_token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+
+        let mut tokenizer = tokenizers::Tokenizer::from_file(dir.join("tokenizer.json"))
+            .map_err(|e| format!("Cannot load tokenizer: {}", e))?;
+        tokenizer.with_padding(Some(tokenizers::PaddingParams::default()));
+        tokenizer
+            .with_truncation(Some(tokenizers::TruncationParams {
+                max_length: EMBED_MAX_TOKENS,
+                ..Default::default()
+            }))
+            .map_err(|e| format!("Cannot configure tokenizer: {}", e))?;
+
+        Ok(Self {
+            model_id: format!("onnx:{}", &model_hash[..16]),
+            session,
+            tokenizer,
+            needs_token_type_ids,
+        })
+    }
+
+    #[cfg(not(feature = "atlas-embeddings"))]
+    fn load(_dir: &Path) -> Result<Self, String> {
+        Err("This build does not include atlas embeddings".to_string())
+    }
+
+    /// L2-normalized embeddings of `texts`, mean-pooled over their tokens
+    #[cfg(feature = "atlas-embeddings")]
+    pub fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        use ort::value::Tensor;
+        use tokenizers::Encoding;
+
+        let mut out = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(EMBED_BATCH_SIZE) {
+            let encodings = self
+                .tokenizer
+                .encode_batch(batch.to_vec(), true)
+                .map_err(|e| e.to_string())?;
+            let rows = encodings.len();
+            let cols = encodings.first().map(Encoding::len).unwrap_or(0);
+            let flat = |field: fn(&Encoding) -> &[u32]| -> Vec<i64> {
+                encodings
+                    .iter()
+                    .flat_map(|e| field(e).iter().map(|&v| v as i64))
+                    .collect()
+            };
+            let tensor = |data: Vec<i64>| {
+                Tensor::from_array(([rows, cols], data))
+                    .map(|t| t.into_dyn())
+                    .map_err(|e| e.to_string())
+            };
+
+            let mask = flat(Encoding::get_attention_mask);
+            let mut inputs = vec![
+                ("input_ids", tensor(flat(Encoding::get_ids))?),
+                ("attention_mask", tensor(mask.clone())?),
+            ];
+            if self.needs_token_type_ids {
+                inputs.push(("token_type_ids", tensor(flat(Encoding::get_type_ids))?));
+            }
+            let outputs = self.session.run(inputs).map_err(|e| e.to_string())?;
+            let (shape, values) = outputs[0]
+                .try_extract_raw_tensor::<f32>()
+                .map_err(|e| e.to_string())?;
+            let width = shape.last().copied().unwrap_or(0) as usize;
+            if width == 0 {
+                return Err("Embedding model returned an empty tensor".to_string());
+            }
+
+            if shape.len() == 2 {
+                // Already pooled: [batch, dims]
+                out.extend(values.chunks(width).map(|row| normalize(row.to_vec())));
+                continue;
+            }
+            // Token states: [batch, tokens, dims], averaged over unmasked tokens
+            for (row, row_mask) in mask.chunks(cols).enumerate() {
+                let mut sum = vec![0f32; width];
+                let mut count = 0f32;
+                for (token, _) in row_mask.iter().enumerate().filter(|(_, m)| **m != 0) {
+                    let start = (row * cols + token) * width;
+                    for (acc, v) in sum.iter_mut().zip(&values[start..start + width]) {
+                        *acc += v;
+                    }
+                    count += 1.0;
+                }
+                if count > 0.0 {
+                    sum.iter_mut().for_each(|v| *v /= count);
+                }
+                out.push(normalize(sum));
+            }
+        }
+        Ok(out)
+    }
+
+    #[cfg(not(feature = "atlas-embeddings"))]
+    pub fn embed(&self, _texts: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        Err("This build does not include atlas embeddings".to_string())
+    }
+}
+
+static EMBEDDER: Mutex<Option<(PathBuf, Arc<AtlasEmbedder>)>> = Mutex::new(None);
+
+/// The embedder for the model in `dir`, loaded on first use
+pub fn load_embedder(dir: &Path) -> Result<Arc<AtlasEmbedder>, String> {
+    let mut cached = EMBEDDER
+        .lock()
+        .map_err(|_| "Embedding model lock poisoned".to_string())?;
+    if let Some((path, embedder)) = cached.as_ref() {
+        if path == dir {
+            return Ok(embedder.clone());
+        }
+    }
+    if !embeddings_available(dir) {
+        return Err(format!(
+            "No embedding model in {} (expected model.onnx and tokenizer.json)",
+            dir.display()
+        ));
+    }
+    let embedder = Arc::new(AtlasEmbedder::load(dir)?);
+    *cached = Some((dir.to_path_buf(), embedder.clone()));
+    Ok(embedder)
+}
+
+/// Embed texts off the async runtime
+pub async fn embed_texts(
+    embedder: Arc<AtlasEmbedder>,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f32>>, String> {
+    tokio::task::spawn_blocking(move || {
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        embedder.embed(&texts)
+    })
+    .await
+    .map_err(|e| format!("Embedding task failed: {}", e))?
+}
+
+#[cfg_attr(not(feature = "atlas-embeddings"), allow(dead_code))]
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+/// Blend lexical (BM25, lower is better) and semantic (cosine) candidates
+/// into one ranking, best first. BM25 scores are rescaled to 0..1 across the
+/// lexical candidates and negative similarities count as 0; a chunk missing
+/// from one list scores 0 there.
+pub fn blend_scores(
+    lexical: &[(String, f64)],
+    semantic: &[(String, f32)],
+    lexical_weight: f64,
+) -> Vec<(String, f64)> {
+    let best = lexical
+        .iter()
+        .map(|(_, s)| *s)
+        .fold(f64::INFINITY, f64::min);
+    let worst = lexical
+        .iter()
+        .map(|(_, s)| *s)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let mut scores: HashMap<&str, f64> = HashMap::new();
+    for (uid, score) in lexical {
+        let scaled = if worst > best {
+            (worst - score) / (worst - best)
+        } else {
+            1.0
+        };
+        *scores.entry(uid.as_str()).or_default() += lexical_weight * scaled;
+    }
+    for (uid, similarity) in semantic {
+        *scores.entry(uid.as_str()).or_default() +=
+            (1.0 - lexical_weight) * (*similarity as f64).max(0.0);
+    }
+
+    let mut blended: Vec<(String, f64)> = scores
+        .into_iter()
+        .map(|(uid, score)| (uid.to_string(), score))
+        .collect();
+    blended.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    blended
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasEmbedSummary {
+    pub repo_id: i64,
+    pub model: String,
+    pub embedded: i64,
+    /// Vectors dropped because their chunk is no longer indexed
+    pub pruned: i64,
+    /// Chunks still without a vector for this model
+    pub remaining: i64,
+}
+
+/// Embed up to `limit` of the repo's chunks that have no vector for the
+/// embedder's model yet, most recently imported sessions first
+pub async fn embed_pending_chunks(
+    db: &SqlitePool,
+    repo_id: i64,
+    embedder: Arc<AtlasEmbedder>,
+    limit: i64,
+) -> Result<AtlasEmbedSummary, String> {
+    let model = embedder.model_id.clone();
+    let pruned = sqlx::query(
+        r#"
+        DELETE FROM atlas_chunk_embeddings
+        WHERE repo_id = ?
+          AND chunk_uid NOT IN (SELECT chunk_uid FROM atlas_chunks WHERE repo_id = ?)
+        "#,
+    )
+    .bind(repo_id)
+    .bind(repo_id)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?
+    .rows_affected() as i64;
+
+    let pending: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT c.chunk_uid, c.text
+        FROM atlas_chunks c
+        LEFT JOIN atlas_chunk_embeddings e ON e.chunk_uid = c.chunk_uid AND e.model = ?
+        WHERE c.repo_id = ? AND e.chunk_uid IS NULL
+        ORDER BY c.session_imported_at DESC, c.chunk_uid ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(&model)
+    .bind(repo_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let (uids, texts): (Vec<String>, Vec<String>) = pending.into_iter().unzip();
+    let vectors = embed_texts(embedder, texts).await?;
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    for (uid, vector) in uids.iter().zip(&vectors) {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO atlas_chunk_embeddings (chunk_uid, repo_id, model, dims, vector)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(uid)
+        .bind(repo_id)
+        .bind(&model)
+        .bind(vector.len() as i64)
+        .bind(encode_vector(vector))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    let remaining: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM atlas_chunks c
+        LEFT JOIN atlas_chunk_embeddings e ON e.chunk_uid = c.chunk_uid AND e.model = ?
+        WHERE c.repo_id = ? AND e.chunk_uid IS NULL
+        "#,
+    )
+    .bind(&model)
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .unwrap_or(0);
+
+    Ok(AtlasEmbedSummary {
+        repo_id,
+        model,
+        embedded: vectors.len() as i64,
+        pruned,
+        remaining,
+    })
+}
+
+/// The `k` indexed chunks matching `filters` most similar to `query`, most
+/// similar first
+pub async fn semantic_matches(
+    db: &SqlitePool,
+    repo_id: i64,
+    model: &str,
+    query: &[f32],
+    filters: &AtlasSearchFilters,
+    k: usize,
+) -> Result<Vec<(String, f32)>, String> {
+    let (filter_sql, filter_binds) = filters.sql_conditions();
+    let sql = format!(
+        r#"
+        SELECT e.chunk_uid, e.vector
+        FROM atlas_chunk_embeddings e
+        JOIN atlas_chunks c ON c.chunk_uid = e.chunk_uid
+        LEFT JOIN sessions s ON s.id = c.session_id
+        WHERE e.repo_id = ? AND e.model = ?{filter_sql}
+        ORDER BY e.created_at DESC
+        LIMIT ?
+        "#
+    );
+    let mut rows_query = sqlx::query_as::<_, (String, Vec<u8>)>(&sql)
+        .bind(repo_id)
+        .bind(model);
+    for value in filter_binds {
+        rows_query = rows_query.bind(value);
+    }
+    let rows = rows_query
+        .bind(SEMANTIC_MAX_CANDIDATES)
+        .fetch_all(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut matches: Vec<(String, f32)> = rows
+        .into_iter()
+        .map(|(uid, bytes)| {
+            let similarity = cosine_similarity(query, &decode_vector(&bytes));
+            (uid, similarity)
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    matches.truncate(k);
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits<S: Copy>(scores: &[(&str, S)]) -> Vec<(String, S)> {
+        scores
+            .iter()
+            .map(|(uid, score)| (uid.to_string(), *score))
+            .collect()
+    }
+
+    fn assert_blended(blended: &[(String, f64)], want: &[(&str, f64)]) {
+        assert_eq!(blended.len(), want.len(), "{blended:?}");
+        for ((uid, score), (want_uid, want_score)) in blended.iter().zip(want) {
+            assert_eq!(uid, want_uid, "{blended:?}");
+            assert!((score - want_score).abs() < 1e-6, "{blended:?}");
+        }
+    }
+
+    #[test]
+    fn lexical_only_hits_are_rescaled_best_first() {
+        let lexical = hits(&[("a", -3.0), ("b", -1.0), ("c", -2.0)]);
+        let blended = blend_scores(&lexical, &[], 0.7);
+        assert_blended(&blended, &[("a", 0.7), ("c", 0.35), ("b", 0.0)]);
+    }
+
+    #[test]
+    fn vector_only_hits_use_their_similarity() {
+        let semantic = hits(&[("a", 0.2f32), ("b", 0.9), ("c", -0.5)]);
+        let blended = blend_scores(&[], &semantic, 0.7);
+        assert_blended(&blended, &[("b", 0.27), ("a", 0.06), ("c", 0.0)]);
+    }
+
+    #[test]
+    fn equal_lexical_scores_count_as_best() {
+        let lexical = hits(&[("b", -2.0), ("a", -2.0)]);
+        let blended = blend_scores(&lexical, &[], 0.5);
+        assert_blended(&blended, &[("a", 0.5), ("b", 0.5)]);
+
+        let blended = blend_scores(&hits(&[("a", 4.2)]), &[], 0.5);
+        assert_blended(&blended, &[("a", 0.5)]);
+    }
+
+    #[test]
+    fn chunks_in_both_lists_sum_their_parts() {
+        let lexical = hits(&[("a", -3.0), ("b", -1.0)]);
+        let semantic = hits(&[("b", 0.9f32), ("c", 0.5)]);
+        let blended = blend_scores(&lexical, &semantic, 0.5);
+        assert_blended(&blended, &[("a", 0.5), ("b", 0.45), ("c", 0.25)]);
+    }
+}
@@ -0,0 +1,353 @@
+//! Background Atlas indexing
+//!
+//! Imports hand sessions to `index_session` instead of projecting them
+//! inline. With the app running, the session is queued in `atlas_index_queue`
+//! and a worker thread projects it, so large imports don't wait on chunking.
+//! A failed projection stays queued and is retried with exponential backoff
+//! (capped at `BACKOFF_MAX_SECS`), so transient errors heal on their own.
+//! Each drained batch emits `atlas-index-progress` for its repo.
+//!
+//! Without the worker (the CLI, tests) sessions are projected inline and only
+//! failures are queued, to be retried the next time the app runs.
+
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::portable::EMPTY_TRACE_JSON;
+use super::projection::{self, UpsertProjectionSummary};
+
+/// How often the worker checks for retries that have come due
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Sessions projected per batch (one progress event per repo per batch)
+const BATCH_SIZE: i64 = 25;
+
+const BACKOFF_BASE_SECS: i64 = 5;
+const BACKOFF_MAX_SECS: i64 = 3600;
+
+/// Wakes the worker; set once the worker thread is running
+static WORKER: OnceLock<Sender<()>> = OnceLock::new();
+
+/// Emitted after each batch the worker projects for a repo
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasIndexProgressEvent {
+    pub repo_id: i64,
+    pub indexed: i64,
+    pub failed: i64,
+    /// Sessions still queued for the repo, including ones waiting to retry
+    pub pending: i64,
+}
+
+/// Seconds to wait before retrying a session that has failed `attempts` times
+pub fn backoff_secs(attempts: i64) -> i64 {
+    let exponent = attempts.saturating_sub(1).clamp(0, 20) as u32;
+    BACKOFF_BASE_SECS
+        .saturating_mul(1 << exponent)
+        .min(BACKOFF_MAX_SECS)
+}
+
+/// Start the worker thread
+pub fn spawn(app_handle: AppHandle) {
+    let (sender, receiver) = mpsc::channel::<()>();
+    if WORKER.set(sender).is_err() {
+        return;
+    }
+    thread::spawn(move || loop {
+        if let Err(err) = drain(&app_handle) {
+            eprintln!("Narrative: Atlas indexing failed: {err}");
+        }
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(()) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    });
+}
+
+/// Project a session into Atlas: queued for the worker when it is running,
+/// otherwise inline (queued for retry on failure)
+pub async fn index_session(db: &SqlitePool, repo_id: i64, session_id: &str) {
+    if let Some(worker) = WORKER.get() {
+        // Before the migration that adds the queue has run, fall back to inline
+        if enqueue(db, repo_id, session_id).await.is_ok() {
+            let _ = worker.send(());
+            return;
+        }
+    }
+
+    if let Err(err) = project_session(db, repo_id, session_id).await {
+        projection::mark_index_error(db, repo_id, &err).await;
+        let _ = record_failure(db, repo_id, session_id, 0, &err).await;
+    }
+}
+
+async fn enqueue(db: &SqlitePool, repo_id: i64, session_id: &str) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO atlas_index_queue (repo_id, session_id)
+        VALUES (?, ?)
+        ON CONFLICT(repo_id, session_id) DO UPDATE SET
+          attempts = 0,
+          next_attempt_at = strftime('%Y-%m-%dT%H:%M:%fZ','now'),
+          last_error = NULL
+        "#,
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Re-derive a session's chunks from its stored trace. `None` if there is no
+/// trace to derive from: the session was deleted or purged, or its chunks
+/// were imported from an Atlas export.
+async fn project_session(
+    db: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+) -> Result<Option<UpsertProjectionSummary>, String> {
+    let raw_json: Option<String> = sqlx::query_scalar(
+        "SELECT raw_json FROM sessions WHERE id = ? AND purged_at IS NULL AND raw_json != ?",
+    )
+    .bind(session_id)
+    .bind(EMPTY_TRACE_JSON)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    let Some(raw_json) = raw_json else {
+        return Ok(None);
+    };
+    let raw_json = crate::import::trace_chunks::load_trace_json(db, session_id, raw_json)
+        .await
+        .map_err(|e| e.to_string())?;
+    projection::upsert_chunks_for_session(db, repo_id, session_id, &raw_json)
+        .await
+        .map(Some)
+}
+
+async fn record_failure(
+    db: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    previous_attempts: i64,
+    error: &str,
+) -> Result<(), String> {
+    let attempts = previous_attempts + 1;
+    sqlx::query(
+        r#"
+        INSERT INTO atlas_index_queue (repo_id, session_id, attempts, next_attempt_at, last_error)
+        VALUES (?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now', ?), ?)
+        ON CONFLICT(repo_id, session_id) DO UPDATE SET
+          attempts = excluded.attempts,
+          next_attempt_at = excluded.next_attempt_at,
+          last_error = excluded.last_error
+        "#,
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .bind(attempts)
+    .bind(format!("+{} seconds", backoff_secs(attempts)))
+    .bind(error)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasReindexSummary {
+    pub repo_id: i64,
+    pub session_id: String,
+    pub deleted_chunks: i64,
+    pub chunks_written: i64,
+    pub truncated: bool,
+}
+
+/// Delete and re-derive one session's chunks now, dropping it from the queue.
+/// `None` if the session has no trace to derive from.
+pub async fn reindex_session(
+    db: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+) -> Result<Option<AtlasReindexSummary>, String> {
+    let deleted_chunks: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM atlas_chunks WHERE repo_id = ? AND session_id = ?",
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let Some(projected) = project_session(db, repo_id, session_id).await? else {
+        return Ok(None);
+    };
+    let _ = sqlx::query("DELETE FROM atlas_index_queue WHERE repo_id = ? AND session_id = ?")
+        .bind(repo_id)
+        .bind(session_id)
+        .execute(db)
+        .await;
+    let _ = projection::refresh_index_state_counts(db, repo_id, None).await;
+
+    Ok(Some(AtlasReindexSummary {
+        repo_id,
+        session_id: session_id.to_string(),
+        deleted_chunks,
+        chunks_written: projected.chunks_written,
+        truncated: projected.truncated,
+    }))
+}
+
+/// Project one batch of due sessions; per repo (indexed, failed)
+pub async fn process_due(db: &SqlitePool) -> Result<BTreeMap<i64, (i64, i64)>, String> {
+    let due: Vec<(i64, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT repo_id, session_id, attempts
+        FROM atlas_index_queue
+        WHERE next_attempt_at <= strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        ORDER BY next_attempt_at ASC, enqueued_at ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(BATCH_SIZE)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut outcomes: BTreeMap<i64, (i64, i64)> = BTreeMap::new();
+    for (repo_id, session_id, attempts) in due {
+        let outcome = outcomes.entry(repo_id).or_default();
+        match project_session(db, repo_id, &session_id).await {
+            Ok(_) => {
+                sqlx::query("DELETE FROM atlas_index_queue WHERE repo_id = ? AND session_id = ?")
+                    .bind(repo_id)
+                    .bind(&session_id)
+                    .execute(db)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                outcome.0 += 1;
+            }
+            Err(err) => {
+                projection::mark_index_error(db, repo_id, &err).await;
+                record_failure(db, repo_id, &session_id, attempts, &err).await?;
+                outcome.1 += 1;
+            }
+        }
+    }
+
+    for repo_id in outcomes.keys() {
+        let _ = projection::refresh_index_state_counts(db, *repo_id, None).await;
+    }
+    Ok(outcomes)
+}
+
+/// Work through every due session, a batch at a time
+fn drain(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(pool) = app_handle
+        .try_state::<crate::DbState>()
+        .map(|state| state.0.clone())
+    else {
+        return Ok(());
+    };
+
+    tauri::async_runtime::block_on(async {
+        loop {
+            let outcomes = process_due(&pool).await?;
+            if outcomes.is_empty() {
+                return Ok(());
+            }
+            for (repo_id, (indexed, failed)) in outcomes {
+                let pending = count_pending(&pool, repo_id).await.unwrap_or(0);
+                let _ = app_handle.emit(
+                    "atlas-index-progress",
+                    AtlasIndexProgressEvent {
+                        repo_id,
+                        indexed,
+                        failed,
+                        pending,
+                    },
+                );
+            }
+        }
+    })
+}
+
+async fn count_pending(db: &SqlitePool, repo_id: i64) -> Result<i64, String> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM atlas_index_queue WHERE repo_id = ?")
+        .bind(repo_id)
+        .fetch_one(db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasIndexQueueEntry {
+    pub session_id: String,
+    /// Failed attempts so far (0: not tried yet)
+    pub attempts: i64,
+    pub next_attempt_at: String,
+    pub last_error: Option<String>,
+    pub enqueued_at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasIndexPending {
+    pub repo_id: i64,
+    pub pending: i64,
+    /// Queued sessions that have failed at least once
+    pub retrying: i64,
+    /// Oldest first, at most `limit`
+    pub entries: Vec<AtlasIndexQueueEntry>,
+}
+
+/// The repo's queued sessions
+pub async fn pending_for_repo(
+    db: &SqlitePool,
+    repo_id: i64,
+    limit: i64,
+) -> Result<AtlasIndexPending, String> {
+    let (pending, retrying): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(attempts > 0), 0)
+        FROM atlas_index_queue
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let entries = sqlx::query_as::<_, AtlasIndexQueueEntry>(
+        r#"
+        SELECT session_id, attempts, next_attempt_at, last_error, enqueued_at
+        FROM atlas_index_queue
+        WHERE repo_id = ?
+        ORDER BY enqueued_at ASC, session_id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(AtlasIndexPending {
+        repo_id,
+        pending,
+        retrying,
+        entries,
+    })
+}
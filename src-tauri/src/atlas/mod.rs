@@ -1,4 +1,11 @@
-pub mod chunking;
+pub mod chunk_settings;
+pub(crate) mod chunking;
 pub mod commands;
-pub mod projection;
+pub mod compaction;
+pub mod embeddings;
+pub mod index_queue;
+pub mod portable;
+pub(crate) mod projection;
+pub mod ranking;
+pub mod tokenizer;
 pub mod types;
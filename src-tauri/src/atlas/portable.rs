@@ -0,0 +1,291 @@
+//! Atlas index export/import
+//!
+//! An export holds a repo's chunks plus the tool, model and timing of each
+//! session they came from, without the session traces themselves. Importing
+//! it on another machine makes those sessions searchable there: sessions the
+//! importer doesn't have are added as trace-less stubs, and chunks are
+//! re-keyed to the local repo. Sessions the importer already indexed keep
+//! their own chunks. A rebuild keeps imported chunks, since they can't be
+//! re-derived without the trace.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::chunking::derive_chunk_uid;
+use super::compaction;
+use super::projection;
+use super::types::ATLAS_DERIVED_VERSION;
+
+pub const EXPORT_FORMAT: &str = "narrative-atlas-export";
+pub const EXPORT_VERSION: u32 = 1;
+
+/// `raw_json` of a session whose trace isn't stored locally
+pub const EMPTY_TRACE_JSON: &str = r#"{"messages":[]}"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedSession {
+    pub id: String,
+    pub tool: String,
+    pub model: Option<String>,
+    pub imported_at: String,
+    pub duration_min: Option<i64>,
+    pub message_count: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedChunk {
+    pub session_id: String,
+    pub chunk_index: i64,
+    pub start_message_index: i64,
+    pub end_message_index: i64,
+    pub role_mask: String,
+    pub text: String,
+    pub session_imported_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedIndexState {
+    pub last_rebuild_at: Option<String>,
+    pub last_updated_at: Option<String>,
+    pub chunk_budget: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasExportFile {
+    pub format: String,
+    pub version: u32,
+    pub derived_version: String,
+    pub exported_at: String,
+    pub sessions: Vec<ExportedSession>,
+    pub chunks: Vec<ExportedChunk>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub index_state: Option<ExportedIndexState>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasExportSummary {
+    pub repo_id: i64,
+    pub path: String,
+    pub sessions: usize,
+    pub chunks: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasImportSummary {
+    pub repo_id: i64,
+    /// Sessions added as trace-less stubs
+    pub sessions_added: usize,
+    /// Sessions already indexed here; their chunks in the file were skipped
+    pub sessions_skipped: usize,
+    pub chunks_imported: u64,
+}
+
+pub async fn export_atlas(
+    db: &SqlitePool,
+    repo_id: i64,
+    path: &Path,
+    include_state: bool,
+) -> Result<AtlasExportSummary, String> {
+    let sessions = sqlx::query_as::<_, ExportedSession>(
+        r#"
+        SELECT s.id, s.tool, s.model, s.imported_at, s.duration_min, s.message_count
+        FROM sessions s
+        WHERE s.id IN (SELECT DISTINCT session_id FROM atlas_chunks WHERE repo_id = ?)
+        ORDER BY s.imported_at ASC, s.id ASC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let chunks = sqlx::query_as::<_, ExportedChunk>(
+        r#"
+        SELECT session_id, chunk_index, start_message_index, end_message_index,
+               role_mask, text, session_imported_at
+        FROM atlas_chunks
+        WHERE repo_id = ?
+        ORDER BY session_id ASC, chunk_index ASC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let index_state = if include_state {
+        let row: Option<(Option<String>, Option<String>)> = sqlx::query_as(
+            "SELECT last_rebuild_at, last_updated_at FROM atlas_index_state WHERE repo_id = ?",
+        )
+        .bind(repo_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+        let (last_rebuild_at, last_updated_at) = row.unwrap_or_default();
+        Some(ExportedIndexState {
+            last_rebuild_at,
+            last_updated_at,
+            chunk_budget: compaction::fetch_chunk_budget(db, repo_id).await?,
+        })
+    } else {
+        None
+    };
+
+    let file = AtlasExportFile {
+        format: EXPORT_FORMAT.to_string(),
+        version: EXPORT_VERSION,
+        derived_version: ATLAS_DERIVED_VERSION.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        sessions,
+        chunks,
+        index_state,
+    };
+    let json = serde_json::to_vec(&file).map_err(|e| e.to_string())?;
+    std::fs::write(path, &json).map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+
+    Ok(AtlasExportSummary {
+        repo_id,
+        path: path.display().to_string(),
+        sessions: file.sessions.len(),
+        chunks: file.chunks.len(),
+        bytes: json.len() as u64,
+    })
+}
+
+/// Read and validate an export file
+pub fn read_export(path: &Path) -> Result<AtlasExportFile, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    let file: AtlasExportFile =
+        serde_json::from_slice(&bytes).map_err(|e| format!("Not an Atlas export: {e}"))?;
+    if file.format != EXPORT_FORMAT || file.version != EXPORT_VERSION {
+        return Err(format!(
+            "Unsupported export ({} v{}); expected {EXPORT_FORMAT} v{EXPORT_VERSION}",
+            file.format, file.version
+        ));
+    }
+    if file.derived_version != ATLAS_DERIVED_VERSION {
+        return Err(format!(
+            "Export was built by Atlas {}; this install uses {ATLAS_DERIVED_VERSION}",
+            file.derived_version
+        ));
+    }
+    Ok(file)
+}
+
+pub async fn import_atlas(
+    db: &SqlitePool,
+    repo_id: i64,
+    file: AtlasExportFile,
+) -> Result<AtlasImportSummary, String> {
+    let indexed: HashSet<String> =
+        sqlx::query_scalar("SELECT DISTINCT session_id FROM atlas_chunks WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_all(db)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .collect();
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    let mut sessions_added = 0;
+    let mut sessions_skipped = 0;
+    let mut importing: HashSet<&str> = HashSet::new();
+    for session in &file.sessions {
+        if indexed.contains(&session.id) {
+            sessions_skipped += 1;
+            continue;
+        }
+        let added = sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO sessions (
+              id, repo_id, tool, model, imported_at, duration_min, message_count,
+              files, trace_available, raw_json
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, '[]', 0, ?)
+            "#,
+        )
+        .bind(&session.id)
+        .bind(repo_id)
+        .bind(&session.tool)
+        .bind(&session.model)
+        .bind(&session.imported_at)
+        .bind(session.duration_min)
+        .bind(session.message_count)
+        .bind(EMPTY_TRACE_JSON)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+        sqlx::query("INSERT OR IGNORE INTO session_repos (session_id, repo_id) VALUES (?, ?)")
+            .bind(&session.id)
+            .bind(repo_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| e.to_string())?;
+        sessions_added += added as usize;
+        importing.insert(session.id.as_str());
+    }
+
+    let mut chunks_imported = 0;
+    for chunk in &file.chunks {
+        if !importing.contains(chunk.session_id.as_str()) {
+            continue;
+        }
+        let chunk_uid = derive_chunk_uid(
+            repo_id,
+            &chunk.session_id,
+            ATLAS_DERIVED_VERSION,
+            chunk.chunk_index,
+            chunk.start_message_index,
+            chunk.end_message_index,
+            &chunk.text,
+        );
+        chunks_imported += sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO atlas_chunks (
+              chunk_uid, repo_id, session_id, chunk_index, start_message_index,
+              end_message_index, role_mask, text, session_imported_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(chunk_uid)
+        .bind(repo_id)
+        .bind(&chunk.session_id)
+        .bind(chunk.chunk_index)
+        .bind(chunk.start_message_index)
+        .bind(chunk.end_message_index)
+        .bind(&chunk.role_mask)
+        .bind(&chunk.text)
+        .bind(&chunk.session_imported_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?
+        .rows_affected();
+    }
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    // Adopt the exporter's chunk budget unless this repo has its own
+    let budget = file.index_state.and_then(|state| state.chunk_budget);
+    if budget.is_some() && compaction::fetch_chunk_budget(db, repo_id).await?.is_none() {
+        compaction::set_chunk_budget(db, repo_id, budget).await?;
+    }
+    let _ = projection::refresh_index_state_counts(db, repo_id, None).await;
+
+    Ok(AtlasImportSummary {
+        repo_id,
+        sessions_added,
+        sessions_skipped,
+        chunks_imported,
+    })
+}
@@ -1,6 +1,8 @@
+use crate::import::commands::session_in_repo_sql;
 use crate::import::parser::SessionTrace;
 use sqlx::{Row, SqlitePool};
 
+use super::chunk_settings;
 use super::chunking::{derive_chunks, DeriveSummary};
 use super::types::ATLAS_DERIVED_VERSION;
 
@@ -21,8 +23,13 @@ pub async fn upsert_chunks_for_session(
         .ok()
         .flatten();
 
+    let options = chunk_settings::fetch_chunking_options(db, repo_id)
+        .await
+        .unwrap_or_default();
+
     let trace = serde_json::from_str::<SessionTrace>(raw_json).unwrap_or_default();
-    let DeriveSummary { chunks, truncated } = derive_chunks(repo_id, session_id, &trace.messages);
+    let DeriveSummary { chunks, truncated } =
+        derive_chunks(repo_id, session_id, &trace.messages, &options);
 
     let mut tx = db.begin().await.map_err(|e| e.to_string())?;
 
@@ -81,14 +88,18 @@ pub async fn upsert_chunks_for_session(
     })
 }
 
+/// Delete the repo's derived chunks. Chunks of sessions without a stored
+/// trace (imported from an Atlas export) can't be re-derived and are kept.
 pub async fn delete_chunks_for_repo(db: &SqlitePool, repo_id: i64) -> Result<u64, String> {
     let result = sqlx::query(
         r#"
         DELETE FROM atlas_chunks
         WHERE repo_id = ?
+          AND session_id NOT IN (SELECT id FROM sessions WHERE raw_json = ?)
         "#,
     )
     .bind(repo_id)
+    .bind(super::portable::EMPTY_TRACE_JSON)
     .execute(db)
     .await
     .map_err(|e| e.to_string())?;
@@ -119,6 +130,10 @@ pub async fn refresh_index_state_counts(
 
     let sessions_indexed: i64 = row.try_get("sessions_indexed").unwrap_or(0);
     let chunks_indexed: i64 = row.try_get("chunks_indexed").unwrap_or(0);
+    let derived_version = chunk_settings::fetch_chunking_options(db, repo_id)
+        .await
+        .unwrap_or_default()
+        .derived_version();
 
     let last_rebuild_at = if reason == Some("rebuild") {
         Some("CURRENT_TIMESTAMP")
@@ -126,6 +141,8 @@ pub async fn refresh_index_state_counts(
         None
     };
 
+    // `derived_version` describes how the repo's chunks were derived, so only a
+    // rebuild (or the first projection) sets it.
     // sqlite doesn't allow binding expressions for CURRENT_TIMESTAMP; so use two query variants.
     if last_rebuild_at.is_some() {
         sqlx::query(
@@ -152,7 +169,7 @@ pub async fn refresh_index_state_counts(
             "#,
         )
         .bind(repo_id)
-        .bind(ATLAS_DERIVED_VERSION)
+        .bind(&derived_version)
         .bind(sessions_indexed)
         .bind(chunks_indexed)
         .execute(db)
@@ -172,7 +189,6 @@ pub async fn refresh_index_state_counts(
             )
             VALUES (?, ?, CURRENT_TIMESTAMP, NULL, ?, ?, CURRENT_TIMESTAMP)
             ON CONFLICT(repo_id) DO UPDATE SET
-              derived_version = excluded.derived_version,
               last_updated_at = excluded.last_updated_at,
               last_error = NULL,
               sessions_indexed = excluded.sessions_indexed,
@@ -181,7 +197,7 @@ pub async fn refresh_index_state_counts(
             "#,
         )
         .bind(repo_id)
-        .bind(ATLAS_DERIVED_VERSION)
+        .bind(&derived_version)
         .bind(sessions_indexed)
         .bind(chunks_indexed)
         .execute(db)
@@ -198,7 +214,6 @@ pub async fn mark_index_error(db: &SqlitePool, repo_id: i64, message: &str) {
         INSERT INTO atlas_index_state (repo_id, derived_version, last_error, last_updated_at, updated_at)
         VALUES (?, ?, ?, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
         ON CONFLICT(repo_id) DO UPDATE SET
-          derived_version = excluded.derived_version,
           last_error = excluded.last_error,
           last_updated_at = excluded.last_updated_at,
           updated_at = CURRENT_TIMESTAMP
@@ -216,14 +231,15 @@ async fn fetch_session_imported_at(
     repo_id: i64,
     session_id: &str,
 ) -> Result<Option<String>, String> {
-    let imported_at: Option<String> = sqlx::query_scalar(
+    let imported_at: Option<String> = sqlx::query_scalar(&format!(
         r#"
-        SELECT imported_at
-        FROM sessions
-        WHERE repo_id = ? AND id = ?
+        SELECT s.imported_at
+        FROM sessions s
+        WHERE s.id = ?2 AND {}
         LIMIT 1
         "#,
-    )
+        session_in_repo_sql("s", "?1")
+    ))
     .bind(repo_id)
     .bind(session_id)
     .fetch_optional(db)
@@ -231,3 +247,54 @@ async fn fetch_session_imported_at(
     .map_err(|e| e.to_string())?;
     Ok(imported_at)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_session_projects_into_member_repo_with_import_date() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo'), (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repos");
+            let raw_json = r#"{"messages":[{"role":"user","text":"Split the parser"}]}"#;
+            // Owned by repo 1, shared with repo 2.
+            sqlx::query(
+                "INSERT INTO sessions (id, repo_id, tool, imported_at, raw_json) VALUES ('shared', 1, 'codex', '2026-03-04T00:00:00Z', ?)",
+            )
+            .bind(raw_json)
+            .execute(&pool)
+            .await
+            .expect("insert session");
+            sqlx::query("INSERT INTO session_repos (session_id, repo_id) VALUES ('shared', 2)")
+                .execute(&pool)
+                .await
+                .expect("insert membership");
+
+            let summary = upsert_chunks_for_session(&pool, 2, "shared", raw_json)
+                .await
+                .expect("project");
+            assert!(summary.chunks_written > 0);
+
+            let imported_at: Vec<Option<String>> = sqlx::query_scalar(
+                "SELECT session_imported_at FROM atlas_chunks WHERE repo_id = 2 AND session_id = 'shared'",
+            )
+            .fetch_all(&pool)
+            .await
+            .expect("chunks");
+            assert!(!imported_at.is_empty());
+            assert!(imported_at
+                .iter()
+                .all(|at| at.as_deref() == Some("2026-03-04T00:00:00Z")));
+        });
+    }
+}
@@ -0,0 +1,184 @@
+//! Atlas search reranking
+//!
+//! BM25 alone favours long chunks that repeat a term, whatever their age or
+//! content. After the FTS query, each candidate's relevance is multiplied by
+//! a recency boost (halving every `recency_half_life_days`) and by the weight
+//! of the strongest role in the chunk, so recent plans and answers rank above
+//! old tool-call output with similar text.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+pub const DEFAULT_RECENCY_HALF_LIFE_DAYS: f64 = 30.0;
+/// A chunk imported just now scores up to this much more than a very old one
+pub const DEFAULT_RECENCY_WEIGHT: f64 = 0.5;
+pub const DEFAULT_ROLE_WEIGHTS: [(&str, f64); 5] = [
+    ("plan", 1.3),
+    ("assistant", 1.15),
+    ("user", 1.0),
+    ("thinking", 0.9),
+    ("tool_call", 0.7),
+];
+
+/// Per-search overrides; unset fields use the defaults above
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasRankingOptions {
+    /// `false` keeps the raw BM25 / blended order
+    pub enabled: Option<bool>,
+    pub recency_half_life_days: Option<f64>,
+    /// 0 disables the recency boost
+    pub recency_weight: Option<f64>,
+    /// Replaces the weights of the roles it names
+    pub role_weights: Option<HashMap<String, f64>>,
+}
+
+/// Resolved options for one search
+pub struct Ranker {
+    now: DateTime<Utc>,
+    half_life_days: f64,
+    recency_weight: f64,
+    role_weights: HashMap<String, f64>,
+}
+
+impl Ranker {
+    /// `None` if reranking is disabled
+    pub fn new(options: &AtlasRankingOptions, now: DateTime<Utc>) -> Option<Self> {
+        if options.enabled == Some(false) {
+            return None;
+        }
+        let mut role_weights: HashMap<String, f64> = DEFAULT_ROLE_WEIGHTS
+            .iter()
+            .map(|(role, weight)| (role.to_string(), *weight))
+            .collect();
+        for (role, weight) in options.role_weights.iter().flatten() {
+            role_weights.insert(role.clone(), weight.max(0.0));
+        }
+        Some(Self {
+            now,
+            half_life_days: options
+                .recency_half_life_days
+                .filter(|days| *days > 0.0)
+                .unwrap_or(DEFAULT_RECENCY_HALF_LIFE_DAYS),
+            recency_weight: options
+                .recency_weight
+                .unwrap_or(DEFAULT_RECENCY_WEIGHT)
+                .max(0.0),
+            role_weights,
+        })
+    }
+
+    /// Multiplier for a chunk's relevance (higher is better)
+    pub fn factor(&self, imported_at: Option<&str>, role_mask: &str) -> f64 {
+        let recency = imported_at
+            .and_then(parse_timestamp)
+            .map(|at| {
+                let age_days = (self.now - at).num_seconds().max(0) as f64 / 86_400.0;
+                0.5f64.powf(age_days / self.half_life_days)
+            })
+            .unwrap_or(0.0);
+        let role = role_mask
+            .split(',')
+            .filter_map(|role| self.role_weights.get(role.trim()))
+            .copied()
+            .fold(None, |best: Option<f64>, w| {
+                Some(best.map_or(w, |b| b.max(w)))
+            })
+            .unwrap_or(1.0);
+        (1.0 + self.recency_weight * recency) * role
+    }
+}
+
+/// `imported_at` values are RFC 3339 or SQLite `CURRENT_TIMESTAMP` (UTC)
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|at| at.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|at| at.and_utc())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        "2026-03-31T00:00:00Z".parse().unwrap()
+    }
+
+    fn assert_factor(ranker: &Ranker, imported_at: Option<&str>, role_mask: &str, want: f64) {
+        let got = ranker.factor(imported_at, role_mask);
+        assert!(
+            (got - want).abs() < 1e-9,
+            "{imported_at:?} {role_mask}: {got}"
+        );
+    }
+
+    #[test]
+    fn recency_boost_halves_every_half_life() {
+        let ranker = Ranker::new(&AtlasRankingOptions::default(), now()).unwrap();
+        assert_factor(&ranker, Some("2026-03-31T00:00:00Z"), "user", 1.5);
+        assert_factor(&ranker, Some("2026-03-01T00:00:00Z"), "user", 1.25);
+        assert_factor(&ranker, Some("2026-01-30T00:00:00Z"), "user", 1.125);
+        // SQLite CURRENT_TIMESTAMP format
+        assert_factor(&ranker, Some("2026-03-01 00:00:00"), "user", 1.25);
+        // Clock skew doesn't boost past a fresh import
+        assert_factor(&ranker, Some("2026-04-30T00:00:00Z"), "user", 1.5);
+        // Unknown age gets no boost
+        assert_factor(&ranker, None, "user", 1.0);
+        assert_factor(&ranker, Some("last week"), "user", 1.0);
+
+        let options = AtlasRankingOptions {
+            recency_half_life_days: Some(10.0),
+            recency_weight: Some(1.0),
+            ..Default::default()
+        };
+        let ranker = Ranker::new(&options, now()).unwrap();
+        assert_factor(&ranker, Some("2026-03-21T00:00:00Z"), "user", 1.5);
+
+        let options = AtlasRankingOptions {
+            recency_weight: Some(0.0),
+            ..Default::default()
+        };
+        let ranker = Ranker::new(&options, now()).unwrap();
+        assert_factor(&ranker, Some("2026-03-31T00:00:00Z"), "user", 1.0);
+    }
+
+    #[test]
+    fn strongest_role_in_the_chunk_sets_the_weight() {
+        let ranker = Ranker::new(&AtlasRankingOptions::default(), now()).unwrap();
+        assert_factor(&ranker, None, "tool_call", 0.7);
+        assert_factor(&ranker, None, "tool_call, plan", 1.3);
+        assert_factor(&ranker, None, "thinking,assistant", 1.15);
+        assert_factor(&ranker, None, "system", 1.0);
+        assert_factor(&ranker, None, "", 1.0);
+        // Role and recency multiply
+        assert_factor(&ranker, Some("2026-03-31T00:00:00Z"), "tool_call", 1.05);
+
+        let options = AtlasRankingOptions {
+            role_weights: Some(HashMap::from([
+                ("tool_call".to_string(), 2.0),
+                ("user".to_string(), -1.0),
+            ])),
+            ..Default::default()
+        };
+        let ranker = Ranker::new(&options, now()).unwrap();
+        assert_factor(&ranker, None, "tool_call,plan", 2.0);
+        assert_factor(&ranker, None, "assistant", 1.15);
+        // Negative weights are clamped
+        assert_factor(&ranker, None, "user", 0.0);
+    }
+
+    #[test]
+    fn disabled_ranking_has_no_ranker() {
+        let options = AtlasRankingOptions {
+            enabled: Some(false),
+            ..Default::default()
+        };
+        assert!(Ranker::new(&options, now()).is_none());
+    }
+}
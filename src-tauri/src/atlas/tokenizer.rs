@@ -0,0 +1,207 @@
+//! Atlas FTS tokenizer settings
+//!
+//! `atlas_chunks_fts` is created with `tokenize='unicode61'`. These options
+//! describe alternatives: porter stemming (English word forms), how diacritics
+//! are folded, extra token characters (keeps code identifiers such as
+//! `snake_case` whole) and a trigram tokenizer for languages without spaces
+//! (CJK) and substring matches. Applying new options recreates the FTS table
+//! and rebuilds it from `atlas_chunks`.
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+/// Longest `token_chars` accepted
+const TOKEN_CHARS_MAX: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AtlasTokenizerKind {
+    #[default]
+    Unicode61,
+    /// Indexes every 3-character sequence; queries need at least 3 characters
+    Trigram,
+}
+
+impl AtlasTokenizerKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            AtlasTokenizerKind::Unicode61 => "unicode61",
+            AtlasTokenizerKind::Trigram => "trigram",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "trigram" => AtlasTokenizerKind::Trigram,
+            _ => AtlasTokenizerKind::Unicode61,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasTokenizerOptions {
+    pub tokenizer: AtlasTokenizerKind,
+    /// Stem English words ("running" matches "run")
+    pub porter: bool,
+    /// unicode61 only: 0 keeps diacritics, 1 folds them (SQLite default), 2
+    /// also folds combining marks
+    pub remove_diacritics: u8,
+    /// unicode61 only: punctuation indexed as part of words, e.g. "_."
+    pub token_chars: String,
+}
+
+impl Default for AtlasTokenizerOptions {
+    fn default() -> Self {
+        Self {
+            tokenizer: AtlasTokenizerKind::Unicode61,
+            porter: false,
+            remove_diacritics: 1,
+            token_chars: String::new(),
+        }
+    }
+}
+
+impl AtlasTokenizerOptions {
+    /// The FTS5 `tokenize` argument for these options
+    pub fn tokenize_spec(&self) -> Result<String, String> {
+        if self.remove_diacritics > 2 {
+            return Err("removeDiacritics must be 0, 1 or 2".to_string());
+        }
+        if self.token_chars.chars().count() > TOKEN_CHARS_MAX
+            || !self
+                .token_chars
+                .chars()
+                .all(|c| c.is_ascii_punctuation() && c != '\'' && c != '"')
+        {
+            return Err(format!(
+                "tokenChars must be at most {TOKEN_CHARS_MAX} ASCII punctuation characters (no quotes)"
+            ));
+        }
+
+        let mut spec = String::new();
+        if self.porter {
+            spec.push_str("porter ");
+        }
+        spec.push_str(self.tokenizer.as_str());
+        if self.tokenizer == AtlasTokenizerKind::Unicode61 {
+            if self.remove_diacritics != 1 {
+                spec.push_str(&format!(" remove_diacritics {}", self.remove_diacritics));
+            }
+            if !self.token_chars.is_empty() {
+                spec.push_str(&format!(" tokenchars '{}'", self.token_chars));
+            }
+        }
+        Ok(spec)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasTokenizerSummary {
+    pub options: AtlasTokenizerOptions,
+    pub tokenize: String,
+    /// False when the options were unchanged and nothing was rebuilt
+    pub rebuilt: bool,
+    pub chunks_indexed: i64,
+}
+
+pub async fn fetch_tokenizer_options(db: &SqlitePool) -> Result<AtlasTokenizerOptions, String> {
+    let row: Option<(String, bool, i64, String)> = sqlx::query_as(
+        r#"
+        SELECT tokenizer, porter != 0, remove_diacritics, token_chars
+        FROM atlas_fts_settings
+        WHERE id = 1
+        "#,
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(row
+        .map(
+            |(tokenizer, porter, remove_diacritics, token_chars)| AtlasTokenizerOptions {
+                tokenizer: AtlasTokenizerKind::parse(&tokenizer),
+                porter,
+                remove_diacritics: remove_diacritics.clamp(0, 2) as u8,
+                token_chars,
+            },
+        )
+        .unwrap_or_default())
+}
+
+/// Recreate `atlas_chunks_fts` with `options` and reindex every chunk.
+/// Unchanged options are a no-op unless `force` is set.
+pub async fn apply_tokenizer_options(
+    db: &SqlitePool,
+    options: AtlasTokenizerOptions,
+    force: bool,
+) -> Result<AtlasTokenizerSummary, String> {
+    let tokenize = options.tokenize_spec()?;
+    let chunks_indexed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM atlas_chunks")
+        .fetch_one(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !force && fetch_tokenizer_options(db).await? == options {
+        return Ok(AtlasTokenizerSummary {
+            options,
+            tokenize,
+            rebuilt: false,
+            chunks_indexed,
+        });
+    }
+
+    // The content triggers on atlas_chunks resolve the table by name, so they
+    // keep working against the recreated table.
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query("DROP TABLE IF EXISTS atlas_chunks_fts")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query(&format!(
+        r#"
+        CREATE VIRTUAL TABLE atlas_chunks_fts USING fts5(
+          text,
+          content='atlas_chunks',
+          content_rowid='id',
+          tokenize="{tokenize}",
+          prefix='2 3 4'
+        )
+        "#
+    ))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    sqlx::query("INSERT INTO atlas_chunks_fts(atlas_chunks_fts) VALUES('rebuild')")
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO atlas_fts_settings (id, tokenizer, porter, remove_diacritics, token_chars, updated_at)
+        VALUES (1, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON CONFLICT(id) DO UPDATE SET
+          tokenizer = excluded.tokenizer,
+          porter = excluded.porter,
+          remove_diacritics = excluded.remove_diacritics,
+          token_chars = excluded.token_chars,
+          updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(options.tokenizer.as_str())
+    .bind(options.porter)
+    .bind(options.remove_diacritics as i64)
+    .bind(&options.token_chars)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    Ok(AtlasTokenizerSummary {
+        options,
+        tokenize,
+        rebuilt: true,
+        chunks_indexed,
+    })
+}
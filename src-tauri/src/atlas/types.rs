@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-pub const ATLAS_DERIVED_VERSION: &str = "atlas/0.1.0";
+pub const ATLAS_DERIVED_VERSION: &str = "atlas/0.2.0";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -12,7 +12,9 @@ pub enum AtlasErrorCode {
     BudgetSessionIdTooLong,
     BudgetMaxChunksTooHigh,
     FtsNotAvailable,
+    EmbeddingsNotAvailable,
     InvalidQuery,
+    InvalidExportFile,
     RepoNotFound,
     SessionNotFound,
     Internal,
@@ -87,3 +89,69 @@ pub struct AtlasBudgets {
     pub get_session_max_chunks: u32,
     pub response_max_chars: u32,
 }
+
+/// Optional narrowing of a search. Values are matched exactly (tool and
+/// model case-insensitively); blank values are ignored.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AtlasSearchFilters {
+    pub tool: Option<String>,
+    pub model: Option<String>,
+    /// Sessions imported at or after this time (`YYYY-MM-DD` or RFC 3339)
+    pub imported_after: Option<String>,
+    /// Sessions imported before this time (`YYYY-MM-DD` or RFC 3339)
+    pub imported_before: Option<String>,
+    /// Chunks containing a message with this role ("user", "assistant",
+    /// "thinking", "plan", "tool_call")
+    pub role: Option<String>,
+}
+
+impl AtlasSearchFilters {
+    /// The set values, trimmed
+    fn values(&self) -> [Option<&str>; 5] {
+        [
+            &self.tool,
+            &self.model,
+            &self.imported_after,
+            &self.imported_before,
+            &self.role,
+        ]
+        .map(|value| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        })
+    }
+
+    /// Longest set value, in chars
+    pub fn max_value_chars(&self) -> usize {
+        self.values()
+            .into_iter()
+            .flatten()
+            .map(|value| value.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// `AND ...` conditions over `atlas_chunks c` and `sessions s`, with the
+    /// values to bind in order
+    pub fn sql_conditions(&self) -> (String, Vec<String>) {
+        const CONDITIONS: [&str; 5] = [
+            " AND s.tool = ? COLLATE NOCASE",
+            " AND s.model = ? COLLATE NOCASE",
+            " AND c.session_imported_at >= ?",
+            " AND c.session_imported_at < ?",
+            " AND (',' || c.role_mask || ',') LIKE ('%,' || ? || ',%')",
+        ];
+        let mut sql = String::new();
+        let mut binds = Vec::new();
+        for (condition, value) in CONDITIONS.into_iter().zip(self.values()) {
+            if let Some(value) = value {
+                sql.push_str(condition);
+                binds.push(value.to_string());
+            }
+        }
+        (sql, binds)
+    }
+}
@@ -0,0 +1,452 @@
+//! AI bill of materials (AI-BOM) export
+//!
+//! Writes a JSON manifest of the AI-generated code regions a release
+//! introduced: every AI-agent, AI-assist, or collaborative line range in the
+//! commits between the previous release and this one, with the tool, model,
+//! and session behind it. Like an SBOM, it gives compliance teams provenance
+//! documentation they can archive next to the release.
+//!
+//! Region line numbers refer to the file as of the region's `commitSha`.
+
+use super::directories::range_commits;
+use super::exclusions::load_exclusion_rules;
+use super::line_attribution::{
+    ensure_line_attributions_for_commit, fetch_line_attributions_for_commit,
+    LineAttributionCommitRow,
+};
+use super::utils::{fetch_repo_root, fetch_session_meta};
+use git2::{DescribeFormatOptions, DescribeOptions, Repository};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+pub const AI_BOM_FORMAT: &str = "narrative-ai-bom";
+pub const AI_BOM_SPEC_VERSION: &str = "1.0";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBom {
+    pub bom_format: String,
+    pub spec_version: String,
+    pub metadata: AiBomMetadata,
+    pub summary: AiBomSummary,
+    /// Most lines first
+    pub tools: Vec<AiBomTool>,
+    pub sessions: Vec<AiBomSession>,
+    /// Sorted by path
+    pub files: Vec<AiBomFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomMetadata {
+    pub repository_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository_uri: Option<String>,
+    /// Release tag or rev as requested
+    pub release: String,
+    pub release_sha: String,
+    /// Previous release the range starts after (None: from the root commit)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_release: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_release_sha: Option<String>,
+    pub generated_at: String,
+    pub generator: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomSummary {
+    /// Commits in the release range
+    pub commits: u32,
+    /// Commits with at least one AI region
+    pub ai_commits: u32,
+    pub ai_lines: u32,
+    pub files: u32,
+    pub regions: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomTool {
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub lines: u32,
+    pub commits: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomSession {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<String>,
+    /// Commits the session contributed regions to
+    pub commits: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomFile {
+    pub path: String,
+    pub ai_lines: u32,
+    pub regions: Vec<AiBomRegion>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomRegion {
+    pub commit_sha: String,
+    pub start_line: i32,
+    pub end_line: i32,
+    pub author_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub source: String,
+    pub confidence: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiBomExport {
+    pub path: String,
+    pub release_sha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_release: Option<String>,
+    pub commits: u32,
+    pub regions: u32,
+}
+
+/// Build the manifest from each range commit's line attributions (commits
+/// newest first; human ranges are left out)
+pub fn assemble_ai_bom(
+    metadata: AiBomMetadata,
+    commits: &[(String, Vec<LineAttributionCommitRow>)],
+) -> AiBom {
+    let mut summary = AiBomSummary {
+        commits: commits.len() as u32,
+        ..Default::default()
+    };
+    let mut files: BTreeMap<String, AiBomFile> = BTreeMap::new();
+    let mut tools: BTreeMap<(String, Option<String>), (u32, BTreeSet<&str>)> = BTreeMap::new();
+    let mut sessions: BTreeMap<String, AiBomSession> = BTreeMap::new();
+
+    for (sha, rows) in commits {
+        let mut has_ai = false;
+        for row in rows.iter().filter(|row| row.author_type != "human") {
+            let start = row.start_line.max(1);
+            let end = row.end_line.max(start);
+            let lines = (end - start + 1) as u32;
+            has_ai = true;
+            summary.ai_lines += lines;
+            summary.regions += 1;
+
+            if let Some(tool) = &row.tool {
+                let entry = tools.entry((tool.clone(), row.model.clone())).or_default();
+                entry.0 += lines;
+                entry.1.insert(sha.as_str());
+            }
+            if let Some(session_id) = &row.session_id {
+                let session = sessions
+                    .entry(session_id.clone())
+                    .or_insert_with(|| AiBomSession {
+                        id: session_id.clone(),
+                        tool: row.tool.clone(),
+                        model: row.model.clone(),
+                        conversation_id: None,
+                        commits: Vec::new(),
+                    });
+                if !session.commits.contains(sha) {
+                    session.commits.push(sha.clone());
+                }
+            }
+
+            let file = files
+                .entry(row.file_path.clone())
+                .or_insert_with(|| AiBomFile {
+                    path: row.file_path.clone(),
+                    ai_lines: 0,
+                    regions: Vec::new(),
+                });
+            file.ai_lines += lines;
+            file.regions.push(AiBomRegion {
+                commit_sha: sha.clone(),
+                start_line: start,
+                end_line: end,
+                author_type: row.author_type.clone(),
+                tool: row.tool.clone(),
+                model: row.model.clone(),
+                session_id: row.session_id.clone(),
+                source: row.source.clone(),
+                confidence: row.confidence,
+            });
+        }
+        if has_ai {
+            summary.ai_commits += 1;
+        }
+    }
+    summary.files = files.len() as u32;
+
+    let mut tools = tools
+        .into_iter()
+        .map(|((tool, model), (lines, shas))| AiBomTool {
+            tool,
+            model,
+            lines,
+            commits: shas.len() as u32,
+        })
+        .collect::<Vec<_>>();
+    tools.sort_by(|a, b| b.lines.cmp(&a.lines));
+
+    AiBom {
+        bom_format: AI_BOM_FORMAT.to_string(),
+        spec_version: AI_BOM_SPEC_VERSION.to_string(),
+        metadata,
+        summary,
+        tools,
+        sessions: sessions.into_values().collect(),
+        files: files.into_values().collect(),
+    }
+}
+
+/// Nearest tag reachable from the release's first parent
+fn previous_tag(repo: &Repository, release: &str) -> Option<String> {
+    let parent = repo
+        .revparse_single(release)
+        .and_then(|object| object.peel_to_commit())
+        .and_then(|commit| commit.parent(0))
+        .ok()?;
+    let mut options = DescribeOptions::new();
+    options.describe_tags();
+    let description = parent.as_object().describe(&options).ok()?;
+    let mut format = DescribeFormatOptions::new();
+    format.abbreviated_size(0);
+    description.format(Some(&format)).ok()
+}
+
+/// Build the AI-BOM for `release` (a tag or any rev). The range starts after
+/// `previous_release`, or after the nearest earlier tag when none is given.
+pub async fn build_ai_bom(
+    db: &SqlitePool,
+    repo_id: i64,
+    release: &str,
+    previous_release: Option<&str>,
+) -> Result<AiBom, String> {
+    let release = release.trim();
+    if release.is_empty() {
+        return Err("Release is empty".to_string());
+    }
+
+    let repo_path = fetch_repo_root(db, repo_id).await?;
+    let (previous_release, previous_sha, release_sha, shas, repository_uri) = {
+        let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+        let previous_release = previous_release
+            .map(str::to_string)
+            .or_else(|| previous_tag(&repo, release));
+        let (previous_sha, release_sha, shas) =
+            range_commits(&repo, previous_release.as_deref(), release)?;
+        let repository_uri = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string));
+        (
+            previous_release,
+            previous_sha,
+            release_sha,
+            shas,
+            repository_uri,
+        )
+    };
+
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    let mut commits = Vec::with_capacity(shas.len());
+    for sha in shas {
+        let _ = ensure_line_attributions_for_commit(db, repo_id, &sha).await;
+        let rows = fetch_line_attributions_for_commit(db, repo_id, &sha)
+            .await?
+            .into_iter()
+            .filter(|row| !exclusions.is_excluded(&row.file_path))
+            .collect();
+        commits.push((sha, rows));
+    }
+
+    let metadata = AiBomMetadata {
+        repository_path: repo_path,
+        repository_uri,
+        release: release.to_string(),
+        release_sha,
+        previous_release,
+        previous_release_sha: previous_sha,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        generator: format!("Narrative {}", env!("CARGO_PKG_VERSION")),
+    };
+    let mut bom = assemble_ai_bom(metadata, &commits);
+
+    // Fill in what the session store knows that the line ranges did not
+    for session in &mut bom.sessions {
+        if let Ok(Some(meta)) = fetch_session_meta(db, &session.id).await {
+            session.conversation_id = meta.conversation_id;
+            session.tool = session.tool.take().or(meta.tool);
+            session.model = session.model.take().or(meta.model);
+        }
+    }
+
+    Ok(bom)
+}
+
+/// Build the AI-BOM for `release` and write it as JSON to `output_path`
+pub async fn export_ai_bom(
+    db: &SqlitePool,
+    repo_id: i64,
+    release: &str,
+    previous_release: Option<&str>,
+    output_path: &str,
+) -> Result<AiBomExport, String> {
+    let output_path = output_path.trim();
+    if output_path.is_empty() {
+        return Err("Output path is empty".to_string());
+    }
+
+    let bom = build_ai_bom(db, repo_id, release, previous_release).await?;
+    let contents = serde_json::to_string_pretty(&bom).map_err(|e| e.to_string())?;
+
+    let path = Path::new(output_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("write failed: {e}"))?;
+
+    Ok(AiBomExport {
+        path: output_path.to_string(),
+        release_sha: bom.metadata.release_sha,
+        previous_release: bom.metadata.previous_release,
+        commits: bom.summary.ai_commits,
+        regions: bom.summary.regions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn row(
+        path: &str,
+        author_type: &str,
+        start: i32,
+        end: i32,
+        session: &str,
+    ) -> LineAttributionCommitRow {
+        LineAttributionCommitRow {
+            file_path: path.to_string(),
+            start_line: start,
+            end_line: end,
+            session_id: Some(session.to_string()),
+            author_type: author_type.to_string(),
+            ai_percentage: Some(100),
+            tool: Some("claude_code".to_string()),
+            model: Some("opus".to_string()),
+            source: "tool_diff".to_string(),
+            confidence: 0.9,
+        }
+    }
+
+    fn metadata() -> AiBomMetadata {
+        AiBomMetadata {
+            repository_path: "/tmp/repo".to_string(),
+            repository_uri: None,
+            release: "v1.4.0".to_string(),
+            release_sha: "bbbb".to_string(),
+            previous_release: Some("v1.3.0".to_string()),
+            previous_release_sha: Some("aaaa".to_string()),
+            generated_at: "2026-03-01T00:00:00+00:00".to_string(),
+            generator: "Narrative test".to_string(),
+        }
+    }
+
+    #[test]
+    fn regions_are_grouped_by_file_with_session_references() {
+        let commits = vec![
+            (
+                "c2".to_string(),
+                vec![
+                    row("src/lib.rs", "ai_agent", 10, 14, "s1"),
+                    row("src/lib.rs", "human", 15, 30, "s1"),
+                ],
+            ),
+            (
+                "c1".to_string(),
+                vec![
+                    row("src/lib.rs", "mixed", 1, 2, "s1"),
+                    row("src/main.rs", "ai_tab", 3, 3, "s2"),
+                ],
+            ),
+            ("c0".to_string(), Vec::new()),
+        ];
+
+        let bom = assemble_ai_bom(metadata(), &commits);
+        assert_eq!(
+            bom.summary,
+            AiBomSummary {
+                commits: 3,
+                ai_commits: 2,
+                ai_lines: 8,
+                files: 2,
+                regions: 3,
+            }
+        );
+        assert_eq!(bom.files[0].path, "src/lib.rs");
+        assert_eq!(bom.files[0].ai_lines, 7);
+        assert_eq!(bom.files[0].regions.len(), 2);
+        assert_eq!(bom.tools[0].commits, 2);
+        assert_eq!(bom.sessions.len(), 2);
+        assert_eq!(bom.sessions[0].commits, vec!["c2", "c1"]);
+
+        let json = serde_json::to_value(&bom).unwrap();
+        assert_eq!(json["bomFormat"], "narrative-ai-bom");
+        assert_eq!(json["metadata"]["previousRelease"], "v1.3.0");
+        assert_eq!(json["files"][1]["regions"][0]["authorType"], "ai_tab");
+    }
+
+    #[test]
+    fn previous_release_is_the_nearest_earlier_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+
+        let mut parent: Option<git2::Commit> = None;
+        for (message, tag) in [
+            ("one", Some("v1.0.0")),
+            ("two", None),
+            ("three", Some("v1.1.0")),
+        ] {
+            let parents = parent.iter().collect::<Vec<_>>();
+            let oid = repo
+                .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                .unwrap();
+            let commit = repo.find_commit(oid).unwrap();
+            if let Some(tag) = tag {
+                repo.tag_lightweight(tag, commit.as_object(), false)
+                    .unwrap();
+            }
+            parent = Some(commit);
+        }
+
+        assert_eq!(previous_tag(&repo, "v1.1.0").as_deref(), Some("v1.0.0"));
+        assert_eq!(previous_tag(&repo, "v1.0.0"), None);
+    }
+}
@@ -0,0 +1,174 @@
+//! Per-author attribution breakdown
+//!
+//! Groups commit contribution stats (derived from line attributions) by
+//! commit author, so each contributor's committed code can be split into
+//! AI-agent, AI-assist, collaborative, and human lines.
+
+use super::session_stats::store_contribution_stats;
+use super::stats::compute_contribution_from_attributions;
+use sqlx::SqlitePool;
+
+/// Most commits whose stats are computed on the fly per request
+const MAX_UNCACHED_COMMITS: i64 = 500;
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorAttribution {
+    pub author: String,
+    pub commits: i64,
+    pub total_lines: i64,
+    pub human_lines: i64,
+    pub ai_agent_lines: i64,
+    pub ai_assist_lines: i64,
+    pub collaborative_lines: i64,
+    #[sqlx(skip)]
+    pub ai_percentage: f64,
+}
+
+/// Per-author stats for commits authored between `from` and `to`
+/// (inclusive `YYYY-MM-DD`; None is unbounded), most lines first.
+///
+/// Commits with line attributions but no cached stats are computed first.
+pub async fn compute_author_breakdown(
+    db: &SqlitePool,
+    repo_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<AuthorAttribution>, String> {
+    let uncached: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT la.commit_sha
+        FROM line_attributions la
+        JOIN commits c ON c.repo_id = la.repo_id AND c.sha = la.commit_sha
+        LEFT JOIN commit_contribution_stats s
+          ON s.repo_id = la.repo_id AND s.commit_sha = la.commit_sha
+        WHERE la.repo_id = ?
+          AND s.commit_sha IS NULL
+          AND (? IS NULL OR date(c.authored_at) >= ?)
+          AND (? IS NULL OR date(c.authored_at) <= ?)
+        LIMIT ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .bind(MAX_UNCACHED_COMMITS)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    for commit_sha in uncached {
+        if let Ok(Some(stats)) =
+            compute_contribution_from_attributions(db, repo_id, &commit_sha).await
+        {
+            let _ = store_contribution_stats(db, repo_id, &commit_sha, None, &stats).await;
+        }
+    }
+
+    let mut authors = sqlx::query_as::<_, AuthorAttribution>(
+        r#"
+        SELECT
+            COALESCE(NULLIF(TRIM(c.author), ''), 'unknown') AS author,
+            COUNT(*) AS commits,
+            COALESCE(SUM(s.total_lines), 0) AS total_lines,
+            COALESCE(SUM(s.human_lines), 0) AS human_lines,
+            COALESCE(SUM(s.ai_agent_lines), 0) AS ai_agent_lines,
+            COALESCE(SUM(s.ai_assist_lines), 0) AS ai_assist_lines,
+            COALESCE(SUM(s.collaborative_lines), 0) AS collaborative_lines
+        FROM commit_contribution_stats s
+        JOIN commits c ON c.repo_id = s.repo_id AND c.sha = s.commit_sha
+        WHERE s.repo_id = ?
+          AND (? IS NULL OR date(c.authored_at) >= ?)
+          AND (? IS NULL OR date(c.authored_at) <= ?)
+        GROUP BY 1
+        ORDER BY total_lines DESC, author
+        "#,
+    )
+    .bind(repo_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    for author in &mut authors {
+        let ai_lines = author.ai_agent_lines + author.ai_assist_lines + author.collaborative_lines;
+        if author.total_lines > 0 {
+            author.ai_percentage = ai_lines as f64 / author.total_lines as f64 * 100.0;
+        }
+    }
+
+    Ok(authors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn authors_are_aggregated_within_the_date_range() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            for (sha, author, authored_at, ai, human) in [
+                ("a", "Ada", "2026-02-01T10:00:00Z", 30, 10),
+                ("b", "Ada", "2026-02-03T10:00:00Z", 0, 20),
+                ("c", "Lin", "2026-02-02T10:00:00Z", 5, 5),
+                ("d", "Lin", "2026-01-15T10:00:00Z", 100, 0),
+            ] {
+                sqlx::query(
+                    "INSERT INTO commits (repo_id, sha, author, authored_at) VALUES (1, ?, ?, ?)",
+                )
+                .bind(sha)
+                .bind(author)
+                .bind(authored_at)
+                .execute(&pool)
+                .await
+                .expect("insert commit");
+                sqlx::query(
+                    r#"
+                    INSERT INTO commit_contribution_stats
+                        (repo_id, commit_sha, ai_agent_lines, human_lines, total_lines)
+                    VALUES (1, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(sha)
+                .bind(ai)
+                .bind(human)
+                .bind(ai + human)
+                .execute(&pool)
+                .await
+                .expect("insert stats");
+            }
+
+            let authors = compute_author_breakdown(&pool, 1, Some("2026-02-01"), None)
+                .await
+                .unwrap();
+            let summary = authors
+                .iter()
+                .map(|a| {
+                    (
+                        a.author.as_str(),
+                        a.commits,
+                        a.total_lines,
+                        a.ai_agent_lines,
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(summary, vec![("Ada", 2, 60, 30), ("Lin", 1, 10, 5)]);
+            assert!((authors[0].ai_percentage - 50.0).abs() < 1e-9);
+        });
+    }
+}
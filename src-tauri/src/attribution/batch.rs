@@ -0,0 +1,412 @@
+//! Background attribution of many commits
+//!
+//! `start_attribution_batch` queues commits in `attribution_batch_queue` and
+//! attributes them on a background task, emitting `attribution-batch-progress`
+//! after each commit. A commit leaves the queue once it has been handled, so a
+//! cancelled or interrupted batch resumes where it stopped the next time a
+//! batch is started for the repo.
+
+use super::deletions::ensure_commit_deletions;
+use super::line_attribution::ensure_line_attributions_for_commit;
+use super::models::ContributionStats;
+use super::session_stats::{compute_session_contribution, store_contribution_stats};
+use super::stats::{
+    commit_is_merge, compute_contribution_from_attributions, fetch_cached_stats,
+    fetch_commit_files, fetch_linked_session,
+};
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::Emitter;
+
+/// Cancellation flags for running batches, keyed by repo id
+static ACTIVE_BATCHES: Mutex<BTreeMap<i64, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+/// Progress of a batch, emitted as `attribution-batch-progress` after each commit
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionBatchProgress {
+    pub repo_id: i64,
+    pub commit_sha: Option<String>,
+    /// Commits handled so far in this run
+    pub completed: usize,
+    pub total: usize,
+    /// Commits whose stats were newly computed
+    pub computed: usize,
+    /// "running" | "completed" | "cancelled"
+    pub status: String,
+}
+
+/// Share of the repo's indexed commits that have cached stats
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoAttributionCoverage {
+    pub total_commits: i64,
+    pub attributed_commits: i64,
+    pub coverage_percent: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionBatchStatus {
+    pub repo_id: i64,
+    pub running: bool,
+    /// Commits still queued (left by a cancelled or interrupted batch when not running)
+    pub pending: i64,
+    pub coverage: RepoAttributionCoverage,
+}
+
+/// Attribute one commit and cache its stats; true if new stats were stored
+///
+/// Commits that already have cached stats are left alone.
+pub async fn compute_commit_stats(db: &SqlitePool, repo_id: i64, commit_sha: &str) -> bool {
+    let _ = ensure_line_attributions_for_commit(db, repo_id, commit_sha).await;
+    let _ = ensure_commit_deletions(db, repo_id, commit_sha).await;
+
+    if fetch_cached_stats(db, repo_id, commit_sha).await.is_some() {
+        return false;
+    }
+
+    if let Ok(Some(stats)) = compute_contribution_from_attributions(db, repo_id, commit_sha).await {
+        return store_contribution_stats(db, repo_id, commit_sha, None, &stats)
+            .await
+            .is_ok();
+    }
+
+    if commit_is_merge(db, repo_id, commit_sha).await {
+        let stats = ContributionStats::merge_only();
+        return store_contribution_stats(db, repo_id, commit_sha, None, &stats)
+            .await
+            .is_ok();
+    }
+
+    // Fall back to the linked session's estimate
+    let session = match fetch_linked_session(db, repo_id, commit_sha).await {
+        Ok(s) => s,
+        Err(_) => return false,
+    };
+    let commit_files: Vec<String> = fetch_commit_files(db, repo_id, commit_sha)
+        .await
+        .unwrap_or_default();
+    let stats = compute_session_contribution(&session, &commit_files);
+
+    store_contribution_stats(db, repo_id, commit_sha, Some(session.id.as_str()), &stats)
+        .await
+        .is_ok()
+}
+
+/// Add commits to the repo's queue (already queued commits are kept once)
+pub async fn enqueue_commits(
+    db: &SqlitePool,
+    repo_id: i64,
+    commit_shas: &[String],
+) -> Result<(), String> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for sha in commit_shas {
+        sqlx::query(
+            r#"
+            INSERT INTO attribution_batch_queue (repo_id, commit_sha)
+            VALUES (?, ?)
+            ON CONFLICT(repo_id, commit_sha) DO NOTHING
+            "#,
+        )
+        .bind(repo_id)
+        .bind(sha)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Queued commits in the order they were queued
+pub async fn pending_commits(db: &SqlitePool, repo_id: i64) -> Result<Vec<String>, String> {
+    sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT commit_sha FROM attribution_batch_queue
+        WHERE repo_id = ?
+        ORDER BY queued_at, rowid
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+async fn dequeue_commit(db: &SqlitePool, repo_id: i64, commit_sha: &str) -> Result<(), String> {
+    sqlx::query("DELETE FROM attribution_batch_queue WHERE repo_id = ? AND commit_sha = ?")
+        .bind(repo_id)
+        .bind(commit_sha)
+        .execute(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Attribute queued commits until the queue is empty or `cancel` is set
+///
+/// Commits queued while the batch runs are picked up before it finishes.
+/// Returns the final progress ("completed" or "cancelled").
+pub async fn run_batch(
+    db: &SqlitePool,
+    repo_id: i64,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(AttributionBatchProgress),
+) -> Result<AttributionBatchProgress, String> {
+    let mut progress = AttributionBatchProgress {
+        repo_id,
+        commit_sha: None,
+        completed: 0,
+        total: 0,
+        computed: 0,
+        status: "running".to_string(),
+    };
+
+    loop {
+        let pending = pending_commits(db, repo_id).await?;
+        if pending.is_empty() {
+            break;
+        }
+        progress.total = progress.completed + pending.len();
+
+        for sha in pending {
+            if cancel.load(Ordering::SeqCst) {
+                progress.commit_sha = None;
+                progress.status = "cancelled".to_string();
+                return Ok(progress);
+            }
+
+            if compute_commit_stats(db, repo_id, &sha).await {
+                progress.computed += 1;
+            }
+            dequeue_commit(db, repo_id, &sha).await?;
+
+            progress.completed += 1;
+            progress.commit_sha = Some(sha);
+            on_progress(progress.clone());
+        }
+    }
+
+    progress.commit_sha = None;
+    progress.status = "completed".to_string();
+    Ok(progress)
+}
+
+/// Queue `commit_shas` and attribute the queue on a background task
+///
+/// If a batch is already running for the repo, the commits join its queue and
+/// false is returned. With no commits, restarts whatever a cancelled or
+/// interrupted batch left queued.
+pub async fn start_batch(
+    app_handle: tauri::AppHandle,
+    db: Arc<SqlitePool>,
+    repo_id: i64,
+    commit_shas: &[String],
+) -> Result<bool, String> {
+    enqueue_commits(&db, repo_id, commit_shas).await?;
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = ACTIVE_BATCHES.lock().map_err(|e| e.to_string())?;
+        if active.contains_key(&repo_id) {
+            return Ok(false);
+        }
+        active.insert(repo_id, cancel.clone());
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let emit = |progress: AttributionBatchProgress| {
+            if let Err(e) = app_handle.emit("attribution-batch-progress", progress) {
+                eprintln!("Failed to emit attribution-batch-progress: {}", e);
+            }
+        };
+
+        match run_batch(&db, repo_id, &cancel, emit).await {
+            Ok(done) => emit(done),
+            Err(e) => eprintln!("Attribution batch for repo {} failed: {}", repo_id, e),
+        }
+
+        if let Ok(mut active) = ACTIVE_BATCHES.lock() {
+            active.remove(&repo_id);
+        }
+    });
+
+    Ok(true)
+}
+
+/// Request cancellation of the repo's running batch
+///
+/// The commit being attributed finishes; the rest stay queued. Returns false
+/// if no batch is running for the repo.
+pub fn cancel_batch(repo_id: i64) -> Result<bool, String> {
+    let active = ACTIVE_BATCHES.lock().map_err(|e| e.to_string())?;
+    match active.get(&repo_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Indexed commits with cached stats, as a share of all indexed commits
+pub async fn repo_attribution_coverage(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<RepoAttributionCoverage, String> {
+    let (total_commits, attributed_commits) = sqlx::query_as::<_, (i64, i64)>(
+        r#"
+        SELECT
+            COUNT(*),
+            COUNT(DISTINCT s.commit_sha)
+        FROM commits c
+        LEFT JOIN commit_contribution_stats s
+            ON s.repo_id = c.repo_id AND s.commit_sha = c.sha
+        WHERE c.repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let coverage_percent = if total_commits == 0 {
+        0.0
+    } else {
+        (attributed_commits as f64 / total_commits as f64 * 1000.0).round() / 10.0
+    };
+
+    Ok(RepoAttributionCoverage {
+        total_commits,
+        attributed_commits,
+        coverage_percent,
+    })
+}
+
+pub async fn batch_status(db: &SqlitePool, repo_id: i64) -> Result<AttributionBatchStatus, String> {
+    let running = ACTIVE_BATCHES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .contains_key(&repo_id);
+    let pending: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM attribution_batch_queue WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_one(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(AttributionBatchStatus {
+        repo_id,
+        running,
+        pending,
+        coverage: repo_attribution_coverage(db, repo_id).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> SqlitePool {
+        let pool = crate::test_pool().await;
+
+        sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/nonexistent/repo')")
+            .execute(&pool)
+            .await
+            .expect("repo");
+        for sha in ["a1", "b2", "c3", "d4"] {
+            sqlx::query("INSERT INTO commits (repo_id, sha) VALUES (1, ?)")
+                .bind(sha)
+                .execute(&pool)
+                .await
+                .expect("commit");
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO commit_contribution_stats
+                (repo_id, commit_sha, human_lines, total_lines)
+            VALUES (1, 'a1', 10, 10)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .expect("stats");
+
+        pool
+    }
+
+    fn shas(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn batch_drains_queue_and_reports_progress() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = setup().await;
+            enqueue_commits(&pool, 1, &shas(&["a1", "b2", "c3"]))
+                .await
+                .expect("enqueue");
+            // Re-queuing a commit keeps a single entry
+            enqueue_commits(&pool, 1, &shas(&["b2"]))
+                .await
+                .expect("enqueue");
+
+            let mut seen = Vec::new();
+            let cancel = AtomicBool::new(false);
+            let done = run_batch(&pool, 1, &cancel, |p| seen.push(p.completed))
+                .await
+                .expect("run");
+
+            assert_eq!(done.status, "completed");
+            assert_eq!(done.total, 3);
+            assert_eq!(seen, vec![1, 2, 3]);
+            assert!(pending_commits(&pool, 1).await.expect("pending").is_empty());
+
+            let coverage = repo_attribution_coverage(&pool, 1).await.expect("coverage");
+            assert_eq!(coverage.total_commits, 4);
+            assert_eq!(coverage.attributed_commits, 1);
+            assert_eq!(coverage.coverage_percent, 25.0);
+        });
+    }
+
+    #[test]
+    fn cancelled_batch_leaves_commits_queued() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = setup().await;
+            enqueue_commits(&pool, 1, &shas(&["b2", "c3"]))
+                .await
+                .expect("enqueue");
+
+            let cancel = AtomicBool::new(true);
+            let done = run_batch(&pool, 1, &cancel, |_| {}).await.expect("run");
+            assert_eq!(done.status, "cancelled");
+            assert_eq!(done.completed, 0);
+            assert_eq!(
+                pending_commits(&pool, 1).await.expect("pending"),
+                shas(&["b2", "c3"])
+            );
+
+            let status = batch_status(&pool, 1).await.expect("status");
+            assert!(!status.running);
+            assert_eq!(status.pending, 2);
+        });
+    }
+}
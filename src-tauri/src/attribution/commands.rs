@@ -5,6 +5,11 @@
 //! over the actual implementation logic.
 
 use super::coverage::compute_attribution_coverage;
+use super::deletions::{ensure_commit_deletions, CommitDeletionStats};
+use super::exclusions::{fetch_exclusion_patterns, normalize_exclusions, store_exclusion_patterns};
+use super::languages::{
+    fetch_language_overrides, normalize_language_overrides, store_language_overrides,
+};
 use super::models::{AttributionNoteSummary, ContributionStats};
 use super::note_meta::fetch_attribution_note_meta;
 use super::notes_io::{
@@ -13,9 +18,11 @@ use super::notes_io::{
 use super::prefs::{fetch_or_create_prefs, update_prefs, AttributionPrefs, AttributionPrefsUpdate};
 use super::session_stats::compute_human_contribution;
 use super::stats::{
-    compute_contribution_from_attributions, fetch_cached_stats, fetch_linked_session,
+    commit_is_merge, compute_contribution_from_attributions, fetch_cached_stats,
+    fetch_linked_session,
 };
 use crate::DbState;
+use std::collections::BTreeMap;
 use tauri::State;
 
 #[derive(Debug, serde::Serialize)]
@@ -53,6 +60,16 @@ pub async fn get_commit_contribution_stats(
         return Ok(stats);
     }
 
+    // Merges without line attributions have no changes of their own to
+    // estimate from a session
+    if commit_is_merge(&db.0, repo_id, &commit_sha).await {
+        let stats = ContributionStats::merge_only();
+        if let Err(e) = store_contribution_stats(&db.0, repo_id, &commit_sha, None, &stats).await {
+            eprintln!("Failed to cache stats: {}", e);
+        }
+        return Ok(stats);
+    }
+
     // Get linked session for this commit
     let session = match fetch_linked_session(&db.0, repo_id, &commit_sha).await {
         Ok(s) => s,
@@ -101,6 +118,43 @@ pub async fn get_file_source_lens(
     .await
 }
 
+/// Get attribution for a file as it exists at HEAD
+///
+/// Unlike `get_file_source_lens`, lines are attributed through blame to
+/// whichever commit last changed them.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_file_current_attribution(
+    db: State<'_, DbState>,
+    request: super::models::CurrentFileAttributionRequest,
+) -> Result<super::models::CurrentFileAttribution, String> {
+    super::source_lens::get_file_current_attribution(
+        &db.0,
+        request.repo_id,
+        &request.file_path,
+        request.offset,
+        request.limit,
+    )
+    .await
+}
+
+/// Get symbol-level attribution for a file
+///
+/// Returns each function/class in the file with the share of its lines
+/// written by agents vs humans. Empty for languages without a grammar.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_file_symbol_attribution(
+    db: State<'_, DbState>,
+    request: super::symbols::SymbolAttributionRequest,
+) -> Result<Vec<super::symbols::SymbolAttribution>, String> {
+    super::symbols::get_file_symbol_attribution(
+        &db.0,
+        request.repo_id,
+        &request.commit_sha,
+        &request.file_path,
+    )
+    .await
+}
+
 /// Import a single attribution note from git notes into local storage
 #[tauri::command(rename_all = "camelCase")]
 pub async fn import_attribution_note(
@@ -186,6 +240,59 @@ pub async fn set_attribution_prefs(
     update_prefs(&db.0, repo_id, update).await
 }
 
+/// Get a repo's attribution exclusion globs (e.g. `package-lock.json`).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_attribution_exclusions(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<Vec<String>, String> {
+    fetch_exclusion_patterns(&db.0, repo_id).await
+}
+
+/// Replace a repo's attribution exclusion globs. An empty list excludes
+/// nothing. Cached contribution stats are dropped and recomputed on demand.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_attribution_exclusions(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, String> {
+    store_exclusion_patterns(&db.0, repo_id, &normalize_exclusions(patterns)).await?;
+    fetch_exclusion_patterns(&db.0, repo_id).await
+}
+
+/// Get a repo's language overrides (extension or file name -> language).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_attribution_language_overrides(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<BTreeMap<String, String>, String> {
+    fetch_language_overrides(&db.0, repo_id).await
+}
+
+/// Replace a repo's language overrides; an empty map uses the built-in
+/// extension mapping only.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_attribution_language_overrides(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    overrides: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, String> {
+    store_language_overrides(&db.0, repo_id, &normalize_language_overrides(overrides)).await?;
+    fetch_language_overrides(&db.0, repo_id).await
+}
+
+/// Get a commit's deleted-line stats: how many removed lines were written
+/// by AI or humans, and which session removed them. Computed on first use.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_commit_deletion_stats(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    commit_sha: String,
+) -> Result<CommitDeletionStats, String> {
+    ensure_commit_deletions(&db.0, repo_id, &commit_sha).await
+}
+
 #[tauri::command(rename_all = "camelCase")]
 pub async fn purge_attribution_prompt_meta(
     db: State<'_, DbState>,
@@ -218,66 +325,217 @@ pub async fn purge_attribution_prompt_meta(
     })
 }
 
+/// Get weekly or monthly human vs AI line totals for a repo
+///
+/// Served from a cache that is refreshed incrementally from commit stats.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_attribution_timeseries(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    granularity: super::timeseries::TimeseriesGranularity,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<super::timeseries::AttributionTimeseries, String> {
+    super::timeseries::get_attribution_timeseries(
+        &db.0,
+        repo_id,
+        granularity,
+        since.as_deref(),
+        until.as_deref(),
+    )
+    .await
+}
+
+/// Get attribution rolled up by directory prefix for a commit range
+///
+/// `from` is exclusive and `to` defaults to HEAD. Results are cached per
+/// range and depth until the repo's line attributions change.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_directory_attribution(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    from: Option<String>,
+    to: Option<String>,
+    depth: Option<u32>,
+) -> Result<super::directories::DirectoryRollup, String> {
+    super::directories::get_directory_rollup(
+        &db.0,
+        repo_id,
+        from.as_deref(),
+        to.as_deref(),
+        depth.unwrap_or(2),
+    )
+    .await
+}
+
+/// Export an attribution report (totals, per-commit table, per-tool
+/// breakdown) for the repo's history or the commit range `from..to`
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_attribution_report(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    format: super::report::ReportFormat,
+    output_path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<super::report::AttributionReportExport, String> {
+    super::report::export_attribution_report(
+        &db.0,
+        repo_id,
+        format,
+        from.as_deref(),
+        to.as_deref(),
+        &output_path,
+    )
+    .await
+}
+
+/// Export AI-attributed line ranges as a SARIF file for code-scanning and
+/// review tools
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_attribution_sarif(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    output_path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<super::sarif::SarifExport, String> {
+    super::sarif::export_attribution_sarif(
+        &db.0,
+        repo_id,
+        from.as_deref(),
+        to.as_deref(),
+        &output_path,
+    )
+    .await
+}
+
+/// Write an AI bill of materials for a release: the AI-generated regions
+/// (tool, model, session, line ranges) of the commits since the previous
+/// release (`previousRelease`, or the nearest earlier tag)
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_ai_bom(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    release: String,
+    previous_release: Option<String>,
+    output_path: String,
+) -> Result<super::aibom::AiBomExport, String> {
+    super::aibom::export_ai_bom(
+        &db.0,
+        repo_id,
+        &release,
+        previous_release.as_deref(),
+        &output_path,
+    )
+    .await
+}
+
+/// Record AI tab-completion acceptances reported by an editor
+///
+/// They are matched to committed lines when the commit is attributed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn record_completion_acceptances(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    acceptances: Vec<super::completions::CompletionAcceptance>,
+) -> Result<super::completions::CompletionCaptureSummary, String> {
+    super::completions::record_completion_acceptances(&db.0, repo_id, &acceptances).await
+}
+
+/// Import a JSONL editor log of tab-completion acceptances
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_completion_log(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    log_path: String,
+) -> Result<super::completions::CompletionCaptureSummary, String> {
+    super::completions::import_completion_log(&db.0, repo_id, &log_path).await
+}
+
+/// Attribute commits on a background task
+///
+/// Emits `attribution-batch-progress` after each commit. Returns false if a
+/// batch was already running for the repo; the commits are added to its queue.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn start_attribution_batch(
+    app_handle: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    commit_shas: Option<Vec<String>>,
+) -> Result<bool, String> {
+    super::batch::start_batch(
+        app_handle,
+        db.0.clone(),
+        repo_id,
+        &commit_shas.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Request cancellation of the repo's background attribution batch
+#[tauri::command(rename_all = "camelCase")]
+pub fn cancel_attribution_batch(repo_id: i64) -> Result<bool, String> {
+    super::batch::cancel_batch(repo_id)
+}
+
+/// Whether a batch is running, how many commits are queued, and repo coverage
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_attribution_batch_status(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<super::batch::AttributionBatchStatus, String> {
+    super::batch::batch_status(&db.0, repo_id).await
+}
+
+/// Get the repo's attribution policy rules
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_attribution_policy(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<super::policy::AttributionPolicy, String> {
+    super::policy::fetch_attribution_policy(&db.0, repo_id).await
+}
+
+/// Replace the repo's attribution policy rules
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_attribution_policy(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    policy: super::policy::AttributionPolicy,
+) -> Result<(), String> {
+    super::policy::store_attribution_policy(&db.0, repo_id, &policy).await
+}
+
+/// Check the commits in `from..to` against the repo's policy (or `policy`
+/// when given) and return pass/fail with violations
+#[tauri::command(rename_all = "camelCase")]
+pub async fn check_attribution_policy(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    from: Option<String>,
+    to: Option<String>,
+    policy: Option<super::policy::AttributionPolicy>,
+) -> Result<super::policy::PolicyCheckResult, String> {
+    super::policy::check_attribution_policy(&db.0, repo_id, from.as_deref(), to.as_deref(), policy)
+        .await
+}
+
 /// Compute and cache stats for a batch of commits
 ///
-/// Useful for pre-computing stats after importing many sessions.
+/// Useful for pre-computing stats after importing many sessions. Runs inline;
+/// use `start_attribution_batch` for large ranges.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn compute_stats_batch(
     db: State<'_, DbState>,
     repo_id: i64,
     commit_shas: Vec<String>,
 ) -> Result<usize, String> {
-    use super::line_attribution::ensure_line_attributions_for_commit;
-    use super::session_stats::{compute_session_contribution, store_contribution_stats};
-    use super::stats::fetch_commit_files;
-
     let mut computed = 0;
-
     for commit_sha in commit_shas {
-        let _ = ensure_line_attributions_for_commit(&db.0, repo_id, &commit_sha).await;
-
-        // Check if already cached
-        if fetch_cached_stats(&db.0, repo_id, &commit_sha)
-            .await
-            .is_some()
-        {
-            continue;
-        }
-
-        if let Ok(Some(stats)) =
-            compute_contribution_from_attributions(&db.0, repo_id, &commit_sha).await
-        {
-            if store_contribution_stats(&db.0, repo_id, &commit_sha, None, &stats)
-                .await
-                .is_ok()
-            {
-                computed += 1;
-            }
-            continue;
-        }
-
-        // Try to get linked session
-        let session = match fetch_linked_session(&db.0, repo_id, &commit_sha).await {
-            Ok(s) => s,
-            Err(_) => continue,
-        };
-
-        // Get commit files
-        let commit_files: Vec<String> = fetch_commit_files(&db.0, repo_id, &commit_sha)
-            .await
-            .unwrap_or_default();
-
-        // Compute and store
-        let stats = compute_session_contribution(&session, &commit_files);
-        let session_id = session.id.clone();
-
-        if store_contribution_stats(&db.0, repo_id, &commit_sha, Some(&session_id), &stats)
-            .await
-            .is_ok()
-        {
+        if super::batch::compute_commit_stats(&db.0, repo_id, &commit_sha).await {
             computed += 1;
         }
     }
-
     Ok(computed)
 }
@@ -0,0 +1,526 @@
+//! AI tab-completion (`ai_tab`) capture
+//!
+//! Completion acceptances (Copilot/Cursor tab accepts) arrive from the OTLP
+//! receiver or from editor logs while the code is still in the working tree.
+//! They are stored as pending acceptances and matched by content to the lines
+//! a commit changed when that commit is attributed; matched lines become
+//! `ai_tab` line attributions.
+//!
+//! Lines already attributed to an agent session keep that attribution.
+
+use super::exclusions::load_exclusion_rules;
+use super::git_utils::{collect_changed_ranges, commit_renames};
+use super::line_attribution::{fetch_line_attributions, source_confidence, SOURCE_COMPLETION};
+use super::source_lens::load_file_lines;
+use super::utils::fetch_repo_root;
+use git2::{Oid, Repository};
+use sqlx::SqlitePool;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+/// Acceptances this long after the commit time still match it (clock skew)
+const COMMIT_TIME_SLACK_SECS: i64 = 60;
+/// Shortest single-line completion matched inside a longer line
+const MIN_INLINE_MATCH_CHARS: usize = 8;
+
+const EVENT_NAME_KEYS: &[&str] = &["event.name", "event", "name"];
+const TEXT_KEYS: &[&str] = &[
+    "completion.text",
+    "completion.accepted_text",
+    "accepted_text",
+    "suggestion.text",
+];
+const FILE_KEYS: &[&str] = &["code.filepath", "file_path", "file", "path"];
+const START_LINE_KEYS: &[&str] = &["code.lineno", "start_line", "line"];
+const END_LINE_KEYS: &[&str] = &["code.end_lineno", "end_line"];
+const TOOL_KEYS: &[&str] = &["completion.provider", "tool", "service.name"];
+const MODEL_KEYS: &[&str] = &["completion.model", "model", "model_id"];
+const COMMIT_KEYS: &[&str] = &["vcs.commit.sha", "git.commit.sha", "commit_sha"];
+
+/// One accepted completion, as reported by an editor
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionAcceptance {
+    /// Repo-relative or absolute path of the edited file
+    pub file_path: String,
+    pub start_line: Option<i32>,
+    pub end_line: Option<i32>,
+    /// The inserted completion text
+    pub text: String,
+    /// e.g. `copilot`, `cursor`
+    pub tool: String,
+    pub model: Option<String>,
+    /// RFC 3339; defaults to now
+    pub accepted_at: Option<String>,
+    /// Commit the completion belongs to, when the editor knows it
+    pub commit_sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionCaptureSummary {
+    pub recorded: u32,
+    pub skipped: u32,
+}
+
+fn pick<'a>(attrs: &'a HashMap<String, Vec<String>>, keys: &[&str]) -> Option<&'a str> {
+    keys.iter()
+        .filter_map(|key| attrs.get(*key))
+        .flat_map(|values| values.iter())
+        .map(|value| value.as_str())
+        .find(|value| !value.trim().is_empty())
+}
+
+/// True for event names like `copilot.completion.accepted` or `cursor.tab.accept`
+pub fn is_completion_accept_event(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.contains("accept")
+        && ["completion", "tab", "suggestion", "inline"]
+            .iter()
+            .any(|kind| name.contains(kind))
+}
+
+/// Read a completion acceptance from OTLP event attributes, or None if the
+/// event is not one
+pub fn acceptance_from_attributes(
+    attrs: &HashMap<String, Vec<String>>,
+    timestamp_iso: &str,
+) -> Option<CompletionAcceptance> {
+    let name = pick(attrs, EVENT_NAME_KEYS)?;
+    if !is_completion_accept_event(name) {
+        return None;
+    }
+    let line = |keys: &[&str]| pick(attrs, keys).and_then(|value| value.trim().parse().ok());
+
+    Some(CompletionAcceptance {
+        file_path: pick(attrs, FILE_KEYS)?.to_string(),
+        start_line: line(START_LINE_KEYS),
+        end_line: line(END_LINE_KEYS),
+        text: pick(attrs, TEXT_KEYS)?.to_string(),
+        tool: pick(attrs, TOOL_KEYS)
+            .map(|tool| tool.trim().to_ascii_lowercase())
+            .unwrap_or_else(|| "unknown".to_string()),
+        model: pick(attrs, MODEL_KEYS).map(str::to_string),
+        accepted_at: Some(timestamp_iso.to_string()),
+        commit_sha: pick(attrs, COMMIT_KEYS).map(str::to_string),
+    })
+}
+
+fn normalize_time(value: Option<&str>) -> String {
+    value
+        .and_then(|value| chrono::DateTime::parse_from_rfc3339(value.trim()).ok())
+        .map(|time| time.with_timezone(&chrono::Utc))
+        .unwrap_or_else(chrono::Utc::now)
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+fn format_unix(seconds: i64) -> String {
+    chrono::DateTime::from_timestamp(seconds, 0)
+        .unwrap_or_default()
+        .format(TIME_FORMAT)
+        .to_string()
+}
+
+/// Repo-relative `/`-separated path, or None for paths outside the repo
+fn relative_path(repo_root: &str, file_path: &str) -> Option<String> {
+    let path = Path::new(file_path.trim());
+    let relative = if path.is_absolute() {
+        path.strip_prefix(repo_root).ok()?
+    } else {
+        path
+    };
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    let relative = relative.trim_start_matches("./");
+    (!relative.is_empty()).then(|| relative.to_string())
+}
+
+/// Store completion acceptances until their commit is attributed
+pub async fn record_completion_acceptances(
+    db: &SqlitePool,
+    repo_id: i64,
+    acceptances: &[CompletionAcceptance],
+) -> Result<CompletionCaptureSummary, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let mut summary = CompletionCaptureSummary::default();
+
+    for acceptance in acceptances {
+        let file_path = relative_path(&repo_root, &acceptance.file_path);
+        let (Some(file_path), false) = (file_path, acceptance.text.trim().is_empty()) else {
+            summary.skipped += 1;
+            continue;
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO completion_acceptances
+                (repo_id, file_path, start_line, end_line, accepted_text, tool, model,
+                 accepted_at, commit_sha)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(&file_path)
+        .bind(acceptance.start_line)
+        .bind(acceptance.end_line)
+        .bind(&acceptance.text)
+        .bind(acceptance.tool.trim())
+        .bind(&acceptance.model)
+        .bind(normalize_time(acceptance.accepted_at.as_deref()))
+        .bind(&acceptance.commit_sha)
+        .execute(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+        summary.recorded += 1;
+    }
+
+    Ok(summary)
+}
+
+/// Import an editor log of completion acceptances: one JSON
+/// `CompletionAcceptance` per line. Lines that do not parse are skipped.
+pub async fn import_completion_log(
+    db: &SqlitePool,
+    repo_id: i64,
+    log_path: &str,
+) -> Result<CompletionCaptureSummary, String> {
+    let raw = std::fs::read_to_string(log_path).map_err(|e| format!("read failed: {e}"))?;
+    let mut acceptances = Vec::new();
+    let mut unparsed = 0;
+    for line in raw.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<CompletionAcceptance>(line) {
+            Ok(acceptance) => acceptances.push(acceptance),
+            Err(_) => unparsed += 1,
+        }
+    }
+
+    let mut summary = record_completion_acceptances(db, repo_id, &acceptances).await?;
+    summary.skipped += unparsed;
+    Ok(summary)
+}
+
+/// Committed lines (1-based) a completion landed on, among the lines the
+/// commit changed. The first accepted line may complete a partly typed line
+/// and the last may be followed by typed code; blank lines are skipped.
+/// The match nearest the reported line wins.
+pub fn match_acceptance(
+    file_lines: &[String],
+    changed: &BTreeSet<i32>,
+    text: &str,
+    hint: Option<i32>,
+) -> Vec<i32> {
+    let accepted: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let Some(first) = accepted.first() else {
+        return Vec::new();
+    };
+    let content = |line: i32| file_lines[(line - 1) as usize].trim();
+
+    let mut candidates: Vec<Vec<i32>> = Vec::new();
+    for &start in changed {
+        if start < 1 || start as usize > file_lines.len() {
+            continue;
+        }
+        let head = content(start);
+        if accepted.len() == 1 {
+            if head == *first || (first.len() >= MIN_INLINE_MATCH_CHARS && head.contains(first)) {
+                candidates.push(vec![start]);
+            }
+            continue;
+        }
+        if !head.ends_with(first) {
+            continue;
+        }
+
+        let mut matched = vec![start];
+        let mut line = start;
+        for (idx, expected) in accepted.iter().enumerate().skip(1) {
+            line += 1;
+            while (line as usize) <= file_lines.len() && content(line).is_empty() {
+                line += 1;
+            }
+            if line as usize > file_lines.len() || !changed.contains(&line) {
+                break;
+            }
+            let actual = content(line);
+            let is_last = idx == accepted.len() - 1;
+            if actual != *expected && !(is_last && actual.starts_with(expected)) {
+                break;
+            }
+            matched.push(line);
+        }
+        if matched.len() == accepted.len() {
+            candidates.push(matched);
+        }
+    }
+
+    let distance = |lines: &Vec<i32>| hint.map(|hint| (lines[0] - hint).abs()).unwrap_or(0);
+    candidates
+        .into_iter()
+        .min_by_key(|lines| distance(lines))
+        .unwrap_or_default()
+}
+
+/// Contiguous `(start, end)` runs of sorted line numbers
+fn line_runs(lines: &[i32]) -> Vec<(i32, i32)> {
+    let mut runs: Vec<(i32, i32)> = Vec::new();
+    for &line in lines {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => runs.push((line, line)),
+        }
+    }
+    runs
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingAcceptance {
+    id: i64,
+    file_path: String,
+    start_line: Option<i32>,
+    accepted_text: String,
+    tool: String,
+    model: Option<String>,
+}
+
+/// Match pending acceptances to a commit and record the matched lines as
+/// `ai_tab` attributions. Acceptances are considered if they name the
+/// commit, or were made between its first parent's and its own commit time.
+///
+/// Returns the number of lines attributed.
+pub async fn apply_completion_acceptances(
+    db: &SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<u32, String> {
+    let pending: Option<i64> = sqlx::query_scalar(
+        "SELECT 1 FROM completion_acceptances WHERE repo_id = ? AND matched_at IS NULL LIMIT 1",
+    )
+    .bind(repo_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    if pending.is_none() {
+        return Ok(0);
+    }
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let (window_start, window_end, renamed_to) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        let start = commit
+            .parent(0)
+            .ok()
+            .map(|parent| format_unix(parent.time().seconds()));
+        let end = format_unix(commit.time().seconds() + COMMIT_TIME_SLACK_SECS);
+        // Acceptances recorded before a rename in this commit use the old path
+        let renamed_to = commit_renames(&repo, commit_sha)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(new_path, old_path)| (old_path, new_path))
+            .collect::<HashMap<_, _>>();
+        (start, end, renamed_to)
+    };
+
+    let acceptances = sqlx::query_as::<_, PendingAcceptance>(
+        r#"
+        SELECT id, file_path, start_line, accepted_text, tool, model
+        FROM completion_acceptances
+        WHERE repo_id = ?
+          AND matched_at IS NULL
+          AND (
+            commit_sha = ?
+            OR (commit_sha IS NULL
+                AND (? IS NULL OR accepted_at > ?)
+                AND accepted_at <= ?)
+          )
+        ORDER BY accepted_at, id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(window_start.as_deref())
+    .bind(window_start.as_deref())
+    .bind(&window_end)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    if acceptances.is_empty() {
+        return Ok(0);
+    }
+
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    let mut by_file: HashMap<String, Vec<PendingAcceptance>> = HashMap::new();
+    for acceptance in acceptances {
+        let file_path = renamed_to
+            .get(&acceptance.file_path)
+            .unwrap_or(&acceptance.file_path)
+            .clone();
+        if !exclusions.is_excluded(&file_path) {
+            by_file.entry(file_path).or_default().push(acceptance);
+        }
+    }
+
+    let mut attributed = 0;
+    for (file_path, acceptances) in by_file {
+        let (file_lines, changed) = {
+            let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+            let Ok(file_lines) = load_file_lines(&repo, commit_sha, &file_path) else {
+                continue;
+            };
+            let changed = collect_changed_ranges(&repo, commit_sha, &file_path)?
+                .iter()
+                .flat_map(|range| range.start_line..=range.end_line)
+                .collect::<BTreeSet<i32>>();
+            (file_lines, changed)
+        };
+        if changed.is_empty() {
+            continue;
+        }
+
+        let mut taken: HashSet<i32> = fetch_line_attributions(db, repo_id, commit_sha, &file_path)
+            .await?
+            .iter()
+            .filter(|row| row.author_type != "human")
+            .flat_map(|row| row.start_line..=row.end_line)
+            .collect();
+
+        for acceptance in acceptances {
+            let lines = match_acceptance(
+                &file_lines,
+                &changed,
+                &acceptance.accepted_text,
+                acceptance.start_line,
+            );
+            if lines.is_empty() {
+                continue;
+            }
+            let free = lines
+                .into_iter()
+                .filter(|line| taken.insert(*line))
+                .collect::<Vec<_>>();
+
+            for (start, end) in line_runs(&free) {
+                sqlx::query(
+                    r#"
+                    INSERT INTO line_attributions (
+                        repo_id, commit_sha, file_path, start_line, end_line,
+                        session_id, author_type, ai_percentage, tool, model,
+                        source, confidence
+                    )
+                    VALUES (?, ?, ?, ?, ?, NULL, 'ai_tab', 100.0, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(repo_id)
+                .bind(commit_sha)
+                .bind(&file_path)
+                .bind(start)
+                .bind(end)
+                .bind(&acceptance.tool)
+                .bind(&acceptance.model)
+                .bind(SOURCE_COMPLETION)
+                .bind(source_confidence(SOURCE_COMPLETION))
+                .execute(db)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+            }
+            attributed += free.len() as u32;
+
+            sqlx::query(
+                r#"
+                UPDATE completion_acceptances
+                SET commit_sha = ?, matched_at = datetime('now')
+                WHERE id = ?
+                "#,
+            )
+            .bind(commit_sha)
+            .bind(acceptance.id)
+            .execute(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        }
+    }
+
+    Ok(attributed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn multi_line_completions_match_changed_lines_by_content() {
+        let file =
+            lines("fn main() {\n    let total = items\n        .iter()\n\n        .sum();\n}\n");
+        let changed: BTreeSet<i32> = [2, 3, 4, 5].into_iter().collect();
+        let text = "items\n        .iter()\n        .sum();";
+        assert_eq!(
+            match_acceptance(&file, &changed, text, Some(2)),
+            vec![2, 3, 5]
+        );
+
+        // Unchanged lines are never claimed
+        let changed: BTreeSet<i32> = [2].into_iter().collect();
+        assert!(match_acceptance(&file, &changed, text, None).is_empty());
+    }
+
+    #[test]
+    fn single_line_completions_prefer_the_reported_line() {
+        let file = lines("a = compute_total(x)\nb = 1\nc = compute_total(x)\n");
+        let changed: BTreeSet<i32> = [1, 2, 3].into_iter().collect();
+        assert_eq!(
+            match_acceptance(&file, &changed, "compute_total(x)", Some(3)),
+            vec![3]
+        );
+        // Short fragments must match the whole line
+        assert!(match_acceptance(&file, &changed, "1", None).is_empty());
+        assert_eq!(match_acceptance(&file, &changed, "b = 1", None), vec![2]);
+    }
+
+    #[test]
+    fn otlp_accept_events_become_acceptances() {
+        let attrs: HashMap<String, Vec<String>> = [
+            ("event.name", "copilot.completion.accepted"),
+            ("code.filepath", "src/lib.rs"),
+            ("code.lineno", "12"),
+            ("completion.text", "let x = 1;"),
+            ("service.name", "Copilot"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), vec![v.to_string()]))
+        .collect();
+
+        let acceptance = acceptance_from_attributes(&attrs, "2026-03-01T10:00:00Z").unwrap();
+        assert_eq!(acceptance.file_path, "src/lib.rs");
+        assert_eq!(acceptance.start_line, Some(12));
+        assert_eq!(acceptance.tool, "copilot");
+
+        let mut other = attrs.clone();
+        other.insert(
+            "event.name".to_string(),
+            vec!["codex.tool_result".to_string()],
+        );
+        assert!(acceptance_from_attributes(&other, "2026-03-01T10:00:00Z").is_none());
+    }
+
+    #[test]
+    fn paths_are_made_repo_relative() {
+        assert_eq!(
+            relative_path("/work/repo", "/work/repo/src/a.ts").as_deref(),
+            Some("src/a.ts")
+        );
+        assert_eq!(
+            relative_path("/work/repo", "./src/a.ts").as_deref(),
+            Some("src/a.ts")
+        );
+        assert_eq!(relative_path("/work/repo", "/elsewhere/a.ts"), None);
+    }
+}
@@ -1,9 +1,21 @@
 //! Dashboard analytics module
 //!
-//! Provides aggregated statistics for the dashboard view.
-//! Uses precomputed stats from commit_stats_snapshot table for fast queries.
+//! Provides aggregated statistics for the dashboard view, computed from
+//! cached per-commit contribution stats and optionally scoped by date range,
+//! branch, author email, and tool.
 
+use super::authors::{compute_author_breakdown, AuthorAttribution};
+use super::batch::{repo_attribution_coverage, RepoAttributionCoverage};
+use super::exclusions::{load_exclusion_rules, ExclusionRules};
+use super::languages::{language_breakdown, load_language_map, LanguageStats};
+use super::utils::fetch_repo_root;
+use crate::DbState;
+use chrono::Datelike;
+use git2::{Oid, Repository};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tauri::State;
 
 // =============================================================================
 // Types
@@ -15,8 +27,39 @@ pub struct DashboardStats {
     pub repo: RepoInfo,
     pub time_range: TimeRange,
     pub current_period: PeriodStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub previous_period: Option<PeriodStats>,
     pub top_files: PaginatedFiles,
+    /// AI and human lines per language across the current period's files
+    /// (with a tool filter, AI lines are that tool's lines)
+    pub languages: Vec<LanguageStats>,
+    /// Share of the repo's commits with computed attribution stats
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attribution_coverage: Option<RepoAttributionCoverage>,
+    /// Filter the stats were scoped to (absent when unfiltered)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<DashboardStatsFilter>,
+}
+
+/// Narrows dashboard stats to matching commits; every field is optional
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardStatsFilter {
+    /// Inclusive `YYYY-MM-DD` bounds; each overrides that end of the time range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>,
+    /// Only commits reachable from this branch, tag, or rev
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Only commits whose author email matches (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_email: Option<String>,
+    /// Only commits this tool contributed to; other tools' lines are left
+    /// out of the tool breakdown and top files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +77,21 @@ pub enum TimeRange {
     Custom { from: String, to: String },
 }
 
+impl TimeRange {
+    /// Inclusive `YYYY-MM-DD` bounds relative to `today` (None is unbounded)
+    pub fn bounds(&self, today: chrono::NaiveDate) -> (Option<String>, Option<String>) {
+        let days = match self {
+            TimeRange::Custom { from, to } => return (Some(from.clone()), Some(to.clone())),
+            TimeRange::Preset(TimeRangePreset::All) => return (None, None),
+            TimeRange::Preset(TimeRangePreset::SevenDays) => 7,
+            TimeRange::Preset(TimeRangePreset::ThirtyDays) => 30,
+            TimeRange::Preset(TimeRangePreset::NinetyDays) => 90,
+        };
+        let from = today - chrono::Duration::days(days);
+        (Some(from.to_string()), Some(today.to_string()))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TimeRangePreset {
     #[serde(rename = "7d")]
@@ -72,6 +130,14 @@ pub struct PeriodAttribution {
     pub ai_assist_lines: i64,
     pub collaborative_lines: i64,
     pub ai_percentage: f64,
+    /// Lines removed by the period's commits
+    pub deleted_lines: i64,
+    /// Removed lines that had been written by AI
+    pub deleted_ai_lines: i64,
+    /// Lines removed by commits linked to an AI session
+    pub deleted_by_ai_lines: i64,
+    /// Lines added minus lines removed
+    pub net_lines: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,155 +194,734 @@ pub struct FileStats {
 
 /// Get complete dashboard stats in a single call.
 ///
-/// Uses precomputed snapshot table for fast queries.
 /// Returns current period stats, previous period for comparison,
-/// and top AI-contributed files (paginated).
+/// and top AI-contributed files (paginated). Files matching the repo's
+/// attribution exclusions are left out. With a `filter`, every figure is
+/// scoped to the matching commits (e.g. a release branch or one author).
 #[tauri::command]
 pub async fn get_dashboard_stats(
+    db: State<'_, DbState>,
     repo_id: i64,
     time_range: TimeRange,
     files_offset: i64,
     files_limit: i64,
+    filter: Option<DashboardStatsFilter>,
 ) -> Result<DashboardStats, String> {
-    // TODO: Implement real queries against commit_stats_snapshot table
-    // For now, return mock data that matches the Zod schema
+    let filter = filter.unwrap_or_default();
+    let mut stats = compute_dashboard_stats(
+        &db.0,
+        repo_id,
+        time_range,
+        &filter,
+        files_offset,
+        files_limit,
+        chrono::Utc::now().date_naive(),
+    )
+    .await?;
 
-    let mock = mock_dashboard_stats(repo_id, time_range, files_offset, files_limit);
-    Ok(mock)
+    stats.attribution_coverage = repo_attribution_coverage(&db.0, repo_id).await.ok();
+
+    Ok(stats)
 }
 
+/// Render the dashboard to a self-contained HTML file for sharing.
+///
+/// Uses the same time range and filter as `get_dashboard_stats`.
+#[tauri::command]
+pub async fn export_dashboard_html(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    time_range: TimeRange,
+    filter: Option<DashboardStatsFilter>,
+    output_path: String,
+) -> Result<super::dashboard_html::DashboardHtmlExport, String> {
+    super::dashboard_html::export_dashboard_html(
+        &db.0,
+        repo_id,
+        time_range,
+        &filter.unwrap_or_default(),
+        &output_path,
+    )
+    .await
+}
+
+/// Get per-author attribution for commits in a time range.
+///
+/// Splits each author's committed lines into AI-agent, AI-assist,
+/// collaborative, and human lines.
+#[tauri::command]
+pub async fn get_author_attribution_stats(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    time_range: TimeRange,
+) -> Result<Vec<AuthorAttribution>, String> {
+    let (from, to) = time_range.bounds(chrono::Utc::now().date_naive());
+    compute_author_breakdown(&db.0, repo_id, from.as_deref(), to.as_deref()).await
+}
+
+// =============================================================================
+// Queries
 // =============================================================================
-// Mock Data (for development - remove in Phase 4)
-// ============================================================================
 
-fn mock_dashboard_stats(
+/// Most commits walked to resolve a branch filter
+const MAX_BRANCH_COMMITS: usize = 100_000;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct CommitStatsRow {
+    sha: String,
+    day: String,
+    total_lines: i64,
+    human_lines: i64,
+    ai_agent_lines: i64,
+    ai_assist_lines: i64,
+    collaborative_lines: i64,
+    deleted_lines: i64,
+    deleted_ai_lines: i64,
+    deleted_by_ai_lines: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ToolLinesRow {
+    commit_sha: String,
+    tool: String,
+    model: Option<String>,
+    line_count: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct FileLinesRow {
+    commit_sha: String,
+    file_path: String,
+    lines: i64,
+}
+
+/// Dashboard stats for the commits in `time_range` that match `filter`.
+///
+/// `today` anchors preset ranges. The previous period is the same number
+/// of days immediately before the current one (none for unbounded ranges).
+pub async fn compute_dashboard_stats(
+    db: &SqlitePool,
     repo_id: i64,
     time_range: TimeRange,
+    filter: &DashboardStatsFilter,
     files_offset: i64,
     files_limit: i64,
-) -> DashboardStats {
-    DashboardStats {
+    today: chrono::NaiveDate,
+) -> Result<DashboardStats, String> {
+    let repo_path = fetch_repo_root(db, repo_id).await?;
+    let (range_from, range_to) = time_range.bounds(today);
+    let from = filter.since.clone().or(range_from);
+    let to = filter.until.clone().or(range_to);
+
+    let current = scoped_commits(
+        db,
+        repo_id,
+        &repo_path,
+        from.as_deref(),
+        to.as_deref(),
+        filter,
+    )
+    .await?;
+    let current_period = period_stats(db, repo_id, &current, from, to, filter).await?;
+
+    let previous_bounds = match (
+        current_period.period.start.parse::<chrono::NaiveDate>(),
+        current_period.period.end.parse::<chrono::NaiveDate>(),
+    ) {
+        (Ok(start), Ok(end)) if filter.since.is_some() || range_from_is_bounded(&time_range) => {
+            let prev_end = start - chrono::Duration::days(1);
+            Some((prev_end - (end - start), prev_end))
+        }
+        _ => None,
+    };
+    let previous_period = match previous_bounds {
+        Some((prev_start, prev_end)) => {
+            let (prev_from, prev_to) = (prev_start.to_string(), prev_end.to_string());
+            let previous = scoped_commits(
+                db,
+                repo_id,
+                &repo_path,
+                Some(&prev_from),
+                Some(&prev_to),
+                filter,
+            )
+            .await?;
+            Some(
+                period_stats(
+                    db,
+                    repo_id,
+                    &previous,
+                    Some(prev_from),
+                    Some(prev_to),
+                    filter,
+                )
+                .await?,
+            )
+        }
+        None => None,
+    };
+
+    let (top_files, languages) =
+        file_breakdown(db, repo_id, &current, filter, files_offset, files_limit).await?;
+    let name = std::path::Path::new(&repo_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo_path.clone());
+
+    Ok(DashboardStats {
         repo: RepoInfo {
             id: repo_id,
-            path: "/Users/dev/narrative".to_string(),
-            name: "narrative".to_string(),
+            path: repo_path,
+            name,
         },
         time_range,
-        current_period: PeriodStats {
-            period: Period {
-                start: "2026-01-01".to_string(),
-                end: "2026-01-31".to_string(),
-                commits: 42,
-            },
-            attribution: PeriodAttribution {
-                total_lines: 15000,
-                human_lines: 8250,
-                ai_agent_lines: 4500,
-                ai_assist_lines: 2250,
-                collaborative_lines: 1500,
-                ai_percentage: 45.0,
-            },
-            tool_breakdown: vec![
-                ToolStats {
-                    tool: "claude_code".to_string(),
-                    model: Some("claude-3-5-sonnet".to_string()),
-                    line_count: 5000,
-                },
-                ToolStats {
-                    tool: "cursor".to_string(),
-                    model: Some("gpt-4".to_string()),
-                    line_count: 1750,
-                },
-            ],
-            trend: vec![
-                TrendPoint {
-                    date: "2026-01-01".to_string(),
-                    granularity: TrendGranularity::Day,
-                    ai_percentage: 38.0,
-                    commit_count: 5,
-                },
-                TrendPoint {
-                    date: "2026-01-08".to_string(),
-                    granularity: TrendGranularity::Day,
-                    ai_percentage: 42.0,
-                    commit_count: 8,
-                },
-                TrendPoint {
-                    date: "2026-01-15".to_string(),
-                    granularity: TrendGranularity::Day,
-                    ai_percentage: 45.0,
-                    commit_count: 12,
-                },
-                TrendPoint {
-                    date: "2026-01-22".to_string(),
-                    granularity: TrendGranularity::Day,
-                    ai_percentage: 48.0,
-                    commit_count: 10,
-                },
-                TrendPoint {
-                    date: "2026-01-29".to_string(),
-                    granularity: TrendGranularity::Day,
-                    ai_percentage: 52.0,
-                    commit_count: 7,
-                },
-            ],
+        current_period,
+        previous_period,
+        top_files,
+        languages,
+        attribution_coverage: None,
+        filter: (filter != &DashboardStatsFilter::default()).then(|| filter.clone()),
+    })
+}
+
+fn range_from_is_bounded(time_range: &TimeRange) -> bool {
+    !matches!(time_range, TimeRange::Preset(TimeRangePreset::All))
+}
+
+/// Commits authored in `from..=to` that match the filter, oldest first
+async fn scoped_commits(
+    db: &SqlitePool,
+    repo_id: i64,
+    repo_path: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    filter: &DashboardStatsFilter,
+) -> Result<Vec<CommitStatsRow>, String> {
+    let rows = sqlx::query_as::<_, CommitStatsRow>(
+        r#"
+        SELECT
+            c.sha,
+            date(c.authored_at) AS day,
+            COALESCE(s.total_lines, 0) AS total_lines,
+            COALESCE(s.human_lines, 0) AS human_lines,
+            COALESCE(s.ai_agent_lines, 0) AS ai_agent_lines,
+            COALESCE(s.ai_assist_lines, 0) AS ai_assist_lines,
+            COALESCE(s.collaborative_lines, 0) AS collaborative_lines,
+            COALESCE(d.deleted_lines, 0) AS deleted_lines,
+            COALESCE(d.deleted_ai_lines, 0) AS deleted_ai_lines,
+            CASE WHEN d.removed_by_session_id IS NULL THEN 0
+                 ELSE d.deleted_lines END AS deleted_by_ai_lines
+        FROM commits c
+        LEFT JOIN commit_contribution_stats s
+            ON s.repo_id = c.repo_id AND s.commit_sha = c.sha
+        LEFT JOIN commit_deletion_stats d
+            ON d.repo_id = c.repo_id AND d.commit_sha = c.sha
+        WHERE c.repo_id = ?
+          AND c.authored_at IS NOT NULL
+          AND (? IS NULL OR date(c.authored_at) >= ?)
+          AND (? IS NULL OR date(c.authored_at) <= ?)
+          AND (? IS NULL OR s.tool = ? OR EXISTS (
+            SELECT 1 FROM commit_tool_stats t
+            WHERE t.repo_id = c.repo_id AND t.commit_sha = c.sha AND t.tool = ?
+          ))
+        ORDER BY c.authored_at, c.sha
+        "#,
+    )
+    .bind(repo_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .bind(&filter.tool)
+    .bind(&filter.tool)
+    .bind(&filter.tool)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    if filter.branch.is_none() && filter.author_email.is_none() {
+        return Ok(rows);
+    }
+
+    let repo = Repository::open(repo_path).map_err(|e| e.to_string())?;
+    let on_branch = match &filter.branch {
+        Some(branch) => Some(branch_commits(&repo, branch)?),
+        None => None,
+    };
+    let author_email = filter.author_email.as_deref().map(str::trim);
+
+    Ok(rows
+        .into_iter()
+        .filter(|row| {
+            on_branch
+                .as_ref()
+                .map_or(true, |shas| shas.contains(&row.sha))
+        })
+        .filter(|row| {
+            author_email.map_or(true, |email| {
+                Oid::from_str(&row.sha)
+                    .and_then(|oid| repo.find_commit(oid))
+                    .ok()
+                    .and_then(|commit| commit.author().email().map(str::to_string))
+                    .is_some_and(|author| author.eq_ignore_ascii_case(email))
+            })
+        })
+        .collect())
+}
+
+/// Shas of the commits reachable from a branch, tag, or other rev
+fn branch_commits(repo: &Repository, rev: &str) -> Result<HashSet<String>, String> {
+    let tip = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|_| format!("Unknown branch or ref: {rev}"))?;
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.push(tip.id()).map_err(|e| e.to_string())?;
+
+    Ok(walk
+        .filter_map(Result::ok)
+        .take(MAX_BRANCH_COMMITS)
+        .map(|oid| oid.to_string())
+        .collect())
+}
+
+async fn period_stats(
+    db: &SqlitePool,
+    repo_id: i64,
+    commits: &[CommitStatsRow],
+    from: Option<String>,
+    to: Option<String>,
+    filter: &DashboardStatsFilter,
+) -> Result<PeriodStats, String> {
+    let start = from
+        .or_else(|| commits.first().map(|row| row.day.clone()))
+        .unwrap_or_default();
+    let end = to
+        .or_else(|| commits.last().map(|row| row.day.clone()))
+        .unwrap_or_default();
+
+    Ok(PeriodStats {
+        attribution: sum_attribution(commits),
+        tool_breakdown: tool_breakdown(db, repo_id, commits, filter).await?,
+        trend: trend_points(commits, &start, &end),
+        period: Period {
+            start,
+            end,
+            commits: commits.len() as i64,
         },
-        previous_period: Some(PeriodStats {
-            period: Period {
-                start: "2025-12-01".to_string(),
-                end: "2025-12-31".to_string(),
-                commits: 38,
-            },
-            attribution: PeriodAttribution {
-                total_lines: 12000,
-                human_lines: 7200,
-                ai_agent_lines: 3000,
-                ai_assist_lines: 1800,
-                collaborative_lines: 1200,
-                ai_percentage: 40.0,
-            },
-            tool_breakdown: vec![
-                ToolStats {
-                    tool: "claude_code".to_string(),
-                    model: Some("claude-3-5-sonnet".to_string()),
-                    line_count: 3500,
-                },
-                ToolStats {
-                    tool: "cursor".to_string(),
-                    model: Some("gpt-4".to_string()),
-                    line_count: 1300,
-                },
-            ],
-            trend: vec![],
+    })
+}
+
+fn ai_percentage(ai_lines: i64, total_lines: i64) -> f64 {
+    if total_lines <= 0 {
+        return 0.0;
+    }
+    (ai_lines as f64 / total_lines as f64 * 1000.0).round() / 10.0
+}
+
+fn sum_attribution<'a>(commits: impl IntoIterator<Item = &'a CommitStatsRow>) -> PeriodAttribution {
+    let mut totals = PeriodAttribution {
+        total_lines: 0,
+        human_lines: 0,
+        ai_agent_lines: 0,
+        ai_assist_lines: 0,
+        collaborative_lines: 0,
+        ai_percentage: 0.0,
+        deleted_lines: 0,
+        deleted_ai_lines: 0,
+        deleted_by_ai_lines: 0,
+        net_lines: 0,
+    };
+    for row in commits {
+        totals.total_lines += row.total_lines;
+        totals.human_lines += row.human_lines;
+        totals.ai_agent_lines += row.ai_agent_lines;
+        totals.ai_assist_lines += row.ai_assist_lines;
+        totals.collaborative_lines += row.collaborative_lines;
+        totals.deleted_lines += row.deleted_lines;
+        totals.deleted_ai_lines += row.deleted_ai_lines;
+        totals.deleted_by_ai_lines += row.deleted_by_ai_lines;
+    }
+    totals.net_lines = totals.total_lines - totals.deleted_lines;
+    totals.ai_percentage = ai_percentage(
+        totals.ai_agent_lines + totals.ai_assist_lines + totals.collaborative_lines,
+        totals.total_lines,
+    );
+    totals
+}
+
+/// Daily points for ranges up to a month, weekly (by Monday) beyond that
+fn trend_points(commits: &[CommitStatsRow], start: &str, end: &str) -> Vec<TrendPoint> {
+    let span_days = match (
+        start.parse::<chrono::NaiveDate>(),
+        end.parse::<chrono::NaiveDate>(),
+    ) {
+        (Ok(start), Ok(end)) => (end - start).num_days(),
+        _ => 0,
+    };
+    let granularity = if span_days <= 31 {
+        TrendGranularity::Day
+    } else {
+        TrendGranularity::Week
+    };
+
+    let mut buckets: BTreeMap<String, Vec<&CommitStatsRow>> = BTreeMap::new();
+    for row in commits {
+        let key = match (&granularity, row.day.parse::<chrono::NaiveDate>()) {
+            (TrendGranularity::Week, Ok(day)) => {
+                let monday =
+                    day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64);
+                monday.to_string()
+            }
+            _ => row.day.clone(),
+        };
+        buckets.entry(key).or_default().push(row);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(date, rows)| TrendPoint {
+            date,
+            granularity: granularity.clone(),
+            ai_percentage: sum_attribution(rows.iter().copied()).ai_percentage,
+            commit_count: rows.len() as i64,
+        })
+        .collect()
+}
+
+async fn tool_breakdown(
+    db: &SqlitePool,
+    repo_id: i64,
+    commits: &[CommitStatsRow],
+    filter: &DashboardStatsFilter,
+) -> Result<Vec<ToolStats>, String> {
+    let (Some(first), Some(last)) = (commits.first(), commits.last()) else {
+        return Ok(Vec::new());
+    };
+    let in_scope = commits
+        .iter()
+        .map(|row| row.sha.as_str())
+        .collect::<HashSet<_>>();
+
+    let rows = sqlx::query_as::<_, ToolLinesRow>(
+        r#"
+        SELECT t.commit_sha, t.tool, t.model, COALESCE(t.line_count, 0) AS line_count
+        FROM commit_tool_stats t
+        JOIN commits c ON c.repo_id = t.repo_id AND c.sha = t.commit_sha
+        WHERE t.repo_id = ?
+          AND date(c.authored_at) >= ? AND date(c.authored_at) <= ?
+          AND (? IS NULL OR t.tool = ?)
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&first.day)
+    .bind(&last.day)
+    .bind(&filter.tool)
+    .bind(&filter.tool)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut by_tool: BTreeMap<(String, Option<String>), i64> = BTreeMap::new();
+    for row in rows {
+        if in_scope.contains(row.commit_sha.as_str()) {
+            *by_tool.entry((row.tool, row.model)).or_default() += row.line_count;
+        }
+    }
+
+    let mut tools = by_tool
+        .into_iter()
+        .map(|((tool, model), line_count)| ToolStats {
+            tool,
+            model,
+            line_count,
+        })
+        .collect::<Vec<_>>();
+    tools.sort_by(|a, b| b.line_count.cmp(&a.line_count));
+    Ok(tools)
+}
+
+/// Files with the most AI-attributed lines across the scoped commits, and
+/// the per-language totals of every file the commits added lines to
+async fn file_breakdown(
+    db: &SqlitePool,
+    repo_id: i64,
+    commits: &[CommitStatsRow],
+    filter: &DashboardStatsFilter,
+    offset: i64,
+    limit: i64,
+) -> Result<(PaginatedFiles, Vec<LanguageStats>), String> {
+    let page = |files: Vec<FileStats>| {
+        let total = files.len() as i64;
+        let files = files
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(limit.max(0) as usize)
+            .collect();
+        PaginatedFiles {
+            files,
+            total,
+            offset,
+            limit,
+            has_more: offset + limit < total,
+        }
+    };
+    let (Some(first), Some(last)) = (commits.first(), commits.last()) else {
+        return Ok((page(Vec::new()), Vec::new()));
+    };
+    let in_scope = commits
+        .iter()
+        .map(|row| row.sha.as_str())
+        .collect::<HashSet<_>>();
+
+    let ai_rows = sqlx::query_as::<_, FileLinesRow>(
+        r#"
+        SELECT la.commit_sha, la.file_path, SUM(la.end_line - la.start_line + 1) AS lines
+        FROM line_attributions la
+        JOIN commits c ON c.repo_id = la.repo_id AND c.sha = la.commit_sha
+        WHERE la.repo_id = ?
+          AND la.author_type != 'human'
+          AND date(c.authored_at) >= ? AND date(c.authored_at) <= ?
+          AND (? IS NULL OR la.tool = ?)
+        GROUP BY la.commit_sha, la.file_path
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&first.day)
+    .bind(&last.day)
+    .bind(&filter.tool)
+    .bind(&filter.tool)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let added_rows = sqlx::query_as::<_, FileLinesRow>(
+        r#"
+        SELECT fc.commit_sha, fc.path AS file_path, fc.additions AS lines
+        FROM file_changes fc
+        JOIN commits c ON c.repo_id = fc.repo_id AND c.sha = fc.commit_sha
+        WHERE fc.repo_id = ?
+          AND date(c.authored_at) >= ? AND date(c.authored_at) <= ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&first.day)
+    .bind(&last.day)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    // path -> (ai lines, added lines, commits)
+    let mut by_file: HashMap<String, (i64, i64, HashSet<String>)> = HashMap::new();
+    for row in ai_rows {
+        if in_scope.contains(row.commit_sha.as_str()) && !exclusions.is_excluded(&row.file_path) {
+            let entry = by_file.entry(row.file_path).or_default();
+            entry.0 += row.lines;
+            entry.2.insert(row.commit_sha);
+        }
+    }
+    for row in added_rows {
+        if in_scope.contains(row.commit_sha.as_str()) && !exclusions.is_excluded(&row.file_path) {
+            by_file.entry(row.file_path).or_default().1 += row.lines;
+        }
+    }
+
+    let languages = language_breakdown(
+        &load_language_map(db, repo_id).await,
+        &ExclusionRules::default(),
+        by_file.iter().map(|(path, (ai_lines, added_lines, _))| {
+            (path.as_str(), *ai_lines as u32, *added_lines as u32)
         }),
-        top_files: PaginatedFiles {
-            files: vec![
-                FileStats {
-                    file_path: "src/core/attribution-api.ts".to_string(),
-                    total_lines: 350,
-                    ai_lines: 280,
-                    ai_percentage: 80.0,
-                    commit_count: 5,
-                },
-                FileStats {
-                    file_path: "src/ui/views/DashboardView.tsx".to_string(),
-                    total_lines: 250,
-                    ai_lines: 175,
-                    ai_percentage: 70.0,
-                    commit_count: 3,
-                },
-                FileStats {
-                    file_path: "src-tauri/src/attribution/dashboard.rs".to_string(),
-                    total_lines: 180,
-                    ai_lines: 90,
-                    ai_percentage: 50.0,
-                    commit_count: 2,
-                },
-            ],
-            total: 15,
-            offset: files_offset,
-            limit: files_limit,
-            has_more: files_offset + files_limit < 15,
-        },
+    );
+
+    let mut files = by_file
+        .into_iter()
+        .filter(|(_, (ai_lines, _, _))| *ai_lines > 0)
+        .map(|(file_path, (ai_lines, added_lines, shas))| {
+            let total_lines = added_lines.max(ai_lines);
+            FileStats {
+                file_path,
+                total_lines,
+                ai_lines,
+                ai_percentage: ai_percentage(ai_lines, total_lines),
+                commit_count: shas.len() as i64,
+            }
+        })
+        .collect::<Vec<_>>();
+    files.sort_by(|a, b| {
+        b.ai_lines
+            .cmp(&a.ai_lines)
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+
+    Ok((page(files), languages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn setup() -> SqlitePool {
+        let pool = crate::test_pool().await;
+
+        sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/nonexistent/narrative')")
+            .execute(&pool)
+            .await
+            .expect("repo");
+        // (sha, authored_at, total, human, agent, tool)
+        for (sha, authored_at, total, human, agent, tool) in [
+            ("a1", "2026-03-02T10:00:00Z", 10, 10, 0, None),
+            ("b2", "2026-03-10T10:00:00Z", 20, 5, 15, Some("claude_code")),
+            ("c3", "2026-03-20T10:00:00Z", 10, 0, 10, Some("cursor")),
+            ("d4", "2026-02-20T10:00:00Z", 10, 0, 10, Some("claude_code")),
+        ] {
+            sqlx::query("INSERT INTO commits (repo_id, sha, authored_at) VALUES (1, ?, ?)")
+                .bind(sha)
+                .bind(authored_at)
+                .execute(&pool)
+                .await
+                .expect("commit");
+            // Attributions first: inserting them invalidates cached stats
+            if let Some(tool) = tool {
+                sqlx::query(
+                    r#"
+                    INSERT INTO line_attributions
+                        (repo_id, commit_sha, file_path, start_line, end_line, author_type, tool)
+                    VALUES (1, ?, 'src/lib.rs', 1, ?, 'ai_agent', ?)
+                    "#,
+                )
+                .bind(sha)
+                .bind(agent)
+                .bind(tool)
+                .execute(&pool)
+                .await
+                .expect("line attribution");
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO commit_contribution_stats
+                    (repo_id, commit_sha, total_lines, human_lines, ai_agent_lines, tool)
+                VALUES (1, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(sha)
+            .bind(total)
+            .bind(human)
+            .bind(agent)
+            .bind(tool)
+            .execute(&pool)
+            .await
+            .expect("stats");
+            if let Some(tool) = tool {
+                sqlx::query(
+                    "INSERT INTO commit_tool_stats (repo_id, commit_sha, tool, line_count) VALUES (1, ?, ?, ?)",
+                )
+                .bind(sha)
+                .bind(tool)
+                .bind(agent)
+                .execute(&pool)
+                .await
+                .expect("tool stats");
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO commit_deletion_stats
+                (repo_id, commit_sha, deleted_lines, deleted_ai_lines, deleted_human_lines, removed_by_session_id)
+            VALUES (1, 'b2', 8, 3, 5, 's1'), (1, 'c3', 2, 0, 2, NULL)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .expect("deletion stats");
+
+        pool
+    }
+
+    fn march() -> TimeRange {
+        TimeRange::Custom {
+            from: "2026-03-01".to_string(),
+            to: "2026-03-31".to_string(),
+        }
+    }
+
+    #[test]
+    fn filter_scopes_stats_to_tool_and_dates() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = setup().await;
+            let today = chrono::NaiveDate::from_ymd_opt(2026, 4, 1).unwrap();
+
+            let all = compute_dashboard_stats(
+                &pool,
+                1,
+                march(),
+                &DashboardStatsFilter::default(),
+                0,
+                10,
+                today,
+            )
+            .await
+            .expect("unfiltered");
+            assert_eq!(all.repo.name, "narrative");
+            assert!(all.filter.is_none());
+            assert_eq!(all.current_period.period.commits, 3);
+            assert_eq!(all.current_period.attribution.total_lines, 40);
+            assert_eq!(all.current_period.attribution.ai_percentage, 62.5);
+            assert_eq!(all.current_period.attribution.deleted_lines, 10);
+            assert_eq!(all.current_period.attribution.deleted_ai_lines, 3);
+            assert_eq!(all.current_period.attribution.deleted_by_ai_lines, 8);
+            assert_eq!(all.current_period.attribution.net_lines, 30);
+            assert_eq!(all.current_period.tool_breakdown.len(), 2);
+            // Previous period is the 31 days before March
+            let previous = all.previous_period.expect("previous period");
+            assert_eq!(previous.period.start, "2026-01-29");
+            assert_eq!(previous.period.end, "2026-02-28");
+            assert_eq!(previous.period.commits, 1);
+
+            let filter = DashboardStatsFilter {
+                since: Some("2026-03-05".to_string()),
+                tool: Some("claude_code".to_string()),
+                ..Default::default()
+            };
+            let scoped = compute_dashboard_stats(&pool, 1, march(), &filter, 0, 10, today)
+                .await
+                .expect("filtered");
+            assert_eq!(scoped.filter, Some(filter));
+            assert_eq!(scoped.current_period.period.start, "2026-03-05");
+            assert_eq!(scoped.current_period.period.commits, 1);
+            assert_eq!(scoped.current_period.attribution.ai_agent_lines, 15);
+            assert_eq!(scoped.current_period.attribution.ai_percentage, 75.0);
+            let tools = &scoped.current_period.tool_breakdown;
+            assert_eq!(tools.len(), 1);
+            assert_eq!(tools[0].tool, "claude_code");
+            assert_eq!(scoped.top_files.total, 1);
+            assert_eq!(scoped.top_files.files[0].ai_lines, 15);
+            assert_eq!(scoped.languages.len(), 1);
+            assert_eq!(scoped.languages[0].language, "Rust");
+            assert_eq!(scoped.languages[0].ai_lines, 15);
+        });
+    }
+
+    #[test]
+    fn preset_ranges_end_today() {
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        assert_eq!(
+            TimeRange::Preset(TimeRangePreset::SevenDays).bounds(today),
+            (
+                Some("2026-03-24".to_string()),
+                Some("2026-03-31".to_string())
+            )
+        );
+        assert_eq!(
+            TimeRange::Preset(TimeRangePreset::All).bounds(today),
+            (None, None)
+        );
     }
 }
@@ -0,0 +1,416 @@
+//! Static HTML dashboard export
+//!
+//! Renders dashboard stats into one self-contained HTML file (inline CSS
+//! and SVG, no JavaScript or external assets) so the state of a repo can be
+//! shared with people who don't run the app. The raw stats are embedded as
+//! JSON (`#narrative-dashboard-data`) for anyone who wants to re-chart them.
+
+use super::dashboard::{
+    compute_dashboard_stats, DashboardStats, DashboardStatsFilter, PeriodStats, TimeRange,
+};
+use sqlx::SqlitePool;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Files listed in the exported top-files table
+const EXPORT_TOP_FILES: i64 = 25;
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", sans-serif; margin: 2rem auto; max-width: 960px; padding: 0 1rem; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+h2 { margin-top: 2rem; font-size: 1.1rem; }
+.meta { color: #59636e; font-size: 0.9rem; }
+.cards { display: grid; grid-template-columns: repeat(auto-fit, minmax(140px, 1fr)); gap: 0.75rem; margin-top: 1.5rem; }
+.card { border: 1px solid #d1d9e0; border-radius: 8px; padding: 0.75rem; }
+.card .label { color: #59636e; font-size: 0.8rem; }
+.card .value { font-size: 1.5rem; font-weight: 600; }
+.card .delta { font-size: 0.8rem; color: #59636e; }
+table { border-collapse: collapse; width: 100%; font-size: 0.9rem; }
+th, td { text-align: left; padding: 0.35rem 0.5rem; border-bottom: 1px solid #eaeef2; }
+td.num, th.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { background: #eaeef2; border-radius: 4px; height: 0.6rem; min-width: 120px; }
+.bar span { display: block; background: #8250df; border-radius: 4px; height: 100%; }
+svg .ai { fill: #8250df; }
+svg .axis { stroke: #d1d9e0; }
+footer { margin-top: 2rem; color: #59636e; font-size: 0.8rem; }
+"#;
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardHtmlExport {
+    pub path: String,
+    pub commits: i64,
+}
+
+fn escape_html(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn card(out: &mut String, label: &str, value: &str, delta: Option<String>) {
+    let _ = write!(
+        out,
+        r#"<div class="card"><div class="label">{}</div><div class="value">{}</div>"#,
+        escape_html(label),
+        escape_html(value)
+    );
+    if let Some(delta) = delta {
+        let _ = write!(out, r#"<div class="delta">{}</div>"#, escape_html(&delta));
+    }
+    out.push_str("</div>");
+}
+
+/// "+3.2 pts vs previous period" style comparison
+fn delta(current: f64, previous: Option<f64>, unit: &str) -> Option<String> {
+    let previous = previous?;
+    Some(format!(
+        "{:+.1}{} vs previous period",
+        current - previous,
+        unit
+    ))
+}
+
+/// Bar chart of AI share per trend bucket
+fn trend_svg(period: &PeriodStats) -> String {
+    const WIDTH: f64 = 900.0;
+    const HEIGHT: f64 = 160.0;
+    if period.trend.is_empty() {
+        return "<p class=\"meta\">No commits in this period.</p>".to_string();
+    }
+
+    let slot = WIDTH / period.trend.len() as f64;
+    let bar_width = (slot * 0.8).max(1.0);
+    let mut svg = format!(
+        r#"<svg viewBox="0 0 {WIDTH} {}" width="100%" role="img" aria-label="AI share over time">"#,
+        HEIGHT + 1.0
+    );
+    let _ = write!(
+        svg,
+        r#"<line class="axis" x1="0" y1="{HEIGHT}" x2="{WIDTH}" y2="{HEIGHT}"/>"#
+    );
+    for (i, point) in period.trend.iter().enumerate() {
+        let height = HEIGHT * point.ai_percentage.clamp(0.0, 100.0) / 100.0;
+        let _ = write!(
+            svg,
+            r#"<rect class="ai" x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}"><title>{}: {:.1}% AI, {} commit(s)</title></rect>"#,
+            i as f64 * slot + (slot - bar_width) / 2.0,
+            HEIGHT - height,
+            bar_width,
+            height,
+            escape_html(&point.date),
+            point.ai_percentage,
+            point.commit_count
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn bar(value: i64, max: i64) -> String {
+    let width = if max > 0 {
+        value as f64 / max as f64 * 100.0
+    } else {
+        0.0
+    };
+    format!(r#"<div class="bar"><span style="width: {width:.1}%"></span></div>"#)
+}
+
+/// Render dashboard stats as a self-contained HTML page
+pub fn render_dashboard_html(stats: &DashboardStats, generated_at: &str) -> String {
+    let current = &stats.current_period;
+    let previous = stats.previous_period.as_ref();
+    let a = &current.attribution;
+    let title = format!("{} — AI attribution", stats.repo.name);
+
+    let mut out =
+        String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    let _ = writeln!(out, "<title>{}</title>", escape_html(&title));
+    let _ = writeln!(out, "<style>{STYLE}</style>\n</head>\n<body>");
+    let _ = writeln!(out, "<h1>{}</h1>", escape_html(&title));
+
+    let period = if current.period.start.is_empty() {
+        "All history".to_string()
+    } else {
+        format!("{} to {}", current.period.start, current.period.end)
+    };
+    let mut scope = Vec::new();
+    if let Some(filter) = &stats.filter {
+        for (label, value) in [
+            ("branch", &filter.branch),
+            ("author", &filter.author_email),
+            ("tool", &filter.tool),
+        ] {
+            if let Some(value) = value {
+                scope.push(format!("{label} {value}"));
+            }
+        }
+    }
+    let scope = if scope.is_empty() {
+        String::new()
+    } else {
+        format!(" · {}", scope.join(", "))
+    };
+    let _ = writeln!(
+        out,
+        r#"<p class="meta">{} · {}{} · {} commit(s)</p>"#,
+        escape_html(&stats.repo.path),
+        escape_html(&period),
+        escape_html(&scope),
+        current.period.commits
+    );
+
+    out.push_str("<div class=\"cards\">");
+    card(
+        &mut out,
+        "AI share",
+        &format!("{:.1}%", a.ai_percentage),
+        delta(
+            a.ai_percentage,
+            previous.map(|p| p.attribution.ai_percentage),
+            " pts",
+        ),
+    );
+    card(
+        &mut out,
+        "Commits",
+        &current.period.commits.to_string(),
+        delta(
+            current.period.commits as f64,
+            previous.map(|p| p.period.commits as f64),
+            "",
+        ),
+    );
+    card(&mut out, "Total lines", &a.total_lines.to_string(), None);
+    card(&mut out, "AI agent", &a.ai_agent_lines.to_string(), None);
+    card(&mut out, "AI assist", &a.ai_assist_lines.to_string(), None);
+    card(
+        &mut out,
+        "Collaborative",
+        &a.collaborative_lines.to_string(),
+        None,
+    );
+    card(&mut out, "Human", &a.human_lines.to_string(), None);
+    card(
+        &mut out,
+        "Lines removed",
+        &a.deleted_lines.to_string(),
+        None,
+    );
+    card(&mut out, "Net lines", &a.net_lines.to_string(), None);
+    out.push_str("</div>\n");
+
+    out.push_str("<h2>AI share over time</h2>\n");
+    out.push_str(&trend_svg(current));
+    out.push('\n');
+
+    out.push_str("<h2>Tools</h2>\n");
+    if current.tool_breakdown.is_empty() {
+        out.push_str("<p class=\"meta\">No AI tools recorded.</p>\n");
+    } else {
+        let max = current
+            .tool_breakdown
+            .iter()
+            .map(|t| t.line_count)
+            .max()
+            .unwrap_or(0);
+        out.push_str(
+            "<table><tr><th>Tool</th><th>Model</th><th class=\"num\">Lines</th><th></th></tr>\n",
+        );
+        for tool in &current.tool_breakdown {
+            let _ = writeln!(
+                out,
+                r#"<tr><td>{}</td><td>{}</td><td class="num">{}</td><td>{}</td></tr>"#,
+                escape_html(&tool.tool),
+                escape_html(tool.model.as_deref().unwrap_or("-")),
+                tool.line_count,
+                bar(tool.line_count, max)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if !stats.languages.is_empty() {
+        out.push_str("<h2>Languages</h2>\n");
+        out.push_str("<table><tr><th>Language</th><th class=\"num\">Files</th><th class=\"num\">Lines</th><th class=\"num\">AI</th><th class=\"num\">AI %</th></tr>\n");
+        for language in &stats.languages {
+            let _ = writeln!(
+                out,
+                r#"<tr><td>{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{:.1}</td></tr>"#,
+                escape_html(&language.language),
+                language.files,
+                language.total_lines,
+                language.ai_lines,
+                language.ai_percentage
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("<h2>Top AI-contributed files</h2>\n");
+    if stats.top_files.files.is_empty() {
+        out.push_str("<p class=\"meta\">No AI-attributed files.</p>\n");
+    } else {
+        out.push_str("<table><tr><th>File</th><th class=\"num\">AI lines</th><th class=\"num\">Lines</th><th class=\"num\">AI %</th><th class=\"num\">Commits</th></tr>\n");
+        for file in &stats.top_files.files {
+            let _ = writeln!(
+                out,
+                r#"<tr><td>{}</td><td class="num">{}</td><td class="num">{}</td><td class="num">{:.1}</td><td class="num">{}</td></tr>"#,
+                escape_html(&file.file_path),
+                file.ai_lines,
+                file.total_lines,
+                file.ai_percentage,
+                file.commit_count
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    // `</` is escaped so file paths or tool names cannot close the script early
+    let data = serde_json::to_string(stats)
+        .unwrap_or_else(|_| "{}".to_string())
+        .replace("</", "<\\/");
+    let _ = writeln!(
+        out,
+        "<script type=\"application/json\" id=\"narrative-dashboard-data\">{data}</script>"
+    );
+    let _ = writeln!(
+        out,
+        "<footer>Generated by Narrative {} on {}</footer>\n</body>\n</html>",
+        env!("CARGO_PKG_VERSION"),
+        escape_html(generated_at)
+    );
+    out
+}
+
+/// Render the dashboard for `time_range` (scoped by `filter`) to an HTML
+/// file at `output_path`
+pub async fn export_dashboard_html(
+    db: &SqlitePool,
+    repo_id: i64,
+    time_range: TimeRange,
+    filter: &DashboardStatsFilter,
+    output_path: &str,
+) -> Result<DashboardHtmlExport, String> {
+    let output_path = output_path.trim();
+    if output_path.is_empty() {
+        return Err("Output path is empty".to_string());
+    }
+
+    let now = chrono::Utc::now();
+    let stats = compute_dashboard_stats(
+        db,
+        repo_id,
+        time_range,
+        filter,
+        0,
+        EXPORT_TOP_FILES,
+        now.date_naive(),
+    )
+    .await?;
+    let html = render_dashboard_html(&stats, &now.to_rfc3339());
+
+    let path = Path::new(output_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, html).map_err(|e| format!("write failed: {e}"))?;
+
+    Ok(DashboardHtmlExport {
+        path: output_path.to_string(),
+        commits: stats.current_period.period.commits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dashboard::{
+        PaginatedFiles, Period, PeriodAttribution, RepoInfo, TimeRangePreset, ToolStats,
+        TrendGranularity, TrendPoint,
+    };
+    use super::*;
+
+    fn stats() -> DashboardStats {
+        DashboardStats {
+            repo: RepoInfo {
+                id: 1,
+                path: "/work/<repo>".to_string(),
+                name: "repo".to_string(),
+            },
+            time_range: TimeRange::Preset(TimeRangePreset::ThirtyDays),
+            current_period: PeriodStats {
+                period: Period {
+                    start: "2026-03-01".to_string(),
+                    end: "2026-03-31".to_string(),
+                    commits: 4,
+                },
+                attribution: PeriodAttribution {
+                    total_lines: 100,
+                    human_lines: 40,
+                    ai_agent_lines: 50,
+                    ai_assist_lines: 10,
+                    collaborative_lines: 0,
+                    ai_percentage: 60.0,
+                    deleted_lines: 20,
+                    deleted_ai_lines: 5,
+                    deleted_by_ai_lines: 12,
+                    net_lines: 80,
+                },
+                tool_breakdown: vec![ToolStats {
+                    tool: "claude_code".to_string(),
+                    model: None,
+                    line_count: 50,
+                }],
+                trend: vec![TrendPoint {
+                    date: "2026-03-02".to_string(),
+                    granularity: TrendGranularity::Day,
+                    ai_percentage: 60.0,
+                    commit_count: 4,
+                }],
+            },
+            previous_period: None,
+            top_files: PaginatedFiles {
+                files: Vec::new(),
+                total: 0,
+                offset: 0,
+                limit: 25,
+                has_more: false,
+            },
+            languages: Vec::new(),
+            attribution_coverage: None,
+            filter: Some(DashboardStatsFilter {
+                tool: Some("</script><b>".to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn page_is_self_contained_and_escaped() {
+        let html = render_dashboard_html(&stats(), "2026-04-01T00:00:00+00:00");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("/work/&lt;repo&gt;"));
+        assert!(html.contains("tool &lt;/script&gt;&lt;b&gt;"));
+        assert!(html.contains(r#"<div class="value">60.0%</div>"#));
+        assert!(html.contains("<title>2026-03-02: 60.0% AI, 4 commit(s)</title>"));
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("<link"));
+
+        // Only the data block's own closing tag survives
+        assert_eq!(html.matches("</script>").count(), 1);
+        let start = html.find("id=\"narrative-dashboard-data\">").unwrap() + 30;
+        let end = html.find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(data["currentPeriod"]["attribution"]["aiPercentage"], 60.0);
+    }
+}
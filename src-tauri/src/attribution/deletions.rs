@@ -0,0 +1,393 @@
+//! Attribution of deleted lines (net-change accounting)
+//!
+//! Contribution stats count the lines a commit adds, so a large AI-driven
+//! cleanup looks like nothing happened. For each commit this blames the
+//! lines it removed in its first parent, reads their attribution from the
+//! commit that wrote them, and records who wrote the removed code and which
+//! session (if any) removed it. Dashboard stats sum these into churn metrics.
+
+use super::exclusions::load_exclusion_rules;
+use super::git_utils::commit_deleted_ranges;
+use super::line_attribution::fetch_line_attributions;
+use super::propagation::blame_spans;
+use super::source_lens::LineAttributionRow;
+use super::utils::fetch_repo_root;
+use git2::Repository;
+use std::collections::HashMap;
+
+/// Cap on distinct origin files read per commit
+const MAX_DELETION_ORIGINS: usize = 200;
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitDeletionStats {
+    pub commit_sha: String,
+    pub deleted_lines: i64,
+    /// Removed lines originally written by AI (agent, assist, or collaborative)
+    pub deleted_ai_lines: i64,
+    pub deleted_human_lines: i64,
+    /// Session linked to the removing commit (None: removed without AI)
+    pub removed_by_session_id: Option<String>,
+}
+
+/// A run of removed lines with one origin and attribution
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeletedRange {
+    /// Path in the first parent
+    pub file_path: String,
+    /// Lines in the first parent's version of the file
+    pub start_line: i32,
+    pub end_line: i32,
+    pub origin_commit_sha: String,
+    pub author_type: String,
+    pub session_id: Option<String>,
+    pub tool: Option<String>,
+}
+
+/// Removed lines blamed to one origin commit, before attribution
+struct DeletedPiece {
+    file_path: String,
+    start_line: i32,
+    end_line: i32,
+    origin_commit_sha: String,
+    orig_path: String,
+    /// Origin line number minus parent line number
+    shift: i32,
+}
+
+/// Split origin lines `start..=end` into runs covered by the same
+/// attribution row (None where no row covers them)
+pub fn split_by_attribution(
+    start: i32,
+    end: i32,
+    rows: &[LineAttributionRow],
+) -> Vec<(i32, i32, Option<&LineAttributionRow>)> {
+    let mut runs: Vec<(i32, i32, Option<usize>)> = Vec::new();
+    for line in start..=end {
+        let row = rows
+            .iter()
+            .position(|row| row.start_line <= line && line <= row.end_line);
+        match runs.last_mut() {
+            Some((_, run_end, run_row)) if *run_row == row && *run_end + 1 == line => {
+                *run_end = line;
+            }
+            _ => runs.push((line, line, row)),
+        }
+    }
+    runs.into_iter()
+        .map(|(start, end, row)| (start, end, row.map(|index| &rows[index])))
+        .collect()
+}
+
+/// Sum deleted ranges into per-commit stats
+pub fn summarize_deletions(
+    commit_sha: &str,
+    ranges: &[DeletedRange],
+    removed_by_session_id: Option<String>,
+) -> CommitDeletionStats {
+    let mut stats = CommitDeletionStats {
+        commit_sha: commit_sha.to_string(),
+        removed_by_session_id,
+        ..Default::default()
+    };
+    for range in ranges {
+        let lines = (range.end_line - range.start_line + 1).max(0) as i64;
+        stats.deleted_lines += lines;
+        if range.author_type == "human" {
+            stats.deleted_human_lines += lines;
+        } else {
+            stats.deleted_ai_lines += lines;
+        }
+    }
+    stats
+}
+
+/// Blame and attribute the lines `commit_sha` removed
+pub async fn compute_deleted_ranges(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<Vec<DeletedRange>, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    let pieces = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let (parent_sha, deleted) = commit_deleted_ranges(&repo, commit_sha)?;
+        let mut pieces = Vec::new();
+        if let Some(parent_sha) = parent_sha {
+            for (path, ranges) in deleted {
+                if exclusions.is_excluded(&path) {
+                    continue;
+                }
+                let spans = blame_spans(&repo, &parent_sha, &path)?;
+                for (start, end) in ranges {
+                    for span in &spans {
+                        let span_end = span.final_start + span.lines - 1;
+                        let (s, e) = (start.max(span.final_start), end.min(span_end));
+                        if s > e {
+                            continue;
+                        }
+                        pieces.push(DeletedPiece {
+                            file_path: path.clone(),
+                            start_line: s,
+                            end_line: e,
+                            origin_commit_sha: span.commit_sha.clone(),
+                            orig_path: span.orig_path.clone(),
+                            shift: span.orig_start - span.final_start,
+                        });
+                    }
+                }
+            }
+        }
+        pieces
+    };
+
+    let mut origin_rows: HashMap<(String, String), Vec<LineAttributionRow>> = HashMap::new();
+    let mut ranges = Vec::new();
+    for piece in pieces {
+        let key = (piece.origin_commit_sha.clone(), piece.orig_path.clone());
+        if !origin_rows.contains_key(&key) {
+            let rows = if origin_rows.len() < MAX_DELETION_ORIGINS {
+                fetch_line_attributions(db, repo_id, &key.0, &key.1).await?
+            } else {
+                Vec::new()
+            };
+            origin_rows.insert(key.clone(), rows);
+        }
+        let rows = &origin_rows[&key];
+
+        let (orig_start, orig_end) = (piece.start_line + piece.shift, piece.end_line + piece.shift);
+        for (start, end, row) in split_by_attribution(orig_start, orig_end, rows) {
+            ranges.push(DeletedRange {
+                file_path: piece.file_path.clone(),
+                start_line: start - piece.shift,
+                end_line: end - piece.shift,
+                origin_commit_sha: piece.origin_commit_sha.clone(),
+                author_type: row
+                    .map(|row| row.author_type.clone())
+                    .unwrap_or_else(|| "human".to_string()),
+                session_id: row.and_then(|row| row.session_id.clone()),
+                tool: row.and_then(|row| row.tool.clone()),
+            });
+        }
+    }
+
+    Ok(ranges)
+}
+
+/// Compute and store a commit's deleted-line attribution and stats
+pub async fn compute_commit_deletions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<CommitDeletionStats, String> {
+    let ranges = compute_deleted_ranges(db, repo_id, commit_sha).await?;
+    let removed_by: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT session_id FROM session_links
+        WHERE repo_id = ? AND commit_sha = ?
+        ORDER BY confidence DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    let stats = summarize_deletions(commit_sha, &ranges, removed_by);
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM deleted_line_attributions WHERE repo_id = ? AND commit_sha = ?")
+        .bind(repo_id)
+        .bind(commit_sha)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for range in &ranges {
+        sqlx::query(
+            r#"
+            INSERT INTO deleted_line_attributions (
+                repo_id, commit_sha, file_path, start_line, end_line,
+                origin_commit_sha, author_type, session_id, tool
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(commit_sha)
+        .bind(&range.file_path)
+        .bind(range.start_line)
+        .bind(range.end_line)
+        .bind(&range.origin_commit_sha)
+        .bind(&range.author_type)
+        .bind(&range.session_id)
+        .bind(&range.tool)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO commit_deletion_stats (
+            repo_id, commit_sha, deleted_lines, deleted_ai_lines,
+            deleted_human_lines, removed_by_session_id
+        )
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, commit_sha) DO UPDATE SET
+            deleted_lines = excluded.deleted_lines,
+            deleted_ai_lines = excluded.deleted_ai_lines,
+            deleted_human_lines = excluded.deleted_human_lines,
+            removed_by_session_id = excluded.removed_by_session_id,
+            computed_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(stats.deleted_lines)
+    .bind(stats.deleted_ai_lines)
+    .bind(stats.deleted_human_lines)
+    .bind(&stats.removed_by_session_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(stats)
+}
+
+/// A commit's cached deletion stats, computing them if needed
+pub async fn ensure_commit_deletions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<CommitDeletionStats, String> {
+    let cached = sqlx::query_as::<_, CommitDeletionStats>(
+        r#"
+        SELECT commit_sha, deleted_lines, deleted_ai_lines, deleted_human_lines,
+               removed_by_session_id
+        FROM commit_deletion_stats
+        WHERE repo_id = ? AND commit_sha = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    match cached {
+        Some(stats) => Ok(stats),
+        None => compute_commit_deletions(db, repo_id, commit_sha).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::Path;
+
+    fn commit_file(repo: &Repository, dir: &Path, content: &str, message: &str) -> String {
+        std::fs::write(dir.join("lib.rs"), content).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+            .to_string()
+    }
+
+    async fn setup(repo_path: &str) -> sqlx::SqlitePool {
+        let pool = crate::test_pool().await;
+
+        sqlx::query("INSERT INTO repos (id, path) VALUES (1, ?)")
+            .bind(repo_path)
+            .execute(&pool)
+            .await
+            .expect("repo");
+        pool
+    }
+
+    #[test]
+    fn removed_lines_are_attributed_to_their_author() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit_file(&repo, dir.path(), "a\nb\nc\nd\n", "first");
+        // Removes "b" (written by an agent) and "c" (human)
+        let second = commit_file(&repo, dir.path(), "a\nd\n", "second");
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        runtime.block_on(async {
+            let pool = setup(&dir.path().to_string_lossy()).await;
+            sqlx::query(
+                r#"
+                INSERT INTO line_attributions
+                    (repo_id, commit_sha, file_path, start_line, end_line, session_id, author_type, tool)
+                VALUES (1, ?, 'lib.rs', 1, 2, 's1', 'ai_agent', 'claude_code')
+                "#,
+            )
+            .bind(&first)
+            .execute(&pool)
+            .await
+            .expect("attribution");
+
+            let stats = ensure_commit_deletions(&pool, 1, &second)
+                .await
+                .expect("deletions");
+            assert_eq!(stats.deleted_lines, 2);
+            assert_eq!(stats.deleted_ai_lines, 1);
+            assert_eq!(stats.deleted_human_lines, 1);
+            assert_eq!(stats.removed_by_session_id, None);
+
+            let origins: Vec<(i32, String, Option<String>)> = sqlx::query_as(
+                r#"
+                SELECT start_line, author_type, session_id
+                FROM deleted_line_attributions
+                WHERE commit_sha = ?
+                ORDER BY start_line
+                "#,
+            )
+            .bind(&second)
+            .fetch_all(&pool)
+            .await
+            .expect("ranges");
+            assert_eq!(
+                origins,
+                vec![
+                    (2, "ai_agent".to_string(), Some("s1".to_string())),
+                    (3, "human".to_string(), None),
+                ]
+            );
+
+            // Re-attributing the origin commit drops the cached stats
+            sqlx::query("DELETE FROM line_attributions WHERE commit_sha = ?")
+                .bind(&first)
+                .execute(&pool)
+                .await
+                .expect("delete");
+            let cached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM commit_deletion_stats")
+                .fetch_one(&pool)
+                .await
+                .expect("count");
+            assert_eq!(cached, 0);
+        });
+    }
+}
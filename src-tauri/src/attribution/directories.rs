@@ -0,0 +1,334 @@
+//! Per-directory attribution rollups
+//!
+//! Aggregates attribution for the commits in a range by directory prefix
+//! (e.g. depth 2 turns `src-tauri/src/linking.rs` into `src-tauri/src`), so
+//! teams can see which subsystems are most AI-authored. A commit's lines are
+//! the lines it added; attributed lines are split by author type and the
+//! rest count as human.
+//!
+//! Rollups are cached per commit range and depth, and recomputed when the
+//! repo's line attributions change.
+
+use super::exclusions::load_exclusion_rules;
+use super::git_utils::commit_line_additions;
+use super::line_attribution::fetch_line_attributions_for_commit;
+use super::source_lens::{build_line_meta, LineAttributionRow};
+use super::utils::fetch_repo_root;
+use git2::Repository;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Most commits walked for one range
+const MAX_RANGE_COMMITS: usize = 2000;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryRollup {
+    /// Exclusive start of the range (None: from the root commit)
+    pub from_sha: Option<String>,
+    pub to_sha: String,
+    pub depth: u32,
+    pub commits: u32,
+    /// Most AI lines first
+    pub directories: Vec<DirectoryAttribution>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryAttribution {
+    /// Directory prefix (`.` for files at the repo root)
+    pub path: String,
+    pub files: u32,
+    pub total_lines: u32,
+    pub human_lines: u32,
+    pub ai_agent_lines: u32,
+    pub ai_assist_lines: u32,
+    pub collaborative_lines: u32,
+    pub ai_percentage: f64,
+}
+
+/// Lines one commit added to one file, split by author type
+#[derive(Debug, Clone, Default)]
+pub struct FileContribution {
+    pub file_path: String,
+    pub added_lines: u32,
+    pub ai_agent_lines: u32,
+    pub ai_assist_lines: u32,
+    pub collaborative_lines: u32,
+}
+
+/// Directory prefix of a repo-relative file path, at most `depth` levels
+pub fn directory_prefix(file_path: &str, depth: u32) -> String {
+    let mut parts: Vec<&str> = file_path.split('/').filter(|p| !p.is_empty()).collect();
+    parts.pop();
+    if parts.is_empty() {
+        return ".".to_string();
+    }
+    parts.truncate(depth.max(1) as usize);
+    parts.join("/")
+}
+
+/// Group file contributions by directory prefix
+pub fn rollup_directories(depth: u32, entries: &[FileContribution]) -> Vec<DirectoryAttribution> {
+    let mut dirs: BTreeMap<String, (DirectoryAttribution, HashSet<&str>)> = BTreeMap::new();
+    for entry in entries {
+        let path = directory_prefix(&entry.file_path, depth);
+        let (dir, files) = dirs.entry(path.clone()).or_insert_with(|| {
+            (
+                DirectoryAttribution {
+                    path,
+                    files: 0,
+                    total_lines: 0,
+                    human_lines: 0,
+                    ai_agent_lines: 0,
+                    ai_assist_lines: 0,
+                    collaborative_lines: 0,
+                    ai_percentage: 0.0,
+                },
+                HashSet::new(),
+            )
+        });
+        let ai_lines = entry.ai_agent_lines + entry.ai_assist_lines + entry.collaborative_lines;
+        let total = entry.added_lines.max(ai_lines);
+        files.insert(entry.file_path.as_str());
+        dir.total_lines += total;
+        dir.human_lines += total - ai_lines;
+        dir.ai_agent_lines += entry.ai_agent_lines;
+        dir.ai_assist_lines += entry.ai_assist_lines;
+        dir.collaborative_lines += entry.collaborative_lines;
+    }
+
+    let mut rollup: Vec<DirectoryAttribution> = dirs
+        .into_values()
+        .map(|(mut dir, files)| {
+            dir.files = files.len() as u32;
+            let ai_lines = dir.ai_agent_lines + dir.ai_assist_lines + dir.collaborative_lines;
+            if dir.total_lines > 0 {
+                dir.ai_percentage = ai_lines as f64 / dir.total_lines as f64 * 100.0;
+            }
+            dir
+        })
+        .collect();
+    rollup.sort_by(|a, b| {
+        let ai =
+            |d: &DirectoryAttribution| d.ai_agent_lines + d.ai_assist_lines + d.collaborative_lines;
+        ai(b).cmp(&ai(a)).then_with(|| a.path.cmp(&b.path))
+    });
+    rollup
+}
+
+/// Commits reachable from `to` but not from `from`, newest first
+pub(super) fn range_commits(
+    repo: &Repository,
+    from: Option<&str>,
+    to: &str,
+) -> Result<(Option<String>, String, Vec<String>), String> {
+    let resolve = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|obj| obj.peel_to_commit())
+            .map(|commit| commit.id())
+            .map_err(|e| format!("Failed to resolve {}: {}", spec, e))
+    };
+    let to_oid = resolve(to)?;
+    let from_oid = from.map(resolve).transpose()?;
+
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.push(to_oid).map_err(|e| e.to_string())?;
+    if let Some(from_oid) = from_oid {
+        walk.hide(from_oid).map_err(|e| e.to_string())?;
+    }
+    let shas = walk
+        .take(MAX_RANGE_COMMITS)
+        .filter_map(|oid| oid.ok().map(|oid| oid.to_string()))
+        .collect();
+
+    Ok((
+        from_oid.map(|oid| oid.to_string()),
+        to_oid.to_string(),
+        shas,
+    ))
+}
+
+/// Changes whenever line attributions are added or removed for the repo
+async fn attribution_fingerprint(db: &SqlitePool, repo_id: i64) -> Result<String, String> {
+    let (count, max_id): (i64, Option<i64>) =
+        sqlx::query_as("SELECT COUNT(*), MAX(id) FROM line_attributions WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_one(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    Ok(format!("{}:{}", count, max_id.unwrap_or(0)))
+}
+
+/// Roll attribution up by directory for the commits in `from..to`
+/// (`to` defaults to HEAD), using the cache when it is current.
+pub async fn get_directory_rollup(
+    db: &SqlitePool,
+    repo_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+    depth: u32,
+) -> Result<DirectoryRollup, String> {
+    let depth = depth.max(1);
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let (from_sha, to_sha, shas, additions) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let (from_sha, to_sha, shas) = range_commits(&repo, from, to.unwrap_or("HEAD"))?;
+        let additions = shas
+            .iter()
+            .map(|sha| commit_line_additions(&repo, sha).unwrap_or_default())
+            .collect::<Vec<_>>();
+        (from_sha, to_sha, shas, additions)
+    };
+
+    let range_key = format!("{}..{}", from_sha.as_deref().unwrap_or(""), to_sha);
+    let fingerprint = attribution_fingerprint(db, repo_id).await?;
+    let cached: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT payload
+        FROM attribution_directory_rollups
+        WHERE repo_id = ? AND range_key = ? AND depth = ? AND fingerprint = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&range_key)
+    .bind(depth)
+    .bind(&fingerprint)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    if let Some(rollup) = cached.and_then(|payload| serde_json::from_str(&payload).ok()) {
+        return Ok(rollup);
+    }
+
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    let mut entries = Vec::new();
+    for (sha, added) in shas.iter().zip(additions) {
+        let mut by_file: HashMap<String, Vec<LineAttributionRow>> = HashMap::new();
+        for row in fetch_line_attributions_for_commit(db, repo_id, sha).await? {
+            by_file
+                .entry(row.file_path)
+                .or_default()
+                .push(LineAttributionRow {
+                    start_line: row.start_line,
+                    end_line: row.end_line,
+                    session_id: row.session_id,
+                    author_type: row.author_type,
+                    ai_percentage: row.ai_percentage,
+                    tool: row.tool,
+                    model: row.model,
+                    source: row.source,
+                    confidence: row.confidence,
+                    trace_available: 0,
+                });
+        }
+
+        let files: HashSet<&String> = added.keys().chain(by_file.keys()).collect();
+        for file_path in files {
+            if exclusions.is_excluded(file_path) {
+                continue;
+            }
+            let mut entry = FileContribution {
+                file_path: file_path.clone(),
+                added_lines: added.get(file_path).copied().unwrap_or(0),
+                ..Default::default()
+            };
+            if let Some(rows) = by_file.get(file_path) {
+                let max_line = rows.iter().map(|r| r.end_line).max().unwrap_or(0).max(0);
+                for meta in build_line_meta(max_line as usize, rows) {
+                    match meta.author_type.as_str() {
+                        "ai_agent" => entry.ai_agent_lines += 1,
+                        "ai_tab" => entry.ai_assist_lines += 1,
+                        "mixed" => entry.collaborative_lines += 1,
+                        _ => {}
+                    }
+                }
+            }
+            entries.push(entry);
+        }
+    }
+
+    let rollup = DirectoryRollup {
+        from_sha,
+        to_sha,
+        depth,
+        commits: shas.len() as u32,
+        directories: rollup_directories(depth, &entries),
+    };
+
+    let payload = serde_json::to_string(&rollup).map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO attribution_directory_rollups (repo_id, range_key, depth, fingerprint, payload)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, range_key, depth) DO UPDATE SET
+            fingerprint = excluded.fingerprint,
+            payload = excluded.payload,
+            computed_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&range_key)
+    .bind(depth)
+    .bind(&fingerprint)
+    .bind(&payload)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(rollup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(path: &str, added: u32, agent: u32, mixed: u32) -> FileContribution {
+        FileContribution {
+            file_path: path.to_string(),
+            added_lines: added,
+            ai_agent_lines: agent,
+            collaborative_lines: mixed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn prefixes_stop_at_depth() {
+        assert_eq!(
+            directory_prefix("src-tauri/src/linking.rs", 2),
+            "src-tauri/src"
+        );
+        assert_eq!(
+            directory_prefix("src-tauri/src/attribution/mod.rs", 1),
+            "src-tauri"
+        );
+        assert_eq!(directory_prefix("src/App.tsx", 3), "src");
+        assert_eq!(directory_prefix("README.md", 2), ".");
+    }
+
+    #[test]
+    fn directories_sum_files_and_rank_by_ai_lines() {
+        let rollup = rollup_directories(
+            1,
+            &[
+                contribution("src/a.ts", 10, 8, 0),
+                contribution("src/b.ts", 10, 0, 2),
+                contribution("src/a.ts", 5, 0, 0),
+                contribution("docs/guide.md", 20, 0, 0),
+                // Attributed lines beyond the diff's count still count
+                contribution("api/x.rs", 0, 30, 0),
+            ],
+        );
+
+        let summary = rollup
+            .iter()
+            .map(|d| (d.path.as_str(), d.files, d.total_lines, d.human_lines))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("api", 1, 30, 0), ("src", 2, 25, 15), ("docs", 1, 20, 20)]
+        );
+        assert!((rollup[1].ai_percentage - 40.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,187 @@
+//! Per-repo attribution exclusion rules
+//!
+//! Lockfiles, snapshots, and generated code inflate line counts without
+//! saying anything about who wrote the code. Files matching a repo's
+//! exclusion globs get no line attributions and are left out of stats.
+//!
+//! Globs follow gitignore conventions: a pattern without `/` matches a file
+//! or directory name at any depth (`package-lock.json`, `*.generated.*`),
+//! a pattern with `/` is matched from the repo root (`src/gen/**`), and a
+//! pattern naming a directory also excludes everything beneath it.
+
+use regex::Regex;
+use sqlx::SqlitePool;
+
+/// Compiled exclusion globs for one repo
+#[derive(Debug, Default)]
+pub struct ExclusionRules {
+    patterns: Vec<Regex>,
+}
+
+impl ExclusionRules {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns.iter().filter_map(|p| glob_to_regex(p)).collect(),
+        }
+    }
+
+    /// True if a repo-relative path matches any exclusion glob
+    pub fn is_excluded(&self, file_path: &str) -> bool {
+        let path = file_path.replace('\\', "/");
+        let path = path.trim_start_matches("./");
+        self.patterns.iter().any(|re| re.is_match(path))
+    }
+}
+
+/// Translate a gitignore-style glob into an anchored regex
+fn glob_to_regex(pattern: &str) -> Option<Regex> {
+    let body = pattern.trim_start_matches('/').trim_end_matches('/');
+    if body.is_empty() {
+        return None;
+    }
+    let rooted = pattern.starts_with('/') || body.contains('/');
+
+    let mut re = String::from(if rooted { "^" } else { "(?:^|/)" });
+    let chars: Vec<char> = body.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // `**/` matches zero or more directories
+                if chars.get(i + 2) == Some(&'/') {
+                    re.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    re.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+        i += 1;
+    }
+    re.push_str("(?:/.*)?$");
+
+    Regex::new(&re).ok()
+}
+
+/// Normalize exclusion globs: trimmed, `\` as `/`, deduplicated and sorted
+pub fn normalize_exclusions(patterns: Vec<String>) -> Vec<String> {
+    let mut patterns: Vec<String> = patterns
+        .into_iter()
+        .map(|p| p.trim().replace('\\', "/"))
+        .map(|p| p.trim_start_matches("./").to_string())
+        .filter(|p| !p.is_empty() && glob_to_regex(p).is_some())
+        .collect();
+    patterns.sort();
+    patterns.dedup();
+    patterns
+}
+
+pub async fn fetch_exclusion_patterns(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<Vec<String>, String> {
+    sqlx::query_scalar(
+        "SELECT pattern FROM attribution_exclusions WHERE repo_id = ? ORDER BY pattern",
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Load a repo's exclusion rules; no rules if they cannot be read
+pub async fn load_exclusion_rules(db: &SqlitePool, repo_id: i64) -> ExclusionRules {
+    let patterns = fetch_exclusion_patterns(db, repo_id)
+        .await
+        .unwrap_or_default();
+    ExclusionRules::new(&patterns)
+}
+
+/// Replace a repo's exclusion globs and drop its cached contribution stats
+/// (and the time series and directory rollups built from them) so they are
+/// recomputed under the new rules.
+pub async fn store_exclusion_patterns(
+    db: &SqlitePool,
+    repo_id: i64,
+    patterns: &[String],
+) -> Result<(), String> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM attribution_exclusions WHERE repo_id = ?")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for pattern in patterns {
+        sqlx::query("INSERT INTO attribution_exclusions (repo_id, pattern) VALUES (?, ?)")
+            .bind(repo_id)
+            .bind(pattern)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+    for table in [
+        "commit_contribution_stats",
+        "commit_tool_stats",
+        "attribution_timeseries_state",
+        "attribution_directory_rollups",
+        "commit_deletion_stats",
+        "deleted_line_attributions",
+    ] {
+        sqlx::query(&format!("DELETE FROM {table} WHERE repo_id = ?"))
+            .bind(repo_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(patterns: &[&str]) -> ExclusionRules {
+        ExclusionRules::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn bare_names_match_at_any_depth() {
+        let rules = rules(&["package-lock.json", "*.generated.*"]);
+        assert!(rules.is_excluded("package-lock.json"));
+        assert!(rules.is_excluded("apps/web/package-lock.json"));
+        assert!(rules.is_excluded("src/api.generated.ts"));
+        assert!(!rules.is_excluded("src/package-lock.json.md"));
+        assert!(!rules.is_excluded("src/generated.ts"));
+    }
+
+    #[test]
+    fn rooted_and_directory_patterns() {
+        let rules = rules(&["src/gen/**", "__snapshots__", "/dist/"]);
+        assert!(rules.is_excluded("src/gen/api/client.rs"));
+        assert!(!rules.is_excluded("lib/src/gen/client.rs"));
+        assert!(rules.is_excluded("ui/__snapshots__/App.test.tsx.snap"));
+        assert!(rules.is_excluded("dist/index.js"));
+        assert!(!rules.is_excluded("packages/dist/index.js"));
+    }
+
+    #[test]
+    fn normalization_drops_blanks_and_duplicates() {
+        let patterns = normalize_exclusions(vec![
+            " ./yarn.lock ".to_string(),
+            "yarn.lock".to_string(),
+            "".to_string(),
+            "/".to_string(),
+        ]);
+        assert_eq!(patterns, vec!["yarn.lock".to_string()]);
+    }
+}
@@ -1,43 +1,170 @@
 //! Git utilities for diff computation and rewrite key generation
 
 use super::line_attribution::{ChangeKind, ChangedRange};
-use git2::{DiffFormat, DiffOptions, Oid, Repository};
+use git2::{Commit, Delta, Diff, DiffFindOptions, DiffOptions, Oid, Repository, Tree};
 use sha2::{Digest, Sha256};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Trees a commit is diffed against: one per parent, or `None` for a root
+/// commit. A merge has several, and only changes against every parent are
+/// the merge's own (conflict resolutions and edits made while merging).
+fn parent_trees<'r>(commit: &Commit<'r>) -> Result<Vec<Option<Tree<'r>>>, String> {
+    if commit.parent_count() == 0 {
+        return Ok(vec![None]);
+    }
+    commit
+        .parents()
+        .map(|parent| parent.tree().map(Some).map_err(|e| e.to_string()))
+        .collect()
+}
 
-/// List files changed in a commit
-pub fn list_commit_files(repo: &Repository, commit_sha: &str) -> Result<Vec<String>, String> {
+/// Pair deleted and added files into renames, so a renamed file's diff
+/// holds only the lines that actually changed
+fn detect_renames(diff: &mut Diff<'_>) -> Result<(), String> {
+    let mut find = DiffFindOptions::new();
+    find.renames(true);
+    diff.find_similar(Some(&mut find))
+        .map_err(|e| e.to_string())
+}
+
+/// Files renamed in a commit, as new path -> old path (against the first
+/// parent)
+pub fn commit_renames(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Result<HashMap<String, String>, String> {
     let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
     let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let Ok(parent) = commit.parent(0) else {
+        return Ok(HashMap::new());
+    };
+    let parent_tree = parent.tree().map_err(|e| e.to_string())?;
     let tree = commit.tree().map_err(|e| e.to_string())?;
 
-    let parent_tree = if commit.parent_count() > 0 {
-        Some(
-            commit
-                .parent(0)
-                .map_err(|e| e.to_string())?
-                .tree()
-                .map_err(|e| e.to_string())?,
-        )
-    } else {
-        None
-    };
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)
+        .map_err(|e| e.to_string())?;
+    detect_renames(&mut diff)?;
+
+    Ok(diff
+        .deltas()
+        .filter(|delta| delta.status() == Delta::Renamed)
+        .filter_map(|delta| {
+            let old = delta.old_file().path()?.to_string_lossy().to_string();
+            let new = delta.new_file().path()?.to_string_lossy().to_string();
+            Some((new, old))
+        })
+        .collect())
+}
+
+/// True if a commit has more than one parent
+pub fn is_merge_commit(repo: &Repository, commit_sha: &str) -> Result<bool, String> {
+    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    Ok(commit.parent_count() > 1)
+}
+
+/// List files changed in a commit (for merges, files that differ from
+/// every parent). Renamed files are listed under their new path only.
+pub fn list_commit_files(repo: &Repository, commit_sha: &str) -> Result<Vec<String>, String> {
+    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    let tree = commit.tree().map_err(|e| e.to_string())?;
 
     let mut options = DiffOptions::new();
     options.context_lines(0);
 
-    let diff = repo
-        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))
-        .map_err(|e| e.to_string())?;
+    let mut common: Option<HashSet<String>> = None;
+    for parent_tree in parent_trees(&commit)? {
+        let mut diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut options))
+            .map_err(|e| e.to_string())?;
+        detect_renames(&mut diff)?;
+
+        let paths = diff
+            .deltas()
+            .filter_map(|delta| delta.new_file().path())
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<HashSet<_>>();
+        common = Some(match common {
+            Some(common) => common.intersection(&paths).cloned().collect(),
+            None => paths,
+        });
+    }
+
+    Ok(common.unwrap_or_default().into_iter().collect())
+}
 
-    let mut paths = HashSet::new();
-    for delta in diff.deltas() {
-        if let Some(path) = delta.new_file().path() {
-            paths.insert(path.to_string_lossy().to_string());
+/// Lines added per file in a commit (for merges, only lines that are new
+/// against every parent)
+pub fn commit_line_additions(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Result<HashMap<String, u32>, String> {
+    let mut additions = HashMap::new();
+    for (path, ranges) in collect_changed_ranges_by_file(repo, commit_sha)? {
+        let added: i32 = ranges
+            .iter()
+            .map(|range| range.end_line - range.start_line + 1)
+            .sum();
+        if added > 0 {
+            additions.insert(path.replace('\\', "/"), added as u32);
         }
     }
 
-    Ok(paths.into_iter().collect())
+    Ok(additions)
+}
+
+/// Lines a commit removed, per file, as inclusive line ranges in its first
+/// parent's version of the file (keyed by the path there). Root and merge
+/// commits remove nothing of their own.
+pub fn commit_deleted_ranges(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Result<(Option<String>, HashMap<String, Vec<(i32, i32)>>), String> {
+    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+    if commit.parent_count() != 1 {
+        return Ok((None, HashMap::new()));
+    }
+    let parent = commit.parent(0).map_err(|e| e.to_string())?;
+    let parent_tree = parent.tree().map_err(|e| e.to_string())?;
+    let tree = commit.tree().map_err(|e| e.to_string())?;
+
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let mut diff = repo
+        .diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut opts))
+        .map_err(|e| e.to_string())?;
+    detect_renames(&mut diff)?;
+
+    let mut deleted: HashMap<String, Vec<(i32, i32)>> = HashMap::new();
+    diff.foreach(
+        &mut |_delta, _progress| true,
+        None,
+        None,
+        Some(&mut |delta, _hunk, line| {
+            let (Some(path), Some(old_lineno)) = (delta.old_file().path(), line.old_lineno())
+            else {
+                return true;
+            };
+            if line.origin() != '-' {
+                return true;
+            }
+            let line_no = old_lineno as i32;
+            let ranges = deleted
+                .entry(path.to_string_lossy().replace('\\', "/"))
+                .or_default();
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == line_no => *end = line_no,
+                _ => ranges.push((line_no, line_no)),
+            }
+            true
+        }),
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok((Some(parent.id().to_string()), deleted))
 }
 
 #[derive(Default)]
@@ -67,29 +194,58 @@ fn flush_range(state: &mut RangeState, ranges: &mut Vec<ChangedRange>) {
 pub fn collect_changed_ranges_by_file(
     repo: &Repository,
     commit_sha: &str,
+) -> Result<HashMap<String, Vec<ChangedRange>>, String> {
+    commit_changed_ranges(repo, commit_sha, None)
+}
+
+/// Collect changed line ranges for a file in a commit (by its path in the
+/// commit; a file renamed here is diffed against its old path)
+pub fn collect_changed_ranges(
+    repo: &Repository,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<Vec<ChangedRange>, String> {
+    Ok(commit_changed_ranges(repo, commit_sha, Some(file_path))?
+        .remove(file_path)
+        .unwrap_or_default())
+}
+
+/// Changed ranges of a commit against each of its parents, reduced to the
+/// lines changed against all of them
+fn commit_changed_ranges(
+    repo: &Repository,
+    commit_sha: &str,
+    pathspec: Option<&str>,
 ) -> Result<HashMap<String, Vec<ChangedRange>>, String> {
     let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
     let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
     let tree = commit.tree().map_err(|e| e.to_string())?;
 
-    let parent_tree = if commit.parent_count() > 0 {
-        Some(
-            commit
-                .parent(0)
-                .map_err(|e| e.to_string())?
-                .tree()
-                .map_err(|e| e.to_string())?,
-        )
-    } else {
-        None
-    };
+    let per_parent = parent_trees(&commit)?
+        .iter()
+        .map(|parent_tree| changed_ranges_against(repo, parent_tree.as_ref(), &tree, pathspec))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(intersect_changed_ranges(per_parent))
+}
 
+/// Changed line ranges per file between a parent tree and a commit tree
+///
+/// `pathspec` filters by the file's path in the commit. It is applied after
+/// rename detection, which needs the old path in the diff.
+fn changed_ranges_against(
+    repo: &Repository,
+    parent_tree: Option<&Tree<'_>>,
+    tree: &Tree<'_>,
+    pathspec: Option<&str>,
+) -> Result<HashMap<String, Vec<ChangedRange>>, String> {
     let mut opts = DiffOptions::new();
     opts.context_lines(0);
 
-    let diff = repo
-        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
+    let mut diff = repo
+        .diff_tree_to_tree(parent_tree, Some(tree), Some(&mut opts))
         .map_err(|e| e.to_string())?;
+    detect_renames(&mut diff)?;
 
     let mut ranges_by_file: HashMap<String, Vec<ChangedRange>> = HashMap::new();
     let mut states: HashMap<String, RangeState> = HashMap::new();
@@ -104,6 +260,9 @@ pub fn collect_changed_ranges_by_file(
                 return true;
             };
             let path = path.to_string_lossy().to_string();
+            if pathspec.is_some_and(|pathspec| pathspec != path) {
+                return true;
+            }
             let ranges = ranges_by_file.entry(path.clone()).or_default();
             let state = states.entry(path).or_default();
 
@@ -155,113 +314,64 @@ pub fn collect_changed_ranges_by_file(
     Ok(ranges_by_file)
 }
 
-/// Collect changed line ranges for a file in a commit
-pub fn collect_changed_ranges(
-    repo: &Repository,
-    commit_sha: &str,
-    file_path: &str,
-) -> Result<Vec<ChangedRange>, String> {
-    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
-    let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
-    let tree = commit.tree().map_err(|e| e.to_string())?;
+/// Keep the lines changed against every parent. A line counts as added only
+/// if no parent had a version of it; otherwise it is modified.
+fn intersect_changed_ranges(
+    mut per_parent: Vec<HashMap<String, Vec<ChangedRange>>>,
+) -> HashMap<String, Vec<ChangedRange>> {
+    if per_parent.len() <= 1 {
+        return per_parent.pop().unwrap_or_default();
+    }
 
-    let parent_tree = if commit.parent_count() > 0 {
-        Some(
-            commit
-                .parent(0)
-                .map_err(|e| e.to_string())?
-                .tree()
-                .map_err(|e| e.to_string())?,
-        )
-    } else {
-        None
+    let line_kinds = |ranges: &[ChangedRange]| {
+        ranges
+            .iter()
+            .flat_map(|range| {
+                (range.start_line..=range.end_line).map(move |line| (line, range.kind))
+            })
+            .collect::<BTreeMap<i32, ChangeKind>>()
     };
 
-    let mut opts = DiffOptions::new();
-    opts.pathspec(file_path);
-    opts.context_lines(0);
-
-    let diff = repo
-        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
-        .map_err(|e| e.to_string())?;
-
-    let mut ranges: Vec<ChangedRange> = Vec::new();
-    let mut current_start: Option<i32> = None;
-    let mut previous_line: Option<i32> = None;
-    let mut current_kind: Option<ChangeKind> = None;
-    let mut saw_deletion = false;
-
-    diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
-        if line.origin() == '-' {
-            if let (Some(start), Some(prev), Some(kind)) =
-                (current_start, previous_line, current_kind)
-            {
-                ranges.push(ChangedRange {
-                    start_line: start,
-                    end_line: prev,
-                    kind,
-                });
-                current_start = None;
-                previous_line = None;
-                current_kind = None;
-            }
-            saw_deletion = true;
-        } else if line.origin() == '+' {
-            if let Some(new_lineno) = line.new_lineno() {
-                let new_line = new_lineno as i32;
-                match (current_start, previous_line) {
-                    (Some(start), Some(prev)) if new_line == prev + 1 => {
-                        previous_line = Some(new_line);
-                        current_start = Some(start);
-                    }
-                    _ => {
-                        if let (Some(start), Some(prev), Some(kind)) =
-                            (current_start, previous_line, current_kind)
-                        {
-                            ranges.push(ChangedRange {
-                                start_line: start,
-                                end_line: prev,
-                                kind,
-                            });
-                        }
-                        current_start = Some(new_line);
-                        previous_line = Some(new_line);
-                        current_kind = Some(if saw_deletion {
-                            ChangeKind::Modified
-                        } else {
-                            ChangeKind::Added
-                        });
-                    }
+    let first = per_parent.remove(0);
+    let mut merged = HashMap::new();
+    for (path, ranges) in first {
+        let others = per_parent
+            .iter()
+            .map(|by_file| by_file.get(&path).map(|ranges| line_kinds(ranges)))
+            .collect::<Option<Vec<_>>>();
+        let Some(others) = others else {
+            continue;
+        };
+
+        let mut kept: Vec<ChangedRange> = Vec::new();
+        for (line, kind) in line_kinds(&ranges) {
+            let mut kind = kind;
+            let mut in_all = true;
+            for other in &others {
+                match other.get(&line) {
+                    Some(ChangeKind::Modified) => kind = ChangeKind::Modified,
+                    Some(ChangeKind::Added) => {}
+                    None => in_all = false,
                 }
             }
-        } else if current_start.is_some() {
-            if let (Some(start), Some(prev), Some(kind)) =
-                (current_start, previous_line, current_kind)
-            {
-                ranges.push(ChangedRange {
-                    start_line: start,
-                    end_line: prev,
+            if !in_all {
+                continue;
+            }
+            match kept.last_mut() {
+                Some(last) if last.end_line + 1 == line && last.kind == kind => {
+                    last.end_line = line;
+                }
+                _ => kept.push(ChangedRange {
+                    start_line: line,
+                    end_line: line,
                     kind,
-                });
+                }),
             }
-            current_start = None;
-            previous_line = None;
-            current_kind = None;
-            saw_deletion = false;
         }
-        true
-    })
-    .map_err(|e| e.to_string())?;
-
-    if let (Some(start), Some(prev), Some(kind)) = (current_start, previous_line, current_kind) {
-        ranges.push(ChangedRange {
-            start_line: start,
-            end_line: prev,
-            kind,
-        });
+        merged.insert(path, kept);
     }
 
-    Ok(ranges)
+    merged
 }
 
 /// Compute rewrite key (hash of normalized patch)
@@ -340,3 +450,97 @@ pub fn compute_rewrite_key(repo: &Repository, commit_sha: &str) -> Result<String
 fn normalize_patch_line(line: &str) -> String {
     line.chars().filter(|c| !c.is_whitespace()).collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::Path;
+
+    fn commit_file(repo: &Repository, dir: &Path, content: &str, parents: &[Oid]) -> Oid {
+        std::fs::write(dir.join("lib.rs"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parents = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect::<Vec<_>>();
+        let parent_refs = parents.iter().collect::<Vec<_>>();
+        repo.commit(None, &sig, &sig, "commit", &tree, &parent_refs)
+            .unwrap()
+    }
+
+    #[test]
+    fn merges_keep_only_lines_changed_against_every_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_file(&repo, dir.path(), "a\nb\nc\n", &[]);
+        let ours = commit_file(&repo, dir.path(), "a\nb\nC\n", &[base]);
+        let theirs = commit_file(&repo, dir.path(), "A\nb\nc\n", &[base]);
+        // Both sides merged, plus one line written during the merge
+        let merge = commit_file(&repo, dir.path(), "A\nb\nC\nd\n", &[ours, theirs]);
+        let merge = merge.to_string();
+
+        assert!(is_merge_commit(&repo, &merge).unwrap());
+        assert_eq!(list_commit_files(&repo, &merge).unwrap(), vec!["lib.rs"]);
+
+        let ranges = collect_changed_ranges(&repo, &merge, "lib.rs").unwrap();
+        let ranges = ranges
+            .iter()
+            .map(|r| (r.start_line, r.end_line, r.kind))
+            .collect::<Vec<_>>();
+        assert!(ranges == vec![(4, 4, ChangeKind::Added)]);
+
+        let additions = commit_line_additions(&repo, &merge).unwrap();
+        assert_eq!(additions.get("lib.rs"), Some(&1));
+
+        // A regular commit still diffs against its only parent
+        let ours = ours.to_string();
+        assert!(!is_merge_commit(&repo, &ours).unwrap());
+        assert_eq!(
+            commit_line_additions(&repo, &ours).unwrap().get("lib.rs"),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn renamed_files_keep_only_their_edited_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let commit_tree = |path: &str, content: &str, parents: &[&git2::Commit]| {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            let mut builder = repo.treebuilder(None).unwrap();
+            builder.insert(path, blob, 0o100644).unwrap();
+            let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+            repo.commit(None, &sig, &sig, "commit", &tree, parents)
+                .unwrap()
+        };
+
+        let body = (1..=12)
+            .map(|n| format!("fn f{n}() {{}}\n"))
+            .collect::<String>();
+        let base = commit_tree("src/old.rs", &body, &[]);
+        let base = repo.find_commit(base).unwrap();
+        let renamed = commit_tree("src/new.rs", &format!("{body}fn g() {{}}\n"), &[&base]);
+        let renamed = renamed.to_string();
+
+        assert_eq!(
+            list_commit_files(&repo, &renamed).unwrap(),
+            vec!["src/new.rs"]
+        );
+        assert_eq!(
+            commit_renames(&repo, &renamed).unwrap().get("src/new.rs"),
+            Some(&"src/old.rs".to_string())
+        );
+
+        let ranges = collect_changed_ranges(&repo, &renamed, "src/new.rs").unwrap();
+        let ranges = ranges
+            .iter()
+            .map(|r| (r.start_line, r.end_line, r.kind))
+            .collect::<Vec<_>>();
+        assert!(ranges == vec![(13, 13, ChangeKind::Added)]);
+    }
+}
@@ -0,0 +1,332 @@
+//! Per-language attribution breakdown
+//!
+//! Files are classified by extension (or, for files like `Dockerfile`, by
+//! name). A repo can override the mapping, e.g. to count `.mdx` as Markdown
+//! or `.h` as C++. Unknown files are counted as "Other".
+
+use super::exclusions::ExclusionRules;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+
+/// Language for files that match no mapping
+pub const OTHER_LANGUAGE: &str = "Other";
+
+/// Built-in mapping from lowercase extension to language
+const EXTENSION_LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "Rust"),
+    ("ts", "TypeScript"),
+    ("tsx", "TypeScript"),
+    ("mts", "TypeScript"),
+    ("cts", "TypeScript"),
+    ("js", "JavaScript"),
+    ("jsx", "JavaScript"),
+    ("mjs", "JavaScript"),
+    ("cjs", "JavaScript"),
+    ("py", "Python"),
+    ("go", "Go"),
+    ("java", "Java"),
+    ("kt", "Kotlin"),
+    ("kts", "Kotlin"),
+    ("swift", "Swift"),
+    ("c", "C"),
+    ("h", "C"),
+    ("cc", "C++"),
+    ("cpp", "C++"),
+    ("cxx", "C++"),
+    ("hpp", "C++"),
+    ("cs", "C#"),
+    ("rb", "Ruby"),
+    ("php", "PHP"),
+    ("scala", "Scala"),
+    ("sh", "Shell"),
+    ("bash", "Shell"),
+    ("zsh", "Shell"),
+    ("sql", "SQL"),
+    ("html", "HTML"),
+    ("css", "CSS"),
+    ("scss", "CSS"),
+    ("vue", "Vue"),
+    ("svelte", "Svelte"),
+    ("md", "Markdown"),
+    ("mdx", "Markdown"),
+    ("json", "JSON"),
+    ("yaml", "YAML"),
+    ("yml", "YAML"),
+    ("toml", "TOML"),
+    ("xml", "XML"),
+    ("proto", "Protobuf"),
+];
+
+/// Built-in mapping from lowercase file name to language
+const FILE_NAME_LANGUAGES: &[(&str, &str)] = &[
+    ("dockerfile", "Dockerfile"),
+    ("makefile", "Makefile"),
+    ("cmakelists.txt", "CMake"),
+];
+
+/// Language classification with a repo's overrides applied
+#[derive(Debug, Default)]
+pub struct LanguageMap {
+    overrides: HashMap<String, String>,
+}
+
+impl LanguageMap {
+    pub fn new(overrides: &BTreeMap<String, String>) -> Self {
+        Self {
+            overrides: overrides
+                .iter()
+                .map(|(pattern, language)| (pattern.clone(), language.clone()))
+                .collect(),
+        }
+    }
+
+    /// Language of a repo-relative path: overrides first (file name, then
+    /// extension), then the built-in mappings
+    pub fn language_for(&self, file_path: &str) -> String {
+        let file_name = file_path
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(file_path)
+            .to_lowercase();
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_string())
+            .filter(|_| !file_name.starts_with('.') || file_name.matches('.').count() > 1);
+
+        if let Some(language) = self.overrides.get(&file_name) {
+            return language.clone();
+        }
+        if let Some(language) = extension.as_ref().and_then(|ext| self.overrides.get(ext)) {
+            return language.clone();
+        }
+        let builtin = |table: &[(&str, &'static str)], key: &str| {
+            table
+                .iter()
+                .find(|(pattern, _)| *pattern == key)
+                .map(|(_, language)| language.to_string())
+        };
+        builtin(FILE_NAME_LANGUAGES, &file_name)
+            .or_else(|| extension.and_then(|ext| builtin(EXTENSION_LANGUAGES, &ext)))
+            .unwrap_or_else(|| OTHER_LANGUAGE.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: u32,
+    pub total_lines: u32,
+    /// AI-agent, AI-assist, and collaborative lines
+    pub ai_lines: u32,
+    pub human_lines: u32,
+    pub ai_percentage: f64,
+}
+
+/// Sum per-file `(path, ai_lines, total_lines)` into per-language stats,
+/// most lines first. Excluded files are skipped.
+pub fn language_breakdown<'a>(
+    map: &LanguageMap,
+    exclusions: &ExclusionRules,
+    files: impl IntoIterator<Item = (&'a str, u32, u32)>,
+) -> Vec<LanguageStats> {
+    let mut by_language: BTreeMap<String, LanguageStats> = BTreeMap::new();
+    for (path, ai_lines, total_lines) in files {
+        if exclusions.is_excluded(path) {
+            continue;
+        }
+        let language = map.language_for(path);
+        let entry = by_language
+            .entry(language.clone())
+            .or_insert_with(|| LanguageStats {
+                language,
+                files: 0,
+                total_lines: 0,
+                ai_lines: 0,
+                human_lines: 0,
+                ai_percentage: 0.0,
+            });
+        let total_lines = total_lines.max(ai_lines);
+        entry.files += 1;
+        entry.total_lines += total_lines;
+        entry.ai_lines += ai_lines;
+        entry.human_lines += total_lines - ai_lines;
+    }
+
+    let mut languages = by_language
+        .into_values()
+        .map(|mut stats| {
+            if stats.total_lines > 0 {
+                stats.ai_percentage = stats.ai_lines as f64 / stats.total_lines as f64 * 100.0;
+            }
+            stats
+        })
+        .collect::<Vec<_>>();
+    languages.sort_by(|a, b| b.total_lines.cmp(&a.total_lines));
+    languages
+}
+
+/// Lines added per file by a commit and how many of them are AI-attributed,
+/// as `path -> (ai_lines, added_lines)`
+pub async fn commit_file_lines(
+    db: &SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<HashMap<String, (u32, u32)>, String> {
+    let ai_rows: Vec<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT file_path, SUM(end_line - start_line + 1)
+        FROM line_attributions
+        WHERE repo_id = ? AND commit_sha = ? AND author_type != 'human'
+        GROUP BY file_path
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let added_rows: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT path, additions FROM file_changes WHERE repo_id = ? AND commit_sha = ?",
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut files: HashMap<String, (u32, u32)> = HashMap::new();
+    for (path, lines) in ai_rows {
+        files.entry(path).or_default().0 += lines.max(0) as u32;
+    }
+    for (path, lines) in added_rows {
+        files.entry(path).or_default().1 += lines.max(0) as u32;
+    }
+    Ok(files)
+}
+
+/// Normalize overrides: lowercase keys without a leading `.`, trimmed
+/// languages, and empty entries dropped
+pub fn normalize_language_overrides(
+    overrides: BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    overrides
+        .into_iter()
+        .map(|(pattern, language)| {
+            (
+                pattern.trim().trim_start_matches('.').to_lowercase(),
+                language.trim().to_string(),
+            )
+        })
+        .filter(|(pattern, language)| !pattern.is_empty() && !language.is_empty())
+        .collect()
+}
+
+pub async fn fetch_language_overrides(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<BTreeMap<String, String>, String> {
+    let rows: Vec<(String, String)> = sqlx::query_as(
+        "SELECT pattern, language FROM attribution_language_overrides WHERE repo_id = ?",
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Load a repo's language map; built-in mappings only if overrides cannot be read
+pub async fn load_language_map(db: &SqlitePool, repo_id: i64) -> LanguageMap {
+    let overrides = fetch_language_overrides(db, repo_id)
+        .await
+        .unwrap_or_default();
+    LanguageMap::new(&overrides)
+}
+
+/// Replace a repo's language overrides
+pub async fn store_language_overrides(
+    db: &SqlitePool,
+    repo_id: i64,
+    overrides: &BTreeMap<String, String>,
+) -> Result<(), String> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM attribution_language_overrides WHERE repo_id = ?")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for (pattern, language) in overrides {
+        sqlx::query(
+            "INSERT INTO attribution_language_overrides (repo_id, pattern, language) VALUES (?, ?, ?)",
+        )
+        .bind(repo_id)
+        .bind(pattern)
+        .bind(language)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_by_extension_and_file_name() {
+        let map = LanguageMap::default();
+        assert_eq!(map.language_for("src/attribution/mod.rs"), "Rust");
+        assert_eq!(map.language_for("src/ui/App.TSX"), "TypeScript");
+        assert_eq!(map.language_for(".github/workflows/ci.yml"), "YAML");
+        assert_eq!(map.language_for("docker/Dockerfile"), "Dockerfile");
+        assert_eq!(map.language_for(".gitignore"), OTHER_LANGUAGE);
+        assert_eq!(map.language_for("LICENSE"), OTHER_LANGUAGE);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        let overrides = normalize_language_overrides(BTreeMap::from([
+            (".H".to_string(), " C++ ".to_string()),
+            ("Justfile".to_string(), "Just".to_string()),
+            ("  ".to_string(), "Nothing".to_string()),
+        ]));
+        assert_eq!(overrides.len(), 2);
+
+        let map = LanguageMap::new(&overrides);
+        assert_eq!(map.language_for("include/engine.h"), "C++");
+        assert_eq!(map.language_for("justfile"), "Just");
+        assert_eq!(map.language_for("src/engine.c"), "C");
+    }
+
+    #[test]
+    fn breakdown_sums_lines_per_language() {
+        let exclusions = ExclusionRules::new(&["package-lock.json".to_string()]);
+        let languages = language_breakdown(
+            &LanguageMap::default(),
+            &exclusions,
+            [
+                ("src/engine.rs", 2, 40),
+                ("src/lib.rs", 0, 10),
+                ("deploy/app.yaml", 12, 12),
+                ("package-lock.json", 500, 500),
+            ],
+        );
+
+        assert_eq!(languages.len(), 2);
+        assert_eq!(languages[0].language, "Rust");
+        assert_eq!(languages[0].files, 2);
+        assert_eq!(languages[0].human_lines, 48);
+        assert_eq!(languages[0].ai_percentage, 4.0);
+        assert_eq!(languages[1].language, "YAML");
+        assert_eq!(languages[1].ai_percentage, 100.0);
+    }
+}
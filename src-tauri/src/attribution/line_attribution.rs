@@ -1,12 +1,20 @@
 //! Line attribution storage and retrieval
 
-use super::git_utils::{collect_changed_ranges, compute_rewrite_key, list_commit_files};
+use super::completions::apply_completion_acceptances;
+use super::exclusions::load_exclusion_rules;
+use super::git_utils::{
+    collect_changed_ranges, commit_renames, compute_rewrite_key, list_commit_files,
+};
+use super::propagation::{fetch_attributed_rewrite_sources, propagate_line_attributions};
+use super::source_lens::load_file_lines;
 use super::stats::LinkedSessionRow;
 use super::utils::fetch_repo_root;
+use crate::linking::line_fingerprints;
 use git2::Repository;
+use std::collections::HashSet;
 
 /// Database row for line attribution commit
-#[derive(sqlx::FromRow)]
+#[derive(Clone, sqlx::FromRow)]
 pub struct LineAttributionCommitRow {
     pub file_path: String,
     pub start_line: i32,
@@ -16,6 +24,8 @@ pub struct LineAttributionCommitRow {
     pub ai_percentage: Option<i32>,
     pub tool: Option<String>,
     pub model: Option<String>,
+    pub source: String,
+    pub confidence: f64,
 }
 
 #[derive(Clone, Copy)]
@@ -25,17 +35,63 @@ pub struct ChangedRange {
     pub kind: ChangeKind,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ChangeKind {
     Added,
     Modified,
 }
 
+/// Range imported from an attribution note
+pub const SOURCE_NOTE: &str = "note";
+/// Range measured against a session's recorded edits
+pub const SOURCE_TOOL_DIFF: &str = "tool_diff";
+/// Range matched to an accepted tab completion
+pub const SOURCE_COMPLETION: &str = "completion";
+/// Range inferred from which files a linked session touched
+pub const SOURCE_HEURISTIC: &str = "heuristic";
+
+/// Ranges below this confidence are shown as uncertain
+pub const UNCERTAIN_CONFIDENCE: f64 = 0.7;
+
+/// Confidence recorded for a range written from `source`
+pub fn source_confidence(source: &str) -> f64 {
+    match source {
+        SOURCE_NOTE => 1.0,
+        SOURCE_TOOL_DIFF => 0.9,
+        SOURCE_COMPLETION => 0.85,
+        _ => 0.5,
+    }
+}
+
 /// Ensure line attributions exist for a commit
 pub async fn ensure_line_attributions_for_commit(
     db: &sqlx::SqlitePool,
     repo_id: i64,
     commit_sha: &str,
+) -> Result<(), String> {
+    use super::session_stats::store_contribution_stats;
+    use super::stats::compute_contribution_from_attributions;
+
+    ensure_session_line_attributions(db, repo_id, commit_sha).await?;
+
+    // Tab-completion acceptances fill in lines no session claimed
+    if apply_completion_acceptances(db, repo_id, commit_sha).await? > 0 {
+        if let Ok(Some(stats)) =
+            compute_contribution_from_attributions(db, repo_id, commit_sha).await
+        {
+            let _ = store_contribution_stats(db, repo_id, commit_sha, None, &stats).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Attribute a commit's changed lines to its linked sessions, unless
+/// attributions already exist or can be restored from a rewritten commit
+async fn ensure_session_line_attributions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
 ) -> Result<(), String> {
     if line_attributions_exist(db, repo_id, commit_sha).await? {
         return Ok(());
@@ -45,6 +101,10 @@ pub async fn ensure_line_attributions_for_commit(
         return Ok(());
     }
 
+    if let Ok(true) = try_restore_attributions_via_rewrites(db, repo_id, commit_sha).await {
+        return Ok(());
+    }
+
     let sessions = fetch_sessions_for_commit(db, repo_id, commit_sha).await?;
     if sessions.is_empty() {
         return Ok(());
@@ -53,16 +113,32 @@ pub async fn ensure_line_attributions_for_commit(
     let repo_root = fetch_repo_root(db, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let commit_files = list_commit_files(&repo, commit_sha)?;
+    // Sessions may have edited a file under the name it had before this commit
+    let renames = commit_renames(&repo, commit_sha).unwrap_or_default();
+    let exclusions = load_exclusion_rules(db, repo_id).await;
     let session_files = sessions
         .iter()
         .map(|session| parse_session_files(&session.files))
         .collect::<Vec<_>>();
+    let session_proposals = sessions
+        .iter()
+        .map(|session| parse_session_files(&session.edit_fingerprints))
+        .collect::<Vec<_>>();
 
     for file_path in commit_files {
+        if exclusions.is_excluded(&file_path) {
+            continue;
+        }
         let matched_indexes = session_files
             .iter()
             .enumerate()
-            .filter_map(|(idx, files)| files.contains(&file_path).then_some(idx))
+            .filter_map(|(idx, files)| {
+                let touched = files.contains(&file_path)
+                    || renames
+                        .get(&file_path)
+                        .is_some_and(|old_path| files.contains(old_path));
+                touched.then_some(idx)
+            })
             .collect::<Vec<_>>();
         let target_indexes = if matched_indexes.is_empty() {
             vec![0]
@@ -71,13 +147,28 @@ pub async fn ensure_line_attributions_for_commit(
         };
 
         let ranges = collect_changed_ranges(&repo, commit_sha, &file_path)?;
+        let file_lines = if target_indexes
+            .iter()
+            .any(|idx| !session_proposals[*idx].is_empty())
+        {
+            load_file_lines(&repo, commit_sha, &file_path).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
         for range in &ranges {
-            let (author_type, ai_percentage) = match range.kind {
-                ChangeKind::Added => ("ai_agent", None),
-                ChangeKind::Modified => ("mixed", Some(50.0)),
-            };
             for session_index in &target_indexes {
                 let session = &sessions[*session_index];
+                let (author_type, ai_percentage, source) =
+                    match measure_range(&file_lines, range, &session_proposals[*session_index]) {
+                        Some((author_type, ai_percentage)) => {
+                            (author_type, Some(ai_percentage), SOURCE_TOOL_DIFF)
+                        }
+                        // No recorded edits to compare against
+                        None => match range.kind {
+                            ChangeKind::Added => ("ai_agent", None, SOURCE_HEURISTIC),
+                            ChangeKind::Modified => ("mixed", Some(50.0), SOURCE_HEURISTIC),
+                        },
+                    };
                 sqlx::query(
                     r#"
                     INSERT INTO line_attributions (
@@ -90,9 +181,11 @@ pub async fn ensure_line_attributions_for_commit(
                         author_type,
                         ai_percentage,
                         tool,
-                        model
+                        model,
+                        source,
+                        confidence
                     )
-                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     "#,
                 )
                 .bind(repo_id)
@@ -105,6 +198,8 @@ pub async fn ensure_line_attributions_for_commit(
                 .bind(ai_percentage)
                 .bind(&session.tool)
                 .bind(&session.model)
+                .bind(source)
+                .bind(source_confidence(source))
                 .execute(db)
                 .await
                 .map_err(|e| e.to_string())?;
@@ -117,6 +212,46 @@ pub async fn ensure_line_attributions_for_commit(
     Ok(())
 }
 
+/// Author type and AI share of a changed range, measured against the code
+/// the session's edit tool calls proposed (as line fingerprints): lines the
+/// session wrote verbatim count as AI, the rest as human edits.
+///
+/// None when there are no proposals or the range has no lines long enough
+/// to fingerprint.
+pub fn measure_range(
+    file_lines: &[String],
+    range: &ChangedRange,
+    proposed: &HashSet<String>,
+) -> Option<(&'static str, f64)> {
+    if proposed.is_empty() {
+        return None;
+    }
+
+    let (mut significant, mut matched) = (0u32, 0u32);
+    for line in range.start_line..=range.end_line {
+        let Some(text) = file_lines.get((line - 1).max(0) as usize) else {
+            continue;
+        };
+        let Some(fingerprint) = line_fingerprints([text.as_str()]).pop() else {
+            continue;
+        };
+        significant += 1;
+        if proposed.contains(&fingerprint) {
+            matched += 1;
+        }
+    }
+    if significant == 0 {
+        return None;
+    }
+
+    let ai_percentage = (matched as f64 / significant as f64 * 100.0).round();
+    Some(match matched {
+        0 => ("human", 0.0),
+        m if m == significant => ("ai_agent", 100.0),
+        _ => ("mixed", ai_percentage),
+    })
+}
+
 /// Check if line attributions exist for a commit
 async fn line_attributions_exist(
     db: &sqlx::SqlitePool,
@@ -146,9 +281,6 @@ async fn try_restore_attributions_via_rewrite_key(
     repo_id: i64,
     commit_sha: &str,
 ) -> Result<bool, String> {
-    use super::session_stats::store_contribution_stats;
-    use super::stats::compute_contribution_from_attributions;
-
     let Some(rewrite_key) = store_rewrite_key_for_commit(db, repo_id, commit_sha).await? else {
         return Ok(false);
     };
@@ -159,7 +291,35 @@ async fn try_restore_attributions_via_rewrite_key(
         return Ok(false);
     };
 
-    let copied = copy_line_attributions(db, repo_id, &source_commit, commit_sha).await?;
+    restore_attributions_from(db, repo_id, &source_commit, commit_sha).await
+}
+
+/// Try to restore attributions from the commit this one was rewritten from
+/// (recorded by the post-rewrite hook)
+async fn try_restore_attributions_via_rewrites(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<bool, String> {
+    for source_commit in fetch_attributed_rewrite_sources(db, repo_id, commit_sha).await? {
+        if restore_attributions_from(db, repo_id, &source_commit, commit_sha).await? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Propagate attributions from `source_commit` and refresh contribution stats
+async fn restore_attributions_from(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    source_commit: &str,
+    commit_sha: &str,
+) -> Result<bool, String> {
+    use super::session_stats::store_contribution_stats;
+    use super::stats::compute_contribution_from_attributions;
+
+    let copied = propagate_line_attributions(db, repo_id, source_commit, commit_sha).await?;
     if copied == 0 {
         return Ok(false);
     }
@@ -258,52 +418,6 @@ async fn find_commit_by_rewrite_key(
     .map_err(|e| e.to_string())
 }
 
-/// Copy line attributions from one commit to another
-async fn copy_line_attributions(
-    db: &sqlx::SqlitePool,
-    repo_id: i64,
-    source_commit: &str,
-    target_commit: &str,
-) -> Result<u32, String> {
-    let result = sqlx::query(
-        r#"
-        INSERT INTO line_attributions (
-            repo_id,
-            commit_sha,
-            file_path,
-            start_line,
-            end_line,
-            session_id,
-            author_type,
-            ai_percentage,
-            tool,
-            model
-        )
-        SELECT
-            repo_id,
-            ?,
-            file_path,
-            start_line,
-            end_line,
-            session_id,
-            author_type,
-            ai_percentage,
-            tool,
-            model
-        FROM line_attributions
-        WHERE repo_id = ? AND commit_sha = ?
-        "#,
-    )
-    .bind(target_commit)
-    .bind(repo_id)
-    .bind(source_commit)
-    .execute(db)
-    .await
-    .map_err(|e| e.to_string())?;
-
-    Ok(result.rows_affected() as u32)
-}
-
 /// Fetch sessions linked to a commit
 async fn fetch_sessions_for_commit(
     db: &sqlx::SqlitePool,
@@ -312,7 +426,7 @@ async fn fetch_sessions_for_commit(
 ) -> Result<Vec<LinkedSessionRow>, String> {
     sqlx::query_as::<_, LinkedSessionRow>(
         r#"
-        SELECT s.id as session_id, s.tool, s.model, s.files
+        SELECT s.id as session_id, s.tool, s.model, s.files, s.edit_fingerprints
         FROM session_links l
         JOIN sessions s ON s.id = l.session_id
         WHERE l.repo_id = ? AND l.commit_sha = ?
@@ -352,6 +466,8 @@ pub async fn fetch_line_attributions(
             la.ai_percentage,
             la.tool,
             la.model,
+            la.source,
+            la.confidence,
             COALESCE(s.trace_available, 0) as trace_available
         FROM line_attributions la
         LEFT JOIN sessions s ON s.id = la.session_id
@@ -383,7 +499,9 @@ pub async fn fetch_line_attributions_for_commit(
             author_type,
             ai_percentage,
             tool,
-            model
+            model,
+            source,
+            confidence
         FROM line_attributions
         WHERE repo_id = ? AND commit_sha = ?
         ORDER BY file_path, start_line
@@ -395,3 +513,44 @@ pub async fn fetch_line_attributions_for_commit(
     .await
     .map_err(|e| format!("Database error: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_are_measured_against_proposed_lines() {
+        let proposed =
+            line_fingerprints(["let total = items.iter().sum();", "println!(\"{total}\");"])
+                .into_iter()
+                .collect::<HashSet<_>>();
+        let lines = [
+            "let total = items.iter().sum();",
+            "println!(\"{total}\");",
+            "let doubled = total * 2;",
+            "}",
+        ]
+        .map(str::to_string);
+        let range = |start_line, end_line| ChangedRange {
+            start_line,
+            end_line,
+            kind: ChangeKind::Modified,
+        };
+
+        assert_eq!(
+            measure_range(&lines, &range(1, 2), &proposed),
+            Some(("ai_agent", 100.0))
+        );
+        // The closing brace is too short to fingerprint and is not counted
+        assert_eq!(
+            measure_range(&lines, &range(1, 4), &proposed),
+            Some(("mixed", 67.0))
+        );
+        assert_eq!(
+            measure_range(&lines, &range(3, 3), &proposed),
+            Some(("human", 0.0))
+        );
+        assert_eq!(measure_range(&lines, &range(4, 4), &proposed), None);
+        assert_eq!(measure_range(&lines, &range(1, 2), &HashSet::new()), None);
+    }
+}
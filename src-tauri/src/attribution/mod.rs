@@ -16,24 +16,54 @@
 //! - `notes_io.rs` - Git note import/export commands
 //! - `line_attribution.rs` - Line attribution storage and retrieval
 //! - `git_utils.rs` - Git operations (diff, patch-id, file listing)
+//! - `propagation.rs` - Attribution carried through rewrites and later commits
+//! - `symbols.rs` - Function/class-level attribution via tree-sitter
 //! - `utils.rs` - Shared utilities (repo root fetching, session metadata)
 //! - (removed) legacy external CLI detection
 //! - `coverage.rs` - Attribution coverage computation
 //! - `note_meta.rs` - Note metadata persistence
 //! - `prefs.rs` - Attribution preferences storage
+//! - `exclusions.rs` - Per-repo globs for files left out of attribution
 //! - `dashboard.rs` - Dashboard analytics aggregation
+//! - `timeseries.rs` - Cached weekly/monthly attribution rollups
+//! - `authors.rs` - Per-author attribution breakdown
+//! - `directories.rs` - Per-directory attribution rollups by commit range
+//! - `report.rs` - Attribution report export (CSV, JSON, Markdown)
+//! - `sarif.rs` - SARIF export of AI-attributed line ranges
+//! - `completions.rs` - Tab-completion acceptances recorded as `ai_tab` lines
+//! - `batch.rs` - Background attribution batches with progress and resume
+//! - `policy.rs` - Attribution policy rules checked by hooks and CI
+//! - `languages.rs` - Per-language attribution breakdown with overridable mapping
+//! - `aibom.rs` - AI bill of materials (AI-BOM) export per release
+//! - `dashboard_html.rs` - Self-contained HTML export of the dashboard
+//! - `deletions.rs` - Attribution of deleted lines for net-change metrics
 
+pub mod aibom;
+pub mod authors;
+pub mod batch;
 pub mod commands;
+pub mod completions;
 pub mod coverage;
 pub mod dashboard;
+pub mod dashboard_html;
+pub mod deletions;
+pub mod directories;
+pub mod exclusions;
 pub mod git_utils;
+pub mod languages;
 pub mod line_attribution;
 pub mod models;
 pub mod note_meta;
 pub mod notes;
 pub mod notes_io;
+pub mod policy;
 pub mod prefs;
+pub mod propagation;
+pub mod report;
+pub mod sarif;
 pub mod session_stats;
 pub mod source_lens;
 pub mod stats;
+pub mod symbols;
+pub mod timeseries;
 pub mod utils;
@@ -27,6 +27,9 @@ pub struct ContributionStats {
     /// Model used (if known)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// Merge commit: only lines changed against every parent are counted
+    #[serde(default)]
+    pub is_merge: bool,
 }
 
 impl ContributionStats {
@@ -39,6 +42,14 @@ impl ContributionStats {
             ..Default::default()
         }
     }
+
+    /// Create stats for a merge commit with no changes of its own
+    pub fn merge_only() -> Self {
+        Self {
+            is_merge: true,
+            ..Self::human_only(0)
+        }
+    }
 }
 
 /// Statistics for a specific tool
@@ -65,7 +76,18 @@ pub struct SourceLine {
     pub tool: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    /// How the line's attribution was derived: note, tool_diff, completion, heuristic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// 0.0-1.0 certainty of the attribution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Attribution is too weak to be shown as fact
+    pub uncertain: bool,
     pub trace_available: bool,
+    /// Commit that last changed this line (from blame)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
 }
 
 /// Request for source lens data
@@ -88,6 +110,27 @@ pub struct SourceLensPage {
     pub has_more: bool,
 }
 
+/// Request for attribution of a file as it exists at HEAD
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentFileAttributionRequest {
+    pub repo_id: i64,
+    pub file_path: String,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Per-line attribution of a file at HEAD, merged across the commits
+/// that last changed each line
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentFileAttribution {
+    pub head_sha: String,
+    pub lines: Vec<SourceLine>,
+    pub total_lines: u32,
+    pub has_more: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AttributionNoteSummary {
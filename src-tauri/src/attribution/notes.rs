@@ -1,10 +1,7 @@
+use crate::story_anchors::notes_format::note_payload;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-/// Notes ref for Narrative attribution exports/imports (canonical write target).
-pub const ATTRIBUTION_NOTES_REF: &str = "refs/notes/narrative/attribution";
-/// Legacy Narrative notes ref for backward-compatible imports.
-pub const LEGACY_NARRATIVE_ATTRIBUTION_NOTES_REF: &str = "refs/notes/narrative-attribution";
 pub const ATTRIBUTION_SCHEMA_VERSION: &str = "narrative/attribution/1.0.0";
 
 #[derive(Debug, Clone)]
@@ -20,6 +17,16 @@ pub struct NoteFile {
     pub ranges: Vec<NoteRange>,
 }
 
+/// Lines that two exports of a note attribute to different sessions
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NoteConflict {
+    pub path: String,
+    pub start_line: i32,
+    pub end_line: i32,
+    /// Sessions claiming the lines, sorted
+    pub sessions: Vec<String>,
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct NoteSourceMeta {
     pub tool: Option<String>,
@@ -52,6 +59,7 @@ pub struct ParsedAttributionNote {
     pub messages_redacted: Option<bool>,
     pub rewrite_key: Option<String>,
     pub rewrite_algorithm: Option<String>,
+    pub conflicts: Vec<NoteConflict>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -69,6 +77,7 @@ struct NotePayload {
     messages_redacted: Option<bool>,
     #[serde(alias = "prompts")]
     sources: Option<HashMap<String, NoteSourcePayload>>,
+    conflicts: Option<Vec<NoteConflict>>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -107,9 +116,11 @@ struct AttributionNotePayload {
     #[serde(skip_serializing_if = "Option::is_none")]
     rewrite_algorithm: Option<String>,
     #[serde(rename = "prompts")]
-    sources: HashMap<String, AttributionNoteSource>,
+    sources: BTreeMap<String, AttributionNoteSource>,
     #[serde(skip_serializing_if = "Option::is_none")]
     messages_redacted: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    conflicts: Vec<NoteConflict>,
 }
 
 #[derive(Debug, Serialize)]
@@ -130,6 +141,7 @@ struct AttributionAgentId {
 }
 
 pub fn parse_attribution_note(message: &str) -> ParsedAttributionNote {
+    let message = note_payload(message);
     let mut files: Vec<NoteFile> = Vec::new();
     let mut current_file: Option<NoteFile> = None;
     let mut json_lines: Vec<String> = Vec::new();
@@ -215,9 +227,11 @@ pub fn parse_attribution_note(message: &str) -> ParsedAttributionNote {
     let mut messages_redacted: Option<bool> = None;
     let mut rewrite_key: Option<String> = None;
     let mut rewrite_algorithm: Option<String> = None;
+    let mut conflicts: Vec<NoteConflict> = Vec::new();
 
     if !json_text.is_empty() {
         if let Ok(payload) = serde_json::from_str::<NotePayload>(&json_text) {
+            conflicts = payload.conflicts.unwrap_or_default();
             schema_version = payload.schema_version.clone();
             messages_redacted = payload.messages_redacted;
             rewrite_key = payload.rewrite_key.clone();
@@ -282,6 +296,116 @@ pub fn parse_attribution_note(message: &str) -> ParsedAttributionNote {
         messages_redacted,
         rewrite_key,
         rewrite_algorithm,
+        conflicts,
+    }
+}
+
+/// A local export merged into the note already attached to a commit
+#[derive(Debug, Clone, Default)]
+pub struct MergedAttributionNote {
+    pub files: Vec<NoteFile>,
+    pub sources: HashMap<String, NoteSourceMeta>,
+    pub conflicts: Vec<NoteConflict>,
+}
+
+/// Merge locally exported attribution into an existing note
+///
+/// Files and ranges are unioned. Where each side attributes the same lines
+/// of a file to a session the other side has no ranges for in that file,
+/// the overlap is recorded as a conflict rather than either side winning.
+/// Conflicts already in the existing note are kept. Source metadata keeps
+/// the existing note's values and fills gaps from the local export.
+pub fn merge_attribution_notes(
+    existing: &ParsedAttributionNote,
+    files: &[NoteFile],
+    sources: &HashMap<String, NoteSourceMeta>,
+) -> MergedAttributionNote {
+    let sessions_by_file = |files: &[NoteFile]| {
+        let mut by_file: HashMap<String, HashSet<String>> = HashMap::new();
+        for file in files {
+            by_file
+                .entry(file.path.clone())
+                .or_default()
+                .extend(file.ranges.iter().map(|range| range.session_id.clone()));
+        }
+        by_file
+    };
+    let existing_sessions = sessions_by_file(&existing.files);
+    let local_sessions = sessions_by_file(files);
+
+    let mut conflicts = existing.conflicts.clone();
+    for existing_file in &existing.files {
+        let path = &existing_file.path;
+        let (Some(theirs), Some(ours)) = (existing_sessions.get(path), local_sessions.get(path))
+        else {
+            continue;
+        };
+        let local_ranges = files
+            .iter()
+            .filter(|file| &file.path == path)
+            .flat_map(|file| &file.ranges)
+            .filter(|range| !theirs.contains(&range.session_id))
+            .collect::<Vec<_>>();
+        for existing_range in &existing_file.ranges {
+            if ours.contains(&existing_range.session_id) {
+                continue;
+            }
+            for local_range in &local_ranges {
+                let start = existing_range.start_line.max(local_range.start_line);
+                let end = existing_range.end_line.min(local_range.end_line);
+                if start > end {
+                    continue;
+                }
+                let mut sessions = vec![
+                    existing_range.session_id.clone(),
+                    local_range.session_id.clone(),
+                ];
+                sessions.sort();
+                conflicts.push(NoteConflict {
+                    path: path.clone(),
+                    start_line: start,
+                    end_line: end,
+                    sessions,
+                });
+            }
+        }
+    }
+    conflicts.sort();
+    conflicts.dedup();
+
+    let mut ranges_by_file: BTreeMap<String, Vec<NoteRange>> = BTreeMap::new();
+    for file in existing.files.iter().chain(files) {
+        ranges_by_file
+            .entry(file.path.clone())
+            .or_default()
+            .extend(file.ranges.iter().cloned());
+    }
+    let files = ranges_by_file
+        .into_iter()
+        .map(|(path, ranges)| NoteFile { path, ranges })
+        .collect();
+
+    let mut merged_sources = existing.sources.clone();
+    for (session_id, local) in sources {
+        let merged = merged_sources.entry(session_id.clone()).or_default();
+        if merged.tool.is_none() {
+            merged.tool = local.tool.clone();
+        }
+        if merged.model.is_none() {
+            merged.model = local.model.clone();
+        }
+        if merged.checkpoint_kind.is_none() {
+            merged.checkpoint_kind = local.checkpoint_kind.clone();
+        }
+        if merged.conversation_id.is_none() {
+            merged.conversation_id = local.conversation_id.clone();
+        }
+    }
+
+    MergedAttributionNote {
+        files,
+        sources: merged_sources,
+        conflicts,
     }
 }
 
@@ -289,6 +413,7 @@ pub fn build_attribution_note(
     commit_sha: &str,
     files: &[NoteFile],
     sources: &HashMap<String, NoteSourceMeta>,
+    conflicts: &[NoteConflict],
     rewrite_key: Option<&str>,
     rewrite_algorithm: Option<&str>,
 ) -> String {
@@ -335,6 +460,7 @@ pub fn build_attribution_note(
         rewrite_algorithm: rewrite_algorithm.map(|value| value.to_string()),
         sources: build_sources_payload(sources),
         messages_redacted: Some(true),
+        conflicts: conflicts.to_vec(),
     };
 
     let json = serde_json::to_string_pretty(&payload).unwrap_or_else(|_| "{}".to_string());
@@ -365,8 +491,8 @@ fn merge_ranges(mut ranges: Vec<(i32, i32)>) -> Vec<(i32, i32)> {
 
 fn build_sources_payload(
     sources: &HashMap<String, NoteSourceMeta>,
-) -> HashMap<String, AttributionNoteSource> {
-    let mut out = HashMap::new();
+) -> BTreeMap<String, AttributionNoteSource> {
+    let mut out = BTreeMap::new();
     for (session_id, meta) in sources {
         let agent_id = Some(AttributionAgentId {
             tool: meta.tool.clone(),
@@ -387,3 +513,100 @@ fn build_sources_payload(
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, ranges: &[(&str, i32, i32)]) -> NoteFile {
+        NoteFile {
+            path: path.to_string(),
+            ranges: ranges
+                .iter()
+                .map(|(session_id, start_line, end_line)| NoteRange {
+                    session_id: session_id.to_string(),
+                    start_line: *start_line,
+                    end_line: *end_line,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn merge_unions_ranges_and_marks_overlaps() {
+        let existing = parse_attribution_note(&build_attribution_note(
+            "abc",
+            &[
+                file("src/a.rs", &[("s1", 1, 10)]),
+                file("src/b.rs", &[("s1", 1, 2)]),
+            ],
+            &HashMap::new(),
+            &[],
+            None,
+            None,
+        ));
+        let local = [
+            file("src/a.rs", &[("s2", 8, 12)]),
+            file("src/c.rs", &[("s2", 3, 4)]),
+        ];
+
+        let merged = merge_attribution_notes(&existing, &local, &HashMap::new());
+        let paths = merged
+            .files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec!["src/a.rs", "src/b.rs", "src/c.rs"]);
+        assert_eq!(
+            merged.conflicts,
+            vec![NoteConflict {
+                path: "src/a.rs".to_string(),
+                start_line: 8,
+                end_line: 10,
+                sessions: vec!["s1".to_string(), "s2".to_string()],
+            }]
+        );
+
+        // Conflicts survive a round trip through the note text
+        let text = build_attribution_note(
+            "abc",
+            &merged.files,
+            &merged.sources,
+            &merged.conflicts,
+            None,
+            None,
+        );
+        let reparsed = parse_attribution_note(&text);
+        assert_eq!(reparsed.conflicts, merged.conflicts);
+
+        // Re-merging the same export changes nothing
+        let again = merge_attribution_notes(&reparsed, &local, &HashMap::new());
+        let again_text = build_attribution_note(
+            "abc",
+            &again.files,
+            &again.sources,
+            &again.conflicts,
+            None,
+            None,
+        );
+        assert_eq!(again_text, text);
+    }
+
+    #[test]
+    fn same_session_ranges_do_not_conflict() {
+        let existing = parse_attribution_note(&build_attribution_note(
+            "abc",
+            &[file("src/a.rs", &[("s1", 1, 5)])],
+            &HashMap::new(),
+            &[],
+            None,
+            None,
+        ));
+        let merged = merge_attribution_notes(
+            &existing,
+            &[file("src/a.rs", &[("s1", 4, 8), ("s2", 20, 21)])],
+            &HashMap::new(),
+        );
+        assert!(merged.conflicts.is_empty());
+    }
+}
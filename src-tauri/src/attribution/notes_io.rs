@@ -1,20 +1,29 @@
 //! Git note import/export functionality
 
-use super::line_attribution::store_rewrite_key;
+use super::line_attribution::{source_confidence, store_rewrite_key, SOURCE_NOTE};
 use super::note_meta::{
     clear_attribution_note_meta, mark_prompt_metadata_cached, upsert_attribution_note_meta,
     AttributionNoteMetaInput,
 };
 use super::notes::{
-    build_attribution_note, parse_attribution_note, NoteFile, NoteRange, NoteSourceMeta,
-    ParsedAttributionNote, ATTRIBUTION_NOTES_REF, LEGACY_NARRATIVE_ATTRIBUTION_NOTES_REF,
+    build_attribution_note, merge_attribution_notes, parse_attribution_note, NoteFile, NoteRange,
+    NoteSourceMeta, ParsedAttributionNote, ATTRIBUTION_SCHEMA_VERSION,
 };
 use super::prefs::fetch_or_create_prefs;
 use super::stats::compute_contribution_from_attributions;
 use super::utils::{fetch_repo_root, fetch_session_meta};
+use crate::story_anchors::notes_format::{
+    compress_note, note_payload, note_schema_version, schema_compat, split_note_signature,
+    NoteSchemaCompat,
+};
+use crate::story_anchors::notes_signing::{
+    sign_note, verify_note_signature, NoteSignatureCheck, SIGNATURE_UNSIGNED, SIGNATURE_VALID,
+};
+use crate::story_anchors::refs::{fetch_notes_refs, NotesRefs};
 use git2::{Oid, Repository, Signature};
 use serde::Serialize;
 use sha2::{Digest, Sha256};
+use std::path::Path;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -38,7 +47,10 @@ pub struct AttributionNoteBatchSummary {
 #[serde(rename_all = "camelCase")]
 pub struct AttributionNoteExportSummary {
     pub commit_sha: String,
+    /// "exported", "merged" (into an existing note), "unchanged", or "empty"
     pub status: String,
+    /// Overlapping ranges the merged note attributes to different sessions
+    pub conflicts: u32,
 }
 
 const REWRITE_KEY_ALGORITHM: &str = "patch-id";
@@ -111,19 +123,24 @@ pub async fn import_attribution_note_internal(
     use super::session_stats::store_contribution_stats;
 
     let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
 
     // Parse the note in a separate block to ensure repo/note are dropped before await
-    let note_result: Result<Option<(ParsedAttributionNote, String, String)>, String> = {
+    let note_result: Result<
+        Option<(ParsedAttributionNote, String, String, NoteSignatureCheck)>,
+        String,
+    > = {
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
 
-        let note_pair = match repo.find_note(Some(ATTRIBUTION_NOTES_REF), oid) {
-            Ok(note) => Some((note, ATTRIBUTION_NOTES_REF)),
-            Err(_) => match repo.find_note(Some(LEGACY_NARRATIVE_ATTRIBUTION_NOTES_REF), oid) {
-                Ok(note) => Some((note, LEGACY_NARRATIVE_ATTRIBUTION_NOTES_REF)),
-                Err(_) => None,
-            },
-        };
+        let note_pair = refs
+            .attribution_import_precedence()
+            .into_iter()
+            .find_map(|note_ref| {
+                repo.find_note(Some(note_ref), oid)
+                    .ok()
+                    .map(|note| (note, note_ref))
+            });
 
         if let Some((note, note_ref)) = note_pair {
             let message = note
@@ -134,9 +151,12 @@ pub async fn import_attribution_note_internal(
             let note_hash = compute_note_hash(&message);
 
             // note and repo are dropped here, before the await below
-            let parsed = parse_attribution_note(&message);
+            let mut parsed = parse_attribution_note(&message);
+            // Read the version even if a newer payload no longer parses
+            parsed.schema_version = note_schema_version(&message).or(parsed.schema_version);
+            let signature = verify_note_signature(Path::new(&repo_root), &message);
 
-            Ok(Some((parsed, note_ref.to_string(), note_hash)))
+            Ok(Some((parsed, note_ref.to_string(), note_hash, signature)))
         } else {
             Ok(None)
         }
@@ -144,7 +164,7 @@ pub async fn import_attribution_note_internal(
 
     let note_result = note_result?;
 
-    let Some((parsed, note_ref, note_hash)) = note_result else {
+    let Some((parsed, note_ref, note_hash, signature)) = note_result else {
         let _ = clear_attribution_note_meta(db, repo_id, commit_sha).await;
         return Ok(AttributionNoteImportSummary {
             commit_sha: commit_sha.to_string(),
@@ -154,6 +174,28 @@ pub async fn import_attribution_note_internal(
         });
     };
 
+    // Notes from a newer major schema are left for a newer build to import
+    if schema_compat(parsed.schema_version.as_deref(), ATTRIBUTION_SCHEMA_VERSION)
+        == NoteSchemaCompat::Newer
+    {
+        return Ok(AttributionNoteImportSummary {
+            commit_sha: commit_sha.to_string(),
+            status: "unsupported_schema".to_string(),
+            imported_ranges: 0,
+            imported_sessions: 0,
+        });
+    }
+
+    let prefs = fetch_or_create_prefs(db, repo_id).await?;
+    if prefs.require_signed_notes && signature.status != SIGNATURE_VALID {
+        return Ok(AttributionNoteImportSummary {
+            commit_sha: commit_sha.to_string(),
+            status: "untrusted_signature".to_string(),
+            imported_ranges: 0,
+            imported_sessions: 0,
+        });
+    }
+
     if parsed.files.is_empty() {
         let _ = clear_attribution_note_meta(db, repo_id, commit_sha).await;
         return Ok(AttributionNoteImportSummary {
@@ -185,11 +227,13 @@ pub async fn import_attribution_note_internal(
     // Also track in generic story_anchor_note_meta (best-effort; used by Story Anchors UI/status).
     let _ = sqlx::query(
         r#"
-        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version)
-        VALUES (?, ?, 'attribution', ?, ?, ?)
+        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version, signature_status, signer)
+        VALUES (?, ?, 'attribution', ?, ?, ?, ?, ?)
         ON CONFLICT(repo_id, commit_sha, note_kind, note_ref) DO UPDATE SET
             note_hash = excluded.note_hash,
             schema_version = excluded.schema_version,
+            signature_status = excluded.signature_status,
+            signer = excluded.signer,
             updated_at = CURRENT_TIMESTAMP
         "#,
     )
@@ -198,10 +242,11 @@ pub async fn import_attribution_note_internal(
     .bind(&note_ref)
     .bind(&note_hash)
     .bind(parsed.schema_version.clone())
+    .bind(&signature.status)
+    .bind(&signature.signer)
     .execute(db)
     .await;
 
-    let prefs = fetch_or_create_prefs(db, repo_id).await?;
     let metadata_cached = if prefs.cache_prompt_metadata {
         store_prompt_metadata(db, repo_id, commit_sha, &parsed, prefs.store_prompt_text).await?
     } else {
@@ -406,9 +451,11 @@ async fn store_line_attributions_from_note(
                     author_type,
                     ai_percentage,
                     tool,
-                    model
+                    model,
+                    source,
+                    confidence
                 )
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
             )
             .bind(repo_id)
@@ -421,6 +468,8 @@ async fn store_line_attributions_from_note(
             .bind(None::<f32>)
             .bind(&meta.tool)
             .bind(&meta.model)
+            .bind(SOURCE_NOTE)
+            .bind(source_confidence(SOURCE_NOTE))
             .execute(db)
             .await
             .map_err(|e| e.to_string())?;
@@ -447,6 +496,7 @@ async fn export_attribution_note_internal(
         return Ok(AttributionNoteExportSummary {
             commit_sha: commit_sha.to_string(),
             status: "empty".to_string(),
+            conflicts: 0,
         });
     }
 
@@ -502,6 +552,8 @@ async fn export_attribution_note_internal(
 
     let files = files_map.into_values().collect::<Vec<_>>();
 
+    let prefs = fetch_or_create_prefs(db, repo_id).await?;
+    let refs = NotesRefs::for_namespace(prefs.notes_namespace.as_deref())?;
     let repo_root = fetch_repo_root(db, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let rewrite_key = compute_rewrite_key(&repo, commit_sha).ok();
@@ -514,17 +566,58 @@ async fn export_attribution_note_internal(
     )
     .await;
 
+    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+
+    // Another machine may have exported this commit already; merge rather
+    // than overwrite its ranges
+    let existing = repo
+        .find_note(Some(&refs.attribution), oid)
+        .ok()
+        .and_then(|note| note.message().map(str::to_string));
+    let (files, sources, conflicts) = match &existing {
+        Some(message) => {
+            let merged =
+                merge_attribution_notes(&parse_attribution_note(message), &files, &sources);
+            (merged.files, merged.sources, merged.conflicts)
+        }
+        None => (files, sources, Vec::new()),
+    };
+
     let note_text = build_attribution_note(
         commit_sha,
         &files,
         &sources,
+        &conflicts,
         rewrite_key.as_deref(),
         Some(REWRITE_KEY_ALGORITHM),
     );
+    // Unchanged only if the content matches and, when signing, it is signed
+    let status = match &existing {
+        Some(message)
+            if note_payload(message) == note_text
+                && (!prefs.sign_notes || split_note_signature(message).1.is_some()) =>
+        {
+            "unchanged"
+        }
+        Some(_) => "merged",
+        None => "exported",
+    };
+    let note_text = match &existing {
+        Some(message) if status == "unchanged" => message.clone(),
+        _ if prefs.sign_notes => sign_note(Path::new(&repo_root), &compress_note(&note_text))?,
+        _ => compress_note(&note_text),
+    };
+    let (signature_status, signer) = if status == "unchanged" {
+        let check = verify_note_signature(Path::new(&repo_root), &note_text);
+        (check.status, check.signer)
+    } else if prefs.sign_notes {
+        (SIGNATURE_VALID.to_string(), None)
+    } else {
+        (SIGNATURE_UNSIGNED.to_string(), None)
+    };
     let note_hash = compute_note_hash(&note_text);
-    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
 
-    {
+    if status != "unchanged" {
         let signature = repo
             .signature()
             .or_else(|_| Signature::now("Narrative", "narrative@local"))
@@ -533,7 +626,7 @@ async fn export_attribution_note_internal(
         repo.note(
             &signature,
             &signature,
-            Some(ATTRIBUTION_NOTES_REF),
+            Some(&refs.attribution),
             oid,
             &note_text,
             true,
@@ -546,24 +639,29 @@ async fn export_attribution_note_internal(
     // Track generic story anchor note meta (best-effort).
     let _ = sqlx::query(
         r#"
-        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version)
-        VALUES (?, ?, 'attribution', ?, ?, ?)
+        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version, signature_status, signer)
+        VALUES (?, ?, 'attribution', ?, ?, ?, ?, ?)
         ON CONFLICT(repo_id, commit_sha, note_kind, note_ref) DO UPDATE SET
             note_hash = excluded.note_hash,
             schema_version = excluded.schema_version,
+            signature_status = excluded.signature_status,
+            signer = excluded.signer,
             updated_at = CURRENT_TIMESTAMP
         "#,
     )
     .bind(repo_id)
     .bind(commit_sha)
-    .bind(ATTRIBUTION_NOTES_REF)
+    .bind(&refs.attribution)
     .bind(note_hash)
     .bind(super::notes::ATTRIBUTION_SCHEMA_VERSION)
+    .bind(signature_status)
+    .bind(signer)
     .execute(db)
     .await;
 
     Ok(AttributionNoteExportSummary {
         commit_sha: commit_sha.to_string(),
-        status: "exported".to_string(),
+        status: status.to_string(),
+        conflicts: conflicts.len() as u32,
     })
 }
@@ -0,0 +1,355 @@
+//! Attribution policy gate
+//!
+//! Evaluates a repo's policy rules against the commits in a range and
+//! reports pass/fail with one violation per broken rule, so pre-push hooks
+//! and CI can block on attribution (`narrative-cli policy check`).
+//!
+//! "Unreviewed" AI lines are AI-agent and AI-assist lines that no human
+//! edited; collaborative lines count as reviewed.
+
+use super::batch::compute_commit_stats;
+use super::directories::range_commits;
+use super::models::ContributionStats;
+use super::stats::fetch_cached_stats;
+use super::utils::fetch_repo_root;
+use crate::story_anchors::refs::fetch_notes_refs;
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+
+/// One configurable policy rule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PolicyRule {
+    /// Fail if any commit's unreviewed AI lines exceed `max_percent` of its lines
+    #[serde(rename_all = "camelCase")]
+    MaxUnreviewedAiPercent { max_percent: f64 },
+    /// Fail if AI lines across the whole range exceed `max_percent`
+    #[serde(rename_all = "camelCase")]
+    MaxRangeAiPercent { max_percent: f64 },
+    /// Fail if a commit with AI-attributed lines has no attribution note
+    RequireAttributionNotes,
+}
+
+impl PolicyRule {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PolicyRule::MaxUnreviewedAiPercent { .. } => "max_unreviewed_ai_percent",
+            PolicyRule::MaxRangeAiPercent { .. } => "max_range_ai_percent",
+            PolicyRule::RequireAttributionNotes => "require_attribution_notes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionPolicy {
+    pub rules: Vec<PolicyRule>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyViolation {
+    pub rule: String,
+    /// Offending commit (absent for range-wide rules)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyCheckResult {
+    pub passed: bool,
+    pub commits_checked: u32,
+    pub violations: Vec<PolicyViolation>,
+}
+
+/// What the rules need to know about one commit
+#[derive(Debug, Clone)]
+pub struct CommitPolicyInput {
+    pub commit_sha: String,
+    pub stats: Option<ContributionStats>,
+    pub has_note: bool,
+}
+
+fn ai_lines(stats: &ContributionStats) -> u32 {
+    stats.ai_agent_lines + stats.ai_assist_lines + stats.collaborative_lines
+}
+
+fn percent(part: u32, whole: u32) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    part as f64 / whole as f64 * 100.0
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+/// Evaluate `rules` against the commits of a range
+pub fn evaluate_policy(
+    rules: &[PolicyRule],
+    commits: &[CommitPolicyInput],
+) -> Vec<PolicyViolation> {
+    let mut violations = Vec::new();
+
+    for rule in rules {
+        match rule {
+            PolicyRule::MaxUnreviewedAiPercent { max_percent } => {
+                for commit in commits {
+                    let Some(stats) = &commit.stats else {
+                        continue;
+                    };
+                    let unreviewed = stats.ai_agent_lines + stats.ai_assist_lines;
+                    let share = percent(unreviewed, stats.total_lines);
+                    if share > *max_percent {
+                        violations.push(PolicyViolation {
+                            rule: rule.kind().to_string(),
+                            commit_sha: Some(commit.commit_sha.clone()),
+                            message: format!(
+                                "{} has {:.1}% unreviewed AI lines ({} of {}), above {}%",
+                                short_sha(&commit.commit_sha),
+                                share,
+                                unreviewed,
+                                stats.total_lines,
+                                max_percent
+                            ),
+                        });
+                    }
+                }
+            }
+            PolicyRule::MaxRangeAiPercent { max_percent } => {
+                let (ai, total) = commits
+                    .iter()
+                    .filter_map(|commit| commit.stats.as_ref())
+                    .fold((0, 0), |(ai, total), stats| {
+                        (ai + ai_lines(stats), total + stats.total_lines)
+                    });
+                let share = percent(ai, total);
+                if share > *max_percent {
+                    violations.push(PolicyViolation {
+                        rule: rule.kind().to_string(),
+                        commit_sha: None,
+                        message: format!(
+                            "Range has {:.1}% AI lines ({} of {}), above {}%",
+                            share, ai, total, max_percent
+                        ),
+                    });
+                }
+            }
+            PolicyRule::RequireAttributionNotes => {
+                for commit in commits {
+                    let has_ai = commit.stats.as_ref().is_some_and(|s| ai_lines(s) > 0);
+                    if has_ai && !commit.has_note {
+                        violations.push(PolicyViolation {
+                            rule: rule.kind().to_string(),
+                            commit_sha: Some(commit.commit_sha.clone()),
+                            message: format!(
+                                "{} has AI-attributed lines but no attribution note",
+                                short_sha(&commit.commit_sha)
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    violations
+}
+
+/// A repo's policy; no rules if none has been saved
+pub async fn fetch_attribution_policy(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<AttributionPolicy, String> {
+    let rules: Option<String> =
+        sqlx::query_scalar("SELECT rules FROM attribution_policies WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+    match rules {
+        Some(rules) => serde_json::from_str(&rules)
+            .map(|rules| AttributionPolicy { rules })
+            .map_err(|e| format!("Invalid attribution policy: {}", e)),
+        None => Ok(AttributionPolicy::default()),
+    }
+}
+
+pub async fn store_attribution_policy(
+    db: &SqlitePool,
+    repo_id: i64,
+    policy: &AttributionPolicy,
+) -> Result<(), String> {
+    for rule in &policy.rules {
+        if let PolicyRule::MaxUnreviewedAiPercent { max_percent }
+        | PolicyRule::MaxRangeAiPercent { max_percent } = rule
+        {
+            if !(0.0..=100.0).contains(max_percent) {
+                return Err(format!(
+                    "{}: maxPercent must be between 0 and 100",
+                    rule.kind()
+                ));
+            }
+        }
+    }
+    let rules = serde_json::to_string(&policy.rules).map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO attribution_policies (repo_id, rules)
+        VALUES (?, ?)
+        ON CONFLICT(repo_id) DO UPDATE SET
+            rules = excluded.rules,
+            updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        "#,
+    )
+    .bind(repo_id)
+    .bind(rules)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(())
+}
+
+/// Check the commits in `from..to` (`to` defaults to HEAD) against `policy`,
+/// or the repo's saved policy when none is given. Missing stats are computed.
+pub async fn check_attribution_policy(
+    db: &SqlitePool,
+    repo_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+    policy: Option<AttributionPolicy>,
+) -> Result<PolicyCheckResult, String> {
+    let policy = match policy {
+        Some(policy) => policy,
+        None => fetch_attribution_policy(db, repo_id).await?,
+    };
+    let needs_notes = policy
+        .rules
+        .iter()
+        .any(|rule| matches!(rule, PolicyRule::RequireAttributionNotes));
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
+    let (shas, noted) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let (_, _, shas) = range_commits(&repo, from, to.unwrap_or("HEAD"))?;
+        let noted = if needs_notes {
+            shas.iter()
+                .filter(|sha| {
+                    Oid::from_str(sha).is_ok_and(|oid| {
+                        refs.attribution_import_precedence()
+                            .into_iter()
+                            .any(|note_ref| repo.find_note(Some(note_ref), oid).is_ok())
+                    })
+                })
+                .cloned()
+                .collect::<HashSet<_>>()
+        } else {
+            HashSet::new()
+        };
+        (shas, noted)
+    };
+
+    let mut commits = Vec::with_capacity(shas.len());
+    for sha in shas {
+        compute_commit_stats(db, repo_id, &sha).await;
+        commits.push(CommitPolicyInput {
+            stats: fetch_cached_stats(db, repo_id, &sha).await,
+            has_note: noted.contains(&sha),
+            commit_sha: sha,
+        });
+    }
+
+    let violations = evaluate_policy(&policy.rules, &commits);
+    Ok(PolicyCheckResult {
+        passed: violations.is_empty(),
+        commits_checked: commits.len() as u32,
+        violations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(
+        sha: &str,
+        agent: u32,
+        collaborative: u32,
+        total: u32,
+        has_note: bool,
+    ) -> CommitPolicyInput {
+        CommitPolicyInput {
+            commit_sha: sha.to_string(),
+            stats: Some(ContributionStats {
+                ai_agent_lines: agent,
+                collaborative_lines: collaborative,
+                human_lines: total - agent - collaborative,
+                total_lines: total,
+                ..Default::default()
+            }),
+            has_note,
+        }
+    }
+
+    #[test]
+    fn rules_report_offending_commits() {
+        let commits = [
+            commit("aaaaaaaaaa", 9, 0, 10, true),
+            // Collaborative lines were edited by a human, so they pass review
+            commit("bbbbbbbbbb", 1, 8, 10, false),
+            commit("cccccccccc", 0, 0, 10, false),
+        ];
+        let rules = [
+            PolicyRule::MaxUnreviewedAiPercent { max_percent: 80.0 },
+            PolicyRule::RequireAttributionNotes,
+        ];
+
+        let violations = evaluate_policy(&rules, &commits);
+        let found = violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.commit_sha.as_deref().unwrap_or("")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("max_unreviewed_ai_percent", "aaaaaaaaaa"),
+                ("require_attribution_notes", "bbbbbbbbbb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn range_rule_uses_total_ai_share() {
+        let commits = [commit("a", 6, 0, 10, true), commit("b", 0, 2, 10, true)];
+
+        let within = [PolicyRule::MaxRangeAiPercent { max_percent: 40.0 }];
+        assert!(evaluate_policy(&within, &commits).is_empty());
+
+        let over = [PolicyRule::MaxRangeAiPercent { max_percent: 39.0 }];
+        let violations = evaluate_policy(&over, &commits);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].commit_sha.is_none());
+    }
+
+    #[test]
+    fn rules_round_trip_as_tagged_json() {
+        let json = r#"[{"kind":"max_unreviewed_ai_percent","maxPercent":80},{"kind":"require_attribution_notes"}]"#;
+        let rules: Vec<PolicyRule> = serde_json::from_str(json).expect("rules");
+        assert_eq!(
+            rules,
+            vec![
+                PolicyRule::MaxUnreviewedAiPercent { max_percent: 80.0 },
+                PolicyRule::RequireAttributionNotes,
+            ]
+        );
+    }
+}
@@ -11,6 +11,19 @@ pub struct AttributionPrefs {
     pub show_line_overlays: bool,
     pub retention_days: Option<i32>,
     pub last_purged_at: Option<String>,
+    /// Append Narrative trailers to commit messages (prepare-commit-msg hook)
+    pub commit_trailers: bool,
+    /// Sign exported notes with the repo's git signing key
+    pub sign_notes: bool,
+    /// Only import notes signed by a trusted key
+    pub require_signed_notes: bool,
+    /// Export sessions notes in the background when auto-import links a session
+    pub auto_export_notes: bool,
+    /// Import newly fetched notes after a notes fetch or `git pull`
+    pub import_notes_after_fetch: bool,
+    /// Namespace of the repo's notes refs; `None` for `refs/notes/narrative`.
+    /// Changed with `set_notes_namespace`, which migrates existing notes.
+    pub notes_namespace: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -21,6 +34,11 @@ pub struct AttributionPrefsUpdate {
     pub show_line_overlays: Option<bool>,
     pub retention_days: Option<i32>,
     pub clear_retention_days: Option<bool>,
+    pub commit_trailers: Option<bool>,
+    pub sign_notes: Option<bool>,
+    pub require_signed_notes: Option<bool>,
+    pub auto_export_notes: Option<bool>,
+    pub import_notes_after_fetch: Option<bool>,
 }
 
 #[derive(sqlx::FromRow)]
@@ -31,6 +49,12 @@ struct AttributionPrefsRow {
     show_line_overlays: i32,
     retention_days: Option<i32>,
     last_purged_at: Option<String>,
+    commit_trailers: i32,
+    sign_notes: i32,
+    require_signed_notes: i32,
+    auto_export_notes: i32,
+    import_notes_after_fetch: i32,
+    notes_namespace: Option<String>,
 }
 
 impl AttributionPrefsRow {
@@ -42,6 +66,12 @@ impl AttributionPrefsRow {
             show_line_overlays: self.show_line_overlays != 0,
             retention_days: self.retention_days,
             last_purged_at: self.last_purged_at,
+            commit_trailers: self.commit_trailers != 0,
+            sign_notes: self.sign_notes != 0,
+            require_signed_notes: self.require_signed_notes != 0,
+            auto_export_notes: self.auto_export_notes != 0,
+            import_notes_after_fetch: self.import_notes_after_fetch != 0,
+            notes_namespace: self.notes_namespace,
         }
     }
 }
@@ -53,7 +83,9 @@ pub async fn fetch_or_create_prefs(
     if let Some(row) = sqlx::query_as::<_, AttributionPrefsRow>(
         r#"
         SELECT repo_id, cache_prompt_metadata, store_prompt_text, show_line_overlays,
-               retention_days, last_purged_at
+               retention_days, last_purged_at, commit_trailers, sign_notes,
+               require_signed_notes, auto_export_notes, import_notes_after_fetch,
+               notes_namespace
         FROM attribution_prefs
         WHERE repo_id = ?
         "#,
@@ -89,6 +121,12 @@ pub async fn fetch_or_create_prefs(
         show_line_overlays: true,
         retention_days: None,
         last_purged_at: None,
+        commit_trailers: false,
+        sign_notes: false,
+        require_signed_notes: false,
+        auto_export_notes: false,
+        import_notes_after_fetch: false,
+        notes_namespace: None,
     })
 }
 
@@ -117,6 +155,18 @@ pub async fn update_prefs(
             .unwrap_or(current.show_line_overlays),
         retention_days,
         last_purged_at: current.last_purged_at.clone(),
+        commit_trailers: update.commit_trailers.unwrap_or(current.commit_trailers),
+        sign_notes: update.sign_notes.unwrap_or(current.sign_notes),
+        require_signed_notes: update
+            .require_signed_notes
+            .unwrap_or(current.require_signed_notes),
+        auto_export_notes: update
+            .auto_export_notes
+            .unwrap_or(current.auto_export_notes),
+        import_notes_after_fetch: update
+            .import_notes_after_fetch
+            .unwrap_or(current.import_notes_after_fetch),
+        notes_namespace: current.notes_namespace.clone(),
     };
 
     sqlx::query(
@@ -126,6 +176,11 @@ pub async fn update_prefs(
             store_prompt_text = ?,
             show_line_overlays = ?,
             retention_days = ?,
+            commit_trailers = ?,
+            sign_notes = ?,
+            require_signed_notes = ?,
+            auto_export_notes = ?,
+            import_notes_after_fetch = ?,
             updated_at = CURRENT_TIMESTAMP
         WHERE repo_id = ?
         "#,
@@ -134,6 +189,11 @@ pub async fn update_prefs(
     .bind(if next.store_prompt_text { 1 } else { 0 })
     .bind(if next.show_line_overlays { 1 } else { 0 })
     .bind(next.retention_days)
+    .bind(if next.commit_trailers { 1 } else { 0 })
+    .bind(if next.sign_notes { 1 } else { 0 })
+    .bind(if next.require_signed_notes { 1 } else { 0 })
+    .bind(if next.auto_export_notes { 1 } else { 0 })
+    .bind(if next.import_notes_after_fetch { 1 } else { 0 })
     .bind(repo_id)
     .execute(db)
     .await
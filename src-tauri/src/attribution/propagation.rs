@@ -0,0 +1,428 @@
+//! Attribution propagation across history rewrites and later commits
+//!
+//! Line attributions are stored per commit, for the lines that commit changed.
+//! Two things make them go stale:
+//!
+//! - Rewrites (rebase, amend) create new commits with no attributions or
+//!   session links. Rewrites reported by the post-rewrite hook are recorded,
+//!   their session links carried over, and attributions are copied from the
+//!   old commit (or a patch-id match), with line ranges mapped through a diff
+//!   of each file between the two commits.
+//! - Later commits move or keep earlier lines. The Source Lens blames the file
+//!   and reads each line's attribution from the commit that last changed it.
+
+use super::line_attribution::{
+    ensure_line_attributions_for_commit, fetch_line_attributions,
+    fetch_line_attributions_for_commit,
+};
+use super::source_lens::LineAttributionRow;
+use super::utils::fetch_repo_root;
+use git2::{BlameOptions, DiffOptions, Oid, Patch, Repository};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Cap on distinct commits read when blaming a file
+const MAX_BLAME_ORIGINS: usize = 100;
+
+/// Lines of a file at `commit_sha` that came from one origin commit
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameSpan {
+    /// Commit that last changed these lines
+    pub commit_sha: String,
+    /// File path in the origin commit
+    pub orig_path: String,
+    /// First line in the origin commit's version of the file (1-based)
+    pub orig_start: i32,
+    /// First line in the blamed version of the file (1-based)
+    pub final_start: i32,
+    pub lines: i32,
+}
+
+/// Map each line of `old` to its line in `new` (1-based; index 0 is line 1).
+///
+/// Lines removed or rewritten between the two map to None.
+pub fn map_lines(old: &[u8], new: &[u8]) -> Result<Vec<Option<i32>>, String> {
+    let old_count = String::from_utf8_lossy(old).lines().count();
+    let mut map = vec![None; old_count];
+
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    let patch =
+        Patch::from_buffers(old, None, new, None, Some(&mut opts)).map_err(|e| e.to_string())?;
+
+    let mut fill = |from: usize, to: usize, delta: i64| {
+        for line in from..=to {
+            if let Some(slot) = line.checked_sub(1).and_then(|i| map.get_mut(i)) {
+                *slot = Some((line as i64 + delta) as i32);
+            }
+        }
+    };
+
+    let mut next_old = 1usize;
+    let mut delta = 0i64;
+    for idx in 0..patch.num_hunks() {
+        let (hunk, _) = patch.hunk(idx).map_err(|e| e.to_string())?;
+        let old_start = hunk.old_start() as usize;
+        let old_lines = hunk.old_lines() as usize;
+        // A pure insertion's old_start is the line it follows
+        let unchanged_end = if old_lines == 0 {
+            old_start
+        } else {
+            old_start - 1
+        };
+        fill(next_old, unchanged_end, delta);
+        next_old = if old_lines == 0 {
+            old_start + 1
+        } else {
+            old_start + old_lines
+        };
+        delta += hunk.new_lines() as i64 - old_lines as i64;
+    }
+    fill(next_old, old_count, delta);
+
+    Ok(map)
+}
+
+/// Map an inclusive line range through a line map, split into contiguous runs
+pub fn map_range(map: &[Option<i32>], start: i32, end: i32) -> Vec<(i32, i32)> {
+    let mut runs: Vec<(i32, i32)> = Vec::new();
+    for line in start.max(1)..=end {
+        let Some(Some(mapped)) = map.get(line as usize - 1) else {
+            continue;
+        };
+        match runs.last_mut() {
+            Some((_, run_end)) if *run_end + 1 == *mapped => *run_end = *mapped,
+            _ => runs.push((*mapped, *mapped)),
+        }
+    }
+    runs
+}
+
+/// Raw content of a file at a commit (None if it does not exist there)
+fn file_content(repo: &Repository, commit_sha: &str, file_path: &str) -> Option<Vec<u8>> {
+    let commit = repo.find_commit(Oid::from_str(commit_sha).ok()?).ok()?;
+    let entry = commit.tree().ok()?.get_path(Path::new(file_path)).ok()?;
+    let blob = repo.find_blob(entry.id()).ok()?;
+    Some(blob.content().to_vec())
+}
+
+/// Copy a commit's line attributions to another commit (e.g. its rebased
+/// copy), mapping line ranges through each file's diff between the two.
+///
+/// Returns the number of rows written.
+pub async fn propagate_line_attributions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    source_commit: &str,
+    target_commit: &str,
+) -> Result<u32, String> {
+    let rows = fetch_line_attributions_for_commit(db, repo_id, source_commit).await?;
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let mapped = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let mut maps: HashMap<String, Option<Vec<Option<i32>>>> = HashMap::new();
+        let mut mapped = Vec::new();
+        for row in rows {
+            let map = maps.entry(row.file_path.clone()).or_insert_with(|| {
+                let old = file_content(&repo, source_commit, &row.file_path)?;
+                let new = file_content(&repo, target_commit, &row.file_path)?;
+                map_lines(&old, &new).ok()
+            });
+            let Some(map) = map else {
+                continue;
+            };
+            for (start, end) in map_range(map, row.start_line, row.end_line) {
+                mapped.push((start, end, row.clone()));
+            }
+        }
+        mapped
+    };
+
+    let mut written = 0;
+    for (start, end, row) in mapped {
+        sqlx::query(
+            r#"
+            INSERT INTO line_attributions (
+                repo_id,
+                commit_sha,
+                file_path,
+                start_line,
+                end_line,
+                session_id,
+                author_type,
+                ai_percentage,
+                tool,
+                model,
+                source,
+                confidence
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(target_commit)
+        .bind(&row.file_path)
+        .bind(start)
+        .bind(end)
+        .bind(&row.session_id)
+        .bind(&row.author_type)
+        .bind(row.ai_percentage)
+        .bind(&row.tool)
+        .bind(&row.model)
+        .bind(&row.source)
+        .bind(row.confidence)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Record commits rewritten by rebase/amend and carry their session links
+/// (heuristic and Story Anchor) over to the new commits. Unlike rewrite-key
+/// recovery this follows git's own old→new mapping, so it also covers amends
+/// that changed the patch.
+pub async fn record_commit_rewrites(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    pairs: &[(String, String)],
+    command: &str,
+) -> Result<(), String> {
+    for (old_sha, new_sha) in pairs {
+        if old_sha == new_sha {
+            continue;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO commit_rewrites (repo_id, old_sha, new_sha, command)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(repo_id, new_sha, old_sha) DO NOTHING
+            "#,
+        )
+        .bind(repo_id)
+        .bind(old_sha)
+        .bind(new_sha)
+        .bind(command)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO session_links (
+                repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, review_reason
+            )
+            SELECT repo_id, session_id, ?, confidence, auto_linked, needs_review, review_reason
+            FROM session_links
+            WHERE repo_id = ? AND commit_sha = ?
+            ON CONFLICT(repo_id, session_id, commit_sha) DO NOTHING
+            "#,
+        )
+        .bind(new_sha)
+        .bind(repo_id)
+        .bind(old_sha)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO commit_session_links (repo_id, commit_sha, session_id, source, confidence)
+            SELECT repo_id, ?, session_id, 'recovered', COALESCE(confidence, 0.8)
+            FROM commit_session_links
+            WHERE repo_id = ? AND commit_sha = ?
+            ON CONFLICT(repo_id, commit_sha, session_id) DO NOTHING
+            "#,
+        )
+        .bind(new_sha)
+        .bind(repo_id)
+        .bind(old_sha)
+        .execute(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Commits `commit_sha` was rewritten from that have line attributions
+pub async fn fetch_attributed_rewrite_sources(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<Vec<String>, String> {
+    sqlx::query_scalar(
+        r#"
+        SELECT cr.old_sha
+        FROM commit_rewrites cr
+        WHERE cr.repo_id = ? AND cr.new_sha = ?
+          AND EXISTS (
+            SELECT 1
+            FROM line_attributions la
+            WHERE la.repo_id = cr.repo_id AND la.commit_sha = cr.old_sha
+          )
+        ORDER BY cr.created_at DESC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Blame a file at a commit: which commit last changed each run of lines
+pub fn blame_spans(
+    repo: &Repository,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<Vec<BlameSpan>, String> {
+    let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
+    let mut opts = BlameOptions::new();
+    opts.newest_commit(oid);
+    // Follow lines into the file's old path when it was renamed
+    opts.track_copies_same_commit_moves(true);
+    let blame = repo
+        .blame_file(Path::new(file_path), Some(&mut opts))
+        .map_err(|e| e.to_string())?;
+
+    Ok(blame
+        .iter()
+        .map(|hunk| BlameSpan {
+            commit_sha: hunk.orig_commit_id().to_string(),
+            orig_path: hunk
+                .path()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+                .unwrap_or_else(|| file_path.to_string()),
+            orig_start: hunk.orig_start_line() as i32,
+            final_start: hunk.final_start_line() as i32,
+            lines: hunk.lines_in_hunk() as i32,
+        })
+        .collect())
+}
+
+/// Attributions for lines of a file that were last changed before
+/// `commit_sha`, read from the commits that changed them and shifted to
+/// their current line numbers.
+pub async fn inherited_line_attributions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    spans: &[BlameSpan],
+) -> Result<Vec<LineAttributionRow>, String> {
+    let mut by_origin: BTreeMap<(&str, &str), Vec<&BlameSpan>> = BTreeMap::new();
+    for span in spans.iter().filter(|s| s.commit_sha != commit_sha) {
+        by_origin
+            .entry((span.commit_sha.as_str(), span.orig_path.as_str()))
+            .or_default()
+            .push(span);
+    }
+
+    let mut inherited = Vec::new();
+    for ((origin, path), spans) in by_origin.into_iter().take(MAX_BLAME_ORIGINS) {
+        let mut rows = fetch_line_attributions(db, repo_id, origin, path).await?;
+        if rows.is_empty() {
+            let _ = ensure_line_attributions_for_commit(db, repo_id, origin).await;
+            rows = fetch_line_attributions(db, repo_id, origin, path).await?;
+        }
+        for span in spans {
+            let span_end = span.orig_start + span.lines - 1;
+            let shift = span.final_start - span.orig_start;
+            for row in &rows {
+                let start = row.start_line.max(span.orig_start);
+                let end = row.end_line.min(span_end);
+                if start > end {
+                    continue;
+                }
+                inherited.push(LineAttributionRow {
+                    start_line: start + shift,
+                    end_line: end + shift,
+                    session_id: row.session_id.clone(),
+                    author_type: row.author_type.clone(),
+                    ai_percentage: row.ai_percentage,
+                    tool: row.tool.clone(),
+                    model: row.model.clone(),
+                    source: row.source.clone(),
+                    confidence: row.confidence,
+                    trace_available: row.trace_available,
+                });
+            }
+        }
+    }
+
+    Ok(inherited)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_file(repo: &Repository, dir: &Path, content: &str, message: &str) -> String {
+        std::fs::write(dir.join("lib.rs"), content).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("lib.rs")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn lines_map_through_insertions_and_edits() {
+        let old = b"a\nb\nc\nd\n";
+        // Two lines inserted at the top, "c" rewritten
+        let new = b"x\ny\na\nb\nC\nd\n";
+        let map = map_lines(old, new).unwrap();
+        assert_eq!(map, vec![Some(3), Some(4), None, Some(6)]);
+        assert_eq!(map_range(&map, 1, 4), vec![(3, 4), (6, 6)]);
+    }
+
+    #[test]
+    fn blame_points_kept_lines_at_their_origin_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit_file(&repo, dir.path(), "fn a() {}\nfn b() {}\n", "first");
+        let second = commit_file(
+            &repo,
+            dir.path(),
+            "// header\nfn a() {}\nfn b() {}\n",
+            "second",
+        );
+
+        let spans = blame_spans(&repo, &second, "lib.rs").unwrap();
+        assert_eq!(
+            spans,
+            vec![
+                BlameSpan {
+                    commit_sha: second.clone(),
+                    orig_path: "lib.rs".to_string(),
+                    orig_start: 1,
+                    final_start: 1,
+                    lines: 1,
+                },
+                BlameSpan {
+                    commit_sha: first,
+                    orig_path: "lib.rs".to_string(),
+                    orig_start: 1,
+                    final_start: 2,
+                    lines: 2,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,553 @@
+//! Attribution report export
+//!
+//! Builds a shareable report for a repo's history (or a commit range):
+//! overall totals, a per-commit table, and per-tool and per-language
+//! breakdowns, rendered as CSV, JSON, or Markdown.
+
+use super::directories::range_commits;
+use super::exclusions::load_exclusion_rules;
+use super::languages::{commit_file_lines, language_breakdown, load_language_map, LanguageStats};
+use super::models::ContributionStats;
+use super::session_stats::store_contribution_stats;
+use super::stats::{compute_contribution_from_attributions, fetch_cached_stats};
+use super::utils::fetch_repo_root;
+use git2::{Oid, Repository};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionReport {
+    pub repo_path: String,
+    /// Exclusive start of the range (None: from the root commit)
+    pub from_sha: Option<String>,
+    pub to_sha: String,
+    pub generated_at: String,
+    pub totals: ReportTotals,
+    /// Newest first
+    pub commits: Vec<ReportCommit>,
+    /// Most lines first
+    pub tools: Vec<ReportTool>,
+    /// Lines added per language by the attributed commits, most lines first
+    pub languages: Vec<LanguageStats>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTotals {
+    /// Commits in the range
+    pub commits: u32,
+    /// Commits with attribution data (the rest are not in the tables below)
+    pub attributed_commits: u32,
+    pub total_lines: u32,
+    pub human_lines: u32,
+    pub ai_agent_lines: u32,
+    pub ai_assist_lines: u32,
+    pub collaborative_lines: u32,
+    pub ai_percentage: f64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportCommit {
+    pub sha: String,
+    pub author: String,
+    pub authored_at: String,
+    pub subject: String,
+    pub total_lines: u32,
+    pub human_lines: u32,
+    pub ai_agent_lines: u32,
+    pub ai_assist_lines: u32,
+    pub collaborative_lines: u32,
+    pub ai_percentage: f64,
+    pub primary_tool: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTool {
+    pub tool: String,
+    pub model: Option<String>,
+    pub commits: u32,
+    pub line_count: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionReportExport {
+    pub path: String,
+    pub format: ReportFormat,
+    pub commits: u32,
+}
+
+fn percentage(ai_lines: u32, total_lines: u32) -> f64 {
+    if total_lines == 0 {
+        return 0.0;
+    }
+    ai_lines as f64 / total_lines as f64 * 100.0
+}
+
+/// Sum commit rows and their tool breakdowns into report totals
+pub fn summarize(
+    commits: &[ReportCommit],
+    tool_rows: &[(String, Option<String>, u32)],
+    range_commits: u32,
+) -> (ReportTotals, Vec<ReportTool>) {
+    let mut totals = ReportTotals {
+        commits: range_commits,
+        attributed_commits: commits.len() as u32,
+        ..Default::default()
+    };
+    for commit in commits {
+        totals.total_lines += commit.total_lines;
+        totals.human_lines += commit.human_lines;
+        totals.ai_agent_lines += commit.ai_agent_lines;
+        totals.ai_assist_lines += commit.ai_assist_lines;
+        totals.collaborative_lines += commit.collaborative_lines;
+    }
+    totals.ai_percentage = percentage(
+        totals.ai_agent_lines + totals.ai_assist_lines + totals.collaborative_lines,
+        totals.total_lines,
+    );
+
+    let mut by_tool: BTreeMap<(String, Option<String>), ReportTool> = BTreeMap::new();
+    for (tool, model, line_count) in tool_rows {
+        let entry = by_tool
+            .entry((tool.clone(), model.clone()))
+            .or_insert_with(|| ReportTool {
+                tool: tool.clone(),
+                model: model.clone(),
+                commits: 0,
+                line_count: 0,
+            });
+        entry.commits += 1;
+        entry.line_count += line_count;
+    }
+    let mut tools: Vec<ReportTool> = by_tool.into_values().collect();
+    tools.sort_by(|a, b| b.line_count.cmp(&a.line_count));
+
+    (totals, tools)
+}
+
+/// Stats for a commit, computing and caching them from line attributions
+/// when they have not been computed yet
+async fn commit_stats(db: &SqlitePool, repo_id: i64, sha: &str) -> Option<ContributionStats> {
+    if let Some(stats) = fetch_cached_stats(db, repo_id, sha).await {
+        return Some(stats);
+    }
+    let stats = compute_contribution_from_attributions(db, repo_id, sha)
+        .await
+        .ok()
+        .flatten()?;
+    let _ = store_contribution_stats(db, repo_id, sha, None, &stats).await;
+    Some(stats)
+}
+
+/// Build a report for the commits in `from..to` (`to` defaults to HEAD,
+/// no `from` covers the whole history)
+pub async fn build_attribution_report(
+    db: &SqlitePool,
+    repo_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<AttributionReport, String> {
+    let repo_path = fetch_repo_root(db, repo_id).await?;
+    let (from_sha, to_sha, commit_meta) = {
+        let repo = Repository::open(&repo_path).map_err(|e| e.to_string())?;
+        let (from_sha, to_sha, shas) = range_commits(&repo, from, to.unwrap_or("HEAD"))?;
+        let commit_meta = shas
+            .into_iter()
+            .map(|sha| {
+                let commit = Oid::from_str(&sha)
+                    .and_then(|oid| repo.find_commit(oid))
+                    .ok();
+                let author = commit
+                    .as_ref()
+                    .and_then(|c| c.author().name().map(str::to_string))
+                    .unwrap_or_default();
+                let authored_at = commit
+                    .as_ref()
+                    .and_then(|c| chrono::DateTime::from_timestamp(c.time().seconds(), 0))
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default();
+                let subject = commit
+                    .as_ref()
+                    .and_then(|c| c.summary().map(str::to_string))
+                    .unwrap_or_default();
+                (sha, author, authored_at, subject)
+            })
+            .collect::<Vec<_>>();
+        (from_sha, to_sha, commit_meta)
+    };
+
+    let range_len = commit_meta.len() as u32;
+    let mut commits = Vec::new();
+    let mut tool_rows = Vec::new();
+    // path -> (ai lines, added lines)
+    let mut file_lines: HashMap<String, (u32, u32)> = HashMap::new();
+    for (sha, author, authored_at, subject) in commit_meta {
+        let Some(stats) = commit_stats(db, repo_id, &sha).await else {
+            continue;
+        };
+        for (path, (ai_lines, added_lines)) in commit_file_lines(db, repo_id, &sha).await? {
+            let entry = file_lines.entry(path).or_default();
+            entry.0 += ai_lines;
+            entry.1 += added_lines;
+        }
+        for tool in stats.tool_breakdown.iter().flatten() {
+            tool_rows.push((tool.tool.clone(), tool.model.clone(), tool.line_count));
+        }
+        commits.push(ReportCommit {
+            sha,
+            author,
+            authored_at,
+            subject,
+            total_lines: stats.total_lines,
+            human_lines: stats.human_lines,
+            ai_agent_lines: stats.ai_agent_lines,
+            ai_assist_lines: stats.ai_assist_lines,
+            collaborative_lines: stats.collaborative_lines,
+            ai_percentage: percentage(
+                stats.ai_agent_lines + stats.ai_assist_lines + stats.collaborative_lines,
+                stats.total_lines,
+            ),
+            primary_tool: stats.primary_tool,
+            model: stats.model,
+        });
+    }
+
+    let (totals, tools) = summarize(&commits, &tool_rows, range_len);
+    let languages = language_breakdown(
+        &load_language_map(db, repo_id).await,
+        &load_exclusion_rules(db, repo_id).await,
+        file_lines
+            .iter()
+            .map(|(path, (ai_lines, added_lines))| (path.as_str(), *ai_lines, *added_lines)),
+    );
+    Ok(AttributionReport {
+        repo_path,
+        from_sha,
+        to_sha,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        totals,
+        commits,
+        tools,
+        languages,
+    })
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+/// Render a report in the requested format
+pub fn render_report(report: &AttributionReport, format: ReportFormat) -> Result<String, String> {
+    match format {
+        ReportFormat::Json => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+        ReportFormat::Csv => Ok(render_csv(report)),
+        ReportFormat::Markdown => Ok(render_markdown(report)),
+    }
+}
+
+/// CSV with one section per table, separated by a blank line
+fn render_csv(report: &AttributionReport) -> String {
+    let t = &report.totals;
+    let mut out = String::new();
+    out.push_str("commits,attributed_commits,total_lines,human_lines,ai_agent_lines,ai_assist_lines,collaborative_lines,ai_percentage\n");
+    out.push_str(&format!(
+        "{},{},{},{},{},{},{},{:.1}\n\n",
+        t.commits,
+        t.attributed_commits,
+        t.total_lines,
+        t.human_lines,
+        t.ai_agent_lines,
+        t.ai_assist_lines,
+        t.collaborative_lines,
+        t.ai_percentage
+    ));
+
+    out.push_str("sha,author,authored_at,subject,total_lines,human_lines,ai_agent_lines,ai_assist_lines,collaborative_lines,ai_percentage,tool,model\n");
+    for c in &report.commits {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{:.1},{},{}\n",
+            c.sha,
+            csv_field(&c.author),
+            c.authored_at,
+            csv_field(&c.subject),
+            c.total_lines,
+            c.human_lines,
+            c.ai_agent_lines,
+            c.ai_assist_lines,
+            c.collaborative_lines,
+            c.ai_percentage,
+            csv_field(c.primary_tool.as_deref().unwrap_or("")),
+            csv_field(c.model.as_deref().unwrap_or(""))
+        ));
+    }
+
+    out.push_str("\ntool,model,commits,line_count\n");
+    for tool in &report.tools {
+        out.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&tool.tool),
+            csv_field(tool.model.as_deref().unwrap_or("")),
+            tool.commits,
+            tool.line_count
+        ));
+    }
+
+    out.push_str("\nlanguage,files,total_lines,ai_lines,human_lines,ai_percentage\n");
+    for language in &report.languages {
+        out.push_str(&format!(
+            "{},{},{},{},{},{:.1}\n",
+            csv_field(&language.language),
+            language.files,
+            language.total_lines,
+            language.ai_lines,
+            language.human_lines,
+            language.ai_percentage
+        ));
+    }
+    out
+}
+
+fn render_markdown(report: &AttributionReport) -> String {
+    let t = &report.totals;
+    let short = |sha: &str| sha.chars().take(8).collect::<String>();
+    let range = match &report.from_sha {
+        Some(from) => format!("`{}..{}`", short(from), short(&report.to_sha)),
+        None => format!("history up to `{}`", short(&report.to_sha)),
+    };
+
+    let mut out = String::from("# Attribution report\n\n");
+    out.push_str(&format!(
+        "- Repository: `{}`\n- Range: {}\n- Generated: {}\n\n",
+        report.repo_path, range, report.generated_at
+    ));
+
+    out.push_str("## Totals\n\n");
+    out.push_str("| Commits | Attributed | Total lines | Human | AI agent | AI assist | Collaborative | AI % |\n");
+    out.push_str("| ---: | ---: | ---: | ---: | ---: | ---: | ---: | ---: |\n");
+    out.push_str(&format!(
+        "| {} | {} | {} | {} | {} | {} | {} | {:.1} |\n\n",
+        t.commits,
+        t.attributed_commits,
+        t.total_lines,
+        t.human_lines,
+        t.ai_agent_lines,
+        t.ai_assist_lines,
+        t.collaborative_lines,
+        t.ai_percentage
+    ));
+
+    out.push_str("## Commits\n\n");
+    if report.commits.is_empty() {
+        out.push_str("No commits with attribution data.\n\n");
+    } else {
+        out.push_str("| Commit | Author | Date | Subject | Lines | AI % | Tool |\n");
+        out.push_str("| --- | --- | --- | --- | ---: | ---: | --- |\n");
+        for c in &report.commits {
+            out.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} | {:.1} | {} |\n",
+                short(&c.sha),
+                markdown_cell(&c.author),
+                c.authored_at.get(..10).unwrap_or(&c.authored_at),
+                markdown_cell(&c.subject),
+                c.total_lines,
+                c.ai_percentage,
+                markdown_cell(c.primary_tool.as_deref().unwrap_or("-"))
+            ));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Tools\n\n");
+    if report.tools.is_empty() {
+        out.push_str("No AI tools recorded.\n");
+    } else {
+        out.push_str("| Tool | Model | Commits | Lines |\n");
+        out.push_str("| --- | --- | ---: | ---: |\n");
+        for tool in &report.tools {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                markdown_cell(&tool.tool),
+                markdown_cell(tool.model.as_deref().unwrap_or("-")),
+                tool.commits,
+                tool.line_count
+            ));
+        }
+    }
+
+    out.push_str("\n## Languages\n\n");
+    if report.languages.is_empty() {
+        out.push_str("No file changes recorded.\n");
+    } else {
+        out.push_str("| Language | Files | Lines | AI | Human | AI % |\n");
+        out.push_str("| --- | ---: | ---: | ---: | ---: | ---: |\n");
+        for language in &report.languages {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.1} |\n",
+                markdown_cell(&language.language),
+                language.files,
+                language.total_lines,
+                language.ai_lines,
+                language.human_lines,
+                language.ai_percentage
+            ));
+        }
+    }
+    out
+}
+
+/// Build, render, and write a report to `output_path`
+pub async fn export_attribution_report(
+    db: &SqlitePool,
+    repo_id: i64,
+    format: ReportFormat,
+    from: Option<&str>,
+    to: Option<&str>,
+    output_path: &str,
+) -> Result<AttributionReportExport, String> {
+    let output_path = output_path.trim();
+    if output_path.is_empty() {
+        return Err("Output path is empty".to_string());
+    }
+    let report = build_attribution_report(db, repo_id, from, to).await?;
+    let contents = render_report(&report, format)?;
+
+    let path = Path::new(output_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("write failed: {e}"))?;
+
+    Ok(AttributionReportExport {
+        path: output_path.to_string(),
+        format,
+        commits: report.totals.attributed_commits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> AttributionReport {
+        let commits = vec![
+            ReportCommit {
+                sha: "a1b2c3d4e5f6".to_string(),
+                author: "Ada, L.".to_string(),
+                authored_at: "2026-02-01T10:00:00+00:00".to_string(),
+                subject: "Add parser | lexer".to_string(),
+                total_lines: 40,
+                human_lines: 10,
+                ai_agent_lines: 30,
+                ai_assist_lines: 0,
+                collaborative_lines: 0,
+                ai_percentage: 75.0,
+                primary_tool: Some("claude_code".to_string()),
+                model: None,
+            },
+            ReportCommit {
+                sha: "f6e5d4c3b2a1".to_string(),
+                author: "Lin".to_string(),
+                authored_at: "2026-01-30T09:00:00+00:00".to_string(),
+                subject: "Fix \"quoted\" bug".to_string(),
+                total_lines: 20,
+                human_lines: 15,
+                ai_agent_lines: 0,
+                ai_assist_lines: 5,
+                collaborative_lines: 0,
+                ai_percentage: 25.0,
+                primary_tool: Some("cursor".to_string()),
+                model: None,
+            },
+        ];
+        let (totals, tools) = summarize(
+            &commits,
+            &[
+                ("claude_code".to_string(), None, 30),
+                ("cursor".to_string(), None, 5),
+            ],
+            3,
+        );
+        AttributionReport {
+            repo_path: "/tmp/repo".to_string(),
+            from_sha: None,
+            to_sha: "a1b2c3d4e5f6".to_string(),
+            generated_at: "2026-02-02T00:00:00+00:00".to_string(),
+            totals,
+            commits,
+            tools,
+            languages: vec![LanguageStats {
+                language: "Rust".to_string(),
+                files: 2,
+                total_lines: 60,
+                ai_lines: 35,
+                human_lines: 25,
+                ai_percentage: 35.0 / 60.0 * 100.0,
+            }],
+        }
+    }
+
+    #[test]
+    fn totals_and_tools_are_summed() {
+        let report = sample_report();
+        assert_eq!(report.totals.commits, 3);
+        assert_eq!(report.totals.attributed_commits, 2);
+        assert_eq!(report.totals.total_lines, 60);
+        assert_eq!(report.totals.human_lines, 25);
+        assert!((report.totals.ai_percentage - 35.0 / 60.0 * 100.0).abs() < 1e-9);
+        assert_eq!(report.tools[0].tool, "claude_code");
+        assert_eq!(report.tools[0].line_count, 30);
+    }
+
+    #[test]
+    fn csv_escapes_fields_and_separates_sections() {
+        let csv = render_report(&sample_report(), ReportFormat::Csv).unwrap();
+        let sections: Vec<&str> = csv.split("\n\n").collect();
+        assert_eq!(sections.len(), 4);
+        assert!(sections[1].contains("a1b2c3d4e5f6,\"Ada, L.\","));
+        assert!(sections[1].contains("\"Fix \"\"quoted\"\" bug\""));
+        assert!(sections[2].starts_with("tool,model,commits,line_count\nclaude_code,,1,30"));
+        assert_eq!(
+            sections[3],
+            "language,files,total_lines,ai_lines,human_lines,ai_percentage\nRust,2,60,35,25,58.3\n"
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_table_cells() {
+        let md = render_report(&sample_report(), ReportFormat::Markdown).unwrap();
+        assert!(md.contains("- Range: history up to `a1b2c3d4`"));
+        assert!(md.contains("| `a1b2c3d4` | Ada, L. | 2026-02-01 | Add parser \\| lexer | 40 | 75.0 | claude_code |"));
+        assert!(md.contains("| cursor | - | 1 | 5 |"));
+        assert!(md.contains("| Rust | 2 | 60 | 35 | 25 | 58.3 |"));
+    }
+
+    #[test]
+    fn json_uses_camel_case() {
+        let json = render_report(&sample_report(), ReportFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["totals"]["attributedCommits"], 2);
+        assert_eq!(value["commits"][1]["aiAssistLines"], 5);
+    }
+}
@@ -0,0 +1,232 @@
+//! SARIF export of AI-attributed line ranges
+//!
+//! Writes the repo's line attributions as a SARIF 2.1.0 log so code-scanning
+//! UIs and review tooling can overlay AI authorship on diffs. Each AI-agent,
+//! AI-assist, or collaborative range becomes one `note`-level result; line
+//! numbers refer to the file as of the result's commit (`properties.commitSha`).
+
+use super::directories::range_commits;
+use super::exclusions::load_exclusion_rules;
+use super::line_attribution::{fetch_line_attributions_for_commit, LineAttributionCommitRow};
+use super::utils::fetch_repo_root;
+use git2::Repository;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use std::path::Path;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// SARIF rule for each attributed author type (human ranges are not exported)
+fn rule_for(author_type: &str) -> Option<(&'static str, &'static str)> {
+    match author_type {
+        "ai_agent" => Some(("ai-agent", "Lines written by an AI agent")),
+        "ai_tab" => Some(("ai-assist", "Lines accepted from AI completions")),
+        "mixed" => Some((
+            "collaborative",
+            "Lines written by an AI and edited by a human",
+        )),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifExport {
+    pub path: String,
+    pub commits: u32,
+    pub results: u32,
+}
+
+/// One SARIF result for an attributed range, or None for human ranges
+pub fn sarif_result(commit_sha: &str, row: &LineAttributionCommitRow) -> Option<Value> {
+    let (rule_id, description) = rule_for(&row.author_type)?;
+    let start = row.start_line.max(1);
+    let end = row.end_line.max(start);
+
+    let source = match (&row.tool, &row.model) {
+        (Some(tool), Some(model)) => format!(" ({tool}, {model})"),
+        (Some(tool), None) => format!(" ({tool})"),
+        _ => String::new(),
+    };
+    let lines = if start == end {
+        format!("Line {start}")
+    } else {
+        format!("Lines {start}-{end}")
+    };
+
+    Some(json!({
+        "ruleId": rule_id,
+        "level": "note",
+        "kind": "informational",
+        "message": { "text": format!("{lines}: {description}{source}.") },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": row.file_path, "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": start, "endLine": end }
+            }
+        }],
+        "partialFingerprints": {
+            "narrativeAttribution/v1": format!("{commit_sha}:{}:{start}-{end}", row.file_path)
+        },
+        "properties": {
+            "commitSha": commit_sha,
+            "authorType": row.author_type,
+            "tool": row.tool,
+            "model": row.model,
+            "sessionId": row.session_id,
+            "aiPercentage": row.ai_percentage,
+            "source": row.source,
+            "confidence": row.confidence,
+        }
+    }))
+}
+
+/// Assemble a SARIF log around the given results
+pub fn sarif_log(results: Vec<Value>, head_sha: &str, repository_uri: Option<&str>) -> Value {
+    let rules = ["ai_agent", "ai_tab", "mixed"]
+        .into_iter()
+        .filter_map(rule_for)
+        .map(|(id, description)| {
+            json!({
+                "id": id,
+                "shortDescription": { "text": description },
+                "defaultConfiguration": { "level": "note" }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut run = json!({
+        "tool": {
+            "driver": {
+                "name": "Narrative",
+                "version": env!("CARGO_PKG_VERSION"),
+                "informationUri": "https://github.com/jscraik/trace-narrative",
+                "rules": rules
+            }
+        },
+        "results": results
+    });
+    if let Some(uri) = repository_uri {
+        run["versionControlProvenance"] = json!([{
+            "repositoryUri": uri,
+            "revisionId": head_sha
+        }]);
+    }
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [run]
+    })
+}
+
+/// Write the attributed ranges of the commits in `from..to` (`to` defaults
+/// to HEAD) to a SARIF file at `output_path`
+pub async fn export_attribution_sarif(
+    db: &SqlitePool,
+    repo_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+    output_path: &str,
+) -> Result<SarifExport, String> {
+    let output_path = output_path.trim();
+    if output_path.is_empty() {
+        return Err("Output path is empty".to_string());
+    }
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let (to_sha, shas, repository_uri) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let (_, to_sha, shas) = range_commits(&repo, from, to.unwrap_or("HEAD"))?;
+        let repository_uri = repo
+            .find_remote("origin")
+            .ok()
+            .and_then(|remote| remote.url().map(str::to_string));
+        (to_sha, shas, repository_uri)
+    };
+
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    let mut results = Vec::new();
+    let mut commits = 0;
+    for sha in &shas {
+        let before = results.len();
+        for row in fetch_line_attributions_for_commit(db, repo_id, sha).await? {
+            if exclusions.is_excluded(&row.file_path) {
+                continue;
+            }
+            results.extend(sarif_result(sha, &row));
+        }
+        if results.len() > before {
+            commits += 1;
+        }
+    }
+
+    let result_count = results.len() as u32;
+    let log = sarif_log(results, &to_sha, repository_uri.as_deref());
+    let contents = serde_json::to_string_pretty(&log).map_err(|e| e.to_string())?;
+
+    let path = Path::new(output_path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("write failed: {e}"))?;
+
+    Ok(SarifExport {
+        path: output_path.to_string(),
+        commits,
+        results: result_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(author_type: &str, start: i32, end: i32) -> LineAttributionCommitRow {
+        LineAttributionCommitRow {
+            file_path: "src/lib.rs".to_string(),
+            start_line: start,
+            end_line: end,
+            session_id: Some("s1".to_string()),
+            author_type: author_type.to_string(),
+            ai_percentage: Some(100),
+            tool: Some("claude_code".to_string()),
+            model: Some("opus".to_string()),
+            source: "tool_diff".to_string(),
+            confidence: 0.9,
+        }
+    }
+
+    #[test]
+    fn attributed_ranges_become_note_results() {
+        let result = sarif_result("abc123", &row("ai_agent", 3, 7)).unwrap();
+        assert_eq!(result["ruleId"], "ai-agent");
+        assert_eq!(result["level"], "note");
+        assert_eq!(
+            result["message"]["text"],
+            "Lines 3-7: Lines written by an AI agent (claude_code, opus)."
+        );
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/lib.rs");
+        assert_eq!(location["region"]["startLine"], 3);
+        assert_eq!(location["region"]["endLine"], 7);
+        assert_eq!(result["properties"]["commitSha"], "abc123");
+
+        assert!(sarif_result("abc123", &row("human", 1, 2)).is_none());
+    }
+
+    #[test]
+    fn log_declares_rules_and_provenance() {
+        let results = vec![sarif_result("abc123", &row("mixed", 4, 4)).unwrap()];
+        let log = sarif_log(results, "abc123", Some("https://example.com/repo.git"));
+        assert_eq!(log["version"], "2.1.0");
+        let run = &log["runs"][0];
+        assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 3);
+        assert_eq!(run["results"][0]["ruleId"], "collaborative");
+        assert_eq!(
+            run["results"][0]["message"]["text"],
+            "Line 4: Lines written by an AI and edited by a human (claude_code, opus)."
+        );
+        assert_eq!(run["versionControlProvenance"][0]["revisionId"], "abc123");
+    }
+}
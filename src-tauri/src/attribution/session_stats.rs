@@ -59,6 +59,7 @@ pub fn compute_session_contribution(
         }]),
         primary_tool: Some(tool),
         model: None,
+        is_merge: false,
     }
 }
 
@@ -82,8 +83,9 @@ pub async fn store_contribution_stats(
         r#"
         INSERT INTO commit_contribution_stats 
             (repo_id, commit_sha, human_lines, ai_agent_lines, ai_assist_lines, 
-             collaborative_lines, total_lines, ai_percentage, primary_session_id, tool, model)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             collaborative_lines, total_lines, ai_percentage, primary_session_id, tool, model,
+             is_merge)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(repo_id, commit_sha) DO UPDATE SET
             human_lines = excluded.human_lines,
             ai_agent_lines = excluded.ai_agent_lines,
@@ -94,6 +96,7 @@ pub async fn store_contribution_stats(
             primary_session_id = COALESCE(excluded.primary_session_id, commit_contribution_stats.primary_session_id),
             tool = COALESCE(excluded.tool, commit_contribution_stats.tool),
             model = COALESCE(excluded.model, commit_contribution_stats.model),
+            is_merge = excluded.is_merge,
             computed_at = datetime('now')
         "#
     )
@@ -108,6 +111,7 @@ pub async fn store_contribution_stats(
     .bind(session_id)
     .bind(&stats.primary_tool)
     .bind(&stats.model)
+    .bind(stats.is_merge)
     .execute(db)
     .await?;
 
@@ -166,6 +170,7 @@ mod tests {
                     files: None,
                 },
             ],
+            edit_fingerprints: Vec::new(),
         }
     }
 
@@ -188,4 +193,62 @@ mod tests {
         assert_eq!(stats.human_lines, 100);
         assert_eq!(stats.ai_percentage, 0.0);
     }
+
+    #[test]
+    fn cached_stats_are_dropped_when_attributions_or_links_change() {
+        use super::super::stats::fetch_cached_stats;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let stats = compute_human_contribution(10);
+            for sha in ["a", "b"] {
+                store_contribution_stats(&pool, 1, sha, None, &stats)
+                    .await
+                    .expect("store stats");
+            }
+
+            sqlx::query(
+                r#"
+                INSERT INTO line_attributions
+                    (repo_id, commit_sha, file_path, start_line, end_line, author_type)
+                VALUES (1, 'a', 'src/lib.rs', 1, 3, 'ai_agent')
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .expect("insert attribution");
+            assert!(fetch_cached_stats(&pool, 1, "a").await.is_none());
+            assert!(fetch_cached_stats(&pool, 1, "b").await.is_some());
+
+            store_contribution_stats(&pool, 1, "a", None, &stats)
+                .await
+                .expect("store stats");
+            sqlx::query(
+                "INSERT INTO session_links (repo_id, session_id, commit_sha, confidence) VALUES (1, 's1', 'a', 0.9)",
+            )
+            .execute(&pool)
+            .await
+            .expect("insert link");
+            assert!(fetch_cached_stats(&pool, 1, "a").await.is_none());
+
+            store_contribution_stats(&pool, 1, "a", None, &stats)
+                .await
+                .expect("store stats");
+            sqlx::query("UPDATE session_links SET commit_sha = 'b' WHERE session_id = 's1'")
+                .execute(&pool)
+                .await
+                .expect("relink");
+            assert!(fetch_cached_stats(&pool, 1, "a").await.is_none());
+            assert!(fetch_cached_stats(&pool, 1, "b").await.is_none());
+        });
+    }
 }
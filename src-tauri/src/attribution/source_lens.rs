@@ -1,7 +1,8 @@
 //! Source lens (line attribution display) with pagination
 
-use super::line_attribution::ensure_line_attributions_for_commit;
-use super::models::{SourceLensPage, SourceLine};
+use super::line_attribution::{ensure_line_attributions_for_commit, UNCERTAIN_CONFIDENCE};
+use super::models::{CurrentFileAttribution, SourceLensPage, SourceLine};
+use super::propagation::{blame_spans, inherited_line_attributions, BlameSpan};
 use super::{line_attribution::fetch_line_attributions, utils::fetch_repo_root};
 use git2::Repository;
 
@@ -15,13 +16,17 @@ pub struct LineAttributionRow {
     pub ai_percentage: Option<i32>,
     pub tool: Option<String>,
     pub model: Option<String>,
+    /// How the range was derived (see `line_attribution::SOURCE_*`)
+    pub source: String,
+    pub confidence: f64,
     pub trace_available: i32,
 }
 
 /// Get source lens for a file (Source Lens)
 ///
 /// Returns paginated source attribution for a file at a specific commit.
-/// Shows which lines were authored by agents vs humans.
+/// Shows which lines were authored by agents vs humans. Lines last changed
+/// by earlier commits take their attribution from those commits.
 pub async fn get_file_source_lens(
     db: &sqlx::SqlitePool,
     repo_id: i64,
@@ -33,8 +38,13 @@ pub async fn get_file_source_lens(
     let _ = ensure_line_attributions_for_commit(db, repo_id, commit_sha).await;
 
     let repo_root = fetch_repo_root(db, repo_id).await?;
-    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    let file_lines = load_file_lines(&repo, commit_sha, file_path)?;
+    let (file_lines, blame) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let file_lines = load_file_lines(&repo, commit_sha, file_path)?;
+        // Blame is best-effort: without it only this commit's lines are attributed
+        let blame = blame_spans(&repo, commit_sha, file_path).unwrap_or_default();
+        (file_lines, blame)
+    };
 
     if file_lines.is_empty() {
         return Ok(SourceLensPage {
@@ -44,8 +54,10 @@ pub async fn get_file_source_lens(
         });
     }
 
-    let attributions = fetch_line_attributions(db, repo_id, commit_sha, file_path).await?;
+    let mut attributions = fetch_line_attributions(db, repo_id, commit_sha, file_path).await?;
+    attributions.extend(inherited_line_attributions(db, repo_id, commit_sha, &blame).await?);
     let line_meta = build_line_meta(file_lines.len(), &attributions);
+    let origins = line_origins(file_lines.len(), &blame);
 
     let total_lines = file_lines.len() as u32;
     let start = offset as usize;
@@ -73,7 +85,13 @@ pub async fn get_file_source_lens(
                 ai_percentage: meta.ai_percentage,
                 tool: meta.tool,
                 model: meta.model,
+                uncertain: meta
+                    .confidence
+                    .is_some_and(|confidence| confidence < UNCERTAIN_CONFIDENCE),
+                source: meta.source,
+                confidence: meta.confidence,
                 trace_available: meta.trace_available,
+                commit_sha: origins.get(line_index).cloned().flatten(),
             }
         })
         .collect();
@@ -87,6 +105,51 @@ pub async fn get_file_source_lens(
     })
 }
 
+/// Get attribution for a file as it exists at HEAD
+///
+/// Blames the file at HEAD and merges each line's attribution from the
+/// commit that last changed it, so the view reflects today's file rather
+/// than a single commit's changes.
+pub async fn get_file_current_attribution(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    file_path: &str,
+    offset: u32,
+    limit: u32,
+) -> Result<CurrentFileAttribution, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let head_sha = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| format!("Failed to resolve HEAD: {}", e))?;
+        head.id().to_string()
+    };
+
+    let page = get_file_source_lens(db, repo_id, &head_sha, file_path, offset, limit).await?;
+
+    Ok(CurrentFileAttribution {
+        head_sha,
+        lines: page.lines,
+        total_lines: page.total_lines,
+        has_more: page.has_more,
+    })
+}
+
+/// Commit that last changed each line (index 0 is line 1)
+fn line_origins(total_lines: usize, blame: &[BlameSpan]) -> Vec<Option<String>> {
+    let mut origins = vec![None; total_lines];
+    for span in blame {
+        let start = span.final_start.max(1) as usize - 1;
+        let end = (start + span.lines.max(0) as usize).min(total_lines);
+        for origin in origins.iter_mut().take(end).skip(start) {
+            *origin = Some(span.commit_sha.clone());
+        }
+    }
+    origins
+}
+
 /// Load file content from git repository at specific commit
 pub fn load_file_lines(
     repo: &Repository,
@@ -117,6 +180,8 @@ pub struct LineMeta {
     pub ai_percentage: Option<u8>,
     pub tool: Option<String>,
     pub model: Option<String>,
+    pub source: Option<String>,
+    pub confidence: Option<f64>,
     pub trace_available: bool,
 }
 
@@ -128,6 +193,8 @@ impl Default for LineMeta {
             ai_percentage: None,
             tool: None,
             model: None,
+            source: None,
+            confidence: None,
             trace_available: false,
         }
     }
@@ -169,6 +236,8 @@ fn apply_line_attr(meta: &mut LineMeta, attr: &LineAttributionRow) {
         meta.ai_percentage = attr.ai_percentage.map(|v| v as u8);
         meta.tool = attr.tool.clone();
         meta.model = attr.model.clone();
+        meta.source = Some(attr.source.clone());
+        meta.confidence = Some(attr.confidence);
         meta.trace_available = incoming_trace;
         return;
     }
@@ -197,5 +266,63 @@ fn apply_line_attr(meta: &mut LineMeta, attr: &LineAttributionRow) {
     if meta.model.is_none() {
         meta.model = attr.model.clone();
     }
+    // Overlapping ranges are only as certain as the weakest of them
+    if meta
+        .confidence
+        .map_or(true, |confidence| attr.confidence < confidence)
+    {
+        meta.source = Some(attr.source.clone());
+        meta.confidence = Some(attr.confidence);
+    }
     meta.trace_available = meta.trace_available || incoming_trace;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_origins_follow_blame_spans() {
+        let span = |sha: &str, final_start: i32, lines: i32| BlameSpan {
+            commit_sha: sha.to_string(),
+            orig_path: "lib.rs".to_string(),
+            orig_start: 1,
+            final_start,
+            lines,
+        };
+        let origins = line_origins(4, &[span("head", 1, 1), span("older", 2, 5)]);
+        assert_eq!(
+            origins,
+            vec![
+                Some("head".to_string()),
+                Some("older".to_string()),
+                Some("older".to_string()),
+                Some("older".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_ranges_keep_the_weakest_confidence() {
+        let attr = |start: i32, end: i32, source: &str, confidence: f64| LineAttributionRow {
+            start_line: start,
+            end_line: end,
+            session_id: Some("s1".to_string()),
+            author_type: "ai_agent".to_string(),
+            ai_percentage: None,
+            tool: None,
+            model: None,
+            source: source.to_string(),
+            confidence,
+            trace_available: 0,
+        };
+        let meta = build_line_meta(
+            3,
+            &[attr(1, 2, "tool_diff", 0.9), attr(2, 3, "heuristic", 0.5)],
+        );
+        assert_eq!(meta[0].source.as_deref(), Some("tool_diff"));
+        assert_eq!(meta[1].source.as_deref(), Some("heuristic"));
+        assert_eq!(meta[1].confidence, Some(0.5));
+        assert_eq!(meta[2].confidence, Some(0.5));
+    }
+}
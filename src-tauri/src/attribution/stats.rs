@@ -1,5 +1,7 @@
 //! Contribution stats computation
 
+use super::exclusions::load_exclusion_rules;
+use super::git_utils::is_merge_commit;
 use super::line_attribution::LineAttributionCommitRow;
 use super::models::ContributionStats;
 use super::source_lens::LineMeta;
@@ -20,6 +22,7 @@ pub(super) struct ContributionStatsRow {
     pub ai_percentage: i32,
     pub tool: Option<String>,
     pub model: Option<String>,
+    pub is_merge: bool,
 }
 
 impl ContributionStatsRow {
@@ -37,6 +40,7 @@ impl ContributionStatsRow {
             tool_breakdown,
             primary_tool: self.tool,
             model: self.model,
+            is_merge: self.is_merge,
         }
     }
 }
@@ -54,6 +58,7 @@ pub(super) struct LinkedSessionRow {
     pub tool: String,
     pub model: Option<String>,
     pub files: Option<String>,
+    pub edit_fingerprints: Option<String>,
 }
 
 /// Fetch cached stats from database
@@ -65,7 +70,7 @@ pub async fn fetch_cached_stats(
     let row = sqlx::query_as::<_, ContributionStatsRow>(
         r#"
         SELECT human_lines, ai_agent_lines, ai_assist_lines, collaborative_lines,
-               total_lines, ai_percentage, tool, model
+               total_lines, ai_percentage, tool, model, is_merge
         FROM commit_contribution_stats
         WHERE repo_id = ? AND commit_sha = ?
         "#,
@@ -83,6 +88,18 @@ pub async fn fetch_cached_stats(
     row.map(|r| r.into_stats(breakdown))
 }
 
+/// True if the commit is a merge. Session-level estimates are not applied
+/// to merges: the merged commits carry that work, not the merge itself.
+pub async fn commit_is_merge(db: &sqlx::SqlitePool, repo_id: i64, commit_sha: &str) -> bool {
+    let Ok(repo_root) = fetch_repo_root(db, repo_id).await else {
+        return false;
+    };
+    Repository::open(&repo_root)
+        .ok()
+        .and_then(|repo| is_merge_commit(&repo, commit_sha).ok())
+        .unwrap_or(false)
+}
+
 /// Fetch linked session for a commit
 pub async fn fetch_linked_session(
     db: &sqlx::SqlitePool,
@@ -96,6 +113,8 @@ pub async fn fetch_linked_session(
         r#"
         SELECT * FROM session_links
         WHERE repo_id = ? AND commit_sha = ?
+        ORDER BY confidence DESC
+        LIMIT 1
         "#,
     )
     .bind(repo_id)
@@ -122,7 +141,7 @@ pub async fn fetch_linked_session(
     Ok(session)
 }
 
-/// Fetch files changed in a commit
+/// Fetch files changed in a commit, leaving out the repo's excluded files
 pub async fn fetch_commit_files(
     db: &sqlx::SqlitePool,
     repo_id: i64,
@@ -142,7 +161,11 @@ pub async fn fetch_commit_files(
     .await
     .map_err(|e| AttributionError::DatabaseError(e.to_string()))?;
 
-    Ok(rows)
+    let exclusions = load_exclusion_rules(db, repo_id).await;
+    Ok(rows
+        .into_iter()
+        .filter(|path| !exclusions.is_excluded(path))
+        .collect())
 }
 
 /// Fetch tool breakdown for a commit
@@ -196,13 +219,20 @@ pub async fn compute_contribution_from_attributions(
 
     let repo_root = fetch_repo_root(db, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+    let exclusions = load_exclusion_rules(db, repo_id).await;
 
     let mut by_file: HashMap<String, Vec<LineAttributionCommitRow>> = HashMap::new();
     for row in rows {
+        if exclusions.is_excluded(&row.file_path) {
+            continue;
+        }
         by_file.entry(row.file_path.clone()).or_default().push(row);
     }
 
-    let mut stats = ContributionStats::default();
+    let mut stats = ContributionStats {
+        is_merge: is_merge_commit(&repo, commit_sha).unwrap_or(false),
+        ..Default::default()
+    };
     let mut tool_counts: HashMap<(String, Option<String>), u32> = HashMap::new();
 
     for (file_path, attrs) in by_file {
@@ -224,6 +254,8 @@ pub async fn compute_contribution_from_attributions(
                 ai_percentage: row.ai_percentage,
                 tool: row.tool,
                 model: row.model,
+                source: row.source,
+                confidence: row.confidence,
                 trace_available: 0,
             })
             .collect::<Vec<_>>();
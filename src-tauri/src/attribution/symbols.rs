@@ -0,0 +1,544 @@
+//! Symbol-level attribution
+//!
+//! Parses a file with tree-sitter to find its functions, classes, and other
+//! named blocks, then rolls the file's line attributions up to each symbol.
+//! Results are stored in `symbol_attributions` and recomputed when the
+//! commit's line attributions change.
+
+use super::line_attribution::{ensure_line_attributions_for_commit, fetch_line_attributions};
+use super::propagation::{blame_spans, inherited_line_attributions};
+use super::source_lens::{build_line_meta, load_file_lines, LineMeta};
+use super::utils::fetch_repo_root;
+use git2::Repository;
+use std::collections::HashMap;
+use tree_sitter::{Node, Parser};
+
+/// Languages with a bundled tree-sitter grammar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Tsx,
+}
+
+impl SymbolLanguage {
+    fn grammar(self) -> tree_sitter::Language {
+        match self {
+            SymbolLanguage::Rust => tree_sitter_rust::LANGUAGE.into(),
+            SymbolLanguage::Python => tree_sitter_python::LANGUAGE.into(),
+            SymbolLanguage::JavaScript => tree_sitter_javascript::LANGUAGE.into(),
+            SymbolLanguage::TypeScript => tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            SymbolLanguage::Tsx => tree_sitter_typescript::LANGUAGE_TSX.into(),
+        }
+    }
+
+    /// Separator between a container and its member in qualified names
+    fn separator(self) -> &'static str {
+        match self {
+            SymbolLanguage::Rust => "::",
+            _ => ".",
+        }
+    }
+}
+
+/// Pick a grammar from a file's extension
+pub fn language_for_path(file_path: &str) -> Option<SymbolLanguage> {
+    let ext = file_path.rsplit_once('.')?.1.to_ascii_lowercase();
+    match ext.as_str() {
+        "rs" => Some(SymbolLanguage::Rust),
+        "py" | "pyi" => Some(SymbolLanguage::Python),
+        "js" | "jsx" | "mjs" | "cjs" => Some(SymbolLanguage::JavaScript),
+        "ts" | "mts" | "cts" => Some(SymbolLanguage::TypeScript),
+        "tsx" => Some(SymbolLanguage::Tsx),
+        _ => None,
+    }
+}
+
+/// A named block of code (1-based, inclusive line range)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Symbol {
+    /// Qualified name, e.g. `Parser::parse`
+    pub name: String,
+    pub kind: &'static str,
+    pub start_line: i32,
+    pub end_line: i32,
+}
+
+/// Symbol with its line attribution rolled up
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolAttribution {
+    pub file_path: String,
+    pub symbol_name: String,
+    pub symbol_kind: String,
+    pub start_line: i64,
+    pub end_line: i64,
+    pub total_lines: i64,
+    pub human_lines: i64,
+    pub ai_agent_lines: i64,
+    pub ai_assist_lines: i64,
+    pub collaborative_lines: i64,
+    pub ai_percentage: f64,
+    pub primary_tool: Option<String>,
+}
+
+/// Request for a file's symbol attribution
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolAttributionRequest {
+    pub repo_id: i64,
+    pub commit_sha: String,
+    pub file_path: String,
+}
+
+/// Extract functions, classes, and similar symbols from source text.
+///
+/// Symbols are returned in source order; nested symbols follow their
+/// container and carry its name as a prefix.
+pub fn extract_symbols(source: &str, language: SymbolLanguage) -> Result<Vec<Symbol>, String> {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&language.grammar())
+        .map_err(|e| e.to_string())?;
+    let tree = parser
+        .parse(source, None)
+        .ok_or_else(|| "Failed to parse source".to_string())?;
+
+    let mut symbols = Vec::new();
+    collect_symbols(
+        tree.root_node(),
+        source.as_bytes(),
+        language,
+        &mut Vec::new(),
+        &mut symbols,
+    );
+    Ok(symbols)
+}
+
+fn collect_symbols(
+    node: Node,
+    source: &[u8],
+    language: SymbolLanguage,
+    scope: &mut Vec<(String, &'static str)>,
+    out: &mut Vec<Symbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let Some((name, mut kind)) = symbol_for_node(child, source, language) else {
+            collect_symbols(child, source, language, scope, out);
+            continue;
+        };
+
+        // Functions declared inside a type are methods
+        if kind == "function"
+            && matches!(
+                scope.last(),
+                Some((_, "class" | "impl" | "trait" | "interface"))
+            )
+        {
+            kind = "method";
+        }
+
+        let qualified = scope
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .chain(std::iter::once(name.as_str()))
+            .collect::<Vec<_>>()
+            .join(language.separator());
+        out.push(Symbol {
+            name: qualified,
+            kind,
+            start_line: child.start_position().row as i32 + 1,
+            end_line: child.end_position().row as i32 + 1,
+        });
+
+        scope.push((name, kind));
+        collect_symbols(child, source, language, scope, out);
+        scope.pop();
+    }
+}
+
+/// Name and normalized kind of a node, if it declares a symbol
+fn symbol_for_node(
+    node: Node,
+    source: &[u8],
+    language: SymbolLanguage,
+) -> Option<(String, &'static str)> {
+    let (kind, name_field) = match (language, node.kind()) {
+        (SymbolLanguage::Rust, "function_item" | "function_signature_item") => ("function", "name"),
+        (SymbolLanguage::Rust, "struct_item") => ("struct", "name"),
+        (SymbolLanguage::Rust, "enum_item") => ("enum", "name"),
+        (SymbolLanguage::Rust, "trait_item") => ("trait", "name"),
+        (SymbolLanguage::Rust, "impl_item") => ("impl", "type"),
+        (SymbolLanguage::Rust, "mod_item") => ("module", "name"),
+        (SymbolLanguage::Python, "function_definition") => ("function", "name"),
+        (SymbolLanguage::Python, "class_definition") => ("class", "name"),
+        (SymbolLanguage::Rust | SymbolLanguage::Python, _) => return None,
+        (_, "function_declaration" | "generator_function_declaration") => ("function", "name"),
+        (_, "class_declaration" | "abstract_class_declaration") => ("class", "name"),
+        (_, "method_definition") => ("method", "name"),
+        (_, "interface_declaration") => ("interface", "name"),
+        // `const handler = () => { ... }`
+        (_, "variable_declarator") => {
+            let value = node.child_by_field_name("value")?;
+            if !matches!(
+                value.kind(),
+                "arrow_function" | "function_expression" | "function"
+            ) {
+                return None;
+            }
+            ("function", "name")
+        }
+        _ => return None,
+    };
+
+    let name = node
+        .child_by_field_name(name_field)?
+        .utf8_text(source)
+        .ok()?
+        .to_string();
+    Some((name, kind))
+}
+
+/// Roll per-line attribution up to each symbol
+pub fn attribute_symbols(
+    file_path: &str,
+    symbols: &[Symbol],
+    line_meta: &[LineMeta],
+) -> Vec<SymbolAttribution> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let mut attribution = SymbolAttribution {
+                file_path: file_path.to_string(),
+                symbol_name: symbol.name.clone(),
+                symbol_kind: symbol.kind.to_string(),
+                start_line: symbol.start_line as i64,
+                end_line: symbol.end_line as i64,
+                total_lines: 0,
+                human_lines: 0,
+                ai_agent_lines: 0,
+                ai_assist_lines: 0,
+                collaborative_lines: 0,
+                ai_percentage: 0.0,
+                primary_tool: None,
+            };
+            let mut tool_counts: HashMap<&str, u32> = HashMap::new();
+
+            let start = (symbol.start_line.max(1) - 1) as usize;
+            let end = (symbol.end_line.max(0) as usize).min(line_meta.len());
+            for meta in line_meta.get(start..end).unwrap_or_default() {
+                attribution.total_lines += 1;
+                match meta.author_type.as_str() {
+                    "ai_agent" => attribution.ai_agent_lines += 1,
+                    "ai_tab" => attribution.ai_assist_lines += 1,
+                    "mixed" => attribution.collaborative_lines += 1,
+                    _ => {
+                        attribution.human_lines += 1;
+                        continue;
+                    }
+                }
+                if let Some(tool) = meta.tool.as_deref() {
+                    *tool_counts.entry(tool).or_insert(0) += 1;
+                }
+            }
+
+            if attribution.total_lines > 0 {
+                let ai_lines = attribution.ai_agent_lines
+                    + attribution.ai_assist_lines
+                    + attribution.collaborative_lines;
+                attribution.ai_percentage =
+                    ai_lines as f64 / attribution.total_lines as f64 * 100.0;
+            }
+            attribution.primary_tool = tool_counts
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(tool, _)| tool.to_string());
+
+            attribution
+        })
+        .collect()
+}
+
+/// Innermost symbol containing a line (1-based)
+pub fn symbol_at_line(symbols: &[SymbolAttribution], line: i64) -> Option<&SymbolAttribution> {
+    symbols
+        .iter()
+        .filter(|s| s.start_line <= line && line <= s.end_line)
+        .min_by_key(|s| s.end_line - s.start_line)
+}
+
+/// Get symbol attribution for a file at a commit, computing it if missing
+/// or older than the commit's line attributions.
+pub async fn get_file_symbol_attribution(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<Vec<SymbolAttribution>, String> {
+    let Some(language) = language_for_path(file_path) else {
+        return Ok(Vec::new());
+    };
+
+    let _ = ensure_line_attributions_for_commit(db, repo_id, commit_sha).await;
+
+    if !symbol_attributions_stale(db, repo_id, commit_sha, file_path).await? {
+        return fetch_symbol_attributions(db, repo_id, commit_sha, file_path).await;
+    }
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let (file_lines, blame) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let file_lines = load_file_lines(&repo, commit_sha, file_path)?;
+        let blame = blame_spans(&repo, commit_sha, file_path).unwrap_or_default();
+        (file_lines, blame)
+    };
+
+    let mut attributions = fetch_line_attributions(db, repo_id, commit_sha, file_path).await?;
+    attributions.extend(inherited_line_attributions(db, repo_id, commit_sha, &blame).await?);
+    let line_meta = build_line_meta(file_lines.len(), &attributions);
+
+    let symbols = extract_symbols(&file_lines.join("\n"), language)?;
+    let rolled_up = attribute_symbols(file_path, &symbols, &line_meta);
+    store_symbol_attributions(db, repo_id, commit_sha, file_path, &rolled_up).await?;
+
+    Ok(rolled_up)
+}
+
+/// True when a file has no stored symbol attribution, or line attributions
+/// were written after it was computed
+async fn symbol_attributions_stale(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<bool, String> {
+    let computed_at: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT MIN(computed_at)
+        FROM symbol_attributions
+        WHERE repo_id = ? AND commit_sha = ? AND file_path = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(file_path)
+    .fetch_one(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let Some(computed_at) = computed_at else {
+        return Ok(true);
+    };
+
+    let newer: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT 1
+        FROM line_attributions
+        WHERE repo_id = ? AND commit_sha = ? AND created_at > ?
+        LIMIT 1
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(&computed_at)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(newer.is_some())
+}
+
+async fn fetch_symbol_attributions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    file_path: &str,
+) -> Result<Vec<SymbolAttribution>, String> {
+    sqlx::query_as::<_, SymbolAttribution>(
+        r#"
+        SELECT
+            file_path,
+            symbol_name,
+            symbol_kind,
+            start_line,
+            end_line,
+            total_lines,
+            human_lines,
+            ai_agent_lines,
+            ai_assist_lines,
+            collaborative_lines,
+            ai_percentage,
+            primary_tool
+        FROM symbol_attributions
+        WHERE repo_id = ? AND commit_sha = ? AND file_path = ?
+        ORDER BY start_line, end_line DESC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(file_path)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+async fn store_symbol_attributions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    file_path: &str,
+    rows: &[SymbolAttribution],
+) -> Result<(), String> {
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM symbol_attributions
+        WHERE repo_id = ? AND commit_sha = ? AND file_path = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(file_path)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        sqlx::query(
+            r#"
+            INSERT INTO symbol_attributions (
+                repo_id,
+                commit_sha,
+                file_path,
+                symbol_name,
+                symbol_kind,
+                start_line,
+                end_line,
+                total_lines,
+                human_lines,
+                ai_agent_lines,
+                ai_assist_lines,
+                collaborative_lines,
+                ai_percentage,
+                primary_tool
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(commit_sha)
+        .bind(file_path)
+        .bind(&row.symbol_name)
+        .bind(&row.symbol_kind)
+        .bind(row.start_line)
+        .bind(row.end_line)
+        .bind(row.total_lines)
+        .bind(row.human_lines)
+        .bind(row.ai_agent_lines)
+        .bind(row.ai_assist_lines)
+        .bind(row.collaborative_lines)
+        .bind(row.ai_percentage)
+        .bind(&row.primary_tool)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().await.map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(author_type: &str, tool: Option<&str>) -> LineMeta {
+        LineMeta {
+            author_type: author_type.to_string(),
+            tool: tool.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rust_symbols_are_qualified_by_their_container() {
+        let source = "struct Parser;\n\nimpl Parser {\n    fn parse(&self) {\n        todo!()\n    }\n}\n\nfn main() {}\n";
+        let symbols = extract_symbols(source, SymbolLanguage::Rust).unwrap();
+        let summary = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind, s.start_line, s.end_line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Parser", "struct", 1, 1),
+                ("Parser", "impl", 3, 7),
+                ("Parser::parse", "method", 4, 6),
+                ("main", "function", 9, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn python_methods_nest_under_classes() {
+        let source =
+            "class Widget:\n    def render(self):\n        return 1\n\ndef helper():\n    pass\n";
+        let symbols = extract_symbols(source, SymbolLanguage::Python).unwrap();
+        let names = symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ("Widget", "class"),
+                ("Widget.render", "method"),
+                ("helper", "function"),
+            ]
+        );
+    }
+
+    #[test]
+    fn arrow_functions_are_named_by_their_binding() {
+        let source = "export const handler = async () => {\n  return 1;\n};\n";
+        let symbols = extract_symbols(source, SymbolLanguage::TypeScript).unwrap();
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].name, "handler");
+        assert_eq!((symbols[0].start_line, symbols[0].end_line), (1, 3));
+    }
+
+    #[test]
+    fn symbol_percentages_count_lines_inside_the_symbol() {
+        let symbols = vec![Symbol {
+            name: "run".to_string(),
+            kind: "function",
+            start_line: 2,
+            end_line: 6,
+        }];
+        let lines = vec![
+            meta("ai_agent", Some("codex")),
+            meta("ai_agent", Some("claude_code")),
+            meta("ai_agent", Some("claude_code")),
+            meta("mixed", None),
+            meta("human", None),
+            meta("human", None),
+        ];
+
+        let rolled_up = attribute_symbols("src/lib.rs", &symbols, &lines);
+        let run = &rolled_up[0];
+        assert_eq!(run.total_lines, 5);
+        assert_eq!(run.ai_agent_lines, 2);
+        assert_eq!(run.collaborative_lines, 1);
+        assert_eq!(run.human_lines, 2);
+        assert!((run.ai_percentage - 60.0).abs() < f64::EPSILON);
+        assert_eq!(run.primary_tool.as_deref(), Some("claude_code"));
+        assert_eq!(
+            symbol_at_line(&rolled_up, 4).map(|s| s.symbol_name.as_str()),
+            Some("run")
+        );
+        assert!(symbol_at_line(&rolled_up, 1).is_none());
+    }
+}
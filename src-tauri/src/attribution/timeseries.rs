@@ -0,0 +1,407 @@
+//! Repo-level attribution time series
+//!
+//! Rolls per-commit contribution stats up into weekly or monthly periods by
+//! author date. Rollups are cached in `attribution_timeseries` and refreshed
+//! incrementally: only periods containing commits whose stats were computed
+//! since the last refresh are recomputed.
+
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeseriesGranularity {
+    Week,
+    Month,
+}
+
+impl TimeseriesGranularity {
+    fn as_str(self) -> &'static str {
+        match self {
+            TimeseriesGranularity::Week => "week",
+            TimeseriesGranularity::Month => "month",
+        }
+    }
+
+    /// SQL expression for the start of the period containing `c.authored_at`
+    fn period_expr(self) -> &'static str {
+        match self {
+            // Next Sunday (or the same day), back to its Monday
+            TimeseriesGranularity::Week => "date(c.authored_at, 'weekday 0', '-6 days')",
+            TimeseriesGranularity::Month => "strftime('%Y-%m-01', c.authored_at)",
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttributionTimeseries {
+    pub repo_id: i64,
+    pub granularity: TimeseriesGranularity,
+    pub points: Vec<TimeseriesPoint>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeseriesPoint {
+    /// First day of the period (`YYYY-MM-DD`)
+    pub period_start: String,
+    pub commits: i64,
+    pub total_lines: i64,
+    pub human_lines: i64,
+    pub ai_agent_lines: i64,
+    pub ai_assist_lines: i64,
+    pub collaborative_lines: i64,
+    pub ai_percentage: f64,
+    pub tools: Vec<TimeseriesToolLines>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeseriesToolLines {
+    pub tool: String,
+    pub line_count: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct TimeseriesRow {
+    period_start: String,
+    commits: i64,
+    total_lines: i64,
+    human_lines: i64,
+    ai_agent_lines: i64,
+    ai_assist_lines: i64,
+    collaborative_lines: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct TimeseriesToolRow {
+    period_start: String,
+    tool: String,
+    line_count: i64,
+}
+
+/// Get a repo's attribution time series, refreshing stale periods first.
+///
+/// `since`/`until` are inclusive `YYYY-MM-DD` bounds on period start.
+pub async fn get_attribution_timeseries(
+    db: &SqlitePool,
+    repo_id: i64,
+    granularity: TimeseriesGranularity,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<AttributionTimeseries, String> {
+    refresh_timeseries(db, repo_id, granularity).await?;
+
+    let rows = sqlx::query_as::<_, TimeseriesRow>(
+        r#"
+        SELECT period_start, commits, total_lines, human_lines, ai_agent_lines,
+               ai_assist_lines, collaborative_lines
+        FROM attribution_timeseries
+        WHERE repo_id = ? AND granularity = ?
+          AND (? IS NULL OR period_start >= ?)
+          AND (? IS NULL OR period_start <= ?)
+        ORDER BY period_start
+        "#,
+    )
+    .bind(repo_id)
+    .bind(granularity.as_str())
+    .bind(since)
+    .bind(since)
+    .bind(until)
+    .bind(until)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let tool_rows = sqlx::query_as::<_, TimeseriesToolRow>(
+        r#"
+        SELECT period_start, tool, line_count
+        FROM attribution_timeseries_tools
+        WHERE repo_id = ? AND granularity = ?
+          AND (? IS NULL OR period_start >= ?)
+          AND (? IS NULL OR period_start <= ?)
+        ORDER BY period_start, line_count DESC, tool
+        "#,
+    )
+    .bind(repo_id)
+    .bind(granularity.as_str())
+    .bind(since)
+    .bind(since)
+    .bind(until)
+    .bind(until)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut tools_by_period: std::collections::HashMap<String, Vec<TimeseriesToolLines>> =
+        std::collections::HashMap::new();
+    for row in tool_rows {
+        tools_by_period
+            .entry(row.period_start)
+            .or_default()
+            .push(TimeseriesToolLines {
+                tool: row.tool,
+                line_count: row.line_count,
+            });
+    }
+
+    let points = rows
+        .into_iter()
+        .map(|row| {
+            let ai_lines = row.ai_agent_lines + row.ai_assist_lines + row.collaborative_lines;
+            let ai_percentage = if row.total_lines > 0 {
+                ai_lines as f64 / row.total_lines as f64 * 100.0
+            } else {
+                0.0
+            };
+            TimeseriesPoint {
+                tools: tools_by_period
+                    .remove(&row.period_start)
+                    .unwrap_or_default(),
+                period_start: row.period_start,
+                commits: row.commits,
+                total_lines: row.total_lines,
+                human_lines: row.human_lines,
+                ai_agent_lines: row.ai_agent_lines,
+                ai_assist_lines: row.ai_assist_lines,
+                collaborative_lines: row.collaborative_lines,
+                ai_percentage,
+            }
+        })
+        .collect();
+
+    Ok(AttributionTimeseries {
+        repo_id,
+        granularity,
+        points,
+    })
+}
+
+/// Recompute periods touched by stats computed since the stored watermark.
+///
+/// Without a watermark (first run, or after the cache was cleared) the whole
+/// series is rebuilt.
+pub async fn refresh_timeseries(
+    db: &SqlitePool,
+    repo_id: i64,
+    granularity: TimeseriesGranularity,
+) -> Result<(), String> {
+    let db_err = |e: sqlx::Error| format!("Database error: {}", e);
+    let period = granularity.period_expr();
+
+    let latest: Option<String> = sqlx::query_scalar(
+        "SELECT MAX(computed_at) FROM commit_contribution_stats WHERE repo_id = ?",
+    )
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .map_err(db_err)?;
+
+    let mut tx = db.begin().await.map_err(db_err)?;
+
+    let watermark: Option<String> = sqlx::query_scalar(
+        "SELECT watermark FROM attribution_timeseries_state WHERE repo_id = ? AND granularity = ?",
+    )
+    .bind(repo_id)
+    .bind(granularity.as_str())
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    if watermark.is_none() {
+        for table in ["attribution_timeseries", "attribution_timeseries_tools"] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE repo_id = ? AND granularity = ?"
+            ))
+            .bind(repo_id)
+            .bind(granularity.as_str())
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+    }
+
+    let Some(latest) = latest else {
+        return tx.commit().await.map_err(db_err);
+    };
+
+    // Stats computed within the watermark's second may not have been seen
+    let dirty: Vec<String> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT DISTINCT {period}
+        FROM commit_contribution_stats s
+        JOIN commits c ON c.repo_id = s.repo_id AND c.sha = s.commit_sha
+        WHERE s.repo_id = ?
+          AND {period} IS NOT NULL
+          AND (? IS NULL OR s.computed_at >= ?)
+        "#
+    ))
+    .bind(repo_id)
+    .bind(watermark.as_deref())
+    .bind(watermark.as_deref())
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    for period_start in &dirty {
+        for table in ["attribution_timeseries", "attribution_timeseries_tools"] {
+            sqlx::query(&format!(
+                "DELETE FROM {table} WHERE repo_id = ? AND granularity = ? AND period_start = ?"
+            ))
+            .bind(repo_id)
+            .bind(granularity.as_str())
+            .bind(period_start)
+            .execute(&mut *tx)
+            .await
+            .map_err(db_err)?;
+        }
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO attribution_timeseries (
+                repo_id, granularity, period_start, commits, total_lines, human_lines,
+                ai_agent_lines, ai_assist_lines, collaborative_lines
+            )
+            SELECT ?, ?, ?, COUNT(*),
+                   COALESCE(SUM(s.total_lines), 0),
+                   COALESCE(SUM(s.human_lines), 0),
+                   COALESCE(SUM(s.ai_agent_lines), 0),
+                   COALESCE(SUM(s.ai_assist_lines), 0),
+                   COALESCE(SUM(s.collaborative_lines), 0)
+            FROM commit_contribution_stats s
+            JOIN commits c ON c.repo_id = s.repo_id AND c.sha = s.commit_sha
+            WHERE s.repo_id = ? AND c.authored_at IS NOT NULL AND {period} = ?
+            "#
+        ))
+        .bind(repo_id)
+        .bind(granularity.as_str())
+        .bind(period_start)
+        .bind(repo_id)
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO attribution_timeseries_tools (
+                repo_id, granularity, period_start, tool, line_count
+            )
+            SELECT ?, ?, ?, t.tool, SUM(t.line_count)
+            FROM commit_tool_stats t
+            JOIN commits c ON c.repo_id = t.repo_id AND c.sha = t.commit_sha
+            WHERE t.repo_id = ? AND c.authored_at IS NOT NULL AND {period} = ?
+            GROUP BY t.tool
+            "#
+        ))
+        .bind(repo_id)
+        .bind(granularity.as_str())
+        .bind(period_start)
+        .bind(repo_id)
+        .bind(period_start)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_err)?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO attribution_timeseries_state (repo_id, granularity, watermark)
+        VALUES (?, ?, ?)
+        ON CONFLICT(repo_id, granularity) DO UPDATE SET watermark = excluded.watermark
+        "#,
+    )
+    .bind(repo_id)
+    .bind(granularity.as_str())
+    .bind(&latest)
+    .execute(&mut *tx)
+    .await
+    .map_err(db_err)?;
+
+    tx.commit().await.map_err(db_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn add_commit(pool: &SqlitePool, sha: &str, authored_at: &str, ai: i64, human: i64) {
+        sqlx::query("INSERT INTO commits (repo_id, sha, authored_at) VALUES (1, ?, ?)")
+            .bind(sha)
+            .bind(authored_at)
+            .execute(pool)
+            .await
+            .expect("insert commit");
+        sqlx::query(
+            r#"
+            INSERT INTO commit_contribution_stats
+                (repo_id, commit_sha, ai_agent_lines, human_lines, total_lines, tool)
+            VALUES (1, ?, ?, ?, ?, 'codex')
+            "#,
+        )
+        .bind(sha)
+        .bind(ai)
+        .bind(human)
+        .bind(ai + human)
+        .execute(pool)
+        .await
+        .expect("insert stats");
+        sqlx::query(
+            "INSERT INTO commit_tool_stats (repo_id, commit_sha, tool, line_count) VALUES (1, ?, 'codex', ?)",
+        )
+        .bind(sha)
+        .bind(ai)
+        .execute(pool)
+        .await
+        .expect("insert tool stats");
+    }
+
+    #[test]
+    fn weekly_series_groups_by_monday_and_picks_up_new_commits() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            // 2026-03-02 is a Monday; the 8th is the Sunday of that week
+            add_commit(&pool, "a", "2026-03-02T09:00:00Z", 30, 10).await;
+            add_commit(&pool, "b", "2026-03-08T23:00:00Z", 10, 50).await;
+            add_commit(&pool, "c", "2026-03-09T09:00:00Z", 0, 20).await;
+
+            let series =
+                get_attribution_timeseries(&pool, 1, TimeseriesGranularity::Week, None, None)
+                    .await
+                    .unwrap();
+            let weeks = series
+                .points
+                .iter()
+                .map(|p| (p.period_start.as_str(), p.commits, p.total_lines))
+                .collect::<Vec<_>>();
+            assert_eq!(weeks, vec![("2026-03-02", 2, 100), ("2026-03-09", 1, 20)]);
+            assert!((series.points[0].ai_percentage - 40.0).abs() < 1e-9);
+            assert_eq!(series.points[0].tools[0].line_count, 40);
+
+            add_commit(&pool, "d", "2026-03-10T09:00:00Z", 20, 0).await;
+            let series =
+                get_attribution_timeseries(&pool, 1, TimeseriesGranularity::Week, None, None)
+                    .await
+                    .unwrap();
+            assert_eq!(series.points[1].commits, 2);
+            assert_eq!(series.points[1].ai_agent_lines, 20);
+
+            let months =
+                get_attribution_timeseries(&pool, 1, TimeseriesGranularity::Month, None, None)
+                    .await
+                    .unwrap();
+            assert_eq!(months.points.len(), 1);
+            assert_eq!(months.points[0].period_start, "2026-03-01");
+            assert_eq!(months.points[0].commits, 4);
+        });
+    }
+}
@@ -11,7 +11,7 @@ use std::path::PathBuf;
 
 fn usage() -> ! {
     eprintln!(
-        "Usage:\n  narrative-cli hook post-commit --repo <path>\n  narrative-cli hook post-merge --repo <path>\n  narrative-cli hook post-rewrite --repo <path> --command <name> --rewritten <file>\n"
+        "Usage:\n  narrative-cli hook post-commit --repo <path>\n  narrative-cli hook post-merge --repo <path>\n  narrative-cli hook post-rewrite --repo <path> --command <name> --rewritten <file>\n  narrative-cli hook prepare-commit-msg --repo <path> --message-file <file> [--source <source>]\n  narrative-cli policy check --repo <path> [--from <rev>] [--to <rev>] [--json]\n  narrative-cli notes import --repo <path>\n  narrative-cli notes export --repo <path> [--from <rev>] [--to <rev>]\n  narrative-cli notes reconcile --repo <path> [--from <rev>] [--to <rev>]\n  narrative-cli search <query> --repo <path> [--limit <n>] [--mode lexical|hybrid] [--tool <tool>] [--model <model>] [--since <date>] [--until <date>] [--role <role>] [--json]\n"
     );
     std::process::exit(2);
 }
@@ -198,6 +198,16 @@ async fn run_hook(args: Vec<String>) -> Result<(), String> {
         "post-commit" | "post-merge" => {
             let sha = head_sha(&repo_root)?;
             export_head_notes(&db, repo_id, &sha).await?;
+            if sub == "post-commit" {
+                // Upgrade sessions anchored to the uncommitted work this commit contains
+                let _ = narrative_desktop_mvp::wip_anchors::resolve_wip_anchors(
+                    &db,
+                    repo_id,
+                    std::path::Path::new(&repo_root),
+                    &sha,
+                )
+                .await;
+            }
             if sub == "post-merge" {
                 // Record lineage event (implemented even though optional in the plan)
                 let payload = narrative_desktop_mvp::story_anchors::lineage::LineageEventPayload {
@@ -223,6 +233,11 @@ async fn run_hook(args: Vec<String>) -> Result<(), String> {
                     &db, repo_id, &sha, &payload,
                 )
                 .await;
+                // `git pull` may have brought notes from other installs (opt-in)
+                let _ = narrative_desktop_mvp::story_anchors::fetched_notes::import_fetched_notes_if_enabled(
+                    &db, repo_id,
+                )
+                .await;
             }
         }
         "post-rewrite" => {
@@ -279,6 +294,12 @@ async fn run_hook(args: Vec<String>) -> Result<(), String> {
                 .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
                 .unwrap_or(false);
 
+            // Let session links and line attributions follow the rewritten commits
+            let _ = narrative_desktop_mvp::attribution::propagation::record_commit_rewrites(
+                &db, repo_id, &pairs, &cmd,
+            )
+            .await;
+
             // Reconcile newly created commits (the "to" side of pairs)
             let new_shas = pairs.into_iter().map(|(_, to)| to).collect::<Vec<_>>();
             reconcile_commits(&db, repo_id, &repo_root, &new_shas, write_recovered).await?;
@@ -288,9 +309,270 @@ async fn run_hook(args: Vec<String>) -> Result<(), String> {
                 export_head_notes(&db, repo_id, head).await?;
             }
         }
+        "prepare-commit-msg" => {
+            let message_file = arg_value(&args, "--message-file")
+                .ok_or_else(|| "--message-file required".to_string())?;
+            let source = arg_value(&args, "--source").unwrap_or_default();
+            let prefs =
+                narrative_desktop_mvp::attribution::prefs::fetch_or_create_prefs(&db, repo_id)
+                    .await?;
+            // Turning the preference off disables an already-installed hook
+            if prefs.commit_trailers && source != "merge" && source != "squash" {
+                let trailers =
+                    narrative_desktop_mvp::story_anchors::trailers::staged_commit_trailers(
+                        &db,
+                        repo_id,
+                        std::path::Path::new(&repo_root),
+                    )
+                    .await?;
+                if !trailers.is_empty() {
+                    let message = tokio::fs::read_to_string(&message_file)
+                        .await
+                        .unwrap_or_default();
+                    let updated = narrative_desktop_mvp::story_anchors::trailers::append_trailers(
+                        &message, &trailers,
+                    );
+                    if updated != message {
+                        tokio::fs::write(&message_file, updated)
+                            .await
+                            .map_err(|e| format!("Failed to write {message_file}: {e}"))?;
+                    }
+                }
+            }
+        }
+        _ => usage(),
+    }
+
+    Ok(())
+}
+
+/// Check a commit range against the repo's attribution policy; exits 1 on
+/// any violation so pre-push hooks and CI can gate on it.
+async fn run_policy(args: Vec<String>) -> Result<(), String> {
+    if args.get(2).map(String::as_str) != Some("check") {
+        usage();
+    }
+    let repo_root = arg_value(&args, "--repo").ok_or_else(|| "--repo required".to_string())?;
+    let db = connect_db().await?;
+    let repo_id = ensure_repo_id(&db, &repo_root).await?;
+
+    let result = narrative_desktop_mvp::attribution::policy::check_attribution_policy(
+        &db,
+        repo_id,
+        arg_value(&args, "--from").as_deref(),
+        arg_value(&args, "--to").as_deref(),
+        None,
+    )
+    .await?;
+
+    if args.iter().any(|a| a == "--json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?
+        );
+    } else {
+        for violation in &result.violations {
+            eprintln!("[{}] {}", violation.rule, violation.message);
+        }
+        eprintln!(
+            "Attribution policy {}: {} commit(s) checked, {} violation(s)",
+            if result.passed { "passed" } else { "failed" },
+            result.commits_checked,
+            result.violations.len()
+        );
+    }
+
+    if !result.passed {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Import, export or reconcile a repo's notes without hooks. Works on bare
+/// repositories, so a server-side mirror can host the consolidated anchors.
+async fn run_notes(args: Vec<String>) -> Result<(), String> {
+    let sub = args.get(2).cloned().unwrap_or_default();
+    let repo_root = arg_value(&args, "--repo").ok_or_else(|| "--repo required".to_string())?;
+    let from = arg_value(&args, "--from");
+    let to = arg_value(&args, "--to").unwrap_or_else(|| "HEAD".to_string());
+    let db = connect_db().await?;
+    let repo_id = ensure_repo_id(&db, &repo_root).await?;
+
+    match sub.as_str() {
+        "import" => {
+            let summary =
+                narrative_desktop_mvp::story_anchors::notes_export::import_all_notes(&db, repo_id)
+                    .await?;
+            eprintln!(
+                "Imported {} sessions note(s) and {} attribution note(s)",
+                summary.sessions.imported, summary.attribution.imported
+            );
+        }
+        "export" => {
+            let summary =
+                narrative_desktop_mvp::story_anchors::notes_export::export_notes_for_range(
+                    &db,
+                    repo_id,
+                    from.as_deref(),
+                    &to,
+                    |_| {},
+                )
+                .await?;
+            eprintln!(
+                "Exported notes for {} commit(s): {} sessions, {} attribution, {} failed",
+                summary.total,
+                summary.sessions_exported,
+                summary.attribution_exported,
+                summary.failed
+            );
+        }
+        "reconcile" => {
+            let commits = {
+                let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+                narrative_desktop_mvp::story_anchors::notes_export::commits_in_range(
+                    &repo,
+                    from.as_deref(),
+                    &to,
+                )?
+            };
+            reconcile_commits(&db, repo_id, &repo_root, &commits, true).await?;
+            eprintln!("Reconciled {} commit(s)", commits.len());
+        }
         _ => usage(),
     }
+    Ok(())
+}
+
+/// The matched ranges of a search snippet (UTF-16 offsets) in bold
+fn emphasize_highlights(
+    snippet: &str,
+    highlights: &[narrative_desktop_mvp::atlas::commands::AtlasHighlight],
+) -> String {
+    let mut out = String::new();
+    let mut offset = 0u32;
+    let mut ranges = highlights.iter().peekable();
+    for c in snippet.chars() {
+        if ranges.peek().is_some_and(|h| h.start == offset) {
+            out.push_str("\x1b[1m");
+        }
+        out.push(c);
+        offset += c.len_utf16() as u32;
+        if ranges.peek().is_some_and(|h| h.end == offset) {
+            out.push_str("\x1b[0m");
+            ranges.next();
+        }
+    }
+    out
+}
+
+/// Search the repo's indexed sessions (Atlas) from the terminal; the query
+/// accepts the same phrase / OR / -exclude syntax as the app
+async fn run_search(args: Vec<String>) -> Result<(), String> {
+    use narrative_desktop_mvp::atlas::commands::{search, AtlasSearchMode, AtlasSearchRequest};
+    use narrative_desktop_mvp::atlas::types::AtlasSearchFilters;
+    use std::io::IsTerminal;
+
+    let query = args.get(2).cloned().unwrap_or_default();
+    if query.is_empty() || query.starts_with("--") {
+        usage();
+    }
+    let repo_root = arg_value(&args, "--repo").ok_or_else(|| "--repo required".to_string())?;
+    let limit = arg_value(&args, "--limit")
+        .map(|v| {
+            v.parse::<i64>()
+                .map_err(|_| "--limit must be a number".to_string())
+        })
+        .transpose()?;
+    let mode = match arg_value(&args, "--mode").as_deref() {
+        None | Some("lexical") => AtlasSearchMode::Lexical,
+        Some("hybrid") => AtlasSearchMode::Hybrid,
+        Some(other) => return Err(format!("Unknown --mode {other} (lexical|hybrid)")),
+    };
+    let filters = AtlasSearchFilters {
+        tool: arg_value(&args, "--tool"),
+        model: arg_value(&args, "--model"),
+        imported_after: arg_value(&args, "--since"),
+        imported_before: arg_value(&args, "--until"),
+        role: arg_value(&args, "--role"),
+    };
+
+    let db = connect_db().await?;
+    let repo_id: i64 = sqlx::query_scalar("SELECT id FROM repos WHERE path = ?")
+        .bind(&repo_root)
+        .fetch_optional(&db)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("{repo_root} has not been opened in Narrative"))?;
+
+    // Embedding models live next to the database in the app data dir
+    let db_path = env::var("NARRATIVE_DB_PATH")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(default_db_path);
+    let app_data_dir = db_path.as_deref().and_then(|path| path.parent());
+
+    let envelope = search(
+        &db,
+        app_data_dir,
+        AtlasSearchRequest {
+            repo_id,
+            query,
+            limit,
+            mode: Some(mode),
+            filters: Some(filters),
+            ranking: None,
+        },
+    )
+    .await?;
+
+    if args.iter().any(|a| a == "--json") {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&envelope).map_err(|e| e.to_string())?
+        );
+        if !envelope.ok {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
+    let Some(response) = envelope.value else {
+        let message = envelope
+            .error
+            .map(|e| format!("{:?}: {}", e.code, e.message))
+            .unwrap_or_else(|| "Search failed".to_string());
+        return Err(message);
+    };
+    let color = std::io::stdout().is_terminal();
+    for hit in &response.results {
+        println!(
+            "{} · {} · {} · session {} chunk {} (score {:.2})",
+            hit.session_tool.as_deref().unwrap_or("unknown tool"),
+            hit.session_model.as_deref().unwrap_or("unknown model"),
+            hit.session_imported_at.as_deref().unwrap_or("—"),
+            hit.session_id,
+            hit.chunk_index,
+            hit.score
+        );
+        let snippet = if color {
+            emphasize_highlights(&hit.snippet, &hit.highlights)
+        } else {
+            hit.snippet.clone()
+        };
+        for line in snippet.lines() {
+            println!("    {line}");
+        }
+        println!();
+    }
+    eprintln!(
+        "{} result(s){}",
+        response.results.len(),
+        if envelope.meta.and_then(|m| m.truncated) == Some(true) {
+            " (truncated)"
+        } else {
+            ""
+        }
+    );
     Ok(())
 }
 
@@ -304,6 +586,9 @@ async fn main() {
     let cmd = args.get(1).cloned().unwrap_or_default();
     let result = match cmd.as_str() {
         "hook" => run_hook(args).await,
+        "policy" => run_policy(args).await,
+        "notes" => run_notes(args).await,
+        "search" => run_search(args).await,
         _ => Err("Unknown command".into()),
     };
 
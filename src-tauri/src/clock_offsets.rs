@@ -0,0 +1,269 @@
+//! Per-tool clock offsets for session linking.
+//!
+//! Some tools log local time without an offset; their timestamps are read as
+//! UTC (see `import::parser::parse_session_timestamp`), so every session is
+//! off by the user's UTC offset and misses its commits. Each tool can have an
+//! offset in minutes that is added to session times before scoring.
+//!
+//! Offsets are set by hand or detected from confirmed (manual) links: when the
+//! gap between a tool's session ends and the confirmed commits' times is
+//! consistently an hour or more, that gap is the tool's clock offset.
+
+use crate::DbState;
+use sqlx::SqlitePool;
+use tauri::State;
+
+/// Confirmed links needed before an offset is detected
+pub const MIN_OFFSET_SAMPLES: usize = 3;
+
+/// Gaps smaller than this are ordinary session-to-commit delays, not skew
+const MIN_DETECTED_OFFSET_MIN: i64 = 45;
+
+/// Detected offsets are rounded to this (time zones are in quarter hours)
+const OFFSET_STEP_MIN: i64 = 15;
+
+/// Confirmed links per tool read during detection (most recent first)
+const MAX_OFFSET_SAMPLES: i64 = 200;
+
+/// Clock offset applied to a tool's session times
+#[derive(Debug, Clone, PartialEq, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolClockOffset {
+    pub tool: String,
+    pub offset_min: i64,
+    /// "manual" or "detected"
+    pub source: String,
+    /// Confirmed links the detected offset is based on (0 when manual)
+    pub samples: i64,
+}
+
+/// Estimate a clock offset from `commit time - session end` gaps (minutes).
+///
+/// Returns None with too few samples or when the gaps disagree (fewer than
+/// two thirds within 30 minutes of the median), and Some(0) when the median
+/// gap is an ordinary delay.
+pub fn detect_offset(gaps_min: &[i64]) -> Option<i64> {
+    if gaps_min.len() < MIN_OFFSET_SAMPLES {
+        return None;
+    }
+    let mut sorted = gaps_min.to_vec();
+    sorted.sort_unstable();
+    let median = sorted[sorted.len() / 2];
+
+    let agreeing = sorted
+        .iter()
+        .filter(|gap| (*gap - median).abs() <= 30)
+        .count();
+    if agreeing * 3 < sorted.len() * 2 {
+        return None;
+    }
+    if median.abs() < MIN_DETECTED_OFFSET_MIN {
+        return Some(0);
+    }
+    Some((median as f64 / OFFSET_STEP_MIN as f64).round() as i64 * OFFSET_STEP_MIN)
+}
+
+/// Offset for a tool's session times (0 when none is known).
+pub(crate) async fn fetch_clock_offset(pool: &SqlitePool, tool: &str) -> Result<i64, String> {
+    let offset: Option<i64> =
+        sqlx::query_scalar("SELECT offset_min FROM tool_clock_offsets WHERE tool = ?")
+            .bind(tool)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    Ok(offset.unwrap_or(0))
+}
+
+/// Offset for a stored session's tool (0 when the session is unknown).
+pub(crate) async fn fetch_session_clock_offset(
+    pool: &SqlitePool,
+    session_id: &str,
+) -> Result<i64, String> {
+    let offset: Option<i64> = sqlx::query_scalar(
+        r#"
+        SELECT o.offset_min
+        FROM sessions s
+        JOIN tool_clock_offsets o ON o.tool = s.tool
+        WHERE s.id = ?
+        "#,
+    )
+    .bind(session_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(offset.unwrap_or(0))
+}
+
+async fn fetch_clock_offsets(pool: &SqlitePool) -> Result<Vec<ToolClockOffset>, String> {
+    sqlx::query_as::<_, ToolClockOffset>(
+        "SELECT tool, offset_min, source, samples FROM tool_clock_offsets ORDER BY tool",
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Re-detect offsets for tools without a manual one, from confirmed links.
+pub(crate) async fn detect_clock_offsets(pool: &SqlitePool) -> Result<(), String> {
+    let tools: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT s.tool
+        FROM session_links l
+        JOIN sessions s ON s.id = l.session_id
+        WHERE l.auto_linked = 0
+          AND s.tool NOT IN (SELECT tool FROM tool_clock_offsets WHERE source = 'manual')
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    for tool in tools {
+        let gaps = confirmed_link_gaps(pool, &tool).await?;
+        let Some(offset) = detect_offset(&gaps) else {
+            continue;
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO tool_clock_offsets (tool, offset_min, source, samples)
+            VALUES (?, ?, 'detected', ?)
+            ON CONFLICT(tool) DO UPDATE SET
+                offset_min = excluded.offset_min,
+                samples = excluded.samples,
+                updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE tool_clock_offsets.source = 'detected'
+            "#,
+        )
+        .bind(&tool)
+        .bind(offset)
+        .bind(gaps.len() as i64)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    Ok(())
+}
+
+/// `commit time - session end` (minutes) for a tool's confirmed links.
+///
+/// Session ends come from the stored trace's raw (uncorrected) timestamps.
+async fn confirmed_link_gaps(pool: &SqlitePool, tool: &str) -> Result<Vec<i64>, String> {
+    let rows: Vec<(String, String, String)> = sqlx::query_as(
+        r#"
+        SELECT s.id, s.raw_json, c.authored_at
+        FROM session_links l
+        JOIN sessions s ON s.id = l.session_id
+        JOIN commits c ON c.repo_id = l.repo_id AND c.sha = l.commit_sha
+        WHERE l.auto_linked = 0 AND s.tool = ? AND s.purged_at IS NULL
+        ORDER BY l.created_at DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(tool)
+    .bind(MAX_OFFSET_SAMPLES)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut gaps = Vec::new();
+    for (session_id, raw_json, authored_at) in rows {
+        let Ok(commit_time) = chrono::DateTime::parse_from_rfc3339(&authored_at) else {
+            continue;
+        };
+        let Ok(raw_json) =
+            crate::import::trace_chunks::load_trace_json(pool, &session_id, raw_json).await
+        else {
+            continue;
+        };
+        let Ok(trace) = serde_json::from_str::<crate::import::parser::SessionTrace>(&raw_json)
+        else {
+            continue;
+        };
+        if let Some(session_end) = trace.timestamps().last() {
+            gaps.push((commit_time.with_timezone(&chrono::Utc) - session_end).num_minutes());
+        }
+    }
+    Ok(gaps)
+}
+
+/// List the clock offsets applied to each tool's session times.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_tool_clock_offsets(
+    db_state: State<'_, DbState>,
+) -> Result<Vec<ToolClockOffset>, String> {
+    fetch_clock_offsets(db_state.0.as_ref()).await
+}
+
+/// Set a tool's clock offset by hand, or clear it with `None`.
+///
+/// A manual offset is never replaced by detection. Clearing it lets detection
+/// fill it in again. Existing links are not re-scored.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_tool_clock_offset(
+    db_state: State<'_, DbState>,
+    tool: String,
+    offset_min: Option<i64>,
+) -> Result<Vec<ToolClockOffset>, String> {
+    let pool = db_state.0.as_ref();
+    match offset_min {
+        Some(offset) => {
+            if offset.abs() > 24 * 60 {
+                return Err("offsetMin must be within ±24 hours".to_string());
+            }
+            sqlx::query(
+                r#"
+                INSERT INTO tool_clock_offsets (tool, offset_min, source, samples)
+                VALUES (?, ?, 'manual', 0)
+                ON CONFLICT(tool) DO UPDATE SET
+                    offset_min = excluded.offset_min,
+                    source = 'manual',
+                    samples = 0,
+                    updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+                "#,
+            )
+            .bind(&tool)
+            .bind(offset)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        }
+        None => {
+            sqlx::query("DELETE FROM tool_clock_offsets WHERE tool = ?")
+                .bind(&tool)
+                .execute(pool)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+            detect_clock_offsets(pool).await?;
+        }
+    }
+    fetch_clock_offsets(pool).await
+}
+
+/// Detect clock offsets from confirmed links now.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn detect_tool_clock_offsets(
+    db_state: State<'_, DbState>,
+) -> Result<Vec<ToolClockOffset>, String> {
+    let pool = db_state.0.as_ref();
+    detect_clock_offsets(pool).await?;
+    fetch_clock_offsets(pool).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_offset_finds_consistent_skew() {
+        // Too few confirmed links
+        assert_eq!(detect_offset(&[120, 118]), None);
+        // Ordinary delays between session end and commit
+        assert_eq!(detect_offset(&[3, 10, -2, 25]), Some(0));
+        // Local time logged as UTC at UTC-2: commits land ~2h "after"
+        assert_eq!(detect_offset(&[121, 118, 130, 115]), Some(120));
+        // UTC+5:30
+        assert_eq!(detect_offset(&[-332, -328, -325]), Some(-330));
+        // Gaps that disagree say nothing
+        assert_eq!(detect_offset(&[0, 120, 300]), None);
+    }
+}
@@ -3,6 +3,8 @@
 //! Watches AI tool directories for new/modified session files
 //! and emits events to the frontend for auto-import.
 
+use crate::import::copilot_parser::is_copilot_chat_session;
+use crate::import::jetbrains_parser::is_jetbrains_ai_path;
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::ffi::OsStr;
@@ -221,6 +223,11 @@ impl Default for PendingEntry {
 
 /// Check if a path is a session file we care about
 fn is_session_file(path: &Path) -> bool {
+    // Rotated logs (`.jsonl.gz`, `.jsonl.zst`) are judged by their uncompressed name.
+    if let Some(logical) = crate::import::compression::logical_path(path) {
+        return is_session_file(&logical);
+    }
+
     let ext = path.extension().and_then(|e| e.to_str());
     let path_str = path.to_string_lossy().replace('\\', "/");
 
@@ -252,15 +259,25 @@ fn is_session_file(path: &Path) -> bool {
         Some("jsonl") => {
             // Claude Code uses .jsonl
             path_str.contains(".claude")
+                // Goose: <data dir>/goose/sessions/<id>.jsonl
+                || (path_str.to_lowercase().contains("goose") && path_str.contains("/sessions/"))
         }
         Some("json") => {
             // Various tools use .json
-            path_str.contains(".claude")
+            is_cline_history(&path_str)
+                || is_jetbrains_ai_path(&path_str.to_lowercase())
+                || path_str.to_lowercase().contains("sourcegraph.cody-ai")
+                // Copilot Chat sessions in VS Code storage (chatSessions, github.copilot-chat)
+                || is_copilot_chat_session(&path_str.to_lowercase())
+                // opencode: only session info files; messages/parts are resolved from them
+                || is_opencode_session_info(&path_str)
+                || path_str.contains(".claude")
                 // Cursor emits lots of non-session JSON (MCP tool defs, configs, etc).
                 // Restrict to composer artifacts.
                 || (path_str.contains(".cursor") && path_str.contains("/composer/"))
                 || path_str.contains("gemini")
-                || path_str.contains("copilot")
+                // Other files in Copilot Chat's storage are embedding caches
+                || (path_str.contains("copilot") && !path_str.contains("github.copilot-chat/"))
                 || path_str.contains(".continue")
         }
         Some("database") => {
@@ -273,11 +290,47 @@ fn is_session_file(path: &Path) -> bool {
     }
 }
 
+/// Cline / Roo Code task history under VS Code globalStorage.
+///
+/// Checked before the `.claude` heuristics since Cline's storage id
+/// (`saoudrizwan.claude-dev`) also contains ".claude".
+fn is_cline_history(path_str: &str) -> bool {
+    let lower = path_str.to_lowercase();
+    (lower.contains("saoudrizwan.claude-dev") || lower.contains("rooveterinaryinc.roo-cline"))
+        && lower.ends_with("/api_conversation_history.json")
+}
+
+/// opencode session info file (storage/session/<project>/<id>.json)
+fn is_opencode_session_info(path_str: &str) -> bool {
+    path_str.contains("opencode/storage/session/")
+        && !path_str.contains("/session/message/")
+        && !path_str.contains("/session/part/")
+}
+
 /// Detect which AI tool a file belongs to based on its path
 fn detect_tool_from_path(path: &Path) -> String {
     let path_str = path.to_string_lossy().replace('\\', "/");
 
-    if path_str.contains(".claude") {
+    if is_cline_history(&path_str) {
+        if path_str
+            .to_lowercase()
+            .contains("rooveterinaryinc.roo-cline")
+        {
+            "roo_code".to_string()
+        } else {
+            "cline".to_string()
+        }
+    } else if is_jetbrains_ai_path(&path_str.to_lowercase()) {
+        "jetbrains_ai".to_string()
+    } else if path_str.to_lowercase().contains("sourcegraph.cody-ai") {
+        "cody".to_string()
+    } else if is_copilot_chat_session(&path_str.to_lowercase()) {
+        "copilot".to_string()
+    } else if path_str.contains("opencode/storage/") {
+        "opencode".to_string()
+    } else if path_str.to_lowercase().contains("goose") && path_str.contains("/sessions/") {
+        "goose".to_string()
+    } else if path_str.contains(".claude") {
         "claude_code".to_string()
     } else if path_str.contains(".codex") {
         "codex".to_string()
@@ -334,6 +387,32 @@ mod tests {
             "/home/user/.continue/sessions/history.json"
         )));
 
+        // Goose sessions
+        assert!(is_session_file(&PathBuf::from(
+            "/home/user/.local/share/goose/sessions/20240601_1.jsonl"
+        )));
+
+        // opencode session info (but not individual messages)
+        assert!(is_session_file(&PathBuf::from(
+            "/home/user/.local/share/opencode/storage/session/proj/ses_1.json"
+        )));
+        assert!(!is_session_file(&PathBuf::from(
+            "/home/user/.local/share/opencode/storage/message/ses_1/msg_1.json"
+        )));
+
+        // JetBrains AI Assistant chats
+        assert!(is_session_file(&PathBuf::from(
+            "/home/user/.config/JetBrains/PyCharm2024.1/aiAssistant/chats/chat-1.json"
+        )));
+
+        // Copilot Chat sessions (but not its embedding caches)
+        assert!(is_session_file(&PathBuf::from(
+            "/home/user/.config/Code/User/workspaceStorage/abc123/chatSessions/4f1c.json"
+        )));
+        assert!(!is_session_file(&PathBuf::from(
+            "/home/user/.config/Code/User/globalStorage/github.copilot-chat/commandEmbeddings.json"
+        )));
+
         // Non-session files
         assert!(!is_session_file(&PathBuf::from("/home/user/random.txt")));
         assert!(!is_session_file(&PathBuf::from("/home/user/doc.pdf")));
@@ -355,6 +434,12 @@ mod tests {
             detect_tool_from_path(&PathBuf::from("/home/user/.continue/history.json")),
             "continue"
         );
+        assert_eq!(
+            detect_tool_from_path(&PathBuf::from(
+                "/home/user/.config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks/1/api_conversation_history.json"
+            )),
+            "cline"
+        );
         assert_eq!(
             detect_tool_from_path(&PathBuf::from("/home/user/random.json")),
             "unknown"
@@ -0,0 +1,180 @@
+//! Scheduled background backfill
+//!
+//! The file watcher only sees sessions written while the app is running, and the
+//! UI only backfills when auto-ingest is switched on. This thread re-runs
+//! `backfill_recent_sessions` every `backfillIntervalMinutes` for each repo that
+//! has been opened, so sessions created while the app was closed are picked up
+//! without manual action. The config is re-read on every tick, so interval
+//! changes and toggling auto-ingest take effect without a restart.
+//!
+//! On its first tick the thread also resumes batches left in the import journal
+//! by a crash (by then the frontend has applied pending migrations).
+
+use crate::ingest_config::{load_config, IngestConfig};
+use serde::Serialize;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the thread wakes up to check whether a backfill is due
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Files per tool considered per run (same as the UI's enable-time backfill)
+const LIMIT_PER_TOOL: i64 = 10;
+
+/// Emitted after a scheduled backfill imported at least one session
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledBackfillEvent {
+    pub repo_id: i64,
+    pub imported: i64,
+    pub failed: i64,
+}
+
+/// Start the scheduler thread
+pub fn spawn(app_handle: AppHandle) {
+    thread::spawn(move || {
+        let mut last_run: Option<Instant> = None;
+        let mut resumed = false;
+        loop {
+            // The first check happens one poll after startup, once the UI has settled.
+            thread::sleep(POLL_INTERVAL);
+
+            if !resumed {
+                resumed = true;
+                if let Err(err) = resume_interrupted(&app_handle) {
+                    eprintln!("Narrative: resuming interrupted imports failed: {err}");
+                }
+            }
+
+            let config = load_config().unwrap_or_default();
+            let now = Instant::now();
+            if !is_due(&config, last_run, now) {
+                continue;
+            }
+            last_run = Some(now);
+
+            if let Err(err) = run_once(&app_handle) {
+                eprintln!("Narrative: scheduled backfill failed: {err}");
+            }
+        }
+    });
+}
+
+/// Whether a backfill should run now
+fn is_due(config: &IngestConfig, last_run: Option<Instant>, now: Instant) -> bool {
+    if !config.auto_ingest_enabled || config.backfill_interval_minutes == 0 {
+        return false;
+    }
+    let interval = Duration::from_secs(config.backfill_interval_minutes.saturating_mul(60));
+    match last_run {
+        None => true,
+        Some(last) => now.saturating_duration_since(last) >= interval,
+    }
+}
+
+fn resume_interrupted(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(pool) = app_handle
+        .try_state::<crate::DbState>()
+        .map(|state| state.0.clone())
+    else {
+        return Ok(());
+    };
+
+    let resumed =
+        tauri::async_runtime::block_on(super::commands::resume_pending_imports_inner(&pool))?;
+    for batch in resumed {
+        if batch.imported > 0 {
+            let _ = app_handle.emit(
+                "scheduled-backfill",
+                ScheduledBackfillEvent {
+                    repo_id: batch.repo_id,
+                    imported: batch.imported,
+                    failed: batch.failed,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+fn run_once(app_handle: &AppHandle) -> Result<(), String> {
+    let Some(pool) = app_handle
+        .try_state::<crate::DbState>()
+        .map(|state| state.0.clone())
+    else {
+        return Ok(());
+    };
+
+    tauri::async_runtime::block_on(async {
+        let repo_ids: Vec<i64> =
+            sqlx::query_scalar("SELECT id FROM repos WHERE last_opened_at IS NOT NULL ORDER BY id")
+                .fetch_all(&*pool)
+                .await
+                .map_err(|e| e.to_string())?;
+
+        for repo_id in repo_ids {
+            let result = match super::commands::backfill_recent_sessions_inner(
+                &pool,
+                repo_id,
+                LIMIT_PER_TOOL,
+                false,
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(err) => {
+                    eprintln!("Narrative: scheduled backfill failed for repo {repo_id}: {err}");
+                    continue;
+                }
+            };
+
+            if result.imported > 0 {
+                let _ = app_handle.emit(
+                    "scheduled-backfill",
+                    ScheduledBackfillEvent {
+                        repo_id,
+                        imported: result.imported,
+                        failed: result.failed,
+                    },
+                );
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backfill_runs_on_interval_only_when_auto_ingest_enabled() {
+        let now = Instant::now();
+        let mut config = IngestConfig {
+            auto_ingest_enabled: true,
+            backfill_interval_minutes: 30,
+            ..IngestConfig::default()
+        };
+
+        assert!(is_due(&config, None, now));
+        assert!(!is_due(
+            &config,
+            Some(now),
+            now + Duration::from_secs(29 * 60)
+        ));
+        assert!(is_due(
+            &config,
+            Some(now),
+            now + Duration::from_secs(30 * 60)
+        ));
+
+        config.backfill_interval_minutes = 0;
+        assert!(!is_due(&config, None, now));
+
+        config.backfill_interval_minutes = 30;
+        config.auto_ingest_enabled = false;
+        assert!(!is_due(&config, None, now));
+    }
+}
@@ -0,0 +1,319 @@
+//! ChatGPT data-export parser
+//!
+//! Parses the `conversations.json` file from a ChatGPT data export
+//! (Settings → Data controls → Export data). Unlike other session sources, one
+//! export holds every conversation in the account, so it is split into one
+//! `ParsedSession` per conversation instead of going through `ParserRegistry`.
+//!
+//! Each conversation stores its messages as a tree (`mapping`) to support edits
+//! and regenerations. The visible thread is recovered by walking from
+//! `current_node` back to the root through `parent` links.
+
+use super::{
+    parser::{
+        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
+        TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+    tool_sanitizer::ToolSanitizer,
+};
+use crate::session_hash::generate_session_hash;
+use serde_json::Value;
+use std::path::Path;
+
+/// File name used by ChatGPT data exports
+pub(crate) const EXPORT_FILE_NAME: &str = "conversations.json";
+
+/// Exports cover a whole account history, so allow more than a single session file.
+const MAX_SIZE: usize = 512 * 1024 * 1024; // 512MB
+
+pub struct ChatGptExportParser;
+
+impl ChatGptExportParser {
+    /// Check whether a file looks like a ChatGPT export
+    pub fn can_parse(&self, path: &Path) -> bool {
+        path.file_name()
+            .map(|n| n == EXPORT_FILE_NAME)
+            .unwrap_or(false)
+    }
+
+    /// Parse every conversation in the export
+    ///
+    /// Conversations that fail to parse are skipped with a warning; the result
+    /// is only a failure when the file itself cannot be read.
+    pub fn parse_all(&self, path: &Path) -> ParseResult<Vec<ParsedSession>> {
+        // Security: Validate path
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        // Security: Check file size before reading the whole export
+        match std::fs::metadata(path) {
+            Ok(meta) if meta.len() as usize > MAX_SIZE => {
+                return ParseResult::Failure(ParseError::FileTooLarge)
+            }
+            Ok(_) => {}
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        }
+
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+
+        let json: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+        };
+
+        let Some(conversations) = json.as_array() else {
+            return ParseResult::Failure(ParseError::UnsupportedFormat);
+        };
+
+        let mut sessions = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (idx, conversation) in conversations.iter().enumerate() {
+            match parse_conversation(conversation, &mut warnings) {
+                Some(session) => sessions.push(session),
+                None => warnings.push(ParseWarning {
+                    severity: WarningSeverity::Warning,
+                    message: "Skipped conversation without messages".to_string(),
+                    context: Some(format!("conversation {}", idx)),
+                }),
+            }
+        }
+
+        if warnings.is_empty() {
+            ParseResult::Success(sessions)
+        } else {
+            ParseResult::Partial(sessions, warnings)
+        }
+    }
+}
+
+/// Convert one exported conversation into a session
+fn parse_conversation(
+    conversation: &Value,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<ParsedSession> {
+    let conversation_id = conversation["conversation_id"]
+        .as_str()
+        .or_else(|| conversation["id"].as_str())?
+        .to_string();
+    let mapping = conversation["mapping"].as_object()?;
+
+    // Walk from the visible leaf back to the root, then reverse.
+    let mut thread = Vec::new();
+    let mut cursor = conversation["current_node"].as_str();
+    while let Some(node_id) = cursor {
+        let Some(node) = mapping.get(node_id) else {
+            break;
+        };
+        if thread.len() > mapping.len() {
+            break; // Malformed export with a parent cycle
+        }
+        thread.push(node);
+        cursor = node["parent"].as_str();
+    }
+    thread.reverse();
+
+    let mut trace = SessionTrace::new();
+    let mut model: Option<String> = None;
+
+    for node in thread {
+        let message = &node["message"];
+        if message.is_null() {
+            continue;
+        }
+
+        let timestamp = message["create_time"]
+            .as_f64()
+            .and_then(seconds_to_datetime)
+            .map(|ts| ts.to_rfc3339());
+        let Some(text) = message_text(&message["content"]) else {
+            continue;
+        };
+
+        let secret_findings = SecretScanner::scan(&text);
+        if !secret_findings.is_empty() {
+            warnings.push(ParseWarning {
+                severity: WarningSeverity::Security,
+                message: format!(
+                    "Potential secrets detected: {}",
+                    secret_findings
+                        .iter()
+                        .map(|f| f.kind.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                context: Some(format!("conversation {}", conversation_id)),
+            });
+        }
+
+        let recipient = message["recipient"].as_str().unwrap_or("all");
+        match message["author"]["role"].as_str() {
+            Some("user") => trace.add_message(TraceMessage::User { text, timestamp }),
+            Some("assistant") if recipient != "all" => {
+                // Assistant messages addressed to a tool are tool invocations
+                // (e.g. recipient "python" with the code as content).
+                let input = serde_json::json!({ "input": text });
+                trace.add_message(TraceMessage::ToolCall {
+                    tool_name: recipient.to_string(),
+                    input: ToolSanitizer::sanitize(recipient, &input),
+                    timestamp,
+                });
+            }
+            Some("assistant") => {
+                if model.is_none() {
+                    model = message["metadata"]["model_slug"].as_str().map(String::from);
+                }
+                if message["content"]["content_type"].as_str() == Some("thoughts") {
+                    trace.add_message(TraceMessage::Thinking { text, timestamp });
+                } else {
+                    trace.add_message(TraceMessage::Assistant { text, timestamp });
+                }
+            }
+            _ => {} // Skip system prompts and tool output
+        }
+    }
+
+    if trace.messages.is_empty() {
+        return None;
+    }
+
+    let started_at = conversation["create_time"]
+        .as_f64()
+        .and_then(seconds_to_datetime);
+    let ended_at = conversation["update_time"]
+        .as_f64()
+        .and_then(seconds_to_datetime);
+
+    Some(ParsedSession {
+        origin: SessionOrigin {
+            tool: "chatgpt".to_string(),
+            session_id: generate_session_hash("chatgpt", &conversation_id),
+            conversation_id,
+            model,
+        },
+        started_at,
+        ended_at,
+        trace,
+        files_touched: Vec::new(), // Exports do not record local file paths
+        git_branch: None,
+        edit_fingerprints: Vec::new(),
+    })
+}
+
+/// Extract the readable text of a message, whatever its content type
+fn message_text(content: &Value) -> Option<String> {
+    let text = match content["content_type"].as_str() {
+        Some("text") | Some("multimodal_text") => content["parts"]
+            .as_array()?
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some("code") | Some("execution_output") => content["text"].as_str()?.to_string(),
+        Some("thoughts") => content["thoughts"]
+            .as_array()?
+            .iter()
+            .filter_map(|t| t["content"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => return None,
+    };
+
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Export timestamps are fractional seconds since the epoch
+fn seconds_to_datetime(secs: f64) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::from_timestamp_millis((secs * 1000.0) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_conversation(id: &str) -> Value {
+        serde_json::json!({
+            "conversation_id": id,
+            "title": "Refactor parser",
+            "create_time": 1718000000.5,
+            "update_time": 1718000600.0,
+            "current_node": "n3",
+            "mapping": {
+                "root": {"id": "root", "message": null, "parent": null, "children": ["n1"]},
+                "n1": {"id": "n1", "parent": "root", "children": ["n2", "n2b"], "message": {
+                    "author": {"role": "user"}, "create_time": 1718000000.5,
+                    "content": {"content_type": "text", "parts": ["How do I split this module?"]}
+                }},
+                "n2b": {"id": "n2b", "parent": "n1", "children": [], "message": {
+                    "author": {"role": "assistant"}, "create_time": 1718000010.0,
+                    "content": {"content_type": "text", "parts": ["Regenerated away"]}
+                }},
+                "n2": {"id": "n2", "parent": "n1", "children": ["n3"], "message": {
+                    "author": {"role": "assistant"}, "recipient": "python", "create_time": 1718000020.0,
+                    "content": {"content_type": "code", "text": "print(1)"}
+                }},
+                "n3": {"id": "n3", "parent": "n2", "children": [], "message": {
+                    "author": {"role": "assistant"}, "create_time": 1718000030.0,
+                    "metadata": {"model_slug": "gpt-4o"},
+                    "content": {"content_type": "text", "parts": ["Move the helpers into util.rs."]}
+                }}
+            }
+        })
+    }
+
+    #[test]
+    fn test_can_parse_export_file() {
+        let parser = ChatGptExportParser;
+        assert!(parser.can_parse(Path::new("/home/user/Downloads/export/conversations.json")));
+        assert!(!parser.can_parse(Path::new("/home/user/Downloads/export/chat.html")));
+    }
+
+    #[test]
+    fn test_parse_conversation_follows_current_branch() {
+        let mut warnings = Vec::new();
+        let session = parse_conversation(&sample_conversation("c1"), &mut warnings).unwrap();
+
+        assert_eq!(session.origin.tool, "chatgpt");
+        assert_eq!(session.origin.conversation_id, "c1");
+        assert_eq!(session.origin.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(session.message_count(), 3);
+        assert!(matches!(
+            session.trace.messages[1],
+            TraceMessage::ToolCall { ref tool_name, .. } if tool_name == "python"
+        ));
+        // The regenerated reply is not on the visible branch
+        assert!(!serde_json::to_string(&session.trace)
+            .unwrap()
+            .contains("Regenerated away"));
+        assert!(session.started_at.is_some());
+    }
+
+    #[test]
+    fn test_parse_all_splits_conversations() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(EXPORT_FILE_NAME);
+        let export = serde_json::json!([sample_conversation("c1"), sample_conversation("c2")]);
+        std::fs::write(&path, export.to_string()).unwrap();
+
+        let sessions = match ChatGptExportParser.parse_all(&path) {
+            ParseResult::Success(s) | ParseResult::Partial(s, _) => s,
+            ParseResult::Failure(e) => panic!("parse failed: {}", e),
+        };
+
+        assert_eq!(sessions.len(), 2);
+        assert_ne!(sessions[0].origin.session_id, sessions[1].origin.session_id);
+    }
+}
@@ -4,6 +4,8 @@
 //! Files are typically located at ~/.claude/projects/<project>/<uuid>.jsonl
 
 use super::{
+    format_version::{major_version, Compatibility, FormatSpec, FormatVersion, SchemaTracker},
+    line_reader,
     parser::{WarningSeverity, *},
     path_validator::PathValidator,
     secure_parser::SecretScanner,
@@ -11,10 +13,45 @@ use super::{
 };
 use crate::session_hash::generate_session_hash_from_path;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct ClaudeCodeParser;
 
+/// Claude Code schema generations, keyed by the major of each entry's `version`
+static CLAUDE_FORMAT: FormatSpec = FormatSpec {
+    tool: "claude_code",
+    name: "Claude Code",
+    versions: &[
+        FormatVersion {
+            version: "v0",
+            compatibility: Compatibility::Full,
+            notes: "Research preview releases",
+        },
+        FormatVersion {
+            version: "v1",
+            compatibility: Compatibility::Full,
+            notes: "",
+        },
+        FormatVersion {
+            version: "v2",
+            compatibility: Compatibility::Full,
+            notes: "Adds file-history-snapshot and queue-operation entries (ignored)",
+        },
+    ],
+    known_entry_types: &[
+        "user",
+        "assistant",
+        "summary",
+        "system",
+        "file-history-snapshot",
+        "queue-operation",
+    ],
+    incremental: true,
+};
+
+/// Assistant content block types we understand (others are reported and skipped)
+const KNOWN_BLOCK_TYPES: &[&str] = &["text", "thinking", "redacted_thinking", "plan", "tool_use"];
+
 impl SessionParser for ClaudeCodeParser {
     fn can_parse(&self, path: &Path) -> bool {
         // Check path contains .claude and ends with .jsonl
@@ -22,6 +59,14 @@ impl SessionParser for ClaudeCodeParser {
         path_str.contains(".claude") && path.extension().map(|e| e == "jsonl").unwrap_or(false)
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "claude_code"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[".claude/projects"])
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -31,144 +76,196 @@ impl SessionParser for ClaudeCodeParser {
             )));
         }
 
-        // Read file content
-        let content = match std::fs::read_to_string(path) {
-            Ok(c) => c,
+        // Stream lines within the configured read budget
+        let mut lines = match line_reader::open(path) {
+            Ok(lines) => lines,
             Err(e) => return ParseResult::Failure(ParseError::Io(e)),
         };
-
-        // Security: Check file size
-        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_SIZE {
-            return ParseResult::Failure(ParseError::FileTooLarge);
+        let result = parse_lines(lines.by_ref(), path);
+        match lines.finish() {
+            Ok(read_warnings) => result.with_warnings(read_warnings),
+            Err(e) => ParseResult::Failure(ParseError::Io(e)),
         }
+    }
 
-        // Generate deterministic session hash from filename
-        // Format: SHA-256("claude_code:{uuid}")[:16]
-        let session_id = generate_session_hash_from_path("claude_code", path);
+    fn parse_appended(&self, path: &Path, offset: u64) -> Option<ParseResult<AppendedLines>> {
+        let (content, next_offset) = match read_appended_lines(path, offset) {
+            Ok(read) => read,
+            Err(e) => return Some(ParseResult::Failure(e)),
+        };
 
-        let mut trace = SessionTrace::new();
-        let mut model: Option<String> = None;
-        let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
-        let mut files_touched: Vec<String> = Vec::new();
-        let mut warnings: Vec<ParseWarning> = Vec::new();
+        Some(
+            parse_lines(content.lines().enumerate(), path).map(|session| AppendedLines {
+                session,
+                next_offset,
+            }),
+        )
+    }
 
-        // Parse each line (JSONL format)
-        for (line_num, line) in content.lines().enumerate() {
-            if line.trim().is_empty() {
-                continue;
-            }
+    fn format_spec(&self) -> Option<&'static FormatSpec> {
+        Some(&CLAUDE_FORMAT)
+    }
+}
 
-            // Security: Scan for secrets
-            let secret_findings = SecretScanner::scan(line);
-            if !secret_findings.is_empty() {
+/// Parse JSONL lines (a whole file, or lines appended to one)
+fn parse_lines<S: AsRef<str>>(
+    lines: impl Iterator<Item = (usize, S)>,
+    path: &Path,
+) -> ParseResult<ParsedSession> {
+    // Generate deterministic session hash from filename
+    // Format: SHA-256("claude_code:{uuid}")[:16]
+    let session_id = generate_session_hash_from_path("claude_code", path);
+
+    let mut trace = SessionTrace::new();
+    let mut model: Option<String> = None;
+    let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+    let mut files_touched: Vec<String> = Vec::new();
+    let mut git_branch: Option<String> = None;
+    let mut edit_fingerprints: Vec<String> = Vec::new();
+    let mut warnings: Vec<ParseWarning> = Vec::new();
+    let mut schema = SchemaTracker::new(&CLAUDE_FORMAT);
+
+    // Parse each line (JSONL format)
+    for (line_num, line) in lines {
+        let line = line.as_ref();
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Security: Scan for secrets
+        let secret_findings = SecretScanner::scan(line);
+        if !secret_findings.is_empty() {
+            warnings.push(ParseWarning {
+                severity: WarningSeverity::Security,
+                message: format!(
+                    "Potential {} detected",
+                    secret_findings
+                        .iter()
+                        .map(|f| f.kind.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                context: Some(format!("line {}", line_num + 1)),
+            });
+        }
+
+        // Parse JSON entry
+        let entry: Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => {
                 warnings.push(ParseWarning {
-                    severity: WarningSeverity::Security,
-                    message: format!(
-                        "Potential {} detected",
-                        secret_findings
-                            .iter()
-                            .map(|f| f.kind.as_str())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    ),
+                    severity: WarningSeverity::Warning,
+                    message: format!("JSON parse error: {}", e),
                     context: Some(format!("line {}", line_num + 1)),
                 });
+                continue; // Skip bad line, keep parsing
             }
+        };
 
-            // Parse JSON entry
-            let entry: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(e) => {
-                    warnings.push(ParseWarning {
-                        severity: WarningSeverity::Warning,
-                        message: format!("JSON parse error: {}", e),
-                        context: Some(format!("line {}", line_num + 1)),
-                    });
-                    continue; // Skip bad line, keep parsing
-                }
-            };
+        // Format version detection (each entry carries the CLI release)
+        if let Some(version) = entry["version"].as_str().and_then(major_version) {
+            schema.observe_version(&version, line_num);
+        }
+        if let Some(entry_type) = entry["type"].as_str() {
+            schema.observe_entry_type(entry_type, line_num);
+        }
 
-            // Extract timestamp if present
-            if let Some(ts_str) = entry["timestamp"].as_str() {
-                if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(ts_str) {
-                    timestamps.push(ts.with_timezone(&chrono::Utc));
-                }
+        // Extract timestamp if present
+        if let Some(ts_str) = entry["timestamp"].as_str() {
+            if let Some(ts) = parse_session_timestamp(ts_str) {
+                timestamps.push(ts);
             }
+        }
 
-            // Extract model from assistant messages
-            if model.is_none() && entry["type"].as_str() == Some("assistant") {
-                if let Some(m) = entry["message"]["model"].as_str() {
-                    model = Some(m.to_string());
-                }
+        // Extract model from assistant messages
+        if model.is_none() && entry["type"].as_str() == Some("assistant") {
+            if let Some(m) = entry["message"]["model"].as_str() {
+                model = Some(m.to_string());
             }
+        }
 
-            // Extract file paths from tool inputs
-            if let Some(tool_input) = entry["tool_input"].as_object() {
-                for key in &["file_path", "path", "filepath"] {
-                    if let Some(path) = tool_input.get(*key).and_then(|v| v.as_str()) {
-                        files_touched.push(path.to_string());
-                    }
+        // Each entry records the checked-out branch; keep the latest
+        if let Some(branch) = entry["gitBranch"].as_str().filter(|b| !b.is_empty()) {
+            git_branch = Some(branch.to_string());
+        }
+
+        // Extract file paths from tool inputs
+        if let Some(tool_input) = entry["tool_input"].as_object() {
+            for key in &["file_path", "path", "filepath"] {
+                if let Some(path) = tool_input.get(*key).and_then(|v| v.as_str()) {
+                    files_touched.push(path.to_string());
                 }
             }
+        }
 
-            // Parse message based on type
-            match entry["type"].as_str() {
-                Some("user") => {
-                    if let Some(text) = extract_content_text(&entry["message"]["content"]) {
-                        trace.add_message(TraceMessage::User {
-                            text,
-                            timestamp: entry["timestamp"].as_str().map(String::from),
-                        });
-                    }
+        // Parse message based on type
+        match entry["type"].as_str() {
+            Some("user") => {
+                if let Some(text) = extract_content_text(&entry["message"]["content"]) {
+                    trace.add_message(TraceMessage::User {
+                        text,
+                        timestamp: entry["timestamp"].as_str().map(String::from),
+                    });
                 }
-                Some("assistant") => {
-                    if let Err(e) = parse_assistant_message(&entry, &mut trace) {
-                        warnings.push(ParseWarning {
-                            severity: WarningSeverity::Warning,
-                            message: format!("Failed to parse assistant message: {}", e),
-                            context: Some(format!("line {}", line_num + 1)),
-                        });
-                    }
+            }
+            Some("assistant") => {
+                if let Err(e) = parse_assistant_message(
+                    &entry,
+                    &mut trace,
+                    &mut edit_fingerprints,
+                    &mut schema,
+                    line_num,
+                ) {
+                    warnings.push(ParseWarning {
+                        severity: WarningSeverity::Warning,
+                        message: format!("Failed to parse assistant message: {}", e),
+                        context: Some(format!("line {}", line_num + 1)),
+                    });
                 }
-                _ => {} // Skip unknown message types
             }
+            _ => {} // Skip unknown message types
         }
+    }
 
-        // Deduplicate and sort files
-        files_touched.sort();
-        files_touched.dedup();
-
-        // Determine session timestamps
-        let started_at = timestamps.first().copied();
-        let ended_at = timestamps.last().copied();
-
-        // Extract original conversation ID for reference
-        let conversation_id = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        let session = ParsedSession {
-            origin: SessionOrigin {
-                tool: "claude_code".to_string(),
-                session_id,
-                conversation_id,
-                model,
-            },
-            started_at,
-            ended_at,
-            trace,
-            files_touched,
-        };
-
-        // Return result based on warnings
-        if warnings.is_empty() {
-            ParseResult::Success(session)
-        } else {
-            ParseResult::Partial(session, warnings)
-        }
+    warnings.extend(schema.into_warnings());
+
+    // Deduplicate and sort files
+    files_touched.sort();
+    files_touched.dedup();
+    edit_fingerprints.sort();
+    edit_fingerprints.dedup();
+
+    // Determine session timestamps
+    let started_at = timestamps.first().copied();
+    let ended_at = timestamps.last().copied();
+
+    // Extract original conversation ID for reference
+    let conversation_id = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let session = ParsedSession {
+        origin: SessionOrigin {
+            tool: "claude_code".to_string(),
+            session_id,
+            conversation_id,
+            model,
+        },
+        started_at,
+        ended_at,
+        trace,
+        files_touched,
+        git_branch,
+        edit_fingerprints,
+    };
+
+    // Return result based on warnings
+    if warnings.is_empty() {
+        ParseResult::Success(session)
+    } else {
+        ParseResult::Partial(session, warnings)
     }
 }
 
@@ -194,7 +291,13 @@ fn extract_content_text(content: &Value) -> Option<String> {
 }
 
 /// Parse assistant message with content blocks
-fn parse_assistant_message(entry: &Value, trace: &mut SessionTrace) -> Result<(), String> {
+fn parse_assistant_message(
+    entry: &Value,
+    trace: &mut SessionTrace,
+    edit_fingerprints: &mut Vec<String>,
+    schema: &mut SchemaTracker,
+    line_num: usize,
+) -> Result<(), String> {
     let content = entry["message"]["content"]
         .as_array()
         .ok_or("Missing content array")?;
@@ -202,6 +305,12 @@ fn parse_assistant_message(entry: &Value, trace: &mut SessionTrace) -> Result<()
     let timestamp = entry["timestamp"].as_str().map(String::from);
 
     for block in content {
+        if let Some(block_type) = block["type"].as_str() {
+            if !KNOWN_BLOCK_TYPES.contains(&block_type) {
+                schema.observe_unknown_field("content block", block_type, line_num);
+            }
+        }
+
         match block["type"].as_str() {
             Some("text") => {
                 if let Some(text) = block["text"].as_str() {
@@ -241,6 +350,9 @@ fn parse_assistant_message(entry: &Value, trace: &mut SessionTrace) -> Result<()
                 if let Some(name) = block["name"].as_str() {
                     let input = block["input"].clone();
 
+                    // Edit contents are dropped below; keep their fingerprints
+                    edit_fingerprints.extend(crate::linking::edit_fingerprints(name, &input));
+
                     // Sanitize tool input before storing
                     let sanitized = ToolSanitizer::sanitize(name, &input);
 
@@ -309,6 +421,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_content_reports_unknown_schema() {
+        let content = [
+            r#"{"type": "user", "version": "3.0.1", "message": {"content": "Hi"}}"#,
+            r#"{"type": "assistant", "version": "3.0.1", "message": {"content": [{"type": "text", "text": "Hello"}, {"type": "artifact", "text": "x"}]}}"#,
+            r#"{"type": "checkpoint", "version": "3.0.1"}"#,
+        ]
+        .join("\n");
+        let path = std::path::Path::new("/home/user/.claude/projects/p/abc.jsonl");
+
+        match parse_lines(content.lines().enumerate(), path) {
+            ParseResult::Partial(session, warnings) => {
+                assert_eq!(session.trace.messages.len(), 2);
+                let messages: Vec<&str> = warnings.iter().map(|w| w.message.as_str()).collect();
+                assert!(messages
+                    .iter()
+                    .any(|m| m.contains("Unknown Claude Code schema v3")));
+                assert!(messages
+                    .contains(&"Unknown Claude Code schema v3 content block 'artifact' (skipped)"));
+                assert!(messages
+                    .contains(&"Unknown Claude Code schema v3 entry type 'checkpoint' (skipped)"));
+            }
+            _ => panic!("expected partial result with schema warnings"),
+        }
+    }
+
     #[test]
     fn test_extract_content_text_string() {
         let content = serde_json::json!("Hello world");
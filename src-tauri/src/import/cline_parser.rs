@@ -0,0 +1,372 @@
+//! Cline / Roo Code session parser
+//!
+//! Parses task histories written by the Cline and Roo Code VS Code extensions.
+//! Both store one directory per task under VS Code's globalStorage:
+//! - Cline: <globalStorage>/saoudrizwan.claude-dev/tasks/<taskId>/api_conversation_history.json
+//! - Roo Code: <globalStorage>/rooveterinaryinc.roo-cline/tasks/<taskId>/api_conversation_history.json
+//!
+//! The history file is an Anthropic-style message array. Tool use shows up either as
+//! `tool_use` content blocks (newer versions) or as XML tags embedded in assistant text
+//! (e.g. `<write_to_file><path>src/main.rs</path>...`).
+
+use super::{
+    parser::{
+        epoch_timestamp, parse_json_timestamp, ParseError, ParseResult, ParseWarning,
+        ParsedSession, SessionOrigin, SessionTrace, TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+    tool_sanitizer::ToolSanitizer,
+};
+use crate::session_hash::generate_session_hash;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// globalStorage folder used by the Cline extension
+const CLINE_STORAGE_ID: &str = "saoudrizwan.claude-dev";
+
+/// globalStorage folder used by the Roo Code extension
+const ROO_CODE_STORAGE_ID: &str = "rooveterinaryinc.roo-cline";
+
+/// Task history file name shared by both extensions
+const HISTORY_FILE_NAME: &str = "api_conversation_history.json";
+
+/// Tools whose `path` argument identifies a file the agent touched
+const FILE_TOOLS: &[&str] = &[
+    "write_to_file",
+    "apply_diff",
+    "replace_in_file",
+    "insert_content",
+    "search_and_replace",
+    "read_file",
+];
+
+lazy_static! {
+    /// XML-style tool invocations embedded in assistant text
+    static ref XML_TOOL_PATTERN: Regex = Regex::new(
+        r"<(write_to_file|apply_diff|replace_in_file|insert_content|search_and_replace|read_file)>\s*<path>([^<]+)</path>"
+    )
+    .expect("valid regex: Cline XML tool call");
+}
+
+pub struct ClineParser;
+
+impl super::parser::SessionParser for ClineParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/").to_lowercase();
+        (path_str.contains(CLINE_STORAGE_ID) || path_str.contains(ROO_CODE_STORAGE_ID))
+            && path
+                .file_name()
+                .map(|n| n == HISTORY_FILE_NAME)
+                .unwrap_or(false)
+    }
+
+    fn tool_id(&self, path: &Path) -> &str {
+        detect_tool(path)
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        let Some(config) = dirs::config_dir() else {
+            return Vec::new();
+        };
+        crate::ingest_config::VSCODE_EDITOR_DIRS
+            .iter()
+            .flat_map(|editor| {
+                let global = config.join(editor).join("User/globalStorage");
+                [CLINE_STORAGE_ID, ROO_CODE_STORAGE_ID].map(|id| global.join(id).join("tasks"))
+            })
+            .collect()
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        // Security: Validate path
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        // Read file content
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+
+        // Security: Check file size
+        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
+        if content.len() > MAX_SIZE {
+            return ParseResult::Failure(ParseError::FileTooLarge);
+        }
+
+        let json: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+        };
+
+        let Some(messages) = json.as_array() else {
+            return ParseResult::Failure(ParseError::UnsupportedFormat);
+        };
+
+        let tool = detect_tool(path);
+
+        // The task directory name is the task id (a millisecond timestamp).
+        let conversation_id = path
+            .parent()
+            .and_then(|p| p.file_name())
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let session_id = generate_session_hash(tool, &conversation_id);
+
+        let mut trace = SessionTrace::new();
+        let mut warnings = Vec::new();
+        let mut files_touched: Vec<String> = Vec::new();
+        let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+
+        for (idx, msg) in messages.iter().enumerate() {
+            let timestamp = parse_json_timestamp(&msg["ts"]);
+            if let Some(ts) = timestamp {
+                timestamps.push(ts);
+            }
+            let timestamp_str = timestamp.map(|ts| ts.to_rfc3339());
+
+            match msg["role"].as_str() {
+                Some("user") => {
+                    if let Some(text) = extract_text(&msg["content"]) {
+                        scan_for_secrets(&text, idx, &mut warnings);
+                        trace.add_message(TraceMessage::User {
+                            text,
+                            timestamp: timestamp_str,
+                        });
+                    }
+                }
+                Some("assistant") => {
+                    parse_assistant_content(
+                        &msg["content"],
+                        timestamp_str,
+                        idx,
+                        &mut trace,
+                        &mut files_touched,
+                        &mut warnings,
+                    );
+                }
+                _ => {} // Skip unknown roles
+            }
+        }
+
+        files_touched.sort();
+        files_touched.dedup();
+
+        // Fall back to the task id when messages carry no timestamps.
+        let started_at = timestamps.first().copied().or_else(|| {
+            conversation_id
+                .parse::<i64>()
+                .ok()
+                .and_then(epoch_timestamp)
+        });
+        let ended_at = timestamps.last().copied();
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: tool.to_string(),
+                session_id,
+                conversation_id,
+                model: None, // Model is configured per-extension, not recorded in history
+            },
+            started_at,
+            ended_at,
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+/// Identify which extension wrote the file
+fn detect_tool(path: &Path) -> &'static str {
+    let path_str = path.to_string_lossy().to_lowercase();
+    if path_str.contains(ROO_CODE_STORAGE_ID) {
+        "roo_code"
+    } else {
+        "cline"
+    }
+}
+
+/// Extract text content (handles string or content block array)
+fn extract_text(content: &Value) -> Option<String> {
+    match content {
+        Value::String(text) => Some(text.clone()),
+        Value::Array(blocks) => {
+            let text = blocks
+                .iter()
+                .filter(|block| block["type"].as_str() == Some("text"))
+                .filter_map(|block| block["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            if text.trim().is_empty() {
+                None
+            } else {
+                Some(text)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_assistant_content(
+    content: &Value,
+    timestamp: Option<String>,
+    idx: usize,
+    trace: &mut SessionTrace,
+    files: &mut Vec<String>,
+    warnings: &mut Vec<ParseWarning>,
+) {
+    let blocks: Vec<Value> = match content {
+        Value::String(text) => vec![serde_json::json!({ "type": "text", "text": text })],
+        Value::Array(blocks) => blocks.clone(),
+        _ => return,
+    };
+
+    for block in &blocks {
+        match block["type"].as_str() {
+            Some("text") => {
+                let Some(text) = block["text"].as_str() else {
+                    continue;
+                };
+                if text.trim().is_empty() {
+                    continue;
+                }
+                scan_for_secrets(text, idx, warnings);
+
+                // Older versions embed tool calls as XML in the assistant text.
+                for caps in XML_TOOL_PATTERN.captures_iter(text) {
+                    let tool_name = &caps[1];
+                    let file_path = caps[2].trim().to_string();
+                    files.push(file_path.clone());
+                    trace.add_message(TraceMessage::ToolCall {
+                        tool_name: tool_name.to_string(),
+                        input: Some(serde_json::json!({ "path": file_path })),
+                        timestamp: timestamp.clone(),
+                    });
+                }
+
+                trace.add_message(TraceMessage::Assistant {
+                    text: text.to_string(),
+                    timestamp: timestamp.clone(),
+                });
+            }
+            Some("tool_use") => {
+                let Some(name) = block["name"].as_str() else {
+                    continue;
+                };
+                let input = &block["input"];
+                if FILE_TOOLS.contains(&name) {
+                    if let Some(p) = input["path"].as_str() {
+                        files.push(p.to_string());
+                    }
+                }
+
+                // Sanitize tool input before storing
+                trace.add_message(TraceMessage::ToolCall {
+                    tool_name: name.to_string(),
+                    input: ToolSanitizer::sanitize(name, input),
+                    timestamp: timestamp.clone(),
+                });
+            }
+            _ => {} // Skip unknown block types
+        }
+    }
+}
+
+fn scan_for_secrets(text: &str, idx: usize, warnings: &mut Vec<ParseWarning>) {
+    let secret_findings = SecretScanner::scan(text);
+    if !secret_findings.is_empty() {
+        warnings.push(ParseWarning {
+            severity: WarningSeverity::Security,
+            message: format!(
+                "Potential secrets detected: {}",
+                secret_findings
+                    .iter()
+                    .map(|f| f.kind.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            context: Some(format!("message {}", idx)),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::SessionParser;
+
+    #[test]
+    fn test_can_parse_cline_files() {
+        let parser = ClineParser;
+
+        let cline_path = Path::new(
+            "/home/user/.config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks/1718000000000/api_conversation_history.json",
+        );
+        assert!(parser.can_parse(cline_path));
+
+        let roo_path = Path::new(
+            "/home/user/.config/Code/User/globalStorage/rooveterinaryinc.roo-cline/tasks/1718000000000/api_conversation_history.json",
+        );
+        assert!(parser.can_parse(roo_path));
+
+        // UI message logs are not conversation history
+        let ui_path = Path::new(
+            "/home/user/.config/Code/User/globalStorage/saoudrizwan.claude-dev/tasks/1718000000000/ui_messages.json",
+        );
+        assert!(!parser.can_parse(ui_path));
+
+        let other_path = Path::new("/some/other/path.json");
+        assert!(!parser.can_parse(other_path));
+    }
+
+    #[test]
+    fn test_parse_extracts_files_from_tool_calls() {
+        let tmp = tempfile::tempdir().unwrap();
+        let task_dir = tmp
+            .path()
+            .join("saoudrizwan.claude-dev/tasks/1718000000000");
+        std::fs::create_dir_all(&task_dir).unwrap();
+        let history = task_dir.join(HISTORY_FILE_NAME);
+        std::fs::write(
+            &history,
+            r#"[
+                {"role": "user", "content": [{"type": "text", "text": "<task>Fix the bug</task>"}]},
+                {"role": "assistant", "content": [
+                    {"type": "text", "text": "<write_to_file>\n<path>src/lib.rs</path>\n<content>fn main() {}</content>\n</write_to_file>"},
+                    {"type": "tool_use", "name": "apply_diff", "input": {"path": "src/main.rs", "diff": "..."}}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+
+        let session = match ClineParser.parse(&history) {
+            ParseResult::Success(s) | ParseResult::Partial(s, _) => s,
+            ParseResult::Failure(e) => panic!("parse failed: {}", e),
+        };
+
+        assert_eq!(session.origin.tool, "cline");
+        assert_eq!(session.origin.conversation_id, "1718000000000");
+        assert_eq!(session.files_touched, vec!["src/lib.rs", "src/main.rs"]);
+        assert!(session.started_at.is_some());
+        assert!(session.trace.messages.iter().any(
+            |m| matches!(m, TraceMessage::ToolCall { tool_name, .. } if tool_name == "apply_diff")
+        ));
+    }
+}
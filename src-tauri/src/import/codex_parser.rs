@@ -12,7 +12,7 @@ use super::{
 };
 use crate::session_hash::generate_session_hash_from_path;
 use regex::Regex;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct CodexLogParser;
 
@@ -24,6 +24,14 @@ impl SessionParser for CodexLogParser {
         path_str.contains(".codex/logs") && path_str.contains(".log") && path.is_file()
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "codex"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[".codex/logs"])
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -129,6 +137,8 @@ impl SessionParser for CodexLogParser {
             ended_at: None,
             trace,
             files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -10,6 +10,8 @@
 //! we resolve the latest session file and parse that.
 
 use super::{
+    format_version::{Compatibility, FormatSpec, FormatVersion, SchemaTracker},
+    line_reader,
     parser::{WarningSeverity, *},
     path_validator::PathValidator,
     secure_parser::SecretScanner,
@@ -29,6 +31,32 @@ fn permission_denied_error(message: String) -> ParseResult<ParsedSession> {
 
 pub struct CodexSessionJsonlParser;
 
+/// Codex rollout schemas, told apart by line shape (Codex does not version them)
+static CODEX_FORMAT: FormatSpec = FormatSpec {
+    tool: "codex",
+    name: "Codex",
+    versions: &[
+        FormatVersion {
+            version: "v1",
+            compatibility: Compatibility::Partial,
+            notes: "Legacy flat rollout items; no model or timestamps per message",
+        },
+        FormatVersion {
+            version: "v2",
+            compatibility: Compatibility::Full,
+            notes: "`{type, payload}` envelopes with session_meta",
+        },
+    ],
+    known_entry_types: &[
+        "session_meta",
+        "response_item",
+        "event_msg",
+        "turn_context",
+        "compacted",
+    ],
+    incremental: true,
+};
+
 fn normalize_path_slashes(path: &Path) -> String {
     // Many path heuristics below use '/' separators. Normalize so the code works on Windows too.
     path.to_string_lossy().replace('\\', "/")
@@ -56,6 +84,18 @@ impl SessionParser for CodexSessionJsonlParser {
         path.extension().map(|e| e == "jsonl").unwrap_or(false)
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "codex"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[
+            ".codex/sessions",
+            ".codex/archived_sessions",
+            ".codex/history.jsonl",
+        ])
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -69,6 +109,30 @@ impl SessionParser for CodexSessionJsonlParser {
 
         self.parse_session_file(path)
     }
+
+    fn parse_appended(&self, path: &Path, offset: u64) -> Option<ParseResult<AppendedLines>> {
+        // history.jsonl points at other files; only session files grow in place.
+        if normalize_path_slashes(path).ends_with("/.codex/history.jsonl") {
+            return None;
+        }
+
+        let (content, next_offset) = match read_appended_lines(path, offset) {
+            Ok(read) => read,
+            Err(e) => return Some(ParseResult::Failure(e)),
+        };
+
+        Some(
+            self.parse_lines(content.lines().enumerate(), path)
+                .map(|session| AppendedLines {
+                    session,
+                    next_offset,
+                }),
+        )
+    }
+
+    fn format_spec(&self) -> Option<&'static FormatSpec> {
+        Some(&CODEX_FORMAT)
+    }
 }
 
 impl CodexSessionJsonlParser {
@@ -126,27 +190,38 @@ impl CodexSessionJsonlParser {
     }
 
     fn parse_session_file(&self, path: &Path) -> ParseResult<ParsedSession> {
-        let content = match std::fs::read_to_string(path) {
-            Ok(c) => c,
+        // Rollouts can be hundreds of MB; stream them within the read budget.
+        let mut lines = match line_reader::open(path) {
+            Ok(lines) => lines,
             Err(e) => return ParseResult::Failure(ParseError::Io(e)),
         };
-
-        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
-        if content.len() > MAX_SIZE {
-            return ParseResult::Failure(ParseError::FileTooLarge);
+        let result = self.parse_lines(lines.by_ref(), path);
+        match lines.finish() {
+            Ok(read_warnings) => result.with_warnings(read_warnings),
+            Err(e) => ParseResult::Failure(ParseError::Io(e)),
         }
+    }
 
+    /// Parse JSONL lines (a whole file, or lines appended to one)
+    fn parse_lines<S: AsRef<str>>(
+        &self,
+        lines: impl Iterator<Item = (usize, S)>,
+        path: &Path,
+    ) -> ParseResult<ParsedSession> {
         let mut trace = SessionTrace::new();
         let mut warnings: Vec<ParseWarning> = Vec::new();
         let mut files_touched: Vec<String> = Vec::new();
+        let mut edit_fingerprints: Vec<String> = Vec::new();
+        let mut schema = SchemaTracker::new(&CODEX_FORMAT);
 
         let mut conversation_id: Option<String> = None;
         let mut model: Option<String> = None;
+        let mut git_branch: Option<String> = None;
         let mut ts_first: Option<chrono::DateTime<chrono::Utc>> = None;
         let mut ts_last: Option<chrono::DateTime<chrono::Utc>> = None;
 
-        for (line_num, line) in content.lines().enumerate() {
-            let line = line.trim();
+        for (line_num, line) in lines {
+            let line = line.as_ref().trim();
             if line.is_empty() {
                 continue;
             }
@@ -182,15 +257,38 @@ impl CodexSessionJsonlParser {
 
             // Timestamp bookkeeping (if present).
             if let Some(ts) = entry.get("timestamp").and_then(|t| t.as_str()) {
-                if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(ts) {
-                    let dt = dt.with_timezone(&chrono::Utc);
+                if let Some(dt) = parse_session_timestamp(ts) {
                     ts_first = Some(ts_first.map_or(dt, |prev| prev.min(dt)));
                     ts_last = Some(ts_last.map_or(dt, |prev| prev.max(dt)));
                 }
             }
 
             let kind = entry.get("type").and_then(|t| t.as_str()).unwrap_or("");
-            let payload = entry.get("payload").cloned().unwrap_or(Value::Null);
+            let Some(payload) = entry.get("payload") else {
+                // Legacy rollout (v1): a metadata line, then flat response items.
+                schema.observe_version("v1", line_num);
+                if entry.get("record_type").is_some() {
+                    continue;
+                }
+                if kind.is_empty() {
+                    if conversation_id.is_none() {
+                        conversation_id = entry
+                            .get("id")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string());
+                    }
+                } else {
+                    self.parse_response_item(
+                        &entry,
+                        &mut trace,
+                        &mut files_touched,
+                        &mut edit_fingerprints,
+                    );
+                }
+                continue;
+            };
+            schema.observe_version("v2", line_num);
+            schema.observe_entry_type(kind, line_num);
 
             match kind {
                 "session_meta" => {
@@ -206,17 +304,32 @@ impl CodexSessionJsonlParser {
                             .and_then(|v| v.as_str())
                             .map(|s| s.to_string());
                     }
+                    if git_branch.is_none() {
+                        git_branch = payload
+                            .pointer("/git/branch")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string());
+                    }
                 }
                 "response_item" => {
-                    self.parse_response_item(&payload, &mut trace, &mut files_touched);
+                    self.parse_response_item(
+                        payload,
+                        &mut trace,
+                        &mut files_touched,
+                        &mut edit_fingerprints,
+                    );
                 }
                 // Ignore UI/event plumbing; they are not stable and often redundant.
                 _ => {}
             }
         }
 
+        warnings.extend(schema.into_warnings());
+
         files_touched.sort();
         files_touched.dedup();
+        edit_fingerprints.sort();
+        edit_fingerprints.dedup();
 
         let conversation_id = conversation_id
             .or_else(|| {
@@ -245,6 +358,8 @@ impl CodexSessionJsonlParser {
             ended_at: ts_last,
             trace,
             files_touched,
+            git_branch,
+            edit_fingerprints,
         };
 
         if warnings.is_empty() {
@@ -259,6 +374,7 @@ impl CodexSessionJsonlParser {
         payload: &Value,
         trace: &mut SessionTrace,
         files: &mut Vec<String>,
+        edit_fingerprints: &mut Vec<String>,
     ) {
         // Tool call shape (no role, has name+arguments)
         if payload.get("name").is_some() && payload.get("arguments").is_some() {
@@ -288,6 +404,8 @@ impl CodexSessionJsonlParser {
                 }
             }
 
+            edit_fingerprints.extend(crate::linking::edit_fingerprints(&tool_name, &args_raw));
+
             trace.add_message(TraceMessage::ToolCall {
                 tool_name,
                 input: Some(args_raw),
@@ -410,6 +528,59 @@ fn walk_find_first(
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_content_handles_legacy_and_unknown_entries() {
+        let parser = CodexSessionJsonlParser;
+        let path = Path::new("/home/u/.codex/sessions/rollout.jsonl");
+
+        let legacy = [
+            r#"{"id": "legacy-1", "timestamp": "2025-05-01T10:00:00Z", "instructions": null}"#,
+            r#"{"record_type": "state"}"#,
+            r#"{"type": "message", "role": "user", "content": [{"type": "input_text", "text": "hi"}]}"#,
+        ]
+        .join("\n");
+        match parser.parse_lines(legacy.lines().enumerate(), path) {
+            ParseResult::Success(session) => {
+                assert_eq!(session.origin.conversation_id, "legacy-1");
+                assert_eq!(session.trace.messages.len(), 1);
+            }
+            _ => panic!("legacy rollout should parse cleanly"),
+        }
+
+        let future = [
+            r#"{"type": "session_meta", "payload": {"id": "s-2"}}"#,
+            r#"{"type": "checkpoint", "payload": {}}"#,
+        ]
+        .join("\n");
+        match parser.parse_lines(future.lines().enumerate(), path) {
+            ParseResult::Partial(_, warnings) => {
+                assert_eq!(warnings.len(), 1);
+                assert_eq!(
+                    warnings[0].message,
+                    "Unknown Codex schema v2 entry type 'checkpoint' (skipped)"
+                );
+            }
+            _ => panic!("unknown entry type should produce a warning"),
+        }
+    }
+
+    #[test]
+    fn parse_lines_reads_git_branch_from_session_meta() {
+        let parser = CodexSessionJsonlParser;
+        let path = Path::new("/home/u/.codex/sessions/rollout.jsonl");
+        let lines = [
+            r#"{"type": "session_meta", "payload": {"id": "s-3", "git": {"branch": "feature/retry", "commit_hash": "abc"}}}"#,
+            r#"{"type": "response_item", "payload": {"type": "message", "role": "user", "content": [{"type": "input_text", "text": "hi"}]}}"#,
+        ]
+        .join("\n");
+        match parser.parse_lines(lines.lines().enumerate(), path) {
+            ParseResult::Success(session) => {
+                assert_eq!(session.git_branch.as_deref(), Some("feature/retry"));
+            }
+            _ => panic!("rollout with git metadata should parse cleanly"),
+        }
+    }
+
     #[cfg(unix)]
     #[test]
     fn walk_find_first_skips_symlinked_files() {
@@ -0,0 +1,286 @@
+//! Sourcegraph Cody session parser
+//!
+//! Parses chat transcripts persisted by the Cody VS Code extension. Cody keeps its
+//! history under the extension's storage folder, either globally or per workspace:
+//! - <VS Code user dir>/globalStorage/sourcegraph.cody-ai/*.json
+//! - <VS Code user dir>/workspaceStorage/<hash>/sourcegraph.cody-ai/*.json
+//!
+//! A file holds either a single transcript (`{ "id", "interactions": [...] }`), an
+//! exported list of transcripts, or the history map keyed by account
+//! (`{ "<account>": { "chat": { "<id>": {...} } } }`). When a file holds several
+//! transcripts, the most recently active one is imported.
+
+use super::{
+    parser::{
+        parse_json_timestamp, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+};
+use crate::session_hash::generate_session_hash;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Storage folder used by the Cody extension
+pub(crate) const CODY_STORAGE_ID: &str = "sourcegraph.cody-ai";
+
+pub struct CodyParser;
+
+impl super::parser::SessionParser for CodyParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/").to_lowercase();
+        path_str.contains(CODY_STORAGE_ID) && path.extension().map(|e| e == "json").unwrap_or(false)
+    }
+
+    fn tool_id(&self, _path: &Path) -> &str {
+        "cody"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        crate::ingest_config::discover_cody_storage_dirs()
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        // Security: Validate path
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        // Read file content
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+
+        // Security: Check file size
+        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
+        if content.len() > MAX_SIZE {
+            return ParseResult::Failure(ParseError::FileTooLarge);
+        }
+
+        let json: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+        };
+
+        let mut transcripts = Vec::new();
+        collect_transcripts(&json, &mut transcripts);
+
+        // Import the most recently active transcript
+        let Some(transcript) = transcripts
+            .into_iter()
+            .max_by_key(|t| parse_json_timestamp(&t["lastInteractionTimestamp"]))
+        else {
+            return ParseResult::Failure(ParseError::UnsupportedFormat);
+        };
+
+        self.parse_transcript(transcript, path)
+    }
+}
+
+impl CodyParser {
+    fn parse_transcript(&self, transcript: &Value, path: &Path) -> ParseResult<ParsedSession> {
+        let conversation_id = transcript["id"]
+            .as_str()
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or("unknown")
+            .to_string();
+
+        let session_id = generate_session_hash("cody", &conversation_id);
+
+        let mut trace = SessionTrace::new();
+        let mut warnings = Vec::new();
+        let mut model: Option<String> = None;
+        let mut files_touched: Vec<String> = Vec::new();
+        let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+
+        let interactions = transcript["interactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+
+        for (idx, interaction) in interactions.iter().enumerate() {
+            let timestamp = parse_json_timestamp(&interaction["timestamp"]);
+            if let Some(ts) = timestamp {
+                timestamps.push(ts);
+            }
+            let timestamp_str = timestamp.map(|ts| ts.to_rfc3339());
+
+            let human = &interaction["humanMessage"];
+            let assistant = &interaction["assistantMessage"];
+
+            for message in [human, assistant] {
+                if let Some(text) = message["text"].as_str() {
+                    let secret_findings = SecretScanner::scan(text);
+                    if !secret_findings.is_empty() {
+                        warnings.push(ParseWarning {
+                            severity: WarningSeverity::Security,
+                            message: format!(
+                                "Potential secrets detected: {}",
+                                secret_findings
+                                    .iter()
+                                    .map(|f| f.kind.as_str())
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            ),
+                            context: Some(format!("interaction {}", idx)),
+                        });
+                    }
+                }
+            }
+
+            // Context files Cody attached to the prompt
+            if let Some(context_files) = human["contextFiles"].as_array() {
+                for file in context_files {
+                    if let Some(p) = context_file_path(file) {
+                        files_touched.push(p);
+                    }
+                }
+            }
+
+            if let Some(text) = human["text"].as_str() {
+                trace.add_message(TraceMessage::User {
+                    text: text.to_string(),
+                    timestamp: timestamp_str.clone(),
+                });
+            }
+
+            if let Some(text) = assistant["text"].as_str() {
+                if model.is_none() {
+                    model = assistant["model"]
+                        .as_str()
+                        .or_else(|| human["model"].as_str())
+                        .map(String::from);
+                }
+                trace.add_message(TraceMessage::Assistant {
+                    text: text.to_string(),
+                    timestamp: timestamp_str,
+                });
+            }
+        }
+
+        files_touched.sort();
+        files_touched.dedup();
+
+        let started_at = timestamps.iter().min().copied();
+        let ended_at = timestamps
+            .iter()
+            .max()
+            .copied()
+            .or_else(|| parse_json_timestamp(&transcript["lastInteractionTimestamp"]));
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: "cody".to_string(),
+                session_id,
+                conversation_id,
+                model,
+            },
+            started_at,
+            ended_at,
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+/// Walk the supported file shapes and collect every transcript object
+fn collect_transcripts<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) if map.contains_key("interactions") => out.push(value),
+        Value::Object(map) => {
+            for nested in map.values() {
+                collect_transcripts(nested, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_transcripts(item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Context file URIs are either plain strings or VS Code `Uri` objects
+fn context_file_path(file: &Value) -> Option<String> {
+    let uri = &file["uri"];
+    uri["fsPath"]
+        .as_str()
+        .or_else(|| uri["path"].as_str())
+        .or_else(|| uri.as_str().map(|s| s.strip_prefix("file://").unwrap_or(s)))
+        .or_else(|| file["fileName"].as_str())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::SessionParser;
+
+    #[test]
+    fn test_can_parse_cody_files() {
+        let parser = CodyParser;
+
+        let global_path = Path::new(
+            "/home/user/.config/Code/User/globalStorage/sourcegraph.cody-ai/chat-history.json",
+        );
+        assert!(parser.can_parse(global_path));
+
+        let workspace_path = Path::new(
+            "/home/user/.config/Code/User/workspaceStorage/abc123/sourcegraph.cody-ai/history.json",
+        );
+        assert!(parser.can_parse(workspace_path));
+
+        let other_path = Path::new("/some/other/path.json");
+        assert!(!parser.can_parse(other_path));
+    }
+
+    #[test]
+    fn test_collect_transcripts_picks_latest_from_history_map() {
+        let json = serde_json::json!({
+            "https://sourcegraph.com-user": {
+                "chat": {
+                    "old": {"id": "old", "lastInteractionTimestamp": "2024-01-01T00:00:00Z", "interactions": []},
+                    "new": {"id": "new", "lastInteractionTimestamp": "2024-06-01T00:00:00Z", "interactions": []}
+                }
+            }
+        });
+
+        let mut transcripts = Vec::new();
+        collect_transcripts(&json, &mut transcripts);
+        assert_eq!(transcripts.len(), 2);
+
+        let latest = transcripts
+            .into_iter()
+            .max_by_key(|t| parse_json_timestamp(&t["lastInteractionTimestamp"]))
+            .unwrap();
+        assert_eq!(latest["id"], "new");
+    }
+
+    #[test]
+    fn test_context_file_path_variants() {
+        let uri_obj = serde_json::json!({"uri": {"fsPath": "/repo/src/lib.rs"}});
+        let uri_str = serde_json::json!({"uri": "file:///repo/src/main.rs"});
+        assert_eq!(
+            context_file_path(&uri_obj),
+            Some("/repo/src/lib.rs".to_string())
+        );
+        assert_eq!(
+            context_file_path(&uri_str),
+            Some("/repo/src/main.rs".to_string())
+        );
+    }
+}
@@ -1,14 +1,20 @@
 //! Tauri commands for session import
 
 use super::{
-    parser::{ParseError, ParseResult, ParsedSession, WarningSeverity},
+    format_version::ParserCapabilities,
+    journal,
+    parser::{ParseError, ParseResult, ParsedSession, SessionParser, WarningSeverity},
+    quarantine::{self, QuarantinedImport},
     redactor::{redact_text, redact_value, RedactionSummary},
     ParserRegistry,
 };
 use crate::DbState;
 use serde_json::Value;
 use sqlx::FromRow;
-use tauri::State;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{Emitter, State};
 
 /// Result of importing a single session
 #[derive(Debug, Clone, serde::Serialize)]
@@ -33,6 +39,11 @@ pub struct BatchImportResult {
     pub total: usize,
     pub succeeded: Vec<ImportSuccess>,
     pub failed: Vec<ImportFailure>,
+    /// Whether the batch was cancelled before every file was processed
+    pub cancelled: bool,
+    pub workers: Vec<ImportWorkerStatus>,
+    /// Set instead of writing anything when the import ran as a dry run
+    pub preview: Option<ImportPreviewReport>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -113,10 +124,16 @@ pub async fn get_recent_sessions(
                s.redaction_count
         FROM sessions s
         LEFT JOIN session_links l
-          ON l.repo_id = s.repo_id AND l.session_id = s.id
-        WHERE s.repo_id = ?
+          ON l.id = (
+            SELECT x.id FROM session_links x
+            WHERE x.repo_id = ?1 AND x.session_id = s.id
+            ORDER BY x.confidence DESC, x.id
+            LIMIT 1
+          )
+        WHERE s.repo_id = ?1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = ?1)
         ORDER BY s.imported_at DESC
-        LIMIT ?
+        LIMIT ?2
         "#,
     )
     .bind(repo_id)
@@ -211,39 +228,339 @@ pub async fn get_recent_sessions(
     Ok(payloads)
 }
 
+/// Cancellation flags for in-flight batch imports, keyed by caller-supplied token
+static ACTIVE_IMPORTS: Mutex<BTreeMap<String, Arc<AtomicBool>>> = Mutex::new(BTreeMap::new());
+
+/// Progress of a batch import, emitted as `import-progress` after each file
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportProgress {
+    pub cancel_token: Option<String>,
+    /// Parse worker that handled the file
+    pub worker: usize,
+    pub path: String,
+    /// Files processed so far (including this one)
+    pub completed: usize,
+    pub total: usize,
+    /// "succeeded" | "failed"
+    pub status: String,
+}
+
 /// Import multiple session files
 ///
 /// This command handles partial failures - successful imports are returned
 /// even if some files fail. This is important for UX: we don't want one
 /// corrupt file to prevent importing 50 valid sessions.
+///
+/// Emits `import-progress` after each file. When `cancel_token` is given, the
+/// import can be stopped with `cancel_session_import`; sessions imported before
+/// the cancellation are kept.
+///
+/// With `dry_run`, nothing is written; the result's `preview` lists what would
+/// be imported.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn import_session_files(
+    app_handle: tauri::AppHandle,
     db: State<'_, DbState>,
     repo_id: i64,
     file_paths: Vec<String>,
+    cancel_token: Option<String>,
+    dry_run: Option<bool>,
 ) -> Result<BatchImportResult, String> {
-    let registry = ParserRegistry::new();
+    if dry_run.unwrap_or(false) {
+        let total = file_paths.len();
+        let preview = preview_session_files(&db.0, repo_id, file_paths, true).await;
+        return Ok(BatchImportResult {
+            total,
+            succeeded: Vec::new(),
+            failed: Vec::new(),
+            cancelled: false,
+            workers: Vec::new(),
+            preview: Some(preview),
+        });
+    }
+
+    let batch_id = journal::plan_batch(&db.0, repo_id, journal::KIND_BATCH, &file_paths)
+        .await
+        .map_err(|e| format!("Failed to journal import batch: {e}"))?;
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Some(token) = &cancel_token {
+        ACTIVE_IMPORTS
+            .lock()
+            .map_err(|e| e.to_string())?
+            .insert(token.clone(), cancel.clone());
+    }
+
+    let result = import_session_files_inner(
+        &db.0,
+        repo_id,
+        file_paths,
+        &cancel,
+        Some(&batch_id),
+        |mut progress| {
+            progress.cancel_token = cancel_token.clone();
+            if let Err(e) = app_handle.emit("import-progress", progress) {
+                eprintln!("Failed to emit import-progress: {}", e);
+            }
+        },
+    )
+    .await;
+
+    if let Some(token) = &cancel_token {
+        if let Ok(mut active) = ACTIVE_IMPORTS.lock() {
+            active.remove(token);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Request cancellation of a running batch import
+///
+/// The file currently being imported finishes; remaining files are skipped.
+/// Returns false if no import with this token is running.
+#[tauri::command(rename_all = "camelCase")]
+pub fn cancel_session_import(cancel_token: String) -> Result<bool, String> {
+    let active = ACTIVE_IMPORTS.lock().map_err(|e| e.to_string())?;
+    match active.get(&cancel_token) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::SeqCst);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Upper bound on parse workers for a batch import
+const MAX_IMPORT_WORKERS: usize = 8;
+
+/// Parsed files written per transaction
+const IMPORT_WRITE_BATCH: usize = 64;
+
+/// Per-worker counters for a batch import
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportWorkerStatus {
+    pub worker: usize,
+    /// Files this worker parsed that were written (or failed)
+    pub processed: usize,
+    pub failed: usize,
+}
+
+/// A file parsed by a worker, waiting for the writer
+struct ParsedFile {
+    worker: usize,
+    path: String,
+    result: ParseResult<ParsedSession>,
+}
+
+fn import_worker_count(total: usize) -> usize {
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    available.clamp(1, MAX_IMPORT_WORKERS).min(total.max(1))
+}
+
+/// Parse files on a bounded pool of blocking workers; write from this task only
+///
+/// SQLite allows one writer, so parsing (the slow part) fans out while results
+/// are funneled through a channel and committed in batched transactions. With a
+/// `journal_batch`, each file's journal entry is removed in the transaction that
+/// writes it and the rest of the batch is cleared when the import ends.
+async fn import_session_files_inner(
+    pool: &sqlx::SqlitePool,
+    repo_id: i64,
+    file_paths: Vec<String>,
+    cancel: &Arc<AtomicBool>,
+    journal_batch: Option<&str>,
+    mut on_progress: impl FnMut(ImportProgress),
+) -> BatchImportResult {
+    let total = file_paths.len();
+    let worker_count = import_worker_count(total);
+    let registry = Arc::new(ParserRegistry::new());
+    let queue = Arc::new(Mutex::new(file_paths.into_iter().collect::<VecDeque<_>>()));
+    let (sender, mut receiver) = tokio::sync::mpsc::channel::<ParsedFile>(worker_count * 2);
+
+    let mut handles = Vec::with_capacity(worker_count);
+    for worker in 0..worker_count {
+        let registry = registry.clone();
+        let queue = queue.clone();
+        let cancel = cancel.clone();
+        let sender = sender.clone();
+        handles.push(tokio::task::spawn_blocking(move || loop {
+            if cancel.load(Ordering::SeqCst) {
+                break;
+            }
+            let Some(path) = queue.lock().ok().and_then(|mut q| q.pop_front()) else {
+                break;
+            };
+            let result = registry.parse(std::path::Path::new(&path));
+            // The writer hung up (cancelled); stop parsing.
+            if sender
+                .blocking_send(ParsedFile {
+                    worker,
+                    path,
+                    result,
+                })
+                .is_err()
+            {
+                break;
+            }
+        }));
+    }
+    drop(sender);
+
+    let mut workers: Vec<ImportWorkerStatus> = (0..worker_count)
+        .map(|worker| ImportWorkerStatus {
+            worker,
+            ..Default::default()
+        })
+        .collect();
     let mut succeeded = Vec::new();
     let mut failed = Vec::new();
-    let total = file_paths.len();
 
-    for path_str in file_paths {
-        let path = std::path::Path::new(&path_str);
+    while let Some(first) = receiver.recv().await {
+        if cancel.load(Ordering::SeqCst) {
+            break;
+        }
+        let mut batch = vec![first];
+        while batch.len() < IMPORT_WRITE_BATCH {
+            match receiver.try_recv() {
+                Ok(next) => batch.push(next),
+                Err(_) => break,
+            }
+        }
+
+        for (worker, outcome) in write_import_batch(pool, repo_id, journal_batch, batch).await {
+            workers[worker].processed += 1;
+            let (path, status) = match outcome {
+                Ok(success) => {
+                    let path = success.path.clone();
+                    succeeded.push(success);
+                    (path, "succeeded")
+                }
+                Err(failure) => {
+                    workers[worker].failed += 1;
+                    let path = failure.path.clone();
+                    failed.push(failure);
+                    (path, "failed")
+                }
+            };
+
+            on_progress(ImportProgress {
+                cancel_token: None,
+                worker,
+                path,
+                completed: succeeded.len() + failed.len(),
+                total,
+                status: status.to_string(),
+            });
+        }
+    }
+
+    // Parsed-but-unwritten files are dropped on cancellation.
+    drop(receiver);
+    for handle in handles {
+        let _ = handle.await;
+    }
+
+    // Finished or cancelled: nothing of this batch should be resumed.
+    if let Some(batch_id) = journal_batch {
+        if let Err(e) = journal::clear_batch(pool, batch_id).await {
+            eprintln!("Narrative: failed to clear import journal {batch_id}: {e}");
+        }
+    }
+
+    let cancelled = succeeded.len() + failed.len() < total && cancel.load(Ordering::SeqCst);
+    BatchImportResult {
+        total,
+        succeeded,
+        failed,
+        cancelled,
+        workers,
+        preview: None,
+    }
+}
+
+/// Write a batch of parsed files in one transaction
+///
+/// If the commit fails, every file in the batch is reported as a retryable failure.
+async fn write_import_batch(
+    pool: &sqlx::SqlitePool,
+    repo_id: i64,
+    journal_batch: Option<&str>,
+    batch: Vec<ParsedFile>,
+) -> Vec<(usize, Result<ImportSuccess, ImportFailure>)> {
+    let fail_all = |batch: Vec<(usize, String)>, error: String| {
+        batch
+            .into_iter()
+            .map(|(worker, path)| {
+                (
+                    worker,
+                    Err(ImportFailure {
+                        path,
+                        error: error.clone(),
+                        retryable: true,
+                    }),
+                )
+            })
+            .collect()
+    };
+
+    let mut tx = match pool.begin().await {
+        Ok(tx) => tx,
+        Err(e) => {
+            let paths = batch.into_iter().map(|f| (f.worker, f.path)).collect();
+            return fail_all(paths, format!("failed to begin transaction: {e}"));
+        }
+    };
+
+    let mut outcomes = Vec::with_capacity(batch.len());
+    for file in batch {
+        if let Some(batch_id) = journal_batch {
+            if let Err(e) = journal::complete_file(&mut *tx, batch_id, &file.path).await {
+                eprintln!("Narrative: failed to update import journal: {e}");
+            }
+        }
+        let outcome = store_parsed_file(&mut *tx, repo_id, file.path, file.result).await;
+        outcomes.push((file.worker, outcome));
+    }
+
+    if let Err(e) = tx.commit().await {
+        let paths = outcomes
+            .into_iter()
+            .map(|(worker, outcome)| match outcome {
+                Ok(success) => (worker, success.path),
+                Err(failure) => (worker, failure.path),
+            })
+            .collect();
+        return fail_all(paths, format!("failed to commit import batch: {e}"));
+    }
+
+    outcomes
+}
 
-        match registry.parse(path) {
-            ParseResult::Success(session) => match store_session(&db.0, repo_id, &session).await {
+async fn store_parsed_file(
+    conn: &mut sqlx::SqliteConnection,
+    repo_id: i64,
+    path_str: String,
+    result: ParseResult<ParsedSession>,
+) -> Result<ImportSuccess, ImportFailure> {
+    match result {
+        ParseResult::Success(mut session) => {
+            let trim = super::trimming::trim_session(&mut session, 0);
+            match store_session(conn, repo_id, &session, trim.as_ref()).await {
                 Ok(id) => {
-                    log_import(&db.0, repo_id, &path_str, Some(&id), "success", None, None).await;
-                    succeeded.push(ImportSuccess {
+                    log_import(conn, repo_id, &path_str, Some(&id), "success", None, None).await;
+                    Ok(ImportSuccess {
                         path: path_str,
                         session_id: id,
                         warnings: vec![],
-                    });
+                    })
                 }
                 Err(e) => {
                     let error_msg = e.to_string();
                     log_import(
-                        &db.0,
+                        conn,
                         repo_id,
                         &path_str,
                         None,
@@ -252,212 +569,536 @@ pub async fn import_session_files(
                         Some(&error_msg),
                     )
                     .await;
-                    failed.push(ImportFailure {
+                    Err(ImportFailure {
                         path: path_str,
                         error: error_msg,
                         retryable: true,
-                    });
+                    })
                 }
-            },
-            ParseResult::Partial(session, warnings) => {
-                // Check if any warnings are security-related
-                let has_security = warnings
+            }
+        }
+        ParseResult::Partial(mut session, warnings) => {
+            // Check if any warnings are security-related
+            let has_security = warnings
+                .iter()
+                .any(|w| matches!(w.severity, WarningSeverity::Security));
+
+            if has_security {
+                // Security warnings require user confirmation
+                let warning_msgs: Vec<String> = warnings
                     .iter()
-                    .any(|w| matches!(w.severity, WarningSeverity::Security));
+                    .filter(|w| matches!(w.severity, WarningSeverity::Security))
+                    .map(|w| w.message.clone())
+                    .collect();
+
+                let error_msg = format!(
+                    "Security warnings detected: {}. User confirmation required.",
+                    warning_msgs.join("; ")
+                );
+
+                // Hold the file for review instead of dropping it.
+                if let Err(e) = quarantine::quarantine_file(
+                    conn,
+                    repo_id,
+                    &path_str,
+                    &session.origin.tool,
+                    &warnings,
+                )
+                .await
+                {
+                    eprintln!("Narrative: failed to quarantine {}: {}", path_str, e);
+                }
+
+                log_import(
+                    conn,
+                    repo_id,
+                    &path_str,
+                    None,
+                    "failed",
+                    Some(&warning_msgs.join("\n")),
+                    Some(&error_msg),
+                )
+                .await;
+
+                return Err(ImportFailure {
+                    path: path_str,
+                    error: error_msg,
+                    retryable: true, // Can retry after user confirmation
+                });
+            }
 
-                if has_security {
-                    // Security warnings require user confirmation
+            // Non-security warnings: store with warnings logged
+            let trim = super::trimming::trim_session(&mut session, 0);
+            match store_session(conn, repo_id, &session, trim.as_ref()).await {
+                Ok(id) => {
                     let warning_msgs: Vec<String> = warnings
                         .iter()
-                        .filter(|w| matches!(w.severity, WarningSeverity::Security))
-                        .map(|w| w.message.clone())
+                        .map(|w| {
+                            format!(
+                                "[{}] {}",
+                                match w.severity {
+                                    WarningSeverity::Info => "INFO",
+                                    WarningSeverity::Warning => "WARN",
+                                    WarningSeverity::Security => "SEC",
+                                },
+                                w.message
+                            )
+                        })
                         .collect();
 
-                    let error_msg = format!(
-                        "Security warnings detected: {}. User confirmation required.",
-                        warning_msgs.join("; ")
-                    );
+                    log_import(
+                        conn,
+                        repo_id,
+                        &path_str,
+                        Some(id.as_str()),
+                        "partial",
+                        Some(&warning_msgs.join("\n")),
+                        None,
+                    )
+                    .await;
 
+                    Ok(ImportSuccess {
+                        path: path_str,
+                        session_id: id,
+                        warnings: warning_msgs,
+                    })
+                }
+                Err(e) => {
+                    let error_msg = e.to_string();
                     log_import(
-                        &db.0,
+                        conn,
                         repo_id,
                         &path_str,
                         None,
                         "failed",
-                        Some(&warning_msgs.join("\n")),
+                        None,
                         Some(&error_msg),
                     )
                     .await;
-
-                    failed.push(ImportFailure {
+                    Err(ImportFailure {
                         path: path_str,
                         error: error_msg,
-                        retryable: true, // Can retry after user confirmation
-                    });
-                    continue;
-                }
-
-                // Non-security warnings: store with warnings logged
-                match store_session(&db.0, repo_id, &session).await {
-                    Ok(id) => {
-                        let warning_msgs: Vec<String> = warnings
-                            .iter()
-                            .map(|w| {
-                                format!(
-                                    "[{}] {}",
-                                    match w.severity {
-                                        WarningSeverity::Info => "INFO",
-                                        WarningSeverity::Warning => "WARN",
-                                        WarningSeverity::Security => "SEC",
-                                    },
-                                    w.message
-                                )
-                            })
-                            .collect();
-
-                        log_import(
-                            &db.0,
-                            repo_id,
-                            &path_str,
-                            Some(id.as_str()),
-                            "partial",
-                            Some(&warning_msgs.join("\n")),
-                            None,
-                        )
-                        .await;
-
-                        succeeded.push(ImportSuccess {
-                            path: path_str,
-                            session_id: id,
-                            warnings: warning_msgs,
-                        });
-                    }
-                    Err(e) => {
-                        let error_msg = e.to_string();
-                        log_import(
-                            &db.0,
-                            repo_id,
-                            &path_str,
-                            None,
-                            "failed",
-                            None,
-                            Some(&error_msg),
-                        )
-                        .await;
-                        failed.push(ImportFailure {
-                            path: path_str,
-                            error: error_msg,
-                            retryable: true,
-                        });
-                    }
+                        retryable: true,
+                    })
                 }
             }
-            ParseResult::Failure(e) => {
-                let error_msg = e.to_string();
-                let retryable = matches!(e, ParseError::Io(_));
+        }
+        ParseResult::Failure(e) => {
+            let error_msg = e.to_string();
+            let retryable = matches!(e, ParseError::Io(_));
 
-                log_import(
-                    &db.0,
-                    repo_id,
-                    &path_str,
-                    None,
-                    "failed",
-                    None,
-                    Some(&error_msg),
-                )
-                .await;
+            log_import(
+                conn,
+                repo_id,
+                &path_str,
+                None,
+                "failed",
+                None,
+                Some(&error_msg),
+            )
+            .await;
 
-                failed.push(ImportFailure {
-                    path: path_str,
-                    error: error_msg,
-                    retryable,
-                });
-            }
+            Err(ImportFailure {
+                path: path_str,
+                error: error_msg,
+                retryable,
+            })
         }
     }
+}
 
-    Ok(BatchImportResult {
-        total,
-        succeeded,
-        failed,
-    })
+/// List files held back by security warnings, with redacted excerpts for review
+#[tauri::command(rename_all = "camelCase")]
+pub async fn list_quarantined_imports(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<Vec<QuarantinedImport>, String> {
+    quarantine::list_quarantined(&db.0, repo_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Scan for available session files
+/// Import a quarantined file after the user reviewed it
 ///
-/// Searches standard locations for AI session files without importing them.
+/// The session is redacted like any auto-imported session. If the file changed
+/// since it was quarantined, the import is refused so the new content can be
+/// reviewed first.
 #[tauri::command(rename_all = "camelCase")]
-pub async fn scan_for_session_files() -> Result<Vec<ScannedSession>, String> {
-    let mut results = Vec::new();
+pub async fn confirm_quarantined_import(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    quarantine_id: i64,
+) -> Result<AutoImportResult, String> {
+    confirm_quarantined_import_inner(&db.0, repo_id, quarantine_id).await
+}
 
-    // Scan Claude Code directories
-    if let Some(home) = dirs::home_dir() {
-        let claude_dir = home.join(".claude/projects");
-        if claude_dir.exists() {
-            scan_claude_directory(&claude_dir, &mut results).map_err(|e| e.to_string())?;
-        }
+async fn confirm_quarantined_import_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    quarantine_id: i64,
+) -> Result<AutoImportResult, String> {
+    let entry = quarantine::get_quarantined(db, repo_id, quarantine_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Quarantined import {} not found", quarantine_id))?;
+
+    let path = std::path::Path::new(&entry.file_path);
+    if quarantine::file_fingerprint(path) != entry.file_sha256 {
+        return Err(format!(
+            "{} changed since it was quarantined; re-import it to review the new content",
+            entry.file_path
+        ));
     }
 
-    Ok(results)
+    let session = match ParserRegistry::new().parse(path) {
+        ParseResult::Success(parsed) | ParseResult::Partial(parsed, _) => parsed,
+        ParseResult::Failure(e) => return Err(e.to_string()),
+    };
+
+    let result = ingest_parsed_session(db, repo_id, session, &entry.file_path).await?;
+    quarantine::remove_quarantined(db, repo_id, quarantine_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result)
 }
 
-/// Import a single session file (convenience wrapper)
+/// Drop a quarantined file without importing it
 #[tauri::command(rename_all = "camelCase")]
-pub async fn import_session_file(
+pub async fn discard_quarantined_import(
     db: State<'_, DbState>,
     repo_id: i64,
-    file_path: String,
-) -> Result<BatchImportResult, String> {
-    import_session_files(db, repo_id, vec![file_path]).await
+    quarantine_id: i64,
+) -> Result<bool, String> {
+    quarantine::remove_quarantined(&db.0, repo_id, quarantine_id)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Auto-import a session file (redact, dedupe, store, link).
-#[tauri::command(rename_all = "camelCase")]
-pub async fn auto_import_session_file(
-    db: State<'_, DbState>,
-    repo_id: i64,
-    file_path: String,
-) -> Result<AutoImportResult, String> {
-    auto_import_session_file_inner(&db.0, repo_id, file_path).await
+/// What a dry-run import found for one file
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    pub path: String,
+    pub tool: Option<String>,
+    pub session_id: Option<String>,
+    /// "new" | "duplicate" | "blocked" (security warnings) | "failed"
+    pub status: String,
+    pub message_count: usize,
+    pub redaction_count: i64,
+    pub security_warnings: Vec<String>,
+    pub link_candidate: Option<LinkCandidate>,
+    pub error: Option<String>,
 }
 
-async fn auto_import_session_file_inner(
+/// Commit a previewed session would be linked to
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCandidate {
+    pub commit_sha: String,
+    pub confidence: f64,
+    pub needs_review: bool,
+}
+
+/// Summary of a dry-run import
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreviewReport {
+    pub total: i64,
+    pub would_import: i64,
+    pub duplicates: i64,
+    pub blocked: i64,
+    pub failed: i64,
+    pub files: Vec<ImportPreview>,
+}
+
+/// Parse, redact, dedupe-check, and link files without writing anything
+///
+/// `block_on_security` mirrors the manual batch import, which refuses files
+/// with security warnings; auto-import redacts and stores them instead.
+async fn preview_session_files(
     db: &sqlx::SqlitePool,
     repo_id: i64,
-    file_path: String,
-) -> Result<AutoImportResult, String> {
+    file_paths: Vec<String>,
+    block_on_security: bool,
+) -> ImportPreviewReport {
     let registry = ParserRegistry::new();
-    let path = std::path::Path::new(&file_path);
+    let mut report = ImportPreviewReport::default();
+
+    for path in file_paths {
+        let preview = preview_session_file(db, &registry, repo_id, path, block_on_security).await;
+        report.total += 1;
+        match preview.status.as_str() {
+            "new" => report.would_import += 1,
+            "duplicate" => report.duplicates += 1,
+            "blocked" => report.blocked += 1,
+            _ => report.failed += 1,
+        }
+        report.files.push(preview);
+    }
 
-    let session = match registry.parse(path) {
-        ParseResult::Success(parsed) => parsed,
-        ParseResult::Partial(parsed, _warnings) => parsed,
+    report
+}
+
+async fn preview_session_file(
+    db: &sqlx::SqlitePool,
+    registry: &ParserRegistry,
+    repo_id: i64,
+    path: String,
+    block_on_security: bool,
+) -> ImportPreview {
+    let (mut session, warnings) = match registry.parse(std::path::Path::new(&path)) {
+        ParseResult::Success(session) => (session, Vec::new()),
+        ParseResult::Partial(session, warnings) => (session, warnings),
         ParseResult::Failure(e) => {
-            log_auto_ingest(
-                db,
-                repo_id,
-                "unknown",
-                Some(&file_path),
-                None,
-                "failed",
-                0,
-                Some(&e.to_string()),
-            )
-            .await;
-            return Err(e.to_string());
+            return ImportPreview {
+                path,
+                tool: None,
+                session_id: None,
+                status: "failed".to_string(),
+                message_count: 0,
+                redaction_count: 0,
+                security_warnings: Vec::new(),
+                link_candidate: None,
+                error: Some(e.to_string()),
+            };
         }
     };
 
+    let security_warnings: Vec<String> = warnings
+        .iter()
+        .filter(|w| matches!(w.severity, WarningSeverity::Security))
+        .map(|w| w.message.clone())
+        .collect();
+
+    super::trimming::trim_session(&mut session, 0);
     let (redacted_session, redaction) = redact_session(session);
     let dedupe_key = build_dedupe_key(&redacted_session);
+    let session_id = generate_session_id(&redacted_session.origin);
 
-    let session_id = match store_session_with_meta(
-        db,
-        repo_id,
+    let existing: Result<i64, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM sessions s
+        WHERE (s.id = ? AND EXISTS (
+                SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = ?))
+           OR (s.repo_id = ? AND s.dedupe_key = ?)
+        "#,
+    )
+    .bind(&session_id)
+    .bind(repo_id)
+    .bind(repo_id)
+    .bind(&dedupe_key)
+    .fetch_one(db)
+    .await;
+
+    let (status, error) = match existing {
+        Err(e) => ("failed", Some(e.to_string())),
+        Ok(count) if count > 0 => ("duplicate", None),
+        Ok(_) if block_on_security && !security_warnings.is_empty() => ("blocked", None),
+        Ok(_) => ("new", None),
+    };
+
+    let link_candidate = find_link_candidates(db, repo_id, &redacted_session, &session_id)
+        .await
+        .ok()
+        .and_then(|result| result.ok())
+        .and_then(|links| links.into_iter().next())
+        .map(|link| LinkCandidate {
+            commit_sha: link.commit_sha,
+            confidence: link.confidence,
+            needs_review: link.needs_review,
+        });
+
+    ImportPreview {
+        path,
+        tool: Some(redacted_session.origin.tool.clone()),
+        session_id: Some(session_id),
+        status: status.to_string(),
+        message_count: redacted_session.message_count(),
+        redaction_count: redaction.total as i64,
+        security_warnings,
+        link_candidate,
+        error,
+    }
+}
+
+/// Upper bound on files considered under each scan root
+const MAX_SCAN_PER_ROOT: usize = 5000;
+
+/// Scan for available session files
+///
+/// Walks the default session locations of every registered parser without
+/// importing anything. Each file is labelled with the tool of the parser that
+/// would import it.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn scan_for_session_files() -> Result<Vec<ScannedSession>, String> {
+    tokio::task::spawn_blocking(|| {
+        let registry = ParserRegistry::new();
+        scan_session_roots(&registry, &registry.default_roots())
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+fn scan_session_roots(
+    registry: &ParserRegistry,
+    roots: &[std::path::PathBuf],
+) -> Vec<ScannedSession> {
+    let detected_at = chrono::Utc::now().to_rfc3339();
+    let mut seen = std::collections::HashSet::new();
+    let mut results = Vec::new();
+
+    for root in roots {
+        let files = collect_recent_files(
+            &[root.to_string_lossy().to_string()],
+            |p| registry.find_parser(&uncompressed_name(p)).is_some(),
+            MAX_SCAN_PER_ROOT,
+        );
+        for (path, _) in files {
+            // Roots may overlap; report each file once.
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            let logical = uncompressed_name(&path);
+            let Some(parser) = registry.find_parser(&logical) else {
+                continue;
+            };
+            results.push(ScannedSession {
+                path: path.to_string_lossy().to_string(),
+                tool: parser.tool_id(&logical).to_string(),
+                detected_at: detected_at.clone(),
+            });
+        }
+    }
+
+    results
+}
+
+/// Report which session formats each parser understands
+///
+/// Lists the schema versions a parser has been tested against, so the UI can
+/// explain why a newer tool release imports with warnings.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_parser_capabilities() -> Result<Vec<ParserCapabilities>, String> {
+    Ok(ParserRegistry::new().capabilities())
+}
+
+/// Import a single session file (convenience wrapper)
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_session_file(
+    app_handle: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    file_path: String,
+) -> Result<BatchImportResult, String> {
+    import_session_files(app_handle, db, repo_id, vec![file_path], None, None).await
+}
+
+/// Auto-import a session file (redact, dedupe, store, link).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn auto_import_session_file(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    file_path: String,
+) -> Result<AutoImportResult, String> {
+    auto_import_session_file_inner(&db.0, repo_id, file_path).await
+}
+
+async fn auto_import_session_file_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    file_path: String,
+) -> Result<AutoImportResult, String> {
+    let registry = ParserRegistry::new();
+    let path = std::path::Path::new(&file_path);
+
+    let session = match registry.parse(path) {
+        ParseResult::Success(parsed) => parsed,
+        ParseResult::Partial(parsed, _warnings) => parsed,
+        ParseResult::Failure(e) => {
+            log_auto_ingest(
+                db,
+                repo_id,
+                "unknown",
+                Some(&file_path),
+                None,
+                "failed",
+                0,
+                Some(&e.to_string()),
+            )
+            .await;
+            return Err(e.to_string());
+        }
+    };
+
+    ingest_parsed_session(db, repo_id, session, &file_path).await
+}
+
+/// Source path recorded for sessions imported from pasted text
+const PASTED_SOURCE: &str = "<pasted>";
+
+/// Import a transcript pasted from the clipboard or piped from stdin.
+///
+/// The text goes through the lenient transcript parser and then the same
+/// redact/dedupe/store/link pipeline as auto-imported files. `tool_hint` names
+/// the tool the transcript came from (defaults to "pasted").
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_session_from_text(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    text: String,
+    tool_hint: Option<String>,
+) -> Result<AutoImportResult, String> {
+    import_session_from_text_inner(&db.0, repo_id, &text, tool_hint.as_deref()).await
+}
+
+async fn import_session_from_text_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    text: &str,
+    tool_hint: Option<&str>,
+) -> Result<AutoImportResult, String> {
+    let session = match super::text_parser::parse_transcript_text(text, tool_hint) {
+        ParseResult::Success(parsed) => parsed,
+        ParseResult::Partial(parsed, _warnings) => parsed,
+        ParseResult::Failure(e) => {
+            log_auto_ingest(
+                db,
+                repo_id,
+                &super::text_parser::normalize_tool_hint(tool_hint),
+                Some(PASTED_SOURCE),
+                None,
+                "failed",
+                0,
+                Some(&e.to_string()),
+            )
+            .await;
+            return Err(e.to_string());
+        }
+    };
+
+    ingest_parsed_session(db, repo_id, session, PASTED_SOURCE).await
+}
+
+/// Redact, dedupe, store, and link an already-parsed session.
+async fn ingest_parsed_session(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    mut session: ParsedSession,
+    file_path: &str,
+) -> Result<AutoImportResult, String> {
+    let trim = super::trimming::trim_session(&mut session, 0);
+    let (redacted_session, redaction) = redact_session(session);
+    let dedupe_key = build_dedupe_key(&redacted_session);
+
+    let session_id = match store_session_with_meta(
+        db,
+        repo_id,
         &redacted_session,
-        Some(&file_path),
+        Some(file_path),
         Some(&dedupe_key),
         &redaction,
+        trim.as_ref(),
     )
     .await
     {
@@ -467,7 +1108,7 @@ async fn auto_import_session_file_inner(
                 db,
                 repo_id,
                 &redacted_session.origin.tool,
-                Some(&file_path),
+                Some(file_path),
                 Some(&redacted_session.origin.session_id),
                 "skipped",
                 redaction.total as i64,
@@ -484,7 +1125,7 @@ async fn auto_import_session_file_inner(
                 db,
                 repo_id,
                 &redacted_session.origin.tool,
-                Some(&file_path),
+                Some(file_path),
                 Some(&redacted_session.origin.session_id),
                 "failed",
                 redaction.total as i64,
@@ -500,12 +1141,23 @@ async fn auto_import_session_file_inner(
             Ok(result) => (Some(result), None),
             Err(err) => (None, Some(err)),
         };
+    if link_result.is_none() {
+        // Not committed yet: anchor to the dirty working tree until it is
+        let _ = crate::wip_anchors::anchor_recent_session(
+            db,
+            repo_id,
+            &session_id,
+            &redacted_session.files_touched,
+            redacted_session.ended_at,
+        )
+        .await;
+    }
 
     log_auto_ingest(
         db,
         repo_id,
         &redacted_session.origin.tool,
-        Some(&file_path),
+        Some(file_path),
         Some(&session_id),
         "imported",
         redaction.total as i64,
@@ -523,106 +1175,826 @@ async fn auto_import_session_file_inner(
     ))
 }
 
-#[derive(Debug, Clone, serde::Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct BackfillResult {
-    pub attempted: i64,
-    pub imported: i64,
-    pub skipped: i64,
-    pub failed: i64,
+/// Import only the lines appended to a session file since the last import.
+///
+/// Falls back to a full import for formats without line-based storage, and when
+/// the file was truncated or replaced since the recorded offset.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_session_file_incremental(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    file_path: String,
+) -> Result<AutoImportResult, String> {
+    import_session_file_incremental_inner(&db.0, repo_id, file_path).await
 }
 
-fn expand_home(raw: &str) -> std::path::PathBuf {
-    if let Some(stripped) = raw.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(stripped);
-        }
+async fn import_session_file_incremental_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    file_path: String,
+) -> Result<AutoImportResult, String> {
+    let path = std::path::Path::new(&file_path);
+    // Compressed files are rotated archives; offsets into them are meaningless.
+    if super::compression::Compression::detect(path).is_some() {
+        return auto_import_session_file_inner(db, repo_id, file_path).await;
     }
-    std::path::PathBuf::from(raw)
-}
 
-fn collect_recent_files(
-    roots: &[String],
-    predicate: impl Fn(&std::path::Path) -> bool,
-    max_scan: usize,
-) -> Vec<(std::path::PathBuf, std::time::SystemTime)> {
-    let mut out: Vec<(std::path::PathBuf, std::time::SystemTime)> = Vec::new();
+    let registry = ParserRegistry::new();
+    let Some(parser) = registry.find_parser(path) else {
+        return Err(ParseError::UnsupportedFormat.to_string());
+    };
+    let file_len = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
 
-    fn walk(
-        dir: &std::path::Path,
-        predicate: &impl Fn(&std::path::Path) -> bool,
-        out: &mut Vec<(std::path::PathBuf, std::time::SystemTime)>,
-        max_scan: usize,
-    ) {
-        if out.len() >= max_scan {
-            return;
-        }
-        let Ok(entries) = std::fs::read_dir(dir) else {
-            return;
-        };
-        for entry in entries.flatten() {
-            if out.len() >= max_scan {
-                return;
-            }
-            let path = entry.path();
-            let Ok(file_type) = entry.file_type() else {
-                continue;
-            };
-            // Skip symlinks to prevent loops and traversal outside the watched tree.
-            if file_type.is_symlink() {
-                continue;
-            }
-            if file_type.is_dir() {
-                walk(&path, predicate, out, max_scan);
-            } else if file_type.is_file() && predicate(&path) {
-                if let Ok(meta) = std::fs::metadata(&path) {
-                    if let Ok(mtime) = meta.modified() {
-                        out.push((path, mtime));
-                    }
-                }
-            }
+    let tracked: Option<(String, i64, String)> = sqlx::query_as(&format!(
+        r#"
+        SELECT o.session_id, o.byte_offset, s.tool
+        FROM session_import_offsets o
+        JOIN sessions s ON s.id = o.session_id AND {}
+        WHERE o.repo_id = ? AND o.source_path = ?
+        "#,
+        session_in_repo_sql("s", "o.repo_id")
+    ))
+    .bind(repo_id)
+    .bind(&file_path)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // A shrunken file was rotated or rewritten; start over from the beginning.
+    let tracked = tracked.filter(|(_, offset, _)| (*offset as u64) <= file_len);
+
+    let offset = tracked.as_ref().map_or(0, |(_, offset, _)| *offset as u64);
+    if let Some((session_id, _, tool)) = &tracked {
+        if offset == file_len {
+            return Ok(AutoImportResult::skipped(tool.clone(), session_id.clone()));
         }
     }
 
-    for root in roots {
-        let p = expand_home(root);
-        if p.exists() && p.is_file() {
-            if predicate(&p) {
-                if let Ok(meta) = std::fs::metadata(&p) {
-                    if let Ok(mtime) = meta.modified() {
-                        out.push((p, mtime));
-                    }
-                }
-            }
-            continue;
+    let appended = match parser.parse_appended(path, offset) {
+        Some(ParseResult::Success(appended)) => appended,
+        Some(ParseResult::Partial(appended, _warnings)) => appended,
+        Some(ParseResult::Failure(e)) => {
+            log_auto_ingest(
+                db,
+                repo_id,
+                "unknown",
+                Some(&file_path),
+                None,
+                "failed",
+                0,
+                Some(&e.to_string()),
+            )
+            .await;
+            return Err(e.to_string());
         }
-        if p.exists() && p.is_dir() {
-            walk(&p, &predicate, &mut out, max_scan);
+        // No line-based format: nothing to track, import the whole file.
+        None => return auto_import_session_file_inner(db, repo_id, file_path).await,
+    };
+
+    let (fragment, redaction) = redact_session(appended.session);
+    let stored_id = generate_session_id(&fragment.origin);
+
+    let result = match &tracked {
+        Some((session_id, _, _)) => {
+            append_to_stored_session(db, repo_id, session_id, fragment, &file_path, &redaction)
+                .await?
         }
-    }
+        None => {
+            let exists: Option<i64> = sqlx::query_scalar(&format!(
+                "SELECT 1 FROM sessions s WHERE s.id = ?1 AND {}",
+                session_in_repo_sql("s", "?2")
+            ))
+            .bind(&stored_id)
+            .bind(repo_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| e.to_string())?;
+            if exists.is_some() {
+                // Imported before offsets were tracked: the parse from offset 0 is a
+                // superset of what was stored, so replace the stored trace with it.
+                replace_stored_trace(db, repo_id, &stored_id, fragment, &file_path, &redaction, 0)
+                    .await?
+            } else {
+                ingest_parsed_session(db, repo_id, fragment, &file_path).await?
+            }
+        }
+    };
 
-    out.sort_by(|a, b| b.1.cmp(&a.1));
-    out
-}
+    let session_id = tracked
+        .as_ref()
+        .map(|(id, _, _)| id.clone())
+        .unwrap_or(stored_id);
+    sqlx::query(
+        r#"
+        INSERT INTO session_import_offsets (repo_id, source_path, session_id, byte_offset, updated_at)
+        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+        ON CONFLICT(repo_id, source_path) DO UPDATE SET
+            session_id = excluded.session_id,
+            byte_offset = excluded.byte_offset,
+            updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&file_path)
+    .bind(&session_id)
+    .bind(appended.next_offset as i64)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Import Cursor composer chats created or edited since the last import.
+///
+/// Reads `composer.database` directly (read-only) past the watermark stored for
+/// this repo. New chats go through the regular ingest pipeline; chats already
+/// stored here have their trace replaced with the edited version.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_cursor_composer_db(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    db_path: String,
+) -> Result<Vec<AutoImportResult>, String> {
+    import_cursor_composer_db_inner(&db.0, repo_id, db_path).await
+}
+
+async fn import_cursor_composer_db_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    db_path: String,
+) -> Result<Vec<AutoImportResult>, String> {
+    use super::cursor_composer_db::{read_changed_sessions, ComposerWatermark};
+
+    let since = sqlx::query_as::<_, (i64, i64)>(
+        "SELECT updated_at_ms, last_rowid FROM cursor_composer_watermarks WHERE repo_id = ? AND db_path = ?",
+    )
+    .bind(repo_id)
+    .bind(&db_path)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| e.to_string())?
+    .map(|(updated_at, rowid)| ComposerWatermark { updated_at, rowid })
+    .unwrap_or_default();
+
+    let path = std::path::PathBuf::from(&db_path);
+    let read = tokio::task::spawn_blocking(move || read_changed_sessions(&path, since))
+        .await
+        .map_err(|e| e.to_string())?;
+    let (changed, watermark) = match read {
+        Ok(read) => read,
+        Err(e) => {
+            log_auto_ingest(
+                db,
+                repo_id,
+                "cursor",
+                Some(&db_path),
+                None,
+                "failed",
+                0,
+                Some(&e.to_string()),
+            )
+            .await;
+            return Err(e.to_string());
+        }
+    };
+
+    let mut results = Vec::with_capacity(changed.len());
+    for composer in changed {
+        let session_id = generate_session_id(&composer.session.origin);
+        let exists: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT 1 FROM sessions s WHERE s.id = ?1 AND {}",
+            session_in_repo_sql("s", "?2")
+        ))
+        .bind(&session_id)
+        .bind(repo_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+        let result = if exists.is_some() {
+            let (session, redaction) = redact_session(composer.session);
+            replace_stored_trace(db, repo_id, &session_id, session, &db_path, &redaction, 0).await
+        } else {
+            ingest_parsed_session(db, repo_id, composer.session, &db_path).await
+        };
+        match result {
+            Ok(result) => results.push(result),
+            Err(err) => eprintln!(
+                "Narrative: failed to import Cursor chat {}: {err}",
+                composer.composer_id
+            ),
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO cursor_composer_watermarks (repo_id, db_path, updated_at_ms, last_rowid, checked_at)
+        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+        ON CONFLICT(repo_id, db_path) DO UPDATE SET
+            updated_at_ms = excluded.updated_at_ms,
+            last_rowid = excluded.last_rowid,
+            checked_at = excluded.checked_at
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&db_path)
+    .bind(watermark.updated_at)
+    .bind(watermark.rowid)
+    .execute(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(results)
+}
+
+/// Append newly parsed messages to a stored session, then re-project and re-link it.
+async fn append_to_stored_session(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    fragment: ParsedSession,
+    source_path: &str,
+    redaction: &RedactionSummary,
+) -> Result<AutoImportResult, String> {
+    #[allow(clippy::type_complexity)]
+    let (raw_json, files, purged_at, trimmed, fingerprints): (
+        String,
+        Option<String>,
+        Option<String>,
+        i64,
+        Option<String>,
+    ) = sqlx::query_as(&format!(
+        r#"
+        SELECT s.raw_json, s.files, s.purged_at, s.trimmed_message_count, s.edit_fingerprints
+        FROM sessions s
+        WHERE s.id = ?1 AND {}
+        "#,
+        session_in_repo_sql("s", "?2")
+    ))
+    .bind(session_id)
+    .bind(repo_id)
+    .fetch_one(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    // Purged sessions stay purged; only the offset moves forward.
+    if purged_at.is_some() || fragment.trace.messages.is_empty() {
+        return Ok(AutoImportResult::skipped(
+            fragment.origin.tool,
+            session_id.to_string(),
+        ));
+    }
+
+    let raw_json = super::trace_chunks::load_trace_json(db, session_id, raw_json)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut trace: super::parser::SessionTrace =
+        serde_json::from_str(&raw_json).map_err(|e| e.to_string())?;
+    trace.messages.extend(fragment.trace.messages);
+
+    let mut files_touched = parse_json_list(files);
+    files_touched.extend(fragment.files_touched);
+    files_touched.sort();
+    files_touched.dedup();
+    let mut edit_fingerprints = parse_json_list(fingerprints);
+    edit_fingerprints.extend(fragment.edit_fingerprints);
+    edit_fingerprints.sort();
+    edit_fingerprints.dedup();
+
+    let merged = ParsedSession {
+        trace,
+        files_touched,
+        edit_fingerprints,
+        ..fragment
+    };
+    replace_stored_trace(
+        db,
+        repo_id,
+        session_id,
+        merged,
+        source_path,
+        redaction,
+        trimmed.max(0) as usize,
+    )
+    .await
+}
+
+/// Overwrite a stored session's trace and derived fields with `session`.
+///
+/// `previously_dropped` is the number of messages already trimmed from the
+/// stored trace that `session` extends (0 when it is a complete trace).
+async fn replace_stored_trace(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    mut session: ParsedSession,
+    source_path: &str,
+    redaction: &RedactionSummary,
+    previously_dropped: usize,
+) -> Result<AutoImportResult, String> {
+    let trim = super::trimming::trim_session(&mut session, previously_dropped);
+    let dropped = trim.map_or(previously_dropped, |t| t.dropped) as i64;
+    let stored = super::trace_chunks::split_trace(&session.trace).map_err(|e| e.to_string())?;
+    let files_json =
+        serde_json::to_string(&session.files_touched).unwrap_or_else(|_| "[]".to_string());
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(&format!(
+        r#"
+        UPDATE sessions
+        SET raw_json = ?,
+            message_count = ?,
+            files = ?,
+            model = COALESCE(model, ?),
+            redaction_count = redaction_count + ?,
+            trim_strategy = CASE WHEN ? > 0 THEN COALESCE(?, trim_strategy) END,
+            trimmed_message_count = ?,
+            git_branch = COALESCE(?, git_branch),
+            edit_fingerprints = COALESCE(?, edit_fingerprints),
+            imported_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        WHERE id = ? AND {}
+        "#,
+        session_in_repo_sql("sessions", "?")
+    ))
+    .bind(&stored.inline_json)
+    .bind(session.message_count() as i32)
+    .bind(files_json)
+    .bind(&session.origin.model)
+    .bind(redaction.total as i64)
+    .bind(dropped)
+    .bind(trim.map(|t| t.strategy.as_str()))
+    .bind(dropped)
+    .bind(&session.git_branch)
+    .bind(edit_fingerprints_json(&session))
+    .bind(session_id)
+    .bind(repo_id)
+    .bind(repo_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    super::trace_chunks::write_overflow(&mut tx, session_id, &stored.overflow)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    crate::atlas::index_queue::index_session(db, repo_id, session_id).await;
+
+    let (link_result, link_error) =
+        match link_session_to_commit_internal(db, repo_id, &session, session_id).await {
+            Ok(result) => (Some(result), None),
+            Err(err) => (None, Some(err)),
+        };
+
+    log_auto_ingest(
+        db,
+        repo_id,
+        &session.origin.tool,
+        Some(source_path),
+        Some(session_id),
+        "imported",
+        redaction.total as i64,
+        link_error.as_deref(),
+    )
+    .await;
+
+    Ok(AutoImportResult::imported(
+        session.origin.tool,
+        session_id.to_string(),
+        redaction.total as i64,
+        link_result
+            .map(|result| result.needs_review)
+            .unwrap_or(false),
+    ))
+}
+
+/// Result of re-importing a stored session from its source file
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReimportResult {
+    pub session_id: String,
+    pub source_path: String,
+    pub previous_message_count: i64,
+    pub message_count: i64,
+    pub redaction_count: i64,
+}
+
+/// Re-parse a session's original file and refresh it in place.
+///
+/// Updates the trace, counts, and redaction metadata and re-projects Atlas
+/// chunks. The session id is unchanged, so commit links and line attributions
+/// that reference it are kept as they are (no re-linking).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn reimport_session(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    session_id: String,
+) -> Result<ReimportResult, String> {
+    reimport_session_inner(&db.0, repo_id, &session_id).await
+}
+
+async fn reimport_session_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+) -> Result<ReimportResult, String> {
+    // Batch imports don't record source_path on the session; fall back to the import log.
+    let row: Option<(Option<String>, i64, Option<String>, Option<String>)> =
+        sqlx::query_as(&format!(
+            r#"
+        SELECT
+            COALESCE(
+                s.source_path,
+                (SELECT l.file_path FROM session_import_log l
+                 WHERE l.session_id = s.id
+                 ORDER BY l.id DESC LIMIT 1)
+            ),
+            COALESCE(s.message_count, 0),
+            s.dedupe_key,
+            s.purged_at
+        FROM sessions s
+        WHERE s.id = ?1 AND {}
+        "#,
+            session_in_repo_sql("s", "?2")
+        ))
+        .bind(session_id)
+        .bind(repo_id)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let Some((source_path, previous_message_count, dedupe_key, purged_at)) = row else {
+        return Err(format!("Session {} not found", session_id));
+    };
+    // Re-importing would undo a retention purge.
+    if purged_at.is_some() {
+        return Err(format!(
+            "Session {} was purged by retention policy",
+            session_id
+        ));
+    }
+    let source_path =
+        source_path.ok_or_else(|| format!("Session {} has no recorded source file", session_id))?;
+    if source_path == PASTED_SOURCE {
+        return Err(format!(
+            "Session {} was imported from pasted text and has no source file",
+            session_id
+        ));
+    }
+
+    let mut session = match ParserRegistry::new().parse(std::path::Path::new(&source_path)) {
+        ParseResult::Success(parsed) | ParseResult::Partial(parsed, _) => parsed,
+        ParseResult::Failure(e) => return Err(e.to_string()),
+    };
+    if generate_session_id(&session.origin) != session_id {
+        return Err(format!(
+            "{} no longer contains session {}",
+            source_path, session_id
+        ));
+    }
+
+    let trim = super::trimming::trim_session(&mut session, 0);
+    let (session, redaction) = redact_session(session);
+    let stored = super::trace_chunks::split_trace(&session.trace).map_err(|e| e.to_string())?;
+    let files_json =
+        serde_json::to_string(&session.files_touched).unwrap_or_else(|_| "[]".to_string());
+    let redaction_types =
+        serde_json::to_string(&redaction.hits).unwrap_or_else(|_| "[]".to_string());
+    let duration_min = session.started_at.and_then(|start| {
+        session
+            .ended_at
+            .map(|end| (end - start).num_minutes() as i32)
+    });
+    // Keep content-based dedupe in step with the new trace (only for sessions that use it).
+    let dedupe_key = dedupe_key.map(|_| build_dedupe_key(&session));
+
+    let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+    sqlx::query(&format!(
+        r#"
+        UPDATE sessions
+        SET raw_json = ?,
+            message_count = ?,
+            files = ?,
+            model = COALESCE(?, model),
+            duration_min = COALESCE(?, duration_min),
+            redaction_count = ?,
+            redaction_types = ?,
+            dedupe_key = ?,
+            trim_strategy = ?,
+            trimmed_message_count = ?,
+            trace_available = 1
+        WHERE id = ? AND {}
+        "#,
+        session_in_repo_sql("sessions", "?")
+    ))
+    .bind(&stored.inline_json)
+    .bind(session.message_count() as i64)
+    .bind(files_json)
+    .bind(&session.origin.model)
+    .bind(duration_min)
+    .bind(redaction.total as i64)
+    .bind(redaction_types)
+    .bind(&dedupe_key)
+    .bind(trim.map(|t| t.strategy.as_str()))
+    .bind(trim.map_or(0, |t| t.dropped as i64))
+    .bind(session_id)
+    .bind(repo_id)
+    .bind(repo_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+    super::trace_chunks::write_overflow(&mut tx, session_id, &stored.overflow)
+        .await
+        .map_err(|e| e.to_string())?;
+    tx.commit().await.map_err(|e| e.to_string())?;
+
+    crate::atlas::index_queue::index_session(db, repo_id, session_id).await;
+
+    // The whole file has been consumed; keep incremental imports from re-appending it.
+    if let Ok(meta) = std::fs::metadata(&source_path) {
+        let _ = sqlx::query(
+            r#"
+            UPDATE session_import_offsets
+            SET byte_offset = ?, updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+            WHERE repo_id = ? AND source_path = ? AND session_id = ?
+            "#,
+        )
+        .bind(meta.len() as i64)
+        .bind(repo_id)
+        .bind(&source_path)
+        .bind(session_id)
+        .execute(db)
+        .await;
+    }
+
+    log_auto_ingest(
+        db,
+        repo_id,
+        &session.origin.tool,
+        Some(&source_path),
+        Some(session_id),
+        "imported",
+        redaction.total as i64,
+        None,
+    )
+    .await;
+
+    Ok(ReimportResult {
+        session_id: session_id.to_string(),
+        source_path,
+        previous_message_count,
+        message_count: session.message_count() as i64,
+        redaction_count: redaction.total as i64,
+    })
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackfillResult {
+    pub attempted: i64,
+    pub imported: i64,
+    pub skipped: i64,
+    pub failed: i64,
+    /// Per-file preview when run as a dry run (counts are then "would import")
+    pub preview: Option<ImportPreviewReport>,
+}
+
+fn expand_home(raw: &str) -> std::path::PathBuf {
+    if let Some(stripped) = raw.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(stripped);
+        }
+    }
+    std::path::PathBuf::from(raw)
+}
+
+/// Name a file had before rotation compressed it (`a.jsonl.gz` -> `a.jsonl`)
+fn uncompressed_name(path: &std::path::Path) -> std::path::PathBuf {
+    super::compression::logical_path(path).unwrap_or_else(|| path.to_path_buf())
+}
+
+fn collect_recent_files(
+    roots: &[String],
+    predicate: impl Fn(&std::path::Path) -> bool,
+    max_scan: usize,
+) -> Vec<(std::path::PathBuf, std::time::SystemTime)> {
+    let mut out: Vec<(std::path::PathBuf, std::time::SystemTime)> = Vec::new();
+
+    fn walk(
+        dir: &std::path::Path,
+        predicate: &impl Fn(&std::path::Path) -> bool,
+        out: &mut Vec<(std::path::PathBuf, std::time::SystemTime)>,
+        max_scan: usize,
+    ) {
+        if out.len() >= max_scan {
+            return;
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            if out.len() >= max_scan {
+                return;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Skip symlinks to prevent loops and traversal outside the watched tree.
+            if file_type.is_symlink() {
+                continue;
+            }
+            if file_type.is_dir() {
+                walk(&path, predicate, out, max_scan);
+            } else if file_type.is_file() && predicate(&path) {
+                if let Ok(meta) = std::fs::metadata(&path) {
+                    if let Ok(mtime) = meta.modified() {
+                        out.push((path, mtime));
+                    }
+                }
+            }
+        }
+    }
+
+    for root in roots {
+        let p = expand_home(root);
+        if p.exists() && p.is_file() {
+            if predicate(&p) {
+                if let Ok(meta) = std::fs::metadata(&p) {
+                    if let Ok(mtime) = meta.modified() {
+                        out.push((p, mtime));
+                    }
+                }
+            }
+            continue;
+        }
+        if p.exists() && p.is_dir() {
+            walk(&p, &predicate, &mut out, max_scan);
+        }
+    }
+
+    out.sort_by(|a, b| b.1.cmp(&a.1));
+    out
+}
 
 /// Backfill recent session files from configured capture sources.
 ///
 /// This is used to make the UI feel alive immediately after enabling auto-ingest.
+/// With `dry_run`, nothing is written and the counts describe what would happen.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn backfill_recent_sessions(
     db: State<'_, DbState>,
     repo_id: i64,
     limit_per_tool: i64,
+    dry_run: Option<bool>,
+) -> Result<BackfillResult, String> {
+    backfill_recent_sessions_inner(&db.0, repo_id, limit_per_tool, dry_run.unwrap_or(false)).await
+}
+
+pub(crate) async fn backfill_recent_sessions_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    limit_per_tool: i64,
+    dry_run: bool,
 ) -> Result<BackfillResult, String> {
     let config = crate::ingest_config::load_config().unwrap_or_default();
     let limit = limit_per_tool.clamp(1, 50) as usize;
+    let candidates = collect_backfill_candidates(&config, limit);
+
+    if dry_run {
+        let preview = preview_session_files(db, repo_id, candidates, false).await;
+        return Ok(BackfillResult {
+            attempted: preview.total,
+            imported: preview.would_import,
+            skipped: preview.duplicates,
+            failed: preview.failed,
+            preview: Some(preview),
+        });
+    }
+
+    let batch_id = journal::plan_batch(db, repo_id, journal::KIND_BACKFILL, &candidates)
+        .await
+        .map_err(|e| format!("Failed to journal backfill: {e}"))?;
+    Ok(run_backfill_batch(db, repo_id, &batch_id, candidates).await)
+}
+
+/// Auto-import journaled backfill candidates, ticking each off the journal
+async fn run_backfill_batch(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    batch_id: &str,
+    paths: Vec<String>,
+) -> BackfillResult {
+    let mut attempted = 0i64;
+    let mut imported = 0i64;
+    let mut skipped = 0i64;
+    let mut failed = 0i64;
+
+    for path in paths {
+        attempted += 1;
+        match auto_import_session_file_inner(db, repo_id, path.clone()).await {
+            Ok(r) => match r.status.as_str() {
+                "imported" => imported += 1,
+                "skipped" => skipped += 1,
+                _ => {}
+            },
+            Err(_) => failed += 1,
+        }
+        if let Ok(mut conn) = db.acquire().await {
+            if let Err(e) = journal::complete_file(&mut conn, batch_id, &path).await {
+                eprintln!("Narrative: failed to update import journal: {e}");
+            }
+        }
+    }
+
+    if let Err(e) = journal::clear_batch(db, batch_id).await {
+        eprintln!("Narrative: failed to clear import journal {batch_id}: {e}");
+    }
 
+    BackfillResult {
+        attempted,
+        imported,
+        skipped,
+        failed,
+        preview: None,
+    }
+}
+
+/// Outcome of resuming one interrupted batch
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedImport {
+    pub batch_id: String,
+    pub repo_id: i64,
+    pub kind: String,
+    pub attempted: i64,
+    pub imported: i64,
+    pub failed: i64,
+}
+
+/// Finish batch imports and backfills that were interrupted (e.g. by a crash).
+///
+/// Runs at startup; safe to call again, since only journaled files are imported.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn resume_pending_imports(db: State<'_, DbState>) -> Result<Vec<ResumedImport>, String> {
+    resume_pending_imports_inner(&db.0).await
+}
+
+pub(crate) async fn resume_pending_imports_inner(
+    db: &sqlx::SqlitePool,
+) -> Result<Vec<ResumedImport>, String> {
+    let pending = journal::pending_batches(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let mut resumed = Vec::with_capacity(pending.len());
+    for batch in pending {
+        let (attempted, imported, failed) = if batch.kind == journal::KIND_BACKFILL {
+            let result = run_backfill_batch(db, batch.repo_id, &batch.batch_id, batch.paths).await;
+            (result.attempted, result.imported, result.failed)
+        } else {
+            let cancel = Arc::new(AtomicBool::new(false));
+            let result = import_session_files_inner(
+                db,
+                batch.repo_id,
+                batch.paths,
+                &cancel,
+                Some(&batch.batch_id),
+                |_| {},
+            )
+            .await;
+            (
+                result.total as i64,
+                result.succeeded.len() as i64,
+                result.failed.len() as i64,
+            )
+        };
+
+        resumed.push(ResumedImport {
+            batch_id: batch.batch_id,
+            repo_id: batch.repo_id,
+            kind: batch.kind,
+            attempted,
+            imported,
+            failed,
+        });
+    }
+
+    Ok(resumed)
+}
+
+/// Most recent session files per tool from the configured watch paths
+fn collect_backfill_candidates(
+    config: &crate::ingest_config::IngestConfig,
+    limit: usize,
+) -> Vec<String> {
     let mut candidates: Vec<String> = Vec::new();
 
     // Claude session files
     let claude = collect_recent_files(
         &config.watch_paths.claude,
         |p| {
+            let p = uncompressed_name(p);
             p.extension().map(|e| e == "jsonl").unwrap_or(false)
                 && p.to_string_lossy().contains(".claude")
         },
@@ -640,7 +2012,7 @@ pub async fn backfill_recent_sessions(
         let codex = collect_recent_files(
             &config.watch_paths.codex_logs,
             |p| {
-                let s = p.to_string_lossy().replace('\\', "/");
+                let s = uncompressed_name(p).to_string_lossy().replace('\\', "/");
                 // Prefer structured Codex sessions.
                 (s.contains(".codex/sessions/") && s.ends_with(".jsonl"))
                     || (s.contains(".codex/archived_sessions/") && s.ends_with(".jsonl"))
@@ -658,14 +2030,75 @@ pub async fn backfill_recent_sessions(
         );
     }
 
+    // Cody chat history
+    let cody_parser = super::cody_parser::CodyParser;
+    let cody = collect_recent_files(&config.watch_paths.cody, |p| cody_parser.can_parse(p), 5000);
+    candidates.extend(
+        cody.into_iter()
+            .take(limit)
+            .map(|(p, _)| p.to_string_lossy().to_string()),
+    );
+
+    // Copilot Chat sessions
+    let copilot_parser = super::copilot_parser::CopilotParser;
+    let copilot = collect_recent_files(
+        &config.watch_paths.copilot,
+        |p| copilot_parser.can_parse(p),
+        5000,
+    );
+    candidates.extend(
+        copilot
+            .into_iter()
+            .take(limit)
+            .map(|(p, _)| p.to_string_lossy().to_string()),
+    );
+
+    candidates
+}
+
+/// Import every conversation from a ChatGPT data export (`conversations.json`).
+///
+/// Each conversation becomes its own session. Conversations that were already
+/// imported (same conversation id or identical content) are counted as skipped.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_chatgpt_export(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    file_path: String,
+) -> Result<BackfillResult, String> {
+    let parser = super::chatgpt_export_parser::ChatGptExportParser;
+    let path = std::path::Path::new(&file_path);
+    if !parser.can_parse(path) {
+        return Err(ParseError::UnsupportedFormat.to_string());
+    }
+
+    let sessions = match parser.parse_all(path) {
+        ParseResult::Success(sessions) => sessions,
+        ParseResult::Partial(sessions, _warnings) => sessions,
+        ParseResult::Failure(e) => {
+            log_auto_ingest(
+                &db.0,
+                repo_id,
+                "chatgpt",
+                Some(&file_path),
+                None,
+                "failed",
+                0,
+                Some(&e.to_string()),
+            )
+            .await;
+            return Err(e.to_string());
+        }
+    };
+
     let mut attempted = 0i64;
     let mut imported = 0i64;
     let mut skipped = 0i64;
     let mut failed = 0i64;
 
-    for path in candidates {
+    for session in sessions {
         attempted += 1;
-        match auto_import_session_file_inner(&db.0, repo_id, path).await {
+        match ingest_parsed_session(&db.0, repo_id, session, &file_path).await {
             Ok(r) => match r.status.as_str() {
                 "imported" => imported += 1,
                 "skipped" => skipped += 1,
@@ -680,6 +2113,7 @@ pub async fn backfill_recent_sessions(
         imported,
         skipped,
         failed,
+        preview: None,
     })
 }
 
@@ -716,7 +2150,21 @@ pub async fn purge_expired_sessions(
         "#,
     )
     .bind(repo_id)
-    .bind(repo_id)
+    .bind(repo_id)
+    .execute(&*db.0)
+    .await;
+
+    let _ = sqlx::query(
+        r#"
+        DELETE FROM session_trace_chunks
+        WHERE session_id IN (
+            SELECT id
+            FROM sessions
+            WHERE repo_id = ? AND purged_at IS NOT NULL
+          )
+        "#,
+    )
+    .bind(repo_id)
     .execute(&*db.0)
     .await;
 
@@ -733,9 +2181,10 @@ pub struct ScannedSession {
 
 /// Store a parsed session in the database
 async fn store_session(
-    db: &sqlx::SqlitePool,
+    db: &mut sqlx::SqliteConnection,
     repo_id: i64,
     session: &ParsedSession,
+    trim: Option<&super::trimming::TrimSummary>,
 ) -> Result<String, sqlx::Error> {
     use sqlx::query;
 
@@ -750,8 +2199,9 @@ async fn store_session(
             .map(|end| (end - start).num_minutes() as i32)
     });
 
-    // Serialize trace to JSON
-    let trace_json = serde_json::to_string(&session.trace).unwrap_or_else(|_| "{}".to_string());
+    // Serialize trace to JSON (large traces spill into session_trace_chunks)
+    let stored = super::trace_chunks::split_trace(&session.trace)
+        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
 
     // Serialize files touched
     let files_json =
@@ -776,9 +2226,13 @@ async fn store_session(
             source_session_id,
             redaction_count,
             redaction_types,
-            dedupe_key
+            dedupe_key,
+            trim_strategy,
+            trimmed_message_count,
+            git_branch,
+            edit_fingerprints
         )
-        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'), ?, ?, ?, ?, 1, ?, NULL, NULL, 0, NULL, NULL)
+        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'), ?, ?, ?, ?, 1, ?, NULL, NULL, 0, NULL, NULL, ?, ?, ?, ?)
         ON CONFLICT(id) DO UPDATE SET
             imported_at = strftime('%Y-%m-%dT%H:%M:%fZ','now'),
             model = COALESCE(excluded.model, sessions.model),
@@ -787,7 +2241,11 @@ async fn store_session(
             files = excluded.files,
             conversation_id = COALESCE(excluded.conversation_id, sessions.conversation_id),
             trace_available = MAX(excluded.trace_available, sessions.trace_available),
-            raw_json = excluded.raw_json
+            raw_json = excluded.raw_json,
+            trim_strategy = excluded.trim_strategy,
+            trimmed_message_count = excluded.trimmed_message_count,
+            git_branch = COALESCE(excluded.git_branch, sessions.git_branch),
+            edit_fingerprints = COALESCE(excluded.edit_fingerprints, sessions.edit_fingerprints)
         "#,
     )
     .bind(&session_id)
@@ -798,13 +2256,81 @@ async fn store_session(
     .bind(message_count)
     .bind(files_json)
     .bind(&session.origin.conversation_id)
-    .bind(&trace_json)
-    .execute(db)
+    .bind(&stored.inline_json)
+    .bind(trim.map(|t| t.strategy.as_str()))
+    .bind(trim.map_or(0, |t| t.dropped as i64))
+    .bind(&session.git_branch)
+    .bind(edit_fingerprints_json(session))
+    .execute(&mut *db)
     .await?;
+    super::trace_chunks::write_overflow(db, &session_id, &stored.overflow).await?;
+
+    attach_session_to_repo(db, &session_id, repo_id, None).await?;
 
     Ok(session_id)
 }
 
+/// SQL condition: session `alias` belongs to the repo `repo`, as its owner
+/// (`sessions.repo_id`) or through `session_repos`. `repo` is an SQL
+/// expression such as `o.repo_id`, `?2`, or `?` (bound once per use, twice).
+pub(crate) fn session_in_repo_sql(alias: &str, repo: &str) -> String {
+    format!(
+        "({alias}.repo_id = {repo} OR EXISTS (SELECT 1 FROM session_repos sr WHERE sr.session_id = {alias}.id AND sr.repo_id = {repo}))"
+    )
+}
+
+/// Record that a stored session belongs to a repo.
+///
+/// Sessions are stored once globally (the owning repo is `sessions.repo_id`);
+/// `session_repos` lists every repo that imported them. Returns false if the
+/// repo already had the session.
+async fn attach_session_to_repo(
+    conn: &mut sqlx::SqliteConnection,
+    session_id: &str,
+    repo_id: i64,
+    source_path: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO session_repos (session_id, repo_id, source_path)
+        VALUES (?, ?, ?)
+        ON CONFLICT(session_id, repo_id) DO NOTHING
+        "#,
+    )
+    .bind(session_id)
+    .bind(repo_id)
+    .bind(source_path)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add a session stored by another repo to `repo_id` instead of storing it again
+async fn share_stored_session(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    source_path: Option<&str>,
+) -> Result<String, StoreSessionError> {
+    let mut conn = db
+        .acquire()
+        .await
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
+    let attached = attach_session_to_repo(&mut conn, session_id, repo_id, source_path)
+        .await
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
+    drop(conn);
+    if !attached {
+        return Err(StoreSessionError::Duplicate);
+    }
+
+    // Atlas chunks are per repo, so the shared trace is projected for this repo too.
+    crate::atlas::index_queue::index_session(db, repo_id, session_id).await;
+
+    Ok(session_id.to_string())
+}
+
 #[derive(Debug)]
 enum StoreSessionError {
     Duplicate,
@@ -818,6 +2344,7 @@ async fn store_session_with_meta(
     source_path: Option<&str>,
     dedupe_key: Option<&str>,
     redaction: &RedactionSummary,
+    trim: Option<&super::trimming::TrimSummary>,
 ) -> Result<String, StoreSessionError> {
     use sqlx::query;
 
@@ -829,7 +2356,8 @@ async fn store_session_with_meta(
             .map(|end| (end - start).num_minutes() as i32)
     });
 
-    let trace_json = serde_json::to_string(&session.trace).unwrap_or_else(|_| "{}".to_string());
+    let stored = super::trace_chunks::split_trace(&session.trace)
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
     let files_json =
         serde_json::to_string(&session.files_touched).unwrap_or_else(|_| "[]".to_string());
     let redaction_types =
@@ -853,9 +2381,13 @@ async fn store_session_with_meta(
             source_session_id,
             redaction_count,
             redaction_types,
-            dedupe_key
+            dedupe_key,
+            trim_strategy,
+            trimmed_message_count,
+            git_branch,
+            edit_fingerprints
         )
-        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'), ?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?)
+        VALUES (?, ?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ','now'), ?, ?, ?, ?, 1, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         -- NOTE: idx_sessions_repo_dedupe is a *partial* unique index (dedupe_key IS NOT NULL),
         -- so the upsert target must include the same WHERE clause to match it.
         ON CONFLICT(repo_id, dedupe_key) WHERE dedupe_key IS NOT NULL DO NOTHING
@@ -869,12 +2401,16 @@ async fn store_session_with_meta(
     .bind(message_count)
     .bind(files_json)
     .bind(&session.origin.conversation_id)
-    .bind(&trace_json)
+    .bind(&stored.inline_json)
     .bind(source_path)
     .bind(&session.origin.session_id)
     .bind(redaction.total as i64)
     .bind(redaction_types)
     .bind(dedupe_key)
+    .bind(trim.map(|t| t.strategy.as_str()))
+    .bind(trim.map_or(0, |t| t.dropped as i64))
+    .bind(&session.git_branch)
+    .bind(edit_fingerprints_json(session))
     .execute(db)
     .await;
 
@@ -882,11 +2418,12 @@ async fn store_session_with_meta(
         Ok(result) => result,
         Err(e) => {
             let msg = e.to_string();
-            // Treat duplicate primary-key inserts as a no-op duplicate for auto-import.
-            // This prevents noisy failures when the same upstream session is observed again.
-            if msg.contains("UNIQUE constraint failed: sessions.id")
-                || msg.contains("UNIQUE constraint failed: sessions.repo_id, sessions.dedupe_key")
-            {
+            // The same upstream session is already stored, possibly by another repo:
+            // share it with this repo, or treat it as a no-op duplicate.
+            if msg.contains("UNIQUE constraint failed: sessions.id") {
+                return share_stored_session(db, repo_id, &session_id, source_path).await;
+            }
+            if msg.contains("UNIQUE constraint failed: sessions.repo_id, sessions.dedupe_key") {
                 return Err(StoreSessionError::Duplicate);
             }
             return Err(StoreSessionError::Db(msg));
@@ -897,16 +2434,19 @@ async fn store_session_with_meta(
         return Err(StoreSessionError::Duplicate);
     }
 
-    if let Err(err) =
-        crate::atlas::projection::upsert_chunks_for_session(db, repo_id, &session_id, &trace_json)
-            .await
-    {
-        eprintln!(
-            "Narrative: Atlas projection failed during import (repo_id={}, session_id={}): {}",
-            repo_id, session_id, err
-        );
-        crate::atlas::projection::mark_index_error(db, repo_id, &err).await;
-    }
+    let mut conn = db
+        .acquire()
+        .await
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
+    attach_session_to_repo(&mut conn, &session_id, repo_id, source_path)
+        .await
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
+    super::trace_chunks::write_overflow(&mut conn, &session_id, &stored.overflow)
+        .await
+        .map_err(|e| StoreSessionError::Db(e.to_string()))?;
+    drop(conn);
+
+    crate::atlas::index_queue::index_session(db, repo_id, &session_id).await;
 
     Ok(session_id)
 }
@@ -967,6 +2507,8 @@ pub(crate) async fn store_codex_app_server_completed_session(
         ended_at: None,
         trace,
         files_touched,
+        git_branch: None,
+        edit_fingerprints: Vec::new(),
     };
 
     let (session, redaction) = redact_session(session);
@@ -986,6 +2528,7 @@ pub(crate) async fn store_codex_app_server_completed_session(
         Some("codex-app-server"),
         Some(&dedupe_key),
         &redaction,
+        None,
     )
     .await
     {
@@ -1135,21 +2678,612 @@ fn build_dedupe_key(session: &ParsedSession) -> String {
     format!("{:x}", result)
 }
 
+/// A stored session, as needed to link it again
+#[derive(Debug, FromRow)]
+struct StoredSessionRow {
+    id: String,
+    tool: String,
+    model: Option<String>,
+    source_session_id: Option<String>,
+    conversation_id: Option<String>,
+    raw_json: String,
+    files: Option<String>,
+    imported_at: String,
+    git_branch: Option<String>,
+    edit_fingerprints: Option<String>,
+}
+
+/// Outcome of re-linking a repo's stored sessions
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkResult {
+    pub attempted: i64,
+    pub linked: i64,
+    pub unlinked: i64,
+    pub failed: i64,
+}
+
+/// Re-run linking for a repo's stored sessions against the current commits.
+///
+/// Use after a rebase or history rewrite, or after the commit cache was fixed.
+/// Only suggested links are replaced (sessions that no longer match lose them);
+/// manual links are kept. `since` / `until` (RFC 3339) limit the sessions to
+/// those that ended in that range.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn relink_sessions_for_repo(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<RelinkResult, String> {
+    relink_sessions_for_repo_inner(&db.0, repo_id, since.as_deref(), until.as_deref()).await
+}
+
+async fn relink_sessions_for_repo_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<RelinkResult, String> {
+    let since = since.map(parse_date_bound).transpose()?;
+    let until = until.map(parse_date_bound).transpose()?;
+    let rows = fetch_repo_session_rows(db, repo_id).await?;
+
+    let mut result = RelinkResult {
+        attempted: 0,
+        linked: 0,
+        unlinked: 0,
+        failed: 0,
+    };
+    for row in rows {
+        let id = row.id.clone();
+        let Ok(session) = load_stored_session(db, row).await else {
+            result.failed += 1;
+            continue;
+        };
+        if !ended_in_range(&session, since, until) {
+            continue;
+        }
+        result.attempted += 1;
+
+        let links = match find_link_candidates(db, repo_id, &session, &id).await {
+            Ok(Ok(links)) => links,
+            Ok(Err(_)) => Vec::new(),
+            Err(_) => {
+                result.failed += 1;
+                continue;
+            }
+        };
+        if crate::session_links::replace_auto_links(db, repo_id, &id, &links)
+            .await
+            .is_err()
+        {
+            result.failed += 1;
+        } else if links.is_empty() {
+            result.unlinked += 1;
+        } else {
+            result.linked += 1;
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_date_bound(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(|e| format!("Invalid date {}: {}", value, e))
+}
+
+/// Whether a session ended within the optional bounds (sessions with no
+/// known end only match when there are no bounds)
+fn ended_in_range(
+    session: &ParsedSession,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+) -> bool {
+    session
+        .ended_at
+        .map_or(since.is_none() && until.is_none(), |end| {
+            since.map_or(true, |since| end >= since) && until.map_or(true, |until| end <= until)
+        })
+}
+
+/// A repo's stored sessions (including ones shared with other repos),
+/// oldest import first
+async fn fetch_repo_session_rows(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+) -> Result<Vec<StoredSessionRow>, String> {
+    sqlx::query_as::<_, StoredSessionRow>(
+        r#"
+        SELECT s.id, s.tool, s.model, s.source_session_id, s.conversation_id,
+               s.raw_json, s.files, s.imported_at, s.git_branch, s.edit_fingerprints
+        FROM sessions s
+        WHERE (s.repo_id = ?1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = ?1))
+          AND s.purged_at IS NULL
+        ORDER BY s.imported_at
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Sessions scored per transaction by `relink_batch`
+const RELINK_BATCH_SIZE: usize = 200;
+
+/// Progress of `relink_batch`, emitted as `relink-progress` after each batch
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkProgress {
+    pub repo_id: i64,
+    /// Sessions processed so far
+    pub completed: usize,
+    pub total: usize,
+    pub summary: RelinkBatchSummary,
+}
+
+/// How a batch relink changed the repo's suggested links
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkBatchSummary {
+    /// Sessions now linked to a different set of commits
+    pub changed: usize,
+    /// Sessions still linked to the same commits (or still unlinked)
+    pub unchanged: usize,
+    /// Sessions that lost their suggested links, or whose best link now has
+    /// lower confidence
+    pub downgraded: usize,
+    /// Sessions that could not be read or scored
+    pub failed: usize,
+}
+
+/// Compare a session's suggested links before and after relinking
+fn classify_relink(
+    before: &[(String, f64)],
+    after: &[crate::linking::LinkResult],
+    summary: &mut RelinkBatchSummary,
+) {
+    let mut before_shas: Vec<&str> = before.iter().map(|(sha, _)| sha.as_str()).collect();
+    let mut after_shas: Vec<&str> = after.iter().map(|l| l.commit_sha.as_str()).collect();
+    before_shas.sort_unstable();
+    after_shas.sort_unstable();
+    let best_before = before
+        .iter()
+        .map(|(_, c)| *c)
+        .fold(None, |best: Option<f64>, c| {
+            Some(best.map_or(c, |b| b.max(c)))
+        });
+
+    if before_shas == after_shas {
+        summary.unchanged += 1;
+    } else if best_before.is_some_and(|best| after.first().map_or(true, |l| l.confidence < best)) {
+        summary.downgraded += 1;
+    } else {
+        summary.changed += 1;
+    }
+}
+
+/// Relink a repo's stored sessions in batches, reporting progress.
+///
+/// Like `relink_sessions_for_repo`, but scores `batch_size` sessions at a
+/// time (default 200) and writes each batch's links in one transaction,
+/// emitting `relink-progress` after each. Manual links are kept. Returns how
+/// the suggested links changed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn relink_batch(
+    app_handle: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    since: Option<String>,
+    until: Option<String>,
+    batch_size: Option<usize>,
+) -> Result<RelinkBatchSummary, String> {
+    relink_batch_inner(
+        &db.0,
+        repo_id,
+        since.as_deref(),
+        until.as_deref(),
+        batch_size.unwrap_or(RELINK_BATCH_SIZE),
+        |progress| {
+            if let Err(e) = app_handle.emit("relink-progress", progress) {
+                eprintln!("Failed to emit relink-progress: {}", e);
+            }
+        },
+    )
+    .await
+}
+
+async fn relink_batch_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    since: Option<&str>,
+    until: Option<&str>,
+    batch_size: usize,
+    mut on_progress: impl FnMut(RelinkProgress),
+) -> Result<RelinkBatchSummary, String> {
+    let since = since.map(parse_date_bound).transpose()?;
+    let until = until.map(parse_date_bound).transpose()?;
+    let rows = fetch_repo_session_rows(db, repo_id).await?;
+
+    let mut existing: std::collections::HashMap<String, Vec<(String, f64)>> =
+        std::collections::HashMap::new();
+    let link_rows: Vec<(String, String, f64)> = sqlx::query_as(
+        "SELECT session_id, commit_sha, confidence FROM session_links WHERE repo_id = ? AND auto_linked = 1",
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+    for (session_id, sha, confidence) in link_rows {
+        existing
+            .entry(session_id)
+            .or_default()
+            .push((sha, confidence));
+    }
+
+    let mut summary = RelinkBatchSummary::default();
+    let total = rows.len();
+    let mut completed = 0;
+    let mut rows = rows.into_iter().peekable();
+    while rows.peek().is_some() {
+        // Score the batch first, then write it in one transaction
+        let mut pending = Vec::new();
+        for row in rows.by_ref().take(batch_size.max(1)) {
+            completed += 1;
+            let id = row.id.clone();
+            let Ok(session) = load_stored_session(db, row).await else {
+                summary.failed += 1;
+                continue;
+            };
+            if !ended_in_range(&session, since, until) {
+                continue;
+            }
+            match find_link_candidates(db, repo_id, &session, &id).await {
+                Ok(links) => pending.push((id, links.unwrap_or_default())),
+                Err(_) => summary.failed += 1,
+            }
+        }
+
+        let mut tx = db.begin().await.map_err(|e| e.to_string())?;
+        for (id, links) in &pending {
+            crate::session_links::write_auto_links(&mut tx, repo_id, id, links).await?;
+        }
+        tx.commit().await.map_err(|e| e.to_string())?;
+
+        for (id, links) in &pending {
+            let before = existing.get(id).map(Vec::as_slice).unwrap_or_default();
+            classify_relink(before, links, &mut summary);
+        }
+        on_progress(RelinkProgress {
+            repo_id,
+            completed,
+            total,
+            summary,
+        });
+    }
+
+    Ok(summary)
+}
+
+/// Rebuild a stored session for linking.
+///
+/// Start and end times are not stored; they are recovered from the trace,
+/// falling back to the import time.
+async fn load_stored_session(
+    db: &sqlx::SqlitePool,
+    row: StoredSessionRow,
+) -> Result<ParsedSession, String> {
+    use super::parser::{SessionOrigin, SessionTrace};
+
+    let json = super::trace_chunks::load_trace_json(db, &row.id, row.raw_json)
+        .await
+        .map_err(|e| e.to_string())?;
+    let trace: SessionTrace = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+
+    let mut times = trace.timestamps();
+    let started_at = times.next();
+    let ended_at = times.last().or(started_at).or_else(|| {
+        chrono::DateTime::parse_from_rfc3339(&row.imported_at)
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    });
+
+    Ok(ParsedSession {
+        origin: SessionOrigin {
+            tool: row.tool,
+            session_id: row.source_session_id.unwrap_or(row.id),
+            conversation_id: row.conversation_id.unwrap_or_default(),
+            model: row.model,
+        },
+        started_at,
+        ended_at,
+        trace,
+        files_touched: parse_json_list(row.files),
+        git_branch: row.git_branch,
+        edit_fingerprints: parse_json_list(row.edit_fingerprints),
+    })
+}
+
+/// Nearest-miss commits listed per unlinked session
+const NEAREST_MISS_LIMIT: usize = 3;
+
+/// What the user can do about an unlinked session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestedAction {
+    /// The closest commits fall outside the ±4 hour window (often a clock
+    /// offset); link by hand or correct the tool's clock offset
+    ExtendWindow,
+    /// A commit scored below the threshold; link it by hand if it is right
+    ManualLink,
+    /// A commit now matches (e.g. commits were indexed later); relink the repo
+    Relink,
+    /// The stored session could not be read; import the file again
+    Reimport,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlinkedSuggestion {
+    pub action: SuggestedAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_sha: Option<String>,
+    pub detail: String,
+}
+
+/// A session with no link in a repo, and why
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlinkedSession {
+    pub session_id: String,
+    pub tool: String,
+    pub ended_at: Option<String>,
+    /// None when the session would link now
+    pub reason: Option<crate::linking::UnlinkedReason>,
+    pub reason_message: Option<String>,
+    /// Commits that came closest, best first
+    pub nearest_misses: Vec<crate::linking::LinkResult>,
+    pub suggestions: Vec<UnlinkedSuggestion>,
+}
+
+/// List a repo's sessions that have no link, with why and what to do next.
+///
+/// Each session is scored again against the current commits (nothing is
+/// stored), giving the reason it is unlinked, the commits that came closest
+/// and suggested actions. Sessions whose stored trace cannot be read are
+/// included too. Most recently imported first, up to `limit` (default 50).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_unlinked_sessions_report(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<UnlinkedSession>, String> {
+    unlinked_sessions_report_inner(&db.0, repo_id, limit.unwrap_or(50)).await
+}
+
+async fn unlinked_sessions_report_inner(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    limit: i64,
+) -> Result<Vec<UnlinkedSession>, String> {
+    use crate::linking::{link_session_to_all_commits_with_options, LinkOptions, UnlinkedReason};
+
+    let rows = sqlx::query_as::<_, StoredSessionRow>(
+        r#"
+        SELECT s.id, s.tool, s.model, s.source_session_id, s.conversation_id,
+               s.raw_json, s.files, s.imported_at, s.git_branch, s.edit_fingerprints
+        FROM sessions s
+        WHERE (s.repo_id = ?1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = ?1))
+          AND s.purged_at IS NULL
+          AND NOT EXISTS (SELECT 1 FROM session_links l WHERE l.session_id = s.id AND l.repo_id = ?1)
+        ORDER BY s.imported_at DESC
+        LIMIT ?2
+        "#,
+    )
+    .bind(repo_id)
+    .bind(limit)
+    .fetch_all(db)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut report = Vec::new();
+    for row in rows {
+        let id = row.id.clone();
+        let tool = row.tool.clone();
+        let session = match load_stored_session(db, row).await {
+            Ok(session) => session,
+            Err(err) => {
+                let reason = UnlinkedReason::ParseError(err);
+                report.push(UnlinkedSession {
+                    session_id: id,
+                    tool,
+                    ended_at: None,
+                    reason_message: Some(reason.to_string()),
+                    reason: Some(reason),
+                    nearest_misses: Vec::new(),
+                    suggestions: vec![UnlinkedSuggestion {
+                        action: SuggestedAction::Reimport,
+                        commit_sha: None,
+                        detail: "The stored session could not be read; import it again".to_string(),
+                    }],
+                });
+                continue;
+            }
+        };
+
+        let (excerpt, commits, params) = prepare_link_inputs(db, repo_id, &session, &id).await?;
+        let outcome = link_session_to_all_commits_with_options(
+            &excerpt,
+            &commits,
+            LinkOptions {
+                skip_secret_scan: true,
+                params,
+            },
+        );
+
+        let (reason, nearest_misses, suggestions) = match outcome {
+            Ok(links) => {
+                let best = &links[0];
+                let suggestion = UnlinkedSuggestion {
+                    action: SuggestedAction::Relink,
+                    commit_sha: Some(best.commit_sha.clone()),
+                    detail: format!(
+                        "Commit {} now matches ({:.2}); relink the repo",
+                        short_sha(&best.commit_sha),
+                        best.confidence
+                    ),
+                };
+                (None, links, vec![suggestion])
+            }
+            Err(UnlinkedReason::NoCommitsInTimeWindow) => {
+                let closest = super::super::link_commands::query_closest_commits(
+                    db,
+                    repo_id,
+                    &excerpt.imported_at_iso,
+                    NEAREST_MISS_LIMIT as i64,
+                )
+                .await?;
+                let misses =
+                    crate::linking::nearest_misses(&excerpt, &closest, &params, NEAREST_MISS_LIMIT);
+                let suggestions = closest
+                    .first()
+                    .map(|commit| {
+                        let gap = commit_gap_minutes(&excerpt.imported_at_iso, &commit.authored_at);
+                        UnlinkedSuggestion {
+                            action: SuggestedAction::ExtendWindow,
+                            commit_sha: Some(commit.sha.clone()),
+                            detail: format!(
+                                "Closest commit {} is {} minutes from the session, outside the 4-hour window; check the tool's clock offset or link it by hand",
+                                short_sha(&commit.sha),
+                                gap
+                            ),
+                        }
+                    })
+                    .into_iter()
+                    .collect();
+                (
+                    Some(UnlinkedReason::NoCommitsInTimeWindow),
+                    misses,
+                    suggestions,
+                )
+            }
+            Err(reason) => {
+                let misses =
+                    crate::linking::nearest_misses(&excerpt, &commits, &params, NEAREST_MISS_LIMIT);
+                let suggestions = misses
+                    .first()
+                    .map(|miss| UnlinkedSuggestion {
+                        action: SuggestedAction::ManualLink,
+                        commit_sha: Some(miss.commit_sha.clone()),
+                        detail: format!(
+                            "Best candidate {} scored {:.2}, below the {:.2} threshold; link it by hand if it is right",
+                            short_sha(&miss.commit_sha),
+                            miss.confidence,
+                            params.confidence_threshold
+                        ),
+                    })
+                    .into_iter()
+                    .collect();
+                (Some(reason), misses, suggestions)
+            }
+        };
+
+        report.push(UnlinkedSession {
+            session_id: id,
+            tool: session.origin.tool,
+            ended_at: Some(excerpt.imported_at_iso),
+            reason_message: reason.as_ref().map(|r| r.to_string()),
+            reason,
+            nearest_misses,
+            suggestions,
+        });
+    }
+
+    Ok(report)
+}
+
+fn short_sha(sha: &str) -> &str {
+    sha.get(..7).unwrap_or(sha)
+}
+
+/// Whole minutes between two RFC 3339 times (0 if either is unparseable)
+fn commit_gap_minutes(session_end: &str, authored_at: &str) -> i64 {
+    let parse = |value: &str| chrono::DateTime::parse_from_rfc3339(value).ok();
+    match (parse(session_end), parse(authored_at)) {
+        (Some(end), Some(at)) => (at - end).num_minutes().abs(),
+        _ => 0,
+    }
+}
+
 async fn link_session_to_commit_internal(
     db: &sqlx::SqlitePool,
     repo_id: i64,
     session: &ParsedSession,
     stored_session_id: &str,
 ) -> Result<crate::linking::LinkResult, String> {
-    use crate::linking::{
-        link_session_to_commits_with_options, LinkOptions, SessionMessage, SessionMessageRole,
-        SessionTool,
-    };
+    let mut links = find_link_candidates(db, repo_id, session, stored_session_id)
+        .await?
+        .map_err(|e| format!("{:?}", e))?;
+    crate::session_links::replace_auto_links(db, repo_id, stored_session_id, &links).await?;
+    let linked = links.iter().map(|link| link.commit_sha.clone()).collect();
+    crate::story_anchors::sessions_notes_io::auto_export_sessions_notes(db, repo_id, linked).await;
+
+    // Best match first
+    Ok(links.swap_remove(0))
+}
+
+/// Pick the commits a session would link to, best first (read-only)
+///
+/// The outer error is a lookup failure; the inner one says why nothing matched.
+async fn find_link_candidates(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session: &ParsedSession,
+    stored_session_id: &str,
+) -> Result<crate::linking::LinkingResult, String> {
+    use crate::linking::{link_session_to_all_commits_with_options, LinkOptions};
+
+    let (session_excerpt, commits, params) =
+        prepare_link_inputs(db, repo_id, session, stored_session_id).await?;
+    Ok(link_session_to_all_commits_with_options(
+        &session_excerpt,
+        &commits,
+        LinkOptions {
+            skip_secret_scan: true,
+            params,
+        },
+    ))
+}
 
+/// The session excerpt, candidate commits and linking params for a session
+async fn prepare_link_inputs(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session: &ParsedSession,
+    stored_session_id: &str,
+) -> Result<
+    (
+        crate::linking::SessionExcerpt,
+        Vec<crate::linking::GitCommit>,
+        crate::linking::LinkingParams,
+    ),
+    String,
+> {
+    use crate::linking::{SessionMessage, SessionMessageRole, SessionTool};
+
+    // Tools that log local time without an offset are shifted into UTC
+    let clock_offset = chrono::Duration::minutes(
+        crate::clock_offsets::fetch_clock_offset(db, &session.origin.tool).await?,
+    );
     let imported_at_iso = session
         .ended_at
         .or(session.started_at)
-        .map(|dt| dt.to_rfc3339())
+        .map(|dt| (dt + clock_offset).to_rfc3339())
         .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
 
     let mut messages: Vec<SessionMessage> = session
@@ -1161,6 +3295,7 @@ async fn link_session_to_commit_internal(
             id: format!("{}:m{}", session.origin.session_id, idx),
             role: match msg {
                 super::parser::TraceMessage::User { .. } => SessionMessageRole::User,
+                super::parser::TraceMessage::Plan { .. } => SessionMessageRole::Plan,
                 _ => SessionMessageRole::Assistant,
             },
             text: match msg {
@@ -1195,6 +3330,7 @@ async fn link_session_to_commit_internal(
             .and_then(|start| session.ended_at.map(|end| (end - start).num_minutes())),
         imported_at_iso,
         messages,
+        edit_fingerprints: session.edit_fingerprints.clone(),
     };
 
     let session_end = chrono::DateTime::parse_from_rfc3339(&session_excerpt.imported_at_iso)
@@ -1210,44 +3346,24 @@ async fn link_session_to_commit_internal(
 
     let commits = super::super::link_commands::query_commits_in_window(
         db,
-        repo_id,
-        &window_start,
-        &window_end,
-    )
-    .await
-    .map_err(|e| e.to_string())?;
-
-    let result = link_session_to_commits_with_options(
-        &session_excerpt,
-        &commits,
-        LinkOptions {
-            skip_secret_scan: true,
-        },
-    )
-    .map_err(|e| format!("{:?}", e))?;
-
-    sqlx::query(
-        r#"
-        INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked, needs_review)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT(repo_id, session_id) DO UPDATE SET
-            commit_sha = excluded.commit_sha,
-            confidence = excluded.confidence,
-            auto_linked = excluded.auto_linked,
-            needs_review = excluded.needs_review
-        "#,
+        repo_id,
+        &window_start,
+        &window_end,
     )
-    .bind(repo_id)
-    .bind(&session_excerpt.id)
-    .bind(&result.commit_sha)
-    .bind(result.confidence)
-    .bind(result.auto_linked)
-    .bind(if result.needs_review { 1 } else { 0 })
-    .execute(db)
     .await
-    .map_err(|e| format!("Failed to store link: {}", e))?;
+    .map_err(|e| e.to_string())?;
+    let commits = super::super::link_commands::prepare_candidates(
+        db,
+        repo_id,
+        session.git_branch.as_deref(),
+        &session_end,
+        &session.files_touched,
+        commits,
+    )
+    .await;
+    let params = super::super::link_commands::fetch_linking_params(db, repo_id).await?;
 
-    Ok(result)
+    Ok((session_excerpt, commits, params))
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -1281,7 +3397,7 @@ async fn log_auto_ingest(
 
 /// Log import attempt for audit/debugging
 async fn log_import(
-    db: &sqlx::SqlitePool,
+    db: &mut sqlx::SqliteConnection,
     repo_id: i64,
     file_path: &str,
     session_id: Option<&str>,
@@ -1301,157 +3417,1063 @@ async fn log_import(
     .bind(status)
     .bind(warnings)
     .bind(error)
-    .execute(db)
+    .execute(&mut *db)
     .await;
 }
 
-/// Generate a deterministic session ID
-fn generate_session_id(origin: &super::parser::SessionOrigin) -> String {
-    use sha2::{Digest, Sha256};
+/// Edit fingerprints as stored in `sessions.edit_fingerprints` (NULL if none)
+fn edit_fingerprints_json(session: &ParsedSession) -> Option<String> {
+    if session.edit_fingerprints.is_empty() {
+        return None;
+    }
+    serde_json::to_string(&session.edit_fingerprints).ok()
+}
+
+/// Parse a stored JSON array column (`files`, `edit_fingerprints`)
+fn parse_json_list(json: Option<String>) -> Vec<String> {
+    json.and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Generate a deterministic session ID
+fn generate_session_id(origin: &super::parser::SessionOrigin) -> String {
+    use sha2::{Digest, Sha256};
+
+    let input = format!("{}:{}", origin.tool, origin.session_id);
+    let mut hasher = Sha256::new();
+    hasher.update(input.as_bytes());
+    let result = hasher.finalize();
+
+    format!("{:x}", result)[..16].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn collect_recent_files_skips_symlinked_directories() {
+        use std::fs;
+        use std::os::unix::fs::symlink;
+
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let root = tmp.path().join("root");
+        fs::create_dir(&root).expect("create root");
+
+        let nested = root.join("nested");
+        fs::create_dir(&nested).expect("create nested");
+        let wanted = nested.join("session.jsonl");
+        fs::write(&wanted, b"{}").expect("write session");
+
+        // Create a symlink loop: root/loop -> root
+        let loop_link = root.join("loop");
+        symlink(&root, &loop_link).expect("create symlink loop");
+
+        let results = collect_recent_files(
+            &[root.to_string_lossy().to_string()],
+            |path| path.extension().map(|ext| ext == "jsonl").unwrap_or(false),
+            100,
+        );
+
+        assert!(results.iter().any(|(path, _)| path == &wanted), "real file must be found");
+        assert!(
+            results.iter().all(|(path, _)| !path.starts_with(&loop_link)),
+            "symlinked directory must not be traversed"
+        );
+    }
+
+    #[test]
+    fn scan_session_roots_labels_files_by_parser() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let claude = tmp.path().join(".claude/projects/demo");
+        let continue_dir = tmp.path().join(".continue/sessions");
+        std::fs::create_dir_all(&claude).expect("create claude dir");
+        std::fs::create_dir_all(&continue_dir).expect("create continue dir");
+        std::fs::write(claude.join("a.jsonl"), b"{}").expect("write claude session");
+        std::fs::write(claude.join("notes.txt"), b"hi").expect("write notes");
+        std::fs::write(continue_dir.join("b.json"), b"{}").expect("write continue session");
+
+        let registry = ParserRegistry::new();
+        // The Claude root nests inside the temp root; each file is reported once.
+        let results = scan_session_roots(
+            &registry,
+            &[tmp.path().join(".claude"), tmp.path().to_path_buf()],
+        );
+
+        let mut tools: Vec<(String, String)> = results
+            .into_iter()
+            .map(|r| {
+                let name = std::path::Path::new(&r.path)
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                (name, r.tool)
+            })
+            .collect();
+        tools.sort();
+        assert_eq!(
+            tools,
+            vec![
+                ("a.jsonl".to_string(), "claude_code".to_string()),
+                ("b.json".to_string(), "continue".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_session_id_deterministic() {
+        let origin = super::super::parser::SessionOrigin {
+            tool: "claude_code".to_string(),
+            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
+            conversation_id: "conversation-1".to_string(),
+            model: None,
+        };
+
+        let id1 = generate_session_id(&origin);
+        let id2 = generate_session_id(&origin);
+
+        assert_eq!(id1, id2);
+        assert_eq!(id1.len(), 16);
+    }
+
+    #[test]
+    fn test_generate_session_id_unique() {
+        let origin1 = super::super::parser::SessionOrigin {
+            tool: "claude_code".to_string(),
+            session_id: "aaa".to_string(),
+            conversation_id: "conversation-1".to_string(),
+            model: None,
+        };
+
+        let origin2 = super::super::parser::SessionOrigin {
+            tool: "cursor".to_string(),
+            session_id: "aaa".to_string(),
+            conversation_id: "conversation-2".to_string(),
+            model: None,
+        };
+
+        let id1 = generate_session_id(&origin1);
+        let id2 = generate_session_id(&origin2);
+
+        assert_ne!(id1, id2);
+    }
+
+    #[test]
+    fn import_session_file_incremental_appends_only_new_lines() {
+        use std::io::Write;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("abc.jsonl");
+            let path_str = path.to_string_lossy().to_string();
+
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Add a test"}}"#;
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n{assistant}\n")).expect("write");
+
+            let first = import_session_file_incremental_inner(&pool, 1, path_str.clone())
+                .await
+                .expect("first import");
+            assert_eq!(first.status, "imported");
+
+            // One complete line plus a line that is still being written.
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .expect("open for append");
+            write!(file, "{user}\n{{\"type\":\"assist").expect("append");
+
+            let second = import_session_file_incremental_inner(&pool, 1, path_str.clone())
+                .await
+                .expect("second import");
+            assert_eq!(second.session_id, first.session_id);
+
+            let (count, offset): (i64, i64) = sqlx::query_as(
+                "SELECT s.message_count, o.byte_offset FROM sessions s \
+                 JOIN session_import_offsets o ON o.session_id = s.id WHERE s.id = ?",
+            )
+            .bind(&first.session_id)
+            .fetch_one(&pool)
+            .await
+            .expect("session row");
+            assert_eq!(count, 3);
+            let file_len = std::fs::metadata(&path).expect("metadata").len() as i64;
+            assert_eq!(offset, file_len - "{\"type\":\"assist".len() as i64);
+
+            let third = import_session_file_incremental_inner(&pool, 1, path_str)
+                .await
+                .expect("third import");
+            assert_eq!(third.status, "skipped");
+        });
+    }
+
+    #[test]
+    fn reimport_session_refreshes_trace_and_keeps_links() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("abc.jsonl");
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Add a test"}}"#;
+            std::fs::write(&path, format!("{user}\n")).expect("write");
+
+            let imported = auto_import_session_file_inner(&pool, 1, path.to_string_lossy().to_string())
+                .await
+                .expect("import");
+            sqlx::query(
+                "INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked) VALUES (1, ?, 'abc123', 0.9, 0)",
+            )
+            .bind(&imported.session_id)
+            .execute(&pool)
+            .await
+            .expect("manual link");
+
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n{assistant}\n")).expect("rewrite");
+
+            let result = reimport_session_inner(&pool, 1, &imported.session_id)
+                .await
+                .expect("reimport");
+            assert_eq!(result.previous_message_count, 1);
+            assert_eq!(result.message_count, 2);
+
+            let (message_count, raw_json): (i64, String) =
+                sqlx::query_as("SELECT message_count, raw_json FROM sessions WHERE id = ?")
+                    .bind(&imported.session_id)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("session row");
+            assert_eq!(message_count, 2);
+            assert!(raw_json.contains("Done"));
+
+            let commit_sha: String =
+                sqlx::query_scalar("SELECT commit_sha FROM session_links WHERE session_id = ?")
+                    .bind(&imported.session_id)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("link kept");
+            assert_eq!(commit_sha, "abc123");
+
+            let chunks: i64 =
+                sqlx::query_scalar("SELECT COUNT(*) FROM atlas_chunks WHERE session_id = ?")
+                    .bind(&imported.session_id)
+                    .fetch_one(&pool)
+                    .await
+                    .expect("chunks");
+            assert!(chunks > 0);
+        });
+    }
+
+    #[test]
+    fn auto_import_links_session_to_every_matching_commit() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+            // Score on time alone so the test does not depend on file overlap.
+            sqlx::query(
+                "INSERT INTO repo_linking_params (repo_id, temporal_weight, file_overlap_weight, confidence_threshold) VALUES (1, 1.0, 0.0, 0.7)",
+            )
+            .execute(&pool)
+            .await
+            .expect("linking params");
+            for (sha, authored_at) in [
+                ("c1", "2026-01-01T00:00:30Z"),
+                ("c2", "2026-01-01T00:03:00Z"),
+                ("c3", "2026-01-01T01:00:00Z"),
+            ] {
+                sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, ?, ?, 'commit')")
+                    .bind(sha)
+                    .bind(authored_at)
+                    .execute(&pool)
+                    .await
+                    .expect("insert commit");
+            }
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("multi.jsonl");
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Split the parser"}}"#;
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n{assistant}\n")).expect("write");
+
+            let imported = auto_import_session_file_inner(&pool, 1, path.to_string_lossy().to_string())
+                .await
+                .expect("import");
+
+            let links: Vec<(String, f64)> = sqlx::query_as(
+                "SELECT commit_sha, confidence FROM session_links WHERE session_id = ? ORDER BY confidence DESC",
+            )
+            .bind(&imported.session_id)
+            .fetch_all(&pool)
+            .await
+            .expect("links");
+            let shas: Vec<&str> = links.iter().map(|(sha, _)| sha.as_str()).collect();
+            assert_eq!(shas, vec!["c1", "c2"]);
+            assert!(links[0].1 > links[1].1);
+        });
+    }
+
+    #[test]
+    fn relink_follows_rewritten_history() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+            // Score on time alone so the test does not depend on file overlap.
+            sqlx::query(
+                "INSERT INTO repo_linking_params (repo_id, temporal_weight, file_overlap_weight, confidence_threshold) VALUES (1, 1.0, 0.0, 0.7)",
+            )
+            .execute(&pool)
+            .await
+            .expect("linking params");
+            for (sha, authored_at) in [
+                ("c1", "2026-01-01T00:00:30Z"),
+                ("c2", "2026-01-01T00:03:00Z"),
+                ("c3", "2026-01-01T01:00:00Z"),
+            ] {
+                sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, ?, ?, 'commit')")
+                    .bind(sha)
+                    .bind(authored_at)
+                    .execute(&pool)
+                    .await
+                    .expect("insert commit");
+            }
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("multi.jsonl");
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Split the parser"}}"#;
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n{assistant}\n")).expect("write");
+
+            let imported = auto_import_session_file_inner(&pool, 1, path.to_string_lossy().to_string())
+                .await
+                .expect("import");
+
+            // Rewrite history: the linked commits are replaced by a new one.
+            sqlx::query("DELETE FROM commits WHERE repo_id = 1")
+                .execute(&pool)
+                .await
+                .expect("delete commits");
+            sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, 'r1', '2026-01-01T00:01:30Z', 'commit')")
+                .execute(&pool)
+                .await
+                .expect("insert commit");
+
+            let skipped = relink_sessions_for_repo_inner(&pool, 1, Some("2026-02-01T00:00:00Z"), None)
+                .await
+                .expect("relink range");
+            assert_eq!(skipped.attempted, 0);
+
+            let relinked = relink_sessions_for_repo_inner(&pool, 1, None, None)
+                .await
+                .expect("relink");
+            assert_eq!((relinked.attempted, relinked.linked), (1, 1));
+
+            let shas: Vec<String> =
+                sqlx::query_scalar("SELECT commit_sha FROM session_links WHERE session_id = ?")
+                    .bind(&imported.session_id)
+                    .fetch_all(&pool)
+                    .await
+                    .expect("links");
+            assert_eq!(shas, vec!["r1"]);
+
+            // Batched: same commits, then a rewrite that drops the match.
+            let mut events = Vec::new();
+            let summary =
+                relink_batch_inner(&pool, 1, None, None, 1, |p| events.push(p.completed))
+                    .await
+                    .expect("batch relink");
+            assert_eq!(summary.unchanged, 1);
+            assert_eq!(events, vec![1]);
+
+            sqlx::query("DELETE FROM commits WHERE repo_id = 1")
+                .execute(&pool)
+                .await
+                .expect("delete commits");
+            let summary = relink_batch_inner(&pool, 1, None, None, 1, |_| {})
+                .await
+                .expect("batch relink");
+            assert_eq!((summary.downgraded, summary.changed), (1, 0));
+        });
+    }
+
+    #[test]
+    fn unlinked_report_explains_and_suggests() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+            // Score on time alone so the test does not depend on file overlap.
+            sqlx::query(
+                "INSERT INTO repo_linking_params (repo_id, temporal_weight, file_overlap_weight, confidence_threshold) VALUES (1, 1.0, 0.0, 0.7)",
+            )
+            .execute(&pool)
+            .await
+            .expect("linking params");
+            for (sha, authored_at) in [
+                ("c1", "2026-01-01T00:00:30Z"),
+                ("c2", "2026-01-01T00:03:00Z"),
+                ("c3", "2026-01-01T01:00:00Z"),
+            ] {
+                sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, ?, ?, 'commit')")
+                    .bind(sha)
+                    .bind(authored_at)
+                    .execute(&pool)
+                    .await
+                    .expect("insert commit");
+            }
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("multi.jsonl");
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Split the parser"}}"#;
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n{assistant}\n")).expect("write");
+
+            auto_import_session_file_inner(&pool, 1, path.to_string_lossy().to_string())
+                .await
+                .expect("import");
+
+            // Drop the links and commits so the session is unlinked.
+            for sql in [
+                "DELETE FROM session_links WHERE repo_id = 1",
+                "DELETE FROM commits WHERE repo_id = 1",
+                "INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, 'far', '2026-01-01T06:00:00Z', 'commit')",
+            ] {
+                sqlx::query(sql).execute(&pool).await.expect(sql);
+            }
+            let report = unlinked_sessions_report_inner(&pool, 1, 50)
+                .await
+                .expect("report");
+            assert_eq!(report.len(), 1);
+            assert!(matches!(
+                report[0].reason,
+                Some(crate::linking::UnlinkedReason::NoCommitsInTimeWindow)
+            ));
+            assert_eq!(report[0].nearest_misses[0].commit_sha, "far");
+            assert_eq!(report[0].suggestions[0].action, SuggestedAction::ExtendWindow);
+
+            // Just past the session: scored, but under the threshold.
+            sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, 'late', '2026-01-01T00:05:00Z', 'commit')")
+                .execute(&pool)
+                .await
+                .expect("insert commit");
+            let report = unlinked_sessions_report_inner(&pool, 1, 50)
+                .await
+                .expect("report");
+            assert!(matches!(
+                report[0].reason,
+                Some(crate::linking::UnlinkedReason::LowConfidence)
+            ));
+            assert_eq!(report[0].nearest_misses[0].commit_sha, "late");
+            assert_eq!(report[0].suggestions[0].action, SuggestedAction::ManualLink);
+            assert_eq!(report[0].suggestions[0].commit_sha.as_deref(), Some("late"));
+
+            // A matching commit indexed later: relinking would fix it.
+            sqlx::query("INSERT INTO commits (repo_id, sha, authored_at, subject) VALUES (1, 'match', '2026-01-01T00:00:30Z', 'commit')")
+                .execute(&pool)
+                .await
+                .expect("insert commit");
+            let report = unlinked_sessions_report_inner(&pool, 1, 50)
+                .await
+                .expect("report");
+            assert!(report[0].reason.is_none());
+            assert_eq!(report[0].suggestions[0].action, SuggestedAction::Relink);
+
+            relink_sessions_for_repo_inner(&pool, 1, None, None)
+                .await
+                .expect("relink");
+            let report = unlinked_sessions_report_inner(&pool, 1, 50)
+                .await
+                .expect("report");
+            assert!(report.is_empty());
+        });
+    }
+
+    #[test]
+    fn same_session_in_two_repos_is_stored_once() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
 
-    let input = format!("{}:{}", origin.tool, origin.session_id);
-    let mut hasher = Sha256::new();
-    hasher.update(input.as_bytes());
-    let result = hasher.finalize();
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
 
-    format!("{:x}", result)[..16].to_string()
-}
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo'), (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repos");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("shared.jsonl");
+            std::fs::write(
+                &path,
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Touch both repos"}}"#,
+            )
+            .expect("write");
+            let file_path = path.to_string_lossy().to_string();
 
-/// Scan Claude Code directory recursively
-fn scan_claude_directory(
-    dir: &std::path::Path,
-    results: &mut Vec<ScannedSession>,
-) -> Result<(), std::io::Error> {
-    use std::fs;
+            let first = auto_import_session_file_inner(&pool, 1, file_path.clone())
+                .await
+                .expect("import into repo 1");
+            let second = auto_import_session_file_inner(&pool, 2, file_path.clone())
+                .await
+                .expect("import into repo 2");
+            let again = auto_import_session_file_inner(&pool, 2, file_path)
+                .await
+                .expect("re-import into repo 2");
 
-    for entry in fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
+            assert_eq!(first.status, "imported");
+            assert_eq!(second.status, "imported");
+            assert_eq!(second.session_id, first.session_id);
+            assert_eq!(again.status, "skipped");
 
-        if path.is_dir() {
-            // Recurse into subdirectories
-            scan_claude_directory(&path, results)?;
-        } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
-            results.push(ScannedSession {
-                path: path.to_string_lossy().to_string(),
-                tool: "claude_code".to_string(),
-                detected_at: chrono::Utc::now().to_rfc3339(),
-            });
-        }
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+                .fetch_one(&pool)
+                .await
+                .expect("session count");
+            assert_eq!(stored, 1);
+
+            let repos: Vec<i64> = sqlx::query_scalar(
+                "SELECT repo_id FROM session_repos WHERE session_id = ? ORDER BY repo_id",
+            )
+            .bind(&first.session_id)
+            .fetch_all(&pool)
+            .await
+            .expect("memberships");
+            assert_eq!(repos, vec![1, 2]);
+
+            let chunks: i64 =
+                sqlx::query_scalar("SELECT COUNT(*) FROM atlas_chunks WHERE repo_id = 2")
+                    .fetch_one(&pool)
+                    .await
+                    .expect("chunks");
+            assert!(chunks > 0);
+        });
     }
 
-    Ok(())
-}
+    #[test]
+    fn deleting_owner_repo_hands_shared_session_to_member() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use sqlx::sqlite::SqlitePoolOptions;
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo'), (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repos");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let shared = dir.join("shared.jsonl");
+            let solo = dir.join("solo.jsonl");
+            std::fs::write(
+                &shared,
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Touch both repos"}}"#,
+            )
+            .expect("write shared");
+            std::fs::write(
+                &solo,
+                r#"{"type":"user","timestamp":"2026-01-02T00:00:00Z","message":{"content":"Only this repo"}}"#,
+            )
+            .expect("write solo");
+            let shared_path = shared.to_string_lossy().to_string();
+
+            let shared_session = auto_import_session_file_inner(&pool, 1, shared_path.clone())
+                .await
+                .expect("import shared into repo 1");
+            auto_import_session_file_inner(&pool, 2, shared_path)
+                .await
+                .expect("import shared into repo 2");
+            auto_import_session_file_inner(&pool, 1, solo.to_string_lossy().to_string())
+                .await
+                .expect("import solo into repo 1");
+
+            sqlx::query("DELETE FROM repos WHERE id = 1")
+                .execute(&pool)
+                .await
+                .expect("delete repo");
+
+            let remaining: Vec<(String, i64)> =
+                sqlx::query_as("SELECT id, repo_id FROM sessions ORDER BY id")
+                    .fetch_all(&pool)
+                    .await
+                    .expect("sessions");
+            assert_eq!(remaining, vec![(shared_session.session_id, 2)]);
+        });
+    }
 
-    #[cfg(unix)]
     #[test]
-    fn collect_recent_files_skips_symlinked_directories() {
-        use std::fs;
-        use std::os::unix::fs::symlink;
+    fn shared_session_can_be_reimported_from_either_repo() {
+        use std::io::Write;
 
-        let tmp = tempfile::tempdir().expect("tempdir");
-        let root = tmp.path().join("root");
-        fs::create_dir(&root).expect("create root");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
 
-        let nested = root.join("nested");
-        fs::create_dir(&nested).expect("create nested");
-        let wanted = nested.join("session.jsonl");
-        fs::write(&wanted, b"{}").expect("write session");
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
 
-        // Create a symlink loop: root/loop -> root
-        let loop_link = root.join("loop");
-        symlink(&root, &loop_link).expect("create symlink loop");
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo'), (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repos");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("shared.jsonl");
+            let user = r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Touch both repos"}}"#;
+            let assistant = r#"{"type":"assistant","timestamp":"2026-01-01T00:01:00Z","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+            std::fs::write(&path, format!("{user}\n")).expect("write");
+            let file_path = path.to_string_lossy().to_string();
+
+            let first = auto_import_session_file_inner(&pool, 1, file_path.clone())
+                .await
+                .expect("import into repo 1");
+            auto_import_session_file_inner(&pool, 2, file_path)
+                .await
+                .expect("import into repo 2");
 
-        let results = collect_recent_files(
-            &[root.to_string_lossy().to_string()],
-            |path| path.extension().map(|ext| ext == "jsonl").unwrap_or(false),
-            100,
-        );
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(&path)
+                .expect("open");
+            writeln!(file, "{assistant}").expect("append");
 
-        assert!(results.iter().any(|(path, _)| path == &wanted), "real file must be found");
-        assert!(
-            results.iter().all(|(path, _)| !path.starts_with(&loop_link)),
-            "symlinked directory must not be traversed"
-        );
+            // Repo 2 is a member, not the owner.
+            let result = reimport_session_inner(&pool, 2, &first.session_id)
+                .await
+                .expect("reimport from member repo");
+            assert_eq!(result.previous_message_count, 1);
+            assert_eq!(result.message_count, 2);
+
+            let stored: i64 = sqlx::query_scalar("SELECT message_count FROM sessions WHERE id = ?")
+                .bind(&first.session_id)
+                .fetch_one(&pool)
+                .await
+                .expect("message count");
+            assert_eq!(stored, 2);
+        });
     }
 
     #[test]
-    fn test_generate_session_id_deterministic() {
-        let origin = super::super::parser::SessionOrigin {
-            tool: "claude_code".to_string(),
-            session_id: "550e8400-e29b-41d4-a716-446655440000".to_string(),
-            conversation_id: "conversation-1".to_string(),
-            model: None,
-        };
+    fn oversized_session_is_trimmed_and_recorded() {
+        use crate::import::parser::{SessionOrigin, SessionTrace, TraceMessage};
+        use crate::import::trimming::DEFAULT_MAX_MESSAGES;
 
-        let id1 = generate_session_id(&origin);
-        let id2 = generate_session_id(&origin);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
 
-        assert_eq!(id1, id2);
-        assert_eq!(id1.len(), 16);
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let mut trace = SessionTrace::new();
+            for i in 0..DEFAULT_MAX_MESSAGES + 10 {
+                trace.add_message(TraceMessage::User {
+                    text: format!("step {i}"),
+                    timestamp: None,
+                });
+            }
+            let session = ParsedSession {
+                origin: SessionOrigin {
+                    tool: "claude_code".to_string(),
+                    session_id: "long-session".to_string(),
+                    conversation_id: "long-session".to_string(),
+                    model: None,
+                },
+                started_at: None,
+                ended_at: None,
+                trace,
+                files_touched: vec![],
+                git_branch: None,
+                edit_fingerprints: Vec::new(),
+            };
+
+            let result = ingest_parsed_session(&pool, 1, session, "/tmp/long.jsonl")
+                .await
+                .expect("ingest");
+            assert_eq!(result.status, "imported");
+
+            let (message_count, strategy, trimmed): (i64, Option<String>, i64) = sqlx::query_as(
+                "SELECT message_count, trim_strategy, trimmed_message_count FROM sessions WHERE id = ?",
+            )
+            .bind(&result.session_id)
+            .fetch_one(&pool)
+            .await
+            .expect("session row");
+            assert_eq!(message_count, DEFAULT_MAX_MESSAGES as i64);
+            assert_eq!(strategy.as_deref(), Some("summary"));
+            assert_eq!(trimmed, 11);
+        });
     }
 
     #[test]
-    fn test_generate_session_id_unique() {
-        let origin1 = super::super::parser::SessionOrigin {
-            tool: "claude_code".to_string(),
-            session_id: "aaa".to_string(),
-            conversation_id: "conversation-1".to_string(),
-            model: None,
-        };
+    fn flagged_import_is_quarantined_until_confirmed() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
 
-        let origin2 = super::super::parser::SessionOrigin {
-            tool: "cursor".to_string(),
-            session_id: "aaa".to_string(),
-            conversation_id: "conversation-2".to_string(),
-            model: None,
-        };
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
 
-        let id1 = generate_session_id(&origin1);
-        let id2 = generate_session_id(&origin2);
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
 
-        assert_ne!(id1, id2);
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("abc.jsonl");
+            let path_str = path.to_string_lossy().to_string();
+            let secret = "sk-abcdefghijklmnopqrstuvwxyz0123456789";
+            let line = format!(
+                r#"{{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{{"content":"use {secret}"}}}}"#
+            );
+            std::fs::write(&path, format!("{line}\n")).expect("write");
+
+            let cancel = Arc::new(AtomicBool::new(false));
+            let result =
+                import_session_files_inner(&pool, 1, vec![path_str.clone()], &cancel, None, |_| {}).await;
+            assert_eq!(result.failed.len(), 1);
+
+            let quarantined = quarantine::list_quarantined(&pool, 1).await.expect("list");
+            assert_eq!(quarantined.len(), 1);
+            assert_eq!(quarantined[0].excerpts.len(), 1);
+            assert!(!quarantined[0].excerpts[0].text.contains(secret));
+            let id = quarantined[0].id;
+
+            // Content changed after review: refuse until re-reviewed.
+            std::fs::write(&path, format!("{line}\n{line}\n")).expect("rewrite");
+            assert!(confirm_quarantined_import_inner(&pool, 1, id).await.is_err());
+            import_session_files_inner(&pool, 1, vec![path_str], &cancel, None, |_| {}).await;
+
+            let imported = confirm_quarantined_import_inner(&pool, 1, id)
+                .await
+                .expect("confirm");
+            assert_eq!(imported.status, "imported");
+            assert!(quarantine::list_quarantined(&pool, 1)
+                .await
+                .expect("list")
+                .is_empty());
+
+            let raw_json: String = sqlx::query_scalar("SELECT raw_json FROM sessions WHERE id = ?")
+                .bind(&imported.session_id)
+                .fetch_one(&pool)
+                .await
+                .expect("stored session");
+            assert!(!raw_json.contains(secret));
+        });
     }
 
     #[test]
-    fn store_codex_app_server_completed_session_redacts_summary_payload() {
+    fn preview_session_files_writes_nothing() {
         let runtime = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .expect("tokio runtime");
 
         runtime.block_on(async {
-            let pool = SqlitePoolOptions::new()
-                .max_connections(1)
-                .connect("sqlite::memory:")
-                .await
-                .expect("memory sqlite");
+            let pool = crate::test_pool().await;
 
-            sqlx::query(include_str!("../../migrations/001_init.sql"))
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
                 .await
-                .expect("migration 001");
-            sqlx::query(include_str!("../../migrations/004_session_attribution.sql"))
-                .execute(&pool)
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let path = dir.join("abc.jsonl");
+            let path_str = path.to_string_lossy().to_string();
+            let line =
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Hi"}}"#;
+            std::fs::write(&path, format!("{line}\n")).expect("write");
+            let missing = dir.join("missing.jsonl").to_string_lossy().to_string();
+
+            let report =
+                preview_session_files(&pool, 1, vec![path_str.clone(), missing], true).await;
+            assert_eq!(report.total, 2);
+            assert_eq!(report.would_import, 1);
+            assert_eq!(report.failed, 1);
+            assert_eq!(report.files[0].status, "new");
+            assert_eq!(report.files[0].message_count, 1);
+
+            for table in ["sessions", "session_import_log", "ingest_audit_log"] {
+                let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+                    .fetch_one(&pool)
+                    .await
+                    .expect("count rows");
+                assert_eq!(rows, 0, "dry run must not write to {table}");
+            }
+
+            auto_import_session_file_inner(&pool, 1, path_str.clone())
                 .await
-                .expect("migration 004");
-            sqlx::query(include_str!("../../migrations/005_attribution_notes.sql"))
+                .expect("import");
+            let report = preview_session_files(&pool, 1, vec![path_str], true).await;
+            assert_eq!(report.duplicates, 1);
+            assert_eq!(report.files[0].status, "duplicate");
+        });
+    }
+
+    #[test]
+    fn import_session_files_parses_in_parallel_and_writes_every_file() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
                 .await
-                .expect("migration 005");
-            sqlx::query(include_str!("../../migrations/009_auto_ingest.sql"))
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let line =
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Hi"}}"#;
+            let mut paths: Vec<String> = (0..20)
+                .map(|n| {
+                    let path = dir.join(format!("s{n}.jsonl"));
+                    std::fs::write(&path, format!("{line}\n")).expect("write");
+                    path.to_string_lossy().to_string()
+                })
+                .collect();
+            paths.push(dir.join("missing.jsonl").to_string_lossy().to_string());
+
+            let cancel = Arc::new(AtomicBool::new(false));
+            let mut completed = Vec::new();
+            let result = import_session_files_inner(&pool, 1, paths, &cancel, None, |progress| {
+                completed.push(progress.completed);
+            })
+            .await;
+
+            assert!(!result.cancelled);
+            assert_eq!(result.succeeded.len(), 20);
+            assert_eq!(result.failed.len(), 1);
+            assert_eq!(completed, (1..=21).collect::<Vec<_>>());
+            assert_eq!(
+                result.workers.iter().map(|w| w.processed).sum::<usize>(),
+                21
+            );
+            assert_eq!(result.workers.iter().map(|w| w.failed).sum::<usize>(), 1);
+
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+                .fetch_one(&pool)
+                .await
+                .expect("count sessions");
+            assert_eq!(stored, 20);
+        });
+    }
+
+    #[test]
+    fn resume_pending_imports_finishes_interrupted_batch() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
                 .await
-                .expect("migration 009");
-            sqlx::query(include_str!("../../migrations/012_atlas.sql"))
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let line =
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Hi"}}"#;
+            let paths: Vec<String> = (0..3)
+                .map(|n| {
+                    let path = dir.join(format!("s{n}.jsonl"));
+                    std::fs::write(&path, format!("{line}\n")).expect("write");
+                    path.to_string_lossy().to_string()
+                })
+                .collect();
+
+            // A batch that crashed after handling its first file.
+            let batch_id = journal::plan_batch(&pool, 1, journal::KIND_BATCH, &paths)
+                .await
+                .expect("plan batch");
+            let mut conn = pool.acquire().await.expect("conn");
+            journal::complete_file(&mut conn, &batch_id, &paths[0])
+                .await
+                .expect("complete first");
+            drop(conn);
+
+            let resumed = resume_pending_imports_inner(&pool).await.expect("resume");
+            assert_eq!(resumed.len(), 1);
+            assert_eq!(resumed[0].batch_id, batch_id);
+            assert_eq!(resumed[0].attempted, 2);
+            assert_eq!(resumed[0].imported, 2);
+
+            let pending: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM import_journal")
+                .fetch_one(&pool)
+                .await
+                .expect("journal count");
+            assert_eq!(pending, 0);
+
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+                .fetch_one(&pool)
+                .await
+                .expect("count sessions");
+            assert_eq!(stored, 2);
+
+            assert!(resume_pending_imports_inner(&pool)
+                .await
+                .expect("resume again")
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn import_session_files_cancellation_keeps_imported_sessions() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
                 .await
-                .expect("migration 012");
+                .expect("insert repo");
+
+            let tmp = tempfile::tempdir().expect("tempdir");
+            let dir = tmp.path().join(".claude/projects/demo");
+            std::fs::create_dir_all(&dir).expect("create dir");
+            let line =
+                r#"{"type":"user","timestamp":"2026-01-01T00:00:00Z","message":{"content":"Hi"}}"#;
+            let paths: Vec<String> = (0..100)
+                .map(|n| {
+                    let path = dir.join(format!("s{n}.jsonl"));
+                    std::fs::write(&path, format!("{line}\n")).expect("write");
+                    path.to_string_lossy().to_string()
+                })
+                .collect();
+
+            // Cancel as soon as the first batch reports progress. Workers block on
+            // the bounded channel, so most files are never parsed or written.
+            let cancel = Arc::new(AtomicBool::new(false));
+            let mut events = Vec::new();
+            let result = import_session_files_inner(&pool, 1, paths, &cancel, None, |progress| {
+                cancel.store(true, Ordering::SeqCst);
+                events.push(progress);
+            })
+            .await;
+
+            assert!(result.cancelled);
+            assert_eq!(result.total, 100);
+            assert!(!result.succeeded.is_empty() && result.succeeded.len() < 100);
+            assert_eq!(events.len(), result.succeeded.len());
+            assert_eq!(events[0].completed, 1);
+            assert_eq!(events[0].status, "succeeded");
+            assert_eq!(
+                result.workers.iter().map(|w| w.processed).sum::<usize>(),
+                result.succeeded.len()
+            );
+
+            let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sessions")
+                .fetch_one(&pool)
+                .await
+                .expect("count sessions");
+            assert_eq!(stored as usize, result.succeeded.len());
+        });
+    }
+
+    #[test]
+    fn store_codex_app_server_completed_session_redacts_summary_payload() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
 
             sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
@@ -1500,28 +4522,7 @@ mod tests {
             .expect("tokio runtime");
 
         runtime.block_on(async {
-            let pool = SqlitePoolOptions::new()
-                .max_connections(1)
-                .connect("sqlite::memory:")
-                .await
-                .expect("memory sqlite");
-
-            sqlx::query(include_str!("../../migrations/001_init.sql"))
-                .execute(&pool)
-                .await
-                .expect("migration 001");
-            sqlx::query(include_str!("../../migrations/004_session_attribution.sql"))
-                .execute(&pool)
-                .await
-                .expect("migration 004");
-            sqlx::query(include_str!("../../migrations/005_attribution_notes.sql"))
-                .execute(&pool)
-                .await
-                .expect("migration 005");
-            sqlx::query(include_str!("../../migrations/009_auto_ingest.sql"))
-                .execute(&pool)
-                .await
-                .expect("migration 009");
+            let pool = crate::test_pool().await;
 
             sqlx::query("INSERT INTO repos (id, path) VALUES (1, '/tmp/repo')")
                 .execute(&pool)
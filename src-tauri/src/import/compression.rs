@@ -0,0 +1,164 @@
+//! Transparent handling of compressed session files
+//!
+//! Claude Code and Codex occasionally rotate old session logs into `.jsonl.gz`
+//! or `.jsonl.zst`. Parsers rely on path patterns (`.claude/`, file stems,
+//! extensions), so instead of teaching every parser about compression the
+//! registry decompresses the file into a temporary mirror of its original path
+//! and parses that.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Upper bound on decompressed size (guards against decompression bombs)
+const MAX_DECOMPRESSED_SIZE: u64 = 100 * 1024 * 1024; // 100MB
+
+/// Compression formats recognized by file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// Detect compression from the file extension
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("gz") => Some(Compression::Gzip),
+            Some("zst") | Some("zstd") => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// The path the file had before compression (`a.jsonl.gz` -> `a.jsonl`)
+pub fn logical_path(path: &Path) -> Option<PathBuf> {
+    Compression::detect(path)?;
+    let stem = path.file_stem()?;
+    Some(path.with_file_name(stem))
+}
+
+/// A decompressed copy of a session file; removed when dropped
+pub struct DecompressedFile {
+    root: PathBuf,
+    path: PathBuf,
+}
+
+impl DecompressedFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for DecompressedFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+/// Decompress `path` into a private temp directory, mirroring its logical path
+///
+/// Mirroring keeps path-based parser detection and session ids identical to the
+/// uncompressed original (e.g. `~/.claude/projects/p/<uuid>.jsonl`).
+pub fn decompress_to_temp(path: &Path) -> std::io::Result<DecompressedFile> {
+    let compression = Compression::detect(path).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "not a compressed file")
+    })?;
+    let logical = logical_path(path).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, "missing file name")
+    })?;
+
+    let file = std::fs::File::open(path)?;
+    let reader: Box<dyn Read> = match compression {
+        Compression::Gzip => Box::new(flate2::read::GzDecoder::new(file)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(file)?),
+    };
+
+    let mut content = Vec::new();
+    reader
+        .take(MAX_DECOMPRESSED_SIZE + 1)
+        .read_to_end(&mut content)?;
+    if content.len() as u64 > MAX_DECOMPRESSED_SIZE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "decompressed session exceeds 100MB",
+        ));
+    }
+
+    let root = std::env::temp_dir().join(format!(
+        "narrative-decompressed-{}-{}",
+        std::process::id(),
+        rand::random::<u64>()
+    ));
+    // Strip the root/prefix so the mirror stays inside `root`.
+    let relative: PathBuf = logical
+        .components()
+        .filter(|c| matches!(c, std::path::Component::Normal(_)))
+        .collect();
+    let target = root.join(relative);
+
+    let result = (|| {
+        std::fs::create_dir_all(target.parent().unwrap_or(&root))?;
+        std::fs::write(&target, &content)
+    })();
+    if let Err(e) = result {
+        let _ = std::fs::remove_dir_all(&root);
+        return Err(e);
+    }
+
+    Ok(DecompressedFile { root, path: target })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_logical_path_strips_compression_extension() {
+        assert_eq!(
+            logical_path(Path::new("/home/u/.claude/projects/p/abc.jsonl.gz")),
+            Some(PathBuf::from("/home/u/.claude/projects/p/abc.jsonl"))
+        );
+        assert_eq!(
+            logical_path(Path::new("/home/u/.codex/sessions/s.jsonl.zst")),
+            Some(PathBuf::from("/home/u/.codex/sessions/s.jsonl"))
+        );
+        assert_eq!(logical_path(Path::new("/home/u/.claude/abc.jsonl")), None);
+    }
+
+    #[test]
+    fn test_decompress_gzip_mirrors_logical_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(".claude/projects/p/abc.jsonl.gz");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{\"type\":\"user\"}\n").unwrap();
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let decompressed = decompress_to_temp(&path).unwrap();
+        let inner = decompressed.path().to_path_buf();
+        assert!(inner.ends_with(".claude/projects/p/abc.jsonl"));
+        assert_eq!(
+            std::fs::read_to_string(&inner).unwrap(),
+            "{\"type\":\"user\"}\n"
+        );
+
+        drop(decompressed);
+        assert!(!inner.exists());
+    }
+
+    #[test]
+    fn test_decompress_zstd() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join(".codex/sessions/s.jsonl.zst");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, zstd::encode_all(&b"line\n"[..], 3).unwrap()).unwrap();
+
+        let decompressed = decompress_to_temp(&path).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(decompressed.path()).unwrap(),
+            "line\n"
+        );
+    }
+}
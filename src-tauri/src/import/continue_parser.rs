@@ -5,15 +5,15 @@
 
 use super::{
     parser::{
-        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
-        TraceMessage, WarningSeverity,
+        home_roots, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
     },
     path_validator::PathValidator,
     secure_parser::SecretScanner,
 };
 use crate::session_hash::generate_session_hash;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct ContinueParser;
 
@@ -26,6 +26,14 @@ impl super::parser::SessionParser for ContinueParser {
                 || path.extension().map(|e| e == "jsonl").unwrap_or(false))
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "continue"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[".continue/sessions"])
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -153,6 +161,8 @@ impl ContinueParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -264,6 +274,8 @@ impl ContinueParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
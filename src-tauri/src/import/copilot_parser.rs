@@ -1,7 +1,14 @@
 //! GitHub Copilot session parser
 //!
-//! Parses VS Code Copilot conversation exports.
-//! Copilot stores data in VS Code's SQLite database or JSON exports.
+//! Parses VS Code Copilot conversation exports and the chat sessions Copilot
+//! Chat writes to VS Code storage:
+//! - `User/globalStorage/github.copilot-chat/`
+//! - `User/globalStorage/emptyWindowChatSessions/<id>.json`
+//! - `User/workspaceStorage/<hash>/chatSessions/<id>.json`
+//!
+//! Chat session files hold `{sessionId, creationDate, requests: [{message,
+//! response, timestamp, modelId, variableData}]}`; each request becomes a user
+//! turn and its response parts an assistant turn.
 
 use super::{
     parser::{
@@ -13,12 +20,27 @@ use super::{
 };
 use crate::session_hash::generate_session_hash;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct CopilotParser;
 
+/// Check whether a (slash-normalized, lowercased) path is a Copilot Chat session file
+pub(crate) fn is_copilot_chat_session(path_str: &str) -> bool {
+    path_str.ends_with(".json")
+        && (path_str.contains("/chatsessions/")
+            || path_str.contains("/emptywindowchatsessions/")
+            // The extension also keeps embedding caches in its storage folder.
+            || (path_str.contains("github.copilot-chat/") && !path_str.contains("embeddings")))
+}
+
 impl super::parser::SessionParser for CopilotParser {
     fn can_parse(&self, path: &Path) -> bool {
+        let normalized = path.to_string_lossy().replace('\\', "/").to_lowercase();
+        // Inside VS Code storage, only chat session files are sessions.
+        if normalized.contains("/globalstorage/") || normalized.contains("/workspacestorage/") {
+            return is_copilot_chat_session(&normalized);
+        }
+
         let path_str = path.to_string_lossy();
         // Check for Copilot-related paths or filenames
         (path_str.contains("copilot") || path_str.contains("github"))
@@ -26,6 +48,14 @@ impl super::parser::SessionParser for CopilotParser {
                 || path.extension().map(|e| e == "jsonl").unwrap_or(false))
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "copilot"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        crate::ingest_config::discover_copilot_chat_dirs()
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -150,6 +180,8 @@ impl CopilotParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -165,6 +197,11 @@ impl CopilotParser {
             Err(e) => return ParseResult::Failure(ParseError::Json(e)),
         };
 
+        // VS Code chat session (globalStorage / workspaceStorage)
+        if json["requests"].is_array() {
+            return self.parse_chat_session(&json, path);
+        }
+
         let mut trace = SessionTrace::new();
         let mut warnings = Vec::new();
         let model: Option<String> = json["model"]
@@ -216,6 +253,130 @@ impl CopilotParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+
+    /// Convert a VS Code chat session (`requests` with markdown response parts)
+    fn parse_chat_session(&self, json: &Value, path: &Path) -> ParseResult<ParsedSession> {
+        let mut trace = SessionTrace::new();
+        let mut warnings = Vec::new();
+        let mut files_touched: Vec<String> = Vec::new();
+        let mut model: Option<String> = None;
+
+        let requests = json["requests"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        for (idx, request) in requests.iter().enumerate() {
+            let timestamp = request["timestamp"]
+                .as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|t| t.to_rfc3339());
+
+            if let Some(model_id) = request["modelId"].as_str() {
+                model = Some(model_id.trim_start_matches("copilot/").to_string());
+            }
+
+            // Attached files (`#file`, selections) are the files the turn is about.
+            if let Some(variables) = request["variableData"]["variables"].as_array() {
+                files_touched.extend(variables.iter().filter_map(|v| uri_path(&v["value"])));
+            }
+
+            let prompt = request["message"]["text"]
+                .as_str()
+                .unwrap_or("")
+                .to_string();
+            let mut reply = String::new();
+            if let Some(parts) = request["response"].as_array() {
+                for part in parts {
+                    match part["kind"].as_str() {
+                        // Markdown parts have no `kind` in older files
+                        None | Some("markdownContent") => {
+                            if let Some(text) = part["value"]
+                                .as_str()
+                                .or_else(|| part["content"]["value"].as_str())
+                            {
+                                reply.push_str(text);
+                            }
+                        }
+                        Some("inlineReference") => {
+                            if let Some(path) = uri_path(&part["inlineReference"]) {
+                                reply.push_str(&format!("`{}`", path));
+                            }
+                        }
+                        Some("textEditGroup") => {
+                            files_touched.extend(uri_path(part));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            for text in [&prompt, &reply] {
+                let secret_findings = SecretScanner::scan(text);
+                if !secret_findings.is_empty() {
+                    warnings.push(ParseWarning {
+                        severity: WarningSeverity::Security,
+                        message: format!(
+                            "Potential secrets detected: {}",
+                            secret_findings
+                                .iter()
+                                .map(|f| f.kind.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                        context: Some(format!("request {}", idx)),
+                    });
+                }
+            }
+
+            if !prompt.trim().is_empty() {
+                trace.add_message(TraceMessage::User {
+                    text: prompt,
+                    timestamp: timestamp.clone(),
+                });
+            }
+            if !reply.trim().is_empty() {
+                trace.add_message(TraceMessage::Assistant {
+                    text: reply,
+                    timestamp,
+                });
+            }
+        }
+        files_touched.sort();
+        files_touched.dedup();
+
+        let conversation_id = json["sessionId"]
+            .as_str()
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or("unknown")
+            .to_string();
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: "copilot".to_string(),
+                session_id: generate_session_hash("copilot", &conversation_id),
+                conversation_id,
+                model,
+            },
+            started_at: json["creationDate"]
+                .as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis),
+            ended_at: json["lastMessageDate"]
+                .as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis),
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -272,6 +433,19 @@ impl CopilotParser {
     }
 }
 
+/// File path of a VS Code URI object (`{uri: {path}}`, `{path}`, or `{fsPath}`)
+fn uri_path(value: &Value) -> Option<String> {
+    let uri = if value["uri"].is_object() {
+        &value["uri"]
+    } else {
+        value
+    };
+    uri["fsPath"]
+        .as_str()
+        .or_else(|| uri["path"].as_str())
+        .map(String::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -288,8 +462,66 @@ mod tests {
         let github_path = Path::new("/home/user/github-copilot/export.jsonl");
         assert!(parser.can_parse(github_path));
 
+        // VS Code chat sessions written by Copilot Chat
+        assert!(parser.can_parse(Path::new(
+            "/home/user/.config/Code/User/workspaceStorage/abc123/chatSessions/4f1c.json"
+        )));
+        assert!(parser.can_parse(Path::new(
+            "/Users/me/Library/Application Support/Code/User/globalStorage/emptyWindowChatSessions/4f1c.json"
+        )));
+        assert!(!parser.can_parse(Path::new(
+            "/home/user/.config/Code/User/globalStorage/github.copilot-chat/commandEmbeddings.json"
+        )));
+
         // Should not parse other files
         let other_path = Path::new("/some/other/path.txt");
         assert!(!parser.can_parse(other_path));
     }
+
+    #[test]
+    fn test_parses_vscode_chat_session() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp
+            .path()
+            .join("Code/User/workspaceStorage/abc123/chatSessions");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("4f1c.json");
+        let session = serde_json::json!({
+            "version": 3,
+            "sessionId": "4f1c",
+            "creationDate": 1_700_000_000_000_i64,
+            "requests": [{
+                "message": { "text": "rename foo in #file:lib.rs" },
+                "variableData": { "variables": [{ "value": { "uri": { "path": "/repo/src/lib.rs" } } }] },
+                "response": [
+                    { "value": "Renamed it in " },
+                    { "kind": "inlineReference", "inlineReference": { "path": "/repo/src/lib.rs" } },
+                    { "kind": "textEditGroup", "uri": { "path": "/repo/src/main.rs" }, "edits": [] }
+                ],
+                "timestamp": 1_700_000_001_000_i64,
+                "modelId": "copilot/gpt-4o"
+            }]
+        });
+        std::fs::write(&path, session.to_string()).unwrap();
+
+        let parsed = match CopilotParser.parse(&path) {
+            ParseResult::Success(s) => s,
+            _ => panic!("expected clean parse"),
+        };
+        assert_eq!(parsed.origin.conversation_id, "4f1c");
+        assert_eq!(parsed.origin.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(parsed.trace.messages.len(), 2);
+        assert!(matches!(
+            &parsed.trace.messages[1],
+            TraceMessage::Assistant { text, .. } if text == "Renamed it in `/repo/src/lib.rs`"
+        ));
+        assert_eq!(
+            parsed.files_touched,
+            vec![
+                "/repo/src/lib.rs".to_string(),
+                "/repo/src/main.rs".to_string()
+            ]
+        );
+        assert!(parsed.started_at.is_some());
+    }
 }
@@ -0,0 +1,246 @@
+//! Direct reader for Cursor's composer SQLite database
+//!
+//! `~/.cursor/composer/composer.database` holds every composer chat in the
+//! `composer_chat` table. The database is opened read-only (Cursor usually has
+//! it open) and rows are read in `(updatedAt, rowid)` order past a watermark, so
+//! each import only converts chats that were created or edited since the last
+//! one: new chats get a fresh rowid and timestamp, edited chats a later
+//! `updatedAt`.
+
+use super::{
+    cursor_parser::CursorParser,
+    parser::{ParseError, ParseWarning, ParsedSession},
+    path_validator::PathValidator,
+};
+use rusqlite::{Connection, OpenFlags};
+use std::path::Path;
+
+/// Upper bound on chats converted per read; the watermark lets the next read continue.
+pub const MAX_SESSIONS_PER_READ: usize = 500;
+
+/// Position of the last chat read: its `updatedAt` (epoch ms) and rowid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComposerWatermark {
+    pub updated_at: i64,
+    pub rowid: i64,
+}
+
+/// One composer chat converted to a session
+#[derive(Debug)]
+pub struct ComposerSession {
+    pub rowid: i64,
+    pub composer_id: String,
+    pub updated_at: i64,
+    pub session: ParsedSession,
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// Whether `path` is Cursor's composer database
+pub fn is_composer_db(path: &Path) -> bool {
+    let path_str = path.to_string_lossy().replace('\\', "/");
+    path_str.contains(".cursor")
+        && path_str.contains("/composer/")
+        && path_str.ends_with("composer.database")
+}
+
+fn db_error(context: &str, e: rusqlite::Error) -> ParseError {
+    ParseError::Io(std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("{}: {}", context, e),
+    ))
+}
+
+fn open_read_only(path: &Path) -> Result<Connection, ParseError> {
+    // Security: Validate path
+    if let Err(e) = PathValidator::validate(path) {
+        return Err(ParseError::Io(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            e.to_string(),
+        )));
+    }
+
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| db_error("Failed to open Cursor DB", e))
+}
+
+type ComposerRow = (i64, String, Option<String>, Option<i64>, Option<i64>);
+
+fn to_session(row: ComposerRow) -> ComposerSession {
+    let (rowid, id, context, created_at, updated_at) = row;
+    let (session, warnings) = CursorParser.session_from_composer_row(
+        id.clone(),
+        context.as_deref().unwrap_or("{}"),
+        created_at,
+        updated_at,
+    );
+    ComposerSession {
+        rowid,
+        composer_id: id,
+        updated_at: updated_at.or(created_at).unwrap_or(0),
+        session,
+        warnings,
+    }
+}
+
+/// Chats created or edited after `since`, oldest change first, with the
+/// watermark to pass next time (unchanged when nothing was read).
+pub fn read_changed_sessions(
+    path: &Path,
+    since: ComposerWatermark,
+) -> Result<(Vec<ComposerSession>, ComposerWatermark), ParseError> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT rowid, id, context, createdAt, updatedAt
+            FROM composer_chat
+            WHERE (COALESCE(updatedAt, createdAt, 0), rowid) > (?1, ?2)
+            ORDER BY COALESCE(updatedAt, createdAt, 0), rowid
+            LIMIT ?3
+            "#,
+        )
+        .map_err(|e| db_error("Failed to prepare query", e))?;
+
+    let rows = stmt
+        .query_map(
+            rusqlite::params![since.updated_at, since.rowid, MAX_SESSIONS_PER_READ as i64],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .map_err(|e| db_error("Failed to read composer chats", e))?;
+
+    let mut sessions = Vec::new();
+    let mut watermark = since;
+    for row in rows {
+        let session = to_session(row.map_err(|e| db_error("Failed to read composer chat", e))?);
+        watermark = ComposerWatermark {
+            updated_at: session.updated_at,
+            rowid: session.rowid,
+        };
+        sessions.push(session);
+    }
+
+    Ok((sessions, watermark))
+}
+
+/// The most recently created chat
+pub fn read_latest_session(path: &Path) -> Result<Option<ComposerSession>, ParseError> {
+    let conn = open_read_only(path)?;
+    let mut stmt = conn
+        .prepare(
+            r#"
+            SELECT rowid, id, context, createdAt, updatedAt
+            FROM composer_chat
+            ORDER BY createdAt DESC, rowid DESC
+            LIMIT 1
+            "#,
+        )
+        .map_err(|e| db_error("Failed to prepare query", e))?;
+
+    let mut rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })
+        .map_err(|e| db_error("Failed to read composer chats", e))?;
+
+    rows.next()
+        .transpose()
+        .map(|row| row.map(to_session))
+        .map_err(|e| db_error("Failed to read composer chat", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(text: &str) -> String {
+        serde_json::json!({ "messages": [{ "role": "user", "content": text }] }).to_string()
+    }
+
+    #[test]
+    fn test_reads_only_chats_changed_since_watermark() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join(".cursor/composer");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("composer.database");
+
+        let writer = Connection::open(&path).unwrap();
+        writer
+            .execute_batch(
+                "CREATE TABLE composer_chat (id TEXT, context TEXT, createdAt INTEGER, updatedAt INTEGER);",
+            )
+            .unwrap();
+        for (id, ts) in [("a", 1_000), ("b", 2_000)] {
+            writer
+                .execute(
+                    "INSERT INTO composer_chat VALUES (?1, ?2, ?3, ?3)",
+                    rusqlite::params![id, context(id), ts],
+                )
+                .unwrap();
+        }
+
+        let (first, watermark) =
+            read_changed_sessions(&path, ComposerWatermark::default()).unwrap();
+        assert_eq!(
+            first
+                .iter()
+                .map(|s| s.composer_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            watermark,
+            ComposerWatermark {
+                updated_at: 2_000,
+                rowid: 2
+            }
+        );
+
+        let (none, same) = read_changed_sessions(&path, watermark).unwrap();
+        assert!(none.is_empty());
+        assert_eq!(same, watermark);
+
+        // Editing "a" and adding "c" are both picked up.
+        writer
+            .execute(
+                "UPDATE composer_chat SET context = ?1, updatedAt = 3000 WHERE id = 'a'",
+                [context("a edited")],
+            )
+            .unwrap();
+        writer
+            .execute(
+                "INSERT INTO composer_chat VALUES ('c', ?1, 4000, 4000)",
+                [context("c")],
+            )
+            .unwrap();
+
+        let (changed, _) = read_changed_sessions(&path, watermark).unwrap();
+        assert_eq!(
+            changed
+                .iter()
+                .map(|s| s.composer_id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "c"]
+        );
+        assert_eq!(changed[0].session.trace.messages.len(), 1);
+
+        let latest = read_latest_session(&path).unwrap().expect("latest chat");
+        assert_eq!(latest.composer_id, "c");
+    }
+}
@@ -5,31 +5,39 @@
 //!
 //! Table: composer_chat
 //! Columns: id, context, createdAt, updatedAt
+//!
+//! As a single-file parser this yields the latest chat; `cursor_composer_db`
+//! reads every chat changed since the last import.
 
 use super::{
     parser::{
-        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
-        TraceMessage, WarningSeverity,
+        home_roots, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
     },
     path_validator::PathValidator,
     secure_parser::SecretScanner,
 };
 use crate::session_hash::generate_session_hash;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct CursorParser;
 
 impl super::parser::SessionParser for CursorParser {
     fn can_parse(&self, path: &Path) -> bool {
-        let path_str = path.to_string_lossy().replace('\\', "/");
         // Cursor produces *many* JSON files under ~/.cursor (MCP tool defs, configs, etc).
         // For auto-ingest, restrict to the composer database.
         //
         // Cursor composer database location: ~/.cursor/composer/composer.database
-        path_str.contains(".cursor")
-            && path_str.contains("/composer/")
-            && path_str.ends_with("composer.database")
+        super::cursor_composer_db::is_composer_db(path)
+    }
+
+    fn tool_id(&self, _path: &Path) -> &str {
+        "cursor"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[".cursor/composer"])
     }
 
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
@@ -53,54 +61,40 @@ impl super::parser::SessionParser for CursorParser {
 }
 
 impl CursorParser {
-    /// Parse Cursor's SQLite composer database
+    /// Parse the most recently created chat in Cursor's composer database
     fn parse_sqlite_db(&self, path: &Path) -> ParseResult<ParsedSession> {
-        use rusqlite::{Connection, Result as SqliteResult};
-
-        let conn = match Connection::open(path) {
-            Ok(c) => c,
-            Err(e) => {
+        let composer = match super::cursor_composer_db::read_latest_session(path) {
+            Ok(Some(composer)) => composer,
+            Ok(None) => {
                 return ParseResult::Failure(ParseError::Io(std::io::Error::new(
                     std::io::ErrorKind::InvalidData,
-                    format!("Failed to open Cursor DB: {}", e),
+                    "No sessions found in database",
                 )))
             }
+            Err(e) => return ParseResult::Failure(e),
         };
 
-        // Query the composer_chat table
-        let mut stmt = match conn.prepare(
-            "SELECT id, context, createdAt, updatedAt FROM composer_chat ORDER BY createdAt DESC LIMIT 1"
-        ) {
-            Ok(s) => s,
-            Err(e) => return ParseResult::Failure(ParseError::Io(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("Failed to prepare query: {}", e),
-            ))),
-        };
-
-        let session_result: SqliteResult<(String, String, i64, i64)> = stmt.query_row([], |row| {
-            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
-        });
-
-        let (id, context, created_at, _updated_at) = match session_result {
-            Ok(r) => r,
-            Err(e) => {
-                return ParseResult::Failure(ParseError::Io(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("No sessions found in database: {}", e),
-                )))
-            }
-        };
+        if composer.warnings.is_empty() {
+            ParseResult::Success(composer.session)
+        } else {
+            ParseResult::Partial(composer.session, composer.warnings)
+        }
+    }
 
+    /// Build a session from one `composer_chat` row (timestamps in epoch ms)
+    pub(super) fn session_from_composer_row(
+        &self,
+        id: String,
+        context: &str,
+        created_at: Option<i64>,
+        updated_at: Option<i64>,
+    ) -> (ParsedSession, Vec<ParseWarning>) {
         // Parse context JSON for messages
-        let (trace, warnings) = self.parse_cursor_context(&context);
+        let (trace, warnings) = self.parse_cursor_context(context);
 
         // Generate deterministic session hash
         let session_id = generate_session_hash("cursor", &id);
 
-        // Convert timestamps
-        let started_at = chrono::DateTime::from_timestamp(created_at / 1000, 0);
-
         let session = ParsedSession {
             origin: SessionOrigin {
                 tool: "cursor".to_string(),
@@ -108,17 +102,15 @@ impl CursorParser {
                 conversation_id: id,
                 model: None, // Cursor doesn't expose model in this format
             },
-            started_at,
-            ended_at: None,
+            started_at: created_at.and_then(chrono::DateTime::from_timestamp_millis),
+            ended_at: updated_at.and_then(chrono::DateTime::from_timestamp_millis),
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
-        if warnings.is_empty() {
-            ParseResult::Success(session)
-        } else {
-            ParseResult::Partial(session, warnings)
-        }
+        (session, warnings)
     }
 
     /// Parse a standalone Cursor JSON file
@@ -165,6 +157,8 @@ impl CursorParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -0,0 +1,241 @@
+//! Session format version detection
+//!
+//! Claude Code and Codex change their JSONL schemas between releases. Parsers
+//! describe the schema generations they were tested against in a `FormatSpec`
+//! and feed what they see into a `SchemaTracker`, which turns unrecognized
+//! versions and entry types into structured `ParseWarning`s instead of
+//! silently dropping data. The specs double as the compatibility table
+//! returned by `get_parser_capabilities`.
+
+use super::parser::{ParseWarning, WarningSeverity};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// How well a parser handles a schema version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Compatibility {
+    /// All message kinds we store are extracted
+    Full,
+    /// Importable, but some content is skipped
+    Partial,
+}
+
+/// A schema version known to a parser
+#[derive(Debug, Clone, Copy)]
+pub struct FormatVersion {
+    pub version: &'static str,
+    pub compatibility: Compatibility,
+    pub notes: &'static str,
+}
+
+/// Static description of the formats a parser understands
+#[derive(Debug)]
+pub struct FormatSpec {
+    /// Tool id written to `SessionOrigin::tool`
+    pub tool: &'static str,
+    /// Human-readable format name used in warnings
+    pub name: &'static str,
+    /// Known versions, oldest first
+    pub versions: &'static [FormatVersion],
+    /// Top-level entry types the parser recognizes (others are skipped)
+    pub known_entry_types: &'static [&'static str],
+    /// Whether appended lines can be imported without a full re-parse
+    pub incremental: bool,
+}
+
+impl FormatSpec {
+    fn latest(&self) -> Option<&'static str> {
+        self.versions.last().map(|v| v.version)
+    }
+
+    fn is_known(&self, version: &str) -> bool {
+        self.versions.iter().any(|v| v.version == version)
+    }
+}
+
+/// One row of the compatibility table
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatCompatibility {
+    pub version: String,
+    pub compatibility: Compatibility,
+    pub notes: String,
+}
+
+/// Parser capabilities as reported to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParserCapabilities {
+    pub tool: String,
+    pub name: String,
+    pub incremental: bool,
+    pub formats: Vec<FormatCompatibility>,
+    pub known_entry_types: Vec<String>,
+}
+
+impl From<&FormatSpec> for ParserCapabilities {
+    fn from(spec: &FormatSpec) -> Self {
+        Self {
+            tool: spec.tool.to_string(),
+            name: spec.name.to_string(),
+            incremental: spec.incremental,
+            formats: spec
+                .versions
+                .iter()
+                .map(|v| FormatCompatibility {
+                    version: v.version.to_string(),
+                    compatibility: v.compatibility,
+                    notes: v.notes.to_string(),
+                })
+                .collect(),
+            known_entry_types: spec
+                .known_entry_types
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+        }
+    }
+}
+
+/// Schema version from a semver-ish release string (`"2.0.14"` -> `"v2"`)
+pub fn major_version(release: &str) -> Option<String> {
+    let major = release.trim().trim_start_matches('v').split('.').next()?;
+    major.parse::<u64>().ok().map(|m| format!("v{}", m))
+}
+
+/// Collects format observations while parsing and reports each surprise once
+pub struct SchemaTracker {
+    spec: &'static FormatSpec,
+    detected: Option<String>,
+    reported: HashSet<String>,
+    warnings: Vec<ParseWarning>,
+}
+
+impl SchemaTracker {
+    pub fn new(spec: &'static FormatSpec) -> Self {
+        Self {
+            spec,
+            detected: None,
+            reported: HashSet::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// The first schema version observed, if any
+    pub fn detected_version(&self) -> Option<&str> {
+        self.detected.as_deref()
+    }
+
+    /// Record the schema version declared (or implied) by a line
+    pub fn observe_version(&mut self, version: &str, line_num: usize) {
+        if self.detected.is_none() {
+            self.detected = Some(version.to_string());
+        }
+        if self.spec.is_known(version) || !self.reported.insert(format!("version:{}", version)) {
+            return;
+        }
+        self.warnings.push(ParseWarning {
+            severity: WarningSeverity::Warning,
+            message: format!(
+                "Unknown {} schema {} (tested up to {}); unrecognized fields are skipped",
+                self.spec.name,
+                version,
+                self.spec.latest().unwrap_or("none")
+            ),
+            context: Some(format!("line {}", line_num + 1)),
+        });
+    }
+
+    /// Check a top-level entry type against the spec
+    pub fn observe_entry_type(&mut self, entry_type: &str, line_num: usize) {
+        if !self.spec.known_entry_types.contains(&entry_type) {
+            self.observe_unknown_field("entry type", entry_type, line_num);
+        }
+    }
+
+    /// Report a field or variant the parser does not understand
+    pub fn observe_unknown_field(&mut self, kind: &str, name: &str, line_num: usize) {
+        if !self.reported.insert(format!("{}:{}", kind, name)) {
+            return;
+        }
+        let version = self
+            .detected
+            .as_deref()
+            .or(self.spec.latest())
+            .unwrap_or("unversioned");
+        self.warnings.push(ParseWarning {
+            severity: WarningSeverity::Info,
+            message: format!(
+                "Unknown {} schema {} {} '{}' (skipped)",
+                self.spec.name, version, kind, name
+            ),
+            context: Some(format!("line {}", line_num + 1)),
+        });
+    }
+
+    pub fn into_warnings(self) -> Vec<ParseWarning> {
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static TEST_SPEC: FormatSpec = FormatSpec {
+        tool: "test",
+        name: "Test",
+        versions: &[
+            FormatVersion {
+                version: "v1",
+                compatibility: Compatibility::Partial,
+                notes: "",
+            },
+            FormatVersion {
+                version: "v2",
+                compatibility: Compatibility::Full,
+                notes: "",
+            },
+        ],
+        known_entry_types: &["user", "assistant"],
+        incremental: true,
+    };
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(major_version("2.0.14"), Some("v2".to_string()));
+        assert_eq!(major_version("v1"), Some("v1".to_string()));
+        assert_eq!(major_version("beta"), None);
+    }
+
+    #[test]
+    fn test_unknown_version_and_entry_type_reported_once() {
+        let mut tracker = SchemaTracker::new(&TEST_SPEC);
+        tracker.observe_version("v3", 0);
+        tracker.observe_version("v3", 1);
+        tracker.observe_entry_type("user", 1);
+        tracker.observe_entry_type("checkpoint", 2);
+        tracker.observe_entry_type("checkpoint", 3);
+
+        assert_eq!(tracker.detected_version(), Some("v3"));
+        let warnings = tracker.into_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].severity, WarningSeverity::Warning);
+        assert!(warnings[0].message.contains("schema v3"));
+        assert_eq!(warnings[1].severity, WarningSeverity::Info);
+        assert_eq!(
+            warnings[1].message,
+            "Unknown Test schema v3 entry type 'checkpoint' (skipped)"
+        );
+        assert_eq!(warnings[1].context.as_deref(), Some("line 3"));
+    }
+
+    #[test]
+    fn test_known_formats_produce_no_warnings() {
+        let mut tracker = SchemaTracker::new(&TEST_SPEC);
+        tracker.observe_version("v2", 0);
+        tracker.observe_entry_type("assistant", 0);
+        assert!(tracker.into_warnings().is_empty());
+    }
+}
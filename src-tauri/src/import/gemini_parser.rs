@@ -5,15 +5,15 @@
 
 use super::{
     parser::{
-        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
-        TraceMessage, WarningSeverity,
+        home_roots, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
     },
     path_validator::PathValidator,
     secure_parser::SecretScanner,
 };
 use crate::session_hash::generate_session_hash;
 use serde_json::Value;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct GeminiParser;
 
@@ -25,6 +25,14 @@ impl super::parser::SessionParser for GeminiParser {
             && path.extension().map(|e| e == "json").unwrap_or(false)
     }
 
+    fn tool_id(&self, _path: &Path) -> &str {
+        "gemini"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        home_roots(&[".gemini/tmp"])
+    }
+
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
         // Security: Validate path
         if let Err(e) = PathValidator::validate(path) {
@@ -144,6 +152,8 @@ impl super::parser::SessionParser for GeminiParser {
             ended_at: None,
             trace,
             files_touched: Vec::new(),
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
         };
 
         if warnings.is_empty() {
@@ -0,0 +1,402 @@
+//! Config-driven parser for tools without a built-in parser
+//!
+//! Users describe a tool's log format in a mapping file placed under
+//! `<app data dir>/parser-mappings/<name>.json`. Field locations use JSON Pointer
+//! syntax (RFC 6901), e.g. `/message/content`:
+//!
+//! ```json
+//! {
+//!   "tool": "aider",
+//!   "pathContains": ".aider/sessions/",
+//!   "extension": "jsonl",
+//!   "role": "/role",
+//!   "text": "/content",
+//!   "timestamp": "/ts",
+//!   "files": "/files",
+//!   "roleMap": { "human": "user", "ai": "assistant" }
+//! }
+//! ```
+//!
+//! JSONL files map one line to one message. JSON files read the message array
+//! at `messages` (the whole document when omitted). `GenericMappedParser` is
+//! registered last so built-in parsers always win.
+
+use super::{
+    parser::{
+        parse_json_timestamp, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+};
+use crate::session_hash::generate_session_hash;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Directory (under the app data dir) holding user mapping files
+const MAPPINGS_DIR_NAME: &str = "parser-mappings";
+
+/// A user-supplied description of a session log format
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParserMapping {
+    /// Tool id stored on imported sessions
+    pub tool: String,
+    /// Substring the (slash-normalized) file path must contain
+    pub path_contains: String,
+    /// File extension without the dot; `jsonl` selects line-per-message parsing
+    #[serde(default = "default_extension")]
+    pub extension: String,
+    /// Pointer to the message array (JSON files only)
+    #[serde(default)]
+    pub messages: Option<String>,
+    pub role: String,
+    pub text: String,
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    /// Pointer to a file path string or array of paths touched by the message
+    #[serde(default)]
+    pub files: Option<String>,
+    /// Pointer to a per-message model name
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Pointer to the conversation id (defaults to the file stem)
+    #[serde(default)]
+    pub conversation_id: Option<String>,
+    /// Maps tool-specific role names onto user/assistant/thinking
+    #[serde(default)]
+    pub role_map: HashMap<String, String>,
+}
+
+fn default_extension() -> String {
+    "jsonl".to_string()
+}
+
+impl ParserMapping {
+    fn matches(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        !self.path_contains.is_empty()
+            && path_str.contains(&self.path_contains)
+            && path
+                .extension()
+                .map(|e| e == self.extension.as_str())
+                .unwrap_or(false)
+    }
+}
+
+pub struct GenericMappedParser {
+    mappings: Vec<ParserMapping>,
+}
+
+impl GenericMappedParser {
+    pub fn new(mappings: Vec<ParserMapping>) -> Self {
+        Self { mappings }
+    }
+
+    /// Load mappings from the app data dir. Invalid files are skipped.
+    pub fn load() -> Self {
+        match mappings_dir() {
+            Some(dir) => Self::new(load_mappings_from(&dir)),
+            None => Self::new(Vec::new()),
+        }
+    }
+
+    fn mapping_for(&self, path: &Path) -> Option<&ParserMapping> {
+        self.mappings.iter().find(|m| m.matches(path))
+    }
+}
+
+pub fn mappings_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| {
+        d.join(crate::ingest_config::APP_IDENTIFIER)
+            .join(MAPPINGS_DIR_NAME)
+    })
+}
+
+fn load_mappings_from(dir: &Path) -> Vec<ParserMapping> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    files.sort();
+
+    files
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str::<ParserMapping>(&content).ok())
+        .filter(|m| !m.tool.trim().is_empty())
+        .collect()
+}
+
+impl super::parser::SessionParser for GenericMappedParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        self.mapping_for(path).is_some()
+    }
+
+    fn tool_id(&self, path: &Path) -> &str {
+        self.mapping_for(path)
+            .map(|m| m.tool.as_str())
+            .unwrap_or("unknown")
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        let Some(mapping) = self.mapping_for(path) else {
+            return ParseResult::Failure(ParseError::UnsupportedFormat);
+        };
+
+        // Security: Validate path
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        // Read file content
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+
+        // Security: Check file size
+        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
+        if content.len() > MAX_SIZE {
+            return ParseResult::Failure(ParseError::FileTooLarge);
+        }
+
+        let mut warnings = Vec::new();
+        let entries: Vec<Value> = if mapping.extension == "jsonl" {
+            let mut entries = Vec::new();
+            for (line_num, line) in content.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str(line) {
+                    Ok(v) => entries.push(v),
+                    Err(e) => warnings.push(ParseWarning {
+                        severity: WarningSeverity::Warning,
+                        message: format!("JSON parse error: {}", e),
+                        context: Some(format!("line {}", line_num + 1)),
+                    }),
+                }
+            }
+            entries
+        } else {
+            let json: Value = match serde_json::from_str(&content) {
+                Ok(v) => v,
+                Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+            };
+            let list = match &mapping.messages {
+                Some(pointer) => json.pointer(pointer).cloned(),
+                None => Some(json),
+            };
+            match list {
+                Some(Value::Array(items)) => items,
+                _ => return ParseResult::Failure(ParseError::MissingField("messages")),
+            }
+        };
+
+        let mut trace = SessionTrace::new();
+        let mut model: Option<String> = None;
+        let mut conversation_id: Option<String> = None;
+        let mut files_touched: Vec<String> = Vec::new();
+        let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+
+        for (idx, entry) in entries.iter().enumerate() {
+            if conversation_id.is_none() {
+                conversation_id = lookup_str(entry, mapping.conversation_id.as_deref());
+            }
+            if model.is_none() {
+                model = lookup_str(entry, mapping.model.as_deref());
+            }
+            if let Some(value) = mapping.files.as_deref().and_then(|p| entry.pointer(p)) {
+                match value {
+                    Value::String(p) => files_touched.push(p.clone()),
+                    Value::Array(items) => files_touched
+                        .extend(items.iter().filter_map(Value::as_str).map(String::from)),
+                    _ => {}
+                }
+            }
+
+            let Some(text) = lookup_str(entry, Some(&mapping.text)) else {
+                continue;
+            };
+            if text.trim().is_empty() {
+                continue;
+            }
+
+            let secret_findings = SecretScanner::scan(&text);
+            if !secret_findings.is_empty() {
+                warnings.push(ParseWarning {
+                    severity: WarningSeverity::Security,
+                    message: format!(
+                        "Potential secrets detected: {}",
+                        secret_findings
+                            .iter()
+                            .map(|f| f.kind.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    context: Some(format!("message {}", idx)),
+                });
+            }
+
+            let timestamp = mapping
+                .timestamp
+                .as_deref()
+                .and_then(|p| entry.pointer(p))
+                .and_then(parse_json_timestamp);
+            if let Some(ts) = timestamp {
+                timestamps.push(ts);
+            }
+            let timestamp = timestamp.map(|ts| ts.to_rfc3339());
+
+            let raw_role = lookup_str(entry, Some(&mapping.role)).unwrap_or_default();
+            let role = mapping
+                .role_map
+                .get(&raw_role)
+                .cloned()
+                .unwrap_or(raw_role)
+                .to_lowercase();
+
+            match role.as_str() {
+                "user" => trace.add_message(TraceMessage::User { text, timestamp }),
+                "assistant" => trace.add_message(TraceMessage::Assistant { text, timestamp }),
+                "thinking" => trace.add_message(TraceMessage::Thinking { text, timestamp }),
+                _ => {} // Skip system/unknown roles
+            }
+        }
+
+        files_touched.sort();
+        files_touched.dedup();
+
+        let conversation_id = conversation_id.unwrap_or_else(|| {
+            path.file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("unknown")
+                .to_string()
+        });
+        let session_id = generate_session_hash(&mapping.tool, &conversation_id);
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: mapping.tool.clone(),
+                session_id,
+                conversation_id,
+                model,
+            },
+            started_at: timestamps.iter().min().copied(),
+            ended_at: timestamps.iter().max().copied(),
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+/// Resolve a pointer to a string (numbers are stringified, e.g. numeric ids)
+fn lookup_str(entry: &Value, pointer: Option<&str>) -> Option<String> {
+    match entry.pointer(pointer?)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::SessionParser;
+
+    fn mapping(json: Value) -> ParserMapping {
+        serde_json::from_value(json).expect("valid mapping")
+    }
+
+    #[test]
+    fn test_can_parse_only_matching_paths() {
+        let parser = GenericMappedParser::new(vec![mapping(serde_json::json!({
+            "tool": "aider",
+            "pathContains": ".aider/sessions/",
+            "role": "/role",
+            "text": "/content"
+        }))]);
+
+        assert!(parser.can_parse(Path::new("/home/u/.aider/sessions/s1.jsonl")));
+        assert!(!parser.can_parse(Path::new("/home/u/.aider/sessions/s1.json")));
+        assert!(!parser.can_parse(Path::new("/home/u/other/s1.jsonl")));
+        assert!(!GenericMappedParser::new(Vec::new()).can_parse(Path::new("/any/file.jsonl")));
+    }
+
+    #[test]
+    fn test_parse_jsonl_with_role_map() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("mytool/logs");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("run-1.jsonl");
+        std::fs::write(
+            &path,
+            [
+                r#"{"who": "human", "msg": {"body": "Add tests"}, "ts": 1718000000, "sid": "abc"}"#,
+                r#"{"who": "bot", "msg": {"body": "Done"}, "ts": 1718000060, "paths": ["src/lib.rs"]}"#,
+                r#"{"who": "system", "msg": {"body": "ignored"}}"#,
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let parser = GenericMappedParser::new(vec![mapping(serde_json::json!({
+            "tool": "mytool",
+            "pathContains": "mytool/logs/",
+            "role": "/who",
+            "text": "/msg/body",
+            "timestamp": "/ts",
+            "files": "/paths",
+            "conversationId": "/sid",
+            "roleMap": {"human": "user", "bot": "assistant"}
+        }))]);
+
+        let session = match parser.parse(&path) {
+            ParseResult::Success(s) | ParseResult::Partial(s, _) => s,
+            ParseResult::Failure(e) => panic!("parse failed: {}", e),
+        };
+
+        assert_eq!(session.origin.tool, "mytool");
+        assert_eq!(session.origin.conversation_id, "abc");
+        assert_eq!(session.message_count(), 2);
+        assert_eq!(session.files_touched, vec!["src/lib.rs"]);
+        assert_eq!(
+            session.ended_at.unwrap() - session.started_at.unwrap(),
+            chrono::Duration::seconds(60)
+        );
+    }
+
+    #[test]
+    fn test_load_mappings_skips_invalid_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("good.json"),
+            r#"{"tool": "t", "pathContains": "t/", "role": "/r", "text": "/x"}"#,
+        )
+        .unwrap();
+        std::fs::write(tmp.path().join("bad.json"), "{ not json").unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "ignored").unwrap();
+
+        let mappings = load_mappings_from(tmp.path());
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].extension, "jsonl");
+    }
+}
@@ -0,0 +1,234 @@
+//! JetBrains AI Assistant session parser
+//!
+//! Parses chat logs persisted by the AI Assistant plugin in IntelliJ-based IDEs
+//! (IntelliJ IDEA, PyCharm, WebStorm, ...). Chats are stored as one JSON file per
+//! conversation under the IDE's config directory:
+//! - Linux: ~/.config/JetBrains/<Product><version>/aiAssistant/chats/<chatId>.json
+//! - macOS: ~/Library/Application Support/JetBrains/<Product><version>/aiAssistant/chats/<chatId>.json
+//! - Windows: %APPDATA%\JetBrains\<Product><version>\aiAssistant\chats\<chatId>.json
+
+use super::{
+    parser::{
+        parse_json_timestamp, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+};
+use crate::session_hash::generate_session_hash;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+pub struct JetBrainsParser;
+
+/// Check whether a (slash-normalized, lowercased) path looks like an AI Assistant chat
+pub(crate) fn is_jetbrains_ai_path(path_str: &str) -> bool {
+    path_str.contains("jetbrains")
+        && (path_str.contains("aiassistant")
+            || path_str.contains("ai-assistant")
+            || path_str.contains("ai_assistant"))
+}
+
+impl super::parser::SessionParser for JetBrainsParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/").to_lowercase();
+        is_jetbrains_ai_path(&path_str) && path.extension().map(|e| e == "json").unwrap_or(false)
+    }
+
+    fn tool_id(&self, _path: &Path) -> &str {
+        "jetbrains_ai"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        dirs::config_dir()
+            .map(|dir| vec![dir.join("JetBrains")])
+            .unwrap_or_default()
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        // Security: Validate path
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        // Read file content
+        let content = match std::fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+
+        // Security: Check file size
+        const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
+        if content.len() > MAX_SIZE {
+            return ParseResult::Failure(ParseError::FileTooLarge);
+        }
+
+        let json: Value = match serde_json::from_str(&content) {
+            Ok(v) => v,
+            Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+        };
+
+        // Chats are either `{ "messages": [...] }` or wrapped as `{ "chat": { "messages": [...] } }`
+        let chat = if json["chat"].is_object() {
+            &json["chat"]
+        } else {
+            &json
+        };
+
+        let Some(messages) = chat["messages"].as_array() else {
+            return ParseResult::Failure(ParseError::MissingField("messages"));
+        };
+
+        let conversation_id = chat["id"]
+            .as_str()
+            .or_else(|| chat["chatId"].as_str())
+            .or_else(|| path.file_stem().and_then(|s| s.to_str()))
+            .unwrap_or("unknown")
+            .to_string();
+
+        let session_id = generate_session_hash("jetbrains_ai", &conversation_id);
+
+        let mut model: Option<String> = chat["model"]
+            .as_str()
+            .or_else(|| chat["llm"].as_str())
+            .map(String::from);
+        let mut trace = SessionTrace::new();
+        let mut warnings = Vec::new();
+        let mut files_touched: Vec<String> = Vec::new();
+        let mut timestamps: Vec<chrono::DateTime<chrono::Utc>> = Vec::new();
+
+        for (idx, msg) in messages.iter().enumerate() {
+            let text = msg["text"]
+                .as_str()
+                .or_else(|| msg["content"].as_str())
+                .unwrap_or("")
+                .to_string();
+
+            // Security scan
+            let secret_findings = SecretScanner::scan(&text);
+            if !secret_findings.is_empty() {
+                warnings.push(ParseWarning {
+                    severity: WarningSeverity::Security,
+                    message: format!(
+                        "Potential secrets detected: {}",
+                        secret_findings
+                            .iter()
+                            .map(|f| f.kind.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    context: Some(format!("message {}", idx)),
+                });
+            }
+
+            let timestamp = parse_json_timestamp(&msg["timestamp"])
+                .or_else(|| parse_json_timestamp(&msg["createdAt"]));
+            if let Some(ts) = timestamp {
+                timestamps.push(ts);
+            }
+            let timestamp_str = timestamp.map(|ts| ts.to_rfc3339());
+
+            // Attached editor context (open files, selections) hints at touched files.
+            if let Some(attachments) = msg["attachments"].as_array() {
+                for attachment in attachments {
+                    if let Some(p) = attachment["filePath"]
+                        .as_str()
+                        .or_else(|| attachment["path"].as_str())
+                    {
+                        files_touched.push(p.to_string());
+                    }
+                }
+            }
+
+            let role = msg["role"]
+                .as_str()
+                .or_else(|| msg["author"].as_str())
+                .unwrap_or("")
+                .to_lowercase();
+
+            match role.as_str() {
+                "user" | "human" => trace.add_message(TraceMessage::User {
+                    text,
+                    timestamp: timestamp_str,
+                }),
+                "assistant" | "ai" | "bot" | "model" => {
+                    if model.is_none() {
+                        model = msg["model"].as_str().map(String::from);
+                    }
+                    trace.add_message(TraceMessage::Assistant {
+                        text,
+                        timestamp: timestamp_str,
+                    })
+                }
+                _ => {} // Skip system/unknown messages
+            }
+        }
+
+        files_touched.sort();
+        files_touched.dedup();
+
+        let started_at = timestamps
+            .iter()
+            .min()
+            .copied()
+            .or_else(|| parse_json_timestamp(&chat["createdAt"]));
+        let ended_at = timestamps
+            .iter()
+            .max()
+            .copied()
+            .or_else(|| parse_json_timestamp(&chat["updatedAt"]));
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: "jetbrains_ai".to_string(),
+                session_id,
+                conversation_id,
+                model,
+            },
+            started_at,
+            ended_at,
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::SessionParser;
+
+    #[test]
+    fn test_can_parse_jetbrains_files() {
+        let parser = JetBrainsParser;
+
+        let linux_path = Path::new(
+            "/home/user/.config/JetBrains/IntelliJIdea2024.1/aiAssistant/chats/chat-1.json",
+        );
+        assert!(parser.can_parse(linux_path));
+
+        let mac_path = Path::new(
+            "/Users/user/Library/Application Support/JetBrains/PyCharm2024.1/aiAssistant/chats/chat-2.json",
+        );
+        assert!(parser.can_parse(mac_path));
+
+        // Other IDE config files are not chats
+        let options_path =
+            Path::new("/home/user/.config/JetBrains/IntelliJIdea2024.1/options/editor.xml");
+        assert!(!parser.can_parse(options_path));
+
+        let other_path = Path::new("/some/other/path.json");
+        assert!(!parser.can_parse(other_path));
+    }
+}
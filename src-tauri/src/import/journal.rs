@@ -0,0 +1,120 @@
+//! Crash-safe journal for batch imports and backfills
+//!
+//! Before a batch starts, every candidate file is written to `import_journal`
+//! under a fresh batch id. Each row is deleted once its file has been handled
+//! (for batch imports, in the same transaction that stores the session), and the
+//! batch is cleared when it finishes or is cancelled. Whatever is still in the
+//! journal at startup was interrupted by a crash and is resumed by
+//! `resume_pending_imports`.
+
+use rand::{distr::Alphanumeric, Rng};
+use std::collections::BTreeMap;
+
+/// Manual multi-file import (`import_session_files`)
+pub const KIND_BATCH: &str = "batch";
+/// Watch-path backfill (`backfill_recent_sessions`)
+pub const KIND_BACKFILL: &str = "backfill";
+
+/// Files of an interrupted batch that were never handled
+#[derive(Debug, Clone)]
+pub struct PendingBatch {
+    pub batch_id: String,
+    pub repo_id: i64,
+    pub kind: String,
+    pub paths: Vec<String>,
+}
+
+fn new_batch_id() -> String {
+    rand::rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect()
+}
+
+/// Record the files a batch is about to import; returns the batch id
+pub async fn plan_batch(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    kind: &str,
+    paths: &[String],
+) -> Result<String, sqlx::Error> {
+    let batch_id = new_batch_id();
+    let mut tx = db.begin().await?;
+    for path in paths {
+        sqlx::query(
+            r#"
+            INSERT INTO import_journal (batch_id, repo_id, kind, file_path)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(batch_id, file_path) DO NOTHING
+            "#,
+        )
+        .bind(&batch_id)
+        .bind(repo_id)
+        .bind(kind)
+        .bind(path)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(batch_id)
+}
+
+/// Mark one file of a batch as handled (imported, skipped, or failed)
+pub async fn complete_file(
+    conn: &mut sqlx::SqliteConnection,
+    batch_id: &str,
+    path: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM import_journal WHERE batch_id = ? AND file_path = ?")
+        .bind(batch_id)
+        .bind(path)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Drop whatever is left of a batch (finished or cancelled)
+pub async fn clear_batch(db: &sqlx::SqlitePool, batch_id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM import_journal WHERE batch_id = ?")
+        .bind(batch_id)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Batches interrupted before all their files were handled, oldest first
+pub async fn pending_batches(db: &sqlx::SqlitePool) -> Result<Vec<PendingBatch>, sqlx::Error> {
+    let rows: Vec<(String, i64, String, String)> = sqlx::query_as(
+        r#"
+        SELECT batch_id, repo_id, kind, file_path
+        FROM import_journal
+        ORDER BY planned_at, batch_id, rowid
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut order: Vec<String> = Vec::new();
+    let mut batches: BTreeMap<String, PendingBatch> = BTreeMap::new();
+    for (batch_id, repo_id, kind, path) in rows {
+        batches
+            .entry(batch_id.clone())
+            .or_insert_with(|| {
+                order.push(batch_id.clone());
+                PendingBatch {
+                    batch_id,
+                    repo_id,
+                    kind,
+                    paths: Vec::new(),
+                }
+            })
+            .paths
+            .push(path);
+    }
+
+    Ok(order
+        .into_iter()
+        .filter_map(|id| batches.remove(&id))
+        .collect())
+}
@@ -0,0 +1,202 @@
+//! Memory-bounded line reading for large session files
+//!
+//! JSONL sessions are streamed line by line instead of being read into one
+//! string. Reading stops once `maxParseMb` of the file has been consumed (the
+//! rest of the session is dropped with a warning), and single lines longer than
+//! `MAX_LINE_BYTES` are skipped without being buffered, so a multi-hundred-MB
+//! Codex rollout imports its first part instead of exhausting memory.
+
+use super::parser::{ParseWarning, WarningSeverity};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bytes of a session file read before the rest is dropped (when not configured)
+pub const DEFAULT_MAX_PARSE_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Longest single line kept; tool outputs beyond this are skipped
+pub const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+static MAX_PARSE_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_PARSE_BYTES);
+
+/// Current per-file read budget
+pub fn max_parse_bytes() -> u64 {
+    MAX_PARSE_BYTES.load(Ordering::Relaxed)
+}
+
+/// Set the per-file read budget from the ingest config (0 restores the default)
+pub fn set_max_parse_bytes(bytes: u64) {
+    let bytes = if bytes == 0 {
+        DEFAULT_MAX_PARSE_BYTES
+    } else {
+        bytes
+    };
+    MAX_PARSE_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// Lines of a file, read within a byte budget
+///
+/// Yields `(line index, text)`; skipped lines still advance the index so
+/// warnings point at the right line. Collected warnings and any I/O error are
+/// returned by `finish`.
+pub struct BoundedLines<R> {
+    reader: R,
+    budget: u64,
+    consumed: u64,
+    line_num: usize,
+    truncated: bool,
+    warnings: Vec<ParseWarning>,
+    error: Option<std::io::Error>,
+}
+
+/// Open `path` for bounded reading with the configured budget
+pub fn open(path: &Path) -> std::io::Result<BoundedLines<BufReader<std::fs::File>>> {
+    let file = std::fs::File::open(path)?;
+    Ok(BoundedLines::new(BufReader::new(file), max_parse_bytes()))
+}
+
+impl<R: BufRead> BoundedLines<R> {
+    pub fn new(reader: R, budget: u64) -> Self {
+        Self {
+            reader,
+            budget,
+            consumed: 0,
+            line_num: 0,
+            truncated: false,
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Warnings about skipped or dropped content, or the error that stopped reading
+    pub fn finish(self) -> Result<Vec<ParseWarning>, std::io::Error> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.warnings),
+        }
+    }
+
+    /// Read one raw line; the flag is set when it was too long and discarded
+    fn read_line(&mut self) -> std::io::Result<Option<(Vec<u8>, bool)>> {
+        let mut buf = Vec::new();
+        let mut too_long = false;
+        let mut read_any = false;
+        loop {
+            let available = self.reader.fill_buf()?;
+            if available.is_empty() {
+                return Ok(read_any.then_some((buf, too_long)));
+            }
+            read_any = true;
+
+            let (chunk, done) = match available.iter().position(|b| *b == b'\n') {
+                Some(i) => (&available[..=i], true),
+                None => (available, false),
+            };
+            let len = chunk.len();
+            if !too_long {
+                if buf.len() + len > MAX_LINE_BYTES {
+                    too_long = true;
+                    buf = Vec::new();
+                } else {
+                    buf.extend_from_slice(chunk);
+                }
+            }
+            self.reader.consume(len);
+            self.consumed += len as u64;
+
+            if done {
+                return Ok(Some((buf, too_long)));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BoundedLines<R> {
+    type Item = (usize, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.error.is_some() || self.truncated {
+                return None;
+            }
+
+            if self.consumed >= self.budget {
+                if matches!(self.reader.fill_buf(), Ok(rest) if !rest.is_empty()) {
+                    self.truncated = true;
+                    self.warnings.push(ParseWarning {
+                        severity: WarningSeverity::Warning,
+                        message: format!(
+                            "Session truncated: only the first {} MB of the file were read",
+                            self.budget / (1024 * 1024)
+                        ),
+                        context: Some(format!("line {}", self.line_num + 1)),
+                    });
+                }
+                return None;
+            }
+
+            let line_num = self.line_num;
+            match self.read_line() {
+                Ok(None) => return None,
+                Ok(Some((bytes, too_long))) => {
+                    self.line_num += 1;
+                    if too_long {
+                        self.warnings.push(ParseWarning {
+                            severity: WarningSeverity::Warning,
+                            message: format!(
+                                "Skipped line longer than {} MB",
+                                MAX_LINE_BYTES / (1024 * 1024)
+                            ),
+                            context: Some(format!("line {}", line_num + 1)),
+                        });
+                        continue;
+                    }
+
+                    let mut text = String::from_utf8(bytes)
+                        .unwrap_or_else(|e| String::from_utf8_lossy(e.as_bytes()).into_owned());
+                    let trimmed = text.trim_end_matches(['\n', '\r']).len();
+                    text.truncate(trimmed);
+                    return Some((line_num, text));
+                }
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stops_at_budget_with_warning() {
+        let content = "first\nsecond\r\nthird\nfourth\n";
+        let mut lines = BoundedLines::new(content.as_bytes(), 13);
+
+        let read: Vec<(usize, String)> = lines.by_ref().collect();
+        assert_eq!(
+            read,
+            vec![(0, "first".to_string()), (1, "second".to_string())]
+        );
+
+        let warnings = lines.finish().unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("truncated"));
+    }
+
+    #[test]
+    fn test_skips_overlong_lines() {
+        let long = "x".repeat(MAX_LINE_BYTES + 1);
+        let content = format!("a\n{long}\nb");
+        let mut lines = BoundedLines::new(content.as_bytes(), u64::MAX);
+
+        let read: Vec<(usize, String)> = lines.by_ref().collect();
+        assert_eq!(read, vec![(0, "a".to_string()), (2, "b".to_string())]);
+
+        let warnings = lines.finish().unwrap();
+        assert_eq!(warnings[0].context.as_deref(), Some("line 2"));
+    }
+}
@@ -3,27 +3,52 @@
 //! Provides pluggable parsers for different AI coding tools.
 //! All parsers implement security scanning before returning data.
 
+pub mod backfill_scheduler;
+pub mod chatgpt_export_parser;
 pub mod claude_parser;
+pub mod cline_parser;
 pub mod codex_parser;
 pub mod codex_sessions_parser;
+pub mod cody_parser;
 pub mod commands;
+pub mod compression;
 pub mod continue_parser;
 pub mod copilot_parser;
+pub mod cursor_composer_db;
 pub mod cursor_parser;
+pub mod format_version;
 pub mod gemini_parser;
+pub mod generic_mapped_parser;
+pub mod goose_parser;
+pub mod jetbrains_parser;
+pub mod journal;
+pub mod line_reader;
+pub mod opencode_parser;
 pub mod parser;
 pub mod path_validator;
+pub mod plugin_parser;
+pub mod quarantine;
 pub mod redactor;
 pub mod secure_parser;
+pub mod text_parser;
 pub mod tool_sanitizer;
+pub mod trace_chunks;
+pub mod trimming;
 
 use claude_parser::ClaudeCodeParser;
+use cline_parser::ClineParser;
 use codex_parser::CodexLogParser;
 use codex_sessions_parser::CodexSessionJsonlParser;
+use cody_parser::CodyParser;
 use continue_parser::ContinueParser;
 use copilot_parser::CopilotParser;
 use cursor_parser::CursorParser;
+use format_version::ParserCapabilities;
 use gemini_parser::GeminiParser;
+use generic_mapped_parser::GenericMappedParser;
+use goose_parser::GooseParser;
+use jetbrains_parser::JetBrainsParser;
+use opencode_parser::OpencodeParser;
 use parser::{ParseError, ParseResult, ParsedSession, SessionParser};
 
 /// Registry of available session parsers
@@ -43,10 +68,23 @@ impl ParserRegistry {
         registry.register(Box::new(CodexSessionJsonlParser));
         registry.register(Box::new(CodexLogParser));
         registry.register(Box::new(CursorParser));
+        registry.register(Box::new(ClineParser));
+        registry.register(Box::new(JetBrainsParser));
+        registry.register(Box::new(CodyParser));
+        registry.register(Box::new(GooseParser));
+        registry.register(Box::new(OpencodeParser));
         registry.register(Box::new(GeminiParser));
         registry.register(Box::new(CopilotParser));
         registry.register(Box::new(ContinueParser));
 
+        // External parser plugins (subprocesses) from the app data dir
+        for plugin in plugin_parser::load_plugins() {
+            registry.register(Box::new(plugin));
+        }
+
+        // User-defined mappings are a fallback for tools without a built-in parser
+        registry.register(Box::new(GenericMappedParser::load()));
+
         registry
     }
 
@@ -63,8 +101,35 @@ impl ParserRegistry {
             .map(|p| p.as_ref())
     }
 
+    /// Default session locations of every registered parser, without duplicates
+    pub fn default_roots(&self) -> Vec<std::path::PathBuf> {
+        let mut roots: Vec<std::path::PathBuf> = Vec::new();
+        for root in self.parsers.iter().flat_map(|p| p.default_roots()) {
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    /// Compatibility table for parsers that detect format versions
+    pub fn capabilities(&self) -> Vec<ParserCapabilities> {
+        self.parsers
+            .iter()
+            .filter_map(|p| p.format_spec())
+            .map(ParserCapabilities::from)
+            .collect()
+    }
+
     /// Parse a file using the appropriate parser
+    ///
+    /// Compressed files (`.gz`, `.zst`) are decompressed first and parsed as the
+    /// file they were before compression.
     pub fn parse(&self, path: &std::path::Path) -> ParseResult<ParsedSession> {
+        if compression::Compression::detect(path).is_some() {
+            return self.parse_compressed(path);
+        }
+
         let parser = match self.find_parser(path) {
             Some(p) => p,
             None => return ParseResult::Failure(ParseError::UnsupportedFormat),
@@ -72,6 +137,22 @@ impl ParserRegistry {
 
         parser.parse(path)
     }
+
+    fn parse_compressed(&self, path: &std::path::Path) -> ParseResult<ParsedSession> {
+        // Security: Validate the original location; the decompressed copy lives in temp.
+        if let Err(e) = path_validator::PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        let decompressed = match compression::decompress_to_temp(path) {
+            Ok(file) => file,
+            Err(e) => return ParseResult::Failure(ParseError::Io(e)),
+        };
+        self.parse(decompressed.path())
+    }
 }
 
 impl Default for ParserRegistry {
@@ -0,0 +1,361 @@
+//! opencode session parser
+//!
+//! opencode persists each session as a tree of small JSON files under its storage
+//! directory (~/.local/share/opencode/storage):
+//! - session/<projectId>/<sessionId>.json   session info (id, title, time)
+//! - message/<sessionId>/<messageId>.json   message info (role, modelID, time)
+//! - part/<messageId>/<partId>.json         message parts (text, tool, ...)
+//!
+//! Older releases nested everything under `session/` instead
+//! (`session/info/<id>.json`, `session/message/<sid>/...`, `session/part/<sid>/<mid>/...`).
+//! The session info file is the entry point; messages and parts are resolved
+//! relative to the enclosing `storage` directory.
+
+use super::{
+    parser::{
+        home_roots, ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin,
+        SessionTrace, TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+    tool_sanitizer::ToolSanitizer,
+};
+use crate::session_hash::generate_session_hash;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+/// Maximum combined size of all files that make up one session
+const MAX_SIZE: usize = 100 * 1024 * 1024; // 100MB
+
+pub struct OpencodeParser;
+
+impl super::parser::SessionParser for OpencodeParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        path_str.contains("opencode/storage/session/")
+            && !path_str.contains("/session/message/")
+            && !path_str.contains("/session/part/")
+            && path.extension().map(|e| e == "json").unwrap_or(false)
+    }
+
+    fn tool_id(&self, _path: &Path) -> &str {
+        "opencode"
+    }
+
+    fn default_roots(&self) -> Vec<PathBuf> {
+        let mut roots = home_roots(&[".local/share/opencode/storage/session"]);
+        if let Some(data) = dirs::data_dir() {
+            roots.push(data.join("opencode/storage/session"));
+        }
+        roots
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        let mut budget = MAX_SIZE;
+        let info = match read_json(path, &mut budget) {
+            Ok(v) => v,
+            Err(e) => return ParseResult::Failure(e),
+        };
+
+        let Some(session_key) = info["id"].as_str().map(String::from) else {
+            return ParseResult::Failure(ParseError::MissingField("id"));
+        };
+        let Some(storage_root) = find_storage_root(path) else {
+            return ParseResult::Failure(ParseError::UnsupportedFormat);
+        };
+
+        let mut warnings = Vec::new();
+        let mut messages = Vec::new();
+        for file in list_json_files(&message_dirs(&storage_root, &session_key)) {
+            match read_json(&file, &mut budget) {
+                Ok(msg) => messages.push(msg),
+                Err(ParseError::FileTooLarge) => {
+                    return ParseResult::Failure(ParseError::FileTooLarge)
+                }
+                Err(e) => warnings.push(ParseWarning {
+                    severity: WarningSeverity::Warning,
+                    message: format!("Failed to read message: {}", e),
+                    context: file.file_name().map(|n| n.to_string_lossy().to_string()),
+                }),
+            }
+        }
+        messages.sort_by(|a, b| {
+            let a_key = (a["time"]["created"].as_i64(), a["id"].as_str());
+            let b_key = (b["time"]["created"].as_i64(), b["id"].as_str());
+            a_key.cmp(&b_key)
+        });
+
+        let mut trace = SessionTrace::new();
+        let mut model: Option<String> = None;
+        let mut files_touched: Vec<String> = Vec::new();
+
+        for msg in &messages {
+            let Some(message_id) = msg["id"].as_str() else {
+                continue;
+            };
+            let role = msg["role"].as_str().unwrap_or("");
+            if model.is_none() && role == "assistant" {
+                model = msg["modelID"].as_str().map(String::from);
+            }
+            let timestamp = msg["time"]["created"]
+                .as_i64()
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|ts| ts.to_rfc3339());
+
+            let part_files = list_json_files(&part_dirs(&storage_root, &session_key, message_id));
+            for file in part_files {
+                let part = match read_json(&file, &mut budget) {
+                    Ok(p) => p,
+                    Err(ParseError::FileTooLarge) => {
+                        return ParseResult::Failure(ParseError::FileTooLarge)
+                    }
+                    Err(_) => continue,
+                };
+
+                match part["type"].as_str() {
+                    Some("text") => {
+                        let Some(text) = part["text"].as_str() else {
+                            continue;
+                        };
+                        if text.trim().is_empty() {
+                            continue;
+                        }
+                        let secret_findings = SecretScanner::scan(text);
+                        if !secret_findings.is_empty() {
+                            warnings.push(ParseWarning {
+                                severity: WarningSeverity::Security,
+                                message: format!(
+                                    "Potential secrets detected: {}",
+                                    secret_findings
+                                        .iter()
+                                        .map(|f| f.kind.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                ),
+                                context: Some(format!("message {}", message_id)),
+                            });
+                        }
+                        match role {
+                            "user" => trace.add_message(TraceMessage::User {
+                                text: text.to_string(),
+                                timestamp: timestamp.clone(),
+                            }),
+                            "assistant" => trace.add_message(TraceMessage::Assistant {
+                                text: text.to_string(),
+                                timestamp: timestamp.clone(),
+                            }),
+                            _ => {}
+                        }
+                    }
+                    Some("reasoning") => {
+                        if let Some(text) = part["text"].as_str() {
+                            if !text.trim().is_empty() {
+                                trace.add_message(TraceMessage::Thinking {
+                                    text: text.to_string(),
+                                    timestamp: timestamp.clone(),
+                                });
+                            }
+                        }
+                    }
+                    Some("tool") => {
+                        let Some(tool_name) = part["tool"].as_str() else {
+                            continue;
+                        };
+                        let input = &part["state"]["input"];
+                        for key in ["filePath", "path", "file_path"] {
+                            if let Some(p) = input[key].as_str() {
+                                files_touched.push(p.to_string());
+                            }
+                        }
+                        trace.add_message(TraceMessage::ToolCall {
+                            tool_name: tool_name.to_string(),
+                            input: ToolSanitizer::sanitize(tool_name, input),
+                            timestamp: timestamp.clone(),
+                        });
+                    }
+                    _ => {} // Skip step markers, snapshots, patches
+                }
+            }
+        }
+
+        files_touched.sort();
+        files_touched.dedup();
+
+        let started_at = info["time"]["created"]
+            .as_i64()
+            .and_then(chrono::DateTime::from_timestamp_millis);
+        let ended_at = info["time"]["updated"]
+            .as_i64()
+            .and_then(chrono::DateTime::from_timestamp_millis);
+
+        let session_id = generate_session_hash("opencode", &session_key);
+
+        let session = ParsedSession {
+            origin: SessionOrigin {
+                tool: "opencode".to_string(),
+                session_id,
+                conversation_id: session_key,
+                model,
+            },
+            started_at,
+            ended_at,
+            trace,
+            files_touched,
+            git_branch: None,
+            edit_fingerprints: Vec::new(),
+        };
+
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+/// Validate, size-check, and parse one JSON file, charging its size to `budget`
+fn read_json(path: &Path, budget: &mut usize) -> Result<Value, ParseError> {
+    // Security: Validate path
+    PathValidator::validate(path).map_err(|e| {
+        ParseError::Io(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            e.to_string(),
+        ))
+    })?;
+
+    let content = std::fs::read_to_string(path)?;
+
+    // Security: Check cumulative size
+    if content.len() > *budget {
+        return Err(ParseError::FileTooLarge);
+    }
+    *budget -= content.len();
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Walk up from the session info file to the `storage` directory
+fn find_storage_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|p| p.file_name().map(|n| n == "storage").unwrap_or(false))
+        .map(Path::to_path_buf)
+}
+
+fn message_dirs(root: &Path, session_key: &str) -> Vec<PathBuf> {
+    vec![
+        root.join("message").join(session_key),
+        root.join("session/message").join(session_key),
+    ]
+}
+
+fn part_dirs(root: &Path, session_key: &str, message_id: &str) -> Vec<PathBuf> {
+    vec![
+        root.join("part").join(message_id),
+        root.join("session/part").join(session_key).join(message_id),
+    ]
+}
+
+/// JSON files directly inside the given directories, sorted by name
+/// (opencode ids are time-ordered, so name order is creation order).
+fn list_json_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Skip symlinks to prevent traversal outside the storage tree.
+            if !file_type.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            if path.extension().map(|e| e == "json").unwrap_or(false) {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::SessionParser;
+
+    #[test]
+    fn test_can_parse_opencode_files() {
+        let parser = OpencodeParser;
+
+        let session_info =
+            Path::new("/home/user/.local/share/opencode/storage/session/proj1/ses_abc.json");
+        assert!(parser.can_parse(session_info));
+
+        let legacy_info =
+            Path::new("/home/user/.local/share/opencode/storage/session/info/ses_abc.json");
+        assert!(parser.can_parse(legacy_info));
+
+        // Message and part files are resolved from the session, not imported directly
+        let message =
+            Path::new("/home/user/.local/share/opencode/storage/message/ses_abc/msg_1.json");
+        assert!(!parser.can_parse(message));
+        let legacy_part = Path::new(
+            "/home/user/.local/share/opencode/storage/session/part/ses_abc/msg_1/prt_1.json",
+        );
+        assert!(!parser.can_parse(legacy_part));
+    }
+
+    #[test]
+    fn test_parse_session_tree() {
+        let tmp = tempfile::tempdir().unwrap();
+        let storage = tmp.path().join("opencode/storage");
+        let write = |rel: &str, body: &str| {
+            let p = storage.join(rel);
+            std::fs::create_dir_all(p.parent().unwrap()).unwrap();
+            std::fs::write(p, body).unwrap();
+        };
+
+        write(
+            "session/proj1/ses_1.json",
+            r#"{"id": "ses_1", "title": "demo", "time": {"created": 1718000000000, "updated": 1718000600000}}"#,
+        );
+        write(
+            "message/ses_1/msg_1.json",
+            r#"{"id": "msg_1", "role": "user", "time": {"created": 1718000000000}}"#,
+        );
+        write(
+            "message/ses_1/msg_2.json",
+            r#"{"id": "msg_2", "role": "assistant", "modelID": "claude-sonnet-4", "time": {"created": 1718000060000}}"#,
+        );
+        write(
+            "part/msg_1/prt_1.json",
+            r#"{"id": "prt_1", "type": "text", "text": "Rename the helper"}"#,
+        );
+        write(
+            "part/msg_2/prt_1.json",
+            r#"{"id": "prt_1", "type": "tool", "tool": "edit", "state": {"input": {"filePath": "/repo/src/util.ts"}}}"#,
+        );
+        write(
+            "part/msg_2/prt_2.json",
+            r#"{"id": "prt_2", "type": "text", "text": "Done."}"#,
+        );
+
+        let session = match OpencodeParser.parse(&storage.join("session/proj1/ses_1.json")) {
+            ParseResult::Success(s) | ParseResult::Partial(s, _) => s,
+            ParseResult::Failure(e) => panic!("parse failed: {}", e),
+        };
+
+        assert_eq!(session.origin.tool, "opencode");
+        assert_eq!(session.origin.conversation_id, "ses_1");
+        assert_eq!(session.origin.model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(session.files_touched, vec!["/repo/src/util.ts"]);
+        assert_eq!(session.message_count(), 3);
+        assert!(matches!(
+            session.trace.messages.first(),
+            Some(TraceMessage::User { .. })
+        ));
+    }
+}
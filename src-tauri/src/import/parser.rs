@@ -1,7 +1,7 @@
 //! Parser trait and result types for session import
 
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Result of a parse attempt with support for partial success
 pub enum ParseResult<T> {
@@ -13,6 +13,32 @@ pub enum ParseResult<T> {
     Failure(ParseError),
 }
 
+impl<T> ParseResult<T> {
+    /// Transform the parsed value, keeping any warnings
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ParseResult<U> {
+        match self {
+            ParseResult::Success(v) => ParseResult::Success(f(v)),
+            ParseResult::Partial(v, warnings) => ParseResult::Partial(f(v), warnings),
+            ParseResult::Failure(e) => ParseResult::Failure(e),
+        }
+    }
+
+    /// Add warnings raised outside the parser itself (e.g. while reading the file)
+    pub fn with_warnings(self, extra: Vec<ParseWarning>) -> ParseResult<T> {
+        if extra.is_empty() {
+            return self;
+        }
+        match self {
+            ParseResult::Success(v) => ParseResult::Partial(v, extra),
+            ParseResult::Partial(v, mut warnings) => {
+                warnings.extend(extra);
+                ParseResult::Partial(v, warnings)
+            }
+            ParseResult::Failure(e) => ParseResult::Failure(e),
+        }
+    }
+}
+
 /// Warning levels for parse issues
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum WarningSeverity {
@@ -95,6 +121,115 @@ pub trait SessionParser: Send + Sync {
     /// 4. Continue on recoverable errors, collecting warnings
     /// 5. Return Partial result if warnings exist
     fn parse(&self, path: &Path) -> ParseResult<ParsedSession>;
+
+    /// Parse only the complete lines appended after `offset` (JSONL formats)
+    ///
+    /// The returned session holds just the new messages; callers merge it into
+    /// the stored session. Returns None when the format has no stable line
+    /// boundaries, in which case the whole file must be re-parsed.
+    fn parse_appended(&self, _path: &Path, _offset: u64) -> Option<ParseResult<AppendedLines>> {
+        None
+    }
+
+    /// Schema versions this parser detects (None if it does no version detection)
+    fn format_spec(&self) -> Option<&'static super::format_version::FormatSpec> {
+        None
+    }
+
+    /// Tool id for a file this parser claims (used to label scan results)
+    fn tool_id(&self, _path: &Path) -> &str {
+        "unknown"
+    }
+
+    /// Files or directories where the tool writes sessions by default
+    ///
+    /// `scan_for_session_files` walks these; parsers without a fixed location
+    /// (plugins, user mappings) return none.
+    fn default_roots(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+}
+
+/// Parse a session timestamp: RFC 3339, or local time without an offset.
+///
+/// Timestamps without an offset are read as UTC. Tools that log local time
+/// are shifted back into line by their clock offset when linking (see
+/// `clock_offsets`).
+pub fn parse_session_timestamp(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&chrono::Utc));
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| chrono::NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Parse a JSON timestamp: a string as in `parse_session_timestamp`, or epoch
+/// seconds/milliseconds
+pub fn parse_json_timestamp(value: &serde_json::Value) -> Option<chrono::DateTime<chrono::Utc>> {
+    match value.as_i64() {
+        Some(n) => epoch_timestamp(n),
+        None => value.as_str().and_then(parse_session_timestamp),
+    }
+}
+
+/// Epoch seconds or milliseconds as a UTC timestamp
+pub fn epoch_timestamp(n: i64) -> Option<chrono::DateTime<chrono::Utc>> {
+    // Values this large can only be milliseconds (year 2001+ in ms).
+    if n > 1_000_000_000_000 {
+        chrono::DateTime::from_timestamp_millis(n)
+    } else {
+        chrono::DateTime::from_timestamp(n, 0)
+    }
+}
+
+/// `relative` paths under the home directory (empty if it can't be determined)
+pub fn home_roots(relative: &[&str]) -> Vec<PathBuf> {
+    dirs::home_dir()
+        .map(|home| relative.iter().map(|r| home.join(r)).collect())
+        .unwrap_or_default()
+}
+
+/// Messages parsed from lines appended to a session file
+pub struct AppendedLines {
+    pub session: ParsedSession,
+    /// Byte offset just past the last complete line that was consumed
+    pub next_offset: u64,
+}
+
+/// Read the complete lines appended to a file after `offset`
+///
+/// A trailing partial line (still being written) is left for the next read.
+/// Returns the text and the offset just past the last newline consumed.
+pub fn read_appended_lines(path: &Path, offset: u64) -> Result<(String, u64), ParseError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    // Security: Validate path
+    super::path_validator::PathValidator::validate(path).map_err(|e| {
+        ParseError::Io(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            e.to_string(),
+        ))
+    })?;
+
+    let mut file = std::fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+
+    // Security: Check size of the appended region
+    const MAX_SIZE: u64 = 100 * 1024 * 1024; // 100MB
+    let mut buf = Vec::new();
+    file.take(MAX_SIZE + 1).read_to_end(&mut buf)?;
+    if buf.len() as u64 > MAX_SIZE {
+        return Err(ParseError::FileTooLarge);
+    }
+
+    let complete = buf.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    buf.truncate(complete);
+    let text = String::from_utf8(buf)
+        .map_err(|e| ParseError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+
+    Ok((text, offset + complete as u64))
 }
 
 /// Origin information for a session
@@ -143,6 +278,19 @@ pub enum TraceMessage {
     },
 }
 
+impl TraceMessage {
+    /// Timestamp as logged by the tool, if any
+    pub fn timestamp(&self) -> Option<&str> {
+        match self {
+            TraceMessage::User { timestamp, .. }
+            | TraceMessage::Assistant { timestamp, .. }
+            | TraceMessage::Thinking { timestamp, .. }
+            | TraceMessage::Plan { timestamp, .. }
+            | TraceMessage::ToolCall { timestamp, .. } => timestamp.as_deref(),
+        }
+    }
+}
+
 /// Complete trace of a coding session
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionTrace {
@@ -156,6 +304,14 @@ impl SessionTrace {
         }
     }
 
+    /// Parseable message timestamps, in message order
+    pub fn timestamps(&self) -> impl Iterator<Item = chrono::DateTime<chrono::Utc>> + '_ {
+        self.messages
+            .iter()
+            .filter_map(TraceMessage::timestamp)
+            .filter_map(parse_session_timestamp)
+    }
+
     pub fn add_message(&mut self, message: TraceMessage) {
         self.messages.push(message);
     }
@@ -177,6 +333,13 @@ pub struct ParsedSession {
     pub ended_at: Option<chrono::DateTime<chrono::Utc>>,
     pub trace: SessionTrace,
     pub files_touched: Vec<String>,
+    /// Branch checked out while the session ran, when the tool records it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_branch: Option<String>,
+    /// Fingerprints of lines written by edit tool calls (see
+    /// `linking::edit_fingerprints`); the edits themselves are not kept
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edit_fingerprints: Vec<String>,
 }
 
 impl ParsedSession {
@@ -185,3 +348,24 @@ impl ParsedSession {
         self.trace.messages.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_timestamp_formats() {
+        let iso = serde_json::json!("2024-06-10T06:13:20Z");
+        let expected = parse_json_timestamp(&iso);
+        assert!(expected.is_some());
+        let ms = serde_json::json!(1718000000000i64);
+        assert_eq!(parse_json_timestamp(&ms), expected);
+        let secs = serde_json::json!(1718000000i64);
+        assert_eq!(parse_json_timestamp(&secs), expected);
+        // No offset: read as UTC
+        let local = serde_json::json!("2024-06-10 06:13:20");
+        assert_eq!(parse_json_timestamp(&local), expected);
+        assert!(parse_json_timestamp(&serde_json::Value::Null).is_none());
+        assert!(parse_json_timestamp(&serde_json::json!("yesterday")).is_none());
+    }
+}
@@ -126,10 +126,39 @@ impl PathValidator {
             // Codex
             Self::push_unique(&mut dirs, home.join(".codex"));
 
+            // Goose
+            Self::push_unique(&mut dirs, home.join(".local/share/goose"));
+
+            // opencode
+            Self::push_unique(&mut dirs, home.join(".local/share/opencode"));
+
             // Generic
             Self::push_unique(&mut dirs, home.join(".config"));
         }
 
+        // User-selected exports (e.g. ChatGPT `conversations.json`) are usually unzipped here
+        if let Some(downloads) = dirs::download_dir() {
+            Self::push_unique(&mut dirs, downloads);
+        }
+
+        // Goose on Windows: %APPDATA%\Block\goose; opencode on macOS/Windows
+        if let Some(data) = dirs::data_dir() {
+            Self::push_unique(&mut dirs, data.join("Block").join("goose"));
+            Self::push_unique(&mut dirs, data.join("opencode"));
+        }
+
+        // VS Code extension storage (Cline, Roo Code, Cody, ...).
+        // On Linux this is already covered by ~/.config; macOS and Windows keep it elsewhere.
+        if let Some(config) = dirs::config_dir() {
+            for editor in crate::ingest_config::VSCODE_EDITOR_DIRS {
+                Self::push_unique(&mut dirs, config.join(editor).join("User/globalStorage"));
+                Self::push_unique(&mut dirs, config.join(editor).join("User/workspaceStorage"));
+            }
+
+            // JetBrains IDEs (AI Assistant chats)
+            Self::push_unique(&mut dirs, config.join("JetBrains"));
+        }
+
         // Also allow temp directories for testing.
         // On macOS these can resolve through symlinks (for example `/var` -> `/private/var`),
         // so we include both configured and canonicalized variants.
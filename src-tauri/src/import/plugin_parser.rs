@@ -0,0 +1,417 @@
+//! External parser plugins
+//!
+//! Lets users add parsers as standalone executables. A plugin is declared by a
+//! definition file under `<app data dir>/parser-plugins/<name>.json`:
+//!
+//! ```json
+//! {
+//!   "tool": "aider",
+//!   "command": "/usr/local/bin/narrative-aider-parser",
+//!   "args": [],
+//!   "pathContains": ".aider/",
+//!   "extensions": ["jsonl"],
+//!   "timeoutMs": 10000
+//! }
+//! ```
+//!
+//! The plugin is spawned once per request and receives a single JSON line on stdin:
+//! `{"method": "can_parse", "path": "..."}` or `{"method": "parse", "path": "..."}`.
+//! It answers with one JSON document on stdout: `{"result": true}` for
+//! `can_parse`, and `{"result": <ParsedSession>}` for `parse` (`{"error": "..."}`
+//! on failure).
+//!
+//! Plugin output is untrusted. The tool id and session hash are assigned from the
+//! definition, message text is secret-scanned, and tool inputs go through
+//! `ToolSanitizer`, the same as for built-in parsers.
+
+use super::{
+    parser::{
+        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
+        TraceMessage, WarningSeverity,
+    },
+    path_validator::PathValidator,
+    secure_parser::SecretScanner,
+    tool_sanitizer::ToolSanitizer,
+};
+use crate::session_hash::generate_session_hash;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Directory (under the app data dir) holding plugin definitions
+const PLUGINS_DIR_NAME: &str = "parser-plugins";
+
+/// Maximum plugin response size
+const MAX_OUTPUT_SIZE: usize = 100 * 1024 * 1024; // 100MB
+
+/// Cap on files reported by a plugin for one session
+const MAX_FILES_TOUCHED: usize = 10_000;
+
+/// A plugin definition loaded from disk
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginDefinition {
+    /// Tool id stored on imported sessions
+    pub tool: String,
+    /// Absolute path to the plugin executable
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Only ask the plugin about paths containing this substring
+    pub path_contains: String,
+    /// Only ask the plugin about files with these extensions (any when empty)
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    10_000
+}
+
+impl PluginDefinition {
+    /// Cheap local check before spawning the plugin
+    fn prefilter(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        if self.path_contains.is_empty() || !path_str.contains(&self.path_contains) {
+            return false;
+        }
+        self.extensions.is_empty()
+            || path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| self.extensions.iter().any(|x| x == e))
+                .unwrap_or(false)
+    }
+}
+
+/// Errors from invoking a plugin
+#[derive(Debug)]
+enum PluginError {
+    Spawn(std::io::Error),
+    Timeout,
+    OutputTooLarge,
+    InvalidResponse(String),
+    Reported(String),
+}
+
+impl std::fmt::Display for PluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PluginError::Spawn(e) => write!(f, "Failed to run parser plugin: {}", e),
+            PluginError::Timeout => write!(f, "Parser plugin timed out"),
+            PluginError::OutputTooLarge => write!(f, "Parser plugin output too large"),
+            PluginError::InvalidResponse(e) => write!(f, "Invalid parser plugin response: {}", e),
+            PluginError::Reported(e) => write!(f, "Parser plugin error: {}", e),
+        }
+    }
+}
+
+pub struct PluginParser {
+    definition: PluginDefinition,
+}
+
+impl PluginParser {
+    pub fn new(definition: PluginDefinition) -> Self {
+        Self { definition }
+    }
+
+    /// Invoke the plugin with one request and return its `result` value
+    fn call(&self, method: &str, path: &Path) -> Result<Value, PluginError> {
+        let mut child = Command::new(&self.definition.command)
+            .args(&self.definition.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(PluginError::Spawn)?;
+
+        let request = serde_json::json!({
+            "method": method,
+            "path": path.to_string_lossy(),
+        });
+        if let Some(mut stdin) = child.stdin.take() {
+            // A plugin that exits without reading stdin is reported via its output.
+            let _ = writeln!(stdin, "{}", request);
+        }
+
+        // Read stdout on a separate thread so a chatty plugin cannot block on a full pipe.
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let read = (&mut stdout)
+                .take(MAX_OUTPUT_SIZE as u64 + 1)
+                .read_to_end(&mut buf);
+            read.map(|_| buf)
+        });
+
+        let deadline = Instant::now() + Duration::from_millis(self.definition.timeout_ms);
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => break,
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(PluginError::Timeout);
+                }
+                Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+                Err(e) => return Err(PluginError::Spawn(e)),
+            }
+        }
+
+        let output = reader
+            .join()
+            .map_err(|_| PluginError::InvalidResponse("reader panicked".to_string()))?
+            .map_err(|e| PluginError::InvalidResponse(e.to_string()))?;
+        if output.len() > MAX_OUTPUT_SIZE {
+            return Err(PluginError::OutputTooLarge);
+        }
+
+        let response: Value = serde_json::from_slice(&output)
+            .map_err(|e| PluginError::InvalidResponse(e.to_string()))?;
+        if let Some(err) = response["error"].as_str() {
+            return Err(PluginError::Reported(err.to_string()));
+        }
+        match response.get("result") {
+            Some(result) => Ok(result.clone()),
+            None => Err(PluginError::InvalidResponse("missing result".to_string())),
+        }
+    }
+}
+
+impl super::parser::SessionParser for PluginParser {
+    fn can_parse(&self, path: &Path) -> bool {
+        if !self.definition.prefilter(path) {
+            return false;
+        }
+        matches!(self.call("can_parse", path), Ok(Value::Bool(true)))
+    }
+
+    fn tool_id(&self, _path: &Path) -> &str {
+        &self.definition.tool
+    }
+
+    fn parse(&self, path: &Path) -> ParseResult<ParsedSession> {
+        // Security: Validate path before handing it to the plugin
+        if let Err(e) = PathValidator::validate(path) {
+            return ParseResult::Failure(ParseError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                e.to_string(),
+            )));
+        }
+
+        let result = match self.call("parse", path) {
+            Ok(v) => v,
+            Err(PluginError::OutputTooLarge) => {
+                return ParseResult::Failure(ParseError::FileTooLarge)
+            }
+            Err(e) => {
+                return ParseResult::Failure(ParseError::Io(std::io::Error::other(e.to_string())))
+            }
+        };
+
+        let session: ParsedSession = match serde_json::from_value(result) {
+            Ok(s) => s,
+            Err(e) => return ParseResult::Failure(ParseError::Json(e)),
+        };
+
+        let (session, warnings) = sandbox_session(&self.definition.tool, session);
+        if warnings.is_empty() {
+            ParseResult::Success(session)
+        } else {
+            ParseResult::Partial(session, warnings)
+        }
+    }
+}
+
+/// Re-apply the built-in parser guarantees to a plugin-produced session
+fn sandbox_session(tool: &str, session: ParsedSession) -> (ParsedSession, Vec<ParseWarning>) {
+    let mut warnings = Vec::new();
+    let mut trace = SessionTrace::new();
+
+    for (idx, msg) in session.trace.messages.into_iter().enumerate() {
+        let text = match &msg {
+            TraceMessage::User { text, .. }
+            | TraceMessage::Assistant { text, .. }
+            | TraceMessage::Thinking { text, .. }
+            | TraceMessage::Plan { text, .. } => Some(text.as_str()),
+            TraceMessage::ToolCall { .. } => None,
+        };
+        if let Some(text) = text {
+            let secret_findings = SecretScanner::scan(text);
+            if !secret_findings.is_empty() {
+                warnings.push(ParseWarning {
+                    severity: WarningSeverity::Security,
+                    message: format!(
+                        "Potential secrets detected: {}",
+                        secret_findings
+                            .iter()
+                            .map(|f| f.kind.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    context: Some(format!("message {}", idx)),
+                });
+            }
+        }
+
+        let msg = match msg {
+            TraceMessage::ToolCall {
+                tool_name,
+                input,
+                timestamp,
+            } => TraceMessage::ToolCall {
+                input: input.and_then(|value| ToolSanitizer::sanitize(&tool_name, &value)),
+                tool_name,
+                timestamp,
+            },
+            other => other,
+        };
+        trace.add_message(msg);
+    }
+
+    let mut files_touched = session.files_touched;
+    files_touched.retain(|f| !f.trim().is_empty());
+    files_touched.sort();
+    files_touched.dedup();
+    if files_touched.len() > MAX_FILES_TOUCHED {
+        files_touched.truncate(MAX_FILES_TOUCHED);
+        warnings.push(ParseWarning {
+            severity: WarningSeverity::Warning,
+            message: format!("Plugin reported more than {} files", MAX_FILES_TOUCHED),
+            context: None,
+        });
+    }
+
+    let conversation_id = session.origin.conversation_id;
+    let session = ParsedSession {
+        origin: SessionOrigin {
+            tool: tool.to_string(),
+            session_id: generate_session_hash(tool, &conversation_id),
+            conversation_id,
+            model: session.origin.model,
+        },
+        started_at: session.started_at,
+        ended_at: session.ended_at,
+        trace,
+        files_touched,
+        git_branch: None,
+        edit_fingerprints: Vec::new(),
+    };
+
+    (session, warnings)
+}
+
+pub fn plugins_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|d| {
+        d.join(crate::ingest_config::APP_IDENTIFIER)
+            .join(PLUGINS_DIR_NAME)
+    })
+}
+
+/// Load plugin parsers from the app data dir. Invalid definitions are skipped.
+pub fn load_plugins() -> Vec<PluginParser> {
+    match plugins_dir() {
+        Some(dir) => load_definitions_from(&dir)
+            .into_iter()
+            .map(PluginParser::new)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn load_definitions_from(dir: &Path) -> Vec<PluginDefinition> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map(|e| e == "json").unwrap_or(false))
+        .collect();
+    files.sort();
+
+    files
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str::<PluginDefinition>(&content).ok())
+        // Relative commands would resolve against the app's working directory or PATH.
+        .filter(|d| !d.tool.trim().is_empty() && d.command.is_absolute())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_definitions_requires_absolute_command() {
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::write(
+            tmp.path().join("good.json"),
+            r#"{"tool": "aider", "command": "/usr/bin/aider-parser", "pathContains": ".aider/"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            tmp.path().join("relative.json"),
+            r#"{"tool": "x", "command": "x-parser", "pathContains": "x/"}"#,
+        )
+        .unwrap();
+
+        let defs = load_definitions_from(tmp.path());
+        assert_eq!(defs.len(), 1);
+        assert_eq!(defs[0].timeout_ms, 10_000);
+        assert!(defs[0].prefilter(Path::new("/home/u/.aider/chat.jsonl")));
+        assert!(!defs[0].prefilter(Path::new("/home/u/.other/chat.jsonl")));
+    }
+
+    #[test]
+    fn test_sandbox_session_overrides_identity_and_sanitizes_tools() {
+        let session: ParsedSession = serde_json::from_value(serde_json::json!({
+            "origin": {"tool": "claude_code", "session_id": "spoofed", "conversation_id": "c1", "model": null},
+            "trace": {"messages": [
+                {"role": "user", "text": "hello"},
+                {"role": "tool_call", "tool_name": "mystery", "input": {"secret": "x"}}
+            ]},
+            "files_touched": ["src/a.rs", "src/a.rs", ""]
+        }))
+        .unwrap();
+
+        let (session, warnings) = sandbox_session("aider", session);
+        assert!(warnings.is_empty());
+        assert_eq!(session.origin.tool, "aider");
+        assert_eq!(
+            session.origin.session_id,
+            generate_session_hash("aider", "c1")
+        );
+        assert_eq!(session.files_touched, vec!["src/a.rs"]);
+        assert!(matches!(
+            &session.trace.messages[1],
+            TraceMessage::ToolCall { input: None, .. }
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_call_round_trips_json_over_stdio() {
+        let parser = PluginParser::new(PluginDefinition {
+            tool: "echo".to_string(),
+            command: PathBuf::from("/bin/sh"),
+            args: vec![
+                "-c".to_string(),
+                r#"read line; echo '{"result": true}'"#.to_string(),
+            ],
+            path_contains: "/".to_string(),
+            extensions: Vec::new(),
+            timeout_ms: 5_000,
+        });
+
+        let result = parser.call("can_parse", Path::new("/tmp/x.jsonl")).unwrap();
+        assert_eq!(result, Value::Bool(true));
+    }
+}
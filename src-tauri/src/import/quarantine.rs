@@ -0,0 +1,223 @@
+//! Quarantine for session files flagged with security warnings
+//!
+//! Manual imports refuse files whose parse produced `Security` warnings. Instead of
+//! only failing them, the file is recorded here together with redacted excerpts of
+//! the flagged lines, so the user can review it and explicitly approve the import
+//! (with redaction applied) or discard it.
+
+use super::parser::{ParseWarning, WarningSeverity};
+use super::redactor::redact_text;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::path::Path;
+
+/// Flagged lines kept per file
+const MAX_EXCERPTS: usize = 20;
+
+/// Characters kept per flagged line
+const MAX_EXCERPT_CHARS: usize = 400;
+
+/// A flagged line, redacted for display
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlaggedExcerpt {
+    pub context: String,
+    pub text: String,
+}
+
+/// A session file awaiting review
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuarantinedImport {
+    pub id: i64,
+    pub file_path: String,
+    pub tool: Option<String>,
+    pub warnings: Vec<ParseWarning>,
+    pub excerpts: Vec<FlaggedExcerpt>,
+    pub quarantined_at: String,
+    #[serde(skip)]
+    pub file_sha256: Option<String>,
+}
+
+#[derive(FromRow)]
+struct QuarantineRow {
+    id: i64,
+    file_path: String,
+    tool: Option<String>,
+    warnings: String,
+    excerpts: String,
+    file_sha256: Option<String>,
+    quarantined_at: String,
+}
+
+impl From<QuarantineRow> for QuarantinedImport {
+    fn from(row: QuarantineRow) -> Self {
+        Self {
+            id: row.id,
+            file_path: row.file_path,
+            tool: row.tool,
+            warnings: serde_json::from_str(&row.warnings).unwrap_or_default(),
+            excerpts: serde_json::from_str(&row.excerpts).unwrap_or_default(),
+            quarantined_at: row.quarantined_at,
+            file_sha256: row.file_sha256,
+        }
+    }
+}
+
+/// SHA-256 of the file's current content
+pub fn file_fingerprint(path: &Path) -> Option<String> {
+    use sha2::{Digest, Sha256};
+
+    let bytes = std::fs::read(path).ok()?;
+    Some(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+/// Redacted copies of the lines named by Security warnings (`context: "line N"`)
+pub fn flagged_excerpts(path: &Path, warnings: &[ParseWarning]) -> Vec<FlaggedExcerpt> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    warnings
+        .iter()
+        .filter(|w| matches!(w.severity, WarningSeverity::Security))
+        .filter_map(|w| {
+            let context = w.context.as_deref()?;
+            let line_num: usize = context.strip_prefix("line ")?.parse().ok()?;
+            let line = lines.get(line_num.checked_sub(1)?)?;
+            let (redacted, _) = redact_text(line);
+            Some(FlaggedExcerpt {
+                context: context.to_string(),
+                text: redacted.chars().take(MAX_EXCERPT_CHARS).collect(),
+            })
+        })
+        .take(MAX_EXCERPTS)
+        .collect()
+}
+
+/// Record (or refresh) a flagged file
+pub async fn quarantine_file(
+    conn: &mut sqlx::SqliteConnection,
+    repo_id: i64,
+    file_path: &str,
+    tool: &str,
+    warnings: &[ParseWarning],
+) -> Result<(), sqlx::Error> {
+    let path = Path::new(file_path);
+    let warnings_json = serde_json::to_string(warnings).unwrap_or_else(|_| "[]".to_string());
+    let excerpts_json = serde_json::to_string(&flagged_excerpts(path, warnings))
+        .unwrap_or_else(|_| "[]".to_string());
+
+    sqlx::query(
+        r#"
+        INSERT INTO import_quarantine (repo_id, file_path, tool, warnings, excerpts, file_sha256)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, file_path) DO UPDATE SET
+            tool = excluded.tool,
+            warnings = excluded.warnings,
+            excerpts = excluded.excerpts,
+            file_sha256 = excluded.file_sha256,
+            quarantined_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        "#,
+    )
+    .bind(repo_id)
+    .bind(file_path)
+    .bind(tool)
+    .bind(warnings_json)
+    .bind(excerpts_json)
+    .bind(file_fingerprint(path))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn list_quarantined(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+) -> Result<Vec<QuarantinedImport>, sqlx::Error> {
+    let rows: Vec<QuarantineRow> = sqlx::query_as(
+        r#"
+        SELECT id, file_path, tool, warnings, excerpts, file_sha256, quarantined_at
+        FROM import_quarantine
+        WHERE repo_id = ?
+        ORDER BY quarantined_at DESC, id DESC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(QuarantinedImport::from).collect())
+}
+
+pub async fn get_quarantined(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    id: i64,
+) -> Result<Option<QuarantinedImport>, sqlx::Error> {
+    let row: Option<QuarantineRow> = sqlx::query_as(
+        r#"
+        SELECT id, file_path, tool, warnings, excerpts, file_sha256, quarantined_at
+        FROM import_quarantine
+        WHERE repo_id = ? AND id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(QuarantinedImport::from))
+}
+
+/// Remove a quarantine entry; returns false if it did not exist
+pub async fn remove_quarantined(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM import_quarantine WHERE repo_id = ? AND id = ?")
+        .bind(repo_id)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flagged_excerpts_redacts_flagged_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("session.jsonl");
+        std::fs::write(
+            &path,
+            "{\"ok\":true}\n{\"text\":\"key sk-abcdefghijklmnopqrstuvwxyz123456\"}\n",
+        )
+        .unwrap();
+
+        let warnings = vec![
+            ParseWarning {
+                severity: WarningSeverity::Warning,
+                message: "JSON parse error".to_string(),
+                context: Some("line 1".to_string()),
+            },
+            ParseWarning {
+                severity: WarningSeverity::Security,
+                message: "Potential OpenAI API key detected".to_string(),
+                context: Some("line 2".to_string()),
+            },
+        ];
+
+        let excerpts = flagged_excerpts(&path, &warnings);
+        assert_eq!(excerpts.len(), 1);
+        assert_eq!(excerpts[0].context, "line 2");
+        assert!(!excerpts[0].text.contains("sk-abcdef"));
+        assert!(excerpts[0].text.contains("REDACTED"));
+    }
+}
@@ -79,6 +79,15 @@ pub fn redact_text(input: &str) -> (String, RedactionSummary) {
     (redacted, RedactionSummary { total, hits })
 }
 
+/// Kinds of secret present in `input`, in pattern order (nothing is replaced)
+pub fn detect_kinds(input: &str) -> Vec<String> {
+    REDACTION_PATTERNS
+        .iter()
+        .filter(|(pattern, _)| pattern.is_match(input))
+        .map(|(_, name)| name.clone())
+        .collect()
+}
+
 pub fn redact_value(value: &Value) -> (Value, RedactionSummary) {
     match value {
         Value::String(text) => {
@@ -0,0 +1,296 @@
+//! Lenient parser for pasted transcripts
+//!
+//! Some agent sessions only exist as copied text (web-based agents, chat UIs).
+//! This parser accepts whatever was pasted and recovers as much structure as it
+//! can:
+//! - a JSON array of `{role, content}` messages (OpenAI/Anthropic API shape)
+//! - speaker-prefixed text (`User:`, `Assistant:`, `## Claude`, `> prompt`)
+//! - anything else becomes a single user message
+//!
+//! There is no file, so the conversation id is a hash of the text: pasting the
+//! same transcript twice yields the same session.
+
+use super::{
+    parser::{
+        ParseError, ParseResult, ParseWarning, ParsedSession, SessionOrigin, SessionTrace,
+        TraceMessage, WarningSeverity,
+    },
+    secure_parser::SecretScanner,
+};
+use crate::session_hash::generate_session_hash;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde_json::Value;
+
+/// Pasted text is held in memory by the frontend too; keep it modest.
+const MAX_SIZE: usize = 10 * 1024 * 1024; // 10MB
+
+/// Tool id used when no hint is given
+const DEFAULT_TOOL: &str = "pasted";
+
+lazy_static! {
+    /// `User:`, `**Assistant:**`, `## Claude`, `### You` ...
+    static ref SPEAKER_LINE: Regex = Regex::new(
+        r"(?i)^\s*(?:#{1,6}\s*)?\**\s*(user|you|human|assistant|ai|claude|chatgpt|gemini|copilot|codex)\s*\**\s*(?::\**|$)\s*(.*)$"
+    )
+    .expect("valid regex: speaker line");
+
+    /// Relative or absolute file paths with an extension (`src/lib.rs`)
+    static ref FILE_PATH: Regex = Regex::new(r"(?:^|[\s`(])((?:\.{0,2}/)?(?:[\w.-]+/)+[\w.-]+\.[A-Za-z0-9]{1,8})\b")
+        .expect("valid regex: file path");
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Speaker {
+    User,
+    Assistant,
+}
+
+/// Normalize a tool hint into a tool id (`"Claude Web"` -> `"claude_web"`)
+pub fn normalize_tool_hint(hint: Option<&str>) -> String {
+    let normalized: String = hint
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let normalized = normalized.trim_matches('_').to_string();
+    if normalized.is_empty() {
+        DEFAULT_TOOL.to_string()
+    } else {
+        normalized
+    }
+}
+
+/// Parse pasted transcript text
+pub fn parse_transcript_text(text: &str, tool_hint: Option<&str>) -> ParseResult<ParsedSession> {
+    // Security: Check size
+    if text.len() > MAX_SIZE {
+        return ParseResult::Failure(ParseError::FileTooLarge);
+    }
+    if text.trim().is_empty() {
+        return ParseResult::Failure(ParseError::MissingField("text"));
+    }
+
+    let tool = normalize_tool_hint(tool_hint);
+    let mut warnings: Vec<ParseWarning> = Vec::new();
+
+    // Security: Scan for secrets
+    for (line_num, line) in text.lines().enumerate() {
+        let secret_findings = SecretScanner::scan(line);
+        if !secret_findings.is_empty() {
+            warnings.push(ParseWarning {
+                severity: WarningSeverity::Security,
+                message: format!(
+                    "Potential {} detected",
+                    secret_findings
+                        .iter()
+                        .map(|f| f.kind.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                context: Some(format!("line {}", line_num + 1)),
+            });
+        }
+    }
+
+    let messages = match parse_json_messages(text) {
+        Some(messages) => messages,
+        None => {
+            let messages = parse_speaker_turns(text);
+            if messages.len() == 1 {
+                warnings.push(ParseWarning {
+                    severity: WarningSeverity::Info,
+                    message: "No speaker markers found; imported as a single message".to_string(),
+                    context: None,
+                });
+            }
+            messages
+        }
+    };
+
+    let mut trace = SessionTrace::new();
+    let mut files_touched: Vec<String> = Vec::new();
+    for (speaker, body) in messages {
+        files_touched.extend(
+            FILE_PATH
+                .captures_iter(&body)
+                .filter_map(|c| c.get(1).map(|m| m.as_str().to_string())),
+        );
+        trace.add_message(match speaker {
+            Speaker::User => TraceMessage::User {
+                text: body,
+                timestamp: None,
+            },
+            Speaker::Assistant => TraceMessage::Assistant {
+                text: body,
+                timestamp: None,
+            },
+        });
+    }
+    files_touched.sort();
+    files_touched.dedup();
+
+    let conversation_id = {
+        use sha2::{Digest, Sha256};
+        format!("{:x}", Sha256::digest(text.trim().as_bytes()))[..16].to_string()
+    };
+
+    let session = ParsedSession {
+        origin: SessionOrigin {
+            session_id: generate_session_hash(&tool, &conversation_id),
+            tool,
+            conversation_id,
+            model: None,
+        },
+        started_at: None,
+        ended_at: None,
+        trace,
+        files_touched,
+        git_branch: None,
+        edit_fingerprints: Vec::new(),
+    };
+
+    if warnings.is_empty() {
+        ParseResult::Success(session)
+    } else {
+        ParseResult::Partial(session, warnings)
+    }
+}
+
+/// `[{"role": "user", "content": "..."}, ...]` or `{"messages": [...]}`
+fn parse_json_messages(text: &str) -> Option<Vec<(Speaker, String)>> {
+    let value: Value = serde_json::from_str(text.trim()).ok()?;
+    let items = value
+        .as_array()
+        .or_else(|| value.get("messages").and_then(|m| m.as_array()))?;
+
+    let messages: Vec<(Speaker, String)> = items
+        .iter()
+        .filter_map(|item| {
+            let speaker = match item.get("role")?.as_str()? {
+                "user" | "human" => Speaker::User,
+                "assistant" | "model" | "ai" => Speaker::Assistant,
+                _ => return None,
+            };
+            let body = match item.get("content")? {
+                Value::String(s) => s.clone(),
+                Value::Array(parts) => parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                _ => return None,
+            };
+            (!body.trim().is_empty()).then_some((speaker, body))
+        })
+        .collect();
+
+    (!messages.is_empty()).then_some(messages)
+}
+
+/// Split text into turns at speaker markers; text before the first marker is
+/// treated as the user's prompt.
+fn parse_speaker_turns(text: &str) -> Vec<(Speaker, String)> {
+    let mut turns: Vec<(Speaker, String)> = Vec::new();
+    let mut current = Speaker::User;
+    let mut buffer: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        // Terminal agents echo the prompt as `> ...`; what follows is the reply
+        if let Some(prompt) = line.strip_prefix("> ") {
+            flush(current, &mut buffer, &mut turns);
+            buffer.push(prompt);
+            flush(Speaker::User, &mut buffer, &mut turns);
+            current = Speaker::Assistant;
+            continue;
+        }
+
+        if let Some(caps) = SPEAKER_LINE.captures(line) {
+            flush(current, &mut buffer, &mut turns);
+            current = match caps[1].to_lowercase().as_str() {
+                "user" | "you" | "human" => Speaker::User,
+                _ => Speaker::Assistant,
+            };
+            if let Some(rest) = caps.get(2) {
+                buffer.push(rest.as_str());
+            }
+            continue;
+        }
+        buffer.push(line);
+    }
+    flush(current, &mut buffer, &mut turns);
+
+    turns
+}
+
+fn flush(speaker: Speaker, buffer: &mut Vec<&str>, turns: &mut Vec<(Speaker, String)>) {
+    let body = buffer.join("\n").trim().to_string();
+    if !body.is_empty() {
+        turns.push((speaker, body));
+    }
+    buffer.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roles(session: &ParsedSession) -> Vec<&'static str> {
+        session
+            .trace
+            .messages
+            .iter()
+            .map(|m| match m {
+                TraceMessage::User { .. } => "user",
+                TraceMessage::Assistant { .. } => "assistant",
+                _ => "other",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parses_speaker_prefixed_text() {
+        let text = "User: fix the bug in src/lib.rs\nAssistant: Done.\nUpdated `src/lib.rs`.\n\n## You\nthanks";
+        let session = match parse_transcript_text(text, Some("Claude Web")) {
+            ParseResult::Success(s) => s,
+            _ => panic!("expected clean parse"),
+        };
+        assert_eq!(session.origin.tool, "claude_web");
+        assert_eq!(roles(&session), vec!["user", "assistant", "user"]);
+        assert_eq!(session.files_touched, vec!["src/lib.rs".to_string()]);
+    }
+
+    #[test]
+    fn test_parses_json_messages_and_is_deterministic() {
+        let text = r#"[{"role":"user","content":"hi"},{"role":"assistant","content":[{"type":"text","text":"hello"}]}]"#;
+        let first = match parse_transcript_text(text, None) {
+            ParseResult::Success(s) => s,
+            _ => panic!("expected clean parse"),
+        };
+        assert_eq!(first.origin.tool, "pasted");
+        assert_eq!(roles(&first), vec!["user", "assistant"]);
+
+        let second = match parse_transcript_text(text, None) {
+            ParseResult::Success(s) => s,
+            _ => panic!("expected clean parse"),
+        };
+        assert_eq!(first.origin.session_id, second.origin.session_id);
+    }
+
+    #[test]
+    fn test_unstructured_text_becomes_single_message() {
+        match parse_transcript_text("just some notes", None) {
+            ParseResult::Partial(session, warnings) => {
+                assert_eq!(roles(&session), vec!["user"]);
+                assert_eq!(warnings[0].severity, WarningSeverity::Info);
+            }
+            _ => panic!("expected partial result"),
+        }
+        assert!(matches!(
+            parse_transcript_text("   ", None),
+            ParseResult::Failure(_)
+        ));
+    }
+}
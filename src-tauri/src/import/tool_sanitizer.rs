@@ -17,7 +17,17 @@ pub struct ToolSanitizer;
 const ALLOWED_TOOLS: &[&str] = &["readFile", "listDirectory", "searchFiles", "viewFile"];
 
 /// Tools that need parameter sanitization
-const SANITIZED_TOOLS: &[&str] = &["writeFile", "runCommand", "execute", "bash"];
+const SANITIZED_TOOLS: &[&str] = &[
+    "writeFile",
+    "runCommand",
+    "execute",
+    "bash",
+    // Cline / Roo Code
+    "write_to_file",
+    "apply_diff",
+    "replace_in_file",
+    "execute_command",
+];
 
 impl ToolSanitizer {
     /// Sanitize tool call input before storage
@@ -44,8 +54,12 @@ impl ToolSanitizer {
     /// Sanitize specific tool inputs
     fn sanitize_tool(tool_name: &str, input: &Value) -> Option<Value> {
         match tool_name {
-            "writeFile" | "viewFile" => Self::sanitize_write_file(input),
-            "runCommand" | "execute" | "bash" => Self::sanitize_run_command(input),
+            "writeFile" | "viewFile" | "write_to_file" | "apply_diff" | "replace_in_file" => {
+                Self::sanitize_write_file(input)
+            }
+            "runCommand" | "execute" | "bash" | "execute_command" => {
+                Self::sanitize_run_command(input)
+            }
             _ => None,
         }
     }
@@ -150,6 +164,14 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_sanitizes_cline_apply_diff() {
+        let input = json!({"path": "src/main.rs", "diff": "<<<<<<< SEARCH\nsecret\n"});
+        let result = ToolSanitizer::sanitize("apply_diff", &input);
+
+        assert_eq!(result, Some(json!({"path": "src/main.rs"})));
+    }
+
     #[test]
     fn test_write_file_alternate_field_names() {
         // Test file_path variant
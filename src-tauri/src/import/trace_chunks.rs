@@ -0,0 +1,149 @@
+//! Chunked storage for very large session traces
+//!
+//! `sessions.raw_json` holds the trace as `{"messages": [...]}`. When a trace
+//! serializes to more than `CHUNK_BYTES`, only the first chunk of messages is
+//! kept inline and the rest is stored, in order, as JSON arrays in
+//! `session_trace_chunks`. Previews keep reading `raw_json` (a bounded prefix of
+//! the conversation); code that needs every message uses `load_trace_json`.
+
+use super::parser::SessionTrace;
+
+/// Serialized size of one chunk of messages
+pub const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// A trace split for storage
+#[derive(Debug)]
+pub struct StoredTrace {
+    /// `{"messages": [...]}` with the first chunk of messages (stored in `raw_json`)
+    pub inline_json: String,
+    /// Remaining messages as JSON arrays, in order
+    pub overflow: Vec<String>,
+}
+
+/// Split a trace into an inline part and overflow chunks of about `CHUNK_BYTES`
+pub fn split_trace(trace: &SessionTrace) -> Result<StoredTrace, serde_json::Error> {
+    split_with_limit(trace, CHUNK_BYTES)
+}
+
+fn split_with_limit(trace: &SessionTrace, limit: usize) -> Result<StoredTrace, serde_json::Error> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::from("[");
+    for message in &trace.messages {
+        let json = serde_json::to_string(message)?;
+        if current.len() > 1 && current.len() + json.len() + 1 > limit {
+            current.push(']');
+            chunks.push(std::mem::replace(&mut current, String::from("[")));
+        }
+        if current.len() > 1 {
+            current.push(',');
+        }
+        current.push_str(&json);
+    }
+    current.push(']');
+    chunks.push(current);
+
+    let mut chunks = chunks.into_iter();
+    let first = chunks.next().unwrap_or_else(|| "[]".to_string());
+    Ok(StoredTrace {
+        inline_json: format!("{{\"messages\":{}}}", first),
+        overflow: chunks.collect(),
+    })
+}
+
+/// Replace a session's overflow chunks
+pub async fn write_overflow(
+    conn: &mut sqlx::SqliteConnection,
+    session_id: &str,
+    overflow: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM session_trace_chunks WHERE session_id = ?")
+        .bind(session_id)
+        .execute(&mut *conn)
+        .await?;
+    for (index, chunk) in overflow.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO session_trace_chunks (session_id, chunk_index, messages_json) VALUES (?, ?, ?)",
+        )
+        .bind(session_id)
+        .bind(index as i64)
+        .bind(chunk)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// The complete trace JSON of a session, given its inline `raw_json`
+pub async fn load_trace_json(
+    db: &sqlx::SqlitePool,
+    session_id: &str,
+    inline_json: String,
+) -> Result<String, sqlx::Error> {
+    let chunks: Vec<String> = sqlx::query_scalar(
+        "SELECT messages_json FROM session_trace_chunks WHERE session_id = ? ORDER BY chunk_index",
+    )
+    .bind(session_id)
+    .fetch_all(db)
+    .await?;
+    Ok(join_chunks(inline_json, &chunks))
+}
+
+/// Append overflow arrays to the inline `{"messages":[...]}` document
+fn join_chunks(inline_json: String, chunks: &[String]) -> String {
+    if chunks.is_empty() {
+        return inline_json;
+    }
+    let Some(head) = inline_json.strip_suffix("]}") else {
+        return inline_json;
+    };
+
+    let mut out =
+        String::with_capacity(inline_json.len() + chunks.iter().map(|c| c.len()).sum::<usize>());
+    out.push_str(head);
+    let mut has_messages = !head.ends_with('[');
+    for chunk in chunks {
+        let body = chunk
+            .strip_prefix('[')
+            .and_then(|c| c.strip_suffix(']'))
+            .unwrap_or("");
+        if body.is_empty() {
+            continue;
+        }
+        if has_messages {
+            out.push(',');
+        }
+        out.push_str(body);
+        has_messages = true;
+    }
+    out.push_str("]}");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::parser::TraceMessage;
+
+    #[test]
+    fn test_split_and_join_round_trip() {
+        let mut trace = SessionTrace::new();
+        for i in 0..10 {
+            trace.add_message(TraceMessage::User {
+                text: format!("message {i} {}", "x".repeat(20)),
+                timestamp: None,
+            });
+        }
+
+        let stored = split_with_limit(&trace, 120).unwrap();
+        assert!(!stored.overflow.is_empty());
+        let inline: SessionTrace = serde_json::from_str(&stored.inline_json).unwrap();
+        assert!(inline.messages.len() < 10);
+
+        let joined = join_chunks(stored.inline_json, &stored.overflow);
+        assert_eq!(joined, serde_json::to_string(&trace).unwrap());
+
+        let small = split_trace(&trace).unwrap();
+        assert!(small.overflow.is_empty());
+        assert_eq!(small.inline_json, serde_json::to_string(&trace).unwrap());
+    }
+}
@@ -0,0 +1,249 @@
+//! Per-session message limits
+//!
+//! Sessions with more messages than `maxMessagesPerSession` are trimmed before
+//! they are redacted, stored, linked, and projected into Atlas:
+//! - `head` keeps the first messages
+//! - `tail` keeps the most recent messages
+//! - `summary` keeps both ends and replaces the middle with one note counting
+//!   what was dropped
+//!
+//! The number of dropped messages and the strategy are recorded on the session
+//! row (`trimmed_message_count`, `trim_strategy`).
+
+use super::parser::{ParsedSession, SessionTrace, TraceMessage};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// Messages kept per session when not configured
+pub const DEFAULT_MAX_MESSAGES: usize = 5000;
+
+/// Start of the note inserted by the `summary` strategy
+const SUMMARY_PREFIX: &str = "[Trimmed on import:";
+
+/// Which messages to keep when a session exceeds the limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrimStrategy {
+    Head,
+    Tail,
+    #[default]
+    Summary,
+}
+
+impl TrimStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrimStrategy::Head => "head",
+            TrimStrategy::Tail => "tail",
+            TrimStrategy::Summary => "summary",
+        }
+    }
+}
+
+/// Message limit applied to imported sessions (0 disables trimming)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimPolicy {
+    pub max_messages: usize,
+    pub strategy: TrimStrategy,
+}
+
+impl Default for TrimPolicy {
+    fn default() -> Self {
+        Self {
+            max_messages: DEFAULT_MAX_MESSAGES,
+            strategy: TrimStrategy::Summary,
+        }
+    }
+}
+
+static POLICY: RwLock<TrimPolicy> = RwLock::new(TrimPolicy {
+    max_messages: DEFAULT_MAX_MESSAGES,
+    strategy: TrimStrategy::Summary,
+});
+
+/// Current message limit
+pub fn policy() -> TrimPolicy {
+    POLICY.read().map(|p| *p).unwrap_or_default()
+}
+
+/// Set the message limit from the ingest config
+pub fn set_policy(policy: TrimPolicy) {
+    if let Ok(mut current) = POLICY.write() {
+        *current = policy;
+    }
+}
+
+/// What trimming removed from a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimSummary {
+    pub strategy: TrimStrategy,
+    /// Messages dropped, including any dropped by earlier imports of the session
+    pub dropped: usize,
+}
+
+/// Trim `session` to the configured limit
+///
+/// `previously_dropped` carries the count from an earlier import when new
+/// messages are appended to an already trimmed session.
+pub fn trim_session(session: &mut ParsedSession, previously_dropped: usize) -> Option<TrimSummary> {
+    trim_trace(&mut session.trace, &policy(), previously_dropped)
+}
+
+/// Trim a trace to `policy`; returns None when it already fits
+pub fn trim_trace(
+    trace: &mut SessionTrace,
+    policy: &TrimPolicy,
+    previously_dropped: usize,
+) -> Option<TrimSummary> {
+    let max = policy.max_messages;
+    if max == 0 || trace.messages.len() <= max {
+        return None;
+    }
+
+    let mut head = std::mem::take(&mut trace.messages);
+    let total = head.len();
+    // Split into kept head, removed middle, and kept tail.
+    let keep_head = match policy.strategy {
+        TrimStrategy::Head => max,
+        TrimStrategy::Tail => 0,
+        // One slot goes to the note that stands in for the middle.
+        TrimStrategy::Summary => max / 2,
+    };
+    let keep_tail = match policy.strategy {
+        TrimStrategy::Head => 0,
+        TrimStrategy::Tail => max,
+        TrimStrategy::Summary => max.saturating_sub(keep_head + 1),
+    };
+    let mut removed = head.split_off(keep_head);
+    let tail = removed.split_off(total - keep_head - keep_tail);
+
+    // A note left by an earlier trim is not a real message.
+    let dropped = previously_dropped + removed.iter().filter(|m| !is_summary_note(m)).count();
+
+    if policy.strategy == TrimStrategy::Summary {
+        head.push(TraceMessage::Assistant {
+            text: summary_text(&removed, dropped),
+            timestamp: removed.iter().rev().find_map(message_timestamp),
+        });
+    }
+    head.extend(tail);
+    trace.messages = head;
+
+    Some(TrimSummary {
+        strategy: policy.strategy,
+        dropped,
+    })
+}
+
+fn is_summary_note(message: &TraceMessage) -> bool {
+    matches!(message, TraceMessage::Assistant { text, .. } if text.starts_with(SUMMARY_PREFIX))
+}
+
+fn message_timestamp(message: &TraceMessage) -> Option<String> {
+    message.timestamp().map(String::from)
+}
+
+/// Note describing the messages removed from the middle of a session
+fn summary_text(removed: &[TraceMessage], dropped: usize) -> String {
+    let (mut user, mut assistant, mut tool_calls, mut other) = (0, 0, 0, 0);
+    for message in removed.iter().filter(|m| !is_summary_note(m)) {
+        match message {
+            TraceMessage::User { .. } => user += 1,
+            TraceMessage::Assistant { .. } => assistant += 1,
+            TraceMessage::ToolCall { .. } => tool_calls += 1,
+            TraceMessage::Thinking { .. } | TraceMessage::Plan { .. } => other += 1,
+        }
+    }
+    format!(
+        "{SUMMARY_PREFIX} {dropped} messages omitted from the middle of this session \
+         (this pass: {user} user, {assistant} assistant, {tool_calls} tool calls, {other} other)]"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace_of(n: usize) -> SessionTrace {
+        let mut trace = SessionTrace::new();
+        for i in 0..n {
+            trace.add_message(TraceMessage::User {
+                text: format!("m{i}"),
+                timestamp: None,
+            });
+        }
+        trace
+    }
+
+    fn texts(trace: &SessionTrace) -> Vec<String> {
+        trace
+            .messages
+            .iter()
+            .map(|m| match m {
+                TraceMessage::User { text, .. } | TraceMessage::Assistant { text, .. } => {
+                    text.clone()
+                }
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_trim_strategies() {
+        let policy = |strategy| TrimPolicy {
+            max_messages: 4,
+            strategy,
+        };
+
+        let mut head = trace_of(10);
+        let summary = trim_trace(&mut head, &policy(TrimStrategy::Head), 0).unwrap();
+        assert_eq!(texts(&head), vec!["m0", "m1", "m2", "m3"]);
+        assert_eq!(summary.dropped, 6);
+
+        let mut tail = trace_of(10);
+        trim_trace(&mut tail, &policy(TrimStrategy::Tail), 0).unwrap();
+        assert_eq!(texts(&tail), vec!["m6", "m7", "m8", "m9"]);
+
+        let mut middle = trace_of(10);
+        let summary = trim_trace(&mut middle, &policy(TrimStrategy::Summary), 0).unwrap();
+        let kept = texts(&middle);
+        assert_eq!(kept.len(), 4);
+        assert_eq!(&kept[..2], &["m0", "m1"]);
+        assert!(kept[2].starts_with(SUMMARY_PREFIX));
+        assert_eq!(kept[3], "m9");
+        assert_eq!(summary.dropped, 7);
+
+        let mut small = trace_of(3);
+        assert!(trim_trace(&mut small, &policy(TrimStrategy::Tail), 0).is_none());
+        assert!(trim_trace(
+            &mut trace_of(10),
+            &TrimPolicy {
+                max_messages: 0,
+                strategy: TrimStrategy::Head
+            },
+            0
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_retrim_does_not_count_previous_note() {
+        let policy = TrimPolicy {
+            max_messages: 4,
+            strategy: TrimStrategy::Summary,
+        };
+        let mut trace = trace_of(10);
+        let first = trim_trace(&mut trace, &policy, 0).unwrap();
+
+        trace.add_message(TraceMessage::User {
+            text: "m10".to_string(),
+            timestamp: None,
+        });
+        let second = trim_trace(&mut trace, &policy, first.dropped).unwrap();
+
+        assert_eq!(second.dropped, first.dropped + 1);
+        let kept = texts(&trace);
+        assert!(kept[2].contains(&format!("{} messages omitted", second.dropped)));
+        assert_eq!(kept[3], "m10");
+    }
+}
@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 use tauri::command;
 
+use crate::import::trimming::{TrimPolicy, TrimStrategy};
 use crate::secret_store;
 
 pub const CANONICAL_COLLECTOR_ROOT: &str = "~/.agents/otel-collector";
@@ -14,6 +15,14 @@ pub const SECONDARY_LEGACY_COLLECTOR_ROOT: &str = "~/.codex/otel-collector";
 pub const APP_IDENTIFIER: &str = "com.jamie.trace-narrative";
 pub const FIREFLY_LEGACY_APP_IDENTIFIER: &str = "com.jamie.firefly-narrative";
 pub const LEGACY_APP_IDENTIFIER: &str = "com.jamie.narrative-mvp";
+/// VS Code flavours whose `<config dir>/<name>/User` tree holds extension storage.
+pub const VSCODE_EDITOR_DIRS: &[&str] = &["Code", "Code - Insiders", "VSCodium"];
+/// Storage folder used by the Sourcegraph Cody extension.
+const CODY_STORAGE_ID: &str = "sourcegraph.cody-ai";
+/// Storage folder used by the GitHub Copilot Chat extension.
+const COPILOT_CHAT_STORAGE_ID: &str = "github.copilot-chat";
+/// VS Code's own chat store for windows without a workspace (Copilot Chat panels).
+const EMPTY_WINDOW_CHAT_SESSIONS: &str = "emptyWindowChatSessions";
 
 fn default_chatgpt_auth_mode() -> String {
     "chatgpt".to_string()
@@ -40,6 +49,56 @@ pub struct IngestConfig {
     pub redaction_mode: String,
     #[serde(default)]
     pub consent: ConsentState,
+    /// Minutes between scheduled background backfills (0 disables them)
+    #[serde(default = "default_backfill_interval_minutes")]
+    pub backfill_interval_minutes: u64,
+    /// Megabytes of a session file read before the rest is dropped (0 uses the default)
+    #[serde(default = "default_max_parse_mb")]
+    pub max_parse_mb: u64,
+    /// Messages kept per imported session (0 keeps every message)
+    #[serde(default = "default_max_messages_per_session")]
+    pub max_messages_per_session: usize,
+    /// Which messages to keep when a session is over the limit
+    #[serde(default)]
+    pub trim_strategy: TrimStrategy,
+    /// The user's git author names and emails; linking penalizes other authors
+    #[serde(default)]
+    pub my_identities: Vec<String>,
+}
+
+fn default_backfill_interval_minutes() -> u64 {
+    30
+}
+
+fn default_max_parse_mb() -> u64 {
+    crate::import::line_reader::DEFAULT_MAX_PARSE_BYTES / (1024 * 1024)
+}
+
+fn default_max_messages_per_session() -> usize {
+    crate::import::trimming::DEFAULT_MAX_MESSAGES
+}
+
+impl IngestConfig {
+    /// Per-file read budget for the session parsers
+    pub fn max_parse_bytes(&self) -> u64 {
+        self.max_parse_mb.saturating_mul(1024 * 1024)
+    }
+
+    /// Message limit applied to imported sessions
+    pub fn trim_policy(&self) -> TrimPolicy {
+        TrimPolicy {
+            max_messages: self.max_messages_per_session,
+            strategy: self.trim_strategy,
+        }
+    }
+
+    /// Push the import limits to the parsers and the store path, and the
+    /// user's identities to the linker
+    pub fn apply_import_limits(&self) {
+        crate::import::line_reader::set_max_parse_bytes(self.max_parse_bytes());
+        crate::import::trimming::set_policy(self.trim_policy());
+        crate::link_commands::set_my_identities(self.my_identities.clone());
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,6 +110,130 @@ pub struct WatchPaths {
     pub cursor: Vec<String>,
     #[serde(default)]
     pub codex_logs: Vec<String>,
+    #[serde(default = "default_jetbrains_watch_paths")]
+    pub jetbrains: Vec<String>,
+    #[serde(default = "default_cody_watch_paths")]
+    pub cody: Vec<String>,
+    #[serde(default = "default_opencode_watch_paths")]
+    pub opencode: Vec<String>,
+    #[serde(default = "default_copilot_watch_paths")]
+    pub copilot: Vec<String>,
+}
+
+/// JetBrains IDEs keep per-product config (including AI Assistant chats) under
+/// <config dir>/JetBrains, which lives outside $HOME on macOS and Windows.
+fn default_jetbrains_watch_paths() -> Vec<String> {
+    dirs::config_dir()
+        .map(|dir| vec![dir.join("JetBrains").to_string_lossy().to_string()])
+        .unwrap_or_default()
+}
+
+/// Cody keeps chat history in its VS Code globalStorage folder.
+fn default_cody_watch_paths() -> Vec<String> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    VSCODE_EDITOR_DIRS
+        .iter()
+        .map(|editor| {
+            config
+                .join(editor)
+                .join("User/globalStorage")
+                .join(CODY_STORAGE_ID)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Copilot Chat writes chat sessions to VS Code's globalStorage (per-workspace
+/// sessions live under workspaceStorage and are found by discovery instead).
+fn default_copilot_watch_paths() -> Vec<String> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    VSCODE_EDITOR_DIRS
+        .iter()
+        .flat_map(|editor| {
+            let global = config.join(editor).join("User/globalStorage");
+            [
+                global.join(COPILOT_CHAT_STORAGE_ID),
+                global.join(EMPTY_WINDOW_CHAT_SESSIONS),
+            ]
+        })
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// opencode follows XDG on Linux (~/.local/share) and the platform data dir elsewhere.
+fn default_opencode_watch_paths() -> Vec<String> {
+    let mut out = vec!["~/.local/share/opencode/storage".to_string()];
+    if let Some(data) = dirs::data_dir() {
+        let platform = data.join("opencode/storage").to_string_lossy().to_string();
+        if !platform.contains("/.local/share/") {
+            out.push(platform);
+        }
+    }
+    out
+}
+
+/// Existing Cody storage folders, global and per-workspace.
+pub fn discover_cody_storage_dirs() -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let Some(config) = dirs::config_dir() else {
+        return out;
+    };
+    for editor in VSCODE_EDITOR_DIRS {
+        let user_dir = config.join(editor).join("User");
+
+        let global = user_dir.join("globalStorage").join(CODY_STORAGE_ID);
+        if global.is_dir() {
+            out.push(global);
+        }
+
+        // workspaceStorage/<hash>/sourcegraph.cody-ai
+        let Ok(entries) = fs::read_dir(user_dir.join("workspaceStorage")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let candidate = entry.path().join(CODY_STORAGE_ID);
+            if candidate.is_dir() {
+                out.push(candidate);
+            }
+        }
+    }
+    out
+}
+
+/// Existing Copilot Chat session folders: the extension's globalStorage, the
+/// empty-window chat store, and each workspace's `chatSessions`.
+pub fn discover_copilot_chat_dirs() -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let Some(config) = dirs::config_dir() else {
+        return out;
+    };
+    for editor in VSCODE_EDITOR_DIRS {
+        let user_dir = config.join(editor).join("User");
+
+        for name in [COPILOT_CHAT_STORAGE_ID, EMPTY_WINDOW_CHAT_SESSIONS] {
+            let global = user_dir.join("globalStorage").join(name);
+            if global.is_dir() {
+                out.push(global);
+            }
+        }
+
+        // workspaceStorage/<hash>/chatSessions
+        let Ok(entries) = fs::read_dir(user_dir.join("workspaceStorage")) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let candidate = entry.path().join("chatSessions");
+            if candidate.is_dir() {
+                out.push(candidate);
+            }
+        }
+    }
+    out
 }
 
 impl Default for WatchPaths {
@@ -67,6 +250,10 @@ impl Default for WatchPaths {
                 "~/.codex/history.jsonl".to_string(),
                 "~/.codex/logs".to_string(), // legacy fallback
             ],
+            jetbrains: default_jetbrains_watch_paths(),
+            cody: default_cody_watch_paths(),
+            opencode: default_opencode_watch_paths(),
+            copilot: default_copilot_watch_paths(),
         }
     }
 }
@@ -176,6 +363,11 @@ pub struct IngestConfigUpdate {
     pub retention_days: Option<i64>,
     pub redaction_mode: Option<String>,
     pub consent: Option<ConsentState>,
+    pub backfill_interval_minutes: Option<u64>,
+    pub max_parse_mb: Option<u64>,
+    pub max_messages_per_session: Option<usize>,
+    pub trim_strategy: Option<TrimStrategy>,
+    pub my_identities: Option<Vec<String>>,
 }
 
 impl Default for IngestConfig {
@@ -188,6 +380,11 @@ impl Default for IngestConfig {
             retention_days: 30,
             redaction_mode: "redact".to_string(),
             consent: ConsentState::default(),
+            backfill_interval_minutes: default_backfill_interval_minutes(),
+            max_parse_mb: default_max_parse_mb(),
+            max_messages_per_session: default_max_messages_per_session(),
+            trim_strategy: TrimStrategy::default(),
+            my_identities: Vec::new(),
         }
     }
 }
@@ -246,6 +443,25 @@ pub fn apply_update(update: IngestConfigUpdate) -> Result<IngestConfig, String>
     if let Some(value) = update.consent {
         config.consent = value;
     }
+    if let Some(value) = update.backfill_interval_minutes {
+        config.backfill_interval_minutes = value;
+    }
+    if let Some(value) = update.max_parse_mb {
+        config.max_parse_mb = value;
+    }
+    if let Some(value) = update.max_messages_per_session {
+        config.max_messages_per_session = value;
+    }
+    if let Some(value) = update.trim_strategy {
+        config.trim_strategy = value;
+    }
+    if let Some(value) = update.my_identities {
+        config.my_identities = value
+            .into_iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+    }
 
     normalize_codex_watch_paths(&mut config.watch_paths);
     normalize_codex_mode(&mut config.codex);
@@ -253,6 +469,7 @@ pub fn apply_update(update: IngestConfigUpdate) -> Result<IngestConfig, String>
     enforce_collector_roots(&mut config.collector)?;
 
     save_config(&config)?;
+    config.apply_import_limits();
     Ok(config)
 }
 
@@ -610,6 +827,8 @@ pub struct DiscoveredSources {
     pub claude: Vec<String>,
     pub cursor: Vec<String>,
     pub codex_logs: Vec<String>,
+    pub cody: Vec<String>,
+    pub copilot: Vec<String>,
     pub collector: CollectorMigrationStatus,
 }
 
@@ -656,12 +875,24 @@ pub fn discover_capture_sources() -> Result<DiscoveredSources, String> {
         }
     }
 
+    let cody = discover_cody_storage_dirs()
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
+    let copilot = discover_copilot_chat_dirs()
+        .into_iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect();
+
     let collector = get_collector_migration_status_inner()?;
 
     Ok(DiscoveredSources {
         claude,
         cursor,
         codex_logs,
+        cody,
+        copilot,
         collector,
     })
 }
@@ -1159,6 +1390,10 @@ mod tests {
         let mut paths = WatchPaths {
             claude: Vec::new(),
             cursor: Vec::new(),
+            jetbrains: Vec::new(),
+            cody: Vec::new(),
+            opencode: Vec::new(),
+            copilot: Vec::new(),
             codex_logs: vec![
                 "~/.codex/sessions".to_string(),
                 "~/.codex/otel-collector".to_string(),
@@ -1,8 +1,9 @@
 mod activity;
 mod adapters;
 mod agent_tools;
-mod atlas;
+pub mod atlas;
 pub mod attribution;
+mod clock_offsets;
 mod codex_app_server;
 mod commands;
 mod file_watcher;
@@ -13,6 +14,7 @@ mod link_commands;
 mod linking;
 mod models;
 mod otlp_receiver;
+mod pr_links;
 mod recovery_checkpoint;
 pub mod approval_ledger;
 mod rules;
@@ -21,6 +23,8 @@ mod session_hash;
 mod session_links;
 pub mod story_anchors;
 mod trace_commands;
+mod worktrees;
+pub mod wip_anchors;
 
 use notify::RecommendedWatcher;
 use sqlx::{
@@ -79,7 +83,7 @@ async fn ensure_session_links_schema(pool: &SqlitePool) -> Result<(), sqlx::Erro
         confidence REAL NOT NULL,\
         auto_linked BOOLEAN NOT NULL DEFAULT 1,\
         created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),\
-        UNIQUE(repo_id, session_id),\
+        UNIQUE(repo_id, session_id, commit_sha),\
         FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE\
       )",
     )
@@ -120,9 +124,9 @@ impl std::ops::Deref for DbState {
     }
 }
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let migrations = vec![
+/// Schema migrations, applied in order by `tauri_plugin_sql` at startup
+fn migrations() -> Vec<Migration> {
+    vec![
         Migration {
             version: 1,
             description: "create_initial_tables",
@@ -231,8 +235,304 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             sql: include_str!("../migrations/018_trust_recovery_pause_reason.sql"),
             kind: MigrationKind::Up,
         },
-    ];
+        Migration {
+            version: 21,
+            description: "add_session_import_offsets",
+            sql: include_str!("../migrations/021_session_import_offsets.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 22,
+            description: "add_import_quarantine",
+            sql: include_str!("../migrations/022_import_quarantine.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 23,
+            description: "add_session_repos",
+            sql: include_str!("../migrations/023_session_repos.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 24,
+            description: "add_import_journal",
+            sql: include_str!("../migrations/024_import_journal.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 25,
+            description: "add_cursor_composer_watermarks",
+            sql: include_str!("../migrations/025_cursor_composer_watermarks.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 26,
+            description: "add_session_trace_chunks",
+            sql: include_str!("../migrations/026_session_trace_chunks.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 27,
+            description: "add_session_trimming",
+            sql: include_str!("../migrations/027_session_trimming.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 28,
+            description: "add_repo_linking_params",
+            sql: include_str!("../migrations/028_repo_linking_params.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 29,
+            description: "allow_multi_commit_session_links",
+            sql: include_str!("../migrations/029_multi_commit_session_links.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 30,
+            description: "add_linking_message_weight",
+            sql: include_str!("../migrations/030_linking_message_weight.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 31,
+            description: "add_session_git_branch",
+            sql: include_str!("../migrations/031_session_git_branch.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 32,
+            description: "add_session_link_review_reason",
+            sql: include_str!("../migrations/032_session_link_review_reason.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 33,
+            description: "add_link_feedback",
+            sql: include_str!("../migrations/033_link_feedback.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 34,
+            description: "add_session_edit_fingerprints",
+            sql: include_str!("../migrations/034_session_edit_fingerprints.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 35,
+            description: "add_tool_clock_offsets",
+            sql: include_str!("../migrations/035_tool_clock_offsets.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 36,
+            description: "add_repo_link_scopes",
+            sql: include_str!("../migrations/036_repo_link_scopes.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 37,
+            description: "add_link_feedback_confidence",
+            sql: include_str!("../migrations/037_link_feedback_confidence.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 38,
+            description: "add_session_pr_links",
+            sql: include_str!("../migrations/038_session_pr_links.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 39,
+            description: "add_wip_anchors",
+            sql: include_str!("../migrations/039_wip_anchors.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 40,
+            description: "add_commit_rewrites",
+            sql: include_str!("../migrations/040_commit_rewrites.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 41,
+            description: "add_symbol_attributions",
+            sql: include_str!("../migrations/041_symbol_attributions.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 42,
+            description: "add_attribution_exclusions",
+            sql: include_str!("../migrations/042_attribution_exclusions.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 43,
+            description: "add_attribution_timeseries",
+            sql: include_str!("../migrations/043_attribution_timeseries.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 44,
+            description: "add_attribution_directory_rollups",
+            sql: include_str!("../migrations/044_attribution_directory_rollups.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 45,
+            description: "add_merge_commit_stats",
+            sql: include_str!("../migrations/045_merge_commit_stats.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 46,
+            description: "add_completion_acceptances",
+            sql: include_str!("../migrations/046_completion_acceptances.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 47,
+            description: "add_stats_cache_invalidation",
+            sql: include_str!("../migrations/047_stats_cache_invalidation.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 48,
+            description: "add_attribution_batch_queue",
+            sql: include_str!("../migrations/048_attribution_batch_queue.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 49,
+            description: "add_line_attribution_confidence",
+            sql: include_str!("../migrations/049_line_attribution_confidence.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 50,
+            description: "attribution_policies",
+            sql: include_str!("../migrations/050_attribution_policies.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 51,
+            description: "attribution_language_overrides",
+            sql: include_str!("../migrations/051_attribution_language_overrides.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 52,
+            description: "deleted_line_attributions",
+            sql: include_str!("../migrations/052_deleted_line_attributions.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 53,
+            description: "commit_trailers",
+            sql: include_str!("../migrations/053_commit_trailers.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 54,
+            description: "signed_notes",
+            sql: include_str!("../migrations/054_signed_notes.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 55,
+            description: "auto_export_notes",
+            sql: include_str!("../migrations/055_auto_export_notes.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 56,
+            description: "story_anchor_status_cache",
+            sql: include_str!("../migrations/056_story_anchor_status_cache.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 57,
+            description: "notes_namespace",
+            sql: include_str!("../migrations/057_notes_namespace.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 58,
+            description: "release_anchors",
+            sql: include_str!("../migrations/058_release_anchors.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 59,
+            description: "import_fetched_notes",
+            sql: include_str!("../migrations/059_import_fetched_notes.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 60,
+            description: "atlas_embeddings",
+            sql: include_str!("../migrations/060_atlas_embeddings.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 61,
+            description: "atlas_index_queue",
+            sql: include_str!("../migrations/061_atlas_index_queue.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 62,
+            description: "atlas_chunk_budget",
+            sql: include_str!("../migrations/062_atlas_chunk_budget.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 63,
+            description: "atlas_tokenizer",
+            sql: include_str!("../migrations/063_atlas_tokenizer.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 64,
+            description: "atlas_chunking_settings",
+            sql: include_str!("../migrations/064_atlas_chunking_settings.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 65,
+            description: "session_owner_handoff",
+            sql: include_str!("../migrations/065_session_owner_handoff.sql"),
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 66,
+            description: "repo_linking_defaults_version",
+            sql: include_str!("../migrations/066_repo_linking_defaults_version.sql"),
+            kind: MigrationKind::Up,
+        },
+    ]
+}
 
+/// In-memory database with every migration applied, for tests
+#[cfg(test)]
+pub(crate) async fn test_pool() -> SqlitePool {
+    let pool = sqlx::sqlite::SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("memory sqlite");
+    for migration in migrations() {
+        sqlx::query(migration.sql)
+            .execute(&pool)
+            .await
+            .unwrap_or_else(|e| panic!("migration {}: {e}", migration.version));
+    }
+    pool
+}
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     // MCP Bridge: loaded only when compiled with `--features mcp`
     // This keeps the plugin entirely out of production/release builds.
     // Usage: `cargo tauri dev -- --features mcp`
@@ -241,6 +541,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         .invoke_handler(tauri::generate_handler![
             activity::get_ingest_activity,
             activity::get_commit_capture_bundle,
+            activity::query_import_history,
             commands::ensure_narrative_dirs,
             commands::write_narrative_file,
             commands::read_narrative_file,
@@ -255,15 +556,42 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             // Linking algorithm commands
             link_commands::link_session_to_commit,
             link_commands::import_and_link_session_file,
+            link_commands::get_linking_params,
+            link_commands::set_linking_params,
+            link_commands::get_link_feedback_summary,
+            link_commands::reset_learned_linking_params,
+            link_commands::get_link_calibration_report,
+            pr_links::upsert_pull_request,
+            pr_links::get_sessions_for_pr,
+            wip_anchors::anchor_session_to_worktree,
+            wip_anchors::get_wip_anchors,
+            link_commands::get_link_scopes,
+            link_commands::set_link_scopes,
+            clock_offsets::get_tool_clock_offsets,
+            clock_offsets::set_tool_clock_offset,
+            clock_offsets::detect_tool_clock_offsets,
             agent_tools::session_tools::agent_list_sessions,
             agent_tools::session_tools::agent_get_session,
             agent_tools::session_tools::agent_link_session_to_commit,
             agent_tools::session_tools::agent_link_session,
             // Import commands
             import::commands::import_session_files,
+            import::commands::cancel_session_import,
+            import::commands::list_quarantined_imports,
+            import::commands::confirm_quarantined_import,
+            import::commands::discard_quarantined_import,
             import::commands::import_session_file,
             import::commands::auto_import_session_file,
+            import::commands::import_session_from_text,
+            import::commands::import_cursor_composer_db,
+            import::commands::import_session_file_incremental,
+            import::commands::reimport_session,
+            import::commands::relink_sessions_for_repo,
+            import::commands::relink_batch,
+            import::commands::get_unlinked_sessions_report,
+            import::commands::resume_pending_imports,
             import::commands::scan_for_session_files,
+            import::commands::get_parser_capabilities,
             import::commands::get_recent_sessions,
             import::commands::purge_expired_sessions,
             atlas::commands::atlas_capabilities,
@@ -272,11 +600,23 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             atlas::commands::atlas_get_session,
             atlas::commands::atlas_doctor_report,
             atlas::commands::atlas_doctor_rebuild_derived,
+            atlas::commands::atlas_embed_chunks,
+            atlas::commands::atlas_index_pending,
+            atlas::commands::atlas_compact,
+            atlas::commands::atlas_get_tokenizer,
+            atlas::commands::atlas_set_tokenizer,
+            atlas::commands::atlas_export,
+            atlas::commands::atlas_import,
+            atlas::commands::atlas_reindex_session,
+            atlas::commands::atlas_get_chunking,
+            atlas::commands::atlas_set_chunking,
             // Git diff commands
             git_diff::get_commit_added_ranges,
             // Attribution commands
             attribution::commands::get_commit_contribution_stats,
             attribution::commands::get_file_source_lens,
+            attribution::commands::get_file_current_attribution,
+            attribution::commands::get_file_symbol_attribution,
             attribution::commands::compute_stats_batch,
             attribution::commands::import_attribution_note,
             attribution::commands::import_attribution_notes_batch,
@@ -285,7 +625,27 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             attribution::commands::get_attribution_prefs,
             attribution::commands::set_attribution_prefs,
             attribution::commands::purge_attribution_prompt_meta,
+            attribution::commands::get_attribution_exclusions,
+            attribution::commands::set_attribution_exclusions,
+            attribution::commands::get_attribution_language_overrides,
+            attribution::commands::set_attribution_language_overrides,
+            attribution::commands::get_commit_deletion_stats,
+            attribution::commands::get_attribution_timeseries,
+            attribution::commands::get_directory_attribution,
+            attribution::commands::export_attribution_report,
+            attribution::commands::export_attribution_sarif,
+            attribution::commands::export_ai_bom,
+            attribution::commands::record_completion_acceptances,
+            attribution::commands::import_completion_log,
+            attribution::commands::start_attribution_batch,
+            attribution::commands::cancel_attribution_batch,
+            attribution::commands::get_attribution_batch_status,
+            attribution::commands::get_attribution_policy,
+            attribution::commands::set_attribution_policy,
+            attribution::commands::check_attribution_policy,
             attribution::dashboard::get_dashboard_stats,
+            attribution::dashboard::get_author_attribution_stats,
+            attribution::dashboard::export_dashboard_html,
             // OTLP receiver commands
             otlp_receiver::set_active_repo_root,
             otlp_receiver::set_otlp_receiver_enabled,
@@ -333,18 +693,32 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             codex_app_server::codex_app_server_retry_hydrate,
             codex_app_server::codex_app_server_clear_stale_state,
             import::commands::backfill_recent_sessions,
+            import::commands::import_chatgpt_export,
             // Story Anchors (Git Notes + hooks)
             story_anchors::commands::get_story_anchor_status,
+            story_anchors::commands::get_commit_lineage,
             story_anchors::commands::import_session_link_notes_batch,
             story_anchors::commands::export_session_link_note,
+            story_anchors::commands::export_notes_for_range,
             story_anchors::commands::link_sessions_to_commit,
             story_anchors::commands::migrate_attribution_notes_ref,
+            story_anchors::commands::migrate_notes_schema,
+            story_anchors::commands::gc_narrative_notes,
+            story_anchors::commands::set_notes_namespace,
+            story_anchors::commands::create_release_anchor,
+            story_anchors::commands::get_release_anchors,
+            story_anchors::commands::import_fetched_notes,
+            story_anchors::commands::get_anchor_coverage,
             story_anchors::commands::reconcile_after_rewrite,
             story_anchors::commands::install_repo_hooks,
             story_anchors::commands::uninstall_repo_hooks,
             story_anchors::commands::get_repo_hooks_status,
+            story_anchors::commands::doctor_repo_hooks,
             story_anchors::commands::check_git_notes_fetch_config,
             story_anchors::commands::configure_git_notes_fetch,
+            story_anchors::commands::push_narrative_notes,
+            story_anchors::commands::fetch_narrative_notes,
+            story_anchors::commands::merge_narrative_notes,
         ])
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
@@ -353,7 +727,7 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(
             tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:narrative.db", migrations)
+                .add_migrations("sqlite:narrative.db", migrations())
                 .build(),
         )
         .setup(|app| {
@@ -406,6 +780,11 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
             })?;
 
             app.manage(DbState(Arc::new(pool)));
+            ingest_config::load_config()
+                .unwrap_or_default()
+                .apply_import_limits();
+            import::backfill_scheduler::spawn(app.handle().clone());
+            atlas::index_queue::spawn(app.handle().clone());
 
             let otel_state = otlp_receiver::OtelReceiverState::default();
             app.manage(otel_state.clone());
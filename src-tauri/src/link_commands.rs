@@ -8,6 +8,9 @@
 //!
 //! - `link_session_to_commit` - Link a session to the best matching commit
 //! - `import_session_file` - Import a session from a JSON file
+//! - `get_linking_params` / `set_linking_params` - Per-repo score weights and threshold
+//! - `get_link_scopes` / `set_link_scopes` - Monorepo sub-paths that narrow candidates
+//! - `get_link_calibration_report` - Precision of suggested links by confidence
 //!
 //! # Evidence
 //!
@@ -16,13 +19,16 @@
 
 use crate::{
     linking::{
-        detect_secrets, link_session_to_commits, GitCommit, LinkResult, SessionExcerpt,
-        SessionMessage, SessionMessageRole, SessionTool,
+        calibrate_linking_params, link_session_to_all_commits_with_options, FeedbackSample,
+        GitCommit, LinkOptions, LinkResult, LinkingParams, SessionExcerpt, SessionMessage,
+        SessionMessageRole, SessionTool,
     },
     DbState,
 };
+use git2::{DiffFormat, DiffOptions, Oid, Repository};
 use sqlx::{Row, SqlitePool};
 use std::collections::HashMap;
+use std::sync::RwLock;
 use tauri::State;
 
 /// Convert frontend SessionTool to backend SessionTool
@@ -50,12 +56,14 @@ fn convert_session_excerpt(session: FrontendSessionExcerpt) -> SessionExcerpt {
                 role: match m.role.as_str() {
                     "user" => SessionMessageRole::User,
                     "assistant" => SessionMessageRole::Assistant,
+                    "plan" => SessionMessageRole::Plan,
                     _ => SessionMessageRole::User,
                 },
                 text: m.text,
                 files: m.files,
             })
             .collect(),
+        edit_fingerprints: Vec::new(),
     }
 }
 
@@ -81,6 +89,10 @@ pub struct FrontendSessionMessage {
 
 /// Query commits for a repository within a time window.
 ///
+/// Commits indexed under the repo's linked worktrees (registered repos that
+/// share its history) are included, since a session's commit may have been
+/// made from any of them.
+///
 /// # Arguments
 ///
 /// * `pool` - SQLite database pool
@@ -98,36 +110,46 @@ pub(crate) async fn query_commits_in_window(
     window_start: &str,
     window_end: &str,
 ) -> Result<Vec<GitCommit>, String> {
+    let repo_ids = crate::worktrees::repo_ids_sharing_history(pool, repo_id).await?;
+    let repo_placeholders = vec!["?"; repo_ids.len()].join(", ");
+
     // Query commits in time window
-    let commit_rows = sqlx::query(
+    let commit_query = format!(
         r#"
         SELECT sha, authored_at, subject
         FROM commits
-        WHERE repo_id = $1 AND authored_at >= $2 AND authored_at <= $3
+        WHERE repo_id IN ({}) AND authored_at >= ? AND authored_at <= ?
         ORDER BY authored_at ASC
         "#,
-    )
-    .bind(repo_id)
-    .bind(window_start)
-    .bind(window_end)
-    .fetch_all(pool)
-    .await
-    .map_err(|e| format!("Database error: {}", e))?;
+        repo_placeholders
+    );
+    let mut query = sqlx::query(&commit_query);
+    for id in &repo_ids {
+        query = query.bind(id);
+    }
+    let commit_rows = query
+        .bind(window_start)
+        .bind(window_end)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
 
-    // Query file changes for each commit
+    // Query file changes for each commit (worktrees index the same commit
+    // under each repo, so keep the first)
     let mut commits_by_sha: HashMap<String, GitCommit> = HashMap::new();
 
     for row in commit_rows {
         let sha: String = row.get("sha");
-        commits_by_sha.insert(
-            sha.clone(),
-            GitCommit {
+        commits_by_sha
+            .entry(sha.clone())
+            .or_insert_with(|| GitCommit {
                 sha,
                 authored_at: row.get("authored_at"),
                 message: row.get("subject"),
                 files: Vec::new(),
-            },
-        );
+                author_matches: None,
+                added_fingerprints: Vec::new(),
+            });
     }
 
     // If no commits found, return empty
@@ -137,25 +159,17 @@ pub(crate) async fn query_commits_in_window(
 
     // Query file changes for all commits
     let shas: Vec<&String> = commits_by_sha.keys().collect();
-
-    // Build dynamic query for file changes
-    if shas.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let placeholders = shas
-        .iter()
-        .enumerate()
-        .map(|(i, _)| format!("${}", i + 4)) // Start at $4 because $1=$3 are used above
-        .collect::<Vec<_>>()
-        .join(", ");
+    let sha_placeholders = vec!["?"; shas.len()].join(", ");
 
     let file_query = format!(
-        "SELECT commit_sha, path FROM file_changes WHERE repo_id = $1 AND commit_sha IN ({})",
-        placeholders
+        "SELECT DISTINCT commit_sha, path FROM file_changes WHERE repo_id IN ({}) AND commit_sha IN ({})",
+        repo_placeholders, sha_placeholders
     );
 
-    let mut query = sqlx::query(&file_query).bind(repo_id);
+    let mut query = sqlx::query(&file_query);
+    for id in &repo_ids {
+        query = query.bind(id);
+    }
     for sha in &shas {
         query = query.bind(sha);
     }
@@ -178,14 +192,766 @@ pub(crate) async fn query_commits_in_window(
     Ok(commits_by_sha.into_values().collect())
 }
 
-/// Link a session to its best matching commit.
+/// The `limit` commits authored closest to `at`, closest first, with their
+/// changed files. Used to explain sessions with no commits in their window.
+pub(crate) async fn query_closest_commits(
+    pool: &SqlitePool,
+    repo_id: i64,
+    at: &str,
+    limit: i64,
+) -> Result<Vec<GitCommit>, String> {
+    let repo_ids = crate::worktrees::repo_ids_sharing_history(pool, repo_id).await?;
+    let repo_placeholders = vec!["?"; repo_ids.len()].join(", ");
+    let closest_query = format!(
+        r#"
+        SELECT sha, authored_at
+        FROM commits
+        WHERE repo_id IN ({}) AND authored_at IS NOT NULL
+        ORDER BY ABS(julianday(authored_at) - julianday(?)) ASC
+        LIMIT ?
+        "#,
+        repo_placeholders
+    );
+    let mut query = sqlx::query_as::<_, (String, String)>(&closest_query);
+    for id in &repo_ids {
+        query = query.bind(id);
+    }
+    let closest = query
+        .bind(at)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let (Some(earliest), Some(latest)) = (
+        closest.iter().map(|(_, authored_at)| authored_at).min(),
+        closest.iter().map(|(_, authored_at)| authored_at).max(),
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let mut commits = query_commits_in_window(pool, repo_id, earliest, latest).await?;
+    commits.retain(|commit| closest.iter().any(|(sha, _)| *sha == commit.sha));
+    commits.sort_by_key(|commit| {
+        closest
+            .iter()
+            .position(|(sha, _)| *sha == commit.sha)
+            .unwrap_or(usize::MAX)
+    });
+    Ok(commits)
+}
+
+/// The user's git author names and emails (from the ingest config)
+static MY_IDENTITIES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Set the identities whose commits count as the user's own
+pub fn set_my_identities(identities: Vec<String>) {
+    if let Ok(mut current) = MY_IDENTITIES.write() {
+        *current = identities;
+    }
+}
+
+/// Look up candidate commits in the repo before scoring them.
+///
+/// Narrows the candidates to the repo's link scopes (`filter_to_scopes`) and
+/// to the session's branch (`filter_to_branch`), and records whether each
+/// commit's author is one of the user's identities. Candidates are returned
+/// unchanged when the repo cannot be opened.
+pub(crate) async fn prepare_candidates(
+    pool: &SqlitePool,
+    repo_id: i64,
+    git_branch: Option<&str>,
+    session_end: &chrono::DateTime<chrono::Utc>,
+    session_files: &[String],
+    commits: Vec<GitCommit>,
+) -> Vec<GitCommit> {
+    let Ok(repo_root) = crate::attribution::utils::fetch_repo_root(pool, repo_id).await else {
+        return commits;
+    };
+    let scopes = fetch_link_scopes(pool, repo_id).await.unwrap_or_default();
+    let commits = filter_to_scopes(&scopes, &repo_root, session_files, commits);
+    let Ok(repo) = Repository::open(repo_root) else {
+        return commits;
+    };
+    let mut commits = filter_to_branch(&repo, git_branch, session_end, commits);
+    let identities = MY_IDENTITIES
+        .read()
+        .map(|ids| ids.clone())
+        .unwrap_or_default();
+    mark_author_matches(&repo, &identities, &mut commits);
+    mark_added_fingerprints(&repo, &mut commits);
+    commits
+}
+
+/// Fingerprints of a stored session's edits (empty if none were recorded).
+async fn fetch_edit_fingerprints(
+    pool: &SqlitePool,
+    session_id: &str,
+) -> Result<Vec<String>, String> {
+    let stored: Option<Option<String>> =
+        sqlx::query_scalar("SELECT edit_fingerprints FROM sessions WHERE id = ?")
+            .bind(session_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    Ok(stored
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default())
+}
+
+/// Fill in fingerprints of the lines each commit adds, for diff scoring.
+fn mark_added_fingerprints(repo: &Repository, commits: &mut [GitCommit]) {
+    // Bulk commits (vendoring, formatting) say little and are slow to diff
+    const MAX_ADDED_LINES: usize = 5000;
+
+    for commit in commits.iter_mut() {
+        let Some(found) = Oid::from_str(&commit.sha)
+            .ok()
+            .and_then(|oid| repo.find_commit(oid).ok())
+        else {
+            continue;
+        };
+        let Ok(tree) = found.tree() else {
+            continue;
+        };
+        let parent_tree = found.parent(0).ok().and_then(|parent| parent.tree().ok());
+
+        let mut opts = DiffOptions::new();
+        opts.context_lines(0);
+        let Ok(diff) = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
+        else {
+            continue;
+        };
+
+        let mut added: Vec<String> = Vec::new();
+        let printed = diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+            if line.origin() == '+' {
+                added.push(String::from_utf8_lossy(line.content()).into_owned());
+            }
+            added.len() <= MAX_ADDED_LINES
+        });
+        if printed.is_ok() {
+            commit.added_fingerprints =
+                crate::linking::line_fingerprints(added.iter().map(String::as_str));
+        }
+    }
+}
+
+/// Sub-path a repo-relative or absolute file path falls under, if any.
+fn scope_of<'a>(scopes: &'a [String], repo_root: &str, file: &str) -> Option<&'a str> {
+    let file = file.replace('\\', "/");
+    let root = repo_root.replace('\\', "/");
+    let relative = file
+        .strip_prefix(root.trim_end_matches('/'))
+        .map(|rest| rest.trim_start_matches('/'))
+        .unwrap_or(file.as_str());
+    let relative = relative.trim_start_matches("./");
+    scopes
+        .iter()
+        .find(|scope| {
+            relative == scope.as_str()
+                || relative
+                    .strip_prefix(scope.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+        })
+        .map(String::as_str)
+}
+
+/// Narrow candidate commits to the monorepo sub-paths a session worked in.
+///
+/// Applies only when every file the session touched falls under one of the
+/// repo's link scopes; commits touching none of those scopes are dropped.
+/// Commits with no recorded files are kept. All candidates are kept when no
+/// scopes are configured, the session's files are unknown or reach outside
+/// the scopes, or no candidate touches them.
+fn filter_to_scopes(
+    scopes: &[String],
+    repo_root: &str,
+    session_files: &[String],
+    commits: Vec<GitCommit>,
+) -> Vec<GitCommit> {
+    if scopes.is_empty() || session_files.is_empty() {
+        return commits;
+    }
+    let mut session_scopes = Vec::new();
+    for file in session_files {
+        let Some(scope) = scope_of(scopes, repo_root, file) else {
+            return commits;
+        };
+        if !session_scopes.contains(&scope) {
+            session_scopes.push(scope);
+        }
+    }
+
+    let in_scope = |commit: &GitCommit| {
+        commit.files.iter().any(|file| {
+            scope_of(scopes, repo_root, file).is_some_and(|scope| session_scopes.contains(&scope))
+        })
+    };
+    if !commits.iter().any(in_scope) {
+        return commits;
+    }
+    commits
+        .into_iter()
+        .filter(|commit| commit.files.is_empty() || in_scope(commit))
+        .collect()
+}
+
+/// Narrow candidate commits to the branch a session ran on.
+///
+/// The branch comes from the tool's metadata, or else from the HEAD reflog
+/// (the last checkout before the session ended). Only commits reachable from
+/// that branch are kept, so a time-adjacent commit on an unrelated branch is
+/// not picked. All candidates are kept when the branch is unknown, cannot be
+/// resolved, or reaches none of them.
+fn filter_to_branch(
+    repo: &Repository,
+    git_branch: Option<&str>,
+    session_end: &chrono::DateTime<chrono::Utc>,
+    commits: Vec<GitCommit>,
+) -> Vec<GitCommit> {
+    let branch = match git_branch {
+        Some(branch) => Some(branch.to_string()),
+        None => branch_checked_out_at(repo, session_end),
+    };
+    let Some(tip) = branch
+        .and_then(|b| repo.revparse_single(&b).ok())
+        .and_then(|obj| obj.peel_to_commit().ok())
+        .map(|commit| commit.id())
+    else {
+        return commits;
+    };
+
+    let reachable = |sha: &str| {
+        Oid::from_str(sha)
+            .map(|oid| oid == tip || repo.graph_descendant_of(tip, oid).unwrap_or(false))
+            .unwrap_or(false)
+    };
+    if !commits.iter().any(|commit| reachable(&commit.sha)) {
+        return commits;
+    }
+    commits
+        .into_iter()
+        .filter(|commit| reachable(&commit.sha))
+        .collect()
+}
+
+/// Record whether each commit's author name or email is one of `identities`
+/// (case-insensitive). Left as None when no identities are configured.
+fn mark_author_matches(repo: &Repository, identities: &[String], commits: &mut [GitCommit]) {
+    if identities.is_empty() {
+        return;
+    }
+    for commit in commits.iter_mut() {
+        let Some(found) = Oid::from_str(&commit.sha)
+            .ok()
+            .and_then(|oid| repo.find_commit(oid).ok())
+        else {
+            continue;
+        };
+        let author = found.author();
+        let is_mine = |value: Option<&str>| {
+            value.is_some_and(|v| {
+                identities
+                    .iter()
+                    .any(|id| id.trim().eq_ignore_ascii_case(v))
+            })
+        };
+        commit.author_matches = Some(is_mine(author.name()) || is_mine(author.email()));
+    }
+}
+
+/// Branch HEAD pointed at, per the last `checkout` reflog entry before `at`.
+fn branch_checked_out_at(repo: &Repository, at: &chrono::DateTime<chrono::Utc>) -> Option<String> {
+    let reflog = repo.reflog("HEAD").ok()?;
+    // Entries are newest first.
+    reflog
+        .iter()
+        .filter(|entry| entry.committer().when().seconds() <= at.timestamp())
+        .find_map(|entry| {
+            entry
+                .message()?
+                .strip_prefix("checkout: moving from ")?
+                .rsplit_once(" to ")
+                .map(|(_, to)| to.to_string())
+        })
+}
+
+/// Partial update of a repo's linking parameters
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkingParamsUpdate {
+    pub temporal_weight: Option<f64>,
+    pub file_overlap_weight: Option<f64>,
+    pub message_weight: Option<f64>,
+    pub confidence_threshold: Option<f64>,
+}
+
+/// Linking parameters for a repo (when none are stored, the defaults of the
+/// repo's `linking_defaults_version`).
+pub(crate) async fn fetch_linking_params(
+    pool: &SqlitePool,
+    repo_id: i64,
+) -> Result<LinkingParams, String> {
+    let row: Option<(f64, f64, f64, f64)> = sqlx::query_as(
+        r#"
+        SELECT temporal_weight, file_overlap_weight, message_weight, confidence_threshold
+        FROM repo_linking_params
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    if let Some((temporal_weight, file_overlap_weight, message_weight, confidence_threshold)) = row
+    {
+        return Ok(LinkingParams {
+            temporal_weight,
+            file_overlap_weight,
+            message_weight,
+            confidence_threshold,
+        });
+    }
+
+    let version: Option<i64> =
+        sqlx::query_scalar("SELECT linking_defaults_version FROM repos WHERE id = ?")
+            .bind(repo_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    Ok(version.map_or_else(LinkingParams::default, LinkingParams::for_defaults_version))
+}
+
+/// Get the score weights and auto-link threshold used for a repo.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_linking_params(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<LinkingParams, String> {
+    fetch_linking_params(db_state.0.as_ref(), repo_id).await
+}
+
+/// Set a repo's score weights and auto-link threshold.
+///
+/// Omitted fields keep their current value. The weights must sum to 1.
+/// Existing links are not re-scored.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_linking_params(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    update: LinkingParamsUpdate,
+) -> Result<LinkingParams, String> {
+    update_linking_params(db_state.0.as_ref(), repo_id, update).await
+}
+
+async fn update_linking_params(
+    pool: &SqlitePool,
+    repo_id: i64,
+    update: LinkingParamsUpdate,
+) -> Result<LinkingParams, String> {
+    let current = fetch_linking_params(pool, repo_id).await?;
+    let next = LinkingParams {
+        temporal_weight: update.temporal_weight.unwrap_or(current.temporal_weight),
+        file_overlap_weight: update
+            .file_overlap_weight
+            .unwrap_or(current.file_overlap_weight),
+        message_weight: update.message_weight.unwrap_or(current.message_weight),
+        confidence_threshold: update
+            .confidence_threshold
+            .unwrap_or(current.confidence_threshold),
+    };
+    next.validate()?;
+
+    // Params set by hand are kept as-is (learning pauses until a reset).
+    store_linking_params(pool, repo_id, &next, 0).await?;
+
+    Ok(next)
+}
+
+async fn store_linking_params(
+    pool: &SqlitePool,
+    repo_id: i64,
+    params: &LinkingParams,
+    learned_from: i64,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO repo_linking_params (repo_id, temporal_weight, file_overlap_weight, message_weight, confidence_threshold, learned_from)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id) DO UPDATE SET
+            temporal_weight = excluded.temporal_weight,
+            file_overlap_weight = excluded.file_overlap_weight,
+            message_weight = excluded.message_weight,
+            confidence_threshold = excluded.confidence_threshold,
+            learned_from = excluded.learned_from,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(repo_id)
+    .bind(params.temporal_weight)
+    .bind(params.file_overlap_weight)
+    .bind(params.message_weight)
+    .bind(params.confidence_threshold)
+    .bind(learned_from)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to store linking params: {}", e))?;
+
+    Ok(())
+}
+
+/// Linking params in effect for a repo and the corrections behind them
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkFeedbackSummary {
+    pub params: LinkingParams,
+    /// Samples the params were fitted on (0 = defaults or set by hand)
+    pub learned_from: i64,
+    pub accepted: i64,
+    pub rejected: i64,
+}
+
+/// Record that the user kept a suggested link (by linking it manually).
+pub(crate) async fn record_confirmed_link(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    commit_sha: &str,
+) -> Result<(), String> {
+    record_link_feedback(pool, repo_id, session_id, Some(commit_sha), true).await
+}
+
+/// Record that the user removed suggested links (all of the session's when
+/// `commit_sha` is None).
+pub(crate) async fn record_rejected_links(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    commit_sha: Option<&str>,
+) -> Result<(), String> {
+    record_link_feedback(pool, repo_id, session_id, commit_sha, false).await
+}
+
+/// Copy the scores of matching auto links into `link_feedback`, then refit.
+async fn record_link_feedback(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    commit_sha: Option<&str>,
+    accepted: bool,
+) -> Result<(), String> {
+    let recorded = sqlx::query(
+        r#"
+        INSERT INTO link_feedback (repo_id, session_id, commit_sha, temporal_score, file_score, message_score, confidence, accepted)
+        SELECT repo_id, session_id, commit_sha, temporal_score, file_score, COALESCE(message_score, 0), confidence, ?
+        FROM session_links
+        WHERE repo_id = ? AND session_id = ? AND (? IS NULL OR commit_sha = ?)
+          AND auto_linked = 1
+          AND temporal_score IS NOT NULL AND file_score IS NOT NULL
+        "#,
+    )
+    .bind(accepted)
+    .bind(repo_id)
+    .bind(session_id)
+    .bind(commit_sha)
+    .bind(commit_sha)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to record link feedback: {}", e))?
+    .rows_affected();
+
+    if recorded > 0 {
+        relearn_linking_params(pool, repo_id).await?;
+    }
+    Ok(())
+}
+
+/// Refit a repo's params from its feedback, unless they were set by hand.
+async fn relearn_linking_params(pool: &SqlitePool, repo_id: i64) -> Result<(), String> {
+    let learned_from: Option<i64> =
+        sqlx::query_scalar("SELECT learned_from FROM repo_linking_params WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    if learned_from == Some(0) {
+        return Ok(());
+    }
+
+    let rows: Vec<(f64, f64, f64, bool)> = sqlx::query_as(
+        r#"
+        SELECT temporal_score, file_score, message_score, accepted
+        FROM link_feedback
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    let samples: Vec<FeedbackSample> = rows
+        .into_iter()
+        .map(
+            |(temporal_score, file_score, message_score, accepted)| FeedbackSample {
+                temporal_score,
+                file_score,
+                message_score,
+                accepted,
+            },
+        )
+        .collect();
+
+    match calibrate_linking_params(&samples) {
+        Some(params) => store_linking_params(pool, repo_id, &params, samples.len() as i64).await,
+        None => Ok(()),
+    }
+}
+
+async fn fetch_link_feedback_summary(
+    pool: &SqlitePool,
+    repo_id: i64,
+) -> Result<LinkFeedbackSummary, String> {
+    let params = fetch_linking_params(pool, repo_id).await?;
+    let learned_from: i64 =
+        sqlx::query_scalar("SELECT learned_from FROM repo_linking_params WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .unwrap_or(0);
+    let (accepted, rejected): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+          COALESCE(SUM(CASE WHEN accepted = 1 THEN 1 ELSE 0 END), 0),
+          COALESCE(SUM(CASE WHEN accepted = 0 THEN 1 ELSE 0 END), 0)
+        FROM link_feedback
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(LinkFeedbackSummary {
+        params,
+        learned_from,
+        accepted,
+        rejected,
+    })
+}
+
+/// Precision the calibration report aims for by default
+const DEFAULT_TARGET_PRECISION: f64 = 0.9;
+
+/// Suggested links bucketed by confidence against the user's corrections
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCalibrationReport {
+    /// The repo's current auto-link threshold
+    pub threshold: f64,
+    pub target_precision: f64,
+    /// Lowest bucket bound reaching the target precision, if any
+    pub suggested_threshold: Option<f64>,
+    pub buckets: Vec<crate::linking::ConfidenceBucket>,
+}
+
+/// Report how often suggested links at each confidence were kept.
+///
+/// Confirmed and rejected links come from the repo's link corrections;
+/// links corrected before their confidence was recorded are scored with the
+/// current weights. `target_precision` (default 0.9) picks the suggested
+/// threshold.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_link_calibration_report(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    target_precision: Option<f64>,
+) -> Result<LinkCalibrationReport, String> {
+    let target_precision = target_precision.unwrap_or(DEFAULT_TARGET_PRECISION);
+    if !(0.0..=1.0).contains(&target_precision) {
+        return Err("targetPrecision must be between 0 and 1".to_string());
+    }
+    build_link_calibration_report(db_state.0.as_ref(), repo_id, target_precision).await
+}
+
+async fn build_link_calibration_report(
+    pool: &SqlitePool,
+    repo_id: i64,
+    target_precision: f64,
+) -> Result<LinkCalibrationReport, String> {
+    let params = fetch_linking_params(pool, repo_id).await?;
+    let feedback: Vec<(Option<f64>, f64, f64, f64, bool)> = sqlx::query_as(
+        r#"
+        SELECT confidence, temporal_score, file_score, message_score, accepted
+        FROM link_feedback
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    let reviewed: Vec<(f64, bool)> = feedback
+        .into_iter()
+        .map(|(confidence, temporal, file, message, accepted)| {
+            let confidence = confidence.unwrap_or(
+                params.temporal_weight * temporal
+                    + params.file_overlap_weight * file
+                    + params.message_weight * message,
+            );
+            (confidence, accepted)
+        })
+        .collect();
+    let unreviewed: Vec<f64> = sqlx::query_scalar(
+        "SELECT confidence FROM session_links WHERE repo_id = ? AND auto_linked = 1",
+    )
+    .bind(repo_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let buckets = crate::linking::bucket_link_confidence(&reviewed, &unreviewed);
+    Ok(LinkCalibrationReport {
+        threshold: params.confidence_threshold,
+        target_precision,
+        suggested_threshold: crate::linking::suggest_confidence_threshold(
+            &buckets,
+            target_precision,
+        ),
+        buckets,
+    })
+}
+
+/// Get the linking params learned from a repo's link corrections.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_link_feedback_summary(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<LinkFeedbackSummary, String> {
+    fetch_link_feedback_summary(db_state.0.as_ref(), repo_id).await
+}
+
+/// Forget a repo's link corrections and any params learned from them.
+///
+/// Params set by hand are kept. Learning resumes from the next correction.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn reset_learned_linking_params(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<LinkFeedbackSummary, String> {
+    let pool = db_state.0.as_ref();
+    reset_link_feedback(pool, repo_id).await?;
+    fetch_link_feedback_summary(pool, repo_id).await
+}
+
+async fn reset_link_feedback(pool: &SqlitePool, repo_id: i64) -> Result<(), String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM link_feedback WHERE repo_id = ?")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM repo_linking_params WHERE repo_id = ? AND learned_from > 0")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Normalize link scopes: repo-relative directories without leading `./` or
+/// trailing `/`, deduplicated and sorted.
+fn normalize_link_scopes(paths: Vec<String>) -> Vec<String> {
+    let mut scopes: Vec<String> = paths
+        .into_iter()
+        .map(|path| {
+            let path = path.trim().replace('\\', "/");
+            let path = path.trim_start_matches("./").trim_matches('/');
+            path.to_string()
+        })
+        .filter(|path| !path.is_empty() && path != ".")
+        .collect();
+    scopes.sort();
+    scopes.dedup();
+    scopes
+}
+
+async fn fetch_link_scopes(pool: &SqlitePool, repo_id: i64) -> Result<Vec<String>, String> {
+    sqlx::query_scalar("SELECT path FROM repo_link_scopes WHERE repo_id = ? ORDER BY path")
+        .bind(repo_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Get a repo's link scopes (monorepo sub-paths, e.g. `packages/api`).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_link_scopes(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<Vec<String>, String> {
+    fetch_link_scopes(db_state.0.as_ref(), repo_id).await
+}
+
+/// Replace a repo's link scopes. An empty list turns scoping off.
+///
+/// A session whose files all fall under the scopes is only scored against
+/// commits touching those scopes.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_link_scopes(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    paths: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let pool = db_state.0.as_ref();
+    store_link_scopes(pool, repo_id, &normalize_link_scopes(paths)).await?;
+    fetch_link_scopes(pool, repo_id).await
+}
+
+async fn store_link_scopes(
+    pool: &SqlitePool,
+    repo_id: i64,
+    scopes: &[String],
+) -> Result<(), String> {
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM repo_link_scopes WHERE repo_id = ?")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for scope in scopes {
+        sqlx::query("INSERT INTO repo_link_scopes (repo_id, path) VALUES (?, ?)")
+            .bind(repo_id)
+            .bind(scope)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Link a session to its matching commits.
 ///
 /// This command:
 /// 1. Parses the session data
 /// 2. Queries commits in the time window
-/// 3. Runs the linking algorithm
-/// 4. Stores the link if confidence >= 0.7
-/// 5. Returns the link result
+/// 3. Runs the linking algorithm with the repo's linking params
+/// 4. Stores a link for every commit with confidence >= the repo's threshold
+///    (0.7 by default), replacing the session's earlier auto links
+/// 5. Returns the best link
 ///
 /// # Arguments
 ///
@@ -210,10 +976,16 @@ pub async fn link_session_to_commit(
 ) -> Result<LinkResult, String> {
     let db = db_state.0.as_ref();
 
-    // Calculate time window for commit lookup (±4 hours from session)
+    // Calculate time window for commit lookup (±4 hours from session),
+    // correcting for the tool's clock offset
+    let mut session_data = session_data;
+    let clock_offset =
+        crate::clock_offsets::fetch_session_clock_offset(db, &session_data.id).await?;
     let session_end = chrono::DateTime::parse_from_rfc3339(&session_data.imported_at_iso)
         .map_err(|e| format!("Invalid session timestamp: {}", e))?
-        .with_timezone(&chrono::Utc);
+        .with_timezone(&chrono::Utc)
+        + chrono::Duration::minutes(clock_offset);
+    session_data.imported_at_iso = session_end.to_rfc3339();
     let tolerance = chrono::Duration::minutes(240); // 4 hours
     let window_start = (session_end - tolerance)
         .format("%Y-%m-%dT%H:%M:%SZ")
@@ -225,44 +997,41 @@ pub async fn link_session_to_commit(
     // Query commits in time window
     let commits = query_commits_in_window(db, repo_id, &window_start, &window_end).await?;
 
-    // Convert to backend format
-    let session = convert_session_excerpt(session_data);
+    // Convert to backend format; edit fingerprints are only kept server-side
+    let mut session = convert_session_excerpt(session_data);
+    session.edit_fingerprints = fetch_edit_fingerprints(db, &session.id).await?;
 
-    // Run linking algorithm
-    let result = link_session_to_commits(&session, &commits).map_err(|e| format!("{}", e))?;
+    let session_files = crate::linking::extract_session_files(&session.messages);
+    let commits =
+        prepare_candidates(db, repo_id, None, &session_end, &session_files, commits).await;
 
-    // Store the link in database
-    sqlx::query(
-        r#"
-        INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked, needs_review)
-        VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT(repo_id, session_id) DO UPDATE SET
-            commit_sha = excluded.commit_sha,
-            confidence = excluded.confidence,
-            auto_linked = excluded.auto_linked,
-            needs_review = excluded.needs_review
-        RETURNING id
-        "#,
+    // Run linking algorithm
+    let params = fetch_linking_params(db, repo_id).await?;
+    let mut links = link_session_to_all_commits_with_options(
+        &session,
+        &commits,
+        LinkOptions {
+            skip_secret_scan: false,
+            params,
+        },
     )
-    .bind(repo_id)
-    .bind(&session.id)
-    .bind(&result.commit_sha)
-    .bind(result.confidence)
-    .bind(result.auto_linked)
-    .bind(if result.needs_review { 1 } else { 0 })
-    .fetch_one(db)
-    .await
-    .map_err(|e| format!("Failed to store link: {}", e))?
-    .get::<i64, _>("id");
+    .map_err(|e| format!("{}", e))?;
 
-    // Return result
+    // Store every link in database
+    crate::session_links::replace_auto_links(db, repo_id, &session.id, &links).await?;
+
+    // Return the best match
+    let result = links.swap_remove(0);
     Ok(LinkResult {
         commit_sha: result.commit_sha,
         confidence: result.confidence,
         auto_linked: result.auto_linked,
         temporal_score: result.temporal_score,
         file_score: result.file_score,
+        message_score: result.message_score,
+        diff_score: result.diff_score,
         needs_review: result.needs_review,
+        review_reason: result.review_reason,
     })
 }
 
@@ -271,9 +1040,9 @@ pub async fn link_session_to_commit(
 /// This command:
 /// 1. Reads the session file from disk
 /// 2. Validates the format
-/// 3. Scans for secrets (security check)
-/// 4. Links to best matching commit
-/// 5. Returns the result or detected secrets
+/// 3. Links to best matching commit (secrets in messages are redacted
+///    before scoring)
+/// 4. Returns the result
 ///
 /// # Arguments
 ///
@@ -284,7 +1053,7 @@ pub async fn link_session_to_commit(
 /// # Returns
 ///
 /// * `Ok(LinkResult)` - Session imported and linked
-/// * `Err(String)` - Import failed (includes secrets detected)
+/// * `Err(String)` - Import failed
 ///
 /// # Evidence
 ///
@@ -314,23 +1083,206 @@ pub async fn import_and_link_session_file(
     let session_data: FrontendSessionExcerpt = serde_json::from_str(&session_content)
         .map_err(|e| format!("Failed to parse session JSON: {}", e))?;
 
-    // Convert to backend format for secret scanning
-    let session = convert_session_excerpt(session_data.clone());
+    // Import using link_session_to_commit command
+    link_session_to_commit(db_state, repo_id, session_data).await
+}
 
-    // Security: Scan for secrets before processing
-    let detected_secrets: Vec<String> = session
-        .messages
-        .iter()
-        .flat_map(|msg| detect_secrets(&msg.text))
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::{Commit, Signature};
 
-    if !detected_secrets.is_empty() {
-        return Err(format!(
-            "Secrets detected in session: {}. Please redact before importing.",
-            detected_secrets.join(", ")
-        ));
+    fn commit(repo: &Repository, parent: Option<Oid>, message: &str) -> Oid {
+        commit_by(repo, parent, message, "Test", "test@example.com")
     }
 
-    // Import using link_session_to_commit command
-    link_session_to_commit(db_state, repo_id, session_data).await
+    fn commit_by(
+        repo: &Repository,
+        parent: Option<Oid>,
+        message: &str,
+        name: &str,
+        email: &str,
+    ) -> Oid {
+        let sig = Signature::now(name, email).unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parents: Vec<Commit> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&Commit> = parents.iter().collect();
+        repo.commit(None, &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+    }
+
+    fn candidate(sha: Oid) -> GitCommit {
+        GitCommit {
+            sha: sha.to_string(),
+            authored_at: "2024-01-15T14:20:00Z".to_string(),
+            message: String::new(),
+            files: Vec::new(),
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn filter_to_branch_prefers_commits_on_session_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit(&repo, None, "base");
+        let on_main = commit(&repo, Some(base), "main work");
+        let on_feature = commit(&repo, Some(base), "feature work");
+        repo.reference("refs/heads/main", on_main, true, "test")
+            .unwrap();
+        repo.reference("refs/heads/feature", on_feature, true, "test")
+            .unwrap();
+
+        let now = chrono::Utc::now();
+        let candidates = || vec![candidate(on_main), candidate(on_feature)];
+
+        let kept = filter_to_branch(&repo, Some("feature"), &now, candidates());
+        let shas: Vec<String> = kept.into_iter().map(|c| c.sha).collect();
+        assert_eq!(shas, vec![on_feature.to_string()]);
+
+        // Unknown branch: nothing to prefer, keep every candidate.
+        let kept = filter_to_branch(&repo, Some("no-such-branch"), &now, candidates());
+        assert_eq!(kept.len(), 2);
+    }
+
+    #[test]
+    fn filter_to_scopes_keeps_commits_in_session_sub_path() {
+        let touching = |sha: &str, files: &[&str]| GitCommit {
+            sha: sha.to_string(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            ..candidate(Oid::zero())
+        };
+        let candidates = || {
+            vec![
+                touching("api", &["packages/api/src/routes.ts"]),
+                touching("web", &["packages/web/src/app.tsx"]),
+                touching("unknown", &[]),
+            ]
+        };
+        let scopes = normalize_link_scopes(vec![
+            "./packages/api/".to_string(),
+            "packages/web".to_string(),
+            " ".to_string(),
+        ]);
+        assert_eq!(scopes, vec!["packages/api", "packages/web"]);
+
+        let shas = |commits: Vec<GitCommit>| -> Vec<String> {
+            commits.into_iter().map(|c| c.sha).collect()
+        };
+        let session = vec!["/repo/packages/api/src/server.ts".to_string()];
+        let kept = filter_to_scopes(&scopes, "/repo", &session, candidates());
+        assert_eq!(shas(kept), vec!["api", "unknown"]);
+
+        // A sibling whose name shares the prefix is not in scope.
+        let session = vec!["packages/api-client/index.ts".to_string()];
+        let kept = filter_to_scopes(&scopes, "/repo", &session, candidates());
+        assert_eq!(kept.len(), 3);
+
+        // Sessions reaching outside the scopes are not narrowed.
+        let session = vec![
+            "packages/api/src/server.ts".to_string(),
+            "README.md".to_string(),
+        ];
+        let kept = filter_to_scopes(&scopes, "/repo", &session, candidates());
+        assert_eq!(kept.len(), 3);
+    }
+
+    #[test]
+    fn mark_author_matches_checks_name_and_email() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mine = commit_by(&repo, None, "mine", "Jo Dev", "jo@example.com");
+        let theirs = commit_by(&repo, Some(mine), "theirs", "Sam Other", "sam@example.com");
+
+        let mut commits = vec![candidate(mine), candidate(theirs)];
+        mark_author_matches(&repo, &[], &mut commits);
+        assert_eq!(commits[0].author_matches, None);
+
+        mark_author_matches(&repo, &["JO@example.com".to_string()], &mut commits);
+        assert_eq!(commits[0].author_matches, Some(true));
+        assert_eq!(commits[1].author_matches, Some(false));
+
+        mark_author_matches(&repo, &["Sam Other".to_string()], &mut commits);
+        assert_eq!(commits[1].author_matches, Some(true));
+    }
+
+    #[test]
+    fn mark_added_fingerprints_reads_commit_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit(&repo, None, "base");
+        std::fs::write(
+            dir.path().join("retry.rs"),
+            "fn retry() {\n    let delay = backoff.next_delay();\n}\n",
+        )
+        .unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("retry.rs")).unwrap();
+        index.write().unwrap();
+        let edit = commit(&repo, Some(base), "retry");
+
+        let mut commits = vec![candidate(base), candidate(edit)];
+        mark_added_fingerprints(&repo, &mut commits);
+        assert!(commits[0].added_fingerprints.is_empty());
+        assert_eq!(
+            commits[1].added_fingerprints,
+            crate::linking::line_fingerprints([
+                "fn retry() {",
+                "let delay = backoff.next_delay();"
+            ])
+        );
+    }
+
+    #[test]
+    fn repos_without_params_keep_the_defaults_they_were_added_with() {
+        use crate::linking::LINKING_DEFAULTS_VERSION;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            // Repo 1 predates message weighting; repo 2 is new.
+            sqlx::query(
+                "INSERT INTO repos (id, path, linking_defaults_version) VALUES (1, '/tmp/repo', 1)",
+            )
+            .execute(&pool)
+            .await
+            .expect("insert repo");
+            sqlx::query("INSERT INTO repos (id, path) VALUES (2, '/tmp/other')")
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let existing = fetch_linking_params(&pool, 1).await.expect("params");
+            assert_eq!(existing, LinkingParams::default());
+            assert_eq!(existing.message_weight, 0.0);
+
+            let added = fetch_linking_params(&pool, 2).await.expect("params");
+            assert_eq!(
+                added,
+                LinkingParams::for_defaults_version(LINKING_DEFAULTS_VERSION)
+            );
+            assert!(added.message_weight > 0.0);
+
+            // Stored params win over either default.
+            sqlx::query(
+                "INSERT INTO repo_linking_params (repo_id, temporal_weight, file_overlap_weight, confidence_threshold) VALUES (2, 1.0, 0.0, 0.7)",
+            )
+            .execute(&pool)
+            .await
+            .expect("linking params");
+            let stored = fetch_linking_params(&pool, 2).await.expect("params");
+            assert_eq!(stored.temporal_weight, 1.0);
+            assert_eq!(stored.message_weight, 0.0);
+        });
+    }
 }
@@ -13,9 +13,26 @@
 //! 2. **File Overlap Score (40% weight)**: Jaccard similarity between session
 //!    file paths and commit changed files. Paths are normalized (resolve `.` and `..`).
 //!
-//! 3. **Combined Score**: `0.6 * temporal + 0.4 * file_overlap`
+//! 3. **Message Score (0% weight by default)**: Share of the commit subject's
+//!    terms that appear in the session's user and plan messages. Useful when
+//!    sessions record no file paths.
 //!
-//! 4. **Threshold**: Auto-link if `confidence >= 0.7`, else mark as unlinked.
+//! 4. **Combined Score**: `0.6 * temporal + 0.4 * file_overlap + 0.0 * message`
+//!    for repos added before message weighting, and
+//!    `0.6 * temporal + 0.25 * file_overlap + 0.15 * message` for repos added
+//!    since (`repos.linking_defaults_version`), so a session that recorded no
+//!    file paths can still link to a commit it describes
+//!
+//! 5. **Diff Score**: When the session's edits and the commit's diff can both
+//!    be read, the share of the commit's added lines that the session wrote.
+//!    It makes up half of the final confidence (`DIFF_WEIGHT`), so a commit the
+//!    session did not write rarely links on timing alone.
+//!
+//! 6. **Threshold**: Auto-link if `confidence >= 0.7`, else mark as unlinked.
+//!
+//! The weights and threshold above are defaults; each repo can override them
+//! (`LinkingParams`, stored in `repo_linking_params`). Existing repos keep the
+//! defaults they were scored with (`LinkingParams::for_defaults_version`).
 //!
 //! # Security
 //!
@@ -41,12 +58,36 @@ const TIME_WINDOW_TOLERANCE_MIN: i64 = 240;
 /// Temporal decay window (±5 minutes for partial overlap scoring)
 const TEMPORAL_DECAY_MIN: i64 = 5;
 
-/// Minimum confidence threshold for auto-linking
-const CONFIDENCE_THRESHOLD: f64 = 0.7;
+/// Default minimum confidence threshold for auto-linking
+pub const CONFIDENCE_THRESHOLD: f64 = 0.7;
+
+/// Default algorithm weights for combining scores
+pub const TEMPORAL_WEIGHT: f64 = 0.6;
+pub const FILE_OVERLAP_WEIGHT: f64 = 0.4;
+pub const MESSAGE_WEIGHT: f64 = 0.0;
 
-/// Algorithm weights for combining scores
-const TEMPORAL_WEIGHT: f64 = 0.6;
-const FILE_OVERLAP_WEIGHT: f64 = 0.4;
+/// Defaults version given to newly added repos (`repos.linking_defaults_version`).
+/// Version 1 is `TEMPORAL_WEIGHT` / `FILE_OVERLAP_WEIGHT` / `MESSAGE_WEIGHT`;
+/// version 2 moves weight from file overlap to message similarity.
+pub const LINKING_DEFAULTS_VERSION: i64 = 2;
+pub const V2_FILE_OVERLAP_WEIGHT: f64 = 0.25;
+pub const V2_MESSAGE_WEIGHT: f64 = 0.15;
+
+/// Confidence multiplier for commits by someone other than the user
+pub const AUTHOR_MISMATCH_FACTOR: f64 = 0.75;
+
+/// Share of confidence taken by the diff score when it is available
+pub const DIFF_WEIGHT: f64 = 0.5;
+
+/// Trimmed lines shorter than this (`}`, `else {`, blanks) match too easily
+const MIN_DIFF_LINE_LEN: usize = 8;
+
+/// Words too common in commit subjects and prompts to signal a match
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "from", "this", "that", "into", "when", "then", "than", "are",
+    "was", "were", "not", "but", "all", "can", "use", "add", "fix", "update", "make", "please",
+    "should", "would", "could", "now", "also", "some", "just", "its", "our", "you", "your",
+];
 
 // ============================================================================
 // Data Types
@@ -61,6 +102,9 @@ pub struct SessionExcerpt {
     pub duration_min: Option<i64>,
     pub imported_at_iso: String,
     pub messages: Vec<SessionMessage>,
+    /// Fingerprints of lines written by the session's edit tool calls
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub edit_fingerprints: Vec<String>,
 }
 
 /// Type of AI coding assistant.
@@ -88,6 +132,7 @@ pub struct SessionMessage {
 pub enum SessionMessageRole {
     User,
     Assistant,
+    Plan,
 }
 
 /// Git commit from the repository.
@@ -97,6 +142,13 @@ pub struct GitCommit {
     pub authored_at: String,
     pub message: String,
     pub files: Vec<String>,
+    /// Whether the author is one of the user's identities (None when unknown
+    /// or no identities are configured)
+    #[serde(default)]
+    pub author_matches: Option<bool>,
+    /// Fingerprints of lines the commit adds (empty when the diff is unknown)
+    #[serde(default)]
+    pub added_fingerprints: Vec<String>,
 }
 
 /// Result of linking a session to a commit.
@@ -107,7 +159,33 @@ pub struct LinkResult {
     pub auto_linked: bool,
     pub temporal_score: f64,
     pub file_score: f64,
+    #[serde(default)]
+    pub message_score: f64,
+    /// None when the session's edits or the commit's diff were unavailable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff_score: Option<f64>,
     pub needs_review: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review_reason: Option<ReviewReason>,
+}
+
+/// Why a link was flagged for review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewReason {
+    /// Another commit scored within the tie-break margin
+    CloseMatch,
+    /// The commit was authored by someone else
+    AuthorMismatch,
+}
+
+impl ReviewReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewReason::CloseMatch => "close_match",
+            ReviewReason::AuthorMismatch => "author_mismatch",
+        }
+    }
 }
 
 /// Reason why a session failed to link.
@@ -116,7 +194,6 @@ pub enum UnlinkedReason {
     NoCommitsInTimeWindow,
     LowConfidence,
     ParseError(String),
-    SecretDetected(Vec<String>),
 }
 
 // Implement Display for UnlinkedReason to provide user-friendly error messages
@@ -127,14 +204,11 @@ impl std::fmt::Display for UnlinkedReason {
                 write!(f, "No commits found in session time window")
             }
             UnlinkedReason::LowConfidence => {
-                write!(f, "No commit matched the confidence threshold (0.7)")
+                write!(f, "No commit matched the confidence threshold")
             }
             UnlinkedReason::ParseError(msg) => {
                 write!(f, "Failed to parse session data: {}", msg)
             }
-            UnlinkedReason::SecretDetected(secrets) => {
-                write!(f, "Secrets detected in session: {}", secrets.join(", "))
-            }
         }
     }
 }
@@ -150,12 +224,75 @@ impl std::fmt::Display for SessionTool {
     }
 }
 
-/// Result of attempting to link a session.
-pub type LinkingResult = Result<LinkResult, UnlinkedReason>;
+/// Result of attempting to link a session (best link first).
+pub type LinkingResult = Result<Vec<LinkResult>, UnlinkedReason>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct LinkOptions {
     pub skip_secret_scan: bool,
+    pub params: LinkingParams,
+}
+
+/// Score weights and auto-link threshold, configurable per repo.
+///
+/// The weights must sum to 1 so confidence stays in 0-1.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkingParams {
+    pub temporal_weight: f64,
+    pub file_overlap_weight: f64,
+    #[serde(default)]
+    pub message_weight: f64,
+    pub confidence_threshold: f64,
+}
+
+impl Default for LinkingParams {
+    fn default() -> Self {
+        Self {
+            temporal_weight: TEMPORAL_WEIGHT,
+            file_overlap_weight: FILE_OVERLAP_WEIGHT,
+            message_weight: MESSAGE_WEIGHT,
+            confidence_threshold: CONFIDENCE_THRESHOLD,
+        }
+    }
+}
+
+impl LinkingParams {
+    /// Defaults for a repo without stored params, by the repo's
+    /// `linking_defaults_version` (unknown versions get the latest).
+    pub fn for_defaults_version(version: i64) -> Self {
+        if version <= 1 {
+            return Self::default();
+        }
+        Self {
+            file_overlap_weight: V2_FILE_OVERLAP_WEIGHT,
+            message_weight: V2_MESSAGE_WEIGHT,
+            ..Self::default()
+        }
+    }
+
+    /// Check that weights are in 0-1 and sum to 1, and the threshold is in (0, 1].
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, weight) in [
+            ("temporalWeight", self.temporal_weight),
+            ("fileOverlapWeight", self.file_overlap_weight),
+            ("messageWeight", self.message_weight),
+        ] {
+            if !(0.0..=1.0).contains(&weight) {
+                return Err(format!("{} must be between 0 and 1", name));
+            }
+        }
+        let total = self.temporal_weight + self.file_overlap_weight + self.message_weight;
+        if (total - 1.0).abs() > 1e-6 {
+            return Err(
+                "temporalWeight, fileOverlapWeight and messageWeight must sum to 1".to_string(),
+            );
+        }
+        if !(self.confidence_threshold > 0.0 && self.confidence_threshold <= 1.0) {
+            return Err("confidenceThreshold must be greater than 0 and at most 1".to_string());
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -317,15 +454,164 @@ fn normalize_path(path: &str) -> String {
     }
 }
 
+// ============================================================================
+// Commit Message Similarity
+// ============================================================================
+
+/// Lowercased terms of a text, minus short words and stop words.
+fn tokenize(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() >= 3)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Terms from the session's user and plan messages (what was asked for).
+pub fn extract_session_terms(messages: &[SessionMessage]) -> HashSet<String> {
+    messages
+        .iter()
+        .filter(|msg| {
+            matches!(
+                msg.role,
+                SessionMessageRole::User | SessionMessageRole::Plan
+            )
+        })
+        .flat_map(|msg| tokenize(&msg.text))
+        .collect()
+}
+
+/// Calculate commit message similarity by token overlap.
+///
+/// Score = share of the commit message's terms that appear in `session_terms`.
+/// Commit subjects are short, so the union with a long session would swamp a
+/// Jaccard score.
+///
+/// # Returns
+///
+/// Similarity score (0.0 to 1.0); 0.0 when either side has no terms
+pub fn score_message_similarity(session_terms: &HashSet<String>, commit_message: &str) -> f64 {
+    let commit_terms = tokenize(commit_message);
+    if session_terms.is_empty() || commit_terms.is_empty() {
+        return 0.0;
+    }
+
+    let shared = commit_terms.intersection(session_terms).count();
+    shared as f64 / commit_terms.len() as f64
+}
+
+// ============================================================================
+// Diff Content Similarity
+// ============================================================================
+
+/// Fingerprints (SHA-256[:16] of the trimmed text) of the significant lines.
+///
+/// Edit contents are not stored (see `ToolSanitizer`), so sessions and commits
+/// are compared by fingerprint. Sorted and deduplicated.
+pub fn line_fingerprints<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut fingerprints: Vec<String> = lines
+        .into_iter()
+        .map(str::trim)
+        .filter(|line| line.len() >= MIN_DIFF_LINE_LEN)
+        .map(|line| format!("{:x}", Sha256::digest(line.as_bytes()))[..16].to_string())
+        .collect();
+    fingerprints.sort();
+    fingerprints.dedup();
+    fingerprints
+}
+
+/// Fingerprints of the lines an edit tool call writes.
+///
+/// Understands Claude Code's `Edit`, `MultiEdit` and `Write` inputs and
+/// patches (e.g. Codex `apply_patch`, whose added lines start with `+`).
+/// Other tools yield nothing.
+pub fn edit_fingerprints(tool_name: &str, input: &serde_json::Value) -> Vec<String> {
+    // Some tools pass their arguments as a JSON string
+    let parsed;
+    let input = match input
+        .as_str()
+        .map(serde_json::from_str::<serde_json::Value>)
+    {
+        Some(Ok(value)) => {
+            parsed = value;
+            &parsed
+        }
+        _ => input,
+    };
+
+    let mut texts: Vec<&str> = Vec::new();
+    match tool_name {
+        "Edit" => texts.extend(input["new_string"].as_str()),
+        "MultiEdit" => {
+            if let Some(edits) = input["edits"].as_array() {
+                texts.extend(edits.iter().filter_map(|edit| edit["new_string"].as_str()));
+            }
+        }
+        "Write" => texts.extend(input["content"].as_str()),
+        _ => {}
+    }
+    let mut lines: Vec<&str> = texts.iter().flat_map(|text| text.lines()).collect();
+
+    let mut patches: Vec<&str> = Vec::new();
+    patches.extend(input.as_str());
+    patches.extend(input["input"].as_str());
+    patches.extend(input["patch"].as_str());
+    if let Some(command) = input["command"].as_array() {
+        patches.extend(command.iter().filter_map(|arg| arg.as_str()));
+    }
+    for patch in patches
+        .into_iter()
+        .filter(|text| text.contains("*** Begin Patch") || text.contains("\n@@"))
+    {
+        lines.extend(
+            patch
+                .lines()
+                .filter(|line| !line.starts_with("+++"))
+                .filter_map(|line| line.strip_prefix('+')),
+        );
+    }
+
+    line_fingerprints(lines)
+}
+
+/// Calculate diff content similarity.
+///
+/// Score = share of the commit's added lines that the session wrote. Returns
+/// None when either side has no fingerprints, so sessions without edit
+/// records are scored as before.
+pub fn score_diff_similarity(
+    session_fingerprints: &HashSet<&str>,
+    commit_fingerprints: &[String],
+) -> Option<f64> {
+    if session_fingerprints.is_empty() || commit_fingerprints.is_empty() {
+        return None;
+    }
+
+    let shared = commit_fingerprints
+        .iter()
+        .filter(|fp| session_fingerprints.contains(fp.as_str()))
+        .count();
+    Some(shared as f64 / commit_fingerprints.len() as f64)
+}
+
 // ============================================================================
 // Story 3.3: Combine Scores and Apply Threshold
 // ============================================================================
 
 /// Calculate combined link confidence score.
 ///
-/// Combined score = `0.6 * temporal + 0.4 * file_overlap`
+/// Combined score = `temporal_weight * temporal + file_overlap_weight * file_overlap
+/// + message_weight * message` (`0.6`, `0.4` and `0.0` by default)
+///
+/// When a diff score is available it is blended in as
+/// `(1 - DIFF_WEIGHT) * combined + DIFF_WEIGHT * diff`.
 ///
-/// If confidence >= threshold (0.7), returns link result.
+/// A commit authored by someone other than the user has its confidence scaled
+/// by `AUTHOR_MISMATCH_FACTOR` and, if it still links, is flagged for review.
+///
+/// If confidence >= `params.confidence_threshold` (0.7 by default), returns link result.
 /// Otherwise returns None (no link).
 ///
 /// # Arguments
@@ -334,6 +620,9 @@ fn normalize_path(path: &str) -> String {
 /// * `session_duration_min` - Session duration in minutes
 /// * `commit` - Git commit to score against
 /// * `session_files` - File paths from session messages
+/// * `session_terms` - Terms from the session's user and plan messages
+/// * `session_fingerprints` - Fingerprints of lines the session's edits wrote
+/// * `params` - Score weights and threshold
 ///
 /// # Returns
 ///
@@ -348,6 +637,9 @@ pub fn calculate_link_confidence(
     session_duration_min: i64,
     commit: &GitCommit,
     session_files: &[String],
+    session_terms: &HashSet<String>,
+    session_fingerprints: &HashSet<&str>,
+    params: &LinkingParams,
 ) -> Option<LinkResult> {
     // Parse commit timestamp
     let commit_time = match DateTime::parse_from_rfc3339(&commit.authored_at) {
@@ -358,19 +650,37 @@ pub fn calculate_link_confidence(
     // Calculate individual scores
     let temporal_score = score_temporal_overlap(session_end, session_duration_min, &commit_time);
     let file_score = score_file_overlap(session_files, &commit.files);
+    let message_score = score_message_similarity(session_terms, &commit.message);
+    let diff_score = score_diff_similarity(session_fingerprints, &commit.added_fingerprints);
 
     // Combine with weights
-    let confidence = (TEMPORAL_WEIGHT * temporal_score) + (FILE_OVERLAP_WEIGHT * file_score);
+    let mut confidence = (params.temporal_weight * temporal_score)
+        + (params.file_overlap_weight * file_score)
+        + (params.message_weight * message_score);
+
+    // Matching edit content outweighs timing and paths
+    if let Some(diff_score) = diff_score {
+        confidence = (1.0 - DIFF_WEIGHT) * confidence + DIFF_WEIGHT * diff_score;
+    }
+
+    // Penalize colleagues' commits
+    let author_mismatch = commit.author_matches == Some(false);
+    if author_mismatch {
+        confidence *= AUTHOR_MISMATCH_FACTOR;
+    }
 
     // Apply threshold
-    if confidence >= CONFIDENCE_THRESHOLD {
+    if confidence >= params.confidence_threshold {
         Some(LinkResult {
             commit_sha: commit.sha.clone(),
             confidence,
             auto_linked: true,
             temporal_score,
             file_score,
-            needs_review: false,
+            message_score,
+            diff_score,
+            needs_review: author_mismatch,
+            review_reason: author_mismatch.then_some(ReviewReason::AuthorMismatch),
         })
     } else {
         None
@@ -378,34 +688,197 @@ pub fn calculate_link_confidence(
 }
 
 // ============================================================================
-// Story 3.4: Link Session to Commits (with Secret Redaction)
+// Learning From Link Corrections
 // ============================================================================
 
-/// Secret detection patterns for security scanning.
+/// Feedback samples needed (with at least one of each label) before learning
+pub const MIN_FEEDBACK_SAMPLES: usize = 10;
+
+/// A suggested link the user confirmed or rejected, with its component scores.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeedbackSample {
+    pub temporal_score: f64,
+    pub file_score: f64,
+    pub message_score: f64,
+    pub accepted: bool,
+}
+
+fn sigmoid(z: f64) -> f64 {
+    1.0 / (1.0 + (-z).exp())
+}
+
+/// Fit linking params to user corrections with logistic regression.
 ///
-/// # Evidence
+/// Fits `P(accepted) = sigmoid(b + w · scores)` by batch gradient descent
+/// (with light L2 regularization), then normalizes the weights to sum to 1.
+/// The threshold is the decision boundary `-b / sum(w)`, so confidence at or
+/// above it means the model expects the user to keep the link.
 ///
-/// Build Plan Epic 3 Story 3.4.
-/// Resolution Summary Security fix 3 requires secret redaction.
-const SECRET_PATTERNS: &[(&str, &str)] = &[
-    // Base64-like patterns (often used for encoded secrets)
-    (r"[A-Za-z0-9+/]{32,}={0,2}", "base64-like"),
-    // API key prefixes
-    (r"\bsk-[a-zA-Z0-9]{20,}\b", "sk- API key"),
-    (r"\bpk_[a-zA-Z0-9]{20,}\b", "pk_ API key"),
-    // Common secret keywords
-    (
-        r"\b[A-Za-z0-9]{32,}\b",
-        "long random string (possible secret)",
-    ),
-];
+/// Returns None until there are `MIN_FEEDBACK_SAMPLES` samples covering both
+/// labels, or when the fit gives no usable weights.
+pub fn calibrate_linking_params(samples: &[FeedbackSample]) -> Option<LinkingParams> {
+    const STEPS: usize = 2000;
+    const LEARNING_RATE: f64 = 0.5;
+    const L2: f64 = 0.01;
+
+    if samples.len() < MIN_FEEDBACK_SAMPLES
+        || !samples.iter().any(|s| s.accepted)
+        || samples.iter().all(|s| s.accepted)
+    {
+        return None;
+    }
+
+    // Start from the defaults so sparse data stays close to them.
+    let defaults = LinkingParams::default();
+    let mut weights = [
+        defaults.temporal_weight,
+        defaults.file_overlap_weight,
+        defaults.message_weight,
+    ];
+    let mut bias = -defaults.confidence_threshold;
+    let n = samples.len() as f64;
+
+    for _ in 0..STEPS {
+        let mut grad_w = [0.0; 3];
+        let mut grad_b = 0.0;
+        for sample in samples {
+            let x = [
+                sample.temporal_score,
+                sample.file_score,
+                sample.message_score,
+            ];
+            let z = bias + weights.iter().zip(x).map(|(w, x)| w * x).sum::<f64>();
+            let label = if sample.accepted { 1.0 } else { 0.0 };
+            let err = sigmoid(z) - label;
+            for (g, x) in grad_w.iter_mut().zip(x) {
+                *g += err * x;
+            }
+            grad_b += err;
+        }
+        for (w, g) in weights.iter_mut().zip(grad_w) {
+            *w -= LEARNING_RATE * (g / n + L2 * *w);
+        }
+        bias -= LEARNING_RATE * grad_b / n;
+    }
+
+    // Confidence is a weighted sum, so negative weights are dropped.
+    let weights = weights.map(|w| w.max(0.0));
+    let total: f64 = weights.iter().sum();
+    if total <= f64::EPSILON {
+        return None;
+    }
+
+    let params = LinkingParams {
+        temporal_weight: weights[0] / total,
+        file_overlap_weight: weights[1] / total,
+        message_weight: weights[2] / total,
+        confidence_threshold: (-bias / total).clamp(0.05, 1.0),
+    };
+    params.validate().ok().map(|_| params)
+}
+
+/// Confidence buckets in the calibration report (0.1 wide)
+pub const CALIBRATION_BUCKETS: usize = 10;
+
+/// Suggested links in one confidence range, and how the user judged them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceBucket {
+    pub min_confidence: f64,
+    pub max_confidence: f64,
+    pub confirmed: usize,
+    pub rejected: usize,
+    /// Suggested links the user has not confirmed or rejected yet
+    pub unreviewed: usize,
+    /// Share of reviewed links the user kept (None without reviewed links)
+    pub precision: Option<f64>,
+    /// Share kept among reviewed links at or above `min_confidence`, i.e.
+    /// the expected precision with `min_confidence` as the threshold
+    pub precision_at_or_above: Option<f64>,
+    pub reviewed_at_or_above: usize,
+}
+
+/// Bucket suggested links by confidence.
+///
+/// `reviewed` holds each confirmed (true) or rejected (false) link's
+/// confidence; `unreviewed` the confidence of links nobody has judged.
+pub fn bucket_link_confidence(
+    reviewed: &[(f64, bool)],
+    unreviewed: &[f64],
+) -> Vec<ConfidenceBucket> {
+    let index = |confidence: f64| {
+        ((confidence.clamp(0.0, 1.0) * CALIBRATION_BUCKETS as f64) as usize)
+            .min(CALIBRATION_BUCKETS - 1)
+    };
+    let precision = |kept: usize, dropped: usize| {
+        (kept + dropped > 0).then(|| kept as f64 / (kept + dropped) as f64)
+    };
+
+    let mut buckets: Vec<ConfidenceBucket> = (0..CALIBRATION_BUCKETS)
+        .map(|i| ConfidenceBucket {
+            min_confidence: i as f64 / CALIBRATION_BUCKETS as f64,
+            max_confidence: (i + 1) as f64 / CALIBRATION_BUCKETS as f64,
+            confirmed: 0,
+            rejected: 0,
+            unreviewed: 0,
+            precision: None,
+            precision_at_or_above: None,
+            reviewed_at_or_above: 0,
+        })
+        .collect();
+    for &(confidence, accepted) in reviewed {
+        let bucket = &mut buckets[index(confidence)];
+        if accepted {
+            bucket.confirmed += 1;
+        } else {
+            bucket.rejected += 1;
+        }
+    }
+    for &confidence in unreviewed {
+        buckets[index(confidence)].unreviewed += 1;
+    }
+
+    let (mut kept, mut dropped) = (0, 0);
+    for bucket in buckets.iter_mut().rev() {
+        kept += bucket.confirmed;
+        dropped += bucket.rejected;
+        bucket.precision = precision(bucket.confirmed, bucket.rejected);
+        bucket.precision_at_or_above = precision(kept, dropped);
+        bucket.reviewed_at_or_above = kept + dropped;
+    }
+    buckets
+}
+
+/// Lowest bucket bound whose links reach `target_precision`.
+///
+/// Needs `MIN_FEEDBACK_SAMPLES` reviewed links at or above the bound. Returns
+/// None when no bound qualifies.
+pub fn suggest_confidence_threshold(
+    buckets: &[ConfidenceBucket],
+    target_precision: f64,
+) -> Option<f64> {
+    buckets
+        .iter()
+        .filter(|bucket| bucket.min_confidence > 0.0)
+        .find(|bucket| {
+            bucket.reviewed_at_or_above >= MIN_FEEDBACK_SAMPLES
+                && bucket
+                    .precision_at_or_above
+                    .is_some_and(|precision| precision >= target_precision)
+        })
+        .map(|bucket| bucket.min_confidence)
+}
+
+// ============================================================================
+// Story 3.4: Link Session to Commits (with Secret Redaction)
+// ============================================================================
 
 /// Detect secrets in session message text.
 ///
-/// Scans for patterns that may indicate exposed secrets:
-/// - Base64-like strings
-/// - API keys (sk-, pk_)
-/// - Keywords: token, secret, api_key, password
+/// Uses the shared redaction patterns (`import::redactor`), so linking flags
+/// the same secrets that auto-ingest redacts: API keys, tokens, private keys,
+/// credentials in URLs and password assignments. Ordinary words such as
+/// "token" and long identifiers are not secrets.
 ///
 /// # Arguments
 ///
@@ -413,58 +886,23 @@ const SECRET_PATTERNS: &[(&str, &str)] = &[
 ///
 /// # Returns
 ///
-/// * `Vec<String>` - List of detected secret patterns (empty if none found)
+/// * `Vec<String>` - Kinds of secret found (empty if none found)
 ///
 /// # Evidence
 ///
 /// Build Plan Epic 3 Story 3.4.
+/// Resolution Summary Security fix 3 requires secret redaction.
 pub fn detect_secrets(text: &str) -> Vec<String> {
-    detect_secrets_impl(text)
+    crate::import::redactor::detect_kinds(text)
 }
 
-/// Internal implementation of secret detection.
-fn detect_secrets_impl(text: &str) -> Vec<String> {
-    let mut detected = Vec::new();
-
-    // Check for secret keywords
-    let keywords = [
-        "token",
-        "secret",
-        "api_key",
-        "password",
-        "private_key",
-        "credentials",
-    ];
-    let text_lower = text.to_lowercase();
-
-    for keyword in keywords {
-        if text_lower.contains(keyword) {
-            detected.push(format!("keyword: {}", keyword));
-        }
+/// Copy of a session with secrets in its message text redacted
+fn redact_session_secrets(session: &SessionExcerpt) -> SessionExcerpt {
+    let mut redacted = session.clone();
+    for msg in &mut redacted.messages {
+        msg.text = crate::import::redactor::redact_text(&msg.text).0;
     }
-
-    // Check for patterns
-    for (_pattern, name) in SECRET_PATTERNS {
-        // Simple pattern matching (in production, use regex crate)
-        if text.contains("sk-") || text.contains("pk_") {
-            detected.push(name.to_string());
-        }
-
-        // Check for long random strings (possible secrets)
-        let words: Vec<&str> = text.split_whitespace().collect();
-        for word in words {
-            if word.len() >= 32
-                && word
-                    .chars()
-                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
-            {
-                detected.push(format!("long string: {} chars", word.len()));
-                break; // Only report once per message
-            }
-        }
-    }
-
-    detected
+    redacted
 }
 
 /// Extract all file paths from session messages.
@@ -492,42 +930,99 @@ pub fn extract_session_files(messages: &[SessionMessage]) -> Vec<String> {
     files
 }
 
-/// Link a session to the best-matching commit.
+/// Link a session to every commit that clears the threshold.
 ///
 /// This is the main entry point for the linking algorithm. It:
-/// 1. Scans for secrets in session messages (security check)
+/// 1. Redacts secrets in session messages (security check)
 /// 2. Filters commits by time window
 /// 3. Scores each candidate commit
-/// 4. Returns the best match if confidence >= threshold
+/// 4. Returns every match with confidence >= threshold
+///
+/// A long session often produces several commits. The best match (including
+/// its `needs_review` flag) comes first, followed by the other matches by
+/// descending confidence.
 ///
 /// # Arguments
 ///
 /// * `session` - Session excerpt to link
 /// * `commits` - All commits in the repository
+/// * `options` - Secret redaction toggle and the repo's linking params
 ///
 /// # Returns
 ///
-/// * `Ok(LinkResult)` - Session successfully linked to a commit
+/// * `Ok(Vec<LinkResult>)` - Linked commits, best match first (never empty)
 /// * `Err(UnlinkedReason)` - Session could not be linked
 ///
 /// # Evidence
 ///
 /// Build Plan Epic 3 Story 3.4.
-pub fn link_session_to_commits(session: &SessionExcerpt, commits: &[GitCommit]) -> LinkingResult {
-    link_session_to_commits_with_options(
-        session,
-        commits,
-        LinkOptions {
-            skip_secret_scan: false,
-        },
-    )
+pub fn link_session_to_all_commits_with_options(
+    session: &SessionExcerpt,
+    commits: &[GitCommit],
+    options: LinkOptions,
+) -> LinkingResult {
+    score_session_links(session, commits, options).map(|(best, mut others)| {
+        others.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        std::iter::once(best).chain(others).collect()
+    })
+}
+
+/// Commits that came closest to linking, best first.
+///
+/// Scores every commit regardless of the time window and threshold, for
+/// explaining why a session stayed unlinked. Results are not auto links.
+pub fn nearest_misses(
+    session: &SessionExcerpt,
+    commits: &[GitCommit],
+    params: &LinkingParams,
+    limit: usize,
+) -> Vec<LinkResult> {
+    let Ok(session_end) = DateTime::parse_from_rfc3339(&session.imported_at_iso) else {
+        return Vec::new();
+    };
+    let session_end = session_end.with_timezone(&Utc);
+    let duration_min = session.duration_min.unwrap_or(30);
+    let session_files = extract_session_files(&session.messages);
+    let session_terms = extract_session_terms(&session.messages);
+    let session_fingerprints: HashSet<&str> = session
+        .edit_fingerprints
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let params = LinkingParams {
+        confidence_threshold: 0.0,
+        ..*params
+    };
+
+    let mut misses: Vec<LinkResult> = commits
+        .iter()
+        .filter_map(|commit| {
+            calculate_link_confidence(
+                &session_end,
+                duration_min,
+                commit,
+                &session_files,
+                &session_terms,
+                &session_fingerprints,
+                &params,
+            )
+        })
+        .map(|mut result| {
+            result.auto_linked = false;
+            result
+        })
+        .collect();
+    misses.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    misses.truncate(limit);
+    misses
 }
 
-pub fn link_session_to_commits_with_options(
+/// Best link plus the other commits above the threshold
+fn score_session_links(
     session: &SessionExcerpt,
     commits: &[GitCommit],
     options: LinkOptions,
-) -> LinkingResult {
+) -> Result<(LinkResult, Vec<LinkResult>), UnlinkedReason> {
     // Parse session end time
     let session_end = match DateTime::parse_from_rfc3339(&session.imported_at_iso) {
         Ok(dt) => dt.with_timezone(&Utc),
@@ -542,20 +1037,28 @@ pub fn link_session_to_commits_with_options(
     // Get or infer session duration
     let duration_min = session.duration_min.unwrap_or(30); // Default to 30 min if missing
 
-    if !options.skip_secret_scan {
-        // Security: Scan for secrets in session messages
-        let mut detected_secrets = Vec::new();
-        for msg in &session.messages {
-            detected_secrets.extend(detect_secrets(&msg.text));
-        }
-
-        if !detected_secrets.is_empty() {
-            return Err(UnlinkedReason::SecretDetected(detected_secrets));
-        }
-    }
+    // Security: score a redacted copy rather than the secrets themselves
+    let redacted;
+    let session = if !options.skip_secret_scan
+        && session
+            .messages
+            .iter()
+            .any(|msg| !detect_secrets(&msg.text).is_empty())
+    {
+        redacted = redact_session_secrets(session);
+        &redacted
+    } else {
+        session
+    };
 
-    // Extract session files
+    // Extract session files and intent terms
     let session_files = extract_session_files(&session.messages);
+    let session_terms = extract_session_terms(&session.messages);
+    let session_fingerprints: HashSet<&str> = session
+        .edit_fingerprints
+        .iter()
+        .map(String::as_str)
+        .collect();
 
     // Filter commits by time window (±4 hours from session)
     let tolerance = chrono::Duration::minutes(TIME_WINDOW_TOLERANCE_MIN);
@@ -580,6 +1083,7 @@ pub fn link_session_to_commits_with_options(
     }
 
     // Score each candidate commit with tie-breaking logic
+    let mut above_threshold: Vec<LinkResult> = Vec::new();
     let mut best_result: Option<LinkResult> = None;
     let mut second_best: Option<LinkResult> = None;
     const TIE_BREAK_MARGIN: f64 = 0.05; // Within 5% confidence, prefer closer timestamp
@@ -589,9 +1093,16 @@ pub fn link_session_to_commits_with_options(
         candidates.iter().map(|c| (c.sha.clone(), *c)).collect();
 
     for commit in &candidates {
-        if let Some(result) =
-            calculate_link_confidence(&session_end, duration_min, commit, &session_files)
-        {
+        if let Some(result) = calculate_link_confidence(
+            &session_end,
+            duration_min,
+            commit,
+            &session_files,
+            &session_terms,
+            &session_fingerprints,
+            &options.params,
+        ) {
+            above_threshold.push(result.clone());
             match &best_result {
                 None => best_result = Some(result),
                 Some(current_best) => {
@@ -642,12 +1153,19 @@ pub fn link_session_to_commits_with_options(
     // Return best match or error if below threshold
     match best_result {
         Some(mut result) => {
-            let needs_review = second_best
+            let close_match = second_best
                 .as_ref()
                 .map(|second| (result.confidence - second.confidence).abs() <= TIE_BREAK_MARGIN)
                 .unwrap_or(false);
-            result.needs_review = needs_review;
-            Ok(result)
+            if close_match {
+                result.needs_review = true;
+                result.review_reason.get_or_insert(ReviewReason::CloseMatch);
+            }
+            let others = above_threshold
+                .into_iter()
+                .filter(|other| other.commit_sha != result.commit_sha)
+                .collect();
+            Ok((result, others))
         }
         None => Err(UnlinkedReason::LowConfidence),
     }
@@ -738,11 +1256,497 @@ mod tests {
         assert_eq!(normalize_path("./src/utils.ts"), "src/utils.ts");
     }
 
+    #[test]
+    fn test_linking_params_change_outcome() {
+        let session_end = DateTime::parse_from_rfc3339("2024-01-15T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        // In the session window, but touching unrelated files.
+        let commit = GitCommit {
+            sha: "abc123".to_string(),
+            authored_at: "2024-01-15T14:20:00Z".to_string(),
+            message: "Update button".to_string(),
+            files: vec!["src/components/Button.tsx".to_string()],
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        };
+        let session_files = vec!["src/utils.ts".to_string()];
+
+        let no_terms = HashSet::new();
+
+        let default = LinkingParams::default();
+        assert!(calculate_link_confidence(
+            &session_end,
+            30,
+            &commit,
+            &session_files,
+            &no_terms,
+            &HashSet::new(),
+            &default
+        )
+        .is_none());
+
+        let temporal_heavy = LinkingParams {
+            temporal_weight: 0.8,
+            file_overlap_weight: 0.2,
+            message_weight: 0.0,
+            confidence_threshold: 0.75,
+        };
+        assert!(temporal_heavy.validate().is_ok());
+        let result = calculate_link_confidence(
+            &session_end,
+            30,
+            &commit,
+            &session_files,
+            &no_terms,
+            &HashSet::new(),
+            &temporal_heavy,
+        )
+        .expect("linked");
+        assert!((result.confidence - 0.8).abs() < 1e-9);
+
+        let unbalanced = LinkingParams {
+            temporal_weight: 0.8,
+            ..default
+        };
+        assert!(unbalanced.validate().is_err());
+    }
+
+    #[test]
+    fn test_message_similarity_links_without_files() {
+        let messages = vec![
+            SessionMessage {
+                id: "m1".to_string(),
+                role: SessionMessageRole::User,
+                text: "Please add retry logic to the webhook dispatcher".to_string(),
+                files: None,
+            },
+            SessionMessage {
+                id: "m2".to_string(),
+                role: SessionMessageRole::Assistant,
+                text: "Refactoring the billing module instead".to_string(),
+                files: None,
+            },
+        ];
+        let terms = extract_session_terms(&messages);
+        assert!(terms.contains("webhook"));
+        // Assistant messages are not treated as intent.
+        assert!(!terms.contains("billing"));
+
+        assert_eq!(
+            score_message_similarity(&terms, "Add webhook dispatcher retry"),
+            1.0
+        );
+        assert_eq!(score_message_similarity(&terms, "Bump billing deps"), 0.0);
+        assert_eq!(
+            score_message_similarity(&HashSet::new(), "Retry webhook"),
+            0.0
+        );
+
+        let session_end = DateTime::parse_from_rfc3339("2024-01-15T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let commit = GitCommit {
+            sha: "abc123".to_string(),
+            authored_at: "2024-01-15T14:20:00Z".to_string(),
+            message: "Retry webhook dispatcher on timeout".to_string(),
+            files: vec!["src/webhooks.rs".to_string()],
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        };
+
+        // No session files: the default weights cannot reach the threshold.
+        let default = LinkingParams::default();
+        assert!(calculate_link_confidence(
+            &session_end,
+            30,
+            &commit,
+            &[],
+            &terms,
+            &HashSet::new(),
+            &default
+        )
+        .is_none());
+
+        let with_message = LinkingParams {
+            temporal_weight: 0.6,
+            file_overlap_weight: 0.2,
+            message_weight: 0.2,
+            ..default
+        };
+        assert!(with_message.validate().is_ok());
+        let result = calculate_link_confidence(
+            &session_end,
+            30,
+            &commit,
+            &[],
+            &terms,
+            &HashSet::new(),
+            &with_message,
+        )
+        .expect("linked");
+        assert!(result.message_score > 0.5);
+    }
+
+    #[test]
+    fn test_session_without_files_links_by_message() {
+        let session = SessionExcerpt {
+            id: "s1".to_string(),
+            tool: SessionTool::ClaudeCode,
+            duration_min: Some(30),
+            imported_at_iso: "2024-01-15T17:00:00Z".to_string(),
+            messages: vec![SessionMessage {
+                id: "s1:m0".to_string(),
+                role: SessionMessageRole::User,
+                text: "Retry the webhook dispatcher on timeout".to_string(),
+                files: None,
+            }],
+            edit_fingerprints: Vec::new(),
+        };
+        // Same time and files; only the subjects differ.
+        let commit = |sha: &str, message: &str| GitCommit {
+            sha: sha.to_string(),
+            authored_at: "2024-01-15T16:50:00Z".to_string(),
+            message: message.to_string(),
+            files: vec!["src/webhooks.rs".to_string()],
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        };
+        let commits = vec![
+            commit("webhook", "Retry webhook dispatcher on timeout"),
+            commit("docs", "Tidy contributor guide"),
+        ];
+        let link = |params: LinkingParams| {
+            link_session_to_all_commits_with_options(
+                &session,
+                &commits,
+                LinkOptions {
+                    skip_secret_scan: false,
+                    params,
+                },
+            )
+        };
+
+        // Repos added before message weighting keep their scores.
+        assert_eq!(
+            LinkingParams::for_defaults_version(1),
+            LinkingParams::default()
+        );
+        assert!(matches!(
+            link(LinkingParams::for_defaults_version(1)),
+            Err(UnlinkedReason::LowConfidence)
+        ));
+
+        let params = LinkingParams::for_defaults_version(LINKING_DEFAULTS_VERSION);
+        assert!(params.validate().is_ok());
+        let links = link(params).expect("linked");
+        let shas: Vec<&str> = links.iter().map(|l| l.commit_sha.as_str()).collect();
+        assert_eq!(shas, vec!["webhook"]);
+        assert_eq!(links[0].file_score, 0.0);
+        assert_eq!(links[0].message_score, 1.0);
+    }
+
+    #[test]
+    fn test_link_session_to_all_commits() {
+        let session = SessionExcerpt {
+            id: "s1".to_string(),
+            tool: SessionTool::ClaudeCode,
+            duration_min: Some(180),
+            imported_at_iso: "2024-01-15T17:00:00Z".to_string(),
+            messages: vec![SessionMessage {
+                id: "s1:m0".to_string(),
+                role: SessionMessageRole::User,
+                text: "Refactor the API layer".to_string(),
+                files: Some(vec!["src/api.ts".to_string(), "src/utils.ts".to_string()]),
+            }],
+            edit_fingerprints: Vec::new(),
+        };
+        let commit = |sha: &str, at: &str, files: &[&str]| GitCommit {
+            sha: sha.to_string(),
+            authored_at: at.to_string(),
+            message: String::new(),
+            files: files.iter().map(|f| f.to_string()).collect(),
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        };
+        let commits = vec![
+            commit("c1", "2024-01-15T14:30:00Z", &["src/api.ts"]),
+            commit(
+                "c2",
+                "2024-01-15T16:50:00Z",
+                &["src/api.ts", "src/utils.ts"],
+            ),
+            // Outside the session window and unrelated
+            commit("c3", "2024-01-15T19:00:00Z", &["README.md"]),
+        ];
+        let options = LinkOptions {
+            skip_secret_scan: false,
+            params: LinkingParams::default(),
+        };
+
+        let links =
+            link_session_to_all_commits_with_options(&session, &commits, options).expect("linked");
+        let shas: Vec<&str> = links.iter().map(|l| l.commit_sha.as_str()).collect();
+        assert_eq!(shas, vec!["c2", "c1"]);
+        assert!(links[0].confidence > links[1].confidence);
+    }
+
+    #[test]
+    fn test_nearest_misses_rank_commits_below_threshold() {
+        let session = SessionExcerpt {
+            id: "s1".to_string(),
+            tool: SessionTool::ClaudeCode,
+            duration_min: Some(30),
+            imported_at_iso: "2024-01-15T17:00:00Z".to_string(),
+            messages: vec![SessionMessage {
+                id: "s1:m0".to_string(),
+                role: SessionMessageRole::User,
+                text: "Tidy the docs".to_string(),
+                files: Some(vec!["docs/guide.md".to_string()]),
+            }],
+            edit_fingerprints: Vec::new(),
+        };
+        let commit = |sha: &str, at: &str| GitCommit {
+            sha: sha.to_string(),
+            authored_at: at.to_string(),
+            message: String::new(),
+            files: vec!["src/main.rs".to_string()],
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        };
+        let commits = vec![
+            commit("far", "2024-01-15T21:00:00Z"),
+            commit("near", "2024-01-15T16:50:00Z"),
+            commit("later", "2024-01-15T17:03:00Z"),
+        ];
+        let params = LinkingParams::default();
+        let options = LinkOptions {
+            skip_secret_scan: false,
+            params,
+        };
+        assert!(matches!(
+            link_session_to_all_commits_with_options(&session, &commits, options),
+            Err(UnlinkedReason::LowConfidence)
+        ));
+
+        let misses = nearest_misses(&session, &commits, &params, 2);
+        let shas: Vec<&str> = misses.iter().map(|m| m.commit_sha.as_str()).collect();
+        assert_eq!(shas, vec!["near", "later"]);
+        assert!(misses.iter().all(|m| !m.auto_linked));
+        assert!(misses[0].confidence < params.confidence_threshold);
+    }
+
+    #[test]
+    fn test_author_mismatch_is_penalized_and_flagged() {
+        let session_end = DateTime::parse_from_rfc3339("2024-01-15T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let session_files = vec!["src/utils.ts".to_string()];
+        let commit = |author_matches| GitCommit {
+            sha: "abc123".to_string(),
+            authored_at: "2024-01-15T14:20:00Z".to_string(),
+            message: String::new(),
+            files: vec!["src/utils.ts".to_string()],
+            author_matches,
+            added_fingerprints: Vec::new(),
+        };
+        let params = LinkingParams::default();
+        let score = |commit: &GitCommit| {
+            calculate_link_confidence(
+                &session_end,
+                30,
+                commit,
+                &session_files,
+                &HashSet::new(),
+                &HashSet::new(),
+                &params,
+            )
+        };
+
+        let mine = score(&commit(Some(true))).expect("linked");
+        assert!((mine.confidence - 1.0).abs() < 1e-9);
+        assert!(!mine.needs_review);
+        assert_eq!(mine.review_reason, None);
+
+        let colleague = score(&commit(Some(false))).expect("still linked");
+        assert!((colleague.confidence - AUTHOR_MISMATCH_FACTOR).abs() < 1e-9);
+        assert!(colleague.needs_review);
+        assert_eq!(colleague.review_reason, Some(ReviewReason::AuthorMismatch));
+
+        // Unknown author: no penalty
+        let unknown = score(&commit(None)).expect("linked");
+        assert_eq!(unknown.review_reason, None);
+    }
+
+    #[test]
+    fn test_diff_similarity_separates_same_time_commits() {
+        let edit = edit_fingerprints(
+            "Edit",
+            &serde_json::json!({
+                "file_path": "src/retry.rs",
+                "old_string": "}",
+                "new_string": "    let delay = backoff.next_delay();\n    sleep(delay).await;\n}",
+            }),
+        );
+        // The closing brace is too short to count.
+        assert_eq!(edit.len(), 2);
+
+        let patch = edit_fingerprints(
+            "apply_patch",
+            &serde_json::json!({
+                "input": "*** Begin Patch\n*** Update File: src/retry.rs\n@@\n-    retry();\n+    let delay = backoff.next_delay();\n*** End Patch",
+            }),
+        );
+        assert_eq!(patch.len(), 1);
+        assert!(edit.contains(&patch[0]));
+        assert!(edit_fingerprints("Bash", &serde_json::json!({"command": "ls"})).is_empty());
+
+        let session_end = DateTime::parse_from_rfc3339("2024-01-15T14:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let session_files = vec!["src/retry.rs".to_string()];
+        let session_fingerprints: HashSet<&str> = edit.iter().map(String::as_str).collect();
+        let commit = |added: &[&str]| GitCommit {
+            sha: "abc123".to_string(),
+            authored_at: "2024-01-15T14:20:00Z".to_string(),
+            message: String::new(),
+            files: vec!["src/retry.rs".to_string()],
+            author_matches: None,
+            added_fingerprints: line_fingerprints(added.iter().copied()),
+        };
+        let score = |commit: &GitCommit| {
+            calculate_link_confidence(
+                &session_end,
+                30,
+                commit,
+                &session_files,
+                &HashSet::new(),
+                &session_fingerprints,
+                &LinkingParams::default(),
+            )
+        };
+
+        let written = score(&commit(&[
+            "let delay = backoff.next_delay();",
+            "sleep(delay).await;",
+        ]))
+        .expect("linked");
+        assert_eq!(written.diff_score, Some(1.0));
+        assert!((written.confidence - 1.0).abs() < 1e-9);
+
+        // Same time and files, but none of the session's lines
+        assert!(score(&commit(&["unrelated_refactor(config);"])).is_none());
+
+        // Unknown diff: scored as before
+        let unknown = score(&commit(&[])).expect("linked");
+        assert_eq!(unknown.diff_score, None);
+    }
+
+    #[test]
+    fn test_calibrate_learns_from_corrections() {
+        let sample = |temporal_score, file_score, accepted| FeedbackSample {
+            temporal_score,
+            file_score,
+            message_score: 0.0,
+            accepted,
+        };
+        // Users keep links with file overlap and reject time-only matches.
+        let mut samples = Vec::new();
+        for i in 0..6 {
+            let t = 0.8 + 0.04 * i as f64;
+            samples.push(sample(t, 0.6 + 0.05 * i as f64, true));
+            samples.push(sample(t, 0.0, false));
+        }
+
+        assert!(calibrate_linking_params(&samples[..4]).is_none());
+        let only_accepted: Vec<_> = samples.iter().copied().filter(|s| s.accepted).collect();
+        assert!(calibrate_linking_params(&only_accepted).is_none());
+
+        let learned = calibrate_linking_params(&samples).expect("learned");
+        assert!(learned.validate().is_ok());
+        assert!(learned.file_overlap_weight > FILE_OVERLAP_WEIGHT);
+        for s in &samples {
+            let confidence = learned.temporal_weight * s.temporal_score
+                + learned.file_overlap_weight * s.file_score;
+            assert_eq!(confidence >= learned.confidence_threshold, s.accepted);
+        }
+    }
+
+    #[test]
+    fn test_confidence_buckets_estimate_precision() {
+        let mut reviewed = Vec::new();
+        // Low-confidence suggestions are usually wrong, high ones right.
+        for _ in 0..4 {
+            reviewed.push((0.55, false));
+            reviewed.push((0.65, true));
+            reviewed.push((0.65, false));
+        }
+        for _ in 0..8 {
+            reviewed.push((0.75, true));
+        }
+        reviewed.push((0.95, true));
+        reviewed.push((1.0, false));
+        let buckets = bucket_link_confidence(&reviewed, &[0.72, 0.3]);
+
+        assert_eq!(buckets.len(), CALIBRATION_BUCKETS);
+        assert_eq!((buckets[6].confirmed, buckets[6].rejected), (4, 4));
+        assert_eq!(buckets[6].precision, Some(0.5));
+        assert_eq!(buckets[7].unreviewed, 1);
+        assert_eq!(buckets[3].unreviewed, 1);
+        assert_eq!((buckets[9].confirmed, buckets[9].rejected), (1, 1));
+        assert_eq!(buckets[7].precision_at_or_above, Some(0.9));
+        assert_eq!(buckets[7].reviewed_at_or_above, 10);
+
+        assert_eq!(suggest_confidence_threshold(&buckets, 0.9), Some(0.7));
+        assert_eq!(suggest_confidence_threshold(&buckets, 0.6), Some(0.6));
+        assert_eq!(suggest_confidence_threshold(&buckets, 0.99), None);
+    }
+
     #[test]
     fn test_detect_secrets() {
         assert!(detect_secrets("Update Button component").is_empty());
+        // Secret-sounding words and long identifiers are not secrets
+        assert!(detect_secrets("Add API token and secret key").is_empty());
+        assert!(detect_secrets("Rename useSessionLinkingConfidenceScoreHook").is_empty());
+
+        let secrets = detect_secrets("export OPENAI=sk-abc123xyz789foo456bar789baz01234567890");
+        assert_eq!(secrets, vec!["OPENAI_KEY"]);
+    }
+
+    #[test]
+    fn test_secrets_are_redacted_not_refused() {
+        let session = SessionExcerpt {
+            id: "s1".to_string(),
+            tool: SessionTool::ClaudeCode,
+            duration_min: Some(30),
+            imported_at_iso: "2024-01-15T17:00:00Z".to_string(),
+            messages: vec![SessionMessage {
+                id: "s1:m0".to_string(),
+                role: SessionMessageRole::User,
+                text: "Use key sk-abc123xyz789foo456bar789baz01234567890 for the client"
+                    .to_string(),
+                files: Some(vec!["src/client.ts".to_string()]),
+            }],
+            edit_fingerprints: Vec::new(),
+        };
+        let commits = vec![GitCommit {
+            sha: "c1".to_string(),
+            authored_at: "2024-01-15T16:50:00Z".to_string(),
+            message: "Configure client".to_string(),
+            files: vec!["src/client.ts".to_string()],
+            author_matches: None,
+            added_fingerprints: Vec::new(),
+        }];
+        let options = LinkOptions {
+            skip_secret_scan: false,
+            params: LinkingParams::default(),
+        };
+
+        let links =
+            link_session_to_all_commits_with_options(&session, &commits, options).expect("linked");
+        assert_eq!(links[0].commit_sha, "c1");
 
-        let secrets = detect_secrets("Add API token and secret key");
-        assert!(!secrets.is_empty());
+        let redacted = redact_session_secrets(&session);
+        assert!(!redacted.messages[0].text.contains("sk-abc123"));
     }
 }
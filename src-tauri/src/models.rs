@@ -20,12 +20,14 @@ use sqlx::FromRow;
 /// * `commit_sha` - The git commit SHA this session links to
 /// * `confidence` - Algorithm confidence score (0.0 to 1.0)
 /// * `auto_linked` - true if algorithm-suggested, false if manually created
-/// * `needs_review` - true if multiple close matches require review
+/// * `needs_review` - true if the link should be confirmed by the user
+/// * `review_reason` - why it needs review (`close_match`, `author_mismatch`)
 /// * `created_at` - ISO timestamp when link was created
 ///
 /// # Constraints
 ///
-/// * UNIQUE(repo_id, session_id) - One session links to at most one commit (1:1 for MVP)
+/// * UNIQUE(repo_id, session_id, commit_sha) - A session links to each commit at most once
+///   (Migration 029 allows several commits per session)
 /// * FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE
 ///
 /// # Evidence
@@ -42,5 +44,7 @@ pub struct SessionLink {
     pub confidence: f64,
     pub auto_linked: bool,
     pub needs_review: bool,
+    #[sqlx(default)]
+    pub review_reason: Option<String>,
     pub created_at: String,
 }
@@ -17,7 +17,6 @@ use opentelemetry_proto::tonic::resource::v1::Resource;
 use prost::Message;
 use serde::Serialize;
 use serde_json::Value;
-use sqlx::Row;
 use std::{
     collections::{HashMap, HashSet},
     net::SocketAddr,
@@ -28,6 +27,9 @@ use std::{
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::oneshot;
 
+use crate::attribution::completions::{
+    acceptance_from_attributes, record_completion_acceptances, CompletionCaptureSummary,
+};
 use crate::{commands, git_diff, secret_store, DbState};
 
 const OTLP_PORT: u16 = 4318;
@@ -423,13 +425,12 @@ fn validate_api_key(headers: &HeaderMap) -> Result<(), String> {
     }
 }
 
+/// Registered repo for a checkout, including linked worktrees of it
 async fn resolve_repo_id(db: &sqlx::SqlitePool, repo_root: &str) -> Option<i64> {
-    let row = sqlx::query("SELECT id FROM repos WHERE path = ? LIMIT 1")
-        .bind(repo_root)
-        .fetch_optional(db)
+    crate::worktrees::resolve_repo_id_for_path(db, repo_root)
         .await
-        .ok()?;
-    row.map(|r| r.get::<i64, _>("id"))
+        .ok()
+        .flatten()
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -589,6 +590,18 @@ async fn handle_request(
         }
     };
 
+    let (events, completions) = capture_completion_events(&context, events).await;
+    if events.is_empty() && completions.recorded + completions.skipped > 0 {
+        return response(
+            StatusCode::OK,
+            IngestResponse {
+                accepted: completions.recorded as usize,
+                dropped: completions.skipped as usize,
+                errors: Vec::new(),
+            },
+        );
+    }
+
     match ingest_events(&context, events, signal) {
         Ok(outcome) => {
             // Log activity (best effort)
@@ -658,6 +671,48 @@ async fn handle_request(
     }
 }
 
+/// Record tab-completion acceptance events for `ai_tab` attribution and
+/// return the remaining events for trace ingest
+async fn capture_completion_events(
+    context: &ReceiverContext,
+    events: Vec<OtelEvent>,
+) -> (Vec<OtelEvent>, CompletionCaptureSummary) {
+    let mut acceptances = Vec::new();
+    let mut rest = Vec::new();
+    for event in events {
+        match acceptance_from_attributes(&event.attributes, &event.timestamp_iso) {
+            Some(acceptance) => acceptances.push(acceptance),
+            None => rest.push(event),
+        }
+    }
+    if acceptances.is_empty() {
+        return (rest, CompletionCaptureSummary::default());
+    }
+
+    let dropped = CompletionCaptureSummary {
+        recorded: 0,
+        skipped: acceptances.len() as u32,
+    };
+    let Some(repo_root) = context.state.repo_root.lock().ok().and_then(|g| g.clone()) else {
+        return (rest, dropped);
+    };
+    let Some(db) = context
+        .app_handle
+        .try_state::<DbState>()
+        .map(|s| s.0.clone())
+    else {
+        return (rest, dropped);
+    };
+    let Some(repo_id) = resolve_repo_id(&db, &repo_root).await else {
+        return (rest, dropped);
+    };
+
+    let summary = record_completion_acceptances(&db, repo_id, &acceptances)
+        .await
+        .unwrap_or(dropped);
+    (rest, summary)
+}
+
 fn response(status: StatusCode, payload: IngestResponse) -> impl IntoResponse {
     (status, Json(payload))
 }
@@ -0,0 +1,373 @@
+//! Session-to-pull-request links.
+//!
+//! In review workflows the pull request, not the commit, is the unit of work.
+//! Pull requests are registered by the frontend (from the GitHub connector)
+//! with their head and base branches. A session belongs to a PR when:
+//!
+//! - it is linked to one of the PR's commits (`commit`), or
+//! - it ran on the PR's head branch (`branch`), for sessions whose commits
+//!   are not linked (yet).
+//!
+//! A PR's commits are those reachable from its head but not on the base
+//! branch's first-parent history, so they are still found after the PR is
+//! merged with a merge commit. Links are recomputed whenever the PR is
+//! registered again.
+
+use crate::DbState;
+use git2::{Oid, Repository};
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashSet};
+use tauri::State;
+
+/// Cap on commits read from a PR's history
+const MAX_PR_COMMITS: usize = 1000;
+
+/// Cap on base-branch history walked to find where the PR starts
+const MAX_MAINLINE_COMMITS: usize = 20_000;
+
+/// A pull request as registered by the frontend
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestInput {
+    pub number: i64,
+    pub title: String,
+    pub head_branch: String,
+    pub base_branch: String,
+    /// Head commit; preferred over the branch, which may be deleted after merge
+    #[serde(default)]
+    pub head_sha: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// A session's link to a pull request
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrSessionLink {
+    pub session_id: String,
+    /// "commit" or "branch"
+    pub match_kind: String,
+    /// PR commits the session is linked to (0 for branch matches)
+    pub commit_count: i64,
+}
+
+/// A session linked to a pull request
+#[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct PrSession {
+    pub session_id: String,
+    pub tool: String,
+    pub imported_at: String,
+    pub match_kind: String,
+    pub commit_count: i64,
+}
+
+fn resolve_commit(repo: &Repository, spec: &str) -> Result<Oid, git2::Error> {
+    let object = repo
+        .revparse_single(spec)
+        .or_else(|_| repo.revparse_single(&format!("origin/{}", spec)))?;
+    Ok(object.peel_to_commit()?.id())
+}
+
+/// Commits in a pull request: reachable from `head` but not on `base`'s
+/// first-parent history.
+///
+/// A PR that was fast-forwarded into its base has no commits of its own by
+/// this rule; its sessions are matched by branch.
+pub fn pr_commit_shas(
+    repo: &Repository,
+    head: &str,
+    base: &str,
+) -> Result<Vec<String>, git2::Error> {
+    let head = resolve_commit(repo, head)?;
+    let base = resolve_commit(repo, base)?;
+
+    let mut mainline = HashSet::new();
+    let mut next = Some(repo.find_commit(base)?);
+    while let Some(commit) = next.take() {
+        if mainline.len() >= MAX_MAINLINE_COMMITS {
+            break;
+        }
+        mainline.insert(commit.id());
+        next = commit.parent(0).ok();
+    }
+
+    let mut seen = HashSet::new();
+    let mut stack = vec![head];
+    let mut shas = Vec::new();
+    while let Some(oid) = stack.pop() {
+        if shas.len() >= MAX_PR_COMMITS {
+            break;
+        }
+        if mainline.contains(&oid) || !seen.insert(oid) {
+            continue;
+        }
+        shas.push(oid.to_string());
+        stack.extend(repo.find_commit(oid)?.parent_ids());
+    }
+    Ok(shas)
+}
+
+/// Match sessions to a PR by its commits, then by its head branch.
+///
+/// `session_commits` holds each session's linked commits; `branch_sessions`
+/// the sessions that ran on the head branch.
+fn match_pr_sessions(
+    pr_commits: &HashSet<&str>,
+    session_commits: &[(String, String)],
+    branch_sessions: &[String],
+) -> Vec<PrSessionLink> {
+    let mut by_commit: BTreeMap<&str, i64> = BTreeMap::new();
+    for (session_id, sha) in session_commits {
+        if pr_commits.contains(sha.as_str()) {
+            *by_commit.entry(session_id.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut links: Vec<PrSessionLink> = by_commit
+        .iter()
+        .map(|(session_id, count)| PrSessionLink {
+            session_id: session_id.to_string(),
+            match_kind: "commit".to_string(),
+            commit_count: *count,
+        })
+        .collect();
+    for session_id in branch_sessions {
+        if !by_commit.contains_key(session_id.as_str())
+            && !links.iter().any(|link| &link.session_id == session_id)
+        {
+            links.push(PrSessionLink {
+                session_id: session_id.clone(),
+                match_kind: "branch".to_string(),
+                commit_count: 0,
+            });
+        }
+    }
+    links
+}
+
+async fn store_pull_request(
+    pool: &SqlitePool,
+    repo_id: i64,
+    pr: &PullRequestInput,
+) -> Result<(), String> {
+    sqlx::query(
+        r#"
+        INSERT INTO pull_requests (repo_id, number, title, head_branch, base_branch, head_sha, url)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, number) DO UPDATE SET
+            title = excluded.title,
+            head_branch = excluded.head_branch,
+            base_branch = excluded.base_branch,
+            head_sha = excluded.head_sha,
+            url = excluded.url,
+            updated_at = strftime('%Y-%m-%dT%H:%M:%fZ','now')
+        "#,
+    )
+    .bind(repo_id)
+    .bind(pr.number)
+    .bind(&pr.title)
+    .bind(&pr.head_branch)
+    .bind(&pr.base_branch)
+    .bind(&pr.head_sha)
+    .bind(&pr.url)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Recompute and store the sessions linked to a pull request.
+///
+/// When the repo cannot be opened or the PR's refs cannot be resolved, only
+/// branch matches are kept.
+async fn refresh_pr_links(
+    pool: &SqlitePool,
+    repo_id: i64,
+    pr: &PullRequestInput,
+) -> Result<Vec<PrSessionLink>, String> {
+    let pr_commits = match crate::attribution::utils::fetch_repo_root(pool, repo_id).await {
+        Ok(root) => Repository::open(root)
+            .and_then(|repo| {
+                let head = pr.head_sha.as_deref().unwrap_or(&pr.head_branch);
+                pr_commit_shas(&repo, head, &pr.base_branch)
+            })
+            .unwrap_or_default(),
+        Err(_) => Vec::new(),
+    };
+    let pr_commits: HashSet<&str> = pr_commits.iter().map(String::as_str).collect();
+
+    let session_commits: Vec<(String, String)> =
+        sqlx::query_as("SELECT session_id, commit_sha FROM session_links WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    let branch_sessions: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT s.id
+        FROM sessions s
+        WHERE (s.repo_id = ?1
+           OR EXISTS (SELECT 1 FROM session_repos r WHERE r.session_id = s.id AND r.repo_id = ?1))
+          AND s.purged_at IS NULL
+          AND s.git_branch = ?2
+        ORDER BY s.id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&pr.head_branch)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let links = match_pr_sessions(&pr_commits, &session_commits, &branch_sessions);
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM session_pr_links WHERE repo_id = ? AND pr_number = ?")
+        .bind(repo_id)
+        .bind(pr.number)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for link in &links {
+        sqlx::query(
+            "INSERT INTO session_pr_links (repo_id, session_id, pr_number, match_kind, commit_count) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(repo_id)
+        .bind(&link.session_id)
+        .bind(pr.number)
+        .bind(&link.match_kind)
+        .bind(link.commit_count)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(links)
+}
+
+async fn fetch_sessions_for_pr(
+    pool: &SqlitePool,
+    repo_id: i64,
+    pr_number: i64,
+) -> Result<Vec<PrSession>, String> {
+    sqlx::query_as::<_, PrSession>(
+        r#"
+        SELECT l.session_id, s.tool, s.imported_at, l.match_kind, l.commit_count
+        FROM session_pr_links l
+        JOIN sessions s ON s.id = l.session_id
+        WHERE l.repo_id = ? AND l.pr_number = ?
+        ORDER BY l.match_kind = 'branch', s.imported_at
+        "#,
+    )
+    .bind(repo_id)
+    .bind(pr_number)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))
+}
+
+/// Register (or update) a pull request and link its sessions.
+///
+/// Call again when the PR gains commits or sessions are relinked; its
+/// session links are recomputed each time.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn upsert_pull_request(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    pull_request: PullRequestInput,
+) -> Result<Vec<PrSession>, String> {
+    let pool = db_state.0.as_ref();
+    store_pull_request(pool, repo_id, &pull_request).await?;
+    refresh_pr_links(pool, repo_id, &pull_request).await?;
+    fetch_sessions_for_pr(pool, repo_id, pull_request.number).await
+}
+
+/// Sessions linked to a pull request: commit matches first, then sessions
+/// that only ran on its head branch.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_sessions_for_pr(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    pr_number: i64,
+) -> Result<Vec<PrSession>, String> {
+    fetch_sessions_for_pr(db_state.0.as_ref(), repo_id, pr_number).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit(repo: &Repository, parents: &[Oid], message: &str) -> Oid {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|p| repo.find_commit(*p).unwrap())
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(None, &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+    }
+
+    #[test]
+    fn pr_commits_survive_a_merge_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let root = commit(&repo, &[], "root");
+        let on_main = commit(&repo, &[root], "main work");
+        let f1 = commit(&repo, &[root], "feature 1");
+        let f2 = commit(&repo, &[f1], "feature 2");
+        repo.reference("refs/heads/main", on_main, true, "test")
+            .unwrap();
+        repo.reference("refs/heads/feature", f2, true, "test")
+            .unwrap();
+
+        let expected = vec![f2.to_string(), f1.to_string()];
+        assert_eq!(pr_commit_shas(&repo, "feature", "main").unwrap(), expected);
+
+        let merge = commit(&repo, &[on_main, f2], "Merge feature");
+        repo.reference("refs/heads/main", merge, true, "test")
+            .unwrap();
+        assert_eq!(
+            pr_commit_shas(&repo, &f2.to_string(), "main").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn sessions_match_by_commit_then_branch() {
+        let pr_commits: HashSet<&str> = ["f1", "f2"].into_iter().collect();
+        let session_commits = vec![
+            ("s1".to_string(), "f1".to_string()),
+            ("s1".to_string(), "f2".to_string()),
+            ("s2".to_string(), "main".to_string()),
+        ];
+        let branch_sessions = vec!["s1".to_string(), "s3".to_string()];
+
+        let links = match_pr_sessions(&pr_commits, &session_commits, &branch_sessions);
+        assert_eq!(
+            links,
+            vec![
+                PrSessionLink {
+                    session_id: "s1".to_string(),
+                    match_kind: "commit".to_string(),
+                    commit_count: 2,
+                },
+                PrSessionLink {
+                    session_id: "s3".to_string(),
+                    match_kind: "branch".to_string(),
+                    commit_count: 0,
+                },
+            ]
+        );
+    }
+}
@@ -10,6 +10,11 @@
 //! - `get_session_links_for_commit` - Get all sessions linked to a commit
 //! - `delete_session_link` - Remove a session link
 //!
+//! # Multiple Commits per Session
+//!
+//! A session may link to several commits (one row per commit, each with its
+//! own confidence). The highest-confidence row is the session's primary link.
+//!
 //! # Concurrent Safety
 //!
 //! The `create_or_update_session_link` function uses SQLite's `ON CONFLICT`
@@ -20,14 +25,89 @@
 //! Build Plan Epic 2 Stories 2.2-2.3.
 //! Resolution Summary Backend fix 2: "Wrap in transaction, UPDATE on SQLITE_CONSTRAINT."
 
-use crate::{models::SessionLink, DbState};
+use crate::{
+    clock_offsets::detect_clock_offsets,
+    link_commands::{record_confirmed_link, record_rejected_links},
+    linking::LinkResult,
+    models::SessionLink,
+    DbState,
+};
 use sqlx::Row;
 
+/// Replace a session's algorithm-suggested links with `links`.
+///
+/// Manual links are kept; a suggested link to a commit that is already linked
+/// manually only refreshes its confidence.
+pub(crate) async fn replace_auto_links(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    links: &[LinkResult],
+) -> Result<(), String> {
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Failed to store link: {e}"))?;
+    write_auto_links(&mut tx, repo_id, session_id, links).await?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to store link: {e}"))
+}
+
+/// `replace_auto_links` within a caller's transaction (for batched writes)
+pub(crate) async fn write_auto_links(
+    tx: &mut sqlx::SqliteConnection,
+    repo_id: i64,
+    session_id: &str,
+    links: &[LinkResult],
+) -> Result<(), String> {
+    sqlx::query(
+        "DELETE FROM session_links WHERE repo_id = $1 AND session_id = $2 AND auto_linked = 1",
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to store link: {e}"))?;
+
+    for link in links {
+        sqlx::query(
+            r#"
+            INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, review_reason, temporal_score, file_score, message_score)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT(repo_id, session_id, commit_sha) DO UPDATE SET
+                confidence = excluded.confidence,
+                needs_review = excluded.needs_review,
+                review_reason = excluded.review_reason,
+                temporal_score = excluded.temporal_score,
+                file_score = excluded.file_score,
+                message_score = excluded.message_score
+            "#,
+        )
+        .bind(repo_id)
+        .bind(session_id)
+        .bind(&link.commit_sha)
+        .bind(link.confidence)
+        .bind(link.auto_linked)
+        .bind(if link.needs_review { 1 } else { 0 })
+        .bind(link.review_reason.map(|r| r.as_str()))
+        .bind(link.temporal_score)
+        .bind(link.file_score)
+        .bind(link.message_score)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to store link: {e}"))?;
+    }
+
+    Ok(())
+}
+
 /// Create or update a session link (upsert).
 ///
 /// This function implements the upsert pattern using SQLite's `ON CONFLICT`
-/// clause. If a session link already exists for the given `(repo_id, session_id)`
-/// pair, it updates the existing record instead of creating a duplicate.
+/// clause. If a link already exists for the given `(repo_id, session_id, commit_sha)`,
+/// it updates the existing record instead of creating a duplicate. Links from
+/// the same session to other commits are left alone.
 ///
 /// # Arguments
 ///
@@ -75,16 +155,21 @@ pub async fn create_or_update_session_link(
 
     let db = &*pool.0;
 
+    // Linking a suggested commit by hand confirms the suggestion
+    if !auto_linked {
+        record_confirmed_link(db, repo_id, &session_id, &commit_sha).await?;
+    }
+
     // Perform upsert using ON CONFLICT with parameter binding
     let result = sqlx::query(
         r#"
         INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked, needs_review)
         VALUES ($1, $2, $3, $4, $5, $6)
-        ON CONFLICT(repo_id, session_id) DO UPDATE SET
-            commit_sha = excluded.commit_sha,
+        ON CONFLICT(repo_id, session_id, commit_sha) DO UPDATE SET
             confidence = excluded.confidence,
             auto_linked = excluded.auto_linked,
-            needs_review = excluded.needs_review
+            needs_review = excluded.needs_review,
+            review_reason = NULL
         RETURNING id
         "#,
     )
@@ -98,6 +183,13 @@ pub async fn create_or_update_session_link(
     .await
     .map_err(|e| format!("Database error: {e}"))?;
 
+    // Confirmed links show how far off the tool's clock is
+    if !auto_linked {
+        if let Err(e) = detect_clock_offsets(db).await {
+            eprintln!("Narrative: clock offset detection failed: {e}");
+        }
+    }
+
     Ok(result.get("id"))
 }
 
@@ -125,7 +217,7 @@ pub async fn get_session_links_for_repo(
 
     let rows = sqlx::query(
         r#"
-        SELECT id, repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, created_at
+        SELECT id, repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, review_reason, created_at
         FROM session_links
         WHERE repo_id = $1
         ORDER BY created_at DESC
@@ -146,6 +238,7 @@ pub async fn get_session_links_for_repo(
             confidence: row.get("confidence"),
             auto_linked: row.get::<i64, _>("auto_linked") != 0,
             needs_review: row.get::<i64, _>("needs_review") != 0,
+            review_reason: row.get("review_reason"),
             created_at: row.get("created_at"),
         })
         .collect();
@@ -179,7 +272,7 @@ pub async fn get_session_links_for_commit(
 
     let rows = sqlx::query(
         r#"
-        SELECT id, repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, created_at
+        SELECT id, repo_id, session_id, commit_sha, confidence, auto_linked, needs_review, review_reason, created_at
         FROM session_links
         WHERE repo_id = $1 AND commit_sha = $2
         ORDER BY created_at DESC
@@ -201,6 +294,7 @@ pub async fn get_session_links_for_commit(
             confidence: row.get("confidence"),
             auto_linked: row.get::<i64, _>("auto_linked") != 0,
             needs_review: row.get::<i64, _>("needs_review") != 0,
+            review_reason: row.get("review_reason"),
             created_at: row.get("created_at"),
         })
         .collect();
@@ -218,6 +312,7 @@ pub async fn get_session_links_for_commit(
 /// * `pool` - SQLite database pool from tauri_plugin_sql
 /// * `repo_id` - Repository ID
 /// * `session_id` - Session identifier to unlink
+/// * `commit_sha` - Only remove the link to this commit (all links when omitted)
 ///
 /// # Returns
 ///
@@ -232,12 +327,19 @@ pub async fn delete_session_link(
     pool: tauri::State<'_, DbState>,
     repo_id: i64,
     session_id: String,
+    commit_sha: Option<String>,
 ) -> Result<(), String> {
     let db = &*pool.0;
 
-    sqlx::query("DELETE FROM session_links WHERE repo_id = $1 AND session_id = $2")
+    // Unlinking suggested commits rejects the suggestion
+    record_rejected_links(db, repo_id, &session_id, commit_sha.as_deref()).await?;
+
+    sqlx::query(
+        "DELETE FROM session_links WHERE repo_id = $1 AND session_id = $2 AND ($3 IS NULL OR commit_sha = $3)",
+    )
         .bind(repo_id)
         .bind(&session_id)
+        .bind(&commit_sha)
         .execute(db)
         .await
         .map_err(|e| format!("Database error: {e}"))?;
@@ -1,20 +1,44 @@
 //! Tauri commands for Story Anchors.
 
+use super::coverage::{get_anchor_coverage as get_anchor_coverage_impl, AnchorCoverageReport};
+use super::fetched_notes::{
+    import_fetched_notes as import_fetched_notes_impl, import_fetched_notes_if_enabled,
+    FetchedNotesImportSummary,
+};
 use super::hooks as hooks_impl;
+use super::lineage::{get_commit_lineage as get_commit_lineage_impl, CommitLineage};
+use super::notes_export::{
+    export_notes_for_range as export_notes_for_range_impl, NotesExportProgress,
+};
+use super::notes_gc::{gc_narrative_notes as gc_narrative_notes_impl, NotesGcSummary};
+use super::notes_merge::{merge_notes_from_remote, NotesMergeSummary};
+use super::notes_namespace::{
+    set_notes_namespace as set_notes_namespace_impl, NotesNamespaceMigration,
+};
+use super::notes_schema::{
+    migrate_notes_schema as migrate_notes_schema_in_repo, NotesSchemaRefSummary,
+};
+use super::release_anchors::{
+    create_release_anchor as create_release_anchor_impl,
+    get_release_anchors as get_release_anchors_impl, ReleaseAnchor,
+};
 use super::sessions_notes_io::{
     export_sessions_note, import_sessions_notes_batch, SessionsNoteBatchSummary,
     SessionsNoteExportSummary,
 };
-use super::status::{get_commit_story_anchor_status, StoryAnchorCommitStatus};
+use super::status::{get_story_anchor_statuses, StoryAnchorCommitStatus};
+use super::sync::{fetch_notes_from_remote, push_notes_to_remote, NotesSyncSummary};
 use crate::attribution::line_attribution::{
     ensure_line_attributions_for_commit, store_rewrite_key,
 };
+use crate::attribution::timeseries::TimeseriesGranularity;
 use crate::attribution::utils::fetch_repo_root;
-use crate::story_anchors::refs::{ATTRIBUTION_REF_CANONICAL, ATTRIBUTION_REF_LEGACY_NARRATIVE};
+use crate::story_anchors::refs::{fetch_notes_refs, ATTRIBUTION_REF_LEGACY_NARRATIVE};
 use crate::DbState;
 use git2::{Oid, Repository, Signature};
 use serde::Serialize;
 use std::{env, fs, path::PathBuf};
+use tauri::Emitter;
 use tauri::Manager;
 use tauri::State;
 
@@ -243,17 +267,94 @@ pub async fn configure_git_notes_fetch(
     ))
 }
 
+/// Push the repo's local notes refs (`refs/notes/narrative/*` by default) to
+/// a remote (default: origin, else the first remote) and report the result
+/// for each ref.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn push_narrative_notes(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    remote: Option<String>,
+) -> Result<NotesSyncSummary, String> {
+    let repo_root = fetch_repo_root(&db.0, repo_id).await?;
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
+    tokio::task::spawn_blocking(move || push_notes_to_remote(&repo_root, &notes_refs, remote))
+        .await
+        .map_err(|e| format!("Notes push task failed: {}", e))?
+}
+
+/// Fetch a remote's notes refs under the repo's namespace, fast-forwarding
+/// local refs where possible, and report the result for each ref. Imports
+/// the new notes too when `import_notes_after_fetch` is on.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn fetch_narrative_notes(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    remote: Option<String>,
+) -> Result<NotesSyncSummary, String> {
+    let repo_root = fetch_repo_root(&db.0, repo_id).await?;
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
+    let mut summary = tokio::task::spawn_blocking(move || {
+        fetch_notes_from_remote(&repo_root, &notes_refs, remote)
+    })
+    .await
+    .map_err(|e| format!("Notes fetch task failed: {}", e))??;
+    summary.imported = import_fetched_notes_if_enabled(&db.0, repo_id).await?;
+    Ok(summary)
+}
+
+/// Import the notes that arrived (by any fetch or pull) since the last
+/// import and report which commits gained narrative data.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn import_fetched_notes(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<FetchedNotesImportSummary, String> {
+    import_fetched_notes_impl(&db.0, repo_id).await
+}
+
+/// Merge fetched sessions notes into the local ref after `fetch_narrative_notes`
+/// reports it diverged, then re-import the notes that changed.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn merge_narrative_notes(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    remote: Option<String>,
+) -> Result<NotesMergeSummary, String> {
+    let repo_root = fetch_repo_root(&db.0, repo_id).await?;
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
+    let mut summary = tokio::task::spawn_blocking(move || {
+        merge_notes_from_remote(&repo_root, &notes_refs, remote)
+    })
+    .await
+    .map_err(|e| format!("Notes merge task failed: {}", e))??;
+    if !summary.changed_commits.is_empty() {
+        let imported =
+            import_sessions_notes_batch(&db.0, repo_id, summary.changed_commits.clone()).await?;
+        summary.imported_notes = imported.imported;
+    }
+    Ok(summary)
+}
+
+/// Rewrite ancestors/descendants of a commit and the anchors carried across
+/// each rewrite.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_commit_lineage(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    commit_sha: String,
+) -> Result<CommitLineage, String> {
+    get_commit_lineage_impl(&db.0, repo_id, &commit_sha).await
+}
+
+/// Story Anchor status for many commits; note presence is cached per notes tip.
 #[tauri::command(rename_all = "camelCase")]
 pub async fn get_story_anchor_status(
     db: State<'_, DbState>,
     repo_id: i64,
     commit_shas: Vec<String>,
 ) -> Result<Vec<StoryAnchorCommitStatus>, String> {
-    let mut out = Vec::new();
-    for sha in commit_shas {
-        out.push(get_commit_story_anchor_status(&db.0, repo_id, &sha).await);
-    }
-    Ok(out)
+    Ok(get_story_anchor_statuses(&db.0, repo_id, &commit_shas).await)
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -274,6 +375,32 @@ pub async fn export_session_link_note(
     export_sessions_note(&db.0, repo_id, &commit_sha).await
 }
 
+/// Export sessions and attribution notes for `fromRef..toRef` (the whole
+/// history of `toRef`, default HEAD, when `fromRef` is omitted).
+///
+/// Emits `notes-export-progress` after each commit and returns the final report.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn export_notes_for_range(
+    app_handle: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    from_ref: Option<String>,
+    to_ref: Option<String>,
+) -> Result<NotesExportProgress, String> {
+    export_notes_for_range_impl(
+        &db.0,
+        repo_id,
+        from_ref.as_deref(),
+        to_ref.as_deref().unwrap_or("HEAD"),
+        |progress| {
+            if let Err(e) = app_handle.emit("notes-export-progress", progress) {
+                eprintln!("Failed to emit notes-export-progress: {}", e);
+            }
+        },
+    )
+    .await
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LinkSessionsSummary {
@@ -350,6 +477,7 @@ pub async fn migrate_attribution_notes_ref(
     commit_shas: Vec<String>,
 ) -> Result<MigrateAttributionNotesSummary, String> {
     let repo_root = fetch_repo_root(&db.0, repo_id).await?;
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let signature = repo
         .signature()
@@ -387,7 +515,7 @@ pub async fn migrate_attribution_notes_ref(
             .note(
                 &signature,
                 &signature,
-                Some(ATTRIBUTION_REF_CANONICAL),
+                Some(&notes_refs.attribution),
                 oid,
                 message,
                 true,
@@ -409,6 +537,133 @@ pub async fn migrate_attribution_notes_ref(
     })
 }
 
+/// Rewrite older and unversioned sessions/attribution notes at the current
+/// schema version, then re-import the rewritten notes.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn migrate_notes_schema(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<Vec<NotesSchemaRefSummary>, String> {
+    use crate::attribution::notes_io::import_attribution_notes_batch;
+
+    let repo_root = fetch_repo_root(&db.0, repo_id).await?;
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
+    let summaries = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        migrate_notes_schema_in_repo(&repo, &notes_refs)?
+    };
+
+    for summary in &summaries {
+        if summary.upgraded_commits.is_empty() {
+            continue;
+        }
+        let commits = summary.upgraded_commits.clone();
+        if summary.note_ref == notes_refs.sessions {
+            import_sessions_notes_batch(&db.0, repo_id, commits).await?;
+        } else if summary.note_ref == notes_refs.attribution {
+            import_attribution_notes_batch(&db.0, repo_id, commits).await?;
+        }
+    }
+    Ok(summaries)
+}
+
+/// Migrate or prune notes on unreachable commits. Defaults to a dry run
+/// that only reports; migrated notes are re-imported after a real run.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn gc_narrative_notes(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    dry_run: Option<bool>,
+    migrate: Option<bool>,
+) -> Result<NotesGcSummary, String> {
+    use crate::attribution::notes_io::import_attribution_notes_batch;
+
+    let dry_run = dry_run.unwrap_or(true);
+    let notes_refs = fetch_notes_refs(&db.0, repo_id).await?;
+    let summary = gc_narrative_notes_impl(&db.0, repo_id, dry_run, migrate.unwrap_or(true)).await?;
+    if dry_run {
+        return Ok(summary);
+    }
+
+    for ref_summary in &summary.refs {
+        if ref_summary.migrated.is_empty() {
+            continue;
+        }
+        let commits: Vec<String> = ref_summary
+            .migrated
+            .iter()
+            .map(|m| m.to_commit.clone())
+            .collect();
+        if ref_summary.note_ref == notes_refs.sessions {
+            import_sessions_notes_batch(&db.0, repo_id, commits).await?;
+        } else if ref_summary.note_ref == notes_refs.attribution {
+            import_attribution_notes_batch(&db.0, repo_id, commits).await?;
+        }
+    }
+    Ok(summary)
+}
+
+/// Move the repo's notes to another ref namespace, e.g.
+/// `refs/notes/acme/narrative`; an empty or missing namespace restores the
+/// default `refs/notes/narrative`.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn set_notes_namespace(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    namespace: Option<String>,
+) -> Result<NotesNamespaceMigration, String> {
+    let namespace = namespace.filter(|ns| !ns.trim().is_empty());
+    set_notes_namespace_impl(&db.0, repo_id, namespace.as_deref()).await
+}
+
+/// Aggregate the sessions and attribution of a release (`tag` and the commits
+/// since the previous tag, or since `from_ref`) into a release anchor, written
+/// as a note on the tagged commit unless `write_note` is false.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn create_release_anchor(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    tag: String,
+    from_ref: Option<String>,
+    write_note: Option<bool>,
+) -> Result<ReleaseAnchor, String> {
+    let from_ref = from_ref.filter(|r| !r.trim().is_empty());
+    create_release_anchor_impl(
+        &db.0,
+        repo_id,
+        &tag,
+        from_ref.as_deref(),
+        write_note.unwrap_or(true),
+    )
+    .await
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_release_anchors(
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<Vec<ReleaseAnchor>, String> {
+    get_release_anchors_impl(&db.0, repo_id).await
+}
+
+/// How many of the newest `limit` commits carry sessions notes, attribution
+/// notes, both or neither, overall and per week or month.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_anchor_coverage(
+    db: State<'_, DbState>,
+    repo_id: i64,
+    granularity: Option<TimeseriesGranularity>,
+    limit: Option<usize>,
+) -> Result<AnchorCoverageReport, String> {
+    get_anchor_coverage_impl(
+        &db.0,
+        repo_id,
+        granularity.unwrap_or(TimeseriesGranularity::Week),
+        limit,
+    )
+    .await
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ReconcileSummary {
@@ -555,12 +810,8 @@ async fn copy_commit_session_links(
     Ok(copied)
 }
 
-#[tauri::command(rename_all = "camelCase")]
-pub async fn install_repo_hooks(
-    app: tauri::AppHandle,
-    db: State<'_, DbState>,
-    repo_id: i64,
-) -> Result<(), String> {
+/// Database path and (freshly copied) narrative-cli path to write into hooks
+fn prepare_hook_paths(app: &tauri::AppHandle) -> Result<(String, String), String> {
     let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
     let db_path = app_data_dir.join("narrative.db");
     let db_path_str = db_path.to_string_lossy().to_string();
@@ -595,7 +846,7 @@ pub async fn install_repo_hooks(
     }) {
         candidates.push(found);
     }
-    if let Some(found) = find_packaged_narrative_cli(&app) {
+    if let Some(found) = find_packaged_narrative_cli(app) {
         candidates.push(found);
     }
 
@@ -614,7 +865,46 @@ pub async fn install_repo_hooks(
         cli_dest.to_string_lossy().to_string()
     };
 
-    hooks_impl::install_repo_hooks_by_id(&db.0, repo_id, &db_path_str, &cli_path_for_hook).await
+    Ok((db_path_str, cli_path_for_hook))
+}
+
+#[tauri::command(rename_all = "camelCase")]
+pub async fn install_repo_hooks(
+    app: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+) -> Result<(), String> {
+    let (db_path, cli_path) = prepare_hook_paths(&app)?;
+    hooks_impl::install_repo_hooks_by_id(&db.0, repo_id, &db_path, &cli_path).await
+}
+
+/// Check a repo's hooks for problems that would make them fail at commit
+/// time; with `repair`, reinstall them (refreshing narrative-cli) and report
+/// what remains.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn doctor_repo_hooks(
+    app: tauri::AppHandle,
+    db: State<'_, DbState>,
+    repo_id: i64,
+    repair: Option<bool>,
+) -> Result<hooks_impl::RepoHooksDoctorReport, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("narrative.db")
+        .to_string_lossy()
+        .to_string();
+    let report = hooks_impl::doctor_repo_hooks(&db.0, repo_id, Some(&db_path)).await?;
+    if report.healthy || !repair.unwrap_or(false) {
+        return Ok(report);
+    }
+
+    let (db_path, cli_path) = prepare_hook_paths(&app)?;
+    hooks_impl::install_repo_hooks_by_id(&db.0, repo_id, &db_path, &cli_path).await?;
+    let mut report = hooks_impl::doctor_repo_hooks(&db.0, repo_id, Some(&db_path)).await?;
+    report.repaired = true;
+    Ok(report)
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -627,6 +917,8 @@ pub async fn uninstall_repo_hooks(db: State<'_, DbState>, repo_id: i64) -> Resul
 pub struct RepoHooksStatusPayload {
     pub installed: bool,
     pub hooks_dir: String,
+    pub missing_hooks: Vec<String>,
+    pub linked_worktree: bool,
 }
 
 #[tauri::command(rename_all = "camelCase")]
@@ -638,5 +930,7 @@ pub async fn get_repo_hooks_status(
     Ok(RepoHooksStatusPayload {
         installed: status.installed,
         hooks_dir: status.hooks_dir.to_string_lossy().to_string(),
+        missing_hooks: status.missing_hooks,
+        linked_worktree: status.linked_worktree,
     })
 }
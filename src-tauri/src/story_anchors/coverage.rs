@@ -0,0 +1,229 @@
+//! Story Anchor coverage of recent history.
+//!
+//! Walks the most recent commits from HEAD and classifies each by the notes
+//! it carries: sessions and attribution, one of them, or neither. Counts and
+//! percentages are reported overall and per week or month (by author date),
+//! so a team can see how completely its history is anchored and whether
+//! that is improving.
+
+use crate::attribution::timeseries::TimeseriesGranularity;
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::refs::fetch_notes_refs;
+use crate::story_anchors::status::{scan_notes_presence, NotesPresence};
+use chrono::{Datelike, NaiveDate};
+use git2::{Repository, Sort};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Commits walked when no limit is given
+pub const DEFAULT_COVERAGE_COMMITS: usize = 1000;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorCoverageCounts {
+    pub commits: u32,
+    /// Commits with both a sessions and an attribution note
+    pub both: u32,
+    pub sessions_only: u32,
+    pub attribution_only: u32,
+    pub neither: u32,
+    /// Commits with a sessions note, as a percentage of `commits`
+    pub sessions_percentage: f64,
+    /// Commits with an attribution note, as a percentage of `commits`
+    pub attribution_percentage: f64,
+    /// Commits with at least one note, as a percentage of `commits`
+    pub anchored_percentage: f64,
+}
+
+impl AnchorCoverageCounts {
+    fn add(&mut self, presence: Option<&NotesPresence>) {
+        let sessions = presence.is_some_and(|p| p.has_sessions_note);
+        let attribution = presence.is_some_and(|p| p.attribution_ref.is_some());
+        self.commits += 1;
+        match (sessions, attribution) {
+            (true, true) => self.both += 1,
+            (true, false) => self.sessions_only += 1,
+            (false, true) => self.attribution_only += 1,
+            (false, false) => self.neither += 1,
+        }
+    }
+
+    fn finish(&mut self) {
+        let percentage = |count: u32| {
+            if self.commits == 0 {
+                0.0
+            } else {
+                count as f64 / self.commits as f64 * 100.0
+            }
+        };
+        self.sessions_percentage = percentage(self.both + self.sessions_only);
+        self.attribution_percentage = percentage(self.both + self.attribution_only);
+        self.anchored_percentage = percentage(self.commits - self.neither);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorCoveragePeriod {
+    /// First day of the period (`YYYY-MM-DD`)
+    pub period_start: String,
+    #[serde(flatten)]
+    pub counts: AnchorCoverageCounts,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnchorCoverageReport {
+    pub repo_id: i64,
+    pub granularity: TimeseriesGranularity,
+    pub totals: AnchorCoverageCounts,
+    /// Oldest first
+    pub periods: Vec<AnchorCoveragePeriod>,
+}
+
+/// First day of the week (Monday) or month containing `date`
+fn period_start(date: NaiveDate, granularity: TimeseriesGranularity) -> NaiveDate {
+    match granularity {
+        TimeseriesGranularity::Week => {
+            date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64)
+        }
+        TimeseriesGranularity::Month => date.with_day(1).unwrap_or(date),
+    }
+}
+
+/// Tally `commits` (sha, author time in seconds) overall and per period
+pub fn summarize_coverage(
+    commits: &[(String, i64)],
+    presence: &HashMap<String, NotesPresence>,
+    granularity: TimeseriesGranularity,
+) -> (AnchorCoverageCounts, Vec<AnchorCoveragePeriod>) {
+    let mut totals = AnchorCoverageCounts::default();
+    let mut periods: BTreeMap<NaiveDate, AnchorCoverageCounts> = BTreeMap::new();
+    for (sha, seconds) in commits {
+        let note = presence.get(sha);
+        totals.add(note);
+        if let Some(time) = chrono::DateTime::from_timestamp(*seconds, 0) {
+            periods
+                .entry(period_start(time.date_naive(), granularity))
+                .or_default()
+                .add(note);
+        }
+    }
+
+    totals.finish();
+    let periods = periods
+        .into_iter()
+        .map(|(start, mut counts)| {
+            counts.finish();
+            AnchorCoveragePeriod {
+                period_start: start.format("%Y-%m-%d").to_string(),
+                counts,
+            }
+        })
+        .collect();
+    (totals, periods)
+}
+
+/// The newest `limit` commits reachable from HEAD with their author times
+fn recent_commits(repo: &Repository, limit: usize) -> Result<Vec<(String, i64)>, String> {
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.set_sorting(Sort::TIME).map_err(|e| e.to_string())?;
+    walk.push_head().map_err(|e| e.to_string())?;
+
+    let mut commits = Vec::new();
+    for oid in walk.take(limit) {
+        let oid = oid.map_err(|e| e.to_string())?;
+        let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
+        commits.push((oid.to_string(), commit.author().when().seconds()));
+    }
+    Ok(commits)
+}
+
+/// Coverage of the newest `limit` commits (default
+/// `DEFAULT_COVERAGE_COMMITS`) by the repo's sessions and attribution notes
+pub async fn get_anchor_coverage(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    granularity: TimeseriesGranularity,
+    limit: Option<usize>,
+) -> Result<AnchorCoverageReport, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
+    let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+
+    let commits = recent_commits(&repo, limit.unwrap_or(DEFAULT_COVERAGE_COMMITS))?;
+    let shas: Vec<String> = commits.iter().map(|(sha, _)| sha.clone()).collect();
+    let presence = scan_notes_presence(&repo, &refs, &shas)?;
+    let (totals, periods) = summarize_coverage(&commits, &presence, granularity);
+
+    Ok(AnchorCoverageReport {
+        repo_id,
+        granularity,
+        totals,
+        periods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presence(sessions: bool, attribution: bool) -> NotesPresence {
+        NotesPresence {
+            attribution_ref: attribution.then(|| "refs/notes/narrative/attribution".to_string()),
+            has_sessions_note: sessions,
+            has_lineage_note: false,
+        }
+    }
+
+    #[test]
+    fn commits_are_classified_and_bucketed_by_author_week() {
+        // 2026-03-02 is a Monday
+        let monday = 1_772_409_600;
+        let day = 86_400;
+        let commits = vec![
+            ("a".to_string(), monday),
+            ("b".to_string(), monday + 6 * day),
+            ("c".to_string(), monday + 7 * day),
+            ("d".to_string(), monday + 8 * day),
+        ];
+        let presence = HashMap::from([
+            ("a".to_string(), presence(true, true)),
+            ("b".to_string(), presence(true, false)),
+            ("c".to_string(), presence(false, true)),
+        ]);
+
+        let (totals, periods) =
+            summarize_coverage(&commits, &presence, TimeseriesGranularity::Week);
+        assert_eq!(
+            (
+                totals.both,
+                totals.sessions_only,
+                totals.attribution_only,
+                totals.neither
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(totals.sessions_percentage, 50.0);
+        assert_eq!(totals.anchored_percentage, 75.0);
+
+        let weeks: Vec<(&str, u32, f64)> = periods
+            .iter()
+            .map(|p| {
+                (
+                    p.period_start.as_str(),
+                    p.counts.commits,
+                    p.counts.anchored_percentage,
+                )
+            })
+            .collect();
+        assert_eq!(
+            weeks,
+            vec![("2026-03-02", 2, 100.0), ("2026-03-09", 2, 50.0)]
+        );
+
+        let (_, months) = summarize_coverage(&commits, &presence, TimeseriesGranularity::Month);
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].period_start, "2026-03-01");
+    }
+}
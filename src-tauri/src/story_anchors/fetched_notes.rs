@@ -0,0 +1,214 @@
+//! Importing notes that arrived from other Narrative installs.
+//!
+//! `notes_import_tips` records each notes ref's tip as of its last import.
+//! `import_fetched_notes` compares the current tips against it, imports only
+//! the sessions and attribution notes added or changed since, and reports
+//! the commits that gained narrative data (session links or line
+//! attributions) they did not have before.
+//!
+//! With the repo's `import_notes_after_fetch` preference on, this runs after
+//! `fetch_narrative_notes` and from the post-merge hook (`git pull`).
+
+use crate::attribution::notes_io::{import_attribution_notes_batch, AttributionNoteBatchSummary};
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_merge::notes_entries;
+use crate::story_anchors::refs::fetch_notes_refs;
+use crate::story_anchors::sessions_notes_io::{
+    import_sessions_notes_batch, SessionsNoteBatchSummary,
+};
+use git2::{Oid, Repository};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchedNotesImportSummary {
+    /// Commits whose notes were added or changed since the last import
+    pub new_notes: u32,
+    pub sessions: SessionsNoteBatchSummary,
+    pub attribution: AttributionNoteBatchSummary,
+    /// Commits with no narrative data before the import that have some now
+    pub gained_commits: Vec<String>,
+}
+
+/// Commits whose note was added or changed between `old_tip` and `new_tip`.
+/// An `old_tip` that no longer exists counts every note as new.
+pub fn changed_note_commits(
+    repo: &Repository,
+    old_tip: Option<Oid>,
+    new_tip: Oid,
+) -> Result<Vec<String>, String> {
+    let old_entries = old_tip
+        .and_then(|tip| notes_entries(repo, tip).ok())
+        .unwrap_or_else(BTreeMap::new);
+    Ok(notes_entries(repo, new_tip)?
+        .into_iter()
+        .filter(|(sha, blob)| old_entries.get(sha) != Some(blob))
+        .map(|(sha, _)| sha)
+        .collect())
+}
+
+/// True if a commit has session links or line attributions
+async fn has_narrative_data(db: &SqlitePool, repo_id: i64, commit_sha: &str) -> bool {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (SELECT 1 FROM commit_session_links WHERE repo_id = ?1 AND commit_sha = ?2)
+            OR EXISTS (SELECT 1 FROM session_links WHERE repo_id = ?1 AND commit_sha = ?2)
+            OR EXISTS (SELECT 1 FROM line_attributions WHERE repo_id = ?1 AND commit_sha = ?2)
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_one(db)
+    .await
+    .unwrap_or(false)
+}
+
+/// Import the notes that arrived since the last import and record the tips
+/// they were imported from
+pub async fn import_fetched_notes(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<FetchedNotesImportSummary, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
+    let imported_tips: HashMap<String, String> =
+        sqlx::query_as("SELECT note_ref, tip FROM notes_import_tips WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_all(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .into_iter()
+            .collect();
+
+    let mut tips = Vec::new();
+    let mut session_commits = Vec::new();
+    let mut attribution_commits = BTreeSet::new();
+    {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let [attribution, legacy] = refs.attribution_import_precedence();
+        for note_ref in [refs.sessions.as_str(), attribution, legacy] {
+            let Ok(tip) = repo.refname_to_id(note_ref) else {
+                continue;
+            };
+            let old_tip = imported_tips
+                .get(note_ref)
+                .and_then(|tip| Oid::from_str(tip).ok());
+            if old_tip == Some(tip) {
+                continue;
+            }
+            let changed = changed_note_commits(&repo, old_tip, tip)?;
+            if note_ref == refs.sessions {
+                session_commits = changed;
+            } else {
+                attribution_commits.extend(changed);
+            }
+            tips.push((note_ref.to_string(), tip.to_string()));
+        }
+    }
+
+    let candidates: BTreeSet<String> = session_commits
+        .iter()
+        .chain(attribution_commits.iter())
+        .cloned()
+        .collect();
+    let mut without_data = Vec::new();
+    for sha in &candidates {
+        if !has_narrative_data(db, repo_id, sha).await {
+            without_data.push(sha.clone());
+        }
+    }
+
+    let sessions = import_sessions_notes_batch(db, repo_id, session_commits).await?;
+    let attribution =
+        import_attribution_notes_batch(db, repo_id, attribution_commits.into_iter().collect())
+            .await?;
+
+    let mut gained_commits = Vec::new();
+    for sha in without_data {
+        if has_narrative_data(db, repo_id, &sha).await {
+            gained_commits.push(sha);
+        }
+    }
+
+    for (note_ref, tip) in tips {
+        sqlx::query(
+            r#"
+            INSERT INTO notes_import_tips (repo_id, note_ref, tip)
+            VALUES (?, ?, ?)
+            ON CONFLICT(repo_id, note_ref) DO UPDATE SET
+                tip = excluded.tip,
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(repo_id)
+        .bind(note_ref)
+        .bind(tip)
+        .execute(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+
+    Ok(FetchedNotesImportSummary {
+        new_notes: candidates.len() as u32,
+        sessions,
+        attribution,
+        gained_commits,
+    })
+}
+
+/// `import_fetched_notes`, if the repo's `import_notes_after_fetch`
+/// preference is on
+pub async fn import_fetched_notes_if_enabled(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<Option<FetchedNotesImportSummary>, String> {
+    let enabled: Option<bool> = sqlx::query_scalar(
+        "SELECT import_notes_after_fetch != 0 FROM attribution_prefs WHERE repo_id = ?",
+    )
+    .bind(repo_id)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    if !enabled.unwrap_or(false) {
+        return Ok(None);
+    }
+    import_fetched_notes(db, repo_id).await.map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::SESSIONS_REF_CANONICAL;
+    use crate::story_anchors::test_support::{commit, note};
+
+    #[test]
+    fn only_added_or_changed_notes_count_as_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first");
+        let second = commit(&repo, "second");
+        let third = commit(&repo, "third");
+
+        note(&repo, SESSIONS_REF_CANONICAL, first, "s1");
+        let imported = note(&repo, SESSIONS_REF_CANONICAL, second, "s2");
+        assert_eq!(
+            changed_note_commits(&repo, None, imported).unwrap().len(),
+            2
+        );
+        assert!(changed_note_commits(&repo, Some(imported), imported)
+            .unwrap()
+            .is_empty());
+
+        // Another install annotated a new commit and updated an old note
+        note(&repo, SESSIONS_REF_CANONICAL, second, "s2\ns3");
+        let fetched = note(&repo, SESSIONS_REF_CANONICAL, third, "s4");
+        let mut expected = vec![second.to_string(), third.to_string()];
+        expected.sort();
+        assert_eq!(
+            changed_note_commits(&repo, Some(imported), fetched).unwrap(),
+            expected
+        );
+    }
+}
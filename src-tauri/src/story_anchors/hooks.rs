@@ -1,6 +1,12 @@
 //! Per-repo git hook installer (hooks-first integration).
+//!
+//! `doctor_repo_hooks` checks installed hooks for the ways they break
+//! silently (not executable, stale templates, a missing narrative-cli).
 
+use crate::attribution::prefs::fetch_or_create_prefs;
 use crate::attribution::utils::fetch_repo_root;
+use git2::Repository;
+use serde::Serialize;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -22,10 +28,10 @@ fn shell_quote_sh(value: &str) -> String {
 }
 
 fn resolve_hooks_dir(repo_root: &str) -> PathBuf {
-    // Respect core.hooksPath if configured. Git allows this to be absolute or relative to repo root.
-    // If unset, default to .git/hooks.
+    // Respect core.hooksPath if configured (e.g. husky). Git allows this to be absolute, relative
+    // to repo root, or `~`-prefixed (expanded by --path). If unset, default to .git/hooks.
     let configured = Command::new("git")
-        .args(["config", "--get", "core.hooksPath"])
+        .args(["config", "--get", "--path", "core.hooksPath"])
         .current_dir(repo_root)
         .output()
         .ok()
@@ -45,7 +51,39 @@ fn resolve_hooks_dir(repo_root: &str) -> PathBuf {
         return Path::new(repo_root).join(p);
     }
 
-    Path::new(repo_root).join(".git").join("hooks")
+    // In a linked worktree `.git` is a file; hooks live in the common dir.
+    checkout_git_dirs(repo_root).common_dir.join("hooks")
+}
+
+/// Marker in a checkout's own git dir that turns the shared Narrative hooks
+/// off for that checkout only
+pub const WORKTREE_DISABLED_MARKER: &str = "narrative-hooks-disabled";
+
+/// Git dirs of the checkout at `repo_root`
+struct CheckoutGitDirs {
+    /// The checkout's own git dir (`.git/worktrees/<name>` for a linked worktree)
+    git_dir: PathBuf,
+    /// Git dir shared by all of the repo's worktrees
+    common_dir: PathBuf,
+    linked_worktree: bool,
+}
+
+fn checkout_git_dirs(repo_root: &str) -> CheckoutGitDirs {
+    match Repository::open(repo_root) {
+        Ok(repo) => CheckoutGitDirs {
+            git_dir: repo.path().to_path_buf(),
+            common_dir: repo.commondir().to_path_buf(),
+            linked_worktree: repo.is_worktree(),
+        },
+        Err(_) => {
+            let git_dir = Path::new(repo_root).join(".git");
+            CheckoutGitDirs {
+                common_dir: git_dir.clone(),
+                git_dir,
+                linked_worktree: false,
+            }
+        }
+    }
 }
 
 pub fn ensure_executable(path: &Path) -> Result<(), String> {
@@ -65,6 +103,60 @@ fn write_hook_file(path: &Path, content: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// Whether a hook file was written by Narrative (so it is safe to replace
+/// or remove)
+fn is_narrative_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains("NARRATIVE_CLI_PATH"))
+        .unwrap_or(false)
+}
+
+/// Suffix a pre-existing hook is moved to; Narrative's hook runs it first
+/// with the same arguments and stdin, and uninstall moves it back.
+pub const ORIGINAL_HOOK_SUFFIX: &str = ".narrative-orig";
+
+fn original_hook_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(ORIGINAL_HOOK_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Write a Narrative hook, moving any hook it would replace aside so the
+/// new one can chain to it
+fn install_hook(dir: &Path, name: &str, content: &str) -> Result<(), String> {
+    let path = dir.join(name);
+    if path.exists() && !is_narrative_hook(&path) {
+        let original = original_hook_path(&path);
+        if original.exists() {
+            return Err(format!(
+                "Cannot install the {} hook: both {} and {} exist",
+                name,
+                path.display(),
+                original.display()
+            ));
+        }
+        fs::rename(&path, &original).map_err(|e| e.to_string())?;
+    }
+    write_hook_file(&path, content)
+}
+
+/// Remove a Narrative hook and restore the hook it replaced, if any.
+/// Hooks Narrative did not write are left alone.
+fn remove_hook(dir: &Path, name: &str) -> Result<(), String> {
+    let path = dir.join(name);
+    if path.exists() {
+        if !is_narrative_hook(&path) {
+            return Ok(());
+        }
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    let original = original_hook_path(&path);
+    if original.exists() {
+        fs::rename(&original, &path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 pub fn build_post_commit_hook(db_path: &str, cli_path: &str) -> String {
     let db_path = shell_quote_sh(db_path);
     let cli_path = shell_quote_sh(cli_path);
@@ -72,19 +164,26 @@ pub fn build_post_commit_hook(db_path: &str, cli_path: &str) -> String {
         r#"#!/bin/sh
 set +e
 
+orig_status=0
+if [ -x "$0{ORIGINAL_HOOK_SUFFIX}" ]; then
+  "$0{ORIGINAL_HOOK_SUFFIX}" "$@"
+  orig_status=$?
+fi
+
 if [ -n "$NARRATIVE_HOOK_RUNNING" ]; then
-  exit 0
+  exit "$orig_status"
 fi
 export NARRATIVE_HOOK_RUNNING=1
 export NARRATIVE_DB_PATH={db_path}
 export NARRATIVE_CLI_PATH={cli_path}
 
-repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit 0
+repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit "$orig_status"
+[ -f "$(git rev-parse --git-dir 2>/dev/null)/{WORKTREE_DISABLED_MARKER}" ] && exit "$orig_status"
 mkdir -p "$repo_root/.narrative/meta" 2>/dev/null
 log="$repo_root/.narrative/meta/hooks.log"
 
 perl -e 'alarm shift; exec @ARGV' 5 "$NARRATIVE_CLI_PATH" hook post-commit --repo "$repo_root" 2>>"$log" || true
-exit 0
+exit "$orig_status"
 "#
     )
 }
@@ -96,19 +195,26 @@ pub fn build_post_merge_hook(db_path: &str, cli_path: &str) -> String {
         r#"#!/bin/sh
 set +e
 
+orig_status=0
+if [ -x "$0{ORIGINAL_HOOK_SUFFIX}" ]; then
+  "$0{ORIGINAL_HOOK_SUFFIX}" "$@"
+  orig_status=$?
+fi
+
 if [ -n "$NARRATIVE_HOOK_RUNNING" ]; then
-  exit 0
+  exit "$orig_status"
 fi
 export NARRATIVE_HOOK_RUNNING=1
 export NARRATIVE_DB_PATH={db_path}
 export NARRATIVE_CLI_PATH={cli_path}
 
-repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit 0
+repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit "$orig_status"
+[ -f "$(git rev-parse --git-dir 2>/dev/null)/{WORKTREE_DISABLED_MARKER}" ] && exit "$orig_status"
 mkdir -p "$repo_root/.narrative/meta" 2>/dev/null
 log="$repo_root/.narrative/meta/hooks.log"
 
 perl -e 'alarm shift; exec @ARGV' 5 "$NARRATIVE_CLI_PATH" hook post-merge --repo "$repo_root" 2>>"$log" || true
-exit 0
+exit "$orig_status"
 "#
     )
 }
@@ -120,6 +226,49 @@ pub fn build_post_rewrite_hook(db_path: &str, cli_path: &str) -> String {
         r#"#!/bin/sh
 set +e
 
+tmp="$(mktemp "${{TMPDIR:-/tmp}}/narrative-post-rewrite.XXXXXX")" || exit 0
+trap 'rm -f "$tmp" 2>/dev/null || true' EXIT HUP INT TERM
+cat > "$tmp"
+
+orig_status=0
+if [ -x "$0{ORIGINAL_HOOK_SUFFIX}" ]; then
+  "$0{ORIGINAL_HOOK_SUFFIX}" "$@" < "$tmp"
+  orig_status=$?
+fi
+
+if [ -n "$NARRATIVE_HOOK_RUNNING" ]; then
+  exit "$orig_status"
+fi
+export NARRATIVE_HOOK_RUNNING=1
+export NARRATIVE_DB_PATH={db_path}
+export NARRATIVE_CLI_PATH={cli_path}
+
+repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit "$orig_status"
+[ -f "$(git rev-parse --git-dir 2>/dev/null)/{WORKTREE_DISABLED_MARKER}" ] && exit "$orig_status"
+mkdir -p "$repo_root/.narrative/meta" 2>/dev/null
+log="$repo_root/.narrative/meta/hooks.log"
+
+cmd="$1"
+perl -e 'alarm shift; exec @ARGV' 8 "$NARRATIVE_CLI_PATH" hook post-rewrite --repo "$repo_root" --command "$cmd" --rewritten "$tmp" 2>>"$log" || true
+exit "$orig_status"
+"#
+    )
+}
+
+/// Appends Narrative trailers for the sessions behind the staged changes.
+/// Merge and squash messages are left alone.
+pub fn build_prepare_commit_msg_hook(db_path: &str, cli_path: &str) -> String {
+    let db_path = shell_quote_sh(db_path);
+    let cli_path = shell_quote_sh(cli_path);
+    format!(
+        r#"#!/bin/sh
+set +e
+
+# A failing original hook aborts the commit, as it would on its own
+if [ -x "$0{ORIGINAL_HOOK_SUFFIX}" ]; then
+  "$0{ORIGINAL_HOOK_SUFFIX}" "$@" || exit $?
+fi
+
 if [ -n "$NARRATIVE_HOOK_RUNNING" ]; then
   exit 0
 fi
@@ -128,51 +277,96 @@ export NARRATIVE_DB_PATH={db_path}
 export NARRATIVE_CLI_PATH={cli_path}
 
 repo_root="$(git rev-parse --show-toplevel 2>/dev/null)" || exit 0
+[ -f "$(git rev-parse --git-dir 2>/dev/null)/{WORKTREE_DISABLED_MARKER}" ] && exit 0
 mkdir -p "$repo_root/.narrative/meta" 2>/dev/null
 log="$repo_root/.narrative/meta/hooks.log"
 
-cmd="$1"
-tmp="$(mktemp "${{TMPDIR:-/tmp}}/narrative-post-rewrite.XXXXXX")" || exit 0
-trap 'rm -f "$tmp" 2>/dev/null || true' EXIT HUP INT TERM
-cat > "$tmp"
+msg_file="$1"
+source="$2"
+case "$source" in
+  merge|squash) exit 0 ;;
+esac
+case "$msg_file" in
+  /*) ;;
+  *) msg_file="$PWD/$msg_file" ;;
+esac
 
-perl -e 'alarm shift; exec @ARGV' 8 "$NARRATIVE_CLI_PATH" hook post-rewrite --repo "$repo_root" --command "$cmd" --rewritten "$tmp" 2>>"$log" || true
+perl -e 'alarm shift; exec @ARGV' 5 "$NARRATIVE_CLI_PATH" hook prepare-commit-msg --repo "$repo_root" --message-file "$msg_file" --source "$source" 2>>"$log" || true
 exit 0
 "#
     )
 }
 
+/// Optional hook written when the repo's `commit_trailers` preference is on
+pub const PREPARE_COMMIT_MSG_HOOK: &str = "prepare-commit-msg";
+
 pub async fn install_repo_hooks(
     repo_root: &str,
     db_path: &str,
     cli_path: &str,
+    commit_trailers: bool,
 ) -> Result<(), String> {
     let dir = resolve_hooks_dir(repo_root);
     fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
 
-    write_hook_file(
-        &dir.join("post-commit"),
+    install_hook(
+        &dir,
+        "post-commit",
         &build_post_commit_hook(db_path, cli_path),
     )?;
-    write_hook_file(
-        &dir.join("post-rewrite"),
+    install_hook(
+        &dir,
+        "post-rewrite",
         &build_post_rewrite_hook(db_path, cli_path),
     )?;
-    write_hook_file(
-        &dir.join("post-merge"),
+    install_hook(
+        &dir,
+        "post-merge",
         &build_post_merge_hook(db_path, cli_path),
     )?;
 
+    if commit_trailers {
+        install_hook(
+            &dir,
+            PREPARE_COMMIT_MSG_HOOK,
+            &build_prepare_commit_msg_hook(db_path, cli_path),
+        )?;
+    } else {
+        remove_hook(&dir, PREPARE_COMMIT_MSG_HOOK)?;
+    }
+
+    // Hooks are shared by all worktrees; re-enable them for this one
+    let marker = checkout_git_dirs(repo_root)
+        .git_dir
+        .join(WORKTREE_DISABLED_MARKER);
+    if marker.exists() {
+        fs::remove_file(&marker).map_err(|e| e.to_string())?;
+    }
+
     Ok(())
 }
 
+/// Hooks written by `install_repo_hooks`
+pub const NARRATIVE_HOOKS: [&str; 3] = ["post-commit", "post-rewrite", "post-merge"];
+
+/// Remove Narrative's hooks. In a linked worktree they are only turned off
+/// for that worktree, since the hooks dir is shared with the others.
 pub async fn uninstall_repo_hooks(repo_root: &str) -> Result<(), String> {
+    let git_dirs = checkout_git_dirs(repo_root);
+    if git_dirs.linked_worktree {
+        return fs::write(
+            git_dirs.git_dir.join(WORKTREE_DISABLED_MARKER),
+            "Narrative hooks are disabled for this worktree.\n",
+        )
+        .map_err(|e| e.to_string());
+    }
+
     let dir = resolve_hooks_dir(repo_root);
-    for name in ["post-commit", "post-rewrite", "post-merge"] {
-        let path = dir.join(name);
-        if path.exists() {
-            let _ = fs::remove_file(&path);
-        }
+    for name in NARRATIVE_HOOKS
+        .into_iter()
+        .chain(std::iter::once(PREPARE_COMMIT_MSG_HOOK))
+    {
+        remove_hook(&dir, name)?;
     }
     Ok(())
 }
@@ -184,7 +378,8 @@ pub async fn install_repo_hooks_by_id(
     cli_path: &str,
 ) -> Result<(), String> {
     let repo_root = fetch_repo_root(db, repo_id).await?;
-    install_repo_hooks(&repo_root, db_path, cli_path).await
+    let prefs = fetch_or_create_prefs(db, repo_id).await?;
+    install_repo_hooks(&repo_root, db_path, cli_path, prefs.commit_trailers).await
 }
 
 pub async fn uninstall_repo_hooks_by_id(db: &sqlx::SqlitePool, repo_id: i64) -> Result<(), String> {
@@ -195,6 +390,12 @@ pub async fn uninstall_repo_hooks_by_id(db: &sqlx::SqlitePool, repo_id: i64) ->
 pub struct RepoHooksStatus {
     pub hooks_dir: PathBuf,
     pub installed: bool,
+    /// Narrative hooks not present, e.g. `post-rewrite` on installs that
+    /// predate automatic rewrite reconciliation, or `prepare-commit-msg`
+    /// after commit trailers were turned on
+    pub missing_hooks: Vec<String>,
+    /// The repo is a linked worktree sharing its hooks with the main checkout
+    pub linked_worktree: bool,
 }
 
 pub async fn get_repo_hooks_status(
@@ -203,16 +404,207 @@ pub async fn get_repo_hooks_status(
 ) -> Result<RepoHooksStatus, String> {
     let repo_root = fetch_repo_root(db, repo_id).await?;
     let dir = resolve_hooks_dir(&repo_root);
-    let installed = dir.join("post-commit").exists();
+    let git_dirs = checkout_git_dirs(&repo_root);
+    let installed = is_narrative_hook(&dir.join("post-commit"))
+        && !git_dirs.git_dir.join(WORKTREE_DISABLED_MARKER).exists();
+    let mut missing_hooks: Vec<String> = NARRATIVE_HOOKS
+        .iter()
+        .filter(|name| !is_narrative_hook(&dir.join(name)))
+        .map(|name| name.to_string())
+        .collect();
+    let commit_trailers = fetch_or_create_prefs(db, repo_id).await?.commit_trailers;
+    if installed && commit_trailers && !is_narrative_hook(&dir.join(PREPARE_COMMIT_MSG_HOOK)) {
+        missing_hooks.push(PREPARE_COMMIT_MSG_HOOK.to_string());
+    }
     Ok(RepoHooksStatus {
         hooks_dir: dir,
         installed,
+        missing_hooks,
+        linked_worktree: git_dirs.linked_worktree,
+    })
+}
+
+/// Builder for a Narrative hook by name
+fn hook_builder(name: &str) -> Option<fn(&str, &str) -> String> {
+    match name {
+        "post-commit" => Some(build_post_commit_hook),
+        "post-rewrite" => Some(build_post_rewrite_hook),
+        "post-merge" => Some(build_post_merge_hook),
+        PREPARE_COMMIT_MSG_HOOK => Some(build_prepare_commit_msg_hook),
+        _ => None,
+    }
+}
+
+/// Value of an `export NAME='...'` line in a hook script
+fn hook_export(content: &str, name: &str) -> Option<String> {
+    let prefix = format!("export {}=", name);
+    let quoted = content
+        .lines()
+        .find_map(|line| line.strip_prefix(prefix.as_str()))?;
+    let inner = quoted.strip_prefix('\'')?.strip_suffix('\'')?;
+    Some(inner.replace("'\"'\"'", "'"))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Something wrong with an installed hook that would make it fail (usually
+/// silently) at commit time
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookProblem {
+    pub hook: String,
+    /// "missing" | "not_executable" | "stale" | "cli_missing" |
+    /// "cli_not_executable" | "db_path_mismatch"
+    pub kind: String,
+    pub detail: String,
+}
+
+/// Check each of `hooks` in `dir`: present, executable, written by the
+/// current templates, and pointing at an executable narrative-cli (and at
+/// `expected_db_path`, when given)
+pub fn diagnose_hooks(
+    dir: &Path,
+    hooks: &[&str],
+    expected_db_path: Option<&str>,
+) -> Vec<HookProblem> {
+    let mut problems = Vec::new();
+    let mut problem = |hook: &str, kind: &str, detail: String| {
+        problems.push(HookProblem {
+            hook: hook.to_string(),
+            kind: kind.to_string(),
+            detail,
+        })
+    };
+
+    for &name in hooks {
+        let path = dir.join(name);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) if content.contains("NARRATIVE_CLI_PATH") => content,
+            _ => {
+                problem(
+                    name,
+                    "missing",
+                    format!("{} is not installed", path.display()),
+                );
+                continue;
+            }
+        };
+
+        if !is_executable(&path) {
+            problem(
+                name,
+                "not_executable",
+                format!("{} is not executable; git skips it", path.display()),
+            );
+        }
+
+        let cli_path = hook_export(&content, "NARRATIVE_CLI_PATH");
+        let db_path = hook_export(&content, "NARRATIVE_DB_PATH");
+        match cli_path.as_deref() {
+            None => problem(
+                name,
+                "cli_missing",
+                "No narrative-cli path in the hook".to_string(),
+            ),
+            Some(cli) if !Path::new(cli).is_file() => {
+                problem(name, "cli_missing", format!("{} does not exist", cli))
+            }
+            Some(cli) if !is_executable(Path::new(cli)) => problem(
+                name,
+                "cli_not_executable",
+                format!("{} is not executable", cli),
+            ),
+            Some(_) => {}
+        }
+
+        if let (Some(expected), Some(actual)) = (expected_db_path, db_path.as_deref()) {
+            if expected != actual {
+                problem(
+                    name,
+                    "db_path_mismatch",
+                    format!("Hook writes to {} instead of {}", actual, expected),
+                );
+            }
+        }
+
+        // Written by an older Narrative if today's template differs
+        let current = hook_builder(name)
+            .zip(db_path.as_deref().zip(cli_path.as_deref()))
+            .map(|(build, (db, cli))| build(db, cli));
+        if current.is_some_and(|current| current != content) {
+            problem(
+                name,
+                "stale",
+                "Written by an older version of Narrative".to_string(),
+            );
+        }
+    }
+    problems
+}
+
+/// Result of `doctor_repo_hooks`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoHooksDoctorReport {
+    pub hooks_dir: String,
+    pub healthy: bool,
+    pub problems: Vec<HookProblem>,
+    /// Hooks were reinstalled before this report was taken
+    pub repaired: bool,
+}
+
+/// Diagnose a repo's Narrative hooks. Hooks turned off for a linked
+/// worktree are reported healthy; `prepare-commit-msg` is only checked
+/// while commit trailers are on.
+pub async fn doctor_repo_hooks(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    expected_db_path: Option<&str>,
+) -> Result<RepoHooksDoctorReport, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let dir = resolve_hooks_dir(&repo_root);
+    let disabled = checkout_git_dirs(&repo_root)
+        .git_dir
+        .join(WORKTREE_DISABLED_MARKER)
+        .exists();
+
+    let mut hooks: Vec<&str> = NARRATIVE_HOOKS.to_vec();
+    if fetch_or_create_prefs(db, repo_id).await?.commit_trailers {
+        hooks.push(PREPARE_COMMIT_MSG_HOOK);
+    }
+    let problems = if disabled {
+        Vec::new()
+    } else {
+        diagnose_hooks(&dir, &hooks, expected_db_path)
+    };
+
+    Ok(RepoHooksDoctorReport {
+        hooks_dir: dir.to_string_lossy().to_string(),
+        healthy: problems.is_empty(),
+        problems,
+        repaired: false,
     })
 }
 
 #[cfg(test)]
 mod tests {
-    use super::build_post_rewrite_hook;
+    use super::{
+        build_post_commit_hook, build_post_rewrite_hook, build_prepare_commit_msg_hook,
+        checkout_git_dirs, diagnose_hooks, install_hook, install_repo_hooks, remove_hook,
+        resolve_hooks_dir, uninstall_repo_hooks, ORIGINAL_HOOK_SUFFIX, WORKTREE_DISABLED_MARKER,
+    };
+    use std::fs;
 
     #[test]
     fn post_rewrite_hook_uses_mktemp_and_trap_cleanup() {
@@ -224,4 +616,191 @@ mod tests {
         // Must not use a PID-predictable filename.
         assert!(!hook.contains("narrative-post-rewrite-$$.txt"));
     }
+
+    #[test]
+    fn prepare_commit_msg_hook_skips_merges_and_passes_message_file() {
+        let hook = build_prepare_commit_msg_hook("/tmp/db.sqlite", "/usr/local/bin/narrative-cli");
+        assert!(hook.contains("merge|squash) exit 0 ;;"));
+        assert!(hook.contains(
+            r#"hook prepare-commit-msg --repo "$repo_root" --message-file "$msg_file" --source "$source""#
+        ));
+    }
+
+    #[test]
+    fn install_chains_existing_hooks_and_uninstall_restores_them() {
+        let dir = tempfile::tempdir().unwrap();
+        let hook_path = dir.path().join("post-commit");
+        let original_path = dir
+            .path()
+            .join(format!("post-commit{ORIGINAL_HOOK_SUFFIX}"));
+        let user_hook = "#!/bin/sh\necho ran > \"$(dirname \"$0\")/ran.txt\"\nexit 3\n";
+        fs::write(&hook_path, user_hook).unwrap();
+        super::ensure_executable(&hook_path).unwrap();
+
+        let ours = build_post_commit_hook("/tmp/db.sqlite", "/usr/local/bin/narrative-cli");
+        install_hook(dir.path(), "post-commit", &ours).unwrap();
+        assert_eq!(fs::read_to_string(&original_path).unwrap(), user_hook);
+        assert_eq!(fs::read_to_string(&hook_path).unwrap(), ours);
+
+        // Reinstalling keeps the original
+        install_hook(dir.path(), "post-commit", &ours).unwrap();
+        assert_eq!(fs::read_to_string(&original_path).unwrap(), user_hook);
+
+        // The original runs first and its exit status is kept
+        #[cfg(unix)]
+        {
+            let status = std::process::Command::new("sh")
+                .arg(&hook_path)
+                .current_dir(dir.path())
+                .env_remove("NARRATIVE_HOOK_RUNNING")
+                .status()
+                .unwrap();
+            assert_eq!(status.code(), Some(3));
+            assert!(dir.path().join("ran.txt").exists());
+        }
+
+        remove_hook(dir.path(), "post-commit").unwrap();
+        assert_eq!(fs::read_to_string(&hook_path).unwrap(), user_hook);
+        assert!(!original_path.exists());
+
+        // Hooks Narrative did not write are never removed
+        remove_hook(dir.path(), "post-commit").unwrap();
+        assert!(hook_path.exists());
+    }
+
+    #[test]
+    fn linked_worktrees_share_hooks_and_disable_them_individually() {
+        let dir = tempfile::tempdir().unwrap();
+        let main_root = dir.path().join("main");
+        let repo = git2::Repository::init(&main_root).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+        let wt_root = dir.path().join("wt");
+        repo.worktree("wt", &wt_root, None).unwrap();
+        let main_str = main_root.to_string_lossy().to_string();
+        let wt_str = wt_root.to_string_lossy().to_string();
+
+        let wt_dirs = checkout_git_dirs(&wt_str);
+        assert!(wt_dirs.linked_worktree);
+        assert_eq!(
+            wt_dirs.common_dir.canonicalize().unwrap(),
+            main_root.join(".git").canonicalize().unwrap()
+        );
+        let hooks_dir = resolve_hooks_dir(&wt_str);
+        assert_eq!(
+            hooks_dir.parent().unwrap().canonicalize().unwrap(),
+            main_root.join(".git").canonicalize().unwrap()
+        );
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        rt.block_on(async {
+            install_repo_hooks(
+                &wt_str,
+                "/tmp/db.sqlite",
+                "/usr/local/bin/narrative-cli",
+                false,
+            )
+            .await
+            .unwrap();
+            assert!(hooks_dir.join("post-commit").exists());
+
+            // Uninstalling from the worktree only disables it there
+            uninstall_repo_hooks(&wt_str).await.unwrap();
+            assert!(hooks_dir.join("post-commit").exists());
+            assert!(wt_dirs.git_dir.join(WORKTREE_DISABLED_MARKER).exists());
+
+            install_repo_hooks(
+                &wt_str,
+                "/tmp/db.sqlite",
+                "/usr/local/bin/narrative-cli",
+                false,
+            )
+            .await
+            .unwrap();
+            assert!(!wt_dirs.git_dir.join(WORKTREE_DISABLED_MARKER).exists());
+
+            uninstall_repo_hooks(&main_str).await.unwrap();
+            assert!(!hooks_dir.join("post-commit").exists());
+        });
+    }
+
+    #[test]
+    fn doctor_flags_hooks_that_would_fail_silently() {
+        let dir = tempfile::tempdir().unwrap();
+        let cli = dir.path().join("narrative-cli");
+        fs::write(&cli, "#!/bin/sh\n").unwrap();
+        super::ensure_executable(&cli).unwrap();
+        let cli = cli.to_string_lossy().to_string();
+        let hooks = dir.path().join("hooks");
+        fs::create_dir_all(&hooks).unwrap();
+
+        install_hook(
+            &hooks,
+            "post-commit",
+            &build_post_commit_hook("/tmp/db.sqlite", &cli),
+        )
+        .unwrap();
+        install_hook(
+            &hooks,
+            "post-rewrite",
+            &build_post_rewrite_hook("/tmp/db.sqlite", &cli),
+        )
+        .unwrap();
+        let names = ["post-commit", "post-rewrite", "post-merge"];
+        let kinds = |expected_db: Option<&str>| -> Vec<(String, String)> {
+            diagnose_hooks(&hooks, &names, expected_db)
+                .into_iter()
+                .map(|p| (p.hook, p.kind))
+                .collect()
+        };
+        let pair = |hook: &str, kind: &str| (hook.to_string(), kind.to_string());
+
+        assert_eq!(
+            kinds(Some("/tmp/db.sqlite")),
+            vec![pair("post-merge", "missing")]
+        );
+        assert_eq!(
+            kinds(Some("/other/db.sqlite")),
+            vec![
+                pair("post-commit", "db_path_mismatch"),
+                pair("post-rewrite", "db_path_mismatch"),
+                pair("post-merge", "missing"),
+            ]
+        );
+
+        // An older template, and a narrative-cli that has gone away
+        let stale = build_post_commit_hook("/tmp/db.sqlite", &cli).replace("set +e\n", "");
+        fs::write(hooks.join("post-commit"), stale).unwrap();
+        fs::write(
+            hooks.join("post-rewrite"),
+            build_post_rewrite_hook("/tmp/db.sqlite", "/no/such/narrative-cli"),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = hooks.join("post-commit");
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+        }
+        let mut found = kinds(None);
+        found.retain(|(hook, _)| hook != "post-merge");
+        #[cfg(unix)]
+        {
+            assert!(found.contains(&pair("post-commit", "not_executable")));
+            found.retain(|(_, kind)| kind != "not_executable");
+        }
+        assert_eq!(
+            found,
+            vec![
+                pair("post-commit", "stale"),
+                pair("post-rewrite", "cli_missing")
+            ]
+        );
+    }
 }
@@ -2,12 +2,18 @@
 //!
 //! This is intentionally lightweight: we store lineage events in SQLite for observability,
 //! and optionally attach a Git Note under refs/notes/narrative/lineage to HEAD after rewrites/merges.
+//!
+//! `get_commit_lineage` walks a commit's rewrite history both ways: recorded
+//! rewrites (post-rewrite hook) and commits sharing its patch-id rewrite key,
+//! ordered by when their key was first stored. Each hop lists the anchors the
+//! newer commit carries over.
 
 use crate::attribution::utils::fetch_repo_root;
 use crate::story_anchors::notes_format::{compute_note_hash, NOTE_DIVIDER};
-use crate::story_anchors::refs::{LINEAGE_REF_CANONICAL, LINEAGE_SCHEMA_VERSION};
+use crate::story_anchors::refs::{fetch_notes_refs, LINEAGE_SCHEMA_VERSION};
 use git2::{Oid, Repository, Signature};
 use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
 
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -50,6 +56,7 @@ pub async fn write_lineage_note_for_head(
     payload: &LineageEventPayload,
 ) -> Result<(), String> {
     let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let oid = Oid::from_str(head_sha).map_err(|e| e.to_string())?;
 
@@ -76,7 +83,7 @@ pub async fn write_lineage_note_for_head(
     repo.note(
         &signature,
         &signature,
-        Some(LINEAGE_REF_CANONICAL),
+        Some(&refs.lineage),
         oid,
         &message,
         true,
@@ -96,7 +103,7 @@ pub async fn write_lineage_note_for_head(
     )
     .bind(repo_id)
     .bind(head_sha)
-    .bind(LINEAGE_REF_CANONICAL)
+    .bind(&refs.lineage)
     .bind(note_hash)
     .bind(LINEAGE_SCHEMA_VERSION)
     .execute(db)
@@ -104,3 +111,273 @@ pub async fn write_lineage_note_for_head(
 
     Ok(())
 }
+
+/// Hops followed in each direction before a lineage walk stops
+const MAX_LINEAGE_DEPTH: u32 = 25;
+
+/// One rewrite step, always oriented older -> newer
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineageHop {
+    pub from_sha: String,
+    pub to_sha: String,
+    /// "rewrite" (recorded by the post-rewrite hook) or "patch_id"
+    pub via: String,
+    /// git command behind a recorded rewrite ("rebase", "amend")
+    pub command: Option<String>,
+    /// Hops away from the queried commit (1 = direct)
+    pub depth: u32,
+    /// Sessions linked to both commits
+    pub carried_sessions: Vec<String>,
+    /// Note kinds ("sessions", "attribution", "lineage") recorded on both commits
+    pub carried_notes: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitLineage {
+    pub commit_sha: String,
+    pub rewrite_key: Option<String>,
+    /// Commits this one was rewritten from, nearest first
+    pub ancestors: Vec<LineageHop>,
+    /// Commits this one was rewritten into, nearest first
+    pub descendants: Vec<LineageHop>,
+}
+
+/// Direct rewrite neighbours of a commit: (sha, via, command)
+async fn rewrite_neighbours(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    forward: bool,
+) -> Result<Vec<(String, String, Option<String>)>, String> {
+    let recorded_sql = if forward {
+        "SELECT new_sha, command FROM commit_rewrites WHERE repo_id = ? AND old_sha = ? ORDER BY created_at"
+    } else {
+        "SELECT old_sha, command FROM commit_rewrites WHERE repo_id = ? AND new_sha = ? ORDER BY created_at"
+    };
+    let recorded: Vec<(String, Option<String>)> = sqlx::query_as(recorded_sql)
+        .bind(repo_id)
+        .bind(commit_sha)
+        .fetch_all(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    // Nearest patch-equivalent commit by key insertion order
+    let patch_sql = if forward {
+        r#"
+        SELECT b.commit_sha
+        FROM commit_rewrite_keys a
+        JOIN commit_rewrite_keys b
+          ON b.repo_id = a.repo_id AND b.rewrite_key = a.rewrite_key AND b.id > a.id
+        WHERE a.repo_id = ? AND a.commit_sha = ?
+        ORDER BY b.id ASC
+        LIMIT 1
+        "#
+    } else {
+        r#"
+        SELECT b.commit_sha
+        FROM commit_rewrite_keys a
+        JOIN commit_rewrite_keys b
+          ON b.repo_id = a.repo_id AND b.rewrite_key = a.rewrite_key AND b.id < a.id
+        WHERE a.repo_id = ? AND a.commit_sha = ?
+        ORDER BY b.id DESC
+        LIMIT 1
+        "#
+    };
+    let patch_peer: Option<String> = sqlx::query_scalar(patch_sql)
+        .bind(repo_id)
+        .bind(commit_sha)
+        .fetch_optional(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut out: Vec<(String, String, Option<String>)> = recorded
+        .into_iter()
+        .map(|(sha, command)| (sha, "rewrite".to_string(), command))
+        .collect();
+    if let Some(sha) = patch_peer {
+        if !out.iter().any(|(seen, ..)| seen == &sha) {
+            out.push((sha, "patch_id".to_string(), None));
+        }
+    }
+    Ok(out)
+}
+
+async fn linked_sessions(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<HashSet<String>, String> {
+    let sessions: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT session_id FROM commit_session_links WHERE repo_id = ? AND commit_sha = ?
+        UNION
+        SELECT session_id FROM session_links WHERE repo_id = ? AND commit_sha = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(sessions.into_iter().collect())
+}
+
+async fn note_kinds(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<HashSet<String>, String> {
+    let kinds: Vec<String> = sqlx::query_scalar(
+        "SELECT DISTINCT note_kind FROM story_anchor_note_meta WHERE repo_id = ? AND commit_sha = ?",
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(kinds.into_iter().collect())
+}
+
+/// Breadth-first walk of rewrites in one direction
+async fn walk_lineage(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+    forward: bool,
+) -> Result<Vec<LineageHop>, String> {
+    let mut seen: HashSet<String> = HashSet::from([commit_sha.to_string()]);
+    let mut queue: VecDeque<(String, u32)> = VecDeque::from([(commit_sha.to_string(), 0)]);
+    let mut hops = Vec::new();
+
+    while let Some((current, depth)) = queue.pop_front() {
+        if depth >= MAX_LINEAGE_DEPTH {
+            continue;
+        }
+        for (next, via, command) in rewrite_neighbours(db, repo_id, &current, forward).await? {
+            if !seen.insert(next.clone()) {
+                continue;
+            }
+            let (from_sha, to_sha) = if forward {
+                (current.clone(), next.clone())
+            } else {
+                (next.clone(), current.clone())
+            };
+
+            let to_sessions = linked_sessions(db, repo_id, &to_sha).await?;
+            let mut carried_sessions: Vec<String> = linked_sessions(db, repo_id, &from_sha)
+                .await?
+                .intersection(&to_sessions)
+                .cloned()
+                .collect();
+            carried_sessions.sort();
+            let to_notes = note_kinds(db, repo_id, &to_sha).await?;
+            let mut carried_notes: Vec<String> = note_kinds(db, repo_id, &from_sha)
+                .await?
+                .intersection(&to_notes)
+                .cloned()
+                .collect();
+            carried_notes.sort();
+
+            hops.push(LineageHop {
+                from_sha,
+                to_sha,
+                via,
+                command,
+                depth: depth + 1,
+                carried_sessions,
+                carried_notes,
+            });
+            queue.push_back((next, depth + 1));
+        }
+    }
+    Ok(hops)
+}
+
+/// Rewrite ancestors and descendants of a commit, with the anchors carried
+/// across each hop
+pub async fn get_commit_lineage(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_sha: &str,
+) -> Result<CommitLineage, String> {
+    let rewrite_key: Option<String> = sqlx::query_scalar(
+        "SELECT rewrite_key FROM commit_rewrite_keys WHERE repo_id = ? AND commit_sha = ?",
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_optional(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(CommitLineage {
+        commit_sha: commit_sha.to_string(),
+        rewrite_key,
+        ancestors: walk_lineage(db, repo_id, commit_sha, false).await?,
+        descendants: walk_lineage(db, repo_id, commit_sha, true).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lineage_follows_recorded_rewrites_and_patch_ids() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+
+            // a -(amend)-> b, then b rebased to c (same patch-id, no hook record)
+            sqlx::query(
+                r#"
+                INSERT INTO repos (id, path) VALUES (1, '/tmp/repo');
+                INSERT INTO commit_rewrites (repo_id, old_sha, new_sha, command)
+                VALUES (1, 'a', 'b', 'amend');
+                INSERT INTO commit_rewrite_keys (repo_id, commit_sha, rewrite_key, algorithm)
+                VALUES (1, 'b', 'k1', 'patch-id'), (1, 'c', 'k1', 'patch-id');
+                INSERT INTO commit_session_links (repo_id, commit_sha, session_id)
+                VALUES (1, 'a', 's1'), (1, 'b', 's1'), (1, 'b', 's2'), (1, 'c', 's2');
+                INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash)
+                VALUES (1, 'b', 'sessions', 'refs/notes/narrative/sessions', 'h1'),
+                       (1, 'c', 'sessions', 'refs/notes/narrative/sessions', 'h2');
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .expect("seed");
+
+            let lineage = get_commit_lineage(&pool, 1, "b").await.unwrap();
+            assert_eq!(lineage.rewrite_key.as_deref(), Some("k1"));
+            assert_eq!(lineage.ancestors.len(), 1);
+            assert_eq!(lineage.ancestors[0].from_sha, "a");
+            assert_eq!(lineage.ancestors[0].via, "rewrite");
+            assert_eq!(lineage.ancestors[0].command.as_deref(), Some("amend"));
+            assert_eq!(lineage.ancestors[0].carried_sessions, vec!["s1"]);
+            assert!(lineage.ancestors[0].carried_notes.is_empty());
+
+            assert_eq!(lineage.descendants.len(), 1);
+            assert_eq!(lineage.descendants[0].to_sha, "c");
+            assert_eq!(lineage.descendants[0].via, "patch_id");
+            assert_eq!(lineage.descendants[0].carried_sessions, vec!["s2"]);
+            assert_eq!(lineage.descendants[0].carried_notes, vec!["sessions"]);
+
+            // From the newest commit the whole chain is ancestry
+            let lineage = get_commit_lineage(&pool, 1, "c").await.unwrap();
+            let chain: Vec<(&str, &str, u32)> = lineage
+                .ancestors
+                .iter()
+                .map(|hop| (hop.from_sha.as_str(), hop.to_sha.as_str(), hop.depth))
+                .collect();
+            assert_eq!(chain, vec![("b", "c", 1), ("a", "b", 2)]);
+            assert!(lineage.descendants.is_empty());
+        });
+    }
+}
@@ -3,15 +3,41 @@
 //! Narrative-native, Git Notes-backed "story anchors" that travel with commits.
 //! This module implements:
 //! - Session link notes: refs/notes/narrative/sessions
-//! - Hook installer (per-repo .git/hooks)
+//! - Hook installer (per-repo .git/hooks) with a doctor that repairs broken hooks
 //! - Migration helpers for legacy note refs
 //! - Rewrite reconciliation (patch-id based recovery)
+//! - Push/fetch of refs/notes/narrative/* with a remote
+//! - Import of notes fetched from other installs, optionally after each fetch
+//! - Bulk export/import of notes, including on bare (server-side) mirrors
+//! - Three-way merge of diverged sessions notes
+//! - Note schema versions and bulk upgrades
+//! - Opt-in Narrative trailers in commit messages (prepare-commit-msg)
+//! - Optional GPG/SSH signing of notes, verified on import
+//! - Garbage collection of notes on unreachable commits
+//! - Note status for long commit lists, cached per notes ref tip
+//! - Coverage of recent history by notes, overall and over time
+//! - Transparent zstd compression of large notes
+//! - Per-repo notes ref namespaces, with migration between them
+//! - Release anchors: sessions and attribution aggregated per tag
 
 pub mod commands;
+pub mod coverage;
+pub mod fetched_notes;
 pub mod hooks;
 pub mod lineage;
+pub mod notes_merge;
+pub mod notes_namespace;
+pub mod notes_schema;
+pub mod notes_signing;
+pub mod notes_export;
 pub mod notes_format;
+pub mod notes_gc;
 pub mod refs;
+pub mod release_anchors;
 pub mod sessions_notes;
 pub mod sessions_notes_io;
 pub mod status;
+pub mod sync;
+#[cfg(test)]
+mod test_support;
+pub mod trailers;
@@ -0,0 +1,222 @@
+//! Bulk export and import of sessions and attribution notes.
+//!
+//! `export_notes_for_range` walks `from_ref..to_ref` (the whole history of
+//! `to_ref` when `from_ref` is omitted) oldest first and exports both notes
+//! for each commit, reporting progress after every commit so an existing
+//! history can be anchored in one operation. `import_all_notes` imports
+//! every note the repo's notes refs hold.
+//!
+//! Neither needs a worktree, so both run against bare repositories, e.g. a
+//! server-side mirror that consolidates a team's anchors (`narrative-cli
+//! notes ...`).
+
+use crate::attribution::notes_io::{
+    export_attribution_note, import_attribution_notes_batch, AttributionNoteBatchSummary,
+};
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_merge::notes_entries;
+use crate::story_anchors::refs::fetch_notes_refs;
+use crate::story_anchors::sessions_notes_io::{
+    export_sessions_note, import_sessions_notes_batch, SessionsNoteBatchSummary,
+};
+use git2::{Repository, Sort};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::BTreeSet;
+
+/// Progress of a range export, emitted as `notes-export-progress` after each
+/// commit
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesExportProgress {
+    pub repo_id: i64,
+    pub commit_sha: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+    /// Sessions notes written (new or changed)
+    pub sessions_exported: usize,
+    /// Attribution notes written or merged
+    pub attribution_exported: usize,
+    /// Commits with nothing to export
+    pub empty: usize,
+    /// Commits whose export failed
+    pub failed: usize,
+    /// "running" | "completed"
+    pub status: String,
+}
+
+/// Commits in `from_ref..to_ref`, oldest first
+pub fn commits_in_range(
+    repo: &Repository,
+    from_ref: Option<&str>,
+    to_ref: &str,
+) -> Result<Vec<String>, String> {
+    let resolve = |spec: &str| {
+        repo.revparse_single(spec)
+            .and_then(|object| object.peel_to_commit())
+            .map(|commit| commit.id())
+            .map_err(|e| format!("Cannot resolve {}: {}", spec, e))
+    };
+
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(|e| e.to_string())?;
+    walk.push(resolve(to_ref)?).map_err(|e| e.to_string())?;
+    if let Some(from_ref) = from_ref {
+        walk.hide(resolve(from_ref)?).map_err(|e| e.to_string())?;
+    }
+
+    walk.map(|oid| oid.map(|oid| oid.to_string()).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// Export sessions and attribution notes for every commit in the range.
+///
+/// A failed commit is counted and skipped; the returned progress is the
+/// final ("completed") report.
+pub async fn export_notes_for_range(
+    db: &SqlitePool,
+    repo_id: i64,
+    from_ref: Option<&str>,
+    to_ref: &str,
+    mut on_progress: impl FnMut(NotesExportProgress),
+) -> Result<NotesExportProgress, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let commits = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        commits_in_range(&repo, from_ref, to_ref)?
+    };
+
+    let mut progress = NotesExportProgress {
+        repo_id,
+        commit_sha: None,
+        completed: 0,
+        total: commits.len(),
+        sessions_exported: 0,
+        attribution_exported: 0,
+        empty: 0,
+        failed: 0,
+        status: "running".to_string(),
+    };
+
+    for sha in commits {
+        let sessions = export_sessions_note(db, repo_id, &sha).await;
+        let attribution = export_attribution_note(db, repo_id, sha.clone()).await;
+        match (&sessions, &attribution) {
+            (Err(_), _) | (_, Err(_)) => progress.failed += 1,
+            (Ok(s), Ok(a)) if s.status == "empty" && a.status == "empty" => progress.empty += 1,
+            _ => {}
+        }
+        if sessions.is_ok_and(|s| s.status == "exported") {
+            progress.sessions_exported += 1;
+        }
+        if attribution.is_ok_and(|a| a.status == "exported" || a.status == "merged") {
+            progress.attribution_exported += 1;
+        }
+
+        progress.completed += 1;
+        progress.commit_sha = Some(sha);
+        on_progress(progress.clone());
+    }
+
+    progress.commit_sha = None;
+    progress.status = "completed".to_string();
+    Ok(progress)
+}
+
+/// Commits annotated in any of `note_refs` (missing refs are skipped), sorted
+pub fn annotated_commits(repo: &Repository, note_refs: &[&str]) -> Result<Vec<String>, String> {
+    let mut commits = BTreeSet::new();
+    for note_ref in note_refs {
+        if let Ok(tip) = repo.refname_to_id(note_ref) {
+            commits.extend(notes_entries(repo, tip)?.into_keys());
+        }
+    }
+    Ok(commits.into_iter().collect())
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesImportAllSummary {
+    pub sessions: SessionsNoteBatchSummary,
+    pub attribution: AttributionNoteBatchSummary,
+}
+
+/// Import every sessions and attribution note in the repo
+pub async fn import_all_notes(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<NotesImportAllSummary, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
+    let (session_commits, attribution_commits) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        (
+            annotated_commits(&repo, &[refs.sessions.as_str()])?,
+            annotated_commits(&repo, &refs.attribution_import_precedence())?,
+        )
+    };
+
+    Ok(NotesImportAllSummary {
+        sessions: import_sessions_notes_batch(db, repo_id, session_commits).await?,
+        attribution: import_attribution_notes_batch(db, repo_id, attribution_commits).await?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::{ATTRIBUTION_REF_CANONICAL, SESSIONS_REF_CANONICAL};
+    use crate::story_anchors::test_support::{commit, note};
+
+    #[test]
+    fn range_is_listed_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first").to_string();
+        let second = commit(&repo, "second").to_string();
+        let third = commit(&repo, "third").to_string();
+
+        assert_eq!(
+            commits_in_range(&repo, None, "HEAD").unwrap(),
+            vec![first.clone(), second.clone(), third.clone()]
+        );
+        assert_eq!(
+            commits_in_range(&repo, Some(&first), "HEAD").unwrap(),
+            vec![second.clone(), third]
+        );
+        assert_eq!(
+            commits_in_range(&repo, Some(&first), &second).unwrap(),
+            vec![second]
+        );
+        assert!(commits_in_range(&repo, Some("no-such-ref"), "HEAD").is_err());
+    }
+
+    #[test]
+    fn bare_repositories_list_ranges_and_annotated_commits() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = Repository::init(dir.path().join("work")).unwrap();
+        let first = commit(&source, "first").to_string();
+        let second = commit(&source, "second").to_string();
+        let first_oid = git2::Oid::from_str(&first).unwrap();
+        note(&source, SESSIONS_REF_CANONICAL, first_oid, "s1");
+
+        // Mirror everything, notes included, into a bare repo
+        let bare = Repository::init_bare(dir.path().join("mirror.git")).unwrap();
+        let url = dir.path().join("work").to_string_lossy().to_string();
+        bare.remote_anonymous(&url)
+            .unwrap()
+            .fetch(&["+refs/*:refs/*"], None, None)
+            .unwrap();
+        assert!(bare.is_bare());
+
+        assert_eq!(
+            commits_in_range(&bare, None, "HEAD").unwrap(),
+            vec![first.clone(), second]
+        );
+        assert_eq!(
+            annotated_commits(&bare, &[SESSIONS_REF_CANONICAL, ATTRIBUTION_REF_CANONICAL]).unwrap(),
+            vec![first]
+        );
+    }
+}
@@ -1,6 +1,15 @@
 //! Shared utilities for Story Anchor note formatting.
+//!
+//! Notes larger than `COMPRESS_NOTE_THRESHOLD` are stored compressed: a
+//! `COMPRESSED_NOTE_HEADER` line followed by the zstd-compressed note,
+//! base64-encoded in 76-column lines. A signature (if any) covers the stored
+//! form; readers go through `note_payload` and never see the difference.
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::io::Read;
 
 pub const NOTE_DIVIDER: &str = "---";
 
@@ -11,10 +20,99 @@ pub fn compute_note_hash(message: &str) -> String {
     format!("{:x}", result)
 }
 
-/// Split a git note into `(fast_section, json_section)`.
+/// First lines of the armored signatures a signed note may end with
+pub const NOTE_SIGNATURE_HEADERS: [&str; 2] = [
+    "-----BEGIN PGP SIGNATURE-----",
+    "-----BEGIN SSH SIGNATURE-----",
+];
+
+/// Split a note into its signed payload and trailing armored signature (if
+/// any). As with signed tags, the signature covers the payload exactly and
+/// follows it on its own lines.
+pub fn split_note_signature(message: &str) -> (&str, Option<&str>) {
+    let start = NOTE_SIGNATURE_HEADERS
+        .iter()
+        .filter_map(|header| message.rfind(&format!("\n{}", header)))
+        .max();
+    match start {
+        Some(start) => (&message[..start], Some(&message[start + 1..])),
+        None => (message, None),
+    }
+}
+
+/// First line of a compressed note
+pub const COMPRESSED_NOTE_HEADER: &str = "narrative-note: zstd+base64";
+
+/// Notes longer than this (in bytes) are compressed on export
+pub const COMPRESS_NOTE_THRESHOLD: usize = 64 * 1024;
+
+const COMPRESSED_NOTE_LINE_WIDTH: usize = 76;
+
+/// Largest decompressed note accepted; bigger ones are treated as corrupt
+const MAX_DECOMPRESSED_NOTE_SIZE: u64 = 16 * 1024 * 1024; // 16MB
+
+/// Compress a note if it is over `COMPRESS_NOTE_THRESHOLD`; smaller notes
+/// (and any that fail to compress) are returned as is.
+pub fn compress_note(text: &str) -> String {
+    if text.len() <= COMPRESS_NOTE_THRESHOLD {
+        return text.to_string();
+    }
+    let Ok(compressed) = zstd::encode_all(text.as_bytes(), 3) else {
+        return text.to_string();
+    };
+    let encoded = BASE64.encode(compressed);
+
+    let mut out =
+        String::with_capacity(encoded.len() + encoded.len() / COMPRESSED_NOTE_LINE_WIDTH + 64);
+    out.push_str(COMPRESSED_NOTE_HEADER);
+    out.push('\n');
+    // base64 is ASCII, so byte chunks are char boundaries
+    for line in encoded.as_bytes().chunks(COMPRESSED_NOTE_LINE_WIDTH) {
+        out.push_str(&String::from_utf8_lossy(line));
+        out.push('\n');
+    }
+    out
+}
+
+/// Decompress a `compress_note` body; None if it is corrupt or decompresses
+/// to more than `MAX_DECOMPRESSED_NOTE_SIZE`
+fn decompress_note(body: &str) -> Option<String> {
+    let encoded: String = body.split_whitespace().collect();
+    let compressed = BASE64.decode(encoded).ok()?;
+    let mut bytes = Vec::new();
+    zstd::stream::read::Decoder::new(compressed.as_slice())
+        .ok()?
+        .take(MAX_DECOMPRESSED_NOTE_SIZE + 1)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if bytes.len() as u64 > MAX_DECOMPRESSED_NOTE_SIZE {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// The note text a message carries: signature stripped and, for a
+/// compressed note, decompressed. A corrupt compressed note is returned as
+/// stored (and then fails to parse as a note).
+pub fn note_payload(message: &str) -> Cow<'_, str> {
+    let (payload, _) = split_note_signature(message);
+    match payload.split_once('\n') {
+        Some((header, body)) if header.trim() == COMPRESSED_NOTE_HEADER => {
+            match decompress_note(body) {
+                Some(text) => Cow::Owned(text),
+                None => Cow::Borrowed(payload),
+            }
+        }
+        _ => Cow::Borrowed(payload),
+    }
+}
+
+/// Split a git note into `(fast_section, json_section)`, ignoring any
+/// signature and compression.
 ///
 /// If there is no divider, JSON section is empty.
 pub fn split_note_sections(message: &str) -> (String, String) {
+    let message = note_payload(message);
     let mut fast_lines: Vec<&str> = Vec::new();
     let mut json_lines: Vec<&str> = Vec::new();
 
@@ -37,3 +135,197 @@ pub fn split_note_sections(message: &str) -> (String, String) {
         json_lines.join("\n").trim().to_string(),
     )
 }
+
+/// How a note's `schema_version` relates to the version this build writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteSchemaCompat {
+    /// Same major version (newer minor/patch versions only add fields)
+    Current,
+    /// Older version of the same schema; readable, upgraded by migration
+    Older,
+    /// No (or an unrecognized) schema version: written before versioning
+    Unversioned,
+    /// Newer major version; this build cannot read it reliably
+    Newer,
+}
+
+/// Split `narrative/<kind>/X.Y.Z` into `("narrative/<kind>", (X, Y, Z))`
+pub fn parse_schema_version(version: &str) -> Option<(&str, (u32, u32, u32))> {
+    let (kind, number) = version.trim().rsplit_once('/')?;
+    let mut parts = number.split('.').map(|part| part.parse::<u32>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some((kind, (major, minor, patch)))
+}
+
+/// Compare a note's schema version with `current`
+pub fn schema_compat(found: Option<&str>, current: &str) -> NoteSchemaCompat {
+    let (Some(found), Some((current_kind, current_version))) = (
+        found.and_then(parse_schema_version),
+        parse_schema_version(current),
+    ) else {
+        return NoteSchemaCompat::Unversioned;
+    };
+    let (kind, version) = found;
+    if kind != current_kind {
+        return NoteSchemaCompat::Unversioned;
+    }
+    if version.0 > current_version.0 {
+        NoteSchemaCompat::Newer
+    } else if version < current_version {
+        NoteSchemaCompat::Older
+    } else {
+        NoteSchemaCompat::Current
+    }
+}
+
+/// `schema_version` from a note's JSON section, if present
+pub fn note_schema_version(message: &str) -> Option<String> {
+    let (_, json) = split_note_sections(message);
+    let payload: serde_json::Value = serde_json::from_str(&json).ok()?;
+    payload
+        .get("schema_version")
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+/// Rewrite an older or unversioned note at `current`, keeping its fast
+/// section and every JSON field (the rewritten note is unsigned). Returns None when the note is already
+/// current, newer, or has a JSON section that is not an object.
+///
+/// Versions so far differ only in the stamp; a future format change adds
+/// its field transforms here, keyed on the note's version.
+pub fn upgrade_note_schema(message: &str, commit_sha: &str, current: &str) -> Option<String> {
+    let (fast, json) = split_note_sections(message);
+    let mut payload = if json.is_empty() {
+        serde_json::Map::new()
+    } else {
+        match serde_json::from_str::<serde_json::Value>(&json) {
+            Ok(serde_json::Value::Object(map)) => map,
+            _ => return None,
+        }
+    };
+
+    let found = payload
+        .get("schema_version")
+        .and_then(|value| value.as_str());
+    match schema_compat(found, current) {
+        NoteSchemaCompat::Current | NoteSchemaCompat::Newer => return None,
+        NoteSchemaCompat::Older | NoteSchemaCompat::Unversioned => {}
+    }
+    payload.insert("schema_version".to_string(), current.into());
+    payload
+        .entry("base_commit_sha")
+        .or_insert_with(|| commit_sha.into());
+
+    let mut lines: Vec<&str> = fast
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let json = serde_json::to_string_pretty(&serde_json::Value::Object(payload)).ok()?;
+    lines.push(NOTE_DIVIDER);
+    lines.push(&json);
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSIONS: &str = "narrative/sessions/1.2.0";
+
+    #[test]
+    fn compares_schema_versions() {
+        assert_eq!(
+            schema_compat(Some(SESSIONS), SESSIONS),
+            NoteSchemaCompat::Current
+        );
+        assert_eq!(
+            schema_compat(Some("narrative/sessions/1.0.0"), SESSIONS),
+            NoteSchemaCompat::Older
+        );
+        assert_eq!(
+            schema_compat(Some("narrative/sessions/1.3"), SESSIONS),
+            NoteSchemaCompat::Current
+        );
+        assert_eq!(
+            schema_compat(Some("narrative/sessions/2.0.0"), SESSIONS),
+            NoteSchemaCompat::Newer
+        );
+        assert_eq!(schema_compat(None, SESSIONS), NoteSchemaCompat::Unversioned);
+        assert_eq!(
+            schema_compat(Some("narrative/attribution/1.0.0"), SESSIONS),
+            NoteSchemaCompat::Unversioned
+        );
+    }
+
+    #[test]
+    fn upgrade_stamps_version_and_keeps_fields() {
+        let unversioned = "s1\ns2";
+        let upgraded = upgrade_note_schema(unversioned, "abc", SESSIONS).expect("upgraded");
+        assert_eq!(note_schema_version(&upgraded).as_deref(), Some(SESSIONS));
+        assert!(upgraded.starts_with("s1\ns2\n---\n"));
+
+        let older = "s1\n---\n{\"schema_version\":\"narrative/sessions/1.0.0\",\"extra\":1}";
+        let upgraded = upgrade_note_schema(older, "abc", SESSIONS).expect("upgraded");
+        let (_, json) = split_note_sections(&upgraded);
+        let payload: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(payload["extra"], 1);
+        assert_eq!(payload["base_commit_sha"], "abc");
+
+        assert!(upgrade_note_schema(&upgraded, "abc", SESSIONS).is_none());
+    }
+
+    #[test]
+    fn splits_trailing_signatures() {
+        let payload = "s1\n---\n{\"schema_version\":\"narrative/sessions/1.2.0\"}";
+        let signature = "-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----\n";
+        let signed = format!("{}\n{}", payload, signature);
+
+        assert_eq!(split_note_signature(&signed), (payload, Some(signature)));
+        assert_eq!(split_note_signature(payload), (payload, None));
+        assert_eq!(split_note_sections(&signed), split_note_sections(payload));
+        assert_eq!(note_schema_version(&signed).as_deref(), Some(SESSIONS));
+    }
+
+    #[test]
+    fn large_notes_round_trip_through_compression() {
+        let small = "s1\n---\n{\"schema_version\":\"narrative/sessions/1.2.0\"}";
+        assert_eq!(compress_note(small), small);
+
+        let files: String = (0..5000)
+            .map(|i| format!("src/file_{i}.rs\n  s1 {i}-{}\n", i + 10))
+            .collect();
+        let large = format!("{files}---\n{{\"schema_version\":\"{SESSIONS}\"}}");
+        let stored = compress_note(&large);
+        assert!(stored.starts_with(COMPRESSED_NOTE_HEADER));
+        assert!(stored.len() < large.len());
+        assert!(stored.lines().all(|line| line.len() <= 76));
+
+        assert_eq!(note_payload(&stored), large);
+        assert_eq!(split_note_sections(&stored), split_note_sections(&large));
+        assert_eq!(note_schema_version(&stored).as_deref(), Some(SESSIONS));
+
+        // Signed compressed note: the signature is stripped first
+        let signed = format!(
+            "{stored}-----BEGIN SSH SIGNATURE-----\nU1NIU0lH\n-----END SSH SIGNATURE-----\n"
+        );
+        assert_eq!(note_payload(&signed), large);
+
+        // Corrupt body: left as stored
+        let corrupt = format!("{COMPRESSED_NOTE_HEADER}\n!!!\n");
+        assert_eq!(note_payload(&corrupt), corrupt);
+    }
+
+    #[test]
+    fn oversized_compressed_notes_are_not_decompressed() {
+        let huge = "a".repeat(MAX_DECOMPRESSED_NOTE_SIZE as usize + 1);
+        let stored = compress_note(&huge);
+        assert!(stored.len() < 64 * 1024);
+        assert_eq!(note_payload(&stored), stored);
+
+        let at_limit = "a".repeat(MAX_DECOMPRESSED_NOTE_SIZE as usize);
+        assert_eq!(note_payload(&compress_note(&at_limit)), at_limit);
+    }
+}
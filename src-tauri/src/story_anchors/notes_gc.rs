@@ -0,0 +1,370 @@
+//! Garbage collection of notes on unreachable commits.
+//!
+//! Rebases and amends leave Narrative notes attached to commits that no
+//! branch, tag, remote-tracking ref or HEAD leads to any more. Such a note
+//! moves to the commit that replaced it (a recorded rewrite, or a reachable
+//! commit with the same rewrite key) when that commit has no note of its
+//! own; the rest are pruned. Each notes ref is rewritten in one commit, and
+//! a dry run only reports what would change.
+
+use crate::attribution::git_utils::compute_rewrite_key;
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_format::split_note_sections;
+use crate::story_anchors::notes_merge::notes_entries;
+use crate::story_anchors::refs::{fetch_notes_refs, NotesRefs, NOTES_REMOTE_TRACKING_PREFIX};
+use crate::story_anchors::sync::local_notes_refs;
+use git2::{Oid, Repository, Signature};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesGcMigration {
+    pub from_commit: String,
+    pub to_commit: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesGcRefSummary {
+    pub note_ref: String,
+    pub total_notes: u32,
+    /// Notes on unreachable commits (migrated + pruned)
+    pub unreachable: u32,
+    pub migrated: Vec<NotesGcMigration>,
+    /// Unreachable commits whose notes were (or would be) removed
+    pub pruned: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesGcSummary {
+    pub dry_run: bool,
+    pub refs: Vec<NotesGcRefSummary>,
+}
+
+/// A note attached to an unreachable commit
+#[derive(Debug, Clone)]
+pub struct UnreachableNote {
+    pub note_ref: String,
+    pub commit_sha: String,
+    /// Patch-id of the commit, or the one its note recorded if the commit
+    /// object is gone
+    pub rewrite_key: Option<String>,
+}
+
+/// Commits reachable from any ref except notes refs, or from HEAD
+pub fn reachable_commits(repo: &Repository) -> Result<HashSet<Oid>, String> {
+    let mut walk = repo.revwalk().map_err(|e| e.to_string())?;
+    for reference in repo.references().map_err(|e| e.to_string())? {
+        let reference = reference.map_err(|e| e.to_string())?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        if name.starts_with("refs/notes/") || name.starts_with(NOTES_REMOTE_TRACKING_PREFIX) {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            walk.push(commit.id()).map_err(|e| e.to_string())?;
+        }
+    }
+    if let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) {
+        walk.push(head.id()).map_err(|e| e.to_string())?;
+    }
+
+    let mut reachable = HashSet::new();
+    for oid in walk {
+        reachable.insert(oid.map_err(|e| e.to_string())?);
+    }
+    Ok(reachable)
+}
+
+/// `rewrite_key` recorded in a note's JSON section
+fn note_rewrite_key(repo: &Repository, blob: Oid) -> Option<String> {
+    let blob = repo.find_blob(blob).ok()?;
+    let (_, json) = split_note_sections(&String::from_utf8_lossy(blob.content()));
+    let payload: serde_json::Value = serde_json::from_str(&json).ok()?;
+    payload
+        .get("rewrite_key")
+        .and_then(|value| value.as_str())
+        .map(str::to_string)
+}
+
+/// Notes in the local Narrative notes refs on unreachable commits
+pub fn find_unreachable_notes(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+) -> Result<Vec<UnreachableNote>, String> {
+    let reachable = reachable_commits(repo)?;
+    let mut unreachable = Vec::new();
+    for (note_ref, tip) in local_notes_refs(repo, notes_refs)? {
+        for (sha, blob) in notes_entries(repo, tip)? {
+            if Oid::from_str(&sha).is_ok_and(|oid| reachable.contains(&oid)) {
+                continue;
+            }
+            let rewrite_key = compute_rewrite_key(repo, &sha)
+                .ok()
+                .or_else(|| note_rewrite_key(repo, blob));
+            unreachable.push(UnreachableNote {
+                note_ref: note_ref.clone(),
+                commit_sha: sha,
+                rewrite_key,
+            });
+        }
+    }
+    Ok(unreachable)
+}
+
+/// Replace a notes ref's tree with `entries` (flat, keyed by annotated sha)
+fn write_notes_tree(
+    repo: &Repository,
+    note_ref: &str,
+    tip: Oid,
+    entries: &BTreeMap<String, Oid>,
+) -> Result<(), String> {
+    let mut builder = repo.treebuilder(None).map_err(|e| e.to_string())?;
+    for (sha, blob) in entries {
+        builder
+            .insert(sha.as_str(), *blob, 0o100644)
+            .map_err(|e| e.to_string())?;
+    }
+    let tree_id = builder.write().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("Narrative", "narrative@local"))
+        .map_err(|e| e.to_string())?;
+    let parent = repo.find_commit(tip).map_err(|e| e.to_string())?;
+    let commit = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            "Notes garbage-collected by Narrative",
+            &tree,
+            &[&parent],
+        )
+        .map_err(|e| e.to_string())?;
+    repo.reference_matching(note_ref, commit, true, tip, "narrative: gc notes")
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Migrate or prune notes on unreachable commits.
+///
+/// `rewrites` lists, per unreachable commit, the commits that may have
+/// replaced it (best first); a note moves to the first one that is reachable
+/// and has no note in the same ref.
+pub fn gc_notes(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+    unreachable: &[UnreachableNote],
+    rewrites: &HashMap<String, Vec<String>>,
+    dry_run: bool,
+) -> Result<NotesGcSummary, String> {
+    let reachable = reachable_commits(repo)?;
+    let mut summary = NotesGcSummary {
+        dry_run,
+        refs: Vec::new(),
+    };
+
+    for (note_ref, tip) in local_notes_refs(repo, notes_refs)? {
+        let mut entries = notes_entries(repo, tip)?;
+        let mut ref_summary = NotesGcRefSummary {
+            note_ref: note_ref.clone(),
+            total_notes: entries.len() as u32,
+            unreachable: 0,
+            migrated: Vec::new(),
+            pruned: Vec::new(),
+        };
+
+        for note in unreachable.iter().filter(|note| note.note_ref == note_ref) {
+            let Some(blob) = entries.remove(&note.commit_sha) else {
+                continue;
+            };
+            ref_summary.unreachable += 1;
+            let target = rewrites
+                .get(&note.commit_sha)
+                .into_iter()
+                .flatten()
+                .find(|sha| {
+                    Oid::from_str(sha).is_ok_and(|oid| reachable.contains(&oid))
+                        && !entries.contains_key(*sha)
+                })
+                .cloned();
+            match target {
+                Some(to_commit) => {
+                    entries.insert(to_commit.clone(), blob);
+                    ref_summary.migrated.push(NotesGcMigration {
+                        from_commit: note.commit_sha.clone(),
+                        to_commit,
+                    });
+                }
+                None => ref_summary.pruned.push(note.commit_sha.clone()),
+            }
+        }
+
+        if !dry_run && ref_summary.unreachable > 0 {
+            write_notes_tree(repo, &note_ref, tip, &entries)?;
+        }
+        summary.refs.push(ref_summary);
+    }
+    Ok(summary)
+}
+
+/// Commits that may have replaced `note`'s commit: recorded rewrites first,
+/// then commits sharing its rewrite key
+async fn rewrite_candidates(
+    db: &SqlitePool,
+    repo_id: i64,
+    note: &UnreachableNote,
+) -> Result<Vec<String>, String> {
+    let mut candidates: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT new_sha
+        FROM commit_rewrites
+        WHERE repo_id = ? AND old_sha = ?
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&note.commit_sha)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    if let Some(key) = &note.rewrite_key {
+        let same_key: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT commit_sha
+            FROM commit_rewrite_keys
+            WHERE repo_id = ? AND rewrite_key = ? AND commit_sha != ?
+            ORDER BY updated_at DESC
+            "#,
+        )
+        .bind(repo_id)
+        .bind(key)
+        .bind(&note.commit_sha)
+        .fetch_all(db)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+        candidates.extend(same_key);
+    }
+    Ok(candidates)
+}
+
+/// Garbage-collect a repo's Narrative notes (see module docs). With
+/// `migrate` off every unreachable note is pruned.
+pub async fn gc_narrative_notes(
+    db: &SqlitePool,
+    repo_id: i64,
+    dry_run: bool,
+    migrate: bool,
+) -> Result<NotesGcSummary, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let notes_refs = fetch_notes_refs(db, repo_id).await?;
+    let unreachable = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        find_unreachable_notes(&repo, &notes_refs)?
+    };
+
+    let mut rewrites: HashMap<String, Vec<String>> = HashMap::new();
+    if migrate {
+        for note in &unreachable {
+            if !rewrites.contains_key(&note.commit_sha) {
+                let candidates = rewrite_candidates(db, repo_id, note).await?;
+                rewrites.insert(note.commit_sha.clone(), candidates);
+            }
+        }
+    }
+
+    let summary = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        gc_notes(&repo, &notes_refs, &unreachable, &rewrites, dry_run)?
+    };
+
+    if !dry_run {
+        // Cached note meta follows the notes
+        for ref_summary in &summary.refs {
+            let removed = ref_summary
+                .pruned
+                .iter()
+                .chain(ref_summary.migrated.iter().map(|m| &m.from_commit));
+            for commit_sha in removed {
+                for table in ["story_anchor_note_meta", "attribution_note_meta"] {
+                    sqlx::query(&format!(
+                        "DELETE FROM {table} WHERE repo_id = ? AND commit_sha = ? AND note_ref = ?"
+                    ))
+                    .bind(repo_id)
+                    .bind(commit_sha)
+                    .bind(&ref_summary.note_ref)
+                    .execute(db)
+                    .await
+                    .map_err(|e| format!("Database error: {}", e))?;
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::SESSIONS_REF_CANONICAL;
+    use crate::story_anchors::test_support::{commit, commit_to, note};
+
+    #[test]
+    fn unreachable_notes_migrate_to_rewrites_or_are_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let refs = NotesRefs::default();
+        let base = commit(&repo, "base");
+        // Amended away (rewritten to `amended`) and abandoned commits
+        let old = commit_to(&repo, "old", None);
+        let abandoned = commit_to(&repo, "abandoned", None);
+        let amended = commit(&repo, "amended");
+        note(&repo, SESSIONS_REF_CANONICAL, base, "s-base");
+        note(&repo, SESSIONS_REF_CANONICAL, old, "s-old");
+        note(&repo, SESSIONS_REF_CANONICAL, abandoned, "s-abandoned");
+
+        let unreachable = find_unreachable_notes(&repo, &refs).unwrap();
+        let mut shas: Vec<&str> = unreachable.iter().map(|n| n.commit_sha.as_str()).collect();
+        shas.sort();
+        let mut expected = vec![old.to_string(), abandoned.to_string()];
+        expected.sort();
+        assert_eq!(shas, expected);
+
+        let rewrites = HashMap::from([(old.to_string(), vec![amended.to_string()])]);
+        let tip = repo.refname_to_id(SESSIONS_REF_CANONICAL).unwrap();
+
+        let report = gc_notes(&repo, &refs, &unreachable, &rewrites, true).unwrap();
+        assert_eq!(report.refs.len(), 1);
+        assert_eq!(report.refs[0].total_notes, 3);
+        assert_eq!(report.refs[0].unreachable, 2);
+        assert_eq!(
+            report.refs[0].migrated,
+            vec![NotesGcMigration {
+                from_commit: old.to_string(),
+                to_commit: amended.to_string(),
+            }]
+        );
+        assert_eq!(report.refs[0].pruned, vec![abandoned.to_string()]);
+        // Dry run leaves the ref alone
+        assert_eq!(repo.refname_to_id(SESSIONS_REF_CANONICAL).unwrap(), tip);
+
+        gc_notes(&repo, &refs, &unreachable, &rewrites, false).unwrap();
+        let read = |oid: Oid| {
+            repo.find_note(Some(SESSIONS_REF_CANONICAL), oid)
+                .ok()
+                .and_then(|n| n.message().map(str::to_string))
+        };
+        assert_eq!(read(base).as_deref(), Some("s-base"));
+        assert_eq!(read(amended).as_deref(), Some("s-old"));
+        assert_eq!(read(old), None);
+        assert_eq!(read(abandoned), None);
+        assert!(find_unreachable_notes(&repo, &refs).unwrap().is_empty());
+    }
+}
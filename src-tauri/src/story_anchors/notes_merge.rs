@@ -0,0 +1,326 @@
+//! Three-way merge of diverged sessions notes refs.
+//!
+//! When two clones link sessions to the same commits, fetching leaves the
+//! local sessions ref (`refs/notes/narrative/sessions` by default) and the
+//! fetched copy under `refs/narrative/remotes/<remote>/` on different
+//! histories. Notes are merged per annotated commit against the refs' merge
+//! base: a side that left a note unchanged takes the other side's version,
+//! and notes changed on both sides are combined with `merge_sessions_notes`.
+//! The result is a merge commit on the local ref, ready to push.
+
+use crate::story_anchors::notes_format::compress_note;
+use crate::story_anchors::refs::NotesRefs;
+use crate::story_anchors::sessions_notes::merge_sessions_notes;
+use crate::story_anchors::sync::{default_remote_name, notes_tracking_ref};
+use git2::{ObjectType, Oid, Repository, Signature, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const MERGE_STATUS_UP_TO_DATE: &str = "up_to_date";
+pub const MERGE_STATUS_FAST_FORWARD: &str = "fast_forward";
+pub const MERGE_STATUS_MERGED: &str = "merged";
+/// Nothing fetched from the remote for this ref yet
+pub const MERGE_STATUS_NO_REMOTE_REF: &str = "no_remote_ref";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesMergeSummary {
+    pub remote_name: String,
+    pub ref_name: String,
+    pub status: String,
+    /// Commits whose local note changed
+    pub changed_commits: Vec<String>,
+    /// Notes changed on both sides and combined
+    pub combined_notes: u32,
+    /// Changed notes re-imported into session links
+    pub imported_notes: u32,
+}
+
+/// Notes in a notes commit's tree, keyed by annotated object (fanout
+/// directories are flattened)
+pub(crate) fn notes_entries(
+    repo: &Repository,
+    notes_commit: Oid,
+) -> Result<BTreeMap<String, Oid>, String> {
+    let tree = repo
+        .find_commit(notes_commit)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| e.to_string())?;
+    let mut entries = BTreeMap::new();
+    tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(ObjectType::Blob) {
+            if let Some(name) = entry.name() {
+                entries.insert(format!("{}{}", dir.replace('/', ""), name), entry.id());
+            }
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+fn blob_text(repo: &Repository, oid: Oid) -> Result<String, String> {
+    let blob = repo.find_blob(oid).map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(blob.content()).to_string())
+}
+
+/// Merged note for one annotated commit (None: deleted)
+fn merge_entry(
+    repo: &Repository,
+    commit_sha: &str,
+    base: Option<Oid>,
+    ours: Option<Oid>,
+    theirs: Option<Oid>,
+) -> Result<(Option<Oid>, bool), String> {
+    if ours == theirs || theirs == base {
+        return Ok((ours, false));
+    }
+    if ours == base {
+        return Ok((theirs, false));
+    }
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => {
+            let merged = merge_sessions_notes(
+                commit_sha,
+                &blob_text(repo, ours)?,
+                &blob_text(repo, theirs)?,
+            );
+            let blob = repo
+                .blob(compress_note(&merged).as_bytes())
+                .map_err(|e| e.to_string())?;
+            Ok((Some(blob), true))
+        }
+        // Deleted on one side, edited on the other: keep the edit
+        (kept, None) | (None, kept) => Ok((kept, false)),
+    }
+}
+
+/// Merge the fetched sessions notes ref for `remote_name` into the local one
+pub fn merge_sessions_notes_ref(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+    remote_name: &str,
+) -> Result<NotesMergeSummary, String> {
+    let ref_name = notes_refs.sessions.as_str();
+    let mut summary = NotesMergeSummary {
+        remote_name: remote_name.to_string(),
+        ref_name: ref_name.to_string(),
+        status: MERGE_STATUS_UP_TO_DATE.to_string(),
+        changed_commits: Vec::new(),
+        combined_notes: 0,
+        imported_notes: 0,
+    };
+
+    let Ok(theirs) = repo.refname_to_id(&notes_tracking_ref(notes_refs, remote_name, ref_name))
+    else {
+        summary.status = MERGE_STATUS_NO_REMOTE_REF.to_string();
+        return Ok(summary);
+    };
+    let ours = repo.refname_to_id(ref_name).ok();
+    let ours_entries = match ours {
+        Some(oid) => notes_entries(repo, oid)?,
+        None => BTreeMap::new(),
+    };
+    let theirs_entries = notes_entries(repo, theirs)?;
+
+    let fast_forward = match ours {
+        None => true,
+        Some(ours) if ours == theirs => return Ok(summary),
+        Some(ours) => {
+            if repo
+                .graph_descendant_of(ours, theirs)
+                .map_err(|e| e.to_string())?
+            {
+                return Ok(summary);
+            }
+            repo.graph_descendant_of(theirs, ours)
+                .map_err(|e| e.to_string())?
+        }
+    };
+
+    if fast_forward {
+        summary.changed_commits = theirs_entries
+            .iter()
+            .filter(|(sha, oid)| ours_entries.get(*sha) != Some(*oid))
+            .chain(
+                ours_entries
+                    .iter()
+                    .filter(|(sha, _)| !theirs_entries.contains_key(*sha)),
+            )
+            .map(|(sha, _)| sha.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        match ours {
+            Some(ours) => repo.reference_matching(
+                ref_name,
+                theirs,
+                true,
+                ours,
+                "narrative: fast-forward notes",
+            ),
+            None => repo.reference(ref_name, theirs, false, "narrative: fast-forward notes"),
+        }
+        .map_err(|e| e.to_string())?;
+        summary.status = MERGE_STATUS_FAST_FORWARD.to_string();
+        return Ok(summary);
+    }
+
+    let ours = ours.expect("fast-forward handles a missing local ref");
+    let base_entries = match repo.merge_base(ours, theirs) {
+        Ok(base) => notes_entries(repo, base)?,
+        Err(_) => BTreeMap::new(),
+    };
+
+    let mut builder = repo.treebuilder(None).map_err(|e| e.to_string())?;
+    let annotated: BTreeSet<&String> = ours_entries.keys().chain(theirs_entries.keys()).collect();
+    for sha in annotated {
+        let local = ours_entries.get(sha).copied();
+        let (merged, combined) = merge_entry(
+            repo,
+            sha,
+            base_entries.get(sha).copied(),
+            local,
+            theirs_entries.get(sha).copied(),
+        )?;
+        if combined {
+            summary.combined_notes += 1;
+        }
+        if merged != local {
+            summary.changed_commits.push(sha.clone());
+        }
+        if let Some(blob) = merged {
+            builder
+                .insert(sha.as_str(), blob, 0o100644)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    let tree_id = builder.write().map_err(|e| e.to_string())?;
+    let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("Narrative", "narrative@local"))
+        .map_err(|e| e.to_string())?;
+    let ours_commit = repo.find_commit(ours).map_err(|e| e.to_string())?;
+    let theirs_commit = repo.find_commit(theirs).map_err(|e| e.to_string())?;
+    let merge_commit = repo
+        .commit(
+            None,
+            &signature,
+            &signature,
+            &format!("Notes merged from {} by Narrative", remote_name),
+            &tree,
+            &[&ours_commit, &theirs_commit],
+        )
+        .map_err(|e| e.to_string())?;
+    repo.reference_matching(ref_name, merge_commit, true, ours, "narrative: merge notes")
+        .map_err(|e| e.to_string())?;
+
+    summary.status = MERGE_STATUS_MERGED.to_string();
+    Ok(summary)
+}
+
+/// Merge notes in the repo at `repo_root` (default remote: origin, else the
+/// first remote)
+pub fn merge_notes_from_remote(
+    repo_root: &str,
+    notes_refs: &NotesRefs,
+    remote: Option<String>,
+) -> Result<NotesMergeSummary, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let remote_name = remote
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| default_remote_name(&repo))
+        .ok_or_else(|| "No remote configured for repository".to_string())?;
+    merge_sessions_notes_ref(&repo, notes_refs, &remote_name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::SESSIONS_REF_CANONICAL;
+    use crate::story_anchors::sessions_notes::{build_sessions_note, parse_sessions_note};
+    use crate::story_anchors::sync::{fetch_notes, push_notes, SYNC_STATUS_DIVERGED};
+
+    fn empty_commit(repo: &Repository, message: &str) -> Oid {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn write_note(repo: &Repository, commit: Oid, session_ids: &[&str]) {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let ids: Vec<String> = session_ids.iter().map(|id| id.to_string()).collect();
+        let note = build_sessions_note(&commit.to_string(), &ids, None, None, None);
+        repo.note(
+            &sig,
+            &sig,
+            Some(SESSIONS_REF_CANONICAL),
+            commit,
+            &note,
+            true,
+        )
+        .unwrap();
+    }
+
+    fn session_ids(repo: &Repository, commit: Oid) -> Vec<String> {
+        let note = repo
+            .find_note(Some(SESSIONS_REF_CANONICAL), commit)
+            .unwrap();
+        parse_sessions_note(note.message().unwrap()).session_ids
+    }
+
+    #[test]
+    fn diverged_notes_merge_per_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let refs = NotesRefs::default();
+        let remote_path = dir.path().join("remote.git");
+        Repository::init_bare(&remote_path).unwrap();
+        let remote_url = remote_path.to_string_lossy().to_string();
+        let alice = Repository::init(dir.path().join("alice")).unwrap();
+        alice.remote("origin", &remote_url).unwrap();
+        let bob = Repository::init(dir.path().join("bob")).unwrap();
+        bob.remote("origin", &remote_url).unwrap();
+
+        // Shared history: a note on `first`, pushed by Alice and fetched by Bob
+        let first = empty_commit(&alice, "first");
+        let second = empty_commit(&alice, "second");
+        let head = alice.head().unwrap().name().unwrap().to_string();
+        let mut origin = alice.find_remote("origin").unwrap();
+        origin.push(&[format!("{head}:{head}")], None).unwrap();
+        bob.find_remote("origin")
+            .unwrap()
+            .fetch(&[format!("{head}:refs/remotes/origin/shared")], None, None)
+            .unwrap();
+        write_note(&alice, first, &["s1"]);
+        push_notes(&alice, &refs, "origin").unwrap();
+        fetch_notes(&bob, &refs, "origin").unwrap();
+
+        // Both change `first`'s note; only Alice annotates `second`
+        write_note(&alice, first, &["s1", "s2"]);
+        write_note(&alice, second, &["s4"]);
+        push_notes(&alice, &refs, "origin").unwrap();
+        write_note(&bob, first, &["s1", "s3"]);
+        let fetched = fetch_notes(&bob, &refs, "origin").unwrap();
+        assert_eq!(fetched[0].status, SYNC_STATUS_DIVERGED);
+
+        let summary = merge_sessions_notes_ref(&bob, &refs, "origin").unwrap();
+        assert_eq!(summary.status, MERGE_STATUS_MERGED);
+        assert_eq!(summary.combined_notes, 1);
+        assert_eq!(summary.changed_commits.len(), 2);
+        assert_eq!(session_ids(&bob, first), vec!["s1", "s2", "s3"]);
+        assert_eq!(session_ids(&bob, second), vec!["s4"]);
+
+        // Merging again is a no-op; Alice fast-forwards after Bob pushes
+        let again = merge_sessions_notes_ref(&bob, &refs, "origin").unwrap();
+        assert_eq!(again.status, MERGE_STATUS_UP_TO_DATE);
+        push_notes(&bob, &refs, "origin").unwrap();
+        fetch_notes(&alice, &refs, "origin").unwrap();
+        assert_eq!(session_ids(&alice, first), vec!["s1", "s2", "s3"]);
+    }
+}
@@ -0,0 +1,263 @@
+//! Moving a repo's notes to another ref namespace.
+//!
+//! `set_notes_namespace` moves the sessions, attribution, lineage and
+//! releases refs to the same names under the new namespace, deletes the old
+//! refs and records the namespace in the repo's prefs. A target ref that already
+//! exists (e.g. notes fetched under that namespace from a team remote) gains
+//! the notes it lacks; where both refs annotate a commit its own note wins.
+
+use crate::attribution::prefs::fetch_or_create_prefs;
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_merge::notes_entries;
+use crate::story_anchors::refs::{fetch_notes_refs, NotesRefs, DEFAULT_NOTES_NAMESPACE};
+use git2::{Repository, Signature};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesRefMove {
+    pub from_ref: String,
+    pub to_ref: String,
+    /// "moved" | "merged" | "empty"
+    pub status: String,
+    /// Notes now under `to_ref` that came from `from_ref`
+    pub copied: u32,
+    /// Commits annotated in both refs; `to_ref` kept its note
+    pub kept: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesNamespaceMigration {
+    pub from_namespace: String,
+    pub to_namespace: String,
+    pub refs: Vec<NotesRefMove>,
+}
+
+/// Move each of `from`'s refs to the matching ref of `to`
+pub fn move_notes_refs(
+    repo: &Repository,
+    from: &NotesRefs,
+    to: &NotesRefs,
+) -> Result<Vec<NotesRefMove>, String> {
+    let mut moves = Vec::new();
+    for (from_ref, to_ref) in from.all().into_iter().zip(to.all()) {
+        let mut summary = NotesRefMove {
+            from_ref: from_ref.to_string(),
+            to_ref: to_ref.to_string(),
+            status: "empty".to_string(),
+            copied: 0,
+            kept: 0,
+        };
+        let Ok(from_tip) = repo.refname_to_id(from_ref) else {
+            moves.push(summary);
+            continue;
+        };
+        let from_entries = notes_entries(repo, from_tip)?;
+
+        match repo.refname_to_id(to_ref).ok() {
+            None => {
+                repo.reference(to_ref, from_tip, false, "narrative: move notes namespace")
+                    .map_err(|e| e.to_string())?;
+                summary.status = "moved".to_string();
+                summary.copied = from_entries.len() as u32;
+            }
+            Some(to_tip) => {
+                let mut entries = notes_entries(repo, to_tip)?;
+                for (sha, blob) in from_entries {
+                    if entries.contains_key(&sha) {
+                        summary.kept += 1;
+                    } else {
+                        entries.insert(sha, blob);
+                        summary.copied += 1;
+                    }
+                }
+
+                let mut builder = repo.treebuilder(None).map_err(|e| e.to_string())?;
+                for (sha, blob) in &entries {
+                    builder
+                        .insert(sha.as_str(), *blob, 0o100644)
+                        .map_err(|e| e.to_string())?;
+                }
+                let tree_id = builder.write().map_err(|e| e.to_string())?;
+                let tree = repo.find_tree(tree_id).map_err(|e| e.to_string())?;
+                let signature = repo
+                    .signature()
+                    .or_else(|_| Signature::now("Narrative", "narrative@local"))
+                    .map_err(|e| e.to_string())?;
+                let ours = repo.find_commit(to_tip).map_err(|e| e.to_string())?;
+                let theirs = repo.find_commit(from_tip).map_err(|e| e.to_string())?;
+                let merge_commit = repo
+                    .commit(
+                        None,
+                        &signature,
+                        &signature,
+                        &format!("Notes moved from {} by Narrative", from_ref),
+                        &tree,
+                        &[&ours, &theirs],
+                    )
+                    .map_err(|e| e.to_string())?;
+                repo.reference_matching(
+                    to_ref,
+                    merge_commit,
+                    true,
+                    to_tip,
+                    "narrative: move notes namespace",
+                )
+                .map_err(|e| e.to_string())?;
+                summary.status = "merged".to_string();
+            }
+        }
+
+        repo.find_reference(from_ref)
+            .and_then(|mut reference| reference.delete())
+            .map_err(|e| e.to_string())?;
+        moves.push(summary);
+    }
+    Ok(moves)
+}
+
+/// Switch a repo's notes namespace (`None`: back to the default), moving its
+/// notes and the cached note meta along
+pub async fn set_notes_namespace(
+    db: &SqlitePool,
+    repo_id: i64,
+    namespace: Option<&str>,
+) -> Result<NotesNamespaceMigration, String> {
+    let to = NotesRefs::for_namespace(namespace)?;
+    let from = fetch_notes_refs(db, repo_id).await?;
+    let mut migration = NotesNamespaceMigration {
+        from_namespace: from.namespace().to_string(),
+        to_namespace: to.namespace().to_string(),
+        refs: Vec::new(),
+    };
+    if from == to {
+        return Ok(migration);
+    }
+
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    // Make sure there is a prefs row to record the namespace in
+    fetch_or_create_prefs(db, repo_id).await?;
+    migration.refs = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        move_notes_refs(&repo, &from, &to)?
+    };
+
+    let stored = (to.namespace() != DEFAULT_NOTES_NAMESPACE).then(|| to.namespace());
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query(
+        r#"
+        UPDATE attribution_prefs
+        SET notes_namespace = ?, updated_at = CURRENT_TIMESTAMP
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(stored)
+    .bind(repo_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    for moved in &migration.refs {
+        for table in [
+            "story_anchor_note_meta",
+            "attribution_note_meta",
+            "release_anchors",
+        ] {
+            sqlx::query(&format!(
+                "UPDATE OR REPLACE {table} SET note_ref = ? WHERE repo_id = ? AND note_ref = ?"
+            ))
+            .bind(&moved.to_ref)
+            .bind(repo_id)
+            .bind(&moved.from_ref)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        }
+    }
+    // Cached statuses name the old refs
+    sqlx::query("DELETE FROM story_anchor_status_cache WHERE repo_id = ?")
+        .bind(repo_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(migration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::{normalize_notes_namespace, SESSIONS_REF_CANONICAL};
+    use crate::story_anchors::test_support::{commit, note};
+    use git2::Oid;
+
+    fn read(repo: &Repository, note_ref: &str, oid: Oid) -> Option<String> {
+        repo.find_note(Some(note_ref), oid)
+            .ok()
+            .and_then(|n| n.message().map(str::to_string))
+    }
+
+    #[test]
+    fn namespaces_must_sit_under_refs_notes() {
+        assert_eq!(
+            normalize_notes_namespace(" refs/notes/acme/narrative/ ").unwrap(),
+            "refs/notes/acme/narrative"
+        );
+        assert!(normalize_notes_namespace("refs/notes/").is_err());
+        assert!(normalize_notes_namespace("refs/heads/narrative").is_err());
+        assert!(normalize_notes_namespace("refs/notes/acme..narrative").is_err());
+        assert_eq!(
+            NotesRefs::for_namespace(Some("refs/notes/acme/narrative"))
+                .unwrap()
+                .sessions,
+            "refs/notes/acme/narrative/sessions"
+        );
+    }
+
+    #[test]
+    fn notes_move_to_a_new_namespace_and_merge_into_existing_refs() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first");
+        let second = commit(&repo, "second");
+        let from = NotesRefs::default();
+        let to = NotesRefs::for_namespace(Some("refs/notes/acme/narrative")).unwrap();
+
+        note(&repo, SESSIONS_REF_CANONICAL, first, "ours-1");
+        note(&repo, SESSIONS_REF_CANONICAL, second, "ours-2");
+        note(&repo, &from.attribution, first, "attribution");
+        // The team already shares sessions notes under the new namespace
+        note(&repo, &to.sessions, second, "team-2");
+
+        let moves = move_notes_refs(&repo, &from, &to).unwrap();
+        let status: Vec<(&str, u32, u32)> = moves
+            .iter()
+            .map(|m| (m.status.as_str(), m.copied, m.kept))
+            .collect();
+        assert_eq!(
+            status,
+            vec![
+                ("merged", 1, 1),
+                ("moved", 1, 0),
+                ("empty", 0, 0),
+                ("empty", 0, 0)
+            ]
+        );
+
+        assert_eq!(read(&repo, &to.sessions, first).as_deref(), Some("ours-1"));
+        assert_eq!(read(&repo, &to.sessions, second).as_deref(), Some("team-2"));
+        assert_eq!(
+            read(&repo, &to.attribution, first).as_deref(),
+            Some("attribution")
+        );
+        assert!(repo.refname_to_id(SESSIONS_REF_CANONICAL).is_err());
+        assert!(repo.refname_to_id(&from.attribution).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Bulk upgrade of notes to the current schema versions.
+//!
+//! Import reads older and unversioned notes as they are; this rewrites them
+//! in place at the current `schema_version` so every clone sees one format.
+//! Notes from a newer major version are counted and left untouched.
+
+use crate::story_anchors::notes_format::{
+    compress_note, note_schema_version, schema_compat, upgrade_note_schema, NoteSchemaCompat,
+};
+use crate::story_anchors::refs::{NotesRefs, ATTRIBUTION_SCHEMA_VERSION, SESSIONS_SCHEMA_VERSION};
+use git2::{Repository, Signature};
+use serde::Serialize;
+
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesSchemaRefSummary {
+    pub note_ref: String,
+    pub schema_version: String,
+    pub total: u32,
+    pub current: u32,
+    pub upgraded: u32,
+    /// Written by a newer major version; left as is
+    pub unsupported: u32,
+    pub failed: u32,
+    /// Commits whose notes were rewritten
+    pub upgraded_commits: Vec<String>,
+}
+
+/// Upgrade every older or unversioned note under one notes ref
+fn migrate_notes_ref(
+    repo: &Repository,
+    signature: &Signature,
+    note_ref: &str,
+    current: &str,
+) -> Result<NotesSchemaRefSummary, String> {
+    let mut summary = NotesSchemaRefSummary {
+        note_ref: note_ref.to_string(),
+        schema_version: current.to_string(),
+        ..Default::default()
+    };
+    let annotated = match repo.notes(Some(note_ref)) {
+        Ok(notes) => notes
+            .filter_map(Result::ok)
+            .map(|(_, annotated)| annotated)
+            .collect::<Vec<_>>(),
+        // No notes under this ref yet
+        Err(_) => return Ok(summary),
+    };
+
+    for oid in annotated {
+        summary.total += 1;
+        let Some(message) = repo
+            .find_note(Some(note_ref), oid)
+            .ok()
+            .and_then(|note| note.message().map(str::to_string))
+        else {
+            summary.failed += 1;
+            continue;
+        };
+
+        let found = note_schema_version(&message);
+        match schema_compat(found.as_deref(), current) {
+            NoteSchemaCompat::Current => summary.current += 1,
+            NoteSchemaCompat::Newer => summary.unsupported += 1,
+            NoteSchemaCompat::Older | NoteSchemaCompat::Unversioned => {
+                let commit_sha = oid.to_string();
+                let written = upgrade_note_schema(&message, &commit_sha, current)
+                    .ok_or_else(|| "note has an unreadable JSON section".to_string())
+                    .and_then(|upgraded| {
+                        let upgraded = compress_note(&upgraded);
+                        repo.note(signature, signature, Some(note_ref), oid, &upgraded, true)
+                            .map_err(|e| e.to_string())
+                    });
+                match written {
+                    Ok(_) => {
+                        summary.upgraded += 1;
+                        summary.upgraded_commits.push(commit_sha);
+                    }
+                    Err(_) => summary.failed += 1,
+                }
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Upgrade sessions and attribution notes in `repo` to the current schemas
+pub fn migrate_notes_schema(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+) -> Result<Vec<NotesSchemaRefSummary>, String> {
+    let signature = repo
+        .signature()
+        .or_else(|_| Signature::now("Narrative", "narrative@local"))
+        .map_err(|e| e.to_string())?;
+
+    // Refs whose JSON section carries `schema_version`
+    [
+        (&notes_refs.sessions, SESSIONS_SCHEMA_VERSION),
+        (&notes_refs.attribution, ATTRIBUTION_SCHEMA_VERSION),
+    ]
+    .iter()
+    .map(|(note_ref, current)| migrate_notes_ref(repo, &signature, note_ref, current))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::SESSIONS_REF_CANONICAL;
+
+    #[test]
+    fn upgrades_unversioned_notes_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let commit = repo
+            .commit(Some("HEAD"), &sig, &sig, "first", &tree, &[])
+            .unwrap();
+        repo.note(
+            &sig,
+            &sig,
+            Some(SESSIONS_REF_CANONICAL),
+            commit,
+            "s1\ns2",
+            false,
+        )
+        .unwrap();
+
+        let summary = migrate_notes_schema(&repo, &NotesRefs::default()).unwrap();
+        assert_eq!(summary[0].upgraded, 1);
+        assert_eq!(summary[0].upgraded_commits, vec![commit.to_string()]);
+        assert_eq!(summary[1].total, 0);
+        let note = repo
+            .find_note(Some(SESSIONS_REF_CANONICAL), commit)
+            .unwrap();
+        assert_eq!(
+            note_schema_version(note.message().unwrap()).as_deref(),
+            Some(SESSIONS_SCHEMA_VERSION)
+        );
+
+        let again = migrate_notes_schema(&repo, &NotesRefs::default()).unwrap();
+        assert_eq!(again[0].current, 1);
+        assert_eq!(again[0].upgraded, 0);
+    }
+}
@@ -0,0 +1,393 @@
+//! Optional signing of Narrative notes with the repo's git signing setup.
+//!
+//! A signed note is its payload followed by an armored signature, like a
+//! signed tag. Keys come from the config `git commit -S` uses (`gpg.format`,
+//! `user.signingkey`, `gpg.program`, `gpg.ssh.program`); SSH signatures are
+//! checked against `gpg.ssh.allowedSignersFile`.
+
+use crate::story_anchors::notes_format::split_note_signature;
+use git2::Repository;
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// SSH signature namespace, so note signatures cannot pass as commit
+/// signatures (or the reverse)
+pub const NOTES_SIGNATURE_NAMESPACE: &str = "narrative-notes";
+
+pub const SIGNATURE_UNSIGNED: &str = "unsigned";
+/// Signed by a key the repo trusts
+pub const SIGNATURE_VALID: &str = "valid";
+/// The signature does not match the note (tampered or corrupted)
+pub const SIGNATURE_INVALID: &str = "invalid";
+/// Intact signature from an untrusted key, or one that could not be checked
+pub const SIGNATURE_UNVERIFIED: &str = "unverified";
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteSignatureCheck {
+    pub status: String,
+    /// Key owner (GPG user id or SSH principal), when known
+    pub signer: Option<String>,
+}
+
+impl NoteSignatureCheck {
+    fn new(status: &str, signer: Option<String>) -> Self {
+        Self {
+            status: status.to_string(),
+            signer,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SigningFormat {
+    OpenPgp,
+    Ssh,
+}
+
+struct SigningConfig {
+    format: SigningFormat,
+    /// `user.signingkey`: a GPG key id, or an SSH key path or literal key
+    key: Option<String>,
+    /// Default GPG key when `user.signingkey` is unset
+    email: Option<String>,
+    gpg_program: String,
+    ssh_program: String,
+    allowed_signers: Option<PathBuf>,
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+fn signing_config(repo_root: &Path) -> Result<SigningConfig, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let config = repo
+        .config()
+        .and_then(|mut config| config.snapshot())
+        .map_err(|e| e.to_string())?;
+    let get = |name: &str| config.get_string(name).ok().filter(|v| !v.is_empty());
+
+    let format = match get("gpg.format").as_deref() {
+        None | Some("openpgp") => SigningFormat::OpenPgp,
+        Some("ssh") => SigningFormat::Ssh,
+        Some(other) => return Err(format!("Unsupported gpg.format for notes: {}", other)),
+    };
+    Ok(SigningConfig {
+        format,
+        key: get("user.signingkey"),
+        email: get("user.email"),
+        gpg_program: get("gpg.openpgp.program")
+            .or_else(|| get("gpg.program"))
+            .unwrap_or_else(|| "gpg".to_string()),
+        ssh_program: get("gpg.ssh.program").unwrap_or_else(|| "ssh-keygen".to_string()),
+        allowed_signers: get("gpg.ssh.allowedSignersFile").map(|path| expand_home(&path)),
+    })
+}
+
+/// A file in the temp dir, removed on drop
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(contents: &[u8]) -> Result<Self, String> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let path = std::env::temp_dir().join(format!(
+            "narrative-notes-{}-{}-{}",
+            std::process::id(),
+            nanos,
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        // create_new: never follow a planted file or symlink
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        file.write_all(contents).map_err(|e| e.to_string())?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap_or_default()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn run(program: &str, args: &[&str], input: &[u8]) -> Result<Output, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+fn sign_payload(config: &SigningConfig, payload: &str) -> Result<String, String> {
+    let output = match config.format {
+        SigningFormat::OpenPgp => {
+            let key = config
+                .key
+                .as_deref()
+                .or(config.email.as_deref())
+                .ok_or_else(|| "Set user.signingkey to sign notes".to_string())?;
+            let output = run(
+                &config.gpg_program,
+                &["--status-fd=2", "-bsau", key],
+                payload.as_bytes(),
+            )?;
+            if !String::from_utf8_lossy(&output.stderr).contains("[GNUPG:] SIG_CREATED ") {
+                return Err(format!(
+                    "gpg failed to sign the note: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            output
+        }
+        SigningFormat::Ssh => {
+            let key = config
+                .key
+                .as_deref()
+                .ok_or_else(|| "Set user.signingkey to sign notes".to_string())?;
+            // A literal public key signs through the agent
+            let literal = key
+                .strip_prefix("key::")
+                .or_else(|| key.starts_with("ssh-").then_some(key));
+            let key_file = literal
+                .map(|public_key| TempFile::new(public_key.as_bytes()))
+                .transpose()?;
+            let key_path = expand_home(key).to_string_lossy().to_string();
+            let mut args = vec!["-Y", "sign", "-n", NOTES_SIGNATURE_NAMESPACE, "-f"];
+            match &key_file {
+                Some(file) => args.extend([file.path(), "-U"]),
+                None => args.push(&key_path),
+            }
+            let output = run(&config.ssh_program, &args, payload.as_bytes())?;
+            if !output.status.success() {
+                return Err(format!(
+                    "ssh-keygen failed to sign the note: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            output
+        }
+    };
+
+    let signature = String::from_utf8_lossy(&output.stdout).to_string();
+    if signature.trim().is_empty() {
+        return Err("Signing produced no signature".to_string());
+    }
+    Ok(format!("{}\n{}", payload, signature))
+}
+
+/// Sign a note with the repo's signing key, replacing any old signature
+pub fn sign_note(repo_root: &Path, message: &str) -> Result<String, String> {
+    let (payload, _) = split_note_signature(message);
+    let config = signing_config(repo_root)?;
+    sign_payload(&config, payload)
+}
+
+fn verify_openpgp(config: &SigningConfig, payload: &str, signature: &str) -> NoteSignatureCheck {
+    let Ok(sig_file) = TempFile::new(signature.as_bytes()) else {
+        return NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None);
+    };
+    let Ok(output) = run(
+        &config.gpg_program,
+        &["--status-fd=1", "--verify", sig_file.path(), "-"],
+        payload.as_bytes(),
+    ) else {
+        return NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None);
+    };
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    let mut signer = None;
+    let mut good = false;
+    let mut trusted = false;
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        if let Some(rest) = line.strip_prefix("GOODSIG ") {
+            good = true;
+            signer = rest.split_once(' ').map(|(_, uid)| uid.to_string());
+        } else if line.starts_with("BADSIG ") {
+            return NoteSignatureCheck::new(SIGNATURE_INVALID, None);
+        } else if line.starts_with("TRUST_FULLY") || line.starts_with("TRUST_ULTIMATE") {
+            trusted = true;
+        }
+    }
+    // Like git's %G?: a good signature from a key of unknown validity is
+    // not a trusted one
+    match (good, trusted) {
+        (true, true) => NoteSignatureCheck::new(SIGNATURE_VALID, signer),
+        (true, false) => NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, signer),
+        _ => NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None),
+    }
+}
+
+fn verify_ssh(config: &SigningConfig, payload: &str, signature: &str) -> NoteSignatureCheck {
+    let Ok(sig_file) = TempFile::new(signature.as_bytes()) else {
+        return NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None);
+    };
+    let program = config.ssh_program.as_str();
+
+    let principal = config.allowed_signers.as_ref().and_then(|allowed| {
+        let allowed = allowed.to_str()?;
+        let output = run(
+            program,
+            &[
+                "-Y",
+                "find-principals",
+                "-f",
+                allowed,
+                "-s",
+                sig_file.path(),
+            ],
+            b"",
+        )
+        .ok()?;
+        let principals = String::from_utf8_lossy(&output.stdout).to_string();
+        let principal = principals.lines().next()?.trim().to_string();
+        (output.status.success() && !principal.is_empty()).then(|| (allowed, principal))
+    });
+
+    if let Some((allowed, principal)) = principal {
+        let verified = run(
+            program,
+            &[
+                "-Y",
+                "verify",
+                "-f",
+                allowed,
+                "-I",
+                &principal,
+                "-n",
+                NOTES_SIGNATURE_NAMESPACE,
+                "-s",
+                sig_file.path(),
+            ],
+            payload.as_bytes(),
+        );
+        return match verified {
+            Ok(output) if output.status.success() => {
+                NoteSignatureCheck::new(SIGNATURE_VALID, Some(principal))
+            }
+            Ok(_) => NoteSignatureCheck::new(SIGNATURE_INVALID, Some(principal)),
+            Err(_) => NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None),
+        };
+    }
+
+    // No trusted key matches: only check the signature is intact
+    let checked = run(
+        program,
+        &[
+            "-Y",
+            "check-novalidate",
+            "-n",
+            NOTES_SIGNATURE_NAMESPACE,
+            "-s",
+            sig_file.path(),
+        ],
+        payload.as_bytes(),
+    );
+    match checked {
+        Ok(output) if !output.status.success() => NoteSignatureCheck::new(SIGNATURE_INVALID, None),
+        _ => NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None),
+    }
+}
+
+/// Check a note's signature against the keys the repo trusts
+pub fn verify_note_signature(repo_root: &Path, message: &str) -> NoteSignatureCheck {
+    let (payload, Some(signature)) = split_note_signature(message) else {
+        return NoteSignatureCheck::new(SIGNATURE_UNSIGNED, None);
+    };
+    let Ok(config) = signing_config(repo_root) else {
+        return NoteSignatureCheck::new(SIGNATURE_UNVERIFIED, None);
+    };
+    if signature.starts_with("-----BEGIN SSH SIGNATURE-----") {
+        verify_ssh(&config, payload, signature)
+    } else {
+        verify_openpgp(&config, payload, signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssh_signed_notes_verify_against_allowed_signers() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("id_ed25519");
+        let generated = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key)
+            .status();
+        if !generated.is_ok_and(|status| status.success()) {
+            // ssh-keygen unavailable
+            return;
+        }
+        let public_key = fs::read_to_string(dir.path().join("id_ed25519.pub")).unwrap();
+        let allowed = dir.path().join("allowed_signers");
+        fs::write(&allowed, format!("test@example.com {}", public_key)).unwrap();
+
+        let repo = Repository::init(dir.path().join("repo")).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("gpg.format", "ssh").unwrap();
+        config
+            .set_str("user.signingkey", key.to_str().unwrap())
+            .unwrap();
+        let repo_root = repo.workdir().unwrap().to_path_buf();
+
+        let payload = "s1\n---\n{\"schema_version\":\"narrative/sessions/1.0.0\"}";
+        let signed = sign_note(&repo_root, payload).unwrap();
+        assert_eq!(split_note_signature(&signed).0, payload);
+
+        // Intact but untrusted until the key is an allowed signer
+        assert_eq!(
+            verify_note_signature(&repo_root, &signed).status,
+            SIGNATURE_UNVERIFIED
+        );
+        config
+            .set_str("gpg.ssh.allowedSignersFile", allowed.to_str().unwrap())
+            .unwrap();
+        assert_eq!(
+            verify_note_signature(&repo_root, &signed),
+            NoteSignatureCheck::new(SIGNATURE_VALID, Some("test@example.com".to_string()))
+        );
+
+        let tampered = signed.replacen("s1", "s2", 1);
+        assert_eq!(
+            verify_note_signature(&repo_root, &tampered).status,
+            SIGNATURE_INVALID
+        );
+        assert_eq!(
+            verify_note_signature(&repo_root, payload).status,
+            SIGNATURE_UNSIGNED
+        );
+    }
+}
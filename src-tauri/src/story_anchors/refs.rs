@@ -1,9 +1,17 @@
 //! Git Notes refs + schema versions for Story Anchors.
+//!
+//! The constants are the default refs. A repo can keep its notes under its
+//! own namespace (e.g. `refs/notes/acme/narrative`); `fetch_notes_refs`
+//! resolves the refs a repo actually reads and writes.
 
 // Canonical Narrative refs (write targets)
 pub const ATTRIBUTION_REF_CANONICAL: &str = "refs/notes/narrative/attribution";
 pub const SESSIONS_REF_CANONICAL: &str = "refs/notes/narrative/sessions";
 pub const LINEAGE_REF_CANONICAL: &str = "refs/notes/narrative/lineage";
+pub const RELEASES_REF_CANONICAL: &str = "refs/notes/narrative/releases";
+
+// Fetched remote notes, per remote, before they are fast-forwarded locally
+pub const NOTES_REMOTE_TRACKING_PREFIX: &str = "refs/narrative/remotes/";
 
 // Compatibility refs (read/import only unless migrating)
 pub const ATTRIBUTION_REF_LEGACY_NARRATIVE: &str = "refs/notes/narrative-attribution";
@@ -12,7 +20,97 @@ pub const ATTRIBUTION_REF_LEGACY_NARRATIVE: &str = "refs/notes/narrative-attribu
 pub const ATTRIBUTION_SCHEMA_VERSION: &str = "narrative/attribution/1.0.0";
 pub const SESSIONS_SCHEMA_VERSION: &str = "narrative/sessions/1.0.0";
 pub const LINEAGE_SCHEMA_VERSION: &str = "narrative/lineage/1.0.0";
+pub const RELEASE_SCHEMA_VERSION: &str = "narrative/release/1.0.0";
 
 pub fn attribution_import_refs_precedence() -> [&'static str; 2] {
     [ATTRIBUTION_REF_CANONICAL, ATTRIBUTION_REF_LEGACY_NARRATIVE]
 }
+
+/// Namespace the canonical refs live under
+pub const DEFAULT_NOTES_NAMESPACE: &str = "refs/notes/narrative";
+
+/// A repo's notes refs, all under one namespace
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotesRefs {
+    /// Namespace with a trailing `/`, e.g. `refs/notes/narrative/`
+    pub prefix: String,
+    pub attribution: String,
+    pub sessions: String,
+    pub lineage: String,
+    pub releases: String,
+}
+
+impl Default for NotesRefs {
+    fn default() -> Self {
+        Self::under(DEFAULT_NOTES_NAMESPACE)
+    }
+}
+
+impl NotesRefs {
+    fn under(namespace: &str) -> Self {
+        Self {
+            prefix: format!("{}/", namespace),
+            attribution: format!("{}/attribution", namespace),
+            sessions: format!("{}/sessions", namespace),
+            lineage: format!("{}/lineage", namespace),
+            releases: format!("{}/releases", namespace),
+        }
+    }
+
+    /// Refs under `namespace` (`None` for the default namespace)
+    pub fn for_namespace(namespace: Option<&str>) -> Result<Self, String> {
+        match namespace {
+            Some(namespace) => Ok(Self::under(&normalize_notes_namespace(namespace)?)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Namespace without the trailing `/`
+    pub fn namespace(&self) -> &str {
+        self.prefix.trim_end_matches('/')
+    }
+
+    /// Refs to read an attribution note from, preferred first
+    pub fn attribution_import_precedence(&self) -> [&str; 2] {
+        [self.attribution.as_str(), ATTRIBUTION_REF_LEGACY_NARRATIVE]
+    }
+
+    /// The sessions, attribution, lineage and releases refs, in that order
+    pub fn all(&self) -> [&str; 4] {
+        [
+            self.sessions.as_str(),
+            self.attribution.as_str(),
+            self.lineage.as_str(),
+            self.releases.as_str(),
+        ]
+    }
+}
+
+/// Check a namespace and drop any trailing `/`. It must sit under
+/// `refs/notes/` so git treats its refs as notes.
+pub fn normalize_notes_namespace(namespace: &str) -> Result<String, String> {
+    let namespace = namespace.trim().trim_end_matches('/');
+    let valid = namespace
+        .strip_prefix("refs/notes/")
+        .is_some_and(|rest| !rest.is_empty())
+        && git2::Reference::is_valid_name(&format!("{}/sessions", namespace));
+    if !valid {
+        return Err(format!(
+            "Invalid notes namespace '{}': expected a ref prefix like refs/notes/acme/narrative",
+            namespace
+        ));
+    }
+    Ok(namespace.to_string())
+}
+
+/// Notes refs configured for a repo (the defaults unless its namespace was
+/// changed)
+pub async fn fetch_notes_refs(db: &sqlx::SqlitePool, repo_id: i64) -> Result<NotesRefs, String> {
+    let namespace: Option<Option<String>> =
+        sqlx::query_scalar("SELECT notes_namespace FROM attribution_prefs WHERE repo_id = ?")
+            .bind(repo_id)
+            .fetch_optional(db)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+    NotesRefs::for_namespace(namespace.flatten().as_deref())
+}
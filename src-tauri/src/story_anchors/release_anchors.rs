@@ -0,0 +1,335 @@
+//! Story Anchor: Release anchors.
+//!
+//! A release anchor aggregates a release's commits (a tag and everything
+//! since the previous tag, or since an explicit `from_ref`) into one summary:
+//! the sessions linked to those commits and their combined attribution. The
+//! summary is stored per tag for querying and, optionally, written as a note
+//! on the tagged commit under the repo's releases ref so it travels with the
+//! other notes.
+
+use crate::attribution::prefs::fetch_or_create_prefs;
+use crate::attribution::report::{build_attribution_report, ReportTool, ReportTotals};
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_export::commits_in_range;
+use crate::story_anchors::notes_format::{compress_note, compute_note_hash, NOTE_DIVIDER};
+use crate::story_anchors::notes_signing::sign_note;
+use crate::story_anchors::refs::{NotesRefs, RELEASE_SCHEMA_VERSION};
+use git2::{DescribeFormatOptions, DescribeOptions, Oid, Repository, Signature};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseAnchor {
+    pub tag: String,
+    /// Commit the tag points at
+    pub commit_sha: String,
+    /// Exclusive start of the range: the previous tag unless given explicitly
+    /// (None: from the root commit)
+    pub from_ref: Option<String>,
+    pub commit_count: u32,
+    /// Sessions linked to any commit in the release, sorted
+    pub session_ids: Vec<String>,
+    pub totals: ReportTotals,
+    /// Most lines first
+    pub tools: Vec<ReportTool>,
+    pub generated_at: String,
+    /// Ref the release note was written to (None: not written)
+    pub note_ref: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReleaseNotePayload<'a> {
+    schema_version: &'static str,
+    #[serde(flatten)]
+    anchor: &'a ReleaseAnchor,
+}
+
+/// Nearest tag reachable from the commit's first parent (None for a root
+/// commit or when no earlier commit is tagged)
+pub fn previous_tag(repo: &Repository, commit: Oid) -> Option<String> {
+    let parent = repo.find_commit(commit).ok()?.parent(0).ok()?;
+    let mut options = DescribeOptions::new();
+    options.describe_tags();
+    let mut format = DescribeFormatOptions::new();
+    // Just the tag name, no `-<n>-g<sha>` suffix
+    format.abbreviated_size(0);
+    parent
+        .as_object()
+        .describe(&options)
+        .ok()?
+        .format(Some(&format))
+        .ok()
+}
+
+/// The tagged commit, the range start and the release's commits (oldest first)
+pub fn release_range(
+    repo: &Repository,
+    tag: &str,
+    from_ref: Option<&str>,
+) -> Result<(String, Option<String>, Vec<String>), String> {
+    let commit = repo
+        .revparse_single(tag)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Cannot resolve {}: {}", tag, e))?;
+    let from_ref = match from_ref {
+        Some(from_ref) => Some(from_ref.to_string()),
+        None => previous_tag(repo, commit.id()),
+    };
+    let commits = commits_in_range(repo, from_ref.as_deref(), tag)?;
+    Ok((commit.id().to_string(), from_ref, commits))
+}
+
+/// Sessions linked to a commit: notes-sourced links, else heuristic links
+async fn commit_session_ids(db: &SqlitePool, repo_id: i64, commit_sha: &str) -> Vec<String> {
+    let linked: Vec<String> = sqlx::query_scalar(
+        "SELECT session_id FROM commit_session_links WHERE repo_id = ? AND commit_sha = ?",
+    )
+    .bind(repo_id)
+    .bind(commit_sha)
+    .fetch_all(db)
+    .await
+    .unwrap_or_default();
+    if !linked.is_empty() {
+        return linked;
+    }
+    sqlx::query_scalar("SELECT session_id FROM session_links WHERE repo_id = ? AND commit_sha = ?")
+        .bind(repo_id)
+        .bind(commit_sha)
+        .fetch_all(db)
+        .await
+        .unwrap_or_default()
+}
+
+pub fn build_release_note(anchor: &ReleaseAnchor) -> String {
+    let mut lines = vec![format!("release {}", anchor.tag)];
+    if let Some(from_ref) = &anchor.from_ref {
+        lines.push(format!("since {}", from_ref));
+    }
+    lines.push(format!("commits {}", anchor.commit_count));
+    lines.push(format!("ai_percentage {:.1}", anchor.totals.ai_percentage));
+    for session_id in &anchor.session_ids {
+        lines.push(format!("session {}", session_id));
+    }
+
+    let payload = ReleaseNotePayload {
+        schema_version: RELEASE_SCHEMA_VERSION,
+        anchor,
+    };
+    let json = serde_json::to_string_pretty(&payload).unwrap_or_else(|_| "{}".to_string());
+    lines.push(NOTE_DIVIDER.to_string());
+    lines.push(json);
+    lines.join("\n")
+}
+
+/// Aggregate a release into an anchor, store it and (with `write_note`)
+/// attach it to the tagged commit. Re-running replaces the tag's anchor.
+pub async fn create_release_anchor(
+    db: &SqlitePool,
+    repo_id: i64,
+    tag: &str,
+    from_ref: Option<&str>,
+    write_note: bool,
+) -> Result<ReleaseAnchor, String> {
+    let repo_root = fetch_repo_root(db, repo_id).await?;
+    let (commit_sha, from_ref, commits) = {
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        release_range(&repo, tag, from_ref)?
+    };
+
+    let mut session_ids = BTreeSet::new();
+    for sha in &commits {
+        session_ids.extend(commit_session_ids(db, repo_id, sha).await);
+    }
+    let report =
+        build_attribution_report(db, repo_id, from_ref.as_deref(), Some(commit_sha.as_str()))
+            .await?;
+
+    let mut anchor = ReleaseAnchor {
+        tag: tag.to_string(),
+        commit_sha,
+        from_ref,
+        commit_count: commits.len() as u32,
+        session_ids: session_ids.into_iter().collect(),
+        totals: report.totals,
+        tools: report.tools,
+        generated_at: chrono::Utc::now().to_rfc3339(),
+        note_ref: None,
+    };
+
+    let mut note_hash = None;
+    if write_note {
+        let prefs = fetch_or_create_prefs(db, repo_id).await?;
+        let refs = NotesRefs::for_namespace(prefs.notes_namespace.as_deref())?;
+        anchor.note_ref = Some(refs.releases.clone());
+        let note_text = compress_note(&build_release_note(&anchor));
+        let note_text = if prefs.sign_notes {
+            sign_note(Path::new(&repo_root), &note_text)?
+        } else {
+            note_text
+        };
+
+        let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
+        let oid = Oid::from_str(&anchor.commit_sha).map_err(|e| e.to_string())?;
+        let signature = repo
+            .signature()
+            .or_else(|_| Signature::now("Narrative", "narrative@local"))
+            .map_err(|e| e.to_string())?;
+        repo.note(
+            &signature,
+            &signature,
+            Some(&refs.releases),
+            oid,
+            &note_text,
+            true,
+        )
+        .map_err(|e| e.to_string())?;
+        note_hash = Some(compute_note_hash(&note_text));
+    }
+
+    store_release_anchor(db, repo_id, &anchor, note_hash.as_deref()).await?;
+    Ok(anchor)
+}
+
+async fn store_release_anchor(
+    db: &SqlitePool,
+    repo_id: i64,
+    anchor: &ReleaseAnchor,
+    note_hash: Option<&str>,
+) -> Result<(), String> {
+    let summary_json = serde_json::to_string(anchor).map_err(|e| e.to_string())?;
+    sqlx::query(
+        r#"
+        INSERT INTO release_anchors (
+            repo_id, tag, commit_sha, from_ref, commit_count, session_count,
+            total_lines, ai_percentage, note_ref, note_hash, summary_json
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, tag) DO UPDATE SET
+            commit_sha = excluded.commit_sha,
+            from_ref = excluded.from_ref,
+            commit_count = excluded.commit_count,
+            session_count = excluded.session_count,
+            total_lines = excluded.total_lines,
+            ai_percentage = excluded.ai_percentage,
+            note_ref = excluded.note_ref,
+            note_hash = excluded.note_hash,
+            summary_json = excluded.summary_json,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&anchor.tag)
+    .bind(&anchor.commit_sha)
+    .bind(&anchor.from_ref)
+    .bind(anchor.commit_count as i64)
+    .bind(anchor.session_ids.len() as i64)
+    .bind(anchor.totals.total_lines as i64)
+    .bind(anchor.totals.ai_percentage)
+    .bind(&anchor.note_ref)
+    .bind(note_hash)
+    .bind(summary_json)
+    .execute(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(())
+}
+
+/// Stored release anchors, most recently created first
+pub async fn get_release_anchors(
+    db: &SqlitePool,
+    repo_id: i64,
+) -> Result<Vec<ReleaseAnchor>, String> {
+    let rows: Vec<(String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT summary_json, note_ref
+        FROM release_anchors
+        WHERE repo_id = ?
+        ORDER BY updated_at DESC, tag DESC
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    rows.into_iter()
+        .map(|(summary_json, note_ref)| {
+            let mut anchor: ReleaseAnchor =
+                serde_json::from_str(&summary_json).map_err(|e| e.to_string())?;
+            // Follows the note across namespace changes
+            anchor.note_ref = note_ref;
+            Ok(anchor)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::test_support::commit;
+
+    fn tag(repo: &Repository, name: &str, oid: Oid) {
+        let object = repo.find_object(oid, None).unwrap();
+        repo.tag_lightweight(name, &object, false).unwrap();
+    }
+
+    #[test]
+    fn releases_run_from_the_previous_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first");
+        tag(&repo, "v0.1.0", first);
+        let second = commit(&repo, "second");
+        let third = commit(&repo, "third");
+        tag(&repo, "v0.2.0", third);
+
+        let (sha, from_ref, commits) = release_range(&repo, "v0.2.0", None).unwrap();
+        assert_eq!(sha, third.to_string());
+        assert_eq!(from_ref.as_deref(), Some("v0.1.0"));
+        assert_eq!(commits, vec![second.to_string(), third.to_string()]);
+
+        // The first release covers the whole history
+        let (_, from_ref, commits) = release_range(&repo, "v0.1.0", None).unwrap();
+        assert_eq!(from_ref, None);
+        assert_eq!(commits, vec![first.to_string()]);
+
+        let (_, from_ref, commits) =
+            release_range(&repo, "v0.2.0", Some(&second.to_string())).unwrap();
+        assert_eq!(from_ref, Some(second.to_string()));
+        assert_eq!(commits, vec![third.to_string()]);
+        assert!(release_range(&repo, "v9.9.9", None).is_err());
+    }
+
+    #[test]
+    fn release_note_lists_sessions_before_the_payload() {
+        let anchor = ReleaseAnchor {
+            tag: "v1.0.0".to_string(),
+            commit_sha: "abc".to_string(),
+            from_ref: Some("v0.9.0".to_string()),
+            commit_count: 3,
+            session_ids: vec!["s1".to_string(), "s2".to_string()],
+            totals: ReportTotals {
+                ai_percentage: 40.0,
+                ..Default::default()
+            },
+            tools: Vec::new(),
+            generated_at: "2026-01-01T00:00:00Z".to_string(),
+            note_ref: None,
+        };
+        let note = build_release_note(&anchor);
+        let (head, json) = note.split_once(&format!("\n{}\n", NOTE_DIVIDER)).unwrap();
+        assert_eq!(
+            head,
+            "release v1.0.0\nsince v0.9.0\ncommits 3\nai_percentage 40.0\nsession s1\nsession s2"
+        );
+        let payload: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(payload["schemaVersion"], RELEASE_SCHEMA_VERSION);
+        assert_eq!(payload["sessionIds"][1], "s2");
+        let parsed: ReleaseAnchor = serde_json::from_value(payload).unwrap();
+        assert_eq!(parsed, anchor);
+    }
+}
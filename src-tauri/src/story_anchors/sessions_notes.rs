@@ -3,6 +3,7 @@
 use crate::story_anchors::notes_format::{split_note_sections, NOTE_DIVIDER};
 use crate::story_anchors::refs::SESSIONS_SCHEMA_VERSION;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Default)]
 pub struct ParsedSessionsNote {
@@ -125,3 +126,115 @@ pub fn build_sessions_note(
     lines.push(json);
     lines.join("\n")
 }
+
+/// Combine two hints for the same session; per field, the more recently
+/// imported hint wins and the other fills its gaps.
+fn merge_session_hint(a: &SessionHint, b: &SessionHint) -> SessionHint {
+    let (newer, older) = if b.imported_at_iso > a.imported_at_iso {
+        (b, a)
+    } else {
+        (a, b)
+    };
+    SessionHint {
+        session_id: newer.session_id.clone(),
+        tool: newer.tool.clone().or_else(|| older.tool.clone()),
+        model: newer.model.clone().or_else(|| older.model.clone()),
+        imported_at_iso: newer.imported_at_iso.clone(),
+    }
+}
+
+fn latest_import(note: &ParsedSessionsNote) -> Option<&str> {
+    note.session_hints
+        .iter()
+        .filter_map(|hint| hint.imported_at_iso.as_deref())
+        .max()
+}
+
+/// Merge two versions of a commit's sessions note: the union of their
+/// sessions, with hints merged per field (newest wins). Note-level fields
+/// come from the version with the most recent import.
+pub fn merge_sessions_notes(commit_sha: &str, ours: &str, theirs: &str) -> String {
+    let ours = parse_sessions_note(ours);
+    let theirs = parse_sessions_note(theirs);
+
+    let mut session_ids = ours.session_ids.clone();
+    session_ids.extend(theirs.session_ids.iter().cloned());
+
+    let mut hints: BTreeMap<String, SessionHint> = BTreeMap::new();
+    for hint in ours.session_hints.iter().chain(theirs.session_hints.iter()) {
+        let merged = match hints.get(&hint.session_id) {
+            Some(existing) => merge_session_hint(existing, hint),
+            None => hint.clone(),
+        };
+        hints.insert(hint.session_id.clone(), merged);
+    }
+
+    let (newer, older) = if latest_import(&theirs) > latest_import(&ours) {
+        (&theirs, &ours)
+    } else {
+        (&ours, &theirs)
+    };
+    let rewrite_key = newer
+        .rewrite_key
+        .clone()
+        .or_else(|| older.rewrite_key.clone());
+    let rewrite_algorithm = newer
+        .rewrite_algorithm
+        .clone()
+        .or_else(|| older.rewrite_algorithm.clone());
+
+    build_sessions_note(
+        commit_sha,
+        &session_ids,
+        (!hints.is_empty()).then(|| hints.into_values().collect()),
+        rewrite_key.as_deref(),
+        rewrite_algorithm.as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hint(session_id: &str, tool: Option<&str>, model: Option<&str>, at: &str) -> SessionHint {
+        SessionHint {
+            session_id: session_id.to_string(),
+            tool: tool.map(str::to_string),
+            model: model.map(str::to_string),
+            imported_at_iso: Some(at.to_string()),
+        }
+    }
+
+    #[test]
+    fn merge_unions_sessions_and_keeps_newest_hint_fields() {
+        let ours = build_sessions_note(
+            "abc",
+            &["s1".to_string(), "s2".to_string()],
+            Some(vec![
+                hint("s1", Some("codex"), Some("gpt-5"), "2026-05-01T00:00:00Z"),
+                hint("s2", Some("cursor"), None, "2026-05-01T00:00:00Z"),
+            ]),
+            Some("key-ours"),
+            Some("patch-id"),
+        );
+        let theirs = build_sessions_note(
+            "abc",
+            &["s1".to_string(), "s3".to_string()],
+            Some(vec![
+                hint("s1", Some("claude_code"), None, "2026-06-01T00:00:00Z"),
+                hint("s3", Some("codex"), None, "2026-06-01T00:00:00Z"),
+            ]),
+            Some("key-theirs"),
+            None,
+        );
+
+        let merged = parse_sessions_note(&merge_sessions_notes("abc", &ours, &theirs));
+        assert_eq!(merged.session_ids, vec!["s1", "s2", "s3"]);
+        let s1 = &merged.session_hints[0];
+        assert_eq!(s1.tool.as_deref(), Some("claude_code"));
+        assert_eq!(s1.model.as_deref(), Some("gpt-5"));
+        assert_eq!(merged.session_hints.len(), 3);
+        assert_eq!(merged.rewrite_key.as_deref(), Some("key-theirs"));
+        assert_eq!(merged.rewrite_algorithm.as_deref(), Some("patch-id"));
+    }
+}
@@ -1,11 +1,18 @@
 //! Import/export commit↔session Story Anchor notes.
 
+use crate::attribution::prefs::fetch_or_create_prefs;
 use crate::attribution::utils::fetch_repo_root;
-use crate::story_anchors::notes_format::compute_note_hash;
-use crate::story_anchors::refs::{SESSIONS_REF_CANONICAL, SESSIONS_SCHEMA_VERSION};
+use crate::story_anchors::notes_format::{
+    compress_note, compute_note_hash, note_schema_version, schema_compat, NoteSchemaCompat,
+};
+use crate::story_anchors::notes_signing::{
+    sign_note, verify_note_signature, SIGNATURE_UNSIGNED, SIGNATURE_VALID,
+};
+use crate::story_anchors::refs::{fetch_notes_refs, NotesRefs, SESSIONS_SCHEMA_VERSION};
 use crate::story_anchors::sessions_notes::{build_sessions_note, parse_sessions_note, SessionHint};
 use git2::{Oid, Repository, Signature};
 use serde::Serialize;
+use std::path::Path;
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -39,17 +46,18 @@ pub async fn import_sessions_note(
     commit_sha: &str,
 ) -> Result<SessionsNoteImportSummary, String> {
     let repo_root = fetch_repo_root(db, repo_id).await?;
+    let refs = fetch_notes_refs(db, repo_id).await?;
 
     let note_result: Result<Option<(String, String)>, String> = {
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let oid = Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
-        let out = match repo.find_note(Some(SESSIONS_REF_CANONICAL), oid) {
+        let out = match repo.find_note(Some(&refs.sessions), oid) {
             Ok(note) => {
                 let msg = note
                     .message()
                     .ok_or_else(|| "Sessions note is not valid UTF-8".to_string())?
                     .to_string();
-                Ok(Some((msg, SESSIONS_REF_CANONICAL.to_string())))
+                Ok(Some((msg, refs.sessions.clone())))
             }
             Err(_) => Ok(None),
         };
@@ -87,17 +95,42 @@ pub async fn import_sessions_note(
         });
     };
 
-    let parsed = parse_sessions_note(&message);
+    let mut parsed = parse_sessions_note(&message);
+    // Read the version even if a newer payload no longer parses
+    parsed.schema_version = note_schema_version(&message).or(parsed.schema_version);
+    if schema_compat(parsed.schema_version.as_deref(), SESSIONS_SCHEMA_VERSION)
+        == NoteSchemaCompat::Newer
+    {
+        // Keep existing links; a newer build can import this note
+        return Ok(SessionsNoteImportSummary {
+            commit_sha: commit_sha.to_string(),
+            status: "unsupported_schema".to_string(),
+            imported_sessions: 0,
+        });
+    }
+
+    let signature = verify_note_signature(Path::new(&repo_root), &message);
+    let prefs = fetch_or_create_prefs(db, repo_id).await?;
+    if prefs.require_signed_notes && signature.status != SIGNATURE_VALID {
+        // Keep existing links rather than trust a note anyone could have written
+        return Ok(SessionsNoteImportSummary {
+            commit_sha: commit_sha.to_string(),
+            status: "untrusted_signature".to_string(),
+            imported_sessions: 0,
+        });
+    }
     let note_hash = compute_note_hash(&message);
 
     // Store note meta
     sqlx::query(
         r#"
-        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version)
-        VALUES (?, ?, 'sessions', ?, ?, ?)
+        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version, signature_status, signer)
+        VALUES (?, ?, 'sessions', ?, ?, ?, ?, ?)
         ON CONFLICT(repo_id, commit_sha, note_kind, note_ref) DO UPDATE SET
             note_hash = excluded.note_hash,
             schema_version = excluded.schema_version,
+            signature_status = excluded.signature_status,
+            signer = excluded.signer,
             updated_at = CURRENT_TIMESTAMP
         "#,
     )
@@ -106,6 +139,8 @@ pub async fn import_sessions_note(
     .bind(&note_ref)
     .bind(&note_hash)
     .bind(parsed.schema_version.clone().or(Some(SESSIONS_SCHEMA_VERSION.to_string())))
+    .bind(&signature.status)
+    .bind(&signature.signer)
     .execute(db)
     .await
     .map_err(|e| e.to_string())?;
@@ -223,6 +258,8 @@ pub async fn export_sessions_note(
         });
     }
 
+    let prefs = fetch_or_create_prefs(db, repo_id).await?;
+    let refs = NotesRefs::for_namespace(prefs.notes_namespace.as_deref())?;
     let repo_root = fetch_repo_root(db, repo_id).await?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
     let rewrite_key = compute_rewrite_key(&repo, commit_sha).ok();
@@ -263,6 +300,17 @@ pub async fn export_sessions_note(
         rewrite_key.as_deref(),
         Some(REWRITE_KEY_ALGORITHM),
     );
+    let note_text = compress_note(&note_text);
+    let note_text = if prefs.sign_notes {
+        sign_note(Path::new(&repo_root), &note_text)?
+    } else {
+        note_text
+    };
+    let signature_status = if prefs.sign_notes {
+        SIGNATURE_VALID
+    } else {
+        SIGNATURE_UNSIGNED
+    };
 
     let note_hash = compute_note_hash(&note_text);
 
@@ -276,7 +324,7 @@ pub async fn export_sessions_note(
         repo.note(
             &signature,
             &signature,
-            Some(SESSIONS_REF_CANONICAL),
+            Some(&refs.sessions),
             oid,
             &note_text,
             true,
@@ -289,19 +337,22 @@ pub async fn export_sessions_note(
     // Track note meta
     sqlx::query(
         r#"
-        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version)
-        VALUES (?, ?, 'sessions', ?, ?, ?)
+        INSERT INTO story_anchor_note_meta (repo_id, commit_sha, note_kind, note_ref, note_hash, schema_version, signature_status, signer)
+        VALUES (?, ?, 'sessions', ?, ?, ?, ?, NULL)
         ON CONFLICT(repo_id, commit_sha, note_kind, note_ref) DO UPDATE SET
             note_hash = excluded.note_hash,
             schema_version = excluded.schema_version,
+            signature_status = excluded.signature_status,
+            signer = excluded.signer,
             updated_at = CURRENT_TIMESTAMP
         "#,
     )
     .bind(repo_id)
     .bind(commit_sha)
-    .bind(SESSIONS_REF_CANONICAL)
+    .bind(&refs.sessions)
     .bind(note_hash)
     .bind(SESSIONS_SCHEMA_VERSION)
+    .bind(signature_status)
     .execute(db)
     .await
     .ok();
@@ -311,3 +362,38 @@ pub async fn export_sessions_note(
         status: "exported".to_string(),
     })
 }
+
+/// Export the sessions notes of commits a session was just linked to, on a
+/// background task, if the repo's `auto_export_notes` preference is on.
+///
+/// Reads the preference without creating a prefs row; failures are logged.
+pub async fn auto_export_sessions_notes(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_shas: Vec<String>,
+) {
+    if commit_shas.is_empty() {
+        return;
+    }
+    let enabled: bool = sqlx::query_scalar(
+        "SELECT auto_export_notes != 0 FROM attribution_prefs WHERE repo_id = ?",
+    )
+    .bind(repo_id)
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or(false);
+    if !enabled {
+        return;
+    }
+
+    let db = db.clone();
+    tauri::async_runtime::spawn(async move {
+        for sha in commit_shas {
+            if let Err(e) = export_sessions_note(&db, repo_id, &sha).await {
+                eprintln!("Failed to auto-export sessions note for {}: {}", sha, e);
+            }
+        }
+    });
+}
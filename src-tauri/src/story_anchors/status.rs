@@ -1,9 +1,16 @@
 //! Status helpers for Story Anchors.
+//!
+//! Note presence for a list of commits is resolved in one pass over each notes
+//! ref and cached in `story_anchor_status_cache` against the refs' tips, so
+//! repeated lookups of long commit lists only hit the database until a notes
+//! ref moves.
 
-use crate::story_anchors::refs::{
-    ATTRIBUTION_REF_CANONICAL, LINEAGE_REF_CANONICAL, SESSIONS_REF_CANONICAL,
-};
+use crate::attribution::utils::fetch_repo_root;
+use crate::story_anchors::notes_merge::notes_entries;
+use crate::story_anchors::refs::{fetch_notes_refs, NotesRefs};
+use git2::Repository;
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -18,27 +25,59 @@ pub struct StoryAnchorCommitStatus {
     pub attribution_schema_version: Option<String>,
     pub sessions_schema_version: Option<String>,
     pub lineage_schema_version: Option<String>,
+    /// Signature found when the note was last imported or exported
+    /// ("unsigned", "valid", "invalid" or "unverified")
+    pub attribution_signature_status: Option<String>,
+    pub sessions_signature_status: Option<String>,
+    /// Key owner of the signature, when known
+    pub attribution_signer: Option<String>,
+    pub sessions_signer: Option<String>,
 }
 
-pub async fn get_commit_story_anchor_status(
-    db: &sqlx::SqlitePool,
-    repo_id: i64,
+/// note_kind, note_ref, schema_version, signature_status, signer
+type NoteMetaRow = (
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// commit_sha, note_kind, note_ref, schema_version, signature_status, signer
+type CommitNoteMetaRow = (
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+/// Refs whose tips key the status cache, in a fixed order
+fn status_notes_refs(refs: &NotesRefs) -> [&str; 4] {
+    let [attribution, legacy] = refs.attribution_import_precedence();
+    [
+        attribution,
+        legacy,
+        refs.sessions.as_str(),
+        refs.lineage.as_str(),
+    ]
+}
+
+/// Which Narrative notes a commit has in git
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotesPresence {
+    /// Ref holding the attribution note (canonical preferred over legacy)
+    pub attribution_ref: Option<String>,
+    pub has_sessions_note: bool,
+    pub has_lineage_note: bool,
+}
+
+fn status_from_meta(
     commit_sha: &str,
+    rows: Vec<NoteMetaRow>,
+    refs: &NotesRefs,
 ) -> StoryAnchorCommitStatus {
-    // story_anchor_note_meta is keyed by (repo_id, commit_sha, note_kind, note_ref)
-    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
-        r#"
-        SELECT note_kind, note_ref, schema_version
-        FROM story_anchor_note_meta
-        WHERE repo_id = ? AND commit_sha = ?
-        "#,
-    )
-    .bind(repo_id)
-    .bind(commit_sha)
-    .fetch_all(db)
-    .await
-    .unwrap_or_default();
-
     let mut out = StoryAnchorCommitStatus {
         commit_sha: commit_sha.to_string(),
         has_attribution_note: false,
@@ -50,19 +89,27 @@ pub async fn get_commit_story_anchor_status(
         attribution_schema_version: None,
         sessions_schema_version: None,
         lineage_schema_version: None,
+        attribution_signature_status: None,
+        sessions_signature_status: None,
+        attribution_signer: None,
+        sessions_signer: None,
     };
 
-    for (kind, note_ref, schema_version) in rows {
+    for (kind, note_ref, schema_version, signature_status, signer) in rows {
         match kind.as_str() {
             "attribution" => {
                 out.has_attribution_note = true;
                 out.attribution_ref = Some(note_ref);
                 out.attribution_schema_version = schema_version;
+                out.attribution_signature_status = signature_status;
+                out.attribution_signer = signer;
             }
             "sessions" => {
                 out.has_sessions_note = true;
                 out.sessions_ref = Some(note_ref);
                 out.sessions_schema_version = schema_version;
+                out.sessions_signature_status = signature_status;
+                out.sessions_signer = signer;
             }
             "lineage" => {
                 out.has_lineage_note = true;
@@ -73,16 +120,296 @@ pub async fn get_commit_story_anchor_status(
         }
     }
 
-    // If we have no cached meta, still provide canonical refs as hints.
+    // If we have no cached meta, still provide the repo's refs as hints.
     if out.attribution_ref.is_none() {
-        out.attribution_ref = Some(ATTRIBUTION_REF_CANONICAL.to_string());
+        out.attribution_ref = Some(refs.attribution.clone());
     }
     if out.sessions_ref.is_none() {
-        out.sessions_ref = Some(SESSIONS_REF_CANONICAL.to_string());
+        out.sessions_ref = Some(refs.sessions.clone());
     }
     if out.lineage_ref.is_none() {
-        out.lineage_ref = Some(LINEAGE_REF_CANONICAL.to_string());
+        out.lineage_ref = Some(refs.lineage.clone());
     }
 
     out
 }
+
+/// Overlay what git says onto a meta-derived status; meta for a note that is
+/// gone from git is dropped
+fn apply_presence(status: &mut StoryAnchorCommitStatus, presence: &NotesPresence) {
+    status.has_attribution_note = presence.attribution_ref.is_some();
+    if let Some(note_ref) = &presence.attribution_ref {
+        status.attribution_ref = Some(note_ref.clone());
+    } else {
+        status.attribution_schema_version = None;
+        status.attribution_signature_status = None;
+        status.attribution_signer = None;
+    }
+    status.has_sessions_note = presence.has_sessions_note;
+    if !presence.has_sessions_note {
+        status.sessions_schema_version = None;
+        status.sessions_signature_status = None;
+        status.sessions_signer = None;
+    }
+    status.has_lineage_note = presence.has_lineage_note;
+    if !presence.has_lineage_note {
+        status.lineage_schema_version = None;
+    }
+}
+
+/// Tips of the status notes refs, joined (`-` for a missing ref)
+pub fn notes_tip_key(repo: &Repository, refs: &NotesRefs) -> String {
+    status_notes_refs(refs)
+        .iter()
+        .map(|name| {
+            repo.refname_to_id(name)
+                .map(|oid| oid.to_string())
+                .unwrap_or_else(|_| "-".to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Note presence for `commit_shas`, reading each notes ref's tree once
+pub fn scan_notes_presence(
+    repo: &Repository,
+    refs: &NotesRefs,
+    commit_shas: &[String],
+) -> Result<HashMap<String, NotesPresence>, String> {
+    let mut entries: HashMap<&str, BTreeMap<String, git2::Oid>> = HashMap::new();
+    for name in status_notes_refs(refs) {
+        if let Ok(tip) = repo.refname_to_id(name) {
+            entries.insert(name, notes_entries(repo, tip)?);
+        }
+    }
+    let has = |name: &str, sha: &str| entries.get(name).is_some_and(|e| e.contains_key(sha));
+
+    Ok(commit_shas
+        .iter()
+        .map(|sha| {
+            let attribution_ref = refs
+                .attribution_import_precedence()
+                .into_iter()
+                .find(|name| has(*name, sha.as_str()))
+                .map(str::to_string);
+            let presence = NotesPresence {
+                attribution_ref,
+                has_sessions_note: has(refs.sessions.as_str(), sha.as_str()),
+                has_lineage_note: has(refs.lineage.as_str(), sha.as_str()),
+            };
+            (sha.clone(), presence)
+        })
+        .collect())
+}
+
+/// Note presence for `commit_shas` from the cache, scanning the notes refs
+/// for any commit not yet cached at the current tips
+async fn cached_notes_presence(
+    db: &sqlx::SqlitePool,
+    repo_root: &str,
+    repo_id: i64,
+    refs: &NotesRefs,
+    commit_shas: &[String],
+) -> Result<HashMap<String, NotesPresence>, String> {
+    let tip_key = {
+        let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+        notes_tip_key(&repo, refs)
+    };
+
+    let wanted: HashSet<&str> = commit_shas.iter().map(String::as_str).collect();
+    let cached: Vec<(String, Option<String>, bool, bool)> = sqlx::query_as(
+        r#"
+        SELECT commit_sha, attribution_ref, has_sessions_note, has_lineage_note
+        FROM story_anchor_status_cache
+        WHERE repo_id = ? AND notes_tip = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&tip_key)
+    .fetch_all(db)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut presence: HashMap<String, NotesPresence> = cached
+        .into_iter()
+        .filter(|(sha, ..)| wanted.contains(sha.as_str()))
+        .map(
+            |(sha, attribution_ref, has_sessions_note, has_lineage_note)| {
+                let row = NotesPresence {
+                    attribution_ref,
+                    has_sessions_note,
+                    has_lineage_note,
+                };
+                (sha, row)
+            },
+        )
+        .collect();
+
+    let mut missing: Vec<String> = wanted
+        .into_iter()
+        .filter(|sha| !presence.contains_key(*sha))
+        .map(str::to_string)
+        .collect();
+    if missing.is_empty() {
+        return Ok(presence);
+    }
+    missing.sort();
+
+    let scanned = {
+        let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+        scan_notes_presence(&repo, refs, &missing)?
+    };
+
+    let mut tx = db
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    sqlx::query("DELETE FROM story_anchor_status_cache WHERE repo_id = ? AND notes_tip != ?")
+        .bind(repo_id)
+        .bind(&tip_key)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    for (sha, row) in &scanned {
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO story_anchor_status_cache (
+                repo_id, commit_sha, notes_tip, attribution_ref, has_sessions_note,
+                has_lineage_note
+            )
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(sha)
+        .bind(&tip_key)
+        .bind(&row.attribution_ref)
+        .bind(row.has_sessions_note)
+        .bind(row.has_lineage_note)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    presence.extend(scanned);
+    Ok(presence)
+}
+
+/// Story Anchor status for many commits at once.
+///
+/// Note presence comes from git (via the status cache); schema versions and
+/// signatures from the note meta recorded on import/export. Falls back to
+/// meta alone when the repo can't be read.
+pub async fn get_story_anchor_statuses(
+    db: &sqlx::SqlitePool,
+    repo_id: i64,
+    commit_shas: &[String],
+) -> Vec<StoryAnchorCommitStatus> {
+    let refs = fetch_notes_refs(db, repo_id).await.unwrap_or_default();
+    let presence = match fetch_repo_root(db, repo_id).await {
+        Ok(repo_root) => cached_notes_presence(db, &repo_root, repo_id, &refs, commit_shas)
+            .await
+            .ok(),
+        Err(_) => None,
+    };
+
+    let rows: Vec<CommitNoteMetaRow> = sqlx::query_as(
+        r#"
+        SELECT commit_sha, note_kind, note_ref, schema_version, signature_status, signer
+        FROM story_anchor_note_meta
+        WHERE repo_id = ?
+        "#,
+    )
+    .bind(repo_id)
+    .fetch_all(db)
+    .await
+    .unwrap_or_default();
+
+    let wanted: HashSet<&str> = commit_shas.iter().map(String::as_str).collect();
+    let mut meta: HashMap<String, Vec<NoteMetaRow>> = HashMap::new();
+    for (sha, kind, note_ref, schema_version, signature_status, signer) in rows {
+        if wanted.contains(sha.as_str()) {
+            meta.entry(sha).or_default().push((
+                kind,
+                note_ref,
+                schema_version,
+                signature_status,
+                signer,
+            ));
+        }
+    }
+
+    commit_shas
+        .iter()
+        .map(|sha| {
+            let mut status = status_from_meta(sha, meta.remove(sha).unwrap_or_default(), &refs);
+            if let Some(found) = presence.as_ref().and_then(|p| p.get(sha)) {
+                apply_presence(&mut status, found);
+            }
+            status
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::{
+        ATTRIBUTION_REF_LEGACY_NARRATIVE, LINEAGE_REF_CANONICAL, SESSIONS_REF_CANONICAL,
+    };
+    use crate::story_anchors::test_support::{commit, note};
+
+    #[test]
+    fn statuses_come_from_git_and_are_cached_per_notes_tip() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, "first");
+        let second = commit(&repo, "second");
+        note(&repo, SESSIONS_REF_CANONICAL, first, "note");
+        note(&repo, ATTRIBUTION_REF_LEGACY_NARRATIVE, second, "note");
+        let shas = vec![first.to_string(), second.to_string()];
+
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            sqlx::query("INSERT INTO repos (id, path) VALUES (1, ?)")
+                .bind(dir.path().to_string_lossy().to_string())
+                .execute(&pool)
+                .await
+                .expect("insert repo");
+
+            let statuses = get_story_anchor_statuses(&pool, 1, &shas).await;
+            assert!(statuses[0].has_sessions_note);
+            assert!(!statuses[0].has_attribution_note);
+            assert!(statuses[1].has_attribution_note);
+            assert_eq!(
+                statuses[1].attribution_ref.as_deref(),
+                Some(ATTRIBUTION_REF_LEGACY_NARRATIVE)
+            );
+
+            let cached: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM story_anchor_status_cache")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(cached, 2);
+
+            // A new note moves the tip: recomputed, old rows dropped
+            note(&repo, LINEAGE_REF_CANONICAL, second, "note");
+            let statuses = get_story_anchor_statuses(&pool, 1, &shas[1..]).await;
+            assert!(statuses[0].has_lineage_note);
+            let tips: Vec<String> =
+                sqlx::query_scalar("SELECT DISTINCT notes_tip FROM story_anchor_status_cache")
+                    .fetch_all(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(tips, vec![notes_tip_key(&repo, &NotesRefs::default())]);
+        });
+    }
+}
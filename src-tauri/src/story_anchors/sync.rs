@@ -0,0 +1,437 @@
+//! Push/fetch Narrative notes refs to and from a remote.
+//!
+//! Git does not transfer notes unless asked, so story anchors stay local
+//! until someone runs `git push origin 'refs/notes/*'`. These helpers sync
+//! every ref under the repo's notes namespace (`refs/notes/narrative/*` by
+//! default) over git2, authenticating with the SSH agent or the user's
+//! credential helper, and report the outcome per ref.
+//!
+//! Fetched refs land in `refs/narrative/remotes/<remote>/<name>` and only
+//! fast-forward the local ref, so notes that have not been pushed yet are
+//! never overwritten. Diverged refs are reported and left for the user.
+
+use crate::story_anchors::fetched_notes::FetchedNotesImportSummary;
+use crate::story_anchors::refs::{NotesRefs, NOTES_REMOTE_TRACKING_PREFIX};
+use git2::{
+    Cred, CredentialType, Direction, FetchOptions, Oid, PushOptions, Remote, RemoteCallbacks,
+    Repository,
+};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+pub const SYNC_STATUS_CREATED: &str = "created";
+pub const SYNC_STATUS_UPDATED: &str = "updated";
+pub const SYNC_STATUS_UP_TO_DATE: &str = "up_to_date";
+/// Fetch: the local ref has notes the remote does not
+pub const SYNC_STATUS_LOCAL_AHEAD: &str = "local_ahead";
+/// Fetch: local and remote both have notes the other lacks
+pub const SYNC_STATUS_DIVERGED: &str = "diverged";
+/// Push: the remote has notes this clone has not fetched
+pub const SYNC_STATUS_REJECTED: &str = "rejected";
+pub const SYNC_STATUS_FAILED: &str = "failed";
+
+/// Credential callbacks tried before giving up (git2 retries indefinitely)
+const MAX_CREDENTIAL_ATTEMPTS: u32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesRefSyncResult {
+    pub ref_name: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotesSyncSummary {
+    pub remote_name: String,
+    pub refs: Vec<NotesRefSyncResult>,
+    /// Notes imported after a fetch (`import_notes_after_fetch`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub imported: Option<FetchedNotesImportSummary>,
+}
+
+fn ref_result(ref_name: &str, status: &str, message: Option<String>) -> NotesRefSyncResult {
+    NotesRefSyncResult {
+        ref_name: ref_name.to_string(),
+        status: status.to_string(),
+        message,
+    }
+}
+
+/// "origin" if configured, otherwise the first remote
+pub fn default_remote_name(repo: &Repository) -> Option<String> {
+    let remotes = repo.remotes().ok()?;
+    let name = remotes
+        .iter()
+        .flatten()
+        .find(|name| *name == "origin")
+        .or_else(|| remotes.iter().flatten().next())?;
+    Some(name.to_string())
+}
+
+/// Callbacks that authenticate like the git CLI: SSH agent for SSH remotes,
+/// the configured credential helper for HTTPS, then git2's default
+fn remote_callbacks<'a>(config: Option<git2::Config>) -> RemoteCallbacks<'a> {
+    let mut attempts = 0;
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username_from_url, allowed| {
+        attempts += 1;
+        if attempts > MAX_CREDENTIAL_ATTEMPTS {
+            return Err(git2::Error::from_str(
+                "authentication failed; check your SSH agent or credential helper",
+            ));
+        }
+        let username = username_from_url.unwrap_or("git");
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username);
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(config) = &config {
+                if let Ok(cred) = Cred::credential_helper(config, url, username_from_url) {
+                    return Ok(cred);
+                }
+            }
+        }
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Local refs under the notes namespace and their targets
+pub(crate) fn local_notes_refs(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+) -> Result<BTreeMap<String, Oid>, String> {
+    let mut refs = BTreeMap::new();
+    let glob = format!("{}*", notes_refs.prefix);
+    for reference in repo.references_glob(&glob).map_err(|e| e.to_string())? {
+        let reference = reference.map_err(|e| e.to_string())?;
+        if let (Some(name), Some(oid)) = (reference.name(), reference.target()) {
+            refs.insert(name.to_string(), oid);
+        }
+    }
+    Ok(refs)
+}
+
+/// The remote's refs under the notes namespace and their targets
+fn remote_notes_refs(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+    remote: &mut Remote,
+    direction: Direction,
+) -> Result<BTreeMap<String, Oid>, String> {
+    let connection = remote
+        .connect_auth(direction, Some(remote_callbacks(repo.config().ok())), None)
+        .map_err(|e| format!("Failed to connect to remote: {}", e))?;
+    let refs = connection
+        .list()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|head| head.name().starts_with(&notes_refs.prefix))
+        .map(|head| (head.name().to_string(), head.oid()))
+        .collect();
+    Ok(refs)
+}
+
+/// Where a fetched remote notes ref is kept
+pub fn notes_tracking_ref(notes_refs: &NotesRefs, remote_name: &str, ref_name: &str) -> String {
+    format!(
+        "{NOTES_REMOTE_TRACKING_PREFIX}{remote_name}/{}",
+        ref_name.trim_start_matches(notes_refs.prefix.as_str())
+    )
+}
+
+/// Push local Narrative notes refs that the remote lacks or is behind on
+pub fn push_notes(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+    remote_name: &str,
+) -> Result<Vec<NotesRefSyncResult>, String> {
+    let local = local_notes_refs(repo, notes_refs)?;
+    if local.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
+    let remote_refs = remote_notes_refs(repo, notes_refs, &mut remote, Direction::Push)?;
+
+    let mut results = Vec::new();
+    let mut to_push = Vec::new();
+    for (name, oid) in &local {
+        match remote_refs.get(name) {
+            Some(remote_oid) if remote_oid == oid => {
+                results.push(ref_result(name, SYNC_STATUS_UP_TO_DATE, None));
+            }
+            Some(remote_oid) if !repo.graph_descendant_of(*oid, *remote_oid).unwrap_or(false) => {
+                results.push(ref_result(
+                    name,
+                    SYNC_STATUS_REJECTED,
+                    Some("Remote has notes this clone has not fetched; fetch first".to_string()),
+                ));
+            }
+            _ => to_push.push(name.clone()),
+        }
+    }
+
+    if !to_push.is_empty() {
+        let refspecs: Vec<String> = to_push
+            .iter()
+            .map(|name| format!("{name}:{name}"))
+            .collect();
+        let mut statuses: HashMap<String, Option<String>> = HashMap::new();
+        let push_result = {
+            let mut callbacks = remote_callbacks(repo.config().ok());
+            callbacks.push_update_reference(|refname, status| {
+                statuses.insert(refname.to_string(), status.map(str::to_string));
+                Ok(())
+            });
+            let mut options = PushOptions::new();
+            options.remote_callbacks(callbacks);
+            remote.push(&refspecs, Some(&mut options))
+        };
+
+        for name in &to_push {
+            results.push(match (statuses.get(name), &push_result) {
+                (Some(None), _) if remote_refs.contains_key(name) => {
+                    ref_result(name, SYNC_STATUS_UPDATED, None)
+                }
+                (Some(None), _) => ref_result(name, SYNC_STATUS_CREATED, None),
+                (Some(Some(reason)), _) => {
+                    ref_result(name, SYNC_STATUS_REJECTED, Some(reason.clone()))
+                }
+                (None, Err(e)) => ref_result(name, SYNC_STATUS_FAILED, Some(e.to_string())),
+                (None, Ok(())) => ref_result(
+                    name,
+                    SYNC_STATUS_FAILED,
+                    Some("Remote did not report a status".to_string()),
+                ),
+            });
+        }
+    }
+
+    results.sort_by(|a, b| a.ref_name.cmp(&b.ref_name));
+    Ok(results)
+}
+
+/// Fast-forward (or create) a local notes ref to a fetched target
+fn integrate_fetched_ref(repo: &Repository, ref_name: &str, fetched: Oid) -> NotesRefSyncResult {
+    let outcome = match repo.refname_to_id(ref_name).ok() {
+        None => repo
+            .reference(ref_name, fetched, false, "narrative: fetch notes")
+            .map(|_| SYNC_STATUS_CREATED),
+        Some(local) if local == fetched => Ok(SYNC_STATUS_UP_TO_DATE),
+        Some(local) => match repo.graph_descendant_of(fetched, local) {
+            Ok(true) => repo
+                .reference_matching(
+                    ref_name,
+                    fetched,
+                    true,
+                    local,
+                    "narrative: fast-forward notes",
+                )
+                .map(|_| SYNC_STATUS_UPDATED),
+            Ok(false) if repo.graph_descendant_of(local, fetched).unwrap_or(false) => {
+                Ok(SYNC_STATUS_LOCAL_AHEAD)
+            }
+            Ok(false) => Ok(SYNC_STATUS_DIVERGED),
+            Err(e) => Err(e),
+        },
+    };
+
+    match outcome {
+        Ok(SYNC_STATUS_DIVERGED) => ref_result(
+            ref_name,
+            SYNC_STATUS_DIVERGED,
+            Some("Local and remote notes both changed; merge them to combine".to_string()),
+        ),
+        Ok(status) => ref_result(ref_name, status, None),
+        Err(e) => ref_result(ref_name, SYNC_STATUS_FAILED, Some(e.to_string())),
+    }
+}
+
+/// Fetch the remote's Narrative notes refs and fast-forward local ones
+pub fn fetch_notes(
+    repo: &Repository,
+    notes_refs: &NotesRefs,
+    remote_name: &str,
+) -> Result<Vec<NotesRefSyncResult>, String> {
+    let mut remote = repo
+        .find_remote(remote_name)
+        .map_err(|e| format!("Remote '{}' not found: {}", remote_name, e))?;
+    let remote_refs = remote_notes_refs(repo, notes_refs, &mut remote, Direction::Fetch)?;
+    if remote_refs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let refspecs: Vec<String> = remote_refs
+        .keys()
+        .map(|name| {
+            let tracking = notes_tracking_ref(notes_refs, remote_name, name);
+            format!("+{name}:{tracking}")
+        })
+        .collect();
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks(repo.config().ok()));
+    remote
+        .fetch(
+            &refspecs,
+            Some(&mut options),
+            Some("narrative: fetch notes"),
+        )
+        .map_err(|e| format!("Failed to fetch notes: {}", e))?;
+
+    Ok(remote_refs
+        .iter()
+        .map(|(name, oid)| integrate_fetched_ref(repo, name, *oid))
+        .collect())
+}
+
+fn resolve_remote(repo: &Repository, remote: Option<String>) -> Result<String, String> {
+    remote
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| default_remote_name(repo))
+        .ok_or_else(|| "No remote configured for repository".to_string())
+}
+
+/// Push notes from the repo at `repo_root` (blocking; does network I/O)
+pub fn push_notes_to_remote(
+    repo_root: &str,
+    notes_refs: &NotesRefs,
+    remote: Option<String>,
+) -> Result<NotesSyncSummary, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let remote_name = resolve_remote(&repo, remote)?;
+    let refs = push_notes(&repo, notes_refs, &remote_name)?;
+    Ok(NotesSyncSummary {
+        remote_name,
+        refs,
+        imported: None,
+    })
+}
+
+/// Fetch notes into the repo at `repo_root` (blocking; does network I/O)
+pub fn fetch_notes_from_remote(
+    repo_root: &str,
+    notes_refs: &NotesRefs,
+    remote: Option<String>,
+) -> Result<NotesSyncSummary, String> {
+    let repo = Repository::open(repo_root).map_err(|e| e.to_string())?;
+    let remote_name = resolve_remote(&repo, remote)?;
+    let refs = fetch_notes(&repo, notes_refs, &remote_name)?;
+    Ok(NotesSyncSummary {
+        remote_name,
+        refs,
+        imported: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::story_anchors::refs::SESSIONS_REF_CANONICAL;
+    use git2::Signature;
+
+    fn commit_with_note(repo: &Repository, note: &str) -> Oid {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let oid = repo
+            .commit(Some("HEAD"), &sig, &sig, note, &tree, &parents)
+            .unwrap();
+        repo.note(&sig, &sig, Some(SESSIONS_REF_CANONICAL), oid, note, false)
+            .unwrap();
+        oid
+    }
+
+    fn status_of<'a>(results: &'a [NotesRefSyncResult], ref_name: &str) -> &'a str {
+        &results
+            .iter()
+            .find(|r| r.ref_name == ref_name)
+            .expect("ref result")
+            .status
+    }
+
+    #[test]
+    fn push_then_fetch_round_trips_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let refs = NotesRefs::default();
+        let remote_path = dir.path().join("remote.git");
+        Repository::init_bare(&remote_path).unwrap();
+        let remote_url = remote_path.to_string_lossy().to_string();
+
+        let alice = Repository::init(dir.path().join("alice")).unwrap();
+        alice.remote("origin", &remote_url).unwrap();
+        let bob = Repository::init(dir.path().join("bob")).unwrap();
+        bob.remote("origin", &remote_url).unwrap();
+
+        commit_with_note(&alice, "first");
+        let pushed = push_notes(&alice, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&pushed, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_CREATED
+        );
+        let again = push_notes(&alice, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&again, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_UP_TO_DATE
+        );
+
+        let fetched = fetch_notes(&bob, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&fetched, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_CREATED
+        );
+        assert_eq!(
+            bob.refname_to_id(SESSIONS_REF_CANONICAL).unwrap(),
+            alice.refname_to_id(SESSIONS_REF_CANONICAL).unwrap()
+        );
+
+        commit_with_note(&alice, "second");
+        push_notes(&alice, &refs, "origin").unwrap();
+        let fetched = fetch_notes(&bob, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&fetched, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_UPDATED
+        );
+        assert!(bob
+            .find_reference(&notes_tracking_ref(&refs, "origin", SESSIONS_REF_CANONICAL))
+            .is_ok());
+    }
+
+    #[test]
+    fn push_rejects_when_remote_has_unfetched_notes() {
+        let dir = tempfile::tempdir().unwrap();
+        let refs = NotesRefs::default();
+        let remote_path = dir.path().join("remote.git");
+        Repository::init_bare(&remote_path).unwrap();
+        let remote_url = remote_path.to_string_lossy().to_string();
+
+        let alice = Repository::init(dir.path().join("alice")).unwrap();
+        alice.remote("origin", &remote_url).unwrap();
+        let bob = Repository::init(dir.path().join("bob")).unwrap();
+        bob.remote("origin", &remote_url).unwrap();
+
+        commit_with_note(&alice, "alice");
+        push_notes(&alice, &refs, "origin").unwrap();
+        commit_with_note(&bob, "bob");
+
+        let pushed = push_notes(&bob, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&pushed, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_REJECTED
+        );
+        let fetched = fetch_notes(&bob, &refs, "origin").unwrap();
+        assert_eq!(
+            status_of(&fetched, SESSIONS_REF_CANONICAL),
+            SYNC_STATUS_DIVERGED
+        );
+    }
+}
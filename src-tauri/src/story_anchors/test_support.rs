@@ -0,0 +1,30 @@
+//! Git fixtures shared by the Story Anchor tests
+
+use git2::{Oid, Repository, Signature};
+
+/// Commit the index on top of HEAD and move HEAD to it
+pub fn commit(repo: &Repository, message: &str) -> Oid {
+    commit_to(repo, message, Some("HEAD"))
+}
+
+/// Commit the index on top of HEAD, updating `update_ref` (None leaves the
+/// commit unreferenced)
+pub fn commit_to(repo: &Repository, message: &str, update_ref: Option<&str>) -> Oid {
+    let sig = Signature::now("Test", "test@example.com").unwrap();
+    let tree = repo
+        .find_tree(repo.index().unwrap().write_tree().unwrap())
+        .unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(update_ref, &sig, &sig, message, &tree, &parents)
+        .unwrap()
+}
+
+/// Write (or overwrite) the note on `oid` under `note_ref`; returns the new
+/// tip of `note_ref`
+pub fn note(repo: &Repository, note_ref: &str, oid: Oid, text: &str) -> Oid {
+    let sig = Signature::now("Test", "test@example.com").unwrap();
+    repo.note(&sig, &sig, Some(note_ref), oid, text, true)
+        .unwrap();
+    repo.refname_to_id(note_ref).unwrap()
+}
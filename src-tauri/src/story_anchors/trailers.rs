@@ -0,0 +1,182 @@
+//! Commit message trailers naming the sessions behind staged changes.
+//!
+//! When a repo's `commit_trailers` preference is on, the prepare-commit-msg
+//! hook appends one `Narrative-Session` trailer per session anchored to the
+//! staged files and one `Assisted-by` trailer per tool/model they used:
+//!
+//! ```text
+//! Narrative-Session: 3f2a9c1e-session
+//! Assisted-by: claude_code (claude-sonnet-4)
+//! ```
+
+use crate::attribution::utils::fetch_session_meta;
+use crate::wip_anchors::sessions_for_staged_changes;
+use sqlx::SqlitePool;
+use std::path::Path;
+
+pub const SESSION_TRAILER: &str = "Narrative-Session";
+pub const ASSISTED_BY_TRAILER: &str = "Assisted-by";
+
+/// A session to credit in the commit message
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrailerSession {
+    pub session_id: String,
+    pub tool: Option<String>,
+    pub model: Option<String>,
+}
+
+/// Trailer lines for sessions, in order: session ids first, then one
+/// `Assisted-by` per distinct tool/model.
+pub fn build_trailers(sessions: &[TrailerSession]) -> Vec<String> {
+    let mut trailers: Vec<String> = Vec::new();
+    for session in sessions {
+        let line = format!("{}: {}", SESSION_TRAILER, session.session_id);
+        if !trailers.contains(&line) {
+            trailers.push(line);
+        }
+    }
+
+    let mut assisted: Vec<String> = Vec::new();
+    for session in sessions {
+        let Some(tool) = session.tool.as_deref().filter(|t| !t.is_empty()) else {
+            continue;
+        };
+        let line = match session.model.as_deref().filter(|m| !m.is_empty()) {
+            Some(model) => format!("{}: {} ({})", ASSISTED_BY_TRAILER, tool, model),
+            None => format!("{}: {}", ASSISTED_BY_TRAILER, tool),
+        };
+        if !assisted.contains(&line) {
+            assisted.push(line);
+        }
+    }
+    trailers.extend(assisted);
+    trailers
+}
+
+/// `Token: value` with a token git would accept as a trailer key
+fn is_trailer_line(line: &str) -> bool {
+    line.split_once(": ").is_some_and(|(token, _)| {
+        !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Append trailers to a commit message, skipping ones already present.
+///
+/// Trailers join an existing trailer paragraph or start a new one, and stay
+/// above the `#` comment block (and any scissors-cut diff) git adds for the
+/// editor. An empty message keeps its first line free for the subject.
+pub fn append_trailers(message: &str, trailers: &[String]) -> String {
+    let lines: Vec<&str> = message.lines().collect();
+    // `git commit -v` puts the diff below a scissors line; none of it is message
+    let mut split = lines
+        .iter()
+        .position(|line| line.starts_with('#') && line.contains(" >8 "))
+        .unwrap_or(lines.len());
+    while split > 0 && (lines[split - 1].starts_with('#') || lines[split - 1].trim().is_empty()) {
+        split -= 1;
+    }
+    let (body, comments) = lines.split_at(split);
+
+    let missing: Vec<&String> = trailers
+        .iter()
+        .filter(|t| !body.iter().any(|line| line.trim() == t.as_str()))
+        .collect();
+    if missing.is_empty() {
+        return message.to_string();
+    }
+
+    let mut out: Vec<String> = body.iter().map(|line| line.to_string()).collect();
+    match body.last() {
+        None => out.extend([String::new(), String::new()]),
+        Some(last) if !is_trailer_line(last) || body.len() == 1 => out.push(String::new()),
+        Some(_) => {}
+    }
+    out.extend(missing.into_iter().cloned());
+    out.extend(comments.iter().map(|line| line.to_string()));
+
+    let mut updated = out.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Trailers for the sessions anchored to a repo's staged changes
+pub async fn staged_commit_trailers(
+    db: &SqlitePool,
+    repo_id: i64,
+    repo_root: &Path,
+) -> Result<Vec<String>, String> {
+    let session_ids = sessions_for_staged_changes(db, repo_id, repo_root).await?;
+    let mut sessions = Vec::with_capacity(session_ids.len());
+    for session_id in session_ids {
+        let meta = fetch_session_meta(db, &session_id).await?;
+        sessions.push(TrailerSession {
+            session_id,
+            tool: meta.as_ref().and_then(|m| m.tool.clone()),
+            model: meta.and_then(|m| m.model),
+        });
+    }
+    Ok(build_trailers(&sessions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, tool: Option<&str>, model: Option<&str>) -> TrailerSession {
+        TrailerSession {
+            session_id: id.to_string(),
+            tool: tool.map(str::to_string),
+            model: model.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn builds_session_and_deduped_assisted_by_trailers() {
+        let trailers = build_trailers(&[
+            session("s1", Some("claude_code"), Some("claude-sonnet-4")),
+            session("s2", Some("claude_code"), Some("claude-sonnet-4")),
+            session("s3", Some("codex"), None),
+            session("s4", None, None),
+        ]);
+        assert_eq!(
+            trailers,
+            vec![
+                "Narrative-Session: s1",
+                "Narrative-Session: s2",
+                "Narrative-Session: s3",
+                "Narrative-Session: s4",
+                "Assisted-by: claude_code (claude-sonnet-4)",
+                "Assisted-by: codex",
+            ]
+        );
+    }
+
+    #[test]
+    fn appends_above_comments_and_joins_trailer_paragraphs() {
+        let trailers = vec!["Narrative-Session: s1".to_string()];
+
+        let message =
+            "Fix parser\n\n# Please enter the commit message\n# Lines starting with '#'\n";
+        assert_eq!(
+            append_trailers(message, &trailers),
+            "Fix parser\n\nNarrative-Session: s1\n\n# Please enter the commit message\n# Lines starting with '#'\n"
+        );
+
+        let message = "Fix parser\n\nBody text.\n\nSigned-off-by: A <a@example.com>\n";
+        assert_eq!(
+            append_trailers(message, &trailers),
+            "Fix parser\n\nBody text.\n\nSigned-off-by: A <a@example.com>\nNarrative-Session: s1\n"
+        );
+
+        // Subject line left free for the editor
+        let message = "\n# Please enter the commit message\n";
+        assert_eq!(
+            append_trailers(message, &trailers),
+            "\n\nNarrative-Session: s1\n\n# Please enter the commit message\n"
+        );
+
+        // Already present (e.g. amend): unchanged
+        let message = "Fix parser\n\nNarrative-Session: s1\n";
+        assert_eq!(append_trailers(message, &trailers), message);
+    }
+}
@@ -0,0 +1,601 @@
+//! Work-in-progress anchors: sessions linked to uncommitted changes.
+//!
+//! A session often ends well before its work is committed, so linking finds
+//! no commit in its time window. Such a session is anchored to a snapshot of
+//! the dirty working tree instead: the changed files it touched and their
+//! content (blob) hashes, hashed together like a stash. When the post-commit
+//! hook fires, each open anchor whose files are in the new commit gets a real
+//! session link; the anchor is resolved once all of its files are committed.
+//! Committing the exact anchored content links with full confidence, files
+//! edited further before the commit link lower.
+
+use crate::DbState;
+use chrono::{DateTime, Duration, Utc};
+use git2::{Delta, ObjectType, Oid, Repository, Status, StatusOptions};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::State;
+
+/// Sessions that ended longer ago than this are not anchored on import
+pub const AUTO_ANCHOR_WINDOW_MINUTES: i64 = 120;
+
+/// Open anchors older than this are no longer upgraded
+pub const MAX_ANCHOR_AGE_DAYS: i64 = 14;
+
+/// Confidence of a link whose anchored content changed before the commit
+const WIP_LINK_BASE_CONFIDENCE: f64 = 0.7;
+
+/// A changed file in a working-tree snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WipFile {
+    /// Path relative to the repo root
+    pub path: String,
+    /// Blob hash of the working-tree content (None if the file was deleted)
+    pub blob: Option<String>,
+    /// Commit that included the file, once committed
+    #[serde(default)]
+    pub committed_sha: Option<String>,
+}
+
+/// A session anchored to uncommitted changes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WipAnchor {
+    pub id: i64,
+    pub session_id: String,
+    pub snapshot_hash: String,
+    /// HEAD when the snapshot was taken
+    pub base_sha: Option<String>,
+    pub files: Vec<WipFile>,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+    pub resolved_commit_sha: Option<String>,
+}
+
+type WipAnchorRow = (
+    i64,
+    String,
+    String,
+    Option<String>,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+fn anchor_from_row(row: WipAnchorRow) -> WipAnchor {
+    let (id, session_id, snapshot_hash, base_sha, files, created_at, resolved_at, resolved_sha) =
+        row;
+    WipAnchor {
+        id,
+        session_id,
+        snapshot_hash,
+        base_sha,
+        files: serde_json::from_str(&files).unwrap_or_default(),
+        created_at,
+        resolved_at,
+        resolved_commit_sha: resolved_sha,
+    }
+}
+
+/// Session file path relative to the working tree (as git status reports it)
+fn relative_path(workdir: &Path, file: &str) -> String {
+    Path::new(file)
+        .strip_prefix(workdir)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_else(|_| file.trim_start_matches("./").to_string())
+}
+
+/// Changed files in the working tree (staged or not, including untracked).
+///
+/// Limited to `session_files` when any are given.
+pub fn snapshot_worktree(
+    repo: &Repository,
+    session_files: &[String],
+) -> Result<Vec<WipFile>, git2::Error> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("Repository has no working tree"))?;
+    let scope: Vec<String> = session_files
+        .iter()
+        .map(|file| relative_path(workdir, file))
+        .collect();
+
+    let mut options = StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+
+    let mut files = Vec::new();
+    for entry in repo.statuses(Some(&mut options))?.iter() {
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        if !scope.is_empty() && !scope.iter().any(|s| s == path) {
+            continue;
+        }
+        let full_path = workdir.join(path);
+        let deleted = entry
+            .status()
+            .intersects(Status::WT_DELETED | Status::INDEX_DELETED)
+            && !full_path.exists();
+        let blob = if deleted {
+            None
+        } else {
+            Some(Oid::hash_file(ObjectType::Blob, &full_path)?.to_string())
+        };
+        files.push(WipFile {
+            path: path.to_string(),
+            blob,
+            committed_sha: None,
+        });
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Stash-like hash of a snapshot: the git blob hash of its path/blob manifest
+pub fn snapshot_hash(files: &[WipFile]) -> String {
+    let manifest: String = files
+        .iter()
+        .map(|f| format!("{}\0{}\n", f.path, f.blob.as_deref().unwrap_or("-")))
+        .collect();
+    Oid::hash_object(ObjectType::Blob, manifest.as_bytes())
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+/// Paths a commit changed against its first parent, with their new blob
+/// hashes (None for deletions)
+pub fn commit_changes(
+    repo: &Repository,
+    commit_sha: &str,
+) -> Result<HashMap<String, Option<String>>, git2::Error> {
+    let commit = repo.find_commit(Oid::from_str(commit_sha)?)?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+
+    let mut changes = HashMap::new();
+    for delta in diff.deltas() {
+        let file = if delta.status() == Delta::Deleted {
+            delta.old_file()
+        } else {
+            delta.new_file()
+        };
+        let Some(path) = file.path() else {
+            continue;
+        };
+        let blob = (delta.status() != Delta::Deleted).then(|| file.id().to_string());
+        changes.insert(path.to_string_lossy().replace('\\', "/"), blob);
+    }
+    Ok(changes)
+}
+
+/// Paths staged in the index: changed against HEAD's tree (every indexed
+/// path before the first commit)
+pub fn staged_paths(repo: &Repository) -> Result<HashSet<String>, git2::Error> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+    let mut paths = HashSet::new();
+    for delta in diff.deltas() {
+        let file = if delta.status() == Delta::Deleted {
+            delta.old_file()
+        } else {
+            delta.new_file()
+        };
+        if let Some(path) = file.path() {
+            paths.insert(path.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    Ok(paths)
+}
+
+/// Mark the anchored files a commit includes; returns the link confidence
+/// when it includes any.
+fn apply_commit(
+    files: &mut [WipFile],
+    commit_sha: &str,
+    changes: &HashMap<String, Option<String>>,
+) -> Option<f64> {
+    let mut committed = 0;
+    let mut exact = 0;
+    for file in files.iter_mut().filter(|f| f.committed_sha.is_none()) {
+        if let Some(blob) = changes.get(&file.path) {
+            committed += 1;
+            if *blob == file.blob {
+                exact += 1;
+            }
+            file.committed_sha = Some(commit_sha.to_string());
+        }
+    }
+    (committed > 0).then(|| {
+        WIP_LINK_BASE_CONFIDENCE
+            + (1.0 - WIP_LINK_BASE_CONFIDENCE) * exact as f64 / committed as f64
+    })
+}
+
+async fn fetch_anchor(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    snapshot_hash: &str,
+) -> Result<Option<WipAnchor>, String> {
+    let row: Option<WipAnchorRow> = sqlx::query_as(
+        r#"
+        SELECT id, session_id, snapshot_hash, base_sha, files, created_at, resolved_at, resolved_commit_sha
+        FROM wip_anchors
+        WHERE repo_id = ? AND session_id = ? AND snapshot_hash = ?
+        "#,
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .bind(snapshot_hash)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(row.map(anchor_from_row))
+}
+
+/// Anchor a session to the current dirty working tree.
+///
+/// Returns None when none of the session's files (or, if it recorded none,
+/// no files at all) have uncommitted changes.
+async fn anchor_session(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    session_files: &[String],
+) -> Result<Option<WipAnchor>, String> {
+    let repo_root = crate::attribution::utils::fetch_repo_root(pool, repo_id).await?;
+    let repo =
+        Repository::open(&repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let files = snapshot_worktree(&repo, session_files)
+        .map_err(|e| format!("Failed to read working tree: {}", e))?;
+    if files.is_empty() {
+        return Ok(None);
+    }
+    let base_sha = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string());
+    let hash = snapshot_hash(&files);
+    let files_json = serde_json::to_string(&files).map_err(|e| e.to_string())?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO wip_anchors (repo_id, session_id, snapshot_hash, base_sha, files)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(repo_id, session_id, snapshot_hash) DO NOTHING
+        "#,
+    )
+    .bind(repo_id)
+    .bind(session_id)
+    .bind(&hash)
+    .bind(&base_sha)
+    .bind(&files_json)
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    fetch_anchor(pool, repo_id, session_id, &hash).await
+}
+
+/// Anchor a just-imported session that could not be linked to a commit.
+///
+/// Only sessions that ended within `AUTO_ANCHOR_WINDOW_MINUTES` and recorded
+/// the files they touched are anchored; older ones are unlikely to match the
+/// current working tree.
+pub(crate) async fn anchor_recent_session(
+    pool: &SqlitePool,
+    repo_id: i64,
+    session_id: &str,
+    files_touched: &[String],
+    ended_at: Option<DateTime<Utc>>,
+) -> Result<Option<WipAnchor>, String> {
+    let recent = ended_at
+        .is_some_and(|end| Utc::now() - end <= Duration::minutes(AUTO_ANCHOR_WINDOW_MINUTES));
+    if !recent || files_touched.is_empty() {
+        return Ok(None);
+    }
+    anchor_session(pool, repo_id, session_id, files_touched).await
+}
+
+/// Upgrade open anchors to session links for a new commit.
+///
+/// Called from the post-commit hook. Returns the number of sessions linked.
+pub async fn resolve_wip_anchors(
+    pool: &SqlitePool,
+    repo_id: i64,
+    repo_root: &Path,
+    commit_sha: &str,
+) -> Result<usize, String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let changes = commit_changes(&repo, commit_sha)
+        .map_err(|e| format!("Failed to read commit {}: {}", commit_sha, e))?;
+    if changes.is_empty() {
+        return Ok(0);
+    }
+
+    let cutoff = (Utc::now() - Duration::days(MAX_ANCHOR_AGE_DAYS))
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string();
+    let open: Vec<(i64, String, String)> = sqlx::query_as(
+        r#"
+        SELECT id, session_id, files
+        FROM wip_anchors
+        WHERE repo_id = ? AND resolved_at IS NULL AND created_at >= ?
+        ORDER BY id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&cutoff)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    let mut linked = 0;
+    for (id, session_id, files_json) in open {
+        let mut files: Vec<WipFile> = serde_json::from_str(&files_json).unwrap_or_default();
+        let Some(confidence) = apply_commit(&mut files, commit_sha, &changes) else {
+            continue;
+        };
+
+        sqlx::query(
+            r#"
+            INSERT INTO session_links (repo_id, session_id, commit_sha, confidence, auto_linked)
+            VALUES (?, ?, ?, ?, 1)
+            ON CONFLICT(repo_id, session_id, commit_sha)
+            DO UPDATE SET confidence = MAX(confidence, excluded.confidence)
+            "#,
+        )
+        .bind(repo_id)
+        .bind(&session_id)
+        .bind(commit_sha)
+        .bind(confidence)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+        let resolved = files.iter().all(|f| f.committed_sha.is_some());
+        let files_json = serde_json::to_string(&files).map_err(|e| e.to_string())?;
+        sqlx::query(
+            r#"
+            UPDATE wip_anchors
+            SET files = ?,
+                resolved_at = CASE WHEN ? THEN strftime('%Y-%m-%dT%H:%M:%fZ','now') END,
+                resolved_commit_sha = CASE WHEN ? THEN ? END
+            WHERE id = ?
+            "#,
+        )
+        .bind(&files_json)
+        .bind(resolved)
+        .bind(resolved)
+        .bind(commit_sha)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+        linked += 1;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    Ok(linked)
+}
+
+/// Sessions whose open anchors cover files staged for the next commit,
+/// oldest anchor first.
+///
+/// Used by the prepare-commit-msg hook before the commit exists, so only
+/// uncommitted anchored files count.
+pub async fn sessions_for_staged_changes(
+    pool: &SqlitePool,
+    repo_id: i64,
+    repo_root: &Path,
+) -> Result<Vec<String>, String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let staged = staged_paths(&repo).map_err(|e| format!("Failed to read the index: {}", e))?;
+    if staged.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let cutoff = (Utc::now() - Duration::days(MAX_ANCHOR_AGE_DAYS))
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string();
+    let open: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT session_id, files
+        FROM wip_anchors
+        WHERE repo_id = ? AND resolved_at IS NULL AND created_at >= ?
+        ORDER BY id
+        "#,
+    )
+    .bind(repo_id)
+    .bind(&cutoff)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+
+    let mut sessions: Vec<String> = Vec::new();
+    for (session_id, files_json) in open {
+        let files: Vec<WipFile> = serde_json::from_str(&files_json).unwrap_or_default();
+        let covers_staged = files
+            .iter()
+            .any(|f| f.committed_sha.is_none() && staged.contains(&f.path));
+        if covers_staged && !sessions.contains(&session_id) {
+            sessions.push(session_id);
+        }
+    }
+    Ok(sessions)
+}
+
+/// Anchor a session to the repo's current uncommitted changes.
+///
+/// The anchor becomes a session link when its files are committed (via the
+/// post-commit hook). Returns None when there is nothing uncommitted to
+/// anchor to.
+#[tauri::command(rename_all = "camelCase")]
+pub async fn anchor_session_to_worktree(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    session_id: String,
+) -> Result<Option<WipAnchor>, String> {
+    let pool = db_state.0.as_ref();
+    let files_json: Option<Option<String>> = sqlx::query_scalar(&format!(
+        "SELECT s.files FROM sessions s WHERE s.id = ?1 AND {}",
+        crate::import::commands::session_in_repo_sql("s", "?2")
+    ))
+    .bind(&session_id)
+    .bind(repo_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    let Some(files_json) = files_json else {
+        return Err(format!("Session not found: {}", session_id));
+    };
+    let session_files: Vec<String> = files_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+
+    anchor_session(pool, repo_id, &session_id, &session_files).await
+}
+
+/// Work-in-progress anchors for a repo, newest first (open ones only unless
+/// `include_resolved`).
+#[tauri::command(rename_all = "camelCase")]
+pub async fn get_wip_anchors(
+    db_state: State<'_, DbState>,
+    repo_id: i64,
+    include_resolved: Option<bool>,
+) -> Result<Vec<WipAnchor>, String> {
+    let rows: Vec<WipAnchorRow> = sqlx::query_as(
+        r#"
+        SELECT id, session_id, snapshot_hash, base_sha, files, created_at, resolved_at, resolved_commit_sha
+        FROM wip_anchors
+        WHERE repo_id = ? AND (? OR resolved_at IS NULL)
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .bind(repo_id)
+    .bind(include_resolved.unwrap_or(false))
+    .fetch_all(db_state.0.as_ref())
+    .await
+    .map_err(|e| format!("Database error: {}", e))?;
+    Ok(rows.into_iter().map(anchor_from_row).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn commit_paths(repo: &Repository, paths: &[&str], message: &str) -> String {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let mut index = repo.index().unwrap();
+        for path in paths {
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parent_refs)
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn anchors_upgrade_as_their_files_are_committed() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn b() {}\n").unwrap();
+        commit_paths(&repo, &["a.rs", "b.rs"], "init");
+
+        // The session changed a.rs and b.rs; other.rs is unrelated.
+        std::fs::write(dir.path().join("a.rs"), "fn a() { 1 }\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn b() { 2 }\n").unwrap();
+        std::fs::write(dir.path().join("other.rs"), "fn other() {}\n").unwrap();
+        let session_files = vec![
+            dir.path().join("a.rs").to_string_lossy().to_string(),
+            "b.rs".to_string(),
+        ];
+        let mut files = snapshot_worktree(&repo, &session_files).unwrap();
+        assert_eq!(
+            files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(),
+            vec!["a.rs", "b.rs"]
+        );
+        let hash = snapshot_hash(&files);
+        assert_eq!(hash.len(), 40);
+        assert_eq!(
+            hash,
+            snapshot_hash(&snapshot_worktree(&repo, &session_files).unwrap())
+        );
+
+        // a.rs is committed as anchored; b.rs is edited again first.
+        let first = commit_paths(&repo, &["a.rs"], "a");
+        let confidence = apply_commit(&mut files, &first, &commit_changes(&repo, &first).unwrap());
+        assert_eq!(confidence, Some(1.0));
+        assert_eq!(files[0].committed_sha.as_deref(), Some(first.as_str()));
+        assert!(files[1].committed_sha.is_none());
+
+        std::fs::write(dir.path().join("b.rs"), "fn b() { 3 }\n").unwrap();
+        let second = commit_paths(&repo, &["b.rs"], "b");
+        let confidence = apply_commit(
+            &mut files,
+            &second,
+            &commit_changes(&repo, &second).unwrap(),
+        );
+        assert_eq!(confidence, Some(WIP_LINK_BASE_CONFIDENCE));
+        assert!(files.iter().all(|f| f.committed_sha.is_some()));
+
+        // Nothing left to upgrade.
+        assert_eq!(
+            apply_commit(
+                &mut files,
+                &second,
+                &commit_changes(&repo, &second).unwrap()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn staged_paths_lists_index_changes_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn b() {}\n").unwrap();
+        commit_paths(&repo, &["a.rs", "b.rs"], "init");
+        assert!(staged_paths(&repo).unwrap().is_empty());
+
+        std::fs::write(dir.path().join("a.rs"), "fn a() { 1 }\n").unwrap();
+        std::fs::write(dir.path().join("b.rs"), "fn b() { 2 }\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("a.rs")).unwrap();
+        index.write().unwrap();
+
+        let staged = staged_paths(&repo).unwrap();
+        assert_eq!(staged, HashSet::from(["a.rs".to_string()]));
+    }
+}
@@ -0,0 +1,147 @@
+//! Linked worktree support.
+//!
+//! A repo's linked worktrees (`git worktree add`) share its history, but each
+//! checkout may be registered as its own repo with its own commit index.
+//! Commits made in one worktree are then missing from the others' windows,
+//! and sessions run in an unregistered worktree resolve to no repo at all.
+//! Checkouts are matched by their git common dir (the main `.git`).
+
+use git2::Repository;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+
+/// Git common dir of the checkout containing `path` (the main repo's `.git`
+/// for linked worktrees), canonicalized for comparison.
+pub fn common_git_dir(path: &Path) -> Option<PathBuf> {
+    let repo = Repository::discover(path).ok()?;
+    repo.commondir().canonicalize().ok()
+}
+
+/// Ids of registered repos sharing history with `repo_id` (itself first).
+///
+/// Falls back to just `repo_id` when its checkout cannot be opened.
+pub(crate) async fn repo_ids_sharing_history(
+    pool: &SqlitePool,
+    repo_id: i64,
+) -> Result<Vec<i64>, String> {
+    let repos: Vec<(i64, String)> = sqlx::query_as("SELECT id, path FROM repos ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+
+    let Some(common) = repos
+        .iter()
+        .find(|(id, _)| *id == repo_id)
+        .and_then(|(_, path)| common_git_dir(Path::new(path)))
+    else {
+        return Ok(vec![repo_id]);
+    };
+
+    let mut ids = vec![repo_id];
+    ids.extend(
+        repos
+            .iter()
+            .filter(|(id, path)| {
+                *id != repo_id && common_git_dir(Path::new(path)).as_ref() == Some(&common)
+            })
+            .map(|(id, _)| *id),
+    );
+    Ok(ids)
+}
+
+/// Registered repo for a checkout path.
+///
+/// An exact path match wins; otherwise a repo sharing the checkout's git
+/// common dir (e.g. the main checkout for a path inside a linked worktree).
+pub(crate) async fn resolve_repo_id_for_path(
+    pool: &SqlitePool,
+    path: &str,
+) -> Result<Option<i64>, String> {
+    let exact: Option<i64> = sqlx::query_scalar("SELECT id FROM repos WHERE path = ? LIMIT 1")
+        .bind(path)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    if exact.is_some() {
+        return Ok(exact);
+    }
+
+    let Some(common) = common_git_dir(Path::new(path)) else {
+        return Ok(None);
+    };
+    let repos: Vec<(i64, String)> = sqlx::query_as("SELECT id, path FROM repos ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(repos
+        .into_iter()
+        .find(|(_, repo_path)| common_git_dir(Path::new(repo_path)).as_ref() == Some(&common))
+        .map(|(id, _)| id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+
+    fn init_with_commit(path: &Path) -> Repository {
+        let repo = Repository::init(path).unwrap();
+        {
+            let sig = Signature::now("Test", "test@example.com").unwrap();
+            let tree_id = repo.index().unwrap().write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+                .unwrap();
+        }
+        repo
+    }
+
+    #[test]
+    fn worktrees_share_history_with_their_main_checkout() {
+        let dir = tempfile::tempdir().unwrap();
+        let main_path = dir.path().join("main");
+        let wt_path = dir.path().join("feature");
+        let other_path = dir.path().join("other");
+        let main = init_with_commit(&main_path);
+        main.worktree("feature", &wt_path, None).unwrap();
+        init_with_commit(&other_path);
+
+        assert_eq!(common_git_dir(&wt_path), common_git_dir(&main_path));
+        assert_ne!(common_git_dir(&other_path), common_git_dir(&main_path));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("tokio runtime");
+        runtime.block_on(async {
+            let pool = crate::test_pool().await;
+            for (id, path) in [(1, &main_path), (2, &other_path), (3, &wt_path)] {
+                sqlx::query("INSERT INTO repos (id, path) VALUES (?, ?)")
+                    .bind(id)
+                    .bind(path.to_string_lossy().to_string())
+                    .execute(&pool)
+                    .await
+                    .expect("insert repo");
+            }
+
+            assert_eq!(
+                repo_ids_sharing_history(&pool, 1).await.unwrap(),
+                vec![1, 3]
+            );
+            assert_eq!(repo_ids_sharing_history(&pool, 2).await.unwrap(), vec![2]);
+
+            sqlx::query("DELETE FROM repos WHERE id = 3")
+                .execute(&pool)
+                .await
+                .expect("delete worktree repo");
+            let nested = wt_path.join("src");
+            std::fs::create_dir_all(&nested).unwrap();
+            assert_eq!(
+                resolve_repo_id_for_path(&pool, &nested.to_string_lossy())
+                    .await
+                    .unwrap(),
+                Some(1)
+            );
+        });
+    }
+}
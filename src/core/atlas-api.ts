@@ -19,7 +19,9 @@ export const KNOWN_ATLAS_ERROR_CODES = [
 	"REPO_NOT_FOUND",
 	"SESSION_NOT_FOUND",
 	"FTS_NOT_AVAILABLE",
+	"EMBEDDINGS_NOT_AVAILABLE",
 	"INVALID_QUERY",
+	"INVALID_EXPORT_FILE",
 
 	// Catch-all
 	"INTERNAL",
@@ -68,6 +70,7 @@ export type AtlasCapabilities = {
 	derivedVersion: string;
 	fts5Enabled: boolean;
 	ftsTableReady: boolean;
+	embeddingsAvailable: boolean;
 	budgets: AtlasBudgets;
 };
 
@@ -79,6 +82,7 @@ export type AtlasIndexState = {
 	lastError: string | null;
 	sessionsIndexed: number;
 	chunksIndexed: number;
+	chunkBudget: number | null;
 };
 
 export type AtlasIntrospect = {
@@ -87,10 +91,46 @@ export type AtlasIntrospect = {
 	sessionsWithChunks: number;
 };
 
+// "lexical": BM25 score (lower is better); "hybrid": BM25 blended with
+// embedding similarity, score 0..1 (higher is better)
+export type AtlasSearchMode = "lexical" | "hybrid";
+
+// Tool and model match case-insensitively; importedAfter is inclusive,
+// importedBefore exclusive (YYYY-MM-DD or RFC 3339)
+export type AtlasSearchFilters = {
+	tool?: string;
+	model?: string;
+	importedAfter?: string;
+	importedBefore?: string;
+	role?: "user" | "assistant" | "thinking" | "plan" | "tool_call";
+};
+
+// Results are reranked by recency and chunk role unless enabled is false.
+// Defaults: 30-day half-life, recency weight 0.5, roles plan 1.3,
+// assistant 1.15, user 1.0, thinking 0.9, tool_call 0.7
+export type AtlasRankingOptions = {
+	enabled?: boolean;
+	recencyHalfLifeDays?: number;
+	recencyWeight?: number;
+	roleWeights?: Partial<
+		Record<"user" | "assistant" | "thinking" | "plan" | "tool_call", number>
+	>;
+};
+
 export type AtlasSearchRequest = {
 	repoId: number;
 	query: string;
 	limit?: number;
+	mode?: AtlasSearchMode;
+	filters?: AtlasSearchFilters;
+	ranking?: AtlasRankingOptions;
+};
+
+// Matched range of a snippet in UTF-16 code units (string indices); end is
+// exclusive
+export type AtlasHighlight = {
+	start: number;
+	end: number;
 };
 
 export type AtlasSearchResult = {
@@ -99,6 +139,7 @@ export type AtlasSearchResult = {
 	chunkIndex: number;
 	score: number;
 	snippet: string;
+	highlights: AtlasHighlight[];
 	sessionImportedAt: string | null;
 	sessionTool: string | null;
 	sessionModel: string | null;
@@ -107,8 +148,25 @@ export type AtlasSearchResult = {
 // Back-compat alias for local callers (shape matches Rust fields above)
 export type AtlasSearchHit = AtlasSearchResult;
 
+export type AtlasFacetBucket = {
+	value: string;
+	// Matching chunks
+	count: number;
+};
+
+// Hit counts over all keyword matches (not just the returned results),
+// largest first; months ("YYYY-MM") newest first. Use them as filters to
+// drill down.
+export type AtlasSearchFacets = {
+	tools: AtlasFacetBucket[];
+	months: AtlasFacetBucket[];
+	sessions: AtlasFacetBucket[];
+};
+
 export type AtlasSearchResponse = {
+	mode: AtlasSearchMode;
 	results: AtlasSearchResult[];
+	facets: AtlasSearchFacets;
 };
 
 export type AtlasGetSessionRequest = {
@@ -162,6 +220,116 @@ export type AtlasDoctorRebuildSummary = {
 	ftsRebuilt: boolean;
 };
 
+export type AtlasEmbedSummary = {
+	repoId: number;
+	model: string;
+	embedded: number;
+	pruned: number;
+	remaining: number;
+};
+
+export type AtlasIndexQueueEntry = {
+	sessionId: string;
+	attempts: number;
+	nextAttemptAt: string;
+	lastError: string | null;
+	enqueuedAt: string;
+};
+
+export type AtlasIndexPending = {
+	repoId: number;
+	pending: number;
+	retrying: number;
+	entries: AtlasIndexQueueEntry[];
+};
+
+// Payload of the "atlas-index-progress" event, emitted per repo after each
+// batch the background indexer projects
+export type AtlasIndexProgressEvent = {
+	repoId: number;
+	indexed: number;
+	failed: number;
+	pending: number;
+};
+
+export type AtlasIndexSize = {
+	sessions: number;
+	chunks: number;
+	textBytes: number;
+	embeddingBytes: number;
+};
+
+export type AtlasCompactSummary = {
+	repoId: number;
+	chunkBudget: number | null;
+	before: AtlasIndexSize;
+	after: AtlasIndexSize;
+	purgedChunks: number;
+	evictedChunks: number;
+	prunedEmbeddings: number;
+	ftsOptimized: boolean;
+};
+
+// "trigram" suits languages without spaces and substring search; queries
+// need at least 3 characters. removeDiacritics and tokenChars apply to
+// "unicode61" only.
+export type AtlasTokenizerOptions = {
+	tokenizer: "unicode61" | "trigram";
+	porter: boolean;
+	removeDiacritics: 0 | 1 | 2;
+	tokenChars: string;
+};
+
+export type AtlasTokenizerSummary = {
+	options: AtlasTokenizerOptions;
+	tokenize: string;
+	rebuilt: boolean;
+	chunksIndexed: number;
+};
+
+export type AtlasExportSummary = {
+	repoId: number;
+	path: string;
+	sessions: number;
+	chunks: number;
+	bytes: number;
+};
+
+export type AtlasImportSummary = {
+	repoId: number;
+	sessionsAdded: number;
+	sessionsSkipped: number;
+	chunksImported: number;
+};
+
+export type AtlasReindexSummary = {
+	repoId: number;
+	sessionId: string;
+	deletedChunks: number;
+	chunksWritten: number;
+	truncated: boolean;
+};
+
+// maxChars 500..4000; overlapChars (whole trailing messages repeated in the
+// next chunk) must be under half of maxChars
+export type AtlasChunkingOptions = {
+	maxChars: number;
+	overlapChars: number;
+	skipRoles: Array<"user" | "assistant" | "thinking" | "plan" | "tool_call">;
+};
+
+export type AtlasChunkingState = {
+	repoId: number;
+	options: AtlasChunkingOptions;
+	derivedVersion: string;
+};
+
+export type AtlasSetChunkingResponse = {
+	state: AtlasChunkingState;
+	// Absent when the options were unchanged and nothing was rebuilt
+	rebuild?: AtlasDoctorRebuildSummary | null;
+};
+
 function isRecord(value: unknown): value is Record<string, unknown> {
 	return typeof value === "object" && value !== null;
 }
@@ -258,3 +426,96 @@ export async function atlasDoctorRebuildDerived(
 		{ request: { repoId } },
 	);
 }
+
+export async function atlasEmbedChunks(
+	repoId: number,
+	limit?: number,
+): Promise<AtlasEnvelope<AtlasEmbedSummary>> {
+	return invokeAtlas<AtlasEmbedSummary>("atlas_embed_chunks", {
+		repoId,
+		limit,
+	});
+}
+
+export async function atlasIndexPending(
+	repoId: number,
+	limit?: number,
+): Promise<AtlasEnvelope<AtlasIndexPending>> {
+	return invokeAtlas<AtlasIndexPending>("atlas_index_pending", {
+		repoId,
+		limit,
+	});
+}
+
+// chunkBudget: stored before compacting; 0 removes the budget
+export async function atlasCompact(
+	repoId: number,
+	chunkBudget?: number,
+): Promise<AtlasEnvelope<AtlasCompactSummary>> {
+	return invokeAtlas<AtlasCompactSummary>("atlas_compact", {
+		request: { repoId, chunkBudget },
+	});
+}
+
+export async function atlasGetTokenizer(): Promise<
+	AtlasEnvelope<AtlasTokenizerOptions>
+> {
+	return invokeAtlas<AtlasTokenizerOptions>("atlas_get_tokenizer", {});
+}
+
+// Recreates the FTS index with the new options and reindexes every chunk
+export async function atlasSetTokenizer(
+	options: AtlasTokenizerOptions,
+	force?: boolean,
+): Promise<AtlasEnvelope<AtlasTokenizerSummary>> {
+	return invokeAtlas<AtlasTokenizerSummary>("atlas_set_tokenizer", {
+		request: { options, force },
+	});
+}
+
+export async function atlasExport(
+	repoId: number,
+	path: string,
+	includeState?: boolean,
+): Promise<AtlasEnvelope<AtlasExportSummary>> {
+	return invokeAtlas<AtlasExportSummary>("atlas_export", {
+		request: { repoId, path, includeState },
+	});
+}
+
+// Sessions already indexed in this repo keep their own chunks
+export async function atlasImport(
+	repoId: number,
+	path: string,
+): Promise<AtlasEnvelope<AtlasImportSummary>> {
+	return invokeAtlas<AtlasImportSummary>("atlas_import", {
+		request: { repoId, path },
+	});
+}
+
+export async function atlasReindexSession(
+	repoId: number,
+	sessionId: string,
+): Promise<AtlasEnvelope<AtlasReindexSummary>> {
+	return invokeAtlas<AtlasReindexSummary>("atlas_reindex_session", {
+		repoId,
+		sessionId,
+	});
+}
+
+export async function atlasGetChunking(
+	repoId: number,
+): Promise<AtlasEnvelope<AtlasChunkingState>> {
+	return invokeAtlas<AtlasChunkingState>("atlas_get_chunking", { repoId });
+}
+
+// Re-derives the repo's chunks when the options change
+export async function atlasSetChunking(
+	repoId: number,
+	options: AtlasChunkingOptions,
+	force?: boolean,
+): Promise<AtlasEnvelope<AtlasSetChunkingResponse>> {
+	return invokeAtlas<AtlasSetChunkingResponse>("atlas_set_chunking", {
+		request: { repoId, options, force },
+	});
+}
@@ -6,9 +6,13 @@
 
 import { invoke } from "@tauri-apps/api/core";
 import { z } from "zod";
+import type { SourceLine } from "../ui/components/AuthorBadge";
+import type { AutoImportResult } from "./tauri/ingestConfig";
 // Re-exported for consumer use (type-only import for re-export)
 import type {
 	DashboardStats,
+	DashboardStatsFilter,
+	RepoAttributionCoverage,
 	TimeRange,
 	ToolStats,
 	TrendColor,
@@ -48,6 +52,10 @@ const PeriodAttributionSchema = z.object({
 	aiAssistLines: z.number(),
 	collaborativeLines: z.number(),
 	aiPercentage: z.number(),
+	deletedLines: z.number().optional(),
+	deletedAiLines: z.number().optional(),
+	deletedByAiLines: z.number().optional(),
+	netLines: z.number().optional(),
 });
 
 const PeriodStatsSchema = z.object({
@@ -69,6 +77,21 @@ const PaginatedFilesSchema = z.object({
 	hasMore: z.boolean(),
 });
 
+const LanguageStatsSchema = z.object({
+	language: z.string(),
+	files: z.number(),
+	totalLines: z.number(),
+	aiLines: z.number(),
+	humanLines: z.number(),
+	aiPercentage: z.number(),
+});
+
+const RepoAttributionCoverageSchema = z.object({
+	totalCommits: z.number(),
+	attributedCommits: z.number(),
+	coveragePercent: z.number(),
+});
+
 const DashboardStatsSchema = z.object({
 	repo: z.object({
 		id: z.number(),
@@ -82,6 +105,17 @@ const DashboardStatsSchema = z.object({
 	currentPeriod: PeriodStatsSchema,
 	previousPeriod: PeriodStatsSchema.optional(),
 	topFiles: PaginatedFilesSchema,
+	languages: z.array(LanguageStatsSchema).optional(),
+	attributionCoverage: RepoAttributionCoverageSchema.optional(),
+	filter: z
+		.object({
+			since: z.string().optional(),
+			until: z.string().optional(),
+			branch: z.string().optional(),
+			authorEmail: z.string().optional(),
+			tool: z.string().optional(),
+		})
+		.optional(),
 });
 
 // ============================================================================
@@ -91,24 +125,72 @@ const DashboardStatsSchema = z.object({
 /**
  * Get complete dashboard stats in a single call.
  * Uses precomputed cache for fast queries; returns previous period for comparison.
+ * An optional filter scopes every figure to a date range, branch, author, or tool.
  */
 export async function getDashboardStats(
 	repoId: number,
 	timeRange: TimeRange = "30d",
 	filesOffset: number = 0,
 	filesLimit: number = 20,
+	filter?: DashboardStatsFilter,
 ): Promise<DashboardStats> {
 	const raw = await invoke("get_dashboard_stats", {
 		repoId,
 		timeRange,
 		filesOffset,
 		filesLimit,
+		filter,
 	});
 
 	// Validate contract with Zod
 	return DashboardStatsSchema.parse(raw);
 }
 
+export interface DashboardHtmlExport {
+	path: string;
+	commits: number;
+}
+
+/**
+ * Render the dashboard (totals, trend, tools, languages, top files) to a
+ * self-contained HTML file that can be shared without the app.
+ */
+export async function exportDashboardHtml(
+	repoId: number,
+	outputPath: string,
+	timeRange: TimeRange = "30d",
+	filter?: DashboardStatsFilter,
+): Promise<DashboardHtmlExport> {
+	return invoke("export_dashboard_html", {
+		repoId,
+		timeRange,
+		filter,
+		outputPath,
+	});
+}
+
+export interface AuthorAttribution {
+	author: string;
+	commits: number;
+	totalLines: number;
+	humanLines: number;
+	aiAgentLines: number;
+	aiAssistLines: number;
+	collaborativeLines: number;
+	aiPercentage: number;
+}
+
+/**
+ * Get per-author attribution (AI-agent, AI-assist, collaborative, human
+ * lines) for commits authored in the time range. Most lines first.
+ */
+export async function getAuthorAttributionStats(
+	repoId: number,
+	timeRange: TimeRange = "30d",
+): Promise<AuthorAttribution[]> {
+	return invoke("get_author_attribution_stats", { repoId, timeRange });
+}
+
 /**
  * Convert a time range preset or custom range to date strings.
  */
@@ -234,6 +316,8 @@ export interface ContributionStats {
 	toolBreakdown?: ToolStats[];
 	primaryTool?: string;
 	model?: string;
+	/** Merge commit: only lines changed against every parent are counted */
+	isMerge?: boolean;
 }
 
 // Re-export ToolStats and dashboard types from types.ts for backward compatibility
@@ -269,6 +353,73 @@ export interface BatchImportResult {
 	total: number;
 	succeeded: ImportSuccess[];
 	failed: ImportFailure[];
+	cancelled: boolean;
+	workers: ImportWorkerStatus[];
+	/** Present when the import ran as a dry run */
+	preview?: ImportPreviewReport | null;
+}
+
+export interface ReimportResult {
+	sessionId: string;
+	sourcePath: string;
+	previousMessageCount: number;
+	messageCount: number;
+	redactionCount: number;
+}
+
+export interface QuarantinedImport {
+	id: number;
+	filePath: string;
+	tool?: string | null;
+	warnings: Array<{
+		severity: "Info" | "Warning" | "Security";
+		message: string;
+		context?: string | null;
+	}>;
+	/** Flagged lines with redaction applied */
+	excerpts: Array<{ context: string; text: string }>;
+	quarantinedAt: string;
+}
+
+export interface ImportPreview {
+	path: string;
+	tool?: string | null;
+	sessionId?: string | null;
+	status: "new" | "duplicate" | "blocked" | "failed";
+	messageCount: number;
+	redactionCount: number;
+	securityWarnings: string[];
+	linkCandidate?: {
+		commitSha: string;
+		confidence: number;
+		needsReview: boolean;
+	} | null;
+	error?: string | null;
+}
+
+export interface ImportPreviewReport {
+	total: number;
+	wouldImport: number;
+	duplicates: number;
+	blocked: number;
+	failed: number;
+	files: ImportPreview[];
+}
+
+export interface ImportWorkerStatus {
+	worker: number;
+	processed: number;
+	failed: number;
+}
+
+/** Payload of the `import-progress` event emitted after each file */
+export interface ImportProgress {
+	cancelToken?: string | null;
+	worker: number;
+	path: string;
+	completed: number;
+	total: number;
+	status: "succeeded" | "failed";
 }
 
 export interface ScannedSession {
@@ -293,7 +444,10 @@ export interface AttributionNoteBatchSummary {
 
 export interface AttributionNoteExportSummary {
 	commitSha: string;
+	/** "exported", "merged" (into an existing note), "unchanged", or "empty" */
 	status: string;
+	/** Overlapping ranges the merged note attributes to different sessions */
+	conflicts: number;
 }
 
 export interface AttributionCoverageSummary {
@@ -315,6 +469,38 @@ export interface AttributionNoteSummary {
 	evidenceSource?: string;
 }
 
+export interface CurrentFileAttribution {
+	headSha: string;
+	lines: SourceLine[];
+	totalLines: number;
+	hasMore: boolean;
+}
+
+export interface SymbolAttribution {
+	filePath: string;
+	/** Qualified name, e.g. `Parser::parse` or `Widget.render` */
+	symbolName: string;
+	symbolKind:
+		| "function"
+		| "method"
+		| "class"
+		| "struct"
+		| "enum"
+		| "trait"
+		| "impl"
+		| "interface"
+		| "module";
+	startLine: number;
+	endLine: number;
+	totalLines: number;
+	humanLines: number;
+	aiAgentLines: number;
+	aiAssistLines: number;
+	collaborativeLines: number;
+	aiPercentage: number;
+	primaryTool?: string | null;
+}
+
 export interface AttributionPrefs {
 	repoId: number;
 	cachePromptMetadata: boolean;
@@ -322,6 +508,19 @@ export interface AttributionPrefs {
 	showLineOverlays: boolean;
 	retentionDays?: number;
 	lastPurgedAt?: string | null;
+	/** Append Narrative trailers to commit messages (prepare-commit-msg hook) */
+	commitTrailers?: boolean;
+	/** Sign exported notes with the repo's git signing key */
+	signNotes?: boolean;
+	/** Only import notes signed by a trusted key */
+	requireSignedNotes?: boolean;
+	/** Export sessions notes in the background when auto-import links a session */
+	autoExportNotes?: boolean;
+	/** Import newly fetched notes after a notes fetch or `git pull` */
+	importNotesAfterFetch?: boolean;
+	/** Namespace of the repo's notes refs; null for `refs/notes/narrative`.
+	 * Change it with `setNotesNamespace`, which moves existing notes. */
+	notesNamespace?: string | null;
 }
 
 export interface AttributionPrefsUpdate {
@@ -330,6 +529,11 @@ export interface AttributionPrefsUpdate {
 	showLineOverlays?: boolean;
 	retentionDays?: number;
 	clearRetentionDays?: boolean;
+	commitTrailers?: boolean;
+	signNotes?: boolean;
+	requireSignedNotes?: boolean;
+	autoExportNotes?: boolean;
+	importNotesAfterFetch?: boolean;
 }
 
 export interface AttributionPromptPurgeSummary {
@@ -359,12 +563,89 @@ export async function importSessionFile(
 
 /**
  * Import multiple session files (batch)
+ *
+ * Emits `import-progress` per file. Pass a `cancelToken` to allow
+ * `cancelSessionImport` to stop the batch midway. With `dryRun`, nothing is
+ * written and `preview` describes what would be imported.
  */
 export async function importSessionFiles(
 	repoId: number,
 	filePaths: string[],
+	cancelToken?: string,
+	dryRun?: boolean,
 ): Promise<BatchImportResult> {
-	return invoke("import_session_files", { repoId, filePaths });
+	return invoke("import_session_files", {
+		repoId,
+		filePaths,
+		cancelToken,
+		dryRun,
+	});
+}
+
+/**
+ * Re-parse a stored session from its source file, keeping its links
+ */
+export async function reimportSession(
+	repoId: number,
+	sessionId: string,
+): Promise<ReimportResult> {
+	return invoke("reimport_session", { repoId, sessionId });
+}
+
+/**
+ * List session files held back by security warnings
+ */
+export async function listQuarantinedImports(
+	repoId: number,
+): Promise<QuarantinedImport[]> {
+	return invoke("list_quarantined_imports", { repoId });
+}
+
+/**
+ * Import a reviewed quarantined file (redaction is applied)
+ */
+export async function confirmQuarantinedImport(
+	repoId: number,
+	quarantineId: number,
+): Promise<AutoImportResult> {
+	return invoke("confirm_quarantined_import", { repoId, quarantineId });
+}
+
+/**
+ * Discard a quarantined file without importing it
+ */
+export async function discardQuarantinedImport(
+	repoId: number,
+	quarantineId: number,
+): Promise<boolean> {
+	return invoke("discard_quarantined_import", { repoId, quarantineId });
+}
+
+/**
+ * Cancel a running batch import; already-imported sessions are kept
+ */
+export async function cancelSessionImport(
+	cancelToken: string,
+): Promise<boolean> {
+	return invoke("cancel_session_import", { cancelToken });
+}
+
+export interface ResumedImport {
+	batchId: string;
+	repoId: number;
+	kind: "batch" | "backfill";
+	attempted: number;
+	imported: number;
+	failed: number;
+}
+
+/**
+ * Finish batch imports and backfills interrupted by a crash
+ *
+ * The backend also runs this shortly after startup.
+ */
+export async function resumePendingImports(): Promise<ResumedImport[]> {
+	return invoke("resume_pending_imports");
 }
 
 /**
@@ -387,6 +668,149 @@ export async function computeStatsBatch(
 	return invoke("compute_stats_batch", { repoId, commitShas });
 }
 
+/** Progress payload of the `attribution-batch-progress` event */
+export interface AttributionBatchProgress {
+	repoId: number;
+	commitSha?: string | null;
+	completed: number;
+	total: number;
+	computed: number;
+	status: "running" | "completed" | "cancelled";
+}
+
+export interface AttributionBatchStatus {
+	repoId: number;
+	running: boolean;
+	/** Commits still queued (left by a cancelled or interrupted batch when idle) */
+	pending: number;
+	coverage: RepoAttributionCoverage;
+}
+
+/**
+ * Attribute commits on a background task; progress arrives as
+ * `attribution-batch-progress` events. With no commits, resumes whatever a
+ * cancelled or interrupted batch left queued. Returns false if a batch was
+ * already running (the commits join its queue).
+ */
+export async function startAttributionBatch(
+	repoId: number,
+	commitShas?: string[],
+): Promise<boolean> {
+	return invoke("start_attribution_batch", { repoId, commitShas });
+}
+
+/**
+ * Cancel the repo's background attribution batch; queued commits are kept.
+ */
+export async function cancelAttributionBatch(repoId: number): Promise<boolean> {
+	return invoke("cancel_attribution_batch", { repoId });
+}
+
+export async function getAttributionBatchStatus(
+	repoId: number,
+): Promise<AttributionBatchStatus> {
+	return invoke("get_attribution_batch_status", { repoId });
+}
+
+/**
+ * An attribution policy rule. "Unreviewed" AI lines are AI-agent and
+ * AI-assist lines no human edited; collaborative lines count as reviewed.
+ */
+export type PolicyRule =
+	| { kind: "max_unreviewed_ai_percent"; maxPercent: number }
+	| { kind: "max_range_ai_percent"; maxPercent: number }
+	| { kind: "require_attribution_notes" };
+
+export interface AttributionPolicy {
+	rules: PolicyRule[];
+}
+
+export interface PolicyViolation {
+	rule: PolicyRule["kind"];
+	/** Offending commit (absent for range-wide rules) */
+	commitSha?: string;
+	message: string;
+}
+
+export interface PolicyCheckResult {
+	passed: boolean;
+	commitsChecked: number;
+	violations: PolicyViolation[];
+}
+
+export async function getAttributionPolicy(
+	repoId: number,
+): Promise<AttributionPolicy> {
+	return invoke("get_attribution_policy", { repoId });
+}
+
+export async function setAttributionPolicy(
+	repoId: number,
+	policy: AttributionPolicy,
+): Promise<void> {
+	return invoke("set_attribution_policy", { repoId, policy });
+}
+
+/**
+ * Check the commits in `from..to` (`to` defaults to HEAD) against the repo's
+ * saved policy, or `policy` when given.
+ */
+export async function checkAttributionPolicy(
+	repoId: number,
+	from?: string,
+	to?: string,
+	policy?: AttributionPolicy,
+): Promise<PolicyCheckResult> {
+	return invoke("check_attribution_policy", { repoId, from, to, policy });
+}
+
+/**
+ * Get attribution for a file as it exists at HEAD, with each line taken
+ * from the commit that last changed it.
+ */
+export async function getFileCurrentAttribution(
+	repoId: number,
+	filePath: string,
+	offset = 0,
+	limit = 200,
+): Promise<CurrentFileAttribution> {
+	return invoke("get_file_current_attribution", {
+		request: { repoId, filePath, offset, limit },
+	});
+}
+
+/**
+ * Get function/class-level attribution for a file at a commit.
+ * Empty for languages without a bundled grammar.
+ */
+export async function getFileSymbolAttribution(
+	repoId: number,
+	commitSha: string,
+	filePath: string,
+): Promise<SymbolAttribution[]> {
+	return invoke("get_file_symbol_attribution", {
+		request: { repoId, commitSha, filePath },
+	});
+}
+
+/**
+ * Innermost symbol containing a (1-based) line, for Source Lens lookups.
+ */
+export function symbolAtLine(
+	symbols: SymbolAttribution[],
+	line: number,
+): SymbolAttribution | undefined {
+	let match: SymbolAttribution | undefined;
+	for (const symbol of symbols) {
+		if (line < symbol.startLine || line > symbol.endLine) continue;
+		const span = symbol.endLine - symbol.startLine;
+		if (!match || span < match.endLine - match.startLine) {
+			match = symbol;
+		}
+	}
+	return match;
+}
+
 /**
  * Import a single attribution note (git notes) for a commit.
  */
@@ -443,6 +867,270 @@ export async function purgeAttributionPromptMeta(
 	return invoke("purge_attribution_prompt_meta", { repoId });
 }
 
+export type TimeseriesGranularity = "week" | "month";
+
+export interface AttributionTimeseriesPoint {
+	/** First day of the period (YYYY-MM-DD); weeks start on Monday */
+	periodStart: string;
+	commits: number;
+	totalLines: number;
+	humanLines: number;
+	aiAgentLines: number;
+	aiAssistLines: number;
+	collaborativeLines: number;
+	aiPercentage: number;
+	tools: Array<{ tool: string; lineCount: number }>;
+}
+
+export interface AttributionTimeseries {
+	repoId: number;
+	granularity: TimeseriesGranularity;
+	points: AttributionTimeseriesPoint[];
+}
+
+/**
+ * Get weekly or monthly human vs AI line totals (e.g. "AI share over the
+ * last 6 months"). `since`/`until` bound the period start (YYYY-MM-DD).
+ */
+export async function getAttributionTimeseries(
+	repoId: number,
+	granularity: TimeseriesGranularity,
+	since?: string,
+	until?: string,
+): Promise<AttributionTimeseries> {
+	return invoke("get_attribution_timeseries", {
+		repoId,
+		granularity,
+		since,
+		until,
+	});
+}
+
+export interface DirectoryAttribution {
+	/** Directory prefix ("." for files at the repo root) */
+	path: string;
+	files: number;
+	totalLines: number;
+	humanLines: number;
+	aiAgentLines: number;
+	aiAssistLines: number;
+	collaborativeLines: number;
+	aiPercentage: number;
+}
+
+export interface DirectoryRollup {
+	fromSha: string | null;
+	toSha: string;
+	depth: number;
+	commits: number;
+	/** Most AI-authored directories first */
+	directories: DirectoryAttribution[];
+}
+
+/**
+ * Get attribution for the lines added in `from..to` (`to` defaults to HEAD),
+ * grouped by directory prefix up to `depth` levels (default 2).
+ */
+export async function getDirectoryAttribution(
+	repoId: number,
+	options: { from?: string; to?: string; depth?: number } = {},
+): Promise<DirectoryRollup> {
+	return invoke("get_directory_attribution", { repoId, ...options });
+}
+
+export type AttributionReportFormat = "csv" | "json" | "markdown";
+
+export interface AttributionReportExport {
+	path: string;
+	format: AttributionReportFormat;
+	/** Commits with attribution data included in the report */
+	commits: number;
+}
+
+/**
+ * Write an attribution report (totals, per-commit table, per-tool breakdown)
+ * to `outputPath`. Without `from` the report covers the history up to `to`
+ * (default HEAD).
+ */
+export async function exportAttributionReport(
+	repoId: number,
+	format: AttributionReportFormat,
+	outputPath: string,
+	range: { from?: string; to?: string } = {},
+): Promise<AttributionReportExport> {
+	return invoke("export_attribution_report", {
+		repoId,
+		format,
+		outputPath,
+		...range,
+	});
+}
+
+export interface AttributionSarifExport {
+	path: string;
+	/** Commits with at least one exported range */
+	commits: number;
+	results: number;
+}
+
+/**
+ * Write AI-attributed line ranges as a SARIF 2.1.0 log (one note-level
+ * result per range) for code-scanning and review tools.
+ */
+export async function exportAttributionSarif(
+	repoId: number,
+	outputPath: string,
+	range: { from?: string; to?: string } = {},
+): Promise<AttributionSarifExport> {
+	return invoke("export_attribution_sarif", { repoId, outputPath, ...range });
+}
+
+export interface AiBomExport {
+	path: string;
+	releaseSha: string;
+	/** Tag or rev the release range starts after (absent: from the root) */
+	previousRelease?: string;
+	/** Commits with at least one AI region */
+	commits: number;
+	regions: number;
+}
+
+/**
+ * Write an AI bill of materials (JSON) for a release: the AI-generated line
+ * ranges, with tool, model, and session, of the commits since the previous
+ * release. Without `previousRelease`, the nearest earlier tag is used.
+ */
+export async function exportAiBom(
+	repoId: number,
+	release: string,
+	outputPath: string,
+	previousRelease?: string,
+): Promise<AiBomExport> {
+	return invoke("export_ai_bom", {
+		repoId,
+		release,
+		previousRelease,
+		outputPath,
+	});
+}
+
+export interface CompletionAcceptance {
+	/** Repo-relative or absolute path of the edited file */
+	filePath: string;
+	startLine?: number;
+	endLine?: number;
+	/** The inserted completion text */
+	text: string;
+	/** e.g. "copilot", "cursor" */
+	tool: string;
+	model?: string;
+	/** RFC 3339; defaults to now */
+	acceptedAt?: string;
+	commitSha?: string;
+}
+
+export interface CompletionCaptureSummary {
+	recorded: number;
+	skipped: number;
+}
+
+/**
+ * Record tab-completion acceptances. They become `ai_tab` line attributions
+ * once matched to the lines of the commit they end up in.
+ */
+export async function recordCompletionAcceptances(
+	repoId: number,
+	acceptances: CompletionAcceptance[],
+): Promise<CompletionCaptureSummary> {
+	return invoke("record_completion_acceptances", { repoId, acceptances });
+}
+
+/**
+ * Import a JSONL editor log with one `CompletionAcceptance` per line.
+ */
+export async function importCompletionLog(
+	repoId: number,
+	logPath: string,
+): Promise<CompletionCaptureSummary> {
+	return invoke("import_completion_log", { repoId, logPath });
+}
+
+/**
+ * Suggested exclusion globs for files that skew AI percentages.
+ */
+export const SUGGESTED_ATTRIBUTION_EXCLUSIONS = [
+	"package-lock.json",
+	"pnpm-lock.yaml",
+	"yarn.lock",
+	"Cargo.lock",
+	"*.generated.*",
+	"__snapshots__",
+	"*.snap",
+];
+
+/**
+ * Get a repository's attribution exclusion globs.
+ */
+export async function getAttributionExclusions(
+	repoId: number,
+): Promise<string[]> {
+	return invoke("get_attribution_exclusions", { repoId });
+}
+
+/**
+ * Replace a repository's attribution exclusion globs; pass an empty list to
+ * exclude nothing. Matching files get no line attribution and are left out
+ * of stats. A pattern without `/` matches a name at any depth.
+ */
+export async function setAttributionExclusions(
+	repoId: number,
+	patterns: string[],
+): Promise<string[]> {
+	return invoke("set_attribution_exclusions", { repoId, patterns });
+}
+
+/**
+ * Get a repository's language overrides: lowercase extension (`mdx`) or
+ * file name (`justfile`) mapped to the language it is counted under.
+ */
+export async function getAttributionLanguageOverrides(
+	repoId: number,
+): Promise<Record<string, string>> {
+	return invoke("get_attribution_language_overrides", { repoId });
+}
+
+/**
+ * Replace a repository's language overrides; an empty map uses the
+ * built-in extension mapping only.
+ */
+export async function setAttributionLanguageOverrides(
+	repoId: number,
+	overrides: Record<string, string>,
+): Promise<Record<string, string>> {
+	return invoke("set_attribution_language_overrides", { repoId, overrides });
+}
+
+export interface CommitDeletionStats {
+	commitSha: string;
+	deletedLines: number;
+	/** Removed lines that had been written by AI */
+	deletedAiLines: number;
+	deletedHumanLines: number;
+	/** Session linked to the removing commit; null if removed without AI */
+	removedBySessionId: string | null;
+}
+
+/**
+ * Get the lines a commit removed and who originally wrote them. Computed
+ * from blame of the commit's parent on first use, then cached.
+ */
+export async function getCommitDeletionStats(
+	repoId: number,
+	commitSha: string,
+): Promise<CommitDeletionStats> {
+	return invoke("get_commit_deletion_stats", { repoId, commitSha });
+}
+
 // ============================================================================
 // Helpers
 // ============================================================================
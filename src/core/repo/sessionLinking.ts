@@ -23,21 +23,31 @@ export type SessionLinkResult = {
 	autoLinked: boolean;
 	temporalScore: number;
 	fileScore: number;
+	messageScore?: number;
+	/** Share of the commit's added lines the session wrote (absent when unknown) */
+	diffScore?: number;
 	needsReview: boolean;
+	reviewReason?: ReviewReason;
 };
 
+/** Why a link was flagged for review */
+export type ReviewReason = "close_match" | "author_mismatch";
+
 /**
- * Link a session to its best matching commit.
+ * Link a session to its matching commits.
  *
  * This command:
  * 1. Computes time window (±4 hours from session)
  * 2. Queries commits in that window
- * 3. Scores by temporal + file overlap (60% + 40%)
- * 4. Auto-links if confidence >= 0.65
+ * 3. Scores by temporal + file overlap + commit message similarity
+ *    (60% + 40% + 0% by default), blended 50/50 with diff content
+ *    similarity when the session's edits were recorded
+ * 4. Auto-links every commit with confidence >= the repo's threshold
+ *    (0.7 by default)
  *
  * @param repoId - Repository ID
  * @param session - Session excerpt to link
- * @returns Link result with commit and confidence score
+ * @returns The best link (highest confidence)
  */
 export async function linkSessionToCommit(
 	repoId: number,
@@ -49,6 +59,291 @@ export async function linkSessionToCommit(
 	});
 }
 
+/**
+ * Score weights and auto-link threshold used when linking a repo's sessions.
+ * The three weights sum to 1. `messageWeight` scores how well the commit
+ * subject matches the session's user and plan messages.
+ */
+export type LinkingParams = {
+	temporalWeight: number;
+	fileOverlapWeight: number;
+	messageWeight: number;
+	confidenceThreshold: number;
+};
+
+/**
+ * Get the linking params for a repository (defaults if never set).
+ *
+ * @param repoId - Repository ID
+ */
+export async function getLinkingParams(repoId: number): Promise<LinkingParams> {
+	return await invoke<LinkingParams>("get_linking_params", { repoId });
+}
+
+/**
+ * Update the linking params for a repository.
+ *
+ * Omitted fields keep their current value. Existing links are not re-scored.
+ *
+ * @param repoId - Repository ID
+ * @param update - Fields to change
+ * @returns The params now in effect
+ */
+export async function setLinkingParams(
+	repoId: number,
+	update: Partial<LinkingParams>,
+): Promise<LinkingParams> {
+	return await invoke<LinkingParams>("set_linking_params", { repoId, update });
+}
+
+/**
+ * Linking params learned from link corrections.
+ * Manual links to suggested commits count as accepted, unlinks as rejected.
+ * `learnedFrom` is 0 while the params are defaults or were set by hand.
+ */
+export type LinkFeedbackSummary = {
+	params: LinkingParams;
+	learnedFrom: number;
+	accepted: number;
+	rejected: number;
+};
+
+/**
+ * Get the linking params in effect and the corrections they were learned from.
+ *
+ * @param repoId - Repository ID
+ */
+export async function getLinkFeedbackSummary(
+	repoId: number,
+): Promise<LinkFeedbackSummary> {
+	return await invoke<LinkFeedbackSummary>("get_link_feedback_summary", {
+		repoId,
+	});
+}
+
+/**
+ * Forget a repository's link corrections and any params learned from them.
+ * Params set by hand are kept.
+ *
+ * @param repoId - Repository ID
+ */
+export async function resetLearnedLinkingParams(
+	repoId: number,
+): Promise<LinkFeedbackSummary> {
+	return await invoke<LinkFeedbackSummary>("reset_learned_linking_params", {
+		repoId,
+	});
+}
+
+/** Suggested links in one confidence range, and how the user judged them */
+export type ConfidenceBucket = {
+	minConfidence: number;
+	maxConfidence: number;
+	confirmed: number;
+	rejected: number;
+	unreviewed: number;
+	/** Share of reviewed links kept (null without reviewed links) */
+	precision: number | null;
+	/** Expected precision with minConfidence as the threshold */
+	precisionAtOrAbove: number | null;
+	reviewedAtOrAbove: number;
+};
+
+export type LinkCalibrationReport = {
+	threshold: number;
+	targetPrecision: number;
+	/** Lowest bucket bound reaching the target precision, if any */
+	suggestedThreshold: number | null;
+	buckets: ConfidenceBucket[];
+};
+
+/**
+ * Bucket a repository's suggested links by confidence and estimate the
+ * precision of each bucket from confirmed and rejected links.
+ *
+ * @param repoId - Repository ID
+ * @param targetPrecision - Precision the suggested threshold should reach (default 0.9)
+ */
+export async function getLinkCalibrationReport(
+	repoId: number,
+	targetPrecision?: number,
+): Promise<LinkCalibrationReport> {
+	return await invoke<LinkCalibrationReport>("get_link_calibration_report", {
+		repoId,
+		targetPrecision: targetPrecision ?? null,
+	});
+}
+
+/**
+ * Get a repository's link scopes (monorepo sub-paths such as "packages/api").
+ *
+ * @param repoId - Repository ID
+ */
+export async function getLinkScopes(repoId: number): Promise<string[]> {
+	return await invoke<string[]>("get_link_scopes", { repoId });
+}
+
+/**
+ * Replace a repository's link scopes; pass an empty list to turn scoping off.
+ * A session whose files all fall under the scopes is only scored against
+ * commits touching those scopes.
+ *
+ * @param repoId - Repository ID
+ * @param paths - Repo-relative directories
+ */
+export async function setLinkScopes(
+	repoId: number,
+	paths: string[],
+): Promise<string[]> {
+	return await invoke<string[]>("set_link_scopes", { repoId, paths });
+}
+
+/**
+ * Minutes added to a tool's session times before linking.
+ * Corrects tools that log local time without an offset.
+ * `source` is "detected" when learned from confirmed (manual) links.
+ */
+export type ToolClockOffset = {
+	tool: string;
+	offsetMin: number;
+	source: "manual" | "detected";
+	samples: number;
+};
+
+/** List the clock offsets applied to each tool's session times. */
+export async function getToolClockOffsets(): Promise<ToolClockOffset[]> {
+	return await invoke<ToolClockOffset[]>("get_tool_clock_offsets");
+}
+
+/**
+ * Set a tool's clock offset by hand, or pass null to clear it.
+ * Manual offsets are never replaced by detection.
+ *
+ * @param tool - Stored tool name (e.g. "claude_code")
+ * @param offsetMin - Minutes to add to the tool's session times
+ */
+export async function setToolClockOffset(
+	tool: string,
+	offsetMin: number | null,
+): Promise<ToolClockOffset[]> {
+	return await invoke<ToolClockOffset[]>("set_tool_clock_offset", {
+		tool,
+		offsetMin,
+	});
+}
+
+/** Detect clock offsets from confirmed links now. */
+export async function detectToolClockOffsets(): Promise<ToolClockOffset[]> {
+	return await invoke<ToolClockOffset[]>("detect_tool_clock_offsets");
+}
+
+export type RelinkResult = {
+	attempted: number;
+	linked: number;
+	unlinked: number;
+	failed: number;
+};
+
+/**
+ * Re-run linking for a repository's stored sessions against current commits.
+ *
+ * Use after a rebase or history rewrite, or after fixing the commit cache.
+ * Suggested links are replaced; manual links are kept.
+ *
+ * @param repoId - Repository ID
+ * @param range - Optional RFC 3339 bounds on when sessions ended
+ */
+export async function relinkSessionsForRepo(
+	repoId: number,
+	range?: { since?: string; until?: string },
+): Promise<RelinkResult> {
+	return await invoke<RelinkResult>("relink_sessions_for_repo", {
+		repoId,
+		since: range?.since ?? null,
+		until: range?.until ?? null,
+	});
+}
+
+/** How a batch relink changed a repository's suggested links */
+export type RelinkBatchSummary = {
+	changed: number;
+	unchanged: number;
+	/** Lost their suggested links, or the best link's confidence dropped */
+	downgraded: number;
+	failed: number;
+};
+
+/** Payload of the `relink-progress` event, emitted after each batch */
+export type RelinkProgress = {
+	repoId: number;
+	completed: number;
+	total: number;
+	summary: RelinkBatchSummary;
+};
+
+/**
+ * Relink a repository's stored sessions in batches, writing each batch in one
+ * transaction. Listen for `relink-progress` events to follow along.
+ * Manual links are kept.
+ *
+ * @param repoId - Repository ID
+ * @param options - Optional RFC 3339 bounds on when sessions ended, and the
+ *   sessions per batch (default 200)
+ */
+export async function relinkBatch(
+	repoId: number,
+	options?: { since?: string; until?: string; batchSize?: number },
+): Promise<RelinkBatchSummary> {
+	return await invoke<RelinkBatchSummary>("relink_batch", {
+		repoId,
+		since: options?.since ?? null,
+		until: options?.until ?? null,
+		batchSize: options?.batchSize ?? null,
+	});
+}
+
+/** Why a session could not be linked */
+export type UnlinkedReason =
+	| "NoCommitsInTimeWindow"
+	| "LowConfidence"
+	| { ParseError: string };
+
+export type UnlinkedSuggestion = {
+	action: "extend_window" | "manual_link" | "relink" | "reimport";
+	commitSha?: string;
+	detail: string;
+};
+
+/** A session with no link in a repository, and why */
+export type UnlinkedSession = {
+	sessionId: string;
+	tool: string;
+	endedAt: string | null;
+	/** null when the session would link now (relink the repo) */
+	reason: UnlinkedReason | null;
+	reasonMessage: string | null;
+	/** Commits that came closest, best first */
+	nearestMisses: SessionLinkResult[];
+	suggestions: UnlinkedSuggestion[];
+};
+
+/**
+ * List a repository's sessions that have no link, with the reason, the
+ * commits that came closest and suggested next steps. Nothing is stored.
+ *
+ * @param repoId - Repository ID
+ * @param limit - Most recent sessions to report (default 50)
+ */
+export async function getUnlinkedSessionsReport(
+	repoId: number,
+	limit?: number,
+): Promise<UnlinkedSession[]> {
+	return await invoke<UnlinkedSession[]>("get_unlinked_sessions_report", {
+		repoId,
+		limit: limit ?? null,
+	});
+}
+
 /**
  * Import a session file from disk and link it to a commit.
  *
@@ -157,5 +452,119 @@ export type SessionLink = {
 	confidence: number;
 	autoLinked: boolean;
 	needsReview?: boolean;
+	reviewReason?: ReviewReason | null;
 	createdAt: string;
 };
+
+/** A pull request as registered from the GitHub connector */
+export type PullRequestInput = {
+	number: number;
+	title: string;
+	headBranch: string;
+	baseBranch: string;
+	/** Head commit; preferred over the branch, which may be deleted after merge */
+	headSha?: string;
+	url?: string;
+};
+
+/** A session linked to a pull request */
+export type PrSession = {
+	sessionId: string;
+	tool: string;
+	importedAt: string;
+	/** "commit": linked to one of the PR's commits; "branch": ran on its head branch */
+	matchKind: "commit" | "branch";
+	commitCount: number;
+};
+
+/**
+ * Register (or update) a pull request and link its sessions.
+ * Call again when the PR gains commits; its links are recomputed.
+ *
+ * @param repoId - Repository ID
+ * @param pullRequest - PR number, branches and optional head commit
+ */
+export async function upsertPullRequest(
+	repoId: number,
+	pullRequest: PullRequestInput,
+): Promise<PrSession[]> {
+	return await invoke<PrSession[]>("upsert_pull_request", {
+		repoId,
+		pullRequest,
+	});
+}
+
+/**
+ * Get the sessions behind a pull request: commit matches first, then
+ * sessions that only ran on its head branch.
+ *
+ * @param repoId - Repository ID
+ * @param prNumber - Pull request number
+ */
+export async function getSessionsForPr(
+	repoId: number,
+	prNumber: number,
+): Promise<PrSession[]> {
+	return await invoke<PrSession[]>("get_sessions_for_pr", {
+		repoId,
+		prNumber,
+	});
+}
+
+/** A changed file in a work-in-progress snapshot */
+export type WipFile = {
+	/** Path relative to the repo root */
+	path: string;
+	/** Blob hash of the uncommitted content (null if deleted) */
+	blob: string | null;
+	/** Commit that included the file, once committed */
+	committedSha: string | null;
+};
+
+/** A session anchored to uncommitted changes */
+export type WipAnchor = {
+	id: number;
+	sessionId: string;
+	/** Stash-like hash of the snapshot's files and contents */
+	snapshotHash: string;
+	/** HEAD when the snapshot was taken */
+	baseSha: string | null;
+	files: WipFile[];
+	createdAt: string;
+	resolvedAt: string | null;
+	resolvedCommitSha: string | null;
+};
+
+/**
+ * Anchor a session to the repo's current uncommitted changes.
+ * The post-commit hook turns the anchor into session links as its files
+ * are committed. Returns null when nothing is uncommitted.
+ *
+ * @param repoId - Repository ID
+ * @param sessionId - Session ID
+ */
+export async function anchorSessionToWorktree(
+	repoId: number,
+	sessionId: string,
+): Promise<WipAnchor | null> {
+	return await invoke<WipAnchor | null>("anchor_session_to_worktree", {
+		repoId,
+		sessionId,
+	});
+}
+
+/**
+ * Get work-in-progress anchors, newest first.
+ *
+ * @param repoId - Repository ID
+ * @param includeResolved - Also return anchors whose files are all committed
+ */
+export async function getWipAnchors(
+	repoId: number,
+	includeResolved = false,
+): Promise<WipAnchor[]> {
+	return await invoke<WipAnchor[]>("get_wip_anchors", {
+		repoId,
+		includeResolved,
+	});
+}
@@ -1,4 +1,8 @@
 import { invoke } from "@tauri-apps/api/core";
+import type {
+	AttributionNoteBatchSummary,
+	TimeseriesGranularity,
+} from "./attribution-api";
 
 export type StoryAnchorCommitStatus = {
 	commitSha: string;
@@ -11,8 +15,19 @@ export type StoryAnchorCommitStatus = {
 	attributionSchemaVersion?: string | null;
 	sessionsSchemaVersion?: string | null;
 	lineageSchemaVersion?: string | null;
+	/** "unsigned", "valid", "invalid" or "unverified" */
+	attributionSignatureStatus?: NoteSignatureStatus | null;
+	sessionsSignatureStatus?: NoteSignatureStatus | null;
+	attributionSigner?: string | null;
+	sessionsSigner?: string | null;
 };
 
+export type NoteSignatureStatus =
+	| "unsigned"
+	| "valid"
+	| "invalid"
+	| "unverified";
+
 export type SessionsNoteBatchSummary = {
 	total: number;
 	imported: number;
@@ -46,6 +61,41 @@ export async function getStoryAnchorStatus(
 	return invoke("get_story_anchor_status", { repoId, commitShas });
 }
 
+/** One rewrite step, oriented older -> newer */
+export type LineageHop = {
+	fromSha: string;
+	toSha: string;
+	via: "rewrite" | "patch_id";
+	/** git command behind a recorded rewrite ("rebase", "amend") */
+	command?: string | null;
+	/** Hops away from the queried commit (1 = direct) */
+	depth: number;
+	/** Sessions linked to both commits */
+	carriedSessions: string[];
+	/** Note kinds recorded on both commits */
+	carriedNotes: string[];
+};
+
+export type CommitLineage = {
+	commitSha: string;
+	rewriteKey?: string | null;
+	/** Commits this one was rewritten from, nearest first */
+	ancestors: LineageHop[];
+	/** Commits this one was rewritten into, nearest first */
+	descendants: LineageHop[];
+};
+
+/**
+ * Where a commit's story came from and went to across rebases and amends
+ * (recorded rewrites and patch-id matches).
+ */
+export async function getCommitLineage(
+	repoId: number,
+	commitSha: string,
+): Promise<CommitLineage> {
+	return invoke("get_commit_lineage", { repoId, commitSha });
+}
+
 export async function importSessionLinkNotesBatch(
 	repoId: number,
 	commitShas: string[],
@@ -60,6 +110,33 @@ export async function exportSessionLinkNote(
 	return invoke("export_session_link_note", { repoId, commitSha });
 }
 
+/** Progress payload of the `notes-export-progress` event */
+export type NotesExportProgress = {
+	repoId: number;
+	commitSha?: string | null;
+	completed: number;
+	total: number;
+	sessionsExported: number;
+	attributionExported: number;
+	/** Commits with nothing to export */
+	empty: number;
+	failed: number;
+	status: "running" | "completed";
+};
+
+/**
+ * Export sessions and attribution notes for `fromRef..toRef` (all of
+ * `toRef`'s history, default HEAD, without `fromRef`). Progress arrives as
+ * `notes-export-progress` events; resolves with the final report.
+ */
+export async function exportNotesForRange(
+	repoId: number,
+	fromRef?: string,
+	toRef?: string,
+): Promise<NotesExportProgress> {
+	return invoke("export_notes_for_range", { repoId, fromRef, toRef });
+}
+
 export async function migrateAttributionNotesRef(
 	repoId: number,
 	commitShas: string[],
@@ -90,6 +167,11 @@ export async function uninstallRepoHooks(repoId: number): Promise<void> {
 export type RepoHooksStatus = {
 	installed: boolean;
 	hooksDir: string;
+	/** Narrative hooks not installed; reinstall to add them */
+	missingHooks: string[];
+	/** Linked worktree: hooks are shared with the main checkout, and
+	 * uninstalling only disables them for this worktree */
+	linkedWorktree: boolean;
 };
 
 export async function getRepoHooksStatus(
@@ -97,3 +179,304 @@ export async function getRepoHooksStatus(
 ): Promise<RepoHooksStatus> {
 	return invoke("get_repo_hooks_status", { repoId });
 }
+
+export type HookProblemKind =
+	| "missing"
+	| "not_executable"
+	| "stale"
+	| "cli_missing"
+	| "cli_not_executable"
+	| "db_path_mismatch";
+
+export type HookProblem = {
+	hook: string;
+	kind: HookProblemKind;
+	detail: string;
+};
+
+export type RepoHooksDoctorReport = {
+	hooksDir: string;
+	healthy: boolean;
+	problems: HookProblem[];
+	/** Hooks were reinstalled before this report was taken */
+	repaired: boolean;
+};
+
+/** Check installed hooks; with `repair`, reinstall them if anything is wrong */
+export async function doctorRepoHooks(
+	repoId: number,
+	repair?: boolean,
+): Promise<RepoHooksDoctorReport> {
+	return invoke("doctor_repo_hooks", { repoId, repair });
+}
+
+export type NotesRefSyncStatus =
+	| "created"
+	| "updated"
+	| "up_to_date"
+	| "local_ahead"
+	| "diverged"
+	| "rejected"
+	| "failed";
+
+export type NotesRefSyncResult = {
+	refName: string;
+	status: NotesRefSyncStatus;
+	message?: string;
+};
+
+export type NotesSyncSummary = {
+	remoteName: string;
+	refs: NotesRefSyncResult[];
+	/** Notes imported after a fetch (`importNotesAfterFetch` pref) */
+	imported?: FetchedNotesImportSummary;
+};
+
+/**
+ * Push `refs/notes/narrative/*` to a remote (default: origin, else the
+ * first remote).
+ */
+export async function pushNarrativeNotes(
+	repoId: number,
+	remote?: string,
+): Promise<NotesSyncSummary> {
+	return invoke("push_narrative_notes", { repoId, remote });
+}
+
+/**
+ * Fetch `refs/notes/narrative/*` from a remote. Local refs are only
+ * fast-forwarded; diverged refs are reported and left unchanged.
+ */
+export async function fetchNarrativeNotes(
+	repoId: number,
+	remote?: string,
+): Promise<NotesSyncSummary> {
+	return invoke("fetch_narrative_notes", { repoId, remote });
+}
+
+export type FetchedNotesImportSummary = {
+	/** Commits whose notes were added or changed since the last import */
+	newNotes: number;
+	sessions: SessionsNoteBatchSummary;
+	attribution: AttributionNoteBatchSummary;
+	/** Commits with no narrative data before the import that have some now */
+	gainedCommits: string[];
+};
+
+/**
+ * Import the notes that arrived since the last import (from a notes fetch
+ * or any `git fetch`/`git pull`) into session links and line attributions.
+ */
+export async function importFetchedNotes(
+	repoId: number,
+): Promise<FetchedNotesImportSummary> {
+	return invoke("import_fetched_notes", { repoId });
+}
+
+export type NotesMergeSummary = {
+	remoteName: string;
+	refName: string;
+	status: "up_to_date" | "fast_forward" | "merged" | "no_remote_ref";
+	/** Commits whose local note changed */
+	changedCommits: string[];
+	/** Notes changed on both sides and combined */
+	combinedNotes: number;
+	/** Changed notes re-imported into session links */
+	importedNotes: number;
+};
+
+/**
+ * Merge fetched sessions notes into the local ref (union of sessions,
+ * newest hint wins per field). Run after a fetch reports `diverged`.
+ */
+export async function mergeNarrativeNotes(
+	repoId: number,
+	remote?: string,
+): Promise<NotesMergeSummary> {
+	return invoke("merge_narrative_notes", { repoId, remote });
+}
+
+export type NotesSchemaRefSummary = {
+	noteRef: string;
+	schemaVersion: string;
+	total: number;
+	current: number;
+	upgraded: number;
+	/** Written by a newer major schema version; left as is */
+	unsupported: number;
+	failed: number;
+	upgradedCommits: string[];
+};
+
+/**
+ * Rewrite older and unversioned sessions/attribution notes at the current
+ * schema version and re-import them.
+ */
+export async function migrateNotesSchema(
+	repoId: number,
+): Promise<NotesSchemaRefSummary[]> {
+	return invoke("migrate_notes_schema", { repoId });
+}
+
+export type NotesGcMigration = {
+	fromCommit: string;
+	toCommit: string;
+};
+
+export type NotesGcRefSummary = {
+	noteRef: string;
+	totalNotes: number;
+	/** Notes on unreachable commits (migrated + pruned) */
+	unreachable: number;
+	migrated: NotesGcMigration[];
+	pruned: string[];
+};
+
+export type NotesGcSummary = {
+	dryRun: boolean;
+	refs: NotesGcRefSummary[];
+};
+
+/**
+ * Move notes on unreachable (rewritten-away) commits to their rewrites and
+ * prune the rest. Defaults to a dry run that only reports.
+ */
+export async function gcNarrativeNotes(
+	repoId: number,
+	options: { dryRun?: boolean; migrate?: boolean } = {},
+): Promise<NotesGcSummary> {
+	return invoke("gc_narrative_notes", {
+		repoId,
+		dryRun: options.dryRun,
+		migrate: options.migrate,
+	});
+}
+
+export type NotesRefMove = {
+	fromRef: string;
+	toRef: string;
+	status: "moved" | "merged" | "empty";
+	/** Notes now under `toRef` that came from `fromRef` */
+	copied: number;
+	/** Commits annotated in both refs; `toRef` kept its note */
+	kept: number;
+};
+
+export type NotesNamespaceMigration = {
+	fromNamespace: string;
+	toNamespace: string;
+	refs: NotesRefMove[];
+};
+
+/**
+ * Move the repo's notes to another ref namespace (e.g.
+ * `refs/notes/acme/narrative`); null restores `refs/notes/narrative`.
+ */
+export async function setNotesNamespace(
+	repoId: number,
+	namespace: string | null,
+): Promise<NotesNamespaceMigration> {
+	return invoke("set_notes_namespace", { repoId, namespace });
+}
+
+export type ReleaseAnchorTotals = {
+	/** Commits in the range */
+	commits: number;
+	/** Commits with attribution data */
+	attributedCommits: number;
+	totalLines: number;
+	humanLines: number;
+	aiAgentLines: number;
+	aiAssistLines: number;
+	collaborativeLines: number;
+	aiPercentage: number;
+};
+
+export type ReleaseAnchorTool = {
+	tool: string;
+	model?: string | null;
+	commits: number;
+	lineCount: number;
+};
+
+export type ReleaseAnchor = {
+	tag: string;
+	/** Commit the tag points at */
+	commitSha: string;
+	/** Exclusive start of the range (null: from the root commit) */
+	fromRef: string | null;
+	commitCount: number;
+	sessionIds: string[];
+	totals: ReleaseAnchorTotals;
+	/** Most lines first */
+	tools: ReleaseAnchorTool[];
+	generatedAt: string;
+	/** Ref holding the release note (null: no note written) */
+	noteRef: string | null;
+};
+
+/**
+ * Aggregate a release's sessions and attribution into a release anchor.
+ * The range runs from the previous tag unless `fromRef` is given; the anchor
+ * is written as a note on the tagged commit unless `writeNote` is false.
+ */
+export async function createReleaseAnchor(
+	repoId: number,
+	tag: string,
+	options: { fromRef?: string; writeNote?: boolean } = {},
+): Promise<ReleaseAnchor> {
+	return invoke("create_release_anchor", {
+		repoId,
+		tag,
+		fromRef: options.fromRef,
+		writeNote: options.writeNote,
+	});
+}
+
+/** Stored release anchors, most recently created first */
+export async function getReleaseAnchors(
+	repoId: number,
+): Promise<ReleaseAnchor[]> {
+	return invoke("get_release_anchors", { repoId });
+}
+
+export type AnchorCoverageCounts = {
+	commits: number;
+	/** Commits with both a sessions and an attribution note */
+	both: number;
+	sessionsOnly: number;
+	attributionOnly: number;
+	neither: number;
+	sessionsPercentage: number;
+	attributionPercentage: number;
+	/** Commits with at least one note */
+	anchoredPercentage: number;
+};
+
+export type AnchorCoveragePeriod = AnchorCoverageCounts & {
+	/** First day of the period (`YYYY-MM-DD`) */
+	periodStart: string;
+};
+
+export type AnchorCoverageReport = {
+	repoId: number;
+	granularity: TimeseriesGranularity;
+	totals: AnchorCoverageCounts;
+	/** Oldest first */
+	periods: AnchorCoveragePeriod[];
+};
+
+/**
+ * How completely recent history is anchored: the newest `limit` commits
+ * (default 1000) by the notes they carry, overall and per week or month.
+ */
+export async function getAnchorCoverage(
+	repoId: number,
+	options: { granularity?: TimeseriesGranularity; limit?: number } = {},
+): Promise<AnchorCoverageReport> {
+	return invoke("get_anchor_coverage", {
+		repoId,
+		granularity: options.granularity,
+		limit: options.limit,
+	});
+}
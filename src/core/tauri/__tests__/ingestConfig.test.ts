@@ -232,6 +232,31 @@ describe("backfillRecentSessions", () => {
 		];
 		expect(args.limitPerTool).toBe(10);
 	});
+
+	it("forwards dryRun when previewing", async () => {
+		mockInvoke.mockResolvedValue({
+			attempted: 2,
+			imported: 1,
+			skipped: 1,
+			failed: 0,
+			preview: {
+				total: 2,
+				wouldImport: 1,
+				duplicates: 1,
+				blocked: 0,
+				failed: 0,
+				files: [],
+			},
+		});
+
+		const result = await backfillRecentSessions(1, 5, true);
+		expect(mockInvoke).toHaveBeenCalledWith("backfill_recent_sessions", {
+			repoId: 1,
+			limitPerTool: 5,
+			dryRun: true,
+		});
+		expect(result.preview?.wouldImport).toBe(1);
+	});
 });
 
 describe("codexAppServerSubmitApproval", () => {
@@ -40,6 +40,33 @@ export type CommitCaptureBundle = {
 	toolsUsedTop: string[];
 };
 
+export type ImportHistoryFilter = {
+	status?: "imported" | "partial" | "skipped" | "failed";
+	tool?: string;
+	fromIso?: string;
+	toIso?: string;
+	pathContains?: string;
+};
+
+export type ImportHistoryEntry = {
+	log: "audit" | "import";
+	id: number;
+	createdAtIso: string;
+	tool?: string | null;
+	path?: string | null;
+	sessionId?: string | null;
+	action: string;
+	status: string;
+	redactionCount?: number | null;
+	warnings: string[];
+	errorMessage?: string | null;
+};
+
+export type ImportHistoryPage = {
+	entries: ImportHistoryEntry[];
+	nextCursor?: string | null;
+};
+
 export async function getIngestActivity(repoId: number, limit: number) {
 	return invoke<ActivityEvent[]>("get_ingest_activity", { repoId, limit });
 }
@@ -55,3 +82,17 @@ export async function getCommitCaptureBundle(
 		commitSha,
 	});
 }
+
+export async function queryImportHistory(
+	repoId: number,
+	filter?: ImportHistoryFilter,
+	cursor?: string | null,
+	limit?: number,
+) {
+	return invoke<ImportHistoryPage>("query_import_history", {
+		repoId,
+		filter: filter ?? null,
+		cursor: cursor ?? null,
+		limit: limit ?? null,
+	});
+}
@@ -1,8 +1,17 @@
 import { invoke } from "@tauri-apps/api/core";
+import type { ImportPreviewReport } from "../attribution-api";
 
 export type IngestConfig = {
 	autoIngestEnabled: boolean;
-	watchPaths: { claude: string[]; cursor: string[]; codexLogs: string[] };
+	watchPaths: {
+		claude: string[];
+		cursor: string[];
+		codexLogs: string[];
+		jetbrains?: string[];
+		cody?: string[];
+		opencode?: string[];
+		copilot?: string[];
+	};
 	codex: {
 		receiverEnabled: boolean;
 		mode: "otlp" | "logs" | "both";
@@ -25,6 +34,26 @@ export type IngestConfig = {
 	retentionDays: number;
 	redactionMode: "redact";
 	consent: { codexTelemetryGranted: boolean; grantedAtIso?: string };
+	/** Minutes between scheduled background backfills; 0 disables them */
+	backfillIntervalMinutes?: number;
+	/** Megabytes of a session file read before the rest is dropped; 0 uses the default */
+	maxParseMb?: number;
+	/** Messages kept per imported session; 0 keeps every message */
+	maxMessagesPerSession?: number;
+	/** Which messages to keep when a session is over the limit */
+	trimStrategy?: TrimStrategy;
+	/** Your git author names/emails; session links to other authors' commits are penalized */
+	myIdentities?: string[];
+};
+
+/** head keeps the first messages, tail the latest, summary both ends plus a note */
+export type TrimStrategy = "head" | "tail" | "summary";
+
+/** Payload of the "scheduled-backfill" event */
+export type ScheduledBackfillEvent = {
+	repoId: number;
+	imported: number;
+	failed: number;
 };
 
 export type IngestConfigUpdate = Partial<IngestConfig>;
@@ -43,6 +72,8 @@ export type DiscoveredSources = {
 	claude: string[];
 	cursor: string[];
 	codexLogs: string[];
+	cody?: string[];
+	copilot?: string[];
 	collector: CollectorMigrationStatus;
 };
 
@@ -405,18 +436,48 @@ export type BackfillResult = {
 	imported: number;
 	skipped: number;
 	failed: number;
+	/** Present for dry runs; counts then describe what would be imported */
+	preview?: ImportPreviewReport | null;
 };
 
 export async function backfillRecentSessions(
 	repoId: number,
 	limitPerTool = 10,
+	dryRun?: boolean,
 ): Promise<BackfillResult> {
 	return await invoke<BackfillResult>("backfill_recent_sessions", {
 		repoId,
 		limitPerTool,
+		dryRun,
+	});
+}
+
+export async function importChatGptExport(
+	repoId: number,
+	filePath: string,
+): Promise<BackfillResult> {
+	return await invoke<BackfillResult>("import_chatgpt_export", {
+		repoId,
+		filePath,
 	});
 }
 
+export type ParserCapabilities = {
+	tool: string;
+	name: string;
+	incremental: boolean;
+	formats: Array<{
+		version: string;
+		compatibility: "full" | "partial";
+		notes: string;
+	}>;
+	knownEntryTypes: string[];
+};
+
+export async function getParserCapabilities(): Promise<ParserCapabilities[]> {
+	return await invoke<ParserCapabilities[]>("get_parser_capabilities");
+}
+
 export async function startFileWatcher(paths: string[]): Promise<void> {
 	await invoke("start_file_watcher", { watchPaths: paths });
 }
@@ -435,6 +496,40 @@ export async function autoImportSessionFile(
 	});
 }
 
+/** Import a transcript pasted from the clipboard; `toolHint` defaults to "pasted". */
+export async function importSessionFromText(
+	repoId: number,
+	text: string,
+	toolHint?: string,
+): Promise<AutoImportResult> {
+	return await invoke<AutoImportResult>("import_session_from_text", {
+		repoId,
+		text,
+		toolHint: toolHint ?? null,
+	});
+}
+
+/** Import Cursor composer chats created or edited since the last import of `dbPath`. */
+export async function importCursorComposerDb(
+	repoId: number,
+	dbPath: string,
+): Promise<AutoImportResult[]> {
+	return await invoke<AutoImportResult[]>("import_cursor_composer_db", {
+		repoId,
+		dbPath,
+	});
+}
+
+export async function importSessionFileIncremental(
+	repoId: number,
+	filePath: string,
+): Promise<AutoImportResult> {
+	return await invoke<AutoImportResult>("import_session_file_incremental", {
+		repoId,
+		filePath,
+	});
+}
+
 export async function purgeExpiredSessions(
 	repoId: number,
 	retentionDays: number,
@@ -584,6 +584,35 @@ export interface DashboardStats {
 	currentPeriod: PeriodStats;
 	previousPeriod?: PeriodStats;
 	topFiles: PaginatedFiles;
+	/** AI and human lines per language, most lines first */
+	languages?: LanguageStats[];
+	attributionCoverage?: RepoAttributionCoverage;
+	filter?: DashboardStatsFilter;
+}
+
+export interface LanguageStats {
+	language: string;
+	files: number;
+	totalLines: number;
+	aiLines: number;
+	humanLines: number;
+	aiPercentage: number;
+}
+
+/** Narrows dashboard stats to matching commits; every field is optional */
+export interface DashboardStatsFilter {
+	since?: string; // YYYY-MM-DD, overrides the time range start
+	until?: string; // YYYY-MM-DD, overrides the time range end
+	branch?: string;
+	authorEmail?: string;
+	tool?: string;
+}
+
+/** Share of a repo's indexed commits with computed attribution stats */
+export interface RepoAttributionCoverage {
+	totalCommits: number;
+	attributedCommits: number;
+	coveragePercent: number;
 }
 
 export type DashboardState =
@@ -679,6 +708,14 @@ export interface PeriodStats {
 		aiAssistLines: number;
 		collaborativeLines: number;
 		aiPercentage: number;
+		/** Lines removed by the period's commits */
+		deletedLines?: number;
+		/** Removed lines that had been written by AI */
+		deletedAiLines?: number;
+		/** Lines removed by commits linked to an AI session */
+		deletedByAiLines?: number;
+		/** Lines added minus lines removed */
+		netLines?: number;
 	};
 	toolBreakdown: ToolStats[];
 	trend: TrendPoint[];
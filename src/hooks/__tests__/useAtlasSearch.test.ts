@@ -39,6 +39,7 @@ function createSearchHit(sessionId: string): AtlasSearchHit {
 		chunkIndex: 0,
 		score: 1,
 		snippet: "snippet",
+		highlights: [],
 		sessionImportedAt: null,
 		sessionTool: "codex",
 		sessionModel: "gpt",
@@ -50,7 +51,11 @@ function okSearchEnvelope(
 ): AtlasEnvelope<AtlasSearchResponse> {
 	return {
 		ok: true,
-		value: { results },
+		value: {
+			mode: "lexical",
+			results,
+			facets: { tools: [], months: [], sessions: [] },
+		},
 		meta: { truncated: false },
 	};
 }
@@ -274,7 +274,14 @@ export function useAutoIngest(params: {
 
 	const watchPaths = useMemo(() => {
 		if (!config) return [];
-		const base = [...config.watchPaths.claude, ...config.watchPaths.cursor];
+		const base = [
+			...config.watchPaths.claude,
+			...config.watchPaths.cursor,
+			...(config.watchPaths.jetbrains ?? []),
+			...(config.watchPaths.cody ?? []),
+			...(config.watchPaths.opencode ?? []),
+			...(config.watchPaths.copilot ?? []),
+		];
 		if (config.codex.mode === "logs" || config.codex.mode === "both") {
 			base.push(...(config.watchPaths.codexLogs ?? []));
 		}
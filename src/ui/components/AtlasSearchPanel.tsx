@@ -1,4 +1,11 @@
-import { useCallback, useEffect, useMemo, useRef, useState } from "react";
+import {
+	type ReactNode,
+	useCallback,
+	useEffect,
+	useMemo,
+	useRef,
+	useState,
+} from "react";
 import type {
 	AtlasCapabilities,
 	AtlasDoctorRebuildSummary,
@@ -31,6 +38,27 @@ function formatHitMeta(hit: AtlasSearchHit): string {
 	return `${tool}${model}${time}`;
 }
 
+function renderSnippet(hit: AtlasSearchHit): ReactNode {
+	if (!hit.snippet) return "(no snippet)";
+	const parts: ReactNode[] = [];
+	let cursor = 0;
+	for (const { start, end } of hit.highlights ?? []) {
+		if (start < cursor || end <= start) continue;
+		if (start > cursor) parts.push(hit.snippet.slice(cursor, start));
+		parts.push(
+			<mark
+				key={start}
+				className="rounded-sm bg-accent-amber-bg text-text-primary"
+			>
+				{hit.snippet.slice(start, end)}
+			</mark>,
+		);
+		cursor = end;
+	}
+	if (cursor < hit.snippet.length) parts.push(hit.snippet.slice(cursor));
+	return parts;
+}
+
 function summarizeObject(obj: unknown): string {
 	try {
 		return JSON.stringify(obj, null, 2);
@@ -228,7 +256,9 @@ export function AtlasSearchPanel(props: { repoId: number | null }) {
 		const derived = info.capabilities.derivedVersion;
 
 		const ftsStr = fts5 ? "FTS5 enabled" : "FTS5 disabled";
-		const readyStr = ftsReady ? "FTS table ready" : "FTS table missing";
+		const readyStr = ftsReady
+			? "FTS table ready"
+			: "FTS table missing (substring search)";
 		return `${ftsStr} · ${readyStr} · derived: ${derived}`;
 	}, [info.capabilities]);
 
@@ -270,7 +300,8 @@ export function AtlasSearchPanel(props: { repoId: number | null }) {
 						spellCheck={false}
 					/>
 					<div id="atlas-search-help" className="text-xs text-text-tertiary">
-						Tip: use a few keywords; results update as you type.
+						Tip: use a few keywords; results update as you type. Quote a phrase,
+						join alternatives with OR, and exclude words with -word.
 					</div>
 
 					<div className="mt-2 flex flex-wrap items-center gap-2">
@@ -401,7 +432,7 @@ export function AtlasSearchPanel(props: { repoId: number | null }) {
 											Chunk {hit.chunkIndex} · score {hit.score.toFixed(2)}
 										</div>
 										<div className="text-xs text-text-tertiary whitespace-pre-wrap break-words">
-											{hit.snippet || "(no snippet)"}
+											{renderSnippet(hit)}
 										</div>
 									</div>
 								</button>
@@ -81,6 +81,69 @@ export function AttributionSettingsPanel({
 					/>
 				</div>
 
+				<div className="flex items-center justify-between py-1">
+					<span className="text-xs text-text-secondary">
+						Add session trailers to commit messages
+					</span>
+					<Toggle
+						checked={attributionPrefs?.commitTrailers ?? false}
+						onCheckedChange={(c) =>
+							onUpdateAttributionPrefs?.({ commitTrailers: c })
+						}
+						aria-label="Add session trailers to commit messages"
+					/>
+				</div>
+
+				<div className="flex items-center justify-between py-1">
+					<span className="text-xs text-text-secondary">Sign exported notes</span>
+					<Toggle
+						checked={attributionPrefs?.signNotes ?? false}
+						onCheckedChange={(c) =>
+							onUpdateAttributionPrefs?.({ signNotes: c })
+						}
+						aria-label="Sign exported notes"
+					/>
+				</div>
+
+				<div className="flex items-center justify-between py-1">
+					<span className="text-xs text-text-secondary">
+						Only import signed notes
+					</span>
+					<Toggle
+						checked={attributionPrefs?.requireSignedNotes ?? false}
+						onCheckedChange={(c) =>
+							onUpdateAttributionPrefs?.({ requireSignedNotes: c })
+						}
+						aria-label="Only import signed notes"
+					/>
+				</div>
+
+				<div className="flex items-center justify-between py-1">
+					<span className="text-xs text-text-secondary">
+						Export notes when sessions are linked
+					</span>
+					<Toggle
+						checked={attributionPrefs?.autoExportNotes ?? false}
+						onCheckedChange={(c) =>
+							onUpdateAttributionPrefs?.({ autoExportNotes: c })
+						}
+						aria-label="Export notes when sessions are linked"
+					/>
+				</div>
+
+				<div className="flex items-center justify-between py-1">
+					<span className="text-xs text-text-secondary">
+						Import notes after fetch or pull
+					</span>
+					<Toggle
+						checked={attributionPrefs?.importNotesAfterFetch ?? false}
+						onCheckedChange={(c) =>
+							onUpdateAttributionPrefs?.({ importNotesAfterFetch: c })
+						}
+						aria-label="Import notes after fetch or pull"
+					/>
+				</div>
+
 				<div className="border-t border-border-subtle pt-3 mt-1">
 					<div className="flex items-center justify-between mb-2">
 						<label
@@ -9,7 +9,15 @@ export interface SourceLine {
 	aiPercentage?: number;
 	tool?: string;
 	model?: string;
+	/** How the attribution was derived */
+	source?: "note" | "tool_diff" | "completion" | "heuristic";
+	/** 0-1 certainty of the attribution */
+	confidence?: number;
+	/** Attribution is too weak to be shown as fact */
+	uncertain?: boolean;
 	traceAvailable?: boolean;
+	/** Commit that last changed this line (from blame) */
+	commitSha?: string;
 }
 
 function getBadgeLabel(line: SourceLine): string {
@@ -28,8 +36,23 @@ function getBadgeLabel(line: SourceLine): string {
 	return "Unknown";
 }
 
+const SOURCE_LABELS: Record<NonNullable<SourceLine["source"]>, string> = {
+	note: "attribution note",
+	tool_diff: "recorded tool edits",
+	completion: "accepted completion",
+	heuristic: "session overlap",
+};
+
 function getBadgeTitle(line: SourceLine): string {
 	const parts: string[] = [];
+	if (line.uncertain) parts.push("Uncertain attribution");
+	if (line.source) {
+		const confidence =
+			line.confidence !== undefined
+				? ` (${Math.round(line.confidence * 100)}% confidence)`
+				: "";
+		parts.push(`From ${SOURCE_LABELS[line.source]}${confidence}`);
+	}
 	if (line.tool) parts.push(`Tool: ${formatToolName(line.tool)}`);
 	if (line.model) parts.push(`Model: ${line.model}`);
 	if (line.sessionId) parts.push(`Session: ${line.sessionId}`);
@@ -48,14 +71,19 @@ export interface AuthorBadgeProps {
 }
 
 export function AuthorBadge({ line }: AuthorBadgeProps) {
-	const label = getBadgeLabel(line);
+	const label = line.uncertain
+		? `${getBadgeLabel(line)}?`
+		: getBadgeLabel(line);
 	const title = getBadgeTitle(line);
+	const uncertainClass = line.uncertain
+		? " border border-dashed border-current opacity-70"
+		: "";
 
 	switch (line.authorType) {
 		case "ai_agent":
 			return (
 				<span
-					className="inline-flex items-center gap-1 rounded-full bg-accent-green-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-green"
+					className={`inline-flex items-center gap-1 rounded-full bg-accent-green-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-green${uncertainClass}`}
 					title={formatBadgeTitle("AI-generated", title)}
 				>
 					<Bot className="w-3 h-3" />
@@ -65,7 +93,7 @@ export function AuthorBadge({ line }: AuthorBadgeProps) {
 		case "ai_tab":
 			return (
 				<span
-					className="inline-flex items-center gap-1 rounded-full bg-accent-blue-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-blue"
+					className={`inline-flex items-center gap-1 rounded-full bg-accent-blue-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-blue${uncertainClass}`}
 					title={formatBadgeTitle("Assist suggestions", title)}
 				>
 					<Bot className="w-3 h-3" />
@@ -75,7 +103,7 @@ export function AuthorBadge({ line }: AuthorBadgeProps) {
 		case "mixed":
 			return (
 				<span
-					className="inline-flex items-center gap-1 rounded-full bg-accent-amber-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-amber"
+					className={`inline-flex items-center gap-1 rounded-full bg-accent-amber-light px-2 py-0.5 text-[0.625rem] font-medium text-accent-amber${uncertainClass}`}
 					title={formatBadgeTitle("Modified lines (AI + human edits)", title)}
 				>
 					<Users className="w-3 h-3" />
@@ -105,14 +133,20 @@ export function AuthorBadge({ line }: AuthorBadgeProps) {
 	}
 }
 
-export function getLineColor(authorType: string): string {
+export function getLineColor(authorType: string, uncertain = false): string {
 	switch (authorType) {
 		case "ai_agent":
-			return "bg-accent-green-bg/30 hover:bg-accent-green-bg/50";
+			return uncertain
+				? "bg-accent-green-bg/10 hover:bg-accent-green-bg/30"
+				: "bg-accent-green-bg/30 hover:bg-accent-green-bg/50";
 		case "ai_tab":
-			return "bg-accent-blue-bg/30 hover:bg-accent-blue-bg/50";
+			return uncertain
+				? "bg-accent-blue-bg/10 hover:bg-accent-blue-bg/30"
+				: "bg-accent-blue-bg/30 hover:bg-accent-blue-bg/50";
 		case "mixed":
-			return "bg-accent-amber-bg/30 hover:bg-accent-amber-bg/50";
+			return uncertain
+				? "bg-accent-amber-bg/10 hover:bg-accent-amber-bg/30"
+				: "bg-accent-amber-bg/30 hover:bg-accent-amber-bg/50";
 		case "human":
 			return "";
 		default:
@@ -16,6 +16,7 @@ function getLineAriaLabel(line: SourceLine): string {
 	}
 
 	const details: string[] = [`Line ${line.lineNumber}`, authorLabel];
+	if (line.uncertain) details.push("Uncertain");
 	if (line.tool) details.push(`Tool ${formatToolName(line.tool)}`);
 	if (line.model) details.push(`Model ${line.model}`);
 	if (line.traceAvailable === false) details.push("Trace unavailable");
@@ -52,7 +53,7 @@ export function SourceLensLineTable({
 							key={line.lineNumber}
 							tabIndex={0}
 							aria-label={getLineAriaLabel(line)}
-							className={`border-b border-border-subtle last:border-0 transition-colors motion-reduce:transition-none focus-visible:outline focus-visible:outline-2 focus-visible:outline-accent-blue ${showLineOverlays ? getLineColor(line.authorType) : ""}`}
+							className={`border-b border-border-subtle last:border-0 transition-colors motion-reduce:transition-none focus-visible:outline focus-visible:outline-2 focus-visible:outline-accent-blue ${showLineOverlays ? getLineColor(line.authorType, line.uncertain) : ""}`}
 						>
 							<td className="w-10 text-right text-text-muted select-none align-top px-4 py-1.5">
 								{line.lineNumber}
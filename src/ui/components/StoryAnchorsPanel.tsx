@@ -1,6 +1,7 @@
 import { ChevronRight, GitBranch, Settings2 } from "lucide-react";
 import { useCallback, useEffect, useRef, useState } from "react";
 import {
+	doctorRepoHooks,
 	exportSessionLinkNote,
 	getRepoHooksStatus,
 	getStoryAnchorStatus,
@@ -212,6 +213,25 @@ export function StoryAnchorsPanel(props: {
 			setMessage("Uninstalled repo hooks.");
 		});
 
+	const repairHooksAction = () =>
+		void runAction(async ({ expectedRepoId, isStaleRequest }) => {
+			if (!expectedRepoId) return;
+			const report = await doctorRepoHooks(expectedRepoId, true);
+			if (isStaleRequest()) return;
+			if (report.healthy) {
+				setMessage(
+					report.repaired
+						? "Repaired repo hooks."
+						: "Repo hooks look healthy.",
+				);
+			} else {
+				const problems = report.problems
+					.map((problem) => `${problem.hook}: ${problem.detail}`)
+					.join("; ");
+				setMessage(`Repo hooks still have problems: ${problems}`);
+			}
+		});
+
 	const refreshIndexedStatusAction = () =>
 		void runAction(
 			async ({ expectedRepoId, expectedIndexedCommitShas, isStaleRequest }) => {
@@ -427,6 +447,7 @@ export function StoryAnchorsPanel(props: {
 					canRun={Boolean(repoId)}
 					onInstallHooks={installHooksAction}
 					onUninstallHooks={uninstallHooksAction}
+					onRepairHooks={repairHooksAction}
 					onRefresh={() => void refresh()}
 				/>
 
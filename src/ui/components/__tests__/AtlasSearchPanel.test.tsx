@@ -55,6 +55,7 @@ function createCapabilities() {
 		derivedVersion: "v1",
 		fts5Enabled: true,
 		ftsTableReady: true,
+		embeddingsAvailable: false,
 		budgets: {
 			queryMaxChars: 2000,
 			queryMaxTerms: 20,
@@ -77,6 +78,7 @@ function createIntrospect(repoId: number) {
 			lastError: null,
 			sessionsIndexed: 1,
 			chunksIndexed: 1,
+			chunkBudget: null,
 		},
 		chunksInTable: 1,
 		sessionsWithChunks: 1,
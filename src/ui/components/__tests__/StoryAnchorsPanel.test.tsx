@@ -8,6 +8,7 @@ import {
 import { beforeEach, describe, expect, it, vi } from "vitest";
 import { StoryAnchorsPanel } from "../StoryAnchorsPanel";
 
+const mockDoctorRepoHooks = vi.hoisted(() => vi.fn());
 const mockExportSessionLinkNote = vi.hoisted(() => vi.fn());
 const mockGetStoryAnchorStatus = vi.hoisted(() => vi.fn());
 const mockGetRepoHooksStatus = vi.hoisted(() => vi.fn());
@@ -18,6 +19,7 @@ const mockReconcileAfterRewrite = vi.hoisted(() => vi.fn());
 const mockUninstallRepoHooks = vi.hoisted(() => vi.fn());
 
 vi.mock("../../../core/story-anchors-api", () => ({
+	doctorRepoHooks: mockDoctorRepoHooks,
 	exportSessionLinkNote: mockExportSessionLinkNote,
 	getStoryAnchorStatus: mockGetStoryAnchorStatus,
 	getRepoHooksStatus: mockGetRepoHooksStatus,
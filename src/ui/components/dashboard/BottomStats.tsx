@@ -2,6 +2,8 @@ interface BottomStatsProps {
 	repoCount?: number;
 	sessionCount?: number;
 	aiPercentage?: number;
+	/** Share of the repo's commits with computed attribution */
+	coveragePercentage?: number;
 	costThisWeek?: string;
 	openPRs?: number;
 }
@@ -10,6 +12,7 @@ export function BottomStats({
 	repoCount = 0,
 	sessionCount = 0,
 	aiPercentage = 0,
+	coveragePercentage,
 	costThisWeek,
 	openPRs,
 }: BottomStatsProps) {
@@ -17,6 +20,9 @@ export function BottomStats({
 		{ value: repoCount, label: "repos active" },
 		{ value: sessionCount, label: "sessions" },
 		{ value: `${aiPercentage}%`, label: "AI" },
+		...(coveragePercentage !== undefined
+			? [{ value: `${coveragePercentage}%`, label: "attributed" }]
+			: []),
 		...(costThisWeek ? [{ value: costThisWeek, label: "cost" }] : []),
 		...(openPRs !== undefined ? [{ value: openPRs, label: "open PRs" }] : []),
 	];
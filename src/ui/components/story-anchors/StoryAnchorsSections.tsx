@@ -38,6 +38,7 @@ export function HooksStatusRow(props: {
 	canRun: boolean;
 	onInstallHooks: () => void;
 	onUninstallHooks: () => void;
+	onRepairHooks: () => void;
 	onRefresh: () => void;
 }) {
 	const {
@@ -47,6 +48,7 @@ export function HooksStatusRow(props: {
 		canRun,
 		onInstallHooks,
 		onUninstallHooks,
+		onRepairHooks,
 		onRefresh,
 	} = props;
 
@@ -75,6 +77,13 @@ export function HooksStatusRow(props: {
 				disabled={busy || !canRun}
 				onClick={onUninstallHooks}
 			/>
+			{hookInstalled ? (
+				<ActionButton
+					label="Repair hooks"
+					disabled={busy || !canRun}
+					onClick={onRepairHooks}
+				/>
+			) : null}
 			<ActionButton label="Refresh" disabled={busy} onClick={onRefresh} />
 		</div>
 	);
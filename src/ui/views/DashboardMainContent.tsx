@@ -538,6 +538,7 @@ export function DashboardMainContent({
 				repoCount={1}
 				sessionCount={stats.currentPeriod.period.commits}
 				aiPercentage={aiPct}
+				coveragePercentage={stats.attributionCoverage?.coveragePercent}
 			/>
 		</div>
 	);